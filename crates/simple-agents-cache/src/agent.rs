@@ -0,0 +1,344 @@
+//! Prefix-aware caching for tool-calling agent loops.
+//!
+//! Each iteration of an agent loop resends the whole conversation so far
+//! plus one new tool result. [`AgentCache`] keys replies on a hash of the
+//! message prefix they answer, computed incrementally, so a repeated run of
+//! the same loop is served from the cache one iteration at a time and a
+//! conversation that diverges late can still find the reply to its longest
+//! shared prefix.
+
+use simple_agents_types::prelude::*;
+use std::time::Duration;
+
+/// Prefix for cache keys written by [`AgentCache`].
+const KEY_PREFIX: &str = "agent";
+
+/// A cached reply to the first `messages` messages of a conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixHit {
+    /// Length of the matched message prefix
+    pub messages: usize,
+    /// Reply stored for that prefix
+    pub response: CompletionResponse,
+}
+
+/// Cache of assistant replies keyed on conversation prefixes.
+///
+/// Keys have three parts:
+/// - the namespace, which callers bump to invalidate everything, e.g. when
+///   a tool's implementation changes but its schema does not;
+/// - a version hash of the system messages, tool definitions, model and
+///   sampling parameters, so changing the system prompt or the tool
+///   registry never serves replies recorded under the old one;
+/// - an incremental hash of the remaining messages up to the prefix.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_cache::agent::AgentCache;
+/// use simple_agents_cache::InMemoryCache;
+/// use simple_agents_types::prelude::*;
+/// use std::time::Duration;
+///
+/// # async fn example(provider: &dyn Provider, mut request: CompletionRequest) -> Result<()> {
+/// let cache = InMemoryCache::new(10 * 1024 * 1024, 1000);
+/// let agent_cache = AgentCache::new(Box::new(cache), "support-agent-v1", Duration::from_secs(3600));
+///
+/// loop {
+///     let response = agent_cache.complete(provider, &request).await?;
+///     let reply = response.choices[0].message.clone();
+///     if reply.tool_calls.is_empty() {
+///         break;
+///     }
+///     request.messages.push(reply);
+///     // ...run the tools and push their results...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AgentCache {
+    cache: Box<dyn Cache>,
+    namespace: String,
+    ttl: Duration,
+}
+
+impl AgentCache {
+    /// Cache replies in `cache` under `namespace` for `ttl`.
+    pub fn new(cache: Box<dyn Cache>, namespace: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            cache,
+            namespace: namespace.into(),
+            ttl,
+        }
+    }
+
+    /// Get the namespace.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Get the cache TTL.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Cache keys for every prefix of the request's conversation.
+    ///
+    /// Element `i` is the key for the first `i + 1` messages. System
+    /// messages are part of the version component rather than the prefix,
+    /// so they always share the key of the message before them.
+    pub fn prefix_keys(&self, request: &CompletionRequest) -> Vec<String> {
+        let version = Self::version(request);
+        let mut hasher = blake3::Hasher::new();
+        request
+            .messages
+            .iter()
+            .map(|message| {
+                if message.role != Role::System {
+                    // Length-prefix each message so boundaries cannot shift
+                    let json = serde_json::to_vec(message).unwrap_or_default();
+                    hasher.update(&(json.len() as u64).to_le_bytes());
+                    hasher.update(&json);
+                }
+                format!("{}:{}:{}:{}", KEY_PREFIX, self.namespace, version, hasher.finalize().to_hex())
+            })
+            .collect()
+    }
+
+    /// Look up the reply stored for the request's full conversation.
+    pub async fn lookup(&self, request: &CompletionRequest) -> Result<Option<CompletionResponse>> {
+        match self.prefix_keys(request).last() {
+            Some(key) => self.get(key).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Find the reply stored for the longest prefix of the conversation.
+    ///
+    /// Checks prefixes from longest to shortest, so this makes up to one
+    /// cache lookup per message.
+    pub async fn lookup_prefix(&self, request: &CompletionRequest) -> Result<Option<PrefixHit>> {
+        let keys = self.prefix_keys(request);
+        for (index, key) in keys.iter().enumerate().rev() {
+            if let Some(response) = self.get(key).await? {
+                return Ok(Some(PrefixHit {
+                    messages: index + 1,
+                    response,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Store the reply to the request's full conversation.
+    pub async fn store(&self, request: &CompletionRequest, response: &CompletionResponse) -> Result<()> {
+        let Some(key) = self.prefix_keys(request).pop() else {
+            return Ok(());
+        };
+        self.cache.set(&key, serde_json::to_vec(response)?, self.ttl).await
+    }
+
+    /// Serve `request` from the cache, or complete it with `provider` and
+    /// store the reply.
+    ///
+    /// Streaming requests are rejected, as in [`Provider::complete`].
+    pub async fn complete(
+        &self,
+        provider: &dyn Provider,
+        request: &CompletionRequest,
+    ) -> Result<CompletionResponse> {
+        request.ensure_not_streaming()?;
+
+        if let Some(response) = self.lookup(request).await? {
+            return Ok(response);
+        }
+
+        let response = provider.complete(request).await?;
+        self.store(request, &response).await?;
+        Ok(response)
+    }
+
+    /// Remove all cached entries, including those of other namespaces.
+    pub async fn clear(&self) -> Result<()> {
+        self.cache.clear().await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<CompletionResponse>> {
+        let Some(bytes) = self.cache.get(key).await? else {
+            return Ok(None);
+        };
+        // An entry that no longer parses is treated as a miss and overwritten
+        Ok(serde_json::from_slice(&bytes).ok())
+    }
+
+    /// Hash of everything about the request except its non-system messages.
+    fn version(request: &CompletionRequest) -> String {
+        let mut scoped = request.clone();
+        scoped.messages.retain(|message| message.role == Role::System);
+        scoped.fingerprint()[..16].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryCache;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Calls `lookup` twice, then answers.
+    struct ToolLoopProvider {
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl Provider for ToolLoopProvider {
+        fn name(&self) -> &str {
+            "tool-loop"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com").with_body(serde_json::to_value(req)?))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let request: CompletionRequest = serde_json::from_value(req.body)?;
+            let tool_results = request.messages.iter().filter(|m| m.role == Role::Tool).count();
+            Ok(ProviderResponse::new(200, serde_json::json!({ "tool_results": tool_results })))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            let iteration = resp.body["tool_results"].as_u64().unwrap_or_default();
+            let (message, finish_reason) = if iteration < 2 {
+                let id = format!("call_{}", iteration);
+                let call = ToolCall::function(id, "lookup", r#"{"page":1}"#);
+                (Message::assistant("").with_tool_call(call), FinishReason::ToolCalls)
+            } else {
+                (Message::assistant("Done"), FinishReason::Stop)
+            };
+            Ok(CompletionResponse {
+                id: format!("resp-{}", iteration),
+                model: "gpt-4".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message,
+                    finish_reason,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+    }
+
+    fn request(system: &str) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::system(system))
+            .message(Message::user("Find the answer"))
+            .tool(ToolDefinition::function("lookup", "Look up a page", serde_json::json!({})))
+            .build()
+            .unwrap()
+    }
+
+    /// Run the agent loop to completion, returning the final request.
+    async fn run_loop(
+        cache: &AgentCache,
+        provider: &dyn Provider,
+        mut request: CompletionRequest,
+    ) -> CompletionRequest {
+        loop {
+            let response = cache.complete(provider, &request).await.unwrap();
+            let reply = response.choices[0].message.clone();
+            let Some(call) = reply.tool_calls.first().cloned() else {
+                request.messages.push(reply);
+                return request;
+            };
+            request.messages.push(reply);
+            request.messages.push(Message::tool("page contents", call.id));
+        }
+    }
+
+    fn setup() -> (AgentCache, ToolLoopProvider, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let provider = ToolLoopProvider { calls: calls.clone() };
+        let cache = AgentCache::new(
+            Box::new(InMemoryCache::new(1024 * 1024, 100)),
+            "test-v1",
+            Duration::from_secs(60),
+        );
+        (cache, provider, calls)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_loop_hits_every_iteration() {
+        let (cache, provider, calls) = setup();
+
+        let first = run_loop(&cache, &provider, request("Be helpful.")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let second = run_loop(&cache, &provider, request("Be helpful.")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(first.messages, second.messages);
+        assert_eq!(second.messages.last().unwrap().content, "Done");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_prefix_finds_longest_shared_prefix() {
+        let (cache, provider, _calls) = setup();
+        let finished = run_loop(&cache, &provider, request("Be helpful.")).await;
+
+        // Diverge after the first tool result: the first reply is keyed on
+        // system + user, the second on everything up to the tool result
+        let mut diverged = finished.clone();
+        diverged.messages.truncate(4);
+        diverged.messages.push(Message::user("Actually, never mind"));
+
+        let hit = cache.lookup_prefix(&diverged).await.unwrap().unwrap();
+        assert_eq!(hit.messages, 4);
+        assert_eq!(hit.response.id, "resp-1");
+        assert!(cache.lookup(&diverged).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_tools_and_namespace_invalidate() {
+        let (cache, provider, calls) = setup();
+        run_loop(&cache, &provider, request("Be helpful.")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Changed system prompt
+        run_loop(&cache, &provider, request("Be terse.")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+
+        // Changed tool registry
+        let mut retooled = request("Be helpful.");
+        retooled.tools.get_or_insert_with(Vec::new).push(ToolDefinition::function(
+            "search",
+            "Search pages",
+            serde_json::json!({}),
+        ));
+        run_loop(&cache, &provider, retooled).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 9);
+
+        // Bumped namespace over the same backing store
+        let bumped = AgentCache { namespace: "test-v2".to_string(), ..cache };
+        run_loop(&bumped, &provider, request("Be helpful.")).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 12);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_streaming_requests() {
+        let (cache, provider, calls) = setup();
+        let mut streaming = request("Be helpful.");
+        streaming.stream = Some(true);
+
+        let result = cache.complete(&provider, &streaming).await;
+        assert!(matches!(result, Err(SimpleAgentsError::Validation(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,330 @@
+//! Exact-match response caching at the provider level.
+
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::time::Duration;
+
+/// Prefix for cache keys written by [`CachingProvider`].
+const KEY_PREFIX: &str = "provider-response:";
+
+/// Provider wrapper that caches raw responses by request fingerprint.
+///
+/// `execute` keys the cache on [`ProviderRequest::fingerprint_excluding`]
+/// the inner provider's [`sensitive_headers`](Provider::sensitive_headers),
+/// so rotating credentials keeps existing entries. A hit
+/// returns the stored [`ProviderResponse`] without calling the inner
+/// provider; a miss calls it and stores successful responses for the
+/// configured TTL. Because caching happens below `transform_response`, the
+/// default [`Provider::complete`] pipeline is cached as well. Streaming
+/// requests are passed through uncached.
+///
+/// [`Provider::execute_with_attempts`] is not forwarded, since the inner
+/// provider's own would skip the cache. Its default loop sends each
+/// attempt through the cache tagged with
+/// [`RETRY_ATTEMPT`](simple_agents_types::provider::RETRY_ATTEMPT), so a
+/// provider that retries as it sends still sends once per attempt.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_cache::{CachingProvider, InMemoryCache};
+/// use simple_agents_types::prelude::*;
+/// use std::time::Duration;
+///
+/// # async fn example(provider: Box<dyn Provider>, request: CompletionRequest) -> Result<()> {
+/// let cache = InMemoryCache::new(10 * 1024 * 1024, 1000);
+/// let cached = CachingProvider::new(provider, Box::new(cache), Duration::from_secs(3600))
+///     .with_bypass_on_n_choices(2);
+///
+/// let first = cached.complete(&request).await?;
+/// let second = cached.complete(&request).await?; // served from the cache
+/// # Ok(())
+/// # }
+/// ```
+pub struct CachingProvider {
+    inner: Box<dyn Provider>,
+    cache: Box<dyn Cache>,
+    ttl: Duration,
+    bypass_n: Option<u32>,
+}
+
+impl CachingProvider {
+    /// Wrap `inner`, caching its responses in `cache` for `ttl`.
+    pub fn new(inner: Box<dyn Provider>, cache: Box<dyn Cache>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            cache,
+            ttl,
+            bypass_n: None,
+        }
+    }
+
+    /// Skip the cache for requests asking for `n` or more choices.
+    ///
+    /// Callers asking for several choices usually want them to differ,
+    /// which a cached response defeats. `with_bypass_on_n_choices(2)`
+    /// caches only single-choice requests.
+    pub fn with_bypass_on_n_choices(mut self, n: u32) -> Self {
+        self.bypass_n = Some(n);
+        self
+    }
+
+    /// Get the cache TTL.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Access the cache.
+    pub fn cache(&self) -> &dyn Cache {
+        self.cache.as_ref()
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &dyn Provider {
+        self.inner.as_ref()
+    }
+
+    /// Whether `req` asks for enough choices to skip the cache.
+    fn bypasses(&self, req: &ProviderRequest) -> bool {
+        let Some(threshold) = self.bypass_n else {
+            return false;
+        };
+        let n = req.body.get("n").and_then(serde_json::Value::as_u64).unwrap_or(1);
+        n >= u64::from(threshold)
+    }
+}
+
+#[async_trait]
+impl Provider for CachingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        if self.bypasses(&req) {
+            return self.inner.execute(req).await;
+        }
+
+        let fingerprint = req.fingerprint_excluding(&self.inner.sensitive_headers());
+        let key = format!("{}{}", KEY_PREFIX, fingerprint);
+        if let Some(bytes) = self.cache.get(&key).await? {
+            // An entry that no longer parses is treated as a miss and overwritten
+            if let Ok(response) = serde_json::from_slice(&bytes) {
+                return Ok(response);
+            }
+        }
+
+        let response = self.inner.execute(req).await?;
+        if response.is_success() {
+            self.cache.set(&key, serde_json::to_vec(&response)?, self.ttl).await?;
+        }
+        Ok(response)
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures_core::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryCache;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    struct CountingProvider {
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_header("Authorization", "Bearer sk-test")
+                .with_body(serde_json::to_value(req)?))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ProviderResponse::new(200, serde_json::json!({ "call": call })))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: format!("resp-{}", resp.body["call"]),
+                model: "gpt-4".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("Hi"),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+    }
+
+    fn provider() -> (CachingProvider, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let inner = CountingProvider { calls: calls.clone() };
+        let cache = InMemoryCache::new(1024 * 1024, 100);
+        let provider = CachingProvider::new(Box::new(inner), Box::new(cache), Duration::from_secs(60));
+        (provider, calls)
+    }
+
+    fn request(content: &str, n: Option<u32>) -> CompletionRequest {
+        let mut builder = CompletionRequest::builder().model("gpt-4").message(Message::user(content));
+        if let Some(n) = n {
+            builder = builder.n(n);
+        }
+        builder.build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_second_request_hits_cache() {
+        let (provider, calls) = provider();
+
+        let first = provider.complete(&request("Hello", None)).await.unwrap();
+        let second = provider.complete(&request("Hello", None)).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+        assert_eq!(second.id, "resp-1");
+    }
+
+    #[tokio::test]
+    async fn test_different_request_misses_cache() {
+        let (provider, calls) = provider();
+
+        provider.complete(&request("Hello", None)).await.unwrap();
+        let other = provider.complete(&request("Goodbye", None)).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(other.id, "resp-2");
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_misses_cache() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let inner = CountingProvider { calls: calls.clone() };
+        let cache = InMemoryCache::new(1024 * 1024, 100);
+        let provider = CachingProvider::new(Box::new(inner), Box::new(cache), Duration::from_millis(10));
+
+        provider.complete(&request("Hello", None)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        provider.complete(&request("Hello", None)).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bypass_on_n_choices() {
+        let (provider, calls) = provider();
+        let provider = provider.with_bypass_on_n_choices(2);
+
+        provider.complete(&request("Hello", Some(3))).await.unwrap();
+        provider.complete(&request("Hello", Some(3))).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        provider.complete(&request("Hello", Some(1))).await.unwrap();
+        provider.complete(&request("Hello", Some(1))).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+    #[tokio::test]
+    async fn test_sensitive_headers_do_not_split_the_cache() {
+        /// Sends a new credential in a provider-specific header every call
+        struct RotatingKeyProvider {
+            inner: CountingProvider,
+            rotations: AtomicU32,
+        }
+
+        #[async_trait]
+        impl Provider for RotatingKeyProvider {
+            fn name(&self) -> &str {
+                "rotating"
+            }
+
+            fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+                let key = self.rotations.fetch_add(1, Ordering::SeqCst);
+                Ok(self.inner.transform_request(req)?.with_header("x-custom-key", format!("key-{}", key)))
+            }
+
+            async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+                self.inner.execute(req).await
+            }
+
+            fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+                self.inner.transform_response(resp)
+            }
+
+            fn sensitive_headers(&self) -> Vec<String> {
+                vec!["X-Custom-Key".to_string()]
+            }
+        }
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let inner = RotatingKeyProvider {
+            inner: CountingProvider { calls: calls.clone() },
+            rotations: AtomicU32::new(0),
+        };
+        let cache = InMemoryCache::new(1024 * 1024, 100);
+        let provider = CachingProvider::new(Box::new(inner), Box::new(cache), Duration::from_secs(60));
+
+        provider.complete(&request("Hello", None)).await.unwrap();
+        provider.complete(&request("Hello", None)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
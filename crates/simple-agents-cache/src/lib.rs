@@ -2,11 +2,23 @@
 //!
 //! Provides various caching strategies for LLM responses.
 
+pub mod agent;
+mod caching;
 mod memory;
 mod noop;
+#[cfg(feature = "redis-cache")]
+mod redis;
+pub mod semantic;
+mod tiered;
 
+pub use agent::AgentCache;
+pub use caching::CachingProvider;
 pub use memory::InMemoryCache;
 pub use noop::NoOpCache;
+#[cfg(feature = "redis-cache")]
+pub use redis::RedisCache;
+pub use semantic::{SemanticCache, SemanticCachedProvider};
+pub use tiered::{CacheTier, TieredCache};
 
 // Re-export the Cache trait
 pub use simple_agents_types::cache::Cache;
@@ -0,0 +1,290 @@
+//! Redis cache implementation with r2d2 connection pooling.
+
+use async_trait::async_trait;
+use redis::{ConnectionLike, RedisResult};
+use simple_agents_types::cache::Cache;
+use simple_agents_types::error::{Result, SimpleAgentsError};
+use std::time::Duration;
+
+/// How long to wait for a pooled connection before failing
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Keys fetched per `SCAN` round trip when clearing a namespace
+const SCAN_COUNT: usize = 100;
+
+/// Redis-backed cache.
+///
+/// Values are stored with `SET key value PX ttl_ms`, so expiry is handled by
+/// Redis. Commands run on a blocking thread using a pooled connection.
+///
+/// With a namespace, keys are stored as `namespace:key` and [`Cache::clear`]
+/// only deletes keys under that prefix. Without one, `clear` runs `FLUSHDB`
+/// and removes everything in the selected database.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_cache::RedisCache;
+/// use simple_agents_types::cache::Cache;
+/// use std::time::Duration;
+///
+/// # async fn example() -> simple_agents_types::error::Result<()> {
+/// let cache = RedisCache::with_namespace("redis://localhost:6379", "simple-agents")?;
+///
+/// cache.set("key1", b"value1".to_vec(), Duration::from_secs(60)).await?;
+/// assert_eq!(cache.get("key1").await?, Some(b"value1".to_vec()));
+/// # Ok(())
+/// # }
+/// ```
+pub struct RedisCache {
+    /// Connection pool
+    pool: r2d2::Pool<redis::Client>,
+    /// Key prefix, without the trailing `:`
+    namespace: Option<String>,
+}
+
+impl RedisCache {
+    /// Create a cache for the Redis server at `url` (e.g. `redis://localhost:6379`).
+    ///
+    /// Connections are opened lazily, so an unreachable server is reported
+    /// by the first cache operation rather than here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Cache`] if `url` is not a valid Redis URL.
+    pub fn new(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        let pool = r2d2::Pool::builder()
+            .connection_timeout(CONNECTION_TIMEOUT)
+            .build_unchecked(client);
+
+        Ok(Self {
+            pool,
+            namespace: None,
+        })
+    }
+
+    /// Create a cache whose keys are prefixed with `namespace:`.
+    ///
+    /// [`Cache::clear`] then only removes keys in this namespace.
+    pub fn with_namespace(url: &str, namespace: impl Into<String>) -> Result<Self> {
+        let mut cache = Self::new(url)?;
+        cache.namespace = Some(namespace.into());
+        Ok(cache)
+    }
+
+    /// Key prefix, if any.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    fn key(&self, key: &str) -> String {
+        namespaced_key(self.namespace.as_deref(), key)
+    }
+
+    /// Run `op` on a pooled connection on a blocking thread.
+    async fn run<T, F>(&self, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut redis::Connection) -> RedisResult<T> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| SimpleAgentsError::Cache(format!("Redis pool error: {}", e)))?;
+            op(&mut conn).map_err(redis_error)
+        })
+        .await
+        .map_err(|e| SimpleAgentsError::Cache(format!("Redis task failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let key = self.key(key);
+        self.run(move |conn| get(conn, &key)).await
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+        let key = self.key(key);
+        self.run(move |conn| set(conn, &key, &value, ttl)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let key = self.key(key);
+        self.run(move |conn| delete(conn, &key)).await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        let namespace = self.namespace.clone();
+        self.run(move |conn| clear(conn, namespace.as_deref())).await
+    }
+
+    fn name(&self) -> &str {
+        "redis"
+    }
+}
+
+fn redis_error(e: redis::RedisError) -> SimpleAgentsError {
+    SimpleAgentsError::Cache(format!("Redis error: {}", e))
+}
+
+fn namespaced_key(namespace: Option<&str>, key: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{}:{}", namespace, key),
+        None => key.to_string(),
+    }
+}
+
+/// `SCAN` pattern matching every key in `namespace`.
+fn namespace_pattern(namespace: &str) -> String {
+    let mut pattern = String::with_capacity(namespace.len() + 2);
+    for c in namespace.chars() {
+        // Escape glob metacharacters so the namespace matches literally
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push_str(":*");
+    pattern
+}
+
+fn get(conn: &mut impl ConnectionLike, key: &str) -> RedisResult<Option<Vec<u8>>> {
+    redis::cmd("GET").arg(key).query(conn)
+}
+
+fn set(conn: &mut impl ConnectionLike, key: &str, value: &[u8], ttl: Duration) -> RedisResult<()> {
+    // Redis rejects `PX 0`; expire as soon as possible instead
+    let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+    redis::cmd("SET")
+        .arg(key)
+        .arg(value)
+        .arg("PX")
+        .arg(ttl_ms)
+        .query(conn)
+}
+
+fn delete(conn: &mut impl ConnectionLike, key: &str) -> RedisResult<()> {
+    redis::cmd("DEL").arg(key).query(conn)
+}
+
+fn clear(conn: &mut impl ConnectionLike, namespace: Option<&str>) -> RedisResult<()> {
+    let Some(namespace) = namespace else {
+        return redis::cmd("FLUSHDB").query(conn);
+    };
+
+    let pattern = namespace_pattern(namespace);
+    let mut cursor = 0u64;
+    loop {
+        let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query(conn)?;
+
+        if !keys.is_empty() {
+            redis::cmd("DEL").arg(&keys).query::<()>(conn)?;
+        }
+
+        if next == 0 {
+            return Ok(());
+        }
+        cursor = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redis::Value;
+    use redis_test::{MockCmd, MockRedisConnection};
+
+    fn scan(cursor: u64, pattern: &str) -> redis::Cmd {
+        let mut cmd = redis::cmd("SCAN");
+        cmd.arg(cursor).arg("MATCH").arg(pattern).arg("COUNT").arg(SCAN_COUNT);
+        cmd
+    }
+
+    fn scan_reply(cursor: &str, keys: &[&str]) -> Value {
+        Value::Array(vec![
+            Value::BulkString(cursor.as_bytes().to_vec()),
+            Value::Array(keys.iter().map(|k| Value::BulkString(k.as_bytes().to_vec())).collect()),
+        ])
+    }
+
+    #[test]
+    fn test_get_set_delete_commands() {
+        let mut conn = MockRedisConnection::new(vec![
+            MockCmd::new(
+                redis::cmd("SET").arg("ns:key").arg(b"value").arg("PX").arg(1500u64),
+                Ok(Value::Okay),
+            ),
+            MockCmd::new(redis::cmd("GET").arg("ns:key"), Ok(Value::BulkString(b"value".to_vec()))),
+            MockCmd::new(redis::cmd("DEL").arg("ns:key"), Ok(Value::Int(1))),
+            MockCmd::new(redis::cmd("GET").arg("ns:key"), Ok(Value::Nil)),
+        ]);
+
+        set(&mut conn, "ns:key", b"value", Duration::from_millis(1500)).unwrap();
+        assert_eq!(get(&mut conn, "ns:key").unwrap(), Some(b"value".to_vec()));
+        delete(&mut conn, "ns:key").unwrap();
+        assert_eq!(get(&mut conn, "ns:key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_zero_ttl_uses_minimum() {
+        let mut conn = MockRedisConnection::new(vec![MockCmd::new(
+            redis::cmd("SET").arg("key").arg(b"v").arg("PX").arg(1u64),
+            Ok(Value::Okay),
+        )]);
+
+        set(&mut conn, "key", b"v", Duration::ZERO).unwrap();
+    }
+
+    #[test]
+    fn test_clear_without_namespace_flushes_db() {
+        let mut conn = MockRedisConnection::new(vec![MockCmd::new(redis::cmd("FLUSHDB"), Ok(Value::Okay))]);
+        clear(&mut conn, None).unwrap();
+    }
+
+    #[test]
+    fn test_clear_with_namespace_only_deletes_prefixed_keys() {
+        let mut conn = MockRedisConnection::new(vec![
+            MockCmd::new(scan(0, "app:*"), Ok(scan_reply("17", &["app:a", "app:b"]))),
+            MockCmd::new(redis::cmd("DEL").arg(&["app:a", "app:b"]), Ok(Value::Int(2))),
+            MockCmd::new(scan(17, "app:*"), Ok(scan_reply("0", &[]))),
+        ]);
+
+        clear(&mut conn, Some("app")).unwrap();
+    }
+
+    #[test]
+    fn test_command_errors_propagate() {
+        let mut conn = MockRedisConnection::new(vec![MockCmd::new(
+            redis::cmd("GET").arg("key"),
+            Err::<Value, _>(redis::RedisError::from((redis::ErrorKind::IoError, "connection reset"))),
+        )]);
+
+        assert!(get(&mut conn, "key").is_err());
+    }
+
+    #[test]
+    fn test_keys_and_patterns() {
+        assert_eq!(namespaced_key(Some("app"), "k"), "app:k");
+        assert_eq!(namespaced_key(None, "k"), "k");
+        assert_eq!(namespace_pattern("app"), "app:*");
+        assert_eq!(namespace_pattern("a*b?"), "a\\*b\\?:*");
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        assert!(matches!(RedisCache::new("not a url"), Err(SimpleAgentsError::Cache(_))));
+
+        let cache = RedisCache::with_namespace("redis://127.0.0.1:6379", "app").unwrap();
+        assert_eq!(cache.namespace(), Some("app"));
+        assert_eq!(cache.key("k"), "app:k");
+    }
+}
@@ -0,0 +1,630 @@
+//! Semantic cache backed by embeddings.
+//!
+//! Exact-key caching misses trivially rephrased prompts. [`SemanticCache`]
+//! embeds the latest user message and returns a cached response when a
+//! stored entry is similar enough and everything else about the request
+//! matches exactly.
+
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::embedding::{cosine_similarity, EmbeddingProvider};
+use simple_agents_types::prelude::*;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Default minimum cosine similarity for a cache hit.
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.95;
+
+/// An entry stored in a [`VectorIndex`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticEntry {
+    /// Exact-match scope (hash of all non-semantic request parameters)
+    pub scope: String,
+    /// Embedding of the latest user message
+    pub embedding: Vec<f32>,
+    /// Cached response
+    pub response: CompletionResponse,
+}
+
+/// A successful similarity search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticMatch {
+    /// Cached response
+    pub response: CompletionResponse,
+    /// Cosine similarity between the query and the stored embedding
+    pub similarity: f32,
+}
+
+/// Storage and search backend for semantic cache entries.
+///
+/// Implement this to back the cache with an external vector store.
+#[async_trait]
+pub trait VectorIndex: Send + Sync {
+    /// Insert an entry.
+    async fn insert(&self, entry: SemanticEntry) -> Result<()>;
+
+    /// Find the most similar entry within `scope` whose similarity is at
+    /// least `threshold`.
+    async fn search(
+        &self,
+        scope: &str,
+        embedding: &[f32],
+        threshold: f32,
+    ) -> Result<Option<SemanticMatch>>;
+
+    /// Remove all entries.
+    async fn clear(&self) -> Result<()>;
+
+    /// Number of stored entries.
+    async fn len(&self) -> Result<usize>;
+
+    /// Whether the index is empty.
+    async fn is_empty(&self) -> Result<bool> {
+        Ok(self.len().await? == 0)
+    }
+}
+
+/// In-memory vector index with LRU eviction by entry count.
+///
+/// Search is a linear scan, which is fine for the few thousand entries a
+/// process-local cache typically holds.
+pub struct InMemoryVectorIndex {
+    state: RwLock<IndexState>,
+    max_entries: usize,
+}
+
+#[derive(Default)]
+struct IndexState {
+    /// Entries paired with their last-access tick
+    entries: Vec<(SemanticEntry, u64)>,
+    tick: u64,
+}
+
+impl InMemoryVectorIndex {
+    /// Create an index holding at most `max_entries` entries (0 = unlimited).
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            state: RwLock::new(IndexState::default()),
+            max_entries,
+        }
+    }
+}
+
+#[async_trait]
+impl VectorIndex for InMemoryVectorIndex {
+    async fn insert(&self, entry: SemanticEntry) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.push((entry, tick));
+
+        if self.max_entries > 0 && state.entries.len() > self.max_entries {
+            // Evict the least recently used entry
+            if let Some(oldest) = state
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (_, accessed))| *accessed)
+                .map(|(i, _)| i)
+            {
+                state.entries.swap_remove(oldest);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        scope: &str,
+        embedding: &[f32],
+        threshold: f32,
+    ) -> Result<Option<SemanticMatch>> {
+        let mut state = self.state.write().await;
+
+        let best = state
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (entry, _))| entry.scope == scope)
+            .map(|(i, (entry, _))| (i, cosine_similarity(&entry.embedding, embedding)))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some((index, similarity)) = best else {
+            return Ok(None);
+        };
+
+        state.tick += 1;
+        let tick = state.tick;
+        let (entry, accessed) = &mut state.entries[index];
+        *accessed = tick;
+
+        Ok(Some(SemanticMatch {
+            response: entry.response.clone(),
+            similarity,
+        }))
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.state.write().await.entries.clear();
+        Ok(())
+    }
+
+    async fn len(&self) -> Result<usize> {
+        Ok(self.state.read().await.entries.len())
+    }
+}
+
+/// Cache that matches requests by embedding similarity.
+///
+/// A lookup hits when:
+/// - the latest user message embeds within the similarity threshold of a
+///   stored entry, and
+/// - every other part of the request (model, earlier messages, sampling
+///   parameters with temperature bucketed to one decimal) matches exactly.
+///
+/// Requests without a user message are never cached.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_cache::SemanticCache;
+/// # use simple_agents_types::embedding::EmbeddingProvider;
+/// # use simple_agents_types::prelude::*;
+///
+/// # async fn example(embedder: Box<dyn EmbeddingProvider>, request: CompletionRequest) -> Result<()> {
+/// let cache = SemanticCache::new(embedder, 1000).with_threshold(0.9);
+///
+/// if let Some(response) = cache.lookup(&request).await? {
+///     println!("cached: {:?}", response.content());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SemanticCache {
+    embedder: Box<dyn EmbeddingProvider>,
+    index: Box<dyn VectorIndex>,
+    threshold: f32,
+}
+
+impl SemanticCache {
+    /// Create a semantic cache with an in-memory index of `max_entries`.
+    pub fn new(embedder: Box<dyn EmbeddingProvider>, max_entries: usize) -> Self {
+        Self::with_index(embedder, Box::new(InMemoryVectorIndex::new(max_entries)))
+    }
+
+    /// Create a semantic cache with a custom vector index.
+    pub fn with_index(embedder: Box<dyn EmbeddingProvider>, index: Box<dyn VectorIndex>) -> Self {
+        Self {
+            embedder,
+            index,
+            threshold: DEFAULT_SIMILARITY_THRESHOLD,
+        }
+    }
+
+    /// Set the minimum cosine similarity for a hit (clamped to -1.0..=1.0).
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Get the similarity threshold.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Look up a cached response for a semantically similar request.
+    pub async fn lookup(&self, request: &CompletionRequest) -> Result<Option<CompletionResponse>> {
+        let Some((scope, query)) = Self::split(request) else {
+            return Ok(None);
+        };
+
+        let embedding = self.embedder.embed(query).await?;
+        let found = self.index.search(&scope, &embedding, self.threshold).await?;
+
+        Ok(found.map(|m| m.response))
+    }
+
+    /// Store a response for a request.
+    pub async fn store(
+        &self,
+        request: &CompletionRequest,
+        response: &CompletionResponse,
+    ) -> Result<()> {
+        let Some((scope, query)) = Self::split(request) else {
+            return Ok(());
+        };
+
+        let embedding = self.embedder.embed(query).await?;
+        self.index
+            .insert(SemanticEntry {
+                scope,
+                embedding,
+                response: response.clone(),
+            })
+            .await
+    }
+
+    /// Remove all cached entries.
+    pub async fn clear(&self) -> Result<()> {
+        self.index.clear().await
+    }
+
+    /// Number of cached entries.
+    pub async fn len(&self) -> Result<usize> {
+        self.index.len().await
+    }
+
+    /// Whether the cache is empty.
+    pub async fn is_empty(&self) -> Result<bool> {
+        self.index.is_empty().await
+    }
+
+    /// Split a request into its exact-match scope and the text to embed.
+    fn split(request: &CompletionRequest) -> Option<(String, &str)> {
+        let position = request
+            .messages
+            .iter()
+            .rposition(|msg| msg.role == Role::User)?;
+
+        let mut scoped = request.clone();
+        scoped.messages.remove(position);
+        scoped.temperature = scoped.temperature.map(|t| (t * 10.0).round() / 10.0);
+
+        // The scope also has to distinguish *which* message was embedded.
+        let scope = format!("{}:{}", position, scoped.fingerprint());
+
+        Some((scope, request.messages[position].content.as_str()))
+    }
+}
+
+/// Provider wrapper that consults a [`SemanticCache`] in `complete`.
+///
+/// The lower-level provider hooks delegate to the inner provider unchanged;
+/// streamed responses go through `execute_stream` and are never cached.
+pub struct SemanticCachedProvider<P> {
+    inner: P,
+    cache: SemanticCache,
+}
+
+impl<P: Provider> SemanticCachedProvider<P> {
+    /// Wrap `inner` with a semantic cache.
+    pub fn new(inner: P, cache: SemanticCache) -> Self {
+        Self { inner, cache }
+    }
+
+    /// Access the cache.
+    pub fn cache(&self) -> &SemanticCache {
+        &self.cache
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for SemanticCachedProvider<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures_core::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        // Streamed responses are never cached
+        req.ensure_not_streaming()?;
+
+        if let Some(response) = self.cache.lookup(req).await? {
+            return Ok(response);
+        }
+
+        let response = self.inner.complete(req).await?;
+        self.cache.store(req, &response).await?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Deterministic embedder: letter frequencies, case-insensitive.
+    struct LetterEmbedder;
+
+    #[async_trait]
+    impl EmbeddingProvider for LetterEmbedder {
+        async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+            let mut counts = vec![0.0; 26];
+            for c in input.chars().filter(|c| c.is_ascii_alphabetic()) {
+                counts[(c.to_ascii_lowercase() as u8 - b'a') as usize] += 1.0;
+            }
+            Ok(counts)
+        }
+    }
+
+    struct CountingProvider {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn transform_request(&self, _req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com"))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ProviderResponse::new(200, serde_json::json!({ "call": call })))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(response(&format!("answer {}", resp.body["call"])))
+        }
+
+        fn sensitive_headers(&self) -> Vec<String> {
+            vec!["x-counting-key".to_string()]
+        }
+
+        fn pricing(&self, _model: &str) -> Option<Pricing> {
+            Some(Pricing { input_per_million: 1.0, output_per_million: 2.0 })
+        }
+
+        async fn execute_stream(
+            &self,
+            _req: ProviderRequest,
+        ) -> Result<Box<dyn futures_core::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: "gpt-4".to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(StreamOnce(Some(Ok(chunk)))))
+        }
+    }
+
+    /// Stream of one item
+    struct StreamOnce(Option<Result<CompletionChunk>>);
+
+    impl futures_core::Stream for StreamOnce {
+        type Item = Result<CompletionChunk>;
+
+        fn poll_next(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Self::Item>> {
+            std::task::Poll::Ready(self.0.take())
+        }
+    }
+
+    fn response(content: &str) -> CompletionResponse {
+        CompletionResponse {
+            id: "resp".to_string(),
+            model: "gpt-4".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(content),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(1, 1),
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        }
+    }
+
+    fn request(model: &str, content: &str, temperature: f32) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model(model)
+            .message(Message::system("Answer briefly."))
+            .message(Message::user(content))
+            .temperature(temperature)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_provider_forwards_hooks_and_streams() {
+        use futures_core::Stream;
+
+        let provider = SemanticCachedProvider::new(
+            CountingProvider { calls: AtomicU32::new(0) },
+            SemanticCache::new(Box::new(LetterEmbedder), 10),
+        );
+        assert_eq!(provider.sensitive_headers(), ["x-counting-key"]);
+        assert_eq!(provider.pricing("gpt-4").unwrap().output_per_million, 2.0);
+
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .stream(true)
+            .build()
+            .unwrap();
+        let provider_request = provider.transform_request(&request).unwrap();
+        let mut stream = provider.execute_stream(provider_request).await.unwrap();
+        let chunk = std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap().id, "chunk");
+        assert!(provider.cache().lookup(&request).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rephrased_prompt_hits() {
+        let cache = SemanticCache::new(Box::new(LetterEmbedder), 10);
+        cache
+            .store(
+                &request("gpt-4", "What is the capital of France?", 0.7),
+                &response("Paris"),
+            )
+            .await
+            .unwrap();
+
+        let hit = cache
+            .lookup(&request("gpt-4", "what is the capital of france", 0.7))
+            .await
+            .unwrap();
+        assert_eq!(hit.unwrap().content(), Some("Paris"));
+    }
+
+    #[tokio::test]
+    async fn test_dissimilar_prompt_misses() {
+        let cache = SemanticCache::new(Box::new(LetterEmbedder), 10);
+        cache
+            .store(
+                &request("gpt-4", "What is the capital of France?", 0.7),
+                &response("Paris"),
+            )
+            .await
+            .unwrap();
+
+        let miss = cache
+            .lookup(&request("gpt-4", "zzz", 0.7))
+            .await
+            .unwrap();
+        assert!(miss.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_parameters_must_match_exactly() {
+        let cache = SemanticCache::new(Box::new(LetterEmbedder), 10);
+        cache
+            .store(&request("gpt-4", "Hello there", 0.7), &response("Hi"))
+            .await
+            .unwrap();
+
+        // Different model
+        assert!(cache
+            .lookup(&request("gpt-3.5-turbo", "Hello there", 0.7))
+            .await
+            .unwrap()
+            .is_none());
+        // Different temperature bucket
+        assert!(cache
+            .lookup(&request("gpt-4", "Hello there", 1.5))
+            .await
+            .unwrap()
+            .is_none());
+        // Same temperature bucket
+        assert!(cache
+            .lookup(&request("gpt-4", "Hello there", 0.71))
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction_by_count() {
+        let cache = SemanticCache::new(Box::new(LetterEmbedder), 2).with_threshold(0.999);
+        cache.store(&request("gpt-4", "aaaa", 0.0), &response("a")).await.unwrap();
+        cache.store(&request("gpt-4", "bbbb", 0.0), &response("b")).await.unwrap();
+
+        // Touch "aaaa" so "bbbb" becomes least recently used
+        assert!(cache.lookup(&request("gpt-4", "aaaa", 0.0)).await.unwrap().is_some());
+
+        cache.store(&request("gpt-4", "cccc", 0.0), &response("c")).await.unwrap();
+
+        assert_eq!(cache.len().await.unwrap(), 2);
+        assert!(cache.lookup(&request("gpt-4", "aaaa", 0.0)).await.unwrap().is_some());
+        assert!(cache.lookup(&request("gpt-4", "bbbb", 0.0)).await.unwrap().is_none());
+        assert!(cache.lookup(&request("gpt-4", "cccc", 0.0)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider_short_circuits() {
+        let provider = SemanticCachedProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            SemanticCache::new(Box::new(LetterEmbedder), 10),
+        );
+
+        let first = provider
+            .complete(&request("gpt-4", "Tell me a joke", 0.5))
+            .await
+            .unwrap();
+        let second = provider
+            .complete(&request("gpt-4", "tell me a joke!", 0.5))
+            .await
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider_refuses_streaming_requests() {
+        let provider = SemanticCachedProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            SemanticCache::new(Box::new(LetterEmbedder), 10),
+        );
+
+        let mut streaming = request("gpt-4", "Tell me a joke", 0.5);
+        streaming.stream = Some(true);
+
+        let result = provider.complete(&streaming).await;
+        assert!(matches!(result, Err(SimpleAgentsError::Validation(_))));
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 0);
+        assert_eq!(provider.cache().len().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,243 @@
+//! Two-level cache combining a fast local tier with a slower shared tier.
+
+use async_trait::async_trait;
+use simple_agents_types::cache::Cache;
+use simple_agents_types::error::Result;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+/// Cache tier that served a hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheTier {
+    /// Fast tier (e.g. in-memory)
+    L1,
+    /// Slow tier (e.g. Redis)
+    L2,
+}
+
+const NO_HIT: u8 = 0;
+const L1_HIT: u8 = 1;
+const L2_HIT: u8 = 2;
+
+/// Two-level cache.
+///
+/// Reads check `l1` first and fall back to `l2`; an `l2` hit is copied into
+/// `l1` so later reads stay local. Writes, deletes and clears go to both.
+///
+/// The [`Cache`] trait does not expose remaining TTLs, so values copied from
+/// `l2` are stored in `l1` for [`TieredCache::DEFAULT_L1_TTL`] (see
+/// [`TieredCache::with_l1_ttl`]). Keep it short when `l2` is shared, since
+/// `l1` will not see deletes made through other instances.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_cache::{InMemoryCache, TieredCache};
+/// use simple_agents_types::cache::Cache;
+/// use std::time::Duration;
+///
+/// # async fn example() {
+/// let cache = TieredCache::new(
+///     Box::new(InMemoryCache::new(1024 * 1024, 100)),
+///     Box::new(InMemoryCache::new(0, 0)), // typically a RedisCache
+/// );
+///
+/// cache.set("key1", b"value1".to_vec(), Duration::from_secs(60)).await.unwrap();
+/// assert_eq!(cache.get("key1").await.unwrap(), Some(b"value1".to_vec()));
+/// # }
+/// ```
+pub struct TieredCache {
+    /// Fast tier
+    l1: Box<dyn Cache>,
+    /// Slow tier
+    l2: Box<dyn Cache>,
+    /// TTL for values copied from L2 into L1
+    l1_ttl: Duration,
+    /// Tier that served the last hit
+    last_hit: AtomicU8,
+}
+
+impl TieredCache {
+    /// Default TTL for values copied from L2 into L1
+    pub const DEFAULT_L1_TTL: Duration = Duration::from_secs(300);
+
+    /// Create a tiered cache from a fast `l1` and a slow `l2`.
+    pub fn new(l1: Box<dyn Cache>, l2: Box<dyn Cache>) -> Self {
+        Self {
+            l1,
+            l2,
+            l1_ttl: Self::DEFAULT_L1_TTL,
+            last_hit: AtomicU8::new(NO_HIT),
+        }
+    }
+
+    /// Set the TTL for values copied from L2 into L1.
+    pub fn with_l1_ttl(mut self, ttl: Duration) -> Self {
+        self.l1_ttl = ttl;
+        self
+    }
+
+    /// Tier that served the most recent [`Cache::get`].
+    ///
+    /// Returns `None` before the first lookup and after a miss.
+    pub fn hit_tier(&self) -> Option<CacheTier> {
+        match self.last_hit.load(Ordering::Acquire) {
+            L1_HIT => Some(CacheTier::L1),
+            L2_HIT => Some(CacheTier::L2),
+            _ => None,
+        }
+    }
+
+    /// Access the fast tier.
+    pub fn l1(&self) -> &dyn Cache {
+        self.l1.as_ref()
+    }
+
+    /// Access the slow tier.
+    pub fn l2(&self) -> &dyn Cache {
+        self.l2.as_ref()
+    }
+
+    fn record_hit(&self, hit: u8) {
+        self.last_hit.store(hit, Ordering::Release);
+    }
+}
+
+#[async_trait]
+impl Cache for TieredCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.l1.get(key).await? {
+            self.record_hit(L1_HIT);
+            return Ok(Some(value));
+        }
+
+        match self.l2.get(key).await? {
+            Some(value) => {
+                self.l1.set(key, value.clone(), self.l1_ttl).await?;
+                self.record_hit(L2_HIT);
+                Ok(Some(value))
+            }
+            None => {
+                self.record_hit(NO_HIT);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+        self.l2.set(key, value.clone(), ttl).await?;
+        self.l1.set(key, value, ttl).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.l2.delete(key).await?;
+        self.l1.delete(key).await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.l2.clear().await?;
+        self.l1.clear().await
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.l1.is_enabled() || self.l2.is_enabled()
+    }
+
+    fn name(&self) -> &str {
+        "tiered"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryCache;
+    use std::sync::Arc;
+
+    /// Handle to a cache shared between several tiered caches.
+    struct Shared(Arc<InMemoryCache>);
+
+    #[async_trait]
+    impl Cache for Shared {
+        async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            self.0.get(key).await
+        }
+
+        async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+            self.0.set(key, value, ttl).await
+        }
+
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.0.delete(key).await
+        }
+
+        async fn clear(&self) -> Result<()> {
+            self.0.clear().await
+        }
+    }
+
+    fn tiered(l2: &Arc<InMemoryCache>) -> TieredCache {
+        TieredCache::new(Box::new(InMemoryCache::new(0, 0)), Box::new(Shared(l2.clone())))
+    }
+
+    #[tokio::test]
+    async fn test_l2_hit_populates_l1() {
+        let l2 = Arc::new(InMemoryCache::new(0, 0));
+        let cache = tiered(&l2);
+        assert_eq!(cache.hit_tier(), None);
+
+        l2.set("key", b"value".to_vec(), Duration::from_secs(60)).await.unwrap();
+        assert_eq!(cache.l1().get("key").await.unwrap(), None);
+
+        assert_eq!(cache.get("key").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(cache.hit_tier(), Some(CacheTier::L2));
+        assert_eq!(cache.l1().get("key").await.unwrap(), Some(b"value".to_vec()));
+
+        assert_eq!(cache.get("key").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(cache.hit_tier(), Some(CacheTier::L1));
+
+        assert_eq!(cache.get("missing").await.unwrap(), None);
+        assert_eq!(cache.hit_tier(), None);
+    }
+
+    #[tokio::test]
+    async fn test_write_is_visible_from_fresh_l1() {
+        let l2 = Arc::new(InMemoryCache::new(0, 0));
+        let writer = tiered(&l2);
+        writer.set("key", b"value".to_vec(), Duration::from_secs(60)).await.unwrap();
+
+        let reader = tiered(&l2);
+        assert_eq!(reader.get("key").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(reader.hit_tier(), Some(CacheTier::L2));
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_clear_hit_both_tiers() {
+        let l2 = Arc::new(InMemoryCache::new(0, 0));
+        let cache = tiered(&l2);
+
+        cache.set("a", b"1".to_vec(), Duration::from_secs(60)).await.unwrap();
+        cache.set("b", b"2".to_vec(), Duration::from_secs(60)).await.unwrap();
+
+        cache.delete("a").await.unwrap();
+        assert_eq!(cache.l1().get("a").await.unwrap(), None);
+        assert_eq!(l2.get("a").await.unwrap(), None);
+
+        cache.clear().await.unwrap();
+        assert_eq!(cache.l1().get("b").await.unwrap(), None);
+        assert_eq!(l2.get("b").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_l1_ttl_for_promoted_values() {
+        let l2 = Arc::new(InMemoryCache::new(0, 0));
+        let cache = tiered(&l2).with_l1_ttl(Duration::from_millis(20));
+
+        l2.set("key", b"value".to_vec(), Duration::from_secs(60)).await.unwrap();
+        cache.get("key").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        assert_eq!(cache.l1().get("key").await.unwrap(), None);
+        assert_eq!(cache.get("key").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(cache.hit_tier(), Some(CacheTier::L2));
+    }
+}
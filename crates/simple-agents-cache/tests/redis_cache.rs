@@ -0,0 +1,55 @@
+//! Integration tests for the Redis cache.
+//!
+//! These need a running Redis server and are ignored by default:
+//!
+//! ```text
+//! REDIS_URL=redis://127.0.0.1:6379 cargo test -p simple-agents-cache --features redis-cache -- --ignored
+//! ```
+
+#![cfg(feature = "redis-cache")]
+
+use simple_agents_cache::RedisCache;
+use simple_agents_types::cache::Cache;
+use std::time::Duration;
+
+fn redis_url() -> String {
+    std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string())
+}
+
+#[tokio::test]
+#[ignore = "requires a running Redis server"]
+async fn test_set_get_delete() {
+    let cache = RedisCache::with_namespace(&redis_url(), "simple-agents-test-basic").unwrap();
+
+    cache.set("key", b"value".to_vec(), Duration::from_secs(60)).await.unwrap();
+    assert_eq!(cache.get("key").await.unwrap(), Some(b"value".to_vec()));
+
+    cache.delete("key").await.unwrap();
+    assert_eq!(cache.get("key").await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "requires a running Redis server"]
+async fn test_ttl_expiry() {
+    let cache = RedisCache::with_namespace(&redis_url(), "simple-agents-test-ttl").unwrap();
+
+    cache.set("key", b"value".to_vec(), Duration::from_millis(100)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(cache.get("key").await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "requires a running Redis server"]
+async fn test_clear_is_scoped_to_namespace() {
+    let ours = RedisCache::with_namespace(&redis_url(), "simple-agents-test-ours").unwrap();
+    let theirs = RedisCache::with_namespace(&redis_url(), "simple-agents-test-theirs").unwrap();
+
+    ours.set("key", b"ours".to_vec(), Duration::from_secs(60)).await.unwrap();
+    theirs.set("key", b"theirs".to_vec(), Duration::from_secs(60)).await.unwrap();
+
+    ours.clear().await.unwrap();
+
+    assert_eq!(ours.get("key").await.unwrap(), None);
+    assert_eq!(theirs.get("key").await.unwrap(), Some(b"theirs".to_vec()));
+    theirs.clear().await.unwrap();
+}
@@ -0,0 +1,181 @@
+//! Benchmarks for the serialization work done on every API call.
+//!
+//! Everything runs in memory; no requests are sent. Run with
+//! `cargo bench -p simple-agents-providers --bench serialization`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use futures::StreamExt;
+use simple_agents_providers::openai::{self, OpenAIProvider};
+use simple_agents_providers::provider_kit::{parse_json_body, DEFAULT_RAW_BODY_LIMIT};
+use simple_agents_providers::streaming::{SseEvent, SseParser};
+use simple_agents_types::cache::CacheKey;
+use simple_agents_types::prelude::*;
+
+const API_KEY: &str = "sk-bench1234567890123456789012345678901234567890";
+
+/// A 10-message conversation: a system prompt and alternating turns
+fn conversation() -> CompletionRequest {
+    let mut builder = CompletionRequest::builder()
+        .model("gpt-4o-mini")
+        .message(Message::system("You are a concise assistant. Answer in one sentence."))
+        .temperature(0.7)
+        .max_tokens(256);
+    for turn in 0..9 {
+        builder = if turn % 2 == 0 {
+            builder.message(Message::user(format!("Question {}: what is {} squared?", turn, turn)))
+        } else {
+            builder.message(Message::assistant(format!("It is {}.", turn * turn)))
+        };
+    }
+    builder.build().unwrap()
+}
+
+fn chat_response() -> ProviderResponse {
+    ProviderResponse::new(
+        200,
+        serde_json::json!({
+            "id": "chatcmpl-bench",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "gpt-4o-mini-2024-07-18",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Sixty-four is eight squared.", "refusal": null},
+                "logprobs": null,
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 120, "completion_tokens": 8, "total_tokens": 128},
+            "system_fingerprint": "fp_bench"
+        }),
+    )
+}
+
+/// An n=4 completion with token logprobs, a few megabytes of JSON
+fn large_chat_body() -> Vec<u8> {
+    let logprobs: Vec<_> = (0..15_000)
+        .map(|_| serde_json::json!({"token": "ab", "logprob": -0.25, "bytes": [97, 98], "top_logprobs": []}))
+        .collect();
+    let choices: Vec<_> = (0..4)
+        .map(|index| {
+            serde_json::json!({
+                "index": index,
+                "message": {"role": "assistant", "content": "ab".repeat(100_000)},
+                "logprobs": {"content": logprobs},
+                "finish_reason": "length"
+            })
+        })
+        .collect();
+    serde_json::to_vec(&serde_json::json!({
+        "id": "chatcmpl-bench",
+        "object": "chat.completion",
+        "created": 1700000000,
+        "model": "gpt-4o-mini",
+        "choices": choices,
+        "usage": {"prompt_tokens": 10, "completion_tokens": 60_000, "total_tokens": 60_010}
+    }))
+    .unwrap()
+}
+
+/// Raw SSE bytes for a streamed completion of `n` content chunks
+fn sse_stream(n: usize) -> Vec<u8> {
+    let mut body = String::new();
+    for i in 0..n {
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-bench",
+            "object": "chat.completion.chunk",
+            "created": 1700000000,
+            "model": "gpt-4o-mini",
+            "choices": [{"index": 0, "delta": {"content": format!("token{} ", i)}, "finish_reason": null}]
+        });
+        body.push_str(&format!("data: {}\n\n", chunk));
+    }
+    body.push_str("data: [DONE]\n\n");
+    body.into_bytes()
+}
+
+/// Parse SSE bytes and concatenate the streamed content
+fn accumulate_chunks(bytes: &[u8]) -> String {
+    let events: Vec<Result<SseEvent>> = SseParser::new().feed(bytes).into_iter().map(Ok).collect();
+    let chunks = openai::chunk_stream(futures::stream::iter(events));
+    futures::executor::block_on(chunks.fold(String::new(), |mut content, chunk| {
+        let chunk = chunk.unwrap();
+        if let Some(text) = chunk.choices.first().and_then(|c| c.delta.content.as_deref()) {
+            content.push_str(text);
+        }
+        futures::future::ready(content)
+    }))
+}
+
+fn bench_request_builder(c: &mut Criterion) {
+    c.bench_function("completion_request_build", |b| {
+        b.iter(|| {
+            CompletionRequest::builder()
+                .model(black_box("gpt-4o-mini"))
+                .message(Message::system("You are a concise assistant."))
+                .message(Message::user(black_box("What is 8 squared?")))
+                .temperature(0.7)
+                .max_tokens(256)
+                .build()
+                .unwrap()
+        })
+    });
+}
+
+fn bench_transform(c: &mut Criterion) {
+    let provider = OpenAIProvider::new(ApiKey::new(API_KEY).unwrap()).unwrap();
+    let request = conversation();
+    c.bench_function("openai_transform_request_10_messages", |b| {
+        b.iter(|| provider.transform_request(black_box(&request)).unwrap())
+    });
+
+    let response = chat_response();
+    c.bench_function("openai_transform_response", |b| {
+        b.iter_batched(
+            || response.clone(),
+            |response| provider.transform_response(response).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_large_response(c: &mut Criterion) {
+    let body = large_chat_body();
+    let mut group = c.benchmark_group("parse_large_response");
+    group.sample_size(10);
+    group.bench_function("via_value", |b| {
+        b.iter(|| {
+            let value: serde_json::Value = serde_json::from_slice(black_box(&body)).unwrap();
+            serde_json::from_value::<openai::OpenAICompletionResponse>(value).unwrap()
+        })
+    });
+    group.bench_function("typed", |b| {
+        b.iter(|| {
+            parse_json_body::<openai::OpenAICompletionResponse>(black_box(&body), DEFAULT_RAW_BODY_LIMIT)
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_cache_key(c: &mut Criterion) {
+    let request = conversation();
+    c.bench_function("cache_key_from_request", |b| {
+        b.iter(|| CacheKey::from_request(black_box("openai"), black_box(&request)))
+    });
+}
+
+fn bench_accumulate_chunks(c: &mut Criterion) {
+    let bytes = sse_stream(100);
+    assert!(accumulate_chunks(&bytes).ends_with("token99 "));
+    c.bench_function("accumulate_chunks_100", |b| b.iter(|| accumulate_chunks(black_box(&bytes))));
+}
+
+criterion_group!(
+    benches,
+    bench_request_builder,
+    bench_transform,
+    bench_large_response,
+    bench_cache_key,
+    bench_accumulate_chunks
+);
+criterion_main!(benches);
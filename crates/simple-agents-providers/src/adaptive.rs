@@ -0,0 +1,544 @@
+//! `max_tokens` sized from observed completion lengths.
+//!
+//! [`AdaptiveTokensProvider`] records how many completion tokens each kind
+//! of request used and sets `max_tokens` to a high percentile of that
+//! history plus headroom, instead of a fixed "just in case" budget.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::message::Role;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Computes the bucket a request's statistics are kept under.
+pub type BucketFn = Arc<dyn Fn(&CompletionRequest) -> String + Send + Sync>;
+
+/// Tuning for [`AdaptiveTokensProvider`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveTokensConfig {
+    /// Percentile of historical completion tokens to budget for (0.0-1.0)
+    pub percentile: f64,
+    /// Multiplier applied to the percentile
+    pub headroom: f64,
+    /// Lower bound for an adaptive `max_tokens`
+    pub min_tokens: u32,
+    /// Upper bound for an adaptive or retried `max_tokens`
+    pub max_tokens: u32,
+    /// Samples a bucket needs before its statistics are used
+    pub min_samples: usize,
+    /// Most recent samples kept per bucket
+    pub window: usize,
+    /// Retry once with double the budget when a completion stops at
+    /// `max_tokens`
+    pub retry_on_length: bool,
+}
+
+impl Default for AdaptiveTokensConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 0.95,
+            headroom: 1.2,
+            min_tokens: 16,
+            max_tokens: 4096,
+            min_samples: 10,
+            window: 200,
+            retry_on_length: false,
+        }
+    }
+}
+
+/// Serializable snapshot of the collected statistics.
+///
+/// Save it with [`AdaptiveTokensProvider::state`] and restore it with
+/// [`AdaptiveTokensProvider::with_state`] so budgets survive restarts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveTokensState {
+    /// Statistics per bucket, ordered by model then bucket
+    pub buckets: Vec<BucketState>,
+}
+
+/// Completion-token history of one bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BucketState {
+    /// Model the samples were collected for
+    pub model: String,
+    /// Bucket key
+    pub bucket: String,
+    /// Completion tokens of recent responses, oldest first
+    pub completion_tokens: Vec<u32>,
+}
+
+/// Provider wrapper that chooses `max_tokens` from completion history.
+///
+/// Statistics are kept per `(model, bucket)`. By default the bucket is a
+/// hash of the request's system messages, so requests built from the same
+/// prompt template share a history; override it with
+/// [`with_bucket_fn`](Self::with_bucket_fn).
+///
+/// Once a bucket has [`min_samples`](AdaptiveTokensConfig::min_samples),
+/// [`Provider::complete`] sets `max_tokens` to the configured percentile
+/// times [`headroom`](AdaptiveTokensConfig::headroom), clamped to
+/// `min_tokens..=max_tokens`. Colder buckets use the request's own
+/// `max_tokens`. The lower-level hooks, `execute_stream` included,
+/// delegate to the inner provider unchanged.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::adaptive::{AdaptiveTokensConfig, AdaptiveTokensProvider};
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let provider = AdaptiveTokensProvider::new(
+///     OpenAIProvider::new(ApiKey::new("sk-...")?)?,
+///     AdaptiveTokensConfig {
+///         retry_on_length: true,
+///         ..Default::default()
+///     },
+/// );
+///
+/// let request = CompletionRequest::builder()
+///     .model("gpt-4o-mini")
+///     .message(Message::system("Summarize in one paragraph."))
+///     .message(Message::user("..."))
+///     .max_tokens(1024)
+///     .build()?;
+/// provider.complete(&request).await?;
+///
+/// let state = serde_json::to_string(&provider.state())?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AdaptiveTokensProvider<P> {
+    inner: P,
+    config: AdaptiveTokensConfig,
+    bucket_fn: Option<BucketFn>,
+    stats: Mutex<HashMap<(String, String), VecDeque<u32>>>,
+}
+
+impl<P: Provider> AdaptiveTokensProvider<P> {
+    /// Wrap `inner` with no history.
+    pub fn new(inner: P, config: AdaptiveTokensConfig) -> Self {
+        Self {
+            inner,
+            config,
+            bucket_fn: None,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Override how requests are grouped into buckets.
+    pub fn with_bucket_fn(mut self, bucket_fn: BucketFn) -> Self {
+        self.bucket_fn = Some(bucket_fn);
+        self
+    }
+
+    /// Restore statistics saved with [`state`](Self::state).
+    ///
+    /// Replaces any history collected so far; buckets longer than the
+    /// configured window keep their most recent samples.
+    pub fn with_state(self, state: AdaptiveTokensState) -> Self {
+        {
+            let mut stats = self.lock_stats();
+            stats.clear();
+            for bucket in state.buckets {
+                let skip = bucket.completion_tokens.len().saturating_sub(self.config.window);
+                let samples = bucket.completion_tokens.into_iter().skip(skip).collect();
+                stats.insert((bucket.model, bucket.bucket), samples);
+            }
+        }
+        self
+    }
+
+    /// Snapshot of the collected statistics.
+    pub fn state(&self) -> AdaptiveTokensState {
+        let mut buckets: Vec<BucketState> = self
+            .lock_stats()
+            .iter()
+            .map(|((model, bucket), samples)| BucketState {
+                model: model.clone(),
+                bucket: bucket.clone(),
+                completion_tokens: samples.iter().copied().collect(),
+            })
+            .collect();
+        buckets.sort_by(|a, b| (&a.model, &a.bucket).cmp(&(&b.model, &b.bucket)));
+        AdaptiveTokensState { buckets }
+    }
+
+    /// The active configuration.
+    pub fn config(&self) -> &AdaptiveTokensConfig {
+        &self.config
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Bucket `req` is tracked under.
+    pub fn bucket(&self, req: &CompletionRequest) -> String {
+        match &self.bucket_fn {
+            Some(bucket_fn) => bucket_fn(req),
+            None => system_prompt_bucket(req),
+        }
+    }
+
+    /// `max_tokens` that [`Provider::complete`] would send for `req`.
+    pub fn budget(&self, req: &CompletionRequest) -> Option<u32> {
+        let key = (req.model.clone(), self.bucket(req));
+        let stats = self.lock_stats();
+        match stats.get(&key) {
+            Some(samples) if !samples.is_empty() && samples.len() >= self.config.min_samples => {
+                let mut sorted: Vec<u32> = samples.iter().copied().collect();
+                sorted.sort_unstable();
+                let rank = (self.config.percentile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+                let value = sorted[rank.clamp(1, sorted.len()) - 1];
+                let budget = (value as f64 * self.config.headroom).ceil() as u32;
+                Some(budget.clamp(self.config.min_tokens, self.config.max_tokens.max(self.config.min_tokens)))
+            }
+            _ => req.max_tokens,
+        }
+    }
+
+    fn record(&self, req: &CompletionRequest, completion_tokens: u32) {
+        let key = (req.model.clone(), self.bucket(req));
+        let mut stats = self.lock_stats();
+        let samples = stats.entry(key).or_default();
+        samples.push_back(completion_tokens);
+        while samples.len() > self.config.window.max(1) {
+            samples.pop_front();
+        }
+    }
+
+    fn lock_stats(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), VecDeque<u32>>> {
+        self.stats.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Default bucket: a short hash of the request's system messages.
+fn system_prompt_bucket(req: &CompletionRequest) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for message in req.messages.iter().filter(|m| m.role == Role::System) {
+        hasher.update(message.content.as_bytes());
+        hasher.update(&[0]);
+    }
+    hasher.finalize().to_hex()[..16].to_string()
+}
+
+fn stopped_at_limit(response: &CompletionResponse) -> bool {
+    response.choices.iter().any(|c| c.finish_reason == FinishReason::Length)
+}
+
+#[async_trait]
+impl<P: Provider> Provider for AdaptiveTokensProvider<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        let mut request = req.clone();
+        request.max_tokens = self.budget(req);
+
+        let mut response = self.inner.complete(&request).await?;
+        if self.config.retry_on_length && stopped_at_limit(&response) {
+            let retry_budget = request
+                .max_tokens
+                .map(|budget| budget.saturating_mul(2).min(self.config.max_tokens))
+                .filter(|retry| Some(*retry) > request.max_tokens);
+            if let Some(retry_budget) = retry_budget {
+                tracing::debug!(
+                    model = %request.model,
+                    budget = request.max_tokens,
+                    retry_budget,
+                    "Completion hit max_tokens; retrying with a larger budget"
+                );
+                request.max_tokens = Some(retry_budget);
+                response = self.inner.complete(&request).await?;
+            }
+        }
+
+        self.record(req, response.usage.completion_tokens);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replies with `completion_tokens` tokens, stopping at `max_tokens`,
+    /// and records the `max_tokens` of every request
+    struct LengthProvider {
+        completion_tokens: u32,
+        seen: Mutex<Vec<Option<u32>>>,
+    }
+
+    impl LengthProvider {
+        fn new(completion_tokens: u32) -> Self {
+            Self {
+                completion_tokens,
+                seen: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn seen(&self) -> Vec<Option<u32>> {
+            self.seen.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Provider for LengthProvider {
+        fn name(&self) -> &str {
+            "length"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            self.seen.lock().unwrap().push(req.max_tokens);
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "max_tokens": req.max_tokens })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            let limit = resp.body["max_tokens"].as_u64().map(|m| m as u32).unwrap_or(u32::MAX);
+            let used = self.completion_tokens.min(limit);
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: "test-model".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("..."),
+                    finish_reason: if used < self.completion_tokens {
+                        FinishReason::Length
+                    } else {
+                        FinishReason::Stop
+                    },
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(10, used),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+
+        async fn execute_stream(
+            &self,
+            _req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: "test-model".to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    fn request(system: &str, max_tokens: u32) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("test-model")
+            .message(Message::system(system))
+            .message(Message::user("Go."))
+            .max_tokens(max_tokens)
+            .build()
+            .unwrap()
+    }
+
+    fn config(min_samples: usize) -> AdaptiveTokensConfig {
+        AdaptiveTokensConfig {
+            min_samples,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cold_bucket_uses_explicit_max_tokens() {
+        let provider = AdaptiveTokensProvider::new(LengthProvider::new(100), config(3));
+
+        for _ in 0..3 {
+            provider.complete(&request("Summarize.", 2000)).await.unwrap();
+        }
+        provider.complete(&request("Summarize.", 2000)).await.unwrap();
+
+        // Three cold requests, then p95 of [100, 100, 100] × 1.2
+        assert_eq!(provider.inner().seen(), vec![Some(2000), Some(2000), Some(2000), Some(120)]);
+    }
+
+    #[tokio::test]
+    async fn test_streams_through_inner() {
+        use futures::StreamExt;
+
+        let provider = AdaptiveTokensProvider::new(LengthProvider::new(100), config(1));
+        let mut req = request("Summarize.", 2000);
+        req.stream = Some(true);
+
+        let stream = provider.execute_stream(provider.transform_request(&req).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().model, "test-model");
+        assert_eq!(provider.inner().seen(), vec![Some(2000)]);
+    }
+
+    #[test]
+    fn test_budget_percentile_and_bounds() {
+        let samples: Vec<u32> = (1..=100).collect();
+        let state = |model: &str, bucket: String| AdaptiveTokensState {
+            buckets: vec![BucketState {
+                model: model.to_string(),
+                bucket,
+                completion_tokens: samples.clone(),
+            }],
+        };
+        let provider = AdaptiveTokensProvider::new(LengthProvider::new(0), config(10));
+        let bucket = provider.bucket(&request("Summarize.", 2000));
+
+        let provider = provider.with_state(state("test-model", bucket.clone()));
+        // p95 of 1..=100 is 95; × 1.2 = 114
+        assert_eq!(provider.budget(&request("Summarize.", 2000)), Some(114));
+        // Other buckets and models stay cold
+        assert_eq!(provider.budget(&request("Translate.", 2000)), Some(2000));
+
+        let bounded = AdaptiveTokensProvider::new(
+            LengthProvider::new(0),
+            AdaptiveTokensConfig {
+                min_tokens: 200,
+                ..config(10)
+            },
+        )
+        .with_state(state("test-model", bucket.clone()));
+        assert_eq!(bounded.budget(&request("Summarize.", 2000)), Some(200));
+
+        let capped = AdaptiveTokensProvider::new(
+            LengthProvider::new(0),
+            AdaptiveTokensConfig {
+                max_tokens: 64,
+                ..config(10)
+            },
+        )
+        .with_state(state("test-model", bucket));
+        assert_eq!(capped.budget(&request("Summarize.", 2000)), Some(64));
+    }
+
+    #[tokio::test]
+    async fn test_length_finish_retries_with_doubled_budget() {
+        let provider = AdaptiveTokensProvider::new(
+            LengthProvider::new(150),
+            AdaptiveTokensConfig {
+                retry_on_length: true,
+                ..config(1)
+            },
+        )
+        .with_bucket_fn(Arc::new(|_| "all".to_string()));
+        let bucket = BucketState {
+            model: "test-model".to_string(),
+            bucket: "all".to_string(),
+            completion_tokens: vec![100],
+        };
+        let provider = provider.with_state(AdaptiveTokensState { buckets: vec![bucket] });
+
+        let response = provider.complete(&request("Summarize.", 2000)).await.unwrap();
+
+        assert_eq!(response.choices[0].finish_reason, FinishReason::Stop);
+        assert_eq!(provider.inner().seen(), vec![Some(120), Some(240)]);
+        // The retried completion is what gets recorded
+        assert_eq!(provider.state().buckets[0].completion_tokens, vec![100, 150]);
+    }
+
+    #[tokio::test]
+    async fn test_length_finish_without_retry() {
+        let provider = AdaptiveTokensProvider::new(LengthProvider::new(150), config(1))
+            .with_bucket_fn(Arc::new(|_| "all".to_string()));
+
+        let response = provider.complete(&request("Summarize.", 100)).await.unwrap();
+        assert_eq!(response.choices[0].finish_reason, FinishReason::Length);
+        assert_eq!(provider.inner().seen(), vec![Some(100)]);
+    }
+
+    #[tokio::test]
+    async fn test_state_round_trip_and_window() {
+        let provider = AdaptiveTokensProvider::new(
+            LengthProvider::new(42),
+            AdaptiveTokensConfig {
+                window: 2,
+                ..config(2)
+            },
+        );
+        for system in ["Summarize.", "Summarize.", "Summarize.", "Translate."] {
+            provider.complete(&request(system, 500)).await.unwrap();
+        }
+
+        let state = provider.state();
+        assert_eq!(state.buckets.len(), 2);
+        assert!(state.buckets.iter().all(|b| b.model == "test-model"));
+        let lengths: Vec<usize> = state.buckets.iter().map(|b| b.completion_tokens.len()).collect();
+        assert!(lengths.contains(&2) && lengths.contains(&1));
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored = AdaptiveTokensProvider::new(LengthProvider::new(42), config(2))
+            .with_state(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.state(), state);
+        assert_eq!(restored.budget(&request("Summarize.", 500)), Some(51));
+        assert_eq!(restored.budget(&request("Translate.", 500)), Some(500));
+    }
+}
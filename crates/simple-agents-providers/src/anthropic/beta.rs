@@ -0,0 +1,53 @@
+//! Anthropic beta feature flags.
+
+/// Beta feature enabled through the `anthropic-beta` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnthropicBeta {
+    /// Computer use tools (`computer-use-2024-10-22`)
+    ComputerUse2024,
+    /// 1M-token context window (`context-1m-2025-08-07`); see
+    /// [`AnthropicProvider::with_long_context`](super::AnthropicProvider::with_long_context)
+    Context1m,
+    /// Files API (`files-api-2025-04-14`)
+    FilesApi2025,
+    /// Message Batches API (`message-batches-2024-09-24`)
+    MessageBatches2024,
+    /// PDF document inputs (`pdfs-2024-09-25`)
+    PdfSupport2024,
+    /// Prompt caching (`prompt-caching-2024-07-31`)
+    PromptCaching2024,
+    /// Token counting API (`token-counting-2024-11-01`)
+    TokensApi,
+}
+
+impl AnthropicBeta {
+    /// Name of the request header carrying enabled betas
+    pub const HEADER: &'static str = "anthropic-beta";
+
+    /// Value sent in the `anthropic-beta` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ComputerUse2024 => "computer-use-2024-10-22",
+            Self::Context1m => "context-1m-2025-08-07",
+            Self::FilesApi2025 => "files-api-2025-04-14",
+            Self::MessageBatches2024 => "message-batches-2024-09-24",
+            Self::PdfSupport2024 => "pdfs-2024-09-25",
+            Self::PromptCaching2024 => "prompt-caching-2024-07-31",
+            Self::TokensApi => "token-counting-2024-11-01",
+        }
+    }
+
+    /// Comma-separated header value for `betas`, or `None` if empty.
+    pub fn header_value(betas: &[AnthropicBeta]) -> Option<String> {
+        if betas.is_empty() {
+            return None;
+        }
+        Some(betas.iter().map(|beta| beta.as_str()).collect::<Vec<_>>().join(","))
+    }
+}
+
+impl std::fmt::Display for AnthropicBeta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
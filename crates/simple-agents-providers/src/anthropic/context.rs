@@ -0,0 +1,63 @@
+//! Checking prompts against the context window before they are sent.
+
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+
+/// Function counting a request's prompt tokens
+pub type PromptTokenFn = Arc<dyn Fn(&CompletionRequest) -> u32 + Send + Sync>;
+
+/// How [`AnthropicProvider`](super::AnthropicProvider) sizes prompts to
+/// check them against its context limit.
+///
+/// Prompts over the limit fail with [`ValidationError::PromptTooLong`]
+/// before the completion is sent.
+#[derive(Clone, Default)]
+pub enum PromptTokenCounter {
+    /// Estimate from message lengths with
+    /// [`CompletionRequest::token_count_total`]; no request is sent
+    #[default]
+    Estimate,
+    /// Ask the `count_tokens` endpoint: exact, at the cost of one more
+    /// request per completion
+    CountTokens,
+    /// Count with a function, e.g. a local tokenizer
+    Custom(PromptTokenFn),
+    /// Send prompts unchecked
+    Off,
+}
+
+impl PromptTokenCounter {
+    /// Count with `count`.
+    pub fn custom(count: impl Fn(&CompletionRequest) -> u32 + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(count))
+    }
+}
+
+impl std::fmt::Debug for PromptTokenCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Estimate => "Estimate",
+            Self::CountTokens => "CountTokens",
+            Self::Custom(_) => "Custom",
+            Self::Off => "Off",
+        })
+    }
+}
+
+/// Fail with [`ValidationError::PromptTooLong`] if `estimated_tokens` is
+/// over `limit`.
+pub(crate) fn check_prompt_tokens(estimated_tokens: u32, limit: u32) -> Result<()> {
+    if estimated_tokens > limit {
+        return Err(ValidationError::PromptTooLong { estimated_tokens, limit }.into());
+    }
+    Ok(())
+}
+
+/// Body fields the `count_tokens` endpoint rejects
+pub(crate) const NOT_COUNTED: &[&str] = &["max_tokens", "stream", "temperature", "top_p", "stop_sequences"];
+
+/// Response of the `count_tokens` endpoint
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct TokenCount {
+    pub(crate) input_tokens: u32,
+}
@@ -1,13 +1,258 @@
 //! Anthropic-specific error handling.
-//!
-//! Implementation coming in Phase 2.
 
+use simple_agents_types::ProviderError;
+use std::time::Duration;
 use thiserror::Error;
 
+/// HTTP status Anthropic uses for `overloaded_error`
+pub const OVERLOADED_STATUS: u16 = 529;
+
 /// Anthropic-specific errors
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum AnthropicError {
-    /// Placeholder error
-    #[error("Not implemented")]
-    NotImplemented,
+    /// Error reported by the Anthropic API
+    #[error("Anthropic API error ({error_type}): {message}")]
+    Api {
+        /// HTTP status code (0 for errors delivered inside a stream)
+        status: u16,
+        /// Anthropic error type (e.g. "invalid_request_error")
+        error_type: String,
+        /// Error message
+        message: String,
+        /// Server-requested delay from the `retry-after` header
+        retry_after: Option<Duration>,
+    },
+}
+
+impl AnthropicError {
+    /// Parse Anthropic error from HTTP response
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - HTTP status code
+    /// * `body` - Response body text
+    pub fn from_response(status: u16, body: &str) -> Self {
+        match serde_json::from_str::<super::AnthropicErrorResponse>(body) {
+            Ok(error_response) => Self::from_error_details(status, &error_response.error),
+            Err(_) => Self::Api {
+                status,
+                error_type: "unknown".to_string(),
+                message: body.to_string(),
+                retry_after: None,
+            },
+        }
+    }
+
+    /// Build an error from the parsed `error` object of a response or stream event
+    pub fn from_error_details(status: u16, details: &super::AnthropicErrorDetails) -> Self {
+        Self::Api {
+            status,
+            error_type: details.error_type.clone(),
+            message: details.message.clone(),
+            retry_after: None,
+        }
+    }
+
+    /// Attach the delay parsed from a `retry-after` header
+    pub fn with_retry_after(self, delay: Option<Duration>) -> Self {
+        match self {
+            Self::Api {
+                status,
+                error_type,
+                message,
+                ..
+            } => Self::Api {
+                status,
+                error_type,
+                message,
+                retry_after: delay,
+            },
+        }
+    }
+
+    /// HTTP status for this error.
+    ///
+    /// Errors delivered inside a stream have no status of their own, so the
+    /// documented status for their error type is used instead.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Api { status, error_type, .. } => match (*status, error_type.as_str()) {
+                (0, "invalid_request_error") => 400,
+                (0, "authentication_error") => 401,
+                (0, "permission_error") => 403,
+                (0, "not_found_error") => 404,
+                (0, "request_too_large") => 413,
+                (0, "rate_limit_error") => 429,
+                (0, "api_error") => 500,
+                (0, "overloaded_error") => OVERLOADED_STATUS,
+                (status, _) => status,
+            },
+        }
+    }
+
+    /// Whether Anthropic reported itself as temporarily overloaded
+    pub fn is_overloaded(&self) -> bool {
+        let overloaded_type = match self {
+            Self::Api { error_type, .. } => error_type == "overloaded_error",
+        };
+        overloaded_type || self.status() == OVERLOADED_STATUS
+    }
+}
+
+/// Convert AnthropicError to ProviderError
+///
+/// The documented error `type` takes precedence over the HTTP status, so an
+/// `overloaded_error` is retryable whatever status carried it.
+impl From<AnthropicError> for ProviderError {
+    fn from(error: AnthropicError) -> Self {
+        let status = error.status();
+        match error {
+            AnthropicError::Api {
+                error_type,
+                message,
+                retry_after,
+                ..
+            } => match (error_type.as_str(), status) {
+                ("overloaded_error" | "api_error", _) => ProviderError::ServerError(message),
+                ("rate_limit_error", _) | (_, 429) => ProviderError::RateLimit { retry_after },
+                ("authentication_error", _) | (_, 401) => ProviderError::InvalidApiKey,
+                ("not_found_error", _) | (_, 404) => ProviderError::ModelNotFound(message),
+                ("request_too_large", _) | (_, 413) => ProviderError::PayloadTooLarge(message),
+                ("invalid_request_error" | "permission_error", _) => {
+                    ProviderError::BadRequest(message)
+                }
+                (_, 400..=499) => ProviderError::BadRequest(message),
+                (_, 500..=599) => ProviderError::ServerError(message),
+                _ => ProviderError::InvalidResponse(message),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(error_type: &str, message: &str) -> String {
+        format!(
+            r#"{{"type": "error", "error": {{"type": "{}", "message": "{}"}}}}"#,
+            error_type, message
+        )
+    }
+
+    #[test]
+    fn test_parse_json_error() {
+        let error = AnthropicError::from_response(401, &body("authentication_error", "invalid x-api-key"));
+        assert_eq!(
+            error,
+            AnthropicError::Api {
+                status: 401,
+                error_type: "authentication_error".to_string(),
+                message: "invalid x-api-key".to_string(),
+                retry_after: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_api_error_fixtures() {
+        // Bodies as sent by the API, including fields the parser ignores
+        let fixtures: &[(u16, &str, &str)] = &[
+            (400, "invalid_request_error", r#"{"type":"error","error":{"type":"invalid_request_error","message":"messages: field required"},"request_id":"req_011CSHoEeqs5C35K2UUqR7Fy"}"#),
+            (401, "authentication_error", r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"},"request_id":"req_011CSHoEeqs5C35K2UUqR7Fz"}"#),
+            (403, "permission_error", r#"{"type":"error","error":{"type":"permission_error","message":"Your API key does not have permission to use the specified resource."}}"#),
+            (404, "not_found_error", r#"{"type":"error","error":{"type":"not_found_error","message":"model: claude-3-opus-2099"}}"#),
+            (429, "rate_limit_error", r#"{"type":"error","error":{"type":"rate_limit_error","message":"This request would exceed the rate limit for your organization."}}"#),
+            (500, "api_error", r#"{"type":"error","error":{"type":"api_error","message":"Internal server error"}}"#),
+            (529, "overloaded_error", r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#),
+        ];
+
+        for (status, expected_type, fixture) in fixtures {
+            let expected_message = serde_json::from_str::<serde_json::Value>(fixture).unwrap()["error"]
+                ["message"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            match AnthropicError::from_response(*status, fixture) {
+                AnthropicError::Api {
+                    status: parsed_status,
+                    error_type,
+                    message,
+                    retry_after,
+                } => {
+                    assert_eq!(parsed_status, *status);
+                    assert_eq!(error_type, *expected_type);
+                    assert_eq!(message, expected_message);
+                    assert_eq!(retry_after, None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_classification_table() {
+        type Check = fn(&ProviderError) -> bool;
+        let cases: &[(u16, &str, &str, Check, bool)] = &[
+            (400, "invalid_request_error", "max_tokens: Field required",
+                |e| matches!(e, ProviderError::BadRequest(m) if m == "max_tokens: Field required"), false),
+            (401, "authentication_error", "invalid x-api-key",
+                |e| matches!(e, ProviderError::InvalidApiKey), false),
+            (403, "permission_error", "Your API key does not have permission to use the specified resource.",
+                |e| matches!(e, ProviderError::BadRequest(_)), false),
+            (404, "not_found_error", "model: claude-9",
+                |e| matches!(e, ProviderError::ModelNotFound(m) if m == "model: claude-9"), false),
+            (413, "request_too_large", "Request exceeds the maximum allowed number of bytes.",
+                |e| matches!(e, ProviderError::PayloadTooLarge(_)), false),
+            (429, "rate_limit_error", "Number of request tokens has exceeded your per-minute rate limit",
+                |e| matches!(e, ProviderError::RateLimit { retry_after: None }), true),
+            (500, "api_error", "Internal server error",
+                |e| matches!(e, ProviderError::ServerError(_)), true),
+            (529, "overloaded_error", "Overloaded",
+                |e| matches!(e, ProviderError::ServerError(m) if m == "Overloaded"), true),
+            // Stream errors carry no status; the type decides
+            (0, "overloaded_error", "Overloaded",
+                |e| matches!(e, ProviderError::ServerError(_)), true),
+            (0, "rate_limit_error", "slow down",
+                |e| matches!(e, ProviderError::RateLimit { .. }), true),
+            // Unknown types fall back to the status
+            (529, "brand_new_error", "Overloaded",
+                |e| matches!(e, ProviderError::ServerError(_)), true),
+            (418, "brand_new_error", "teapot",
+                |e| matches!(e, ProviderError::BadRequest(_)), false),
+        ];
+
+        for (status, error_type, message, expected, retryable) in cases {
+            let error = AnthropicError::from_response(*status, &body(error_type, message));
+            let provider_error = ProviderError::from(error);
+            assert!(expected(&provider_error), "{} {}: got {:?}", status, error_type, provider_error);
+            assert_eq!(provider_error.is_retryable(), *retryable, "{} {}", status, error_type);
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_retry_after() {
+        let error = AnthropicError::from_response(429, &body("rate_limit_error", "rate limited"))
+            .with_retry_after(Some(Duration::from_secs(20)));
+
+        assert!(matches!(
+            ProviderError::from(error),
+            ProviderError::RateLimit { retry_after: Some(d) } if d == Duration::from_secs(20)
+        ));
+    }
+
+    #[test]
+    fn test_is_overloaded() {
+        assert!(AnthropicError::from_response(529, "Overloaded").is_overloaded());
+        assert!(AnthropicError::from_response(0, &body("overloaded_error", "Overloaded")).is_overloaded());
+        assert!(!AnthropicError::from_response(500, &body("api_error", "boom")).is_overloaded());
+    }
+
+    #[test]
+    fn test_parse_plain_text_error() {
+        let error = AnthropicError::from_response(502, "Bad Gateway");
+        assert!(matches!(
+            ProviderError::from(error),
+            ProviderError::ServerError(msg) if msg == "Bad Gateway"
+        ));
+    }
 }
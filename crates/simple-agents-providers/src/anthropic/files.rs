@@ -0,0 +1,184 @@
+//! Anthropic Files API uploads.
+
+use super::{AnthropicBeta, AnthropicFile, AnthropicProvider, RequestBody};
+use simple_agents_types::prelude::*;
+use std::borrow::Cow;
+
+impl AnthropicProvider {
+    /// Largest file the Files API accepts, in bytes
+    pub const MAX_FILE_BYTES: usize = 500 * 1024 * 1024;
+
+    /// Upload a file through the Files API and return its id.
+    ///
+    /// Reference the file in later messages with [`Document::file`];
+    /// requests that do are sent with the Files API beta header. Files over
+    /// [`MAX_FILE_BYTES`](Self::MAX_FILE_BYTES) are rejected with
+    /// [`ProviderError::PayloadTooLarge`] before anything is sent.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use simple_agents_providers::anthropic::AnthropicProvider;
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let provider = AnthropicProvider::new(ApiKey::new("sk-ant-...")?)?;
+    /// let pdf = std::fs::read("report.pdf").unwrap();
+    /// let file_id = provider.upload_file(pdf, "report.pdf").await?;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("claude-3-5-sonnet-20241022")
+    ///     .message(Message::user("Summarize this report").with_document(Document::file(file_id)))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_file(&self, bytes: impl Into<Vec<u8>>, filename: &str) -> Result<String> {
+        let bytes = bytes.into();
+        if bytes.len() > Self::MAX_FILE_BYTES {
+            return Err(ProviderError::PayloadTooLarge(format!(
+                "{} is {} bytes; the Files API accepts up to {} bytes",
+                filename,
+                bytes.len(),
+                Self::MAX_FILE_BYTES
+            ))
+            .into());
+        }
+        if self.debug_requests {
+            tracing::debug!(filename, size = bytes.len(), "Uploading file");
+        }
+
+        let boundary = format!("simple-agents-{}", &blake3::hash(&bytes).to_hex()[..32]);
+        let body = multipart_file(&boundary, filename, &bytes);
+        let mut headers = self.headers_with_betas(&[AnthropicBeta::FilesApi2025]);
+        let content_type = simple_agents_types::provider::headers::CONTENT_TYPE;
+        headers.retain(|(name, _)| !name.eq_ignore_ascii_case(content_type));
+        headers.push((
+            Cow::Borrowed(content_type),
+            Cow::Owned(format!("multipart/form-data; boundary={}", boundary)),
+        ));
+        let headers = crate::utils::build_headers(headers)?;
+
+        let url = format!("{}/files", self.base_url);
+        let response = self
+            .send_body(
+                reqwest::Method::POST,
+                &url,
+                headers,
+                RequestBody::Bytes(body.into()),
+                &self.retry_config,
+            )
+            .await?;
+        let file: AnthropicFile = response.json().await.map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to parse file upload response: {}",
+                e
+            )))
+        })?;
+        Ok(file.id)
+    }
+}
+
+/// `multipart/form-data` body with `bytes` as the `file` field.
+fn multipart_file(boundary: &str, filename: &str, bytes: &[u8]) -> Vec<u8> {
+    // Quotes and line breaks would end the header early
+    let filename: String = filename
+        .chars()
+        .map(|c| if matches!(c, '"' | '\r' | '\n') { '_' } else { c })
+        .collect();
+    let head = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+         Content-Type: {}\r\n\r\n",
+        media_type(&filename),
+    );
+    let tail = format!("\r\n--{boundary}--\r\n");
+
+    let mut body = Vec::with_capacity(head.len() + bytes.len() + tail.len());
+    body.extend_from_slice(head.as_bytes());
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(tail.as_bytes());
+    body
+}
+
+/// MIME type for an uploaded file, from its extension.
+fn media_type(filename: &str) -> &'static str {
+    let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("pdf") => Document::PDF_MEDIA_TYPE,
+        Some("txt") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("csv") => "text/csv",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(base_url: String) -> AnthropicProvider {
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        AnthropicProvider::with_base_url(api_key, base_url).unwrap()
+    }
+
+    #[test]
+    fn test_multipart_file() {
+        let body = multipart_file("b0undary", "q3 \"final\".pdf", b"%PDF-1.7");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--b0undary\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"q3 _final_.pdf\"\r\n\
+             Content-Type: application/pdf\r\n\r\n\
+             %PDF-1.7\r\n--b0undary--\r\n"
+        );
+        assert_eq!(media_type("notes.TXT"), "text/plain");
+        assert_eq!(media_type("archive"), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_upload_file() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/files")
+            .match_header(AnthropicBeta::HEADER, "files-api-2025-04-14")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data; boundary=simple-agents-".to_string()),
+            )
+            .match_body(mockito::Matcher::Regex("filename=\"report.pdf\"".to_string()))
+            .with_status(200)
+            .with_body(
+                r#"{"id": "file_011CNha8iCJcU1wXNR6q4V8w", "type": "file", "filename": "report.pdf",
+                    "mime_type": "application/pdf", "size_bytes": 8, "created_at": "2025-04-14T00:00:00Z"}"#,
+            )
+            .create_async()
+            .await;
+
+        let file_id = provider(server.url()).upload_file(b"%PDF-1.7".to_vec(), "report.pdf").await.unwrap();
+        assert_eq!(file_id, "file_011CNha8iCJcU1wXNR6q4V8w");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_too_large() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/files")
+            .with_status(413)
+            .with_body(
+                r#"{"type": "error", "error": {"type": "request_too_large", "message": "File too large"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let result = provider(server.url()).upload_file(vec![0u8; 16], "big.pdf").await;
+        assert!(matches!(
+            result.map_err(SimpleAgentsError::into_root),
+            Err(SimpleAgentsError::Provider(ProviderError::PayloadTooLarge(msg))) if msg == "File too large"
+        ));
+        mock.assert_async().await;
+    }
+}
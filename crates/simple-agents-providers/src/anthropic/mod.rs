@@ -1,13 +1,1724 @@
 //! Anthropic provider implementation.
 //!
-//! This module provides integration with the Anthropic API (Claude models).
-//! Implementation coming in Phase 2.
-
-#![allow(dead_code)]
+//! This module provides integration with the Anthropic API (Claude models), supporting:
+//! - Claude 3, 3.5 and 3.7 models via the Messages API
+//! - Streaming responses with fully typed SSE events
+//! - PDF documents, inline or uploaded through the Files API, with
+//!   optional citations
+//! - Prompts checked against the context window (200k tokens, or 1M with
+//!   the long-context beta) before they are sent
+//! - Structured error handling
 
+mod beta;
+mod context;
 mod models;
 mod error;
+mod files;
+mod streaming;
+
+pub use beta::AnthropicBeta;
+pub use context::{PromptTokenCounter, PromptTokenFn};
+pub use models::*;
+pub use error::{AnthropicError, OVERLOADED_STATUS};
+pub use streaming::*;
+pub use crate::utils::auth::AuthScheme;
+
+use crate::provider_kit::{self, RequestBuilder, SerializationOptions};
+use crate::retry::ClassifyFn;
+use crate::tls::TlsConfig;
+use crate::utils::auth;
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::Client;
+use simple_agents_types::prelude::*;
+use simple_agents_types::provider::RETRY_ATTEMPT;
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Anthropic API provider
+#[derive(Clone)]
+pub struct AnthropicProvider {
+    credentials: Arc<dyn CredentialSource>,
+    base_url: String,
+    client: Client,
+    retry_config: RetryConfig,
+    classifier: Option<ClassifyFn>,
+    betas: Vec<AnthropicBeta>,
+    auth_scheme: AuthScheme,
+    default_headers: Vec<(String, String)>,
+    sensitive_headers: Vec<String>,
+    debug_requests: bool,
+    citations: bool,
+    serialization: SerializationOptions,
+    model_catalog: Option<Arc<ModelCatalog>>,
+    prompt_token_counter: PromptTokenCounter,
+}
+
+impl std::fmt::Debug for AnthropicProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnthropicProvider")
+            .field("base_url", &self.base_url)
+            .field("retry_config", &self.retry_config)
+            .field("custom_classifier", &self.classifier.is_some())
+            .field("betas", &self.betas)
+            .field("auth_scheme", &self.auth_scheme)
+            .field("default_headers", &self.redacted_default_headers())
+            .field("debug_requests", &self.debug_requests)
+            .field("citations", &self.citations)
+            .field("serialization", &self.serialization)
+            .field("custom_model_catalog", &self.model_catalog.is_some())
+            .field("prompt_token_counter", &self.prompt_token_counter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnthropicProvider {
+    /// Default Anthropic API base URL
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.anthropic.com/v1";
+
+    /// Anthropic API version sent with every request
+    pub const API_VERSION: &'static str = "2023-06-01";
+
+    /// `max_tokens` used when the request does not set one (Anthropic requires it)
+    pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+    /// Largest request body the Messages API accepts, in bytes
+    pub const MAX_REQUEST_BYTES: usize = 32 * 1024 * 1024;
+
+    /// Prompt tokens accepted without the long-context beta
+    pub const CONTEXT_TOKENS: u32 = 200_000;
+
+    /// Prompt tokens accepted with the long-context beta
+    pub const LONG_CONTEXT_TOKENS: u32 = 1_000_000;
+
+    /// Claude models served by the Anthropic Messages API
+    pub const SUPPORTED_MODELS: &'static [&'static str] = &[
+        "claude-3-7-sonnet-20250219",
+        "claude-3-7-sonnet-latest",
+        "claude-3-5-sonnet-20241022",
+        "claude-3-5-sonnet-20240620",
+        "claude-3-5-sonnet-latest",
+        "claude-3-5-haiku-20241022",
+        "claude-3-5-haiku-latest",
+        "claude-3-opus-20240229",
+        "claude-3-opus-latest",
+        "claude-3-sonnet-20240229",
+        "claude-3-haiku-20240307",
+    ];
+
+    /// Create a new Anthropic provider with default configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - Anthropic API key (starts with "sk-ant-"), or a
+    ///   [`CredentialSource`] such as
+    ///   [`RefreshingCredentials`](crate::credentials::RefreshingCredentials)
+    ///   for keys that rotate
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, Self::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a new Anthropic provider with custom base URL
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - Anthropic API key or [`CredentialSource`]
+    /// * `base_url` - Custom base URL (e.g., for a proxy); trailing slashes
+    ///   and a repeated version segment (`/v1/v1`) are removed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `base_url` is not an http(s)
+    /// URL or has a query string, or if the HTTP client cannot be created.
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        let client = crate::utils::build_client(Self::client_builder())?;
+
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        Ok(Self {
+            credentials: Arc::new(credentials),
+            base_url,
+            client,
+            retry_config: RetryConfig::default(),
+            classifier: None,
+            betas: Vec::new(),
+            auth_scheme: AuthScheme::default(),
+            default_headers: Vec::new(),
+            sensitive_headers: Vec::new(),
+            debug_requests: false,
+            citations: false,
+            serialization: SerializationOptions::default(),
+            model_catalog: None,
+            prompt_token_counter: PromptTokenCounter::default(),
+        })
+    }
+
+    /// Set the retry configuration
+    ///
+    /// Overloaded (529), rate-limited (429) and server errors are retried
+    /// with jittered exponential backoff; a `retry-after` header on a 429
+    /// extends the wait. These retries happen as each request is sent.
+    /// Under [`Provider::execute_with_retries`] each request is sent once
+    /// instead and the caller's policy decides the retries, so no request
+    /// is retried twice over.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Override how errors are classified for retries
+    ///
+    /// Defaults to [`crate::retry::classify`].
+    pub fn with_classifier(mut self, classifier: ClassifyFn) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
+
+    /// Enable a beta feature via the `anthropic-beta` header
+    pub fn with_beta(mut self, beta: AnthropicBeta) -> Self {
+        if !self.betas.contains(&beta) {
+            self.betas.push(beta);
+        }
+        self
+    }
+
+    /// Accept prompts of up to 1M tokens through the
+    /// [`AnthropicBeta::Context1m`] beta
+    ///
+    /// Off by default, when prompts over [`CONTEXT_TOKENS`](Self::CONTEXT_TOKENS)
+    /// are rejected before sending; on, the limit is
+    /// [`LONG_CONTEXT_TOKENS`](Self::LONG_CONTEXT_TOKENS). Long prompts are
+    /// billed at a higher rate and only some models accept the beta.
+    pub fn with_long_context(mut self, enabled: bool) -> Self {
+        self.betas.retain(|beta| *beta != AnthropicBeta::Context1m);
+        if enabled {
+            self.betas.push(AnthropicBeta::Context1m);
+        }
+        self
+    }
+
+    /// Prompt tokens accepted: [`LONG_CONTEXT_TOKENS`](Self::LONG_CONTEXT_TOKENS)
+    /// with the long-context beta, else [`CONTEXT_TOKENS`](Self::CONTEXT_TOKENS)
+    pub fn context_limit(&self) -> u32 {
+        if self.betas.contains(&AnthropicBeta::Context1m) {
+            Self::LONG_CONTEXT_TOKENS
+        } else {
+            Self::CONTEXT_TOKENS
+        }
+    }
+
+    /// Set how prompts are sized for the context check
+    ///
+    /// Defaults to [`PromptTokenCounter::Estimate`], a local estimate.
+    pub fn with_prompt_token_counter(mut self, counter: PromptTokenCounter) -> Self {
+        self.prompt_token_counter = counter;
+        self
+    }
+
+    /// Count the prompt tokens of `req` with the `count_tokens` endpoint
+    ///
+    /// The request is built as for a completion, so system prompts and
+    /// documents are counted too.
+    pub async fn count_tokens(&self, req: &CompletionRequest) -> Result<u32> {
+        self.count_request_tokens(&self.build_provider_request(req)?).await
+    }
+
+    /// Ask Claude to cite the documents sent with requests
+    ///
+    /// Document blocks are sent with `citations: {"enabled": true}`, and
+    /// the cited passages come back as [`Annotation`]s on the response
+    /// choice, each covering the span of the content it supports.
+    pub fn with_citations(mut self, enabled: bool) -> Self {
+        self.citations = enabled;
+        self
+    }
+
+    /// Post-process request bodies for a nonconforming gateway
+    ///
+    /// See [`SerializationOptions`]; by default bodies are sent as
+    /// serialized, without `null` fields for unset parameters.
+    pub fn with_serialization(mut self, options: SerializationOptions) -> Self {
+        self.serialization = options;
+        self
+    }
+
+    /// Look models up in `catalog` instead of [`ModelCatalog::builtin`]
+    ///
+    /// Used for [`Provider::model_info`], and so for pricing and feature
+    /// checks; e.g. a builtin catalog extended with
+    /// [`ModelCatalog::with_overrides_json`].
+    pub fn with_model_catalog(mut self, catalog: Arc<ModelCatalog>) -> Self {
+        self.model_catalog = Some(catalog);
+        self
+    }
+
+    /// Enable several beta features
+    ///
+    /// They are sent as one comma-separated `anthropic-beta` header, in
+    /// the order first enabled.
+    pub fn with_betas(self, betas: Vec<AnthropicBeta>) -> Self {
+        betas.into_iter().fold(self, Self::with_beta)
+    }
+
+    /// Beta features enabled for this provider
+    pub fn betas(&self) -> &[AnthropicBeta] {
+        &self.betas
+    }
+
+    /// Set how the API key is sent
+    ///
+    /// Defaults to [`AuthScheme::XApiKey`]. Gateways in front of the API
+    /// often expect [`AuthScheme::Bearer`] instead.
+    pub fn with_auth_scheme(mut self, auth_scheme: AuthScheme) -> Self {
+        self.auth_scheme = auth_scheme;
+        self
+    }
+
+    /// How the API key is sent
+    pub fn auth_scheme(&self) -> AuthScheme {
+        self.auth_scheme
+    }
+
+    /// Send an extra header with every request (e.g. a gateway tenant ID)
+    ///
+    /// Default headers replace built-in headers of the same name; the API
+    /// key header is always set from the credentials. Adding a name more
+    /// than once sends one header per value (e.g. several `Forwarded`
+    /// headers). Names are sent lowercase; see [`provider_kit::header_map`].
+    pub fn with_default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Extra headers sent with every request
+    pub fn default_headers(&self) -> &[(String, String)] {
+        &self.default_headers
+    }
+
+    /// Treat a header as secret: its value is redacted from dry runs,
+    /// request logs and `Debug` output
+    ///
+    /// The API key headers are always redacted.
+    pub fn with_sensitive_header(mut self, name: impl Into<String>) -> Self {
+        self.sensitive_headers.push(name.into());
+        self
+    }
+
+    /// Connect with custom TLS settings, e.g. an internal root CA or a
+    /// client certificate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `tls` is invalid or not
+    /// supported by the enabled TLS backend; see [`TlsConfig::apply`].
+    pub fn with_tls(mut self, tls: &TlsConfig) -> Result<Self> {
+        self.client = crate::utils::build_client(tls.apply(Self::client_builder())?)?;
+        Ok(self)
+    }
+
+    /// HTTP client settings used by [`with_base_url`](Self::with_base_url).
+    fn client_builder() -> reqwest::ClientBuilder {
+        Client::builder()
+            .timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(10)
+            .pool_idle_timeout(Duration::from_secs(90))
+    }
+
+    /// Log every request at debug level before it is sent
+    ///
+    /// The URL, headers and pretty-printed body are logged, with sensitive
+    /// headers redacted (see [`Provider::dry_run`] to inspect a request
+    /// without sending it).
+    pub fn with_debug_requests(mut self, enabled: bool) -> Self {
+        self.debug_requests = enabled;
+        self
+    }
+
+    fn redacted_default_headers(&self) -> Vec<(&str, &str)> {
+        self.default_headers
+            .iter()
+            .map(|(name, value)| {
+                let sensitive = self.sensitive_headers.iter().any(|s| s.eq_ignore_ascii_case(name));
+                (name.as_str(), if sensitive { simple_agents_types::provider::REDACTED } else { value.as_str() })
+            })
+            .collect()
+    }
+
+    /// Get the base URL for this provider
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Build the Anthropic request body for a unified request.
+    fn build_request<'a>(&self, req: &'a CompletionRequest) -> AnthropicCompletionRequest<'a> {
+        // Anthropic takes system prompts outside the conversation
+        let system_messages: Vec<&Message> = req.messages.iter()
+            .filter(|m| m.role == Role::System)
+            .collect();
+        let system = if system_messages.is_empty() {
+            None
+        } else if system_messages.iter().any(|m| m.cache_control.is_some()) {
+            // Cache breakpoints need one block per system message
+            Some(AnthropicSystem::Blocks(system_messages.iter().map(|m| text_block(m)).collect()))
+        } else {
+            let parts: Vec<&str> = system_messages.iter().map(|m| m.content.as_str()).collect();
+            Some(AnthropicSystem::Text(parts.join("\n\n")))
+        };
+
+        let mut messages: Vec<AnthropicMessage<'a>> = req.messages.iter()
+            .filter(|m| m.role != Role::System)
+            .map(|m| AnthropicMessage {
+                role: match m.role {
+                    Role::Assistant => "assistant",
+                    _ => "user",
+                },
+                content: if m.documents.is_empty() && m.cache_control.is_none() {
+                    AnthropicContent::Text(&m.content)
+                } else {
+                    AnthropicContent::Blocks(content_blocks(m, self.citations))
+                },
+            })
+            .collect();
+
+        // A trailing assistant turn is continued by the model. Anthropic
+        // rejects final assistant content ending in whitespace.
+        if let Some(prefill) = &req.assistant_prefill {
+            messages.push(AnthropicMessage {
+                role: "assistant",
+                content: AnthropicContent::Text(prefill.text.trim_end()),
+            });
+        }
+
+        AnthropicCompletionRequest {
+            model: &req.model,
+            messages,
+            system,
+            max_tokens: req.max_tokens.unwrap_or(Self::DEFAULT_MAX_TOKENS),
+            temperature: req.temperature,
+            top_p: req.top_p,
+            stop_sequences: req.stop.as_ref(),
+            // Anthropic has no stream options; usage always arrives in
+            // `message_start` and `message_delta` events
+            stream: req.is_streaming().then_some(true),
+        }
+    }
+
+    /// The Messages API request for `req`, without the context check.
+    fn build_provider_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let betas = document_betas(req)?;
+
+        // `stream_events` enables streaming even if the request did not ask for it
+        let anthropic_request = self.build_request(req);
+        let mut body = serde_json::to_value(&anthropic_request)?;
+        self.serialization.apply(&mut body);
+
+        Ok(RequestBuilder::new(format!("{}/messages", self.base_url))
+            .headers(self.headers_with_betas(&betas))
+            .body(body)
+            .extensions(req.extensions.clone())
+            .build())
+    }
+
+    /// Count the prompt tokens of a Messages API request with the
+    /// `count_tokens` endpoint, sending the same headers.
+    async fn count_request_tokens(&self, req: &ProviderRequest) -> Result<u32> {
+        let mut body = req.body.clone();
+        if let Some(fields) = body.as_object_mut() {
+            for field in context::NOT_COUNTED {
+                fields.remove(*field);
+            }
+        }
+        let url = format!("{}/messages/count_tokens", self.base_url);
+        let mut request = ProviderRequest::new(url).with_body(body);
+        request.headers = req.headers.clone();
+        let response = self.send(request).await?;
+        let count: context::TokenCount =
+            provider_kit::typed_response(response, provider_kit::DEFAULT_RAW_BODY_LIMIT).await?;
+        Ok(count.input_tokens)
+    }
+
+    /// With [`PromptTokenCounter::CountTokens`], count the request's
+    /// prompt tokens and check them against the context limit.
+    async fn check_counted_tokens(&self, req: &ProviderRequest) -> Result<()> {
+        if let PromptTokenCounter::CountTokens = self.prompt_token_counter {
+            let counted = self.count_request_tokens(req).await?;
+            context::check_prompt_tokens(counted, self.context_limit())?;
+        }
+        Ok(())
+    }
+
+    /// Send a request to an endpoint not modeled by this crate.
+    ///
+    /// `path` is appended to the base URL (e.g. `"/models"`) and must stay
+    /// under it; absolute URLs and `..` segments are rejected. The request
+    /// carries the same auth, version, beta and default headers as
+    /// completions, and is retried with the same error classification. A
+    /// `null` body is not sent.
+    ///
+    /// The response keeps its headers; non-JSON bodies are returned as a
+    /// JSON string.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use simple_agents_providers::anthropic::{AnthropicBeta, AnthropicProvider};
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// # async fn example() -> Result<()> {
+    /// let provider = AnthropicProvider::new(ApiKey::new("sk-ant-...")?)?
+    ///     .with_beta(AnthropicBeta::TokensApi);
+    /// let count = provider
+    ///     .raw(
+    ///         reqwest::Method::POST,
+    ///         "/messages/count_tokens",
+    ///         serde_json::json!({
+    ///             "model": "claude-3-5-sonnet-20241022",
+    ///             "messages": [{"role": "user", "content": "Hello"}]
+    ///         }),
+    ///     )
+    ///     .await?;
+    /// println!("{}", count.body["input_tokens"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<ProviderResponse> {
+        let request = RequestBuilder::join(&self.base_url, path)?
+            .headers(self.standard_headers())
+            .body(body)
+            .build();
+        let response = self.send_with_method(method, request).await?;
+        crate::utils::raw_response(response).await
+    }
+
+    /// Headers sent with every request: auth (when cached), version,
+    /// content type, betas and default headers.
+    fn standard_headers(&self) -> Vec<(Cow<'static, str>, Cow<'static, str>)> {
+        self.headers_with_betas(&[])
+    }
+
+    /// Standard headers with `extra` betas enabled on top of the configured ones.
+    fn headers_with_betas(&self, extra: &[AnthropicBeta]) -> Vec<(Cow<'static, str>, Cow<'static, str>)> {
+        let mut headers = vec![
+            (
+                Cow::Borrowed("anthropic-version"),
+                Cow::Borrowed(Self::API_VERSION)
+            ),
+            (
+                Cow::Borrowed(simple_agents_types::provider::headers::CONTENT_TYPE),
+                Cow::Borrowed("application/json")
+            ),
+        ];
+        // The key is fetched again when sending; include it here when it is
+        // already known so the request is complete on its own
+        if let Some(key) = self.credentials.cached_key() {
+            headers.insert(0, auth::header_pair(self.auth_scheme, &key));
+        }
+        let mut betas = self.betas.clone();
+        for beta in extra {
+            if !betas.contains(beta) {
+                betas.push(*beta);
+            }
+        }
+        if let Some(betas) = AnthropicBeta::header_value(&betas) {
+            headers.push((Cow::Borrowed(AnthropicBeta::HEADER), Cow::Owned(betas)));
+        }
+        // Default headers replace built-in ones of the same name
+        headers.retain(|(name, _)| {
+            !self.default_headers.iter().any(|(default, _)| default.eq_ignore_ascii_case(name))
+        });
+        headers.extend(
+            self.default_headers
+                .iter()
+                .map(|(name, value)| (Cow::Owned(name.clone()), Cow::Owned(value.clone()))),
+        );
+        headers
+    }
+
+    /// Send a request, retrying retryable failures per the retry configuration.
+    async fn send(&self, req: ProviderRequest) -> Result<reqwest::Response> {
+        self.send_with_method(reqwest::Method::POST, req).await
+    }
+
+    /// Send a request with `method`, retrying retryable failures per the
+    /// retry configuration.
+    ///
+    /// The auth header is set from the current credentials; a rejected key
+    /// is refreshed and the request retried once. A request made by
+    /// [`Provider::execute_with_attempts`] (tagged with [`RETRY_ATTEMPT`])
+    /// is sent once, since the caller retries it.
+    async fn send_with_method(&self, method: reqwest::Method, req: ProviderRequest) -> Result<reqwest::Response> {
+        if self.debug_requests {
+            crate::utils::log_request(&method, &req, &self.sensitive_headers);
+        }
+
+        let retry_config = if req.extensions.contains_key(RETRY_ATTEMPT) {
+            RetryConfig {
+                max_attempts: 1,
+                ..self.retry_config.clone()
+            }
+        } else {
+            self.retry_config.clone()
+        };
+        let headers = provider_kit::header_map(req.headers)?;
+        self.send_body(method, &req.url, headers, RequestBody::Json(&req.body), &retry_config).await
+    }
+
+    /// Send `body` with prepared headers, refreshing a rejected key and
+    /// retrying retryable failures per `retry_config`.
+    async fn send_body(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        headers: reqwest::header::HeaderMap,
+        body: RequestBody<'_>,
+        retry_config: &RetryConfig,
+    ) -> Result<reqwest::Response> {
+        let classifier: &(dyn Fn(&SimpleAgentsError) -> crate::retry::ErrorClass + Send + Sync) = match &self.classifier {
+            Some(classifier) => classifier.as_ref(),
+            None => &crate::retry::classify,
+        };
+
+        let (method, body) = (&method, &body);
+        crate::credentials::send_with_refresh(self.credentials.as_ref(), |key| {
+            let mut headers = headers.clone();
+            let auth = auth::insert(&mut headers, self.auth_scheme, &key);
+            async move {
+                auth?;
+                crate::retry::execute_with_classifier(
+                    retry_config,
+                    classifier,
+                    || self.send_once(method.clone(), url, headers.clone(), body),
+                )
+                .await
+            }
+        })
+        .await
+    }
+
+    /// Send a request once and return the raw response, mapping API errors.
+    async fn send_once(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        headers: reqwest::header::HeaderMap,
+        body: &RequestBody<'_>,
+    ) -> Result<reqwest::Response> {
+        let mut request = self.client.request(method, url).headers(headers);
+        match body {
+            RequestBody::Json(body) if !body.is_null() => request = request.json(body),
+            RequestBody::Json(_) => {}
+            RequestBody::Bytes(bytes) => request = request.body(bytes.clone()),
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| provider_kit::map_transport_error(e, crate::utils::DEFAULT_TIMEOUT))?;
+        provider_kit::error_for_status_with(response, |status, body, retry_after| {
+            AnthropicError::from_response(status, body)
+                .with_retry_after(retry_after)
+                .into()
+        })
+        .await
+    }
+
+    /// Stream typed Anthropic events for a request built by `transform_request`.
+    ///
+    /// Unlike [`Provider::execute_stream`], this exposes every event type,
+    /// including token counts from `message_start` and `message_delta`.
+    pub async fn stream_events(
+        &self,
+        mut req: ProviderRequest,
+    ) -> Result<impl futures::Stream<Item = Result<AnthropicStreamEvent>> + Send + Unpin> {
+        self.check_counted_tokens(&req).await?;
+        req.body["stream"] = serde_json::Value::Bool(true);
+        let response = self.send(req).await?;
+
+        let events = crate::streaming::sse_events(response.bytes_stream())
+            .filter_map(|event| {
+                let parsed = event.and_then(|event| AnthropicStreamEvent::from_sse(&event));
+                futures::future::ready(parsed.transpose())
+            });
+
+        Ok(Box::pin(events))
+    }
+}
+
+/// Body of an outgoing request.
+enum RequestBody<'a> {
+    /// JSON body; `null` is not sent
+    Json(&'a serde_json::Value),
+    /// Pre-encoded body, e.g. multipart form data
+    Bytes(bytes::Bytes),
+}
+
+/// Content blocks for a message: its documents, then its text.
+///
+/// Anthropic recommends placing documents before the text that refers to
+/// them. The cache breakpoint goes on the last block.
+fn content_blocks(message: &Message, citations: bool) -> Vec<AnthropicRequestBlock<'_>> {
+    let citations = citations.then_some(AnthropicCitationsConfig { enabled: true });
+    let mut blocks: Vec<AnthropicRequestBlock<'_>> = message
+        .documents
+        .iter()
+        .map(|source| AnthropicRequestBlock::Document { source, cache_control: None, citations })
+        .collect();
+    if !message.content.is_empty() || blocks.is_empty() {
+        blocks.push(text_block(message));
+    } else if let Some(AnthropicRequestBlock::Document { cache_control, .. }) = blocks.last_mut() {
+        *cache_control = message.cache_control;
+    }
+    blocks
+}
+
+/// Beta headers needed for the documents in `req`.
+///
+/// Inline documents count toward the request size limit, so they are
+/// checked here rather than rejected by the API after a slow upload.
+fn document_betas(req: &CompletionRequest) -> Result<Vec<AnthropicBeta>> {
+    let documents: Vec<&Document> = req.messages.iter().flat_map(|m| &m.documents).collect();
+    if documents.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let inline_bytes: usize = documents
+        .iter()
+        .map(|document| match document {
+            Document::Base64 { data, .. } => data.len(),
+            Document::File { .. } => 0,
+        })
+        .sum();
+    if inline_bytes > AnthropicProvider::MAX_REQUEST_BYTES {
+        return Err(ProviderError::PayloadTooLarge(format!(
+            "inline documents are {} bytes encoded; requests are limited to {} bytes, \
+             upload larger files with upload_file",
+            inline_bytes,
+            AnthropicProvider::MAX_REQUEST_BYTES
+        ))
+        .into());
+    }
+
+    let mut betas = vec![AnthropicBeta::PdfSupport2024];
+    if documents.iter().any(|document| matches!(document, Document::File { .. })) {
+        betas.push(AnthropicBeta::FilesApi2025);
+    }
+    Ok(betas)
+}
+
+/// The first feature `req` uses that the Messages API request built here
+/// cannot express, so it is rejected rather than silently dropped.
+///
+/// Images are not sent; tools, tool calls and tool results are not mapped
+/// to `tool_use`/`tool_result` blocks; Anthropic has no response format.
+fn unsupported_feature(req: &CompletionRequest) -> Option<Feature> {
+    if req.messages.iter().any(|m| !m.images.is_empty()) {
+        return Some(Feature::Vision);
+    }
+    let tool_messages = req
+        .messages
+        .iter()
+        .any(|m| m.role == Role::Tool || m.tool_call_id.is_some() || !m.tool_calls.is_empty());
+    if req.tools.is_some() || req.tool_choice.is_some() || tool_messages {
+        return Some(Feature::FunctionCalling);
+    }
+    if req.response_format.as_ref().is_some_and(|format| *format != ResponseFormat::Text) {
+        return Some(Feature::JsonSchema);
+    }
+    None
+}
+
+/// Text block carrying a message's content and cache control.
+fn text_block(message: &Message) -> AnthropicRequestBlock<'_> {
+    AnthropicRequestBlock::Text {
+        text: &message.content,
+        cache_control: message.cache_control,
+    }
+}
+
+/// Map an Anthropic stop reason to the unified finish reason.
+pub(crate) fn map_stop_reason(reason: &str) -> FinishReason {
+    match reason {
+        "max_tokens" => FinishReason::Length,
+        "tool_use" => FinishReason::ToolCalls,
+        _ => FinishReason::Stop,
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        Self::SUPPORTED_MODELS
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        let catalog = self.model_catalog.as_deref().unwrap_or(ModelCatalog::builtin());
+        catalog.get(model).cloned()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.sensitive_headers.clone()
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.retry_config.clone()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            function_calling: false,
+            vision: false,
+            json_schema: false,
+            embeddings: false,
+            assistant_prefill: true,
+            documents: true,
+            max_tokens: 8192,
+        }
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        if let Some(feature) = unsupported_feature(req) {
+            return Err(ProviderError::Unsupported {
+                provider: self.name().to_string(),
+                feature,
+            }
+            .into());
+        }
+
+        // `count_tokens` is asked when the request is executed
+        let estimated_tokens = match &self.prompt_token_counter {
+            PromptTokenCounter::Estimate => Some(req.token_count_total()),
+            PromptTokenCounter::Custom(count) => Some(count(req)),
+            PromptTokenCounter::CountTokens | PromptTokenCounter::Off => None,
+        };
+        if let Some(estimated_tokens) = estimated_tokens {
+            context::check_prompt_tokens(estimated_tokens, self.context_limit())?;
+        }
+
+        self.build_provider_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.check_counted_tokens(&req).await?;
+        provider_kit::json_response(self.send(req).await?).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let anthropic_response: AnthropicCompletionResponse = serde_json::from_value(resp.body)
+            .map_err(|e| SimpleAgentsError::Provider(
+                ProviderError::InvalidResponse(format!("Failed to deserialize response: {}", e))
+            ))?;
+
+        let finish_reason = anthropic_response.stop_reason.as_deref()
+            .map(map_stop_reason)
+            .unwrap_or(FinishReason::Stop);
+
+        Ok(CompletionResponse {
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(anthropic_response.text()),
+                finish_reason,
+                logprobs: None,
+                matched_stop: anthropic_response.stop_sequence.clone(),
+                annotations: anthropic_response.annotations(),
+            }],
+            usage: anthropic_response.usage.to_usage(),
+            id: anthropic_response.id,
+            model: anthropic_response.model,
+            // Anthropic reports no creation time; use the receipt time
+            created: Some(simple_agents_types::response::unix_timestamp_now()),
+            created_synthesized: true,
+            provider: Some(self.name().to_string()),
+        })
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        let events = self.stream_events(req).await?;
+        Ok(Box::new(chunk_stream(events)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> AnthropicProvider {
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        AnthropicProvider::new(api_key).unwrap()
+    }
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = provider();
+        assert_eq!(provider.name(), "anthropic");
+        assert_eq!(provider.base_url(), AnthropicProvider::DEFAULT_BASE_URL);
+    }
+
+    #[test]
+    fn test_transform_request() {
+        let provider = provider();
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::system("Be brief."))
+            .message(Message::system("Answer in English."))
+            .message(Message::user("Hello"))
+            .message(Message::assistant("Hi!"))
+            .temperature(0.7)
+            .extension("router.deployment", "eu")
+            .build()
+            .unwrap();
+
+        let provider_request = provider.transform_request(&request).unwrap();
+
+        assert_eq!(provider_request.url, "https://api.anthropic.com/v1/messages");
+        assert_eq!(provider_request.extensions, request.extensions);
+        assert!(!provider_request.body.to_string().contains("router.deployment"));
+        assert!(provider_request.headers.iter().any(|(k, _)| k == "x-api-key"));
+        assert!(provider_request.headers.iter().any(|(k, v)| k == "anthropic-version" && v == "2023-06-01"));
+
+        let body = &provider_request.body;
+        assert_eq!(body["system"], "Be brief.\n\nAnswer in English.");
+        assert_eq!(body["max_tokens"], AnthropicProvider::DEFAULT_MAX_TOKENS);
+        assert_eq!(body["messages"].as_array().unwrap().len(), 2);
+        assert_eq!(body["messages"][1]["role"], "assistant");
+        assert!(body.get("stream").is_none());
+
+        let streaming = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Hello"))
+            .stream(true)
+            .build()
+            .unwrap();
+        let body = provider.transform_request(&streaming).unwrap().body;
+        assert_eq!(body["stream"], true);
+    }
+
+    #[test]
+    fn test_beta_headers() {
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap();
+        let beta_header = |provider: &AnthropicProvider| {
+            let headers = provider.transform_request(&request).unwrap().headers;
+            let mut values = headers.into_iter().filter(|(k, _)| k == AnthropicBeta::HEADER);
+            let value = values.next().map(|(_, v)| v.into_owned());
+            assert!(values.next().is_none());
+            value
+        };
+
+        assert_eq!(beta_header(&provider()), None);
+
+        let single = provider().with_beta(AnthropicBeta::PromptCaching2024);
+        assert_eq!(beta_header(&single).as_deref(), Some("prompt-caching-2024-07-31"));
+
+        let multiple = provider()
+            .with_beta(AnthropicBeta::ComputerUse2024)
+            .with_betas(vec![
+                AnthropicBeta::MessageBatches2024,
+                AnthropicBeta::ComputerUse2024,
+                AnthropicBeta::TokensApi,
+            ]);
+        assert_eq!(multiple.betas().len(), 3);
+        assert_eq!(
+            beta_header(&multiple).as_deref(),
+            Some("computer-use-2024-10-22,message-batches-2024-09-24,token-counting-2024-11-01")
+        );
+    }
+
+    #[test]
+    fn test_transform_request_cache_control() {
+        let provider = provider();
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::system("You are a helpful assistant."))
+            .message(Message::system("<document>").with_cache_control(CacheControlType::Ephemeral))
+            .message(Message::user("Summarize the document."))
+            .build()
+            .unwrap();
+
+        let body = provider.transform_request(&request).unwrap().body;
+        assert_eq!(
+            body["system"],
+            serde_json::json!([
+                {"type": "text", "text": "You are a helpful assistant."},
+                {"type": "text", "text": "<document>", "cache_control": {"type": "ephemeral"}}
+            ])
+        );
+        assert_eq!(body["messages"][0]["content"], "Summarize the document.");
+
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("<long context>").with_cache_control(CacheControlType::Ephemeral))
+            .build()
+            .unwrap();
+        let body = provider.transform_request(&request).unwrap().body;
+        assert!(body.get("system").is_none());
+        assert_eq!(
+            body["messages"][0]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "<long context>", "cache_control": {"type": "ephemeral"}}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_transform_request_rejects_images() {
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("What is this?").with_image(ImageUrl::new("https://example.com/cat.png")))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            provider().transform_request(&request),
+            Err(SimpleAgentsError::Provider(ProviderError::Unsupported {
+                feature: Feature::Vision,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_transform_request_rejects_tools_and_response_formats() {
+        let base = || {
+            CompletionRequest::builder()
+                .model("claude-3-5-sonnet-20241022")
+                .message(Message::system("Reply in JSON."))
+                .message(Message::user("Weather in Paris?"))
+        };
+        let unsupported = |request: CompletionRequest| match provider().transform_request(&request) {
+            Err(SimpleAgentsError::Provider(ProviderError::Unsupported { feature, .. })) => feature,
+            other => panic!("unexpected result {:?}", other),
+        };
+
+        let parameters = serde_json::json!({"type": "object"});
+        let tool = ToolDefinition::function("get_weather", "Get the weather", parameters);
+        assert_eq!(unsupported(base().tool(tool).build().unwrap()), Feature::FunctionCalling);
+        let tool_result = base()
+            .message(Message::assistant("").with_tool_call(ToolCall::function("call_1", "get_weather", "{}")))
+            .message(Message::tool("18C", "call_1"))
+            .build()
+            .unwrap();
+        assert_eq!(unsupported(tool_result), Feature::FunctionCalling);
+        let json = base().response_format(ResponseFormat::JsonObject).build().unwrap();
+        assert_eq!(unsupported(json), Feature::JsonSchema);
+
+        let text = base().response_format(ResponseFormat::Text).build().unwrap();
+        assert!(provider().transform_request(&text).is_ok());
+    }
+
+    #[test]
+    fn test_transform_request_documents() {
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(
+                Message::user("Compare these reports")
+                    .with_document(Document::pdf("JVBERi0xLjcK"))
+                    .with_document(Document::file("file_011CNha8iCJcU1wXNR6q4V8w")),
+            )
+            .build()
+            .unwrap();
+        let provider_request = provider().transform_request(&request).unwrap();
+
+        assert_eq!(
+            provider_request.body["messages"][0]["content"],
+            serde_json::json!([
+                {
+                    "type": "document",
+                    "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0xLjcK"}
+                },
+                {"type": "document", "source": {"type": "file", "file_id": "file_011CNha8iCJcU1wXNR6q4V8w"}},
+                {"type": "text", "text": "Compare these reports"}
+            ])
+        );
+        let beta = provider_request.headers.iter().find(|(k, _)| k == AnthropicBeta::HEADER);
+        assert_eq!(beta.map(|(_, v)| v.as_ref()), Some("pdfs-2024-09-25,files-api-2025-04-14"));
+    }
+
+    #[test]
+    fn test_transform_request_citations() {
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("What color is the grass?").with_document(Document::pdf("JVBERi0xLjcK")))
+            .build()
+            .unwrap();
+
+        let body = provider().with_citations(true).transform_request(&request).unwrap().body;
+        assert_eq!(body["messages"][0]["content"][0]["citations"], serde_json::json!({"enabled": true}));
+        let body = provider().transform_request(&request).unwrap().body;
+        assert!(body["messages"][0]["content"][0].get("citations").is_none());
+    }
+
+    #[test]
+    fn test_transform_response_citations() {
+        // Two cited spans in separate text blocks, citing a text document and a PDF
+        let body = serde_json::json!({
+            "id": "msg_01Ahm5vVcn3mWJbJ6b4bSAes",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-5-sonnet-20241022",
+            "content": [
+                {"type": "text", "text": "According to the documents, "},
+                {
+                    "type": "text",
+                    "text": "the grass is green",
+                    "citations": [{
+                        "type": "char_location",
+                        "cited_text": "The grass is green.",
+                        "document_index": 0,
+                        "document_title": "Example Document",
+                        "start_char_index": 0,
+                        "end_char_index": 20
+                    }]
+                },
+                {"type": "text", "text": " and ", "citations": null},
+                {
+                    "type": "text",
+                    "text": "the sky is blue",
+                    "citations": [{
+                        "type": "page_location",
+                        "cited_text": "The sky is blue.\n",
+                        "document_index": 1,
+                        "document_title": null,
+                        "start_page_number": 2,
+                        "end_page_number": 3
+                    }]
+                },
+                {"type": "text", "text": "."}
+            ],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 2095, "output_tokens": 503}
+        });
+
+        let response = provider().transform_response(ProviderResponse::new(200, body)).unwrap();
+        let choice = &response.choices[0];
+        let content = &choice.message.content;
+        assert_eq!(content, "According to the documents, the grass is green and the sky is blue.");
+        assert_eq!(choice.annotations.len(), 2);
+
+        let grass = &choice.annotations[0];
+        assert_eq!(grass.kind, AnnotationKind::DocumentCitation);
+        assert_eq!(grass.document_index, Some(0));
+        assert_eq!(grass.title.as_deref(), Some("Example Document"));
+        assert_eq!(grass.quote.as_deref(), Some("The grass is green."));
+        assert_eq!(grass.cited_text(content), Some("the grass is green"));
+
+        let sky = &choice.annotations[1];
+        assert_eq!(sky.document_index, Some(1));
+        assert_eq!(sky.title, None);
+        assert_eq!(sky.cited_text(content), Some("the sky is blue"));
+
+        assert_eq!(
+            choice.content_with_footnotes(),
+            "According to the documents, the grass is green[1] and the sky is blue[2].\n\n\
+             [1] Example Document: document 0\n\
+             [2] document 1"
+        );
+    }
+
+    #[test]
+    fn test_transform_request_rejects_oversized_documents() {
+        let data = "A".repeat(AnthropicProvider::MAX_REQUEST_BYTES + 4);
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Summarize").with_document(Document::pdf(data)))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            provider().transform_request(&request),
+            Err(SimpleAgentsError::Provider(ProviderError::PayloadTooLarge(_)))
+        ));
+    }
+
+    #[test]
+    fn test_transform_response_cache_usage() {
+        let body = serde_json::json!({
+            "id": "msg_123",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Summary."}],
+            "model": "claude-3-5-sonnet-20241022",
+            "stop_reason": "end_turn",
+            "usage": {
+                "input_tokens": 21,
+                "cache_creation_input_tokens": 0,
+                "cache_read_input_tokens": 188086,
+                "output_tokens": 393
+            }
+        });
+
+        let response = provider().transform_response(ProviderResponse::new(200, body)).unwrap();
+        assert_eq!(response.usage.prompt_tokens, 188107);
+        assert_eq!(response.usage.cache_creation_input_tokens, Some(0));
+        assert_eq!(response.usage.cache_read_input_tokens, Some(188086));
+    }
+
+    #[test]
+    fn test_transform_response() {
+        let provider = provider();
+        let body = serde_json::json!({
+            "id": "msg_123",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Hello!"}],
+            "model": "claude-3-5-sonnet-20241022",
+            "stop_reason": "max_tokens",
+            "usage": {"input_tokens": 10, "output_tokens": 4}
+        });
+
+        let response = provider.transform_response(ProviderResponse::new(200, body)).unwrap();
+        assert_eq!(response.id, "msg_123");
+        assert_eq!(response.content(), Some("Hello!"));
+        assert_eq!(response.choices[0].finish_reason, FinishReason::Length);
+        assert_eq!(response.usage.total_tokens, 14);
+        assert_eq!(response.provider.as_deref(), Some("anthropic"));
+
+        // No timestamp from Anthropic: receipt time, marked as synthesized
+        assert!(response.created_synthesized);
+        let now = simple_agents_types::response::unix_timestamp_now();
+        assert!((now - response.created.unwrap()).abs() <= 5);
+    }
+
+    #[test]
+    fn test_anthropic_wire_round_trip() {
+        let provider = provider();
+        let plain_usage = serde_json::json!({"input_tokens": 10, "output_tokens": 4});
+        let cache_usage = serde_json::json!({
+            "input_tokens": 10,
+            "cache_creation_input_tokens": 2048,
+            "cache_read_input_tokens": 0,
+            "output_tokens": 4
+        });
+        for (text, stop_reason, stop_sequence, usage) in [
+            ("Hello!", "end_turn", None, plain_usage.clone()),
+            ("Truncated", "max_tokens", None, cache_usage),
+            ("Step 1", "stop_sequence", Some("Step 2"), plain_usage),
+        ] {
+            let wire = serde_json::json!({
+                "id": "msg_123",
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "text", "text": text}],
+                "model": "claude-3-5-sonnet-20241022",
+                "stop_reason": stop_reason,
+                "stop_sequence": stop_sequence,
+                "usage": usage
+            });
+
+            let unified = provider.transform_response(ProviderResponse::new(200, wire.clone())).unwrap();
+            assert_eq!(unified.choices[0].matched_stop.as_deref(), stop_sequence);
+            let round_trip = unified.to_anthropic_wire();
+            assert_eq!(round_trip, wire);
+
+            // The re-serialized form also parses as an Anthropic response
+            let parsed: AnthropicCompletionResponse = serde_json::from_value(round_trip).unwrap();
+            assert_eq!(parsed.text(), text);
+        }
+    }
+
+    #[test]
+    fn test_map_stop_reason() {
+        assert_eq!(map_stop_reason("end_turn"), FinishReason::Stop);
+        assert_eq!(map_stop_reason("stop_sequence"), FinishReason::Stop);
+        assert_eq!(map_stop_reason("max_tokens"), FinishReason::Length);
+        assert_eq!(map_stop_reason("tool_use"), FinishReason::ToolCalls);
+    }
+
+    fn fast_retry(max_attempts: u32) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            backoff_multiplier: 2.0,
+            jitter: true,
+        }
+    }
+
+    fn hello_request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_context_limit_with_fake_estimator() {
+        let estimate = |tokens: u32| PromptTokenCounter::custom(move |_| tokens);
+        let check = |provider: AnthropicProvider, tokens: u32| {
+            provider
+                .with_prompt_token_counter(estimate(tokens))
+                .transform_request(&hello_request())
+        };
+
+        assert_eq!(provider().context_limit(), AnthropicProvider::CONTEXT_TOKENS);
+        assert!(check(provider(), 200_000).is_ok());
+        let err = check(provider(), 200_001).unwrap_err();
+        assert!(matches!(
+            err,
+            SimpleAgentsError::Validation(ValidationError::PromptTooLong {
+                estimated_tokens: 200_001,
+                limit: 200_000
+            })
+        ));
+        assert_eq!(err.code(), ErrorCode::ContextTooLong);
+        assert!(err.to_string().contains("about 200001 tokens, over the 200000-token context limit"));
+
+        let long = provider().with_long_context(true);
+        assert_eq!(long.context_limit(), AnthropicProvider::LONG_CONTEXT_TOKENS);
+        assert!(check(long.clone(), 1_000_000).is_ok());
+        assert!(matches!(
+            check(long.clone(), 1_000_001),
+            Err(SimpleAgentsError::Validation(ValidationError::PromptTooLong { limit: 1_000_000, .. }))
+        ));
+
+        // Opting out again restores the default limit and drops the beta
+        let off = long.with_long_context(false);
+        assert!(off.betas().is_empty());
+        assert!(check(off, 200_001).is_err());
+        let unchecked = provider().with_prompt_token_counter(PromptTokenCounter::Off);
+        assert!(unchecked.transform_request(&hello_request()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_context_limit_with_count_tokens() {
+        let mut server = mockito::Server::new_async().await;
+        let count = server
+            .mock("POST", "/messages/count_tokens")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "claude-3-5-sonnet-20241022",
+                "messages": [{"role": "user", "content": "Hello"}]
+            })))
+            .with_status(200)
+            .with_body(r#"{"input_tokens": 250000}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let messages = server
+            .mock("POST", "/messages")
+            .match_header(AnthropicBeta::HEADER, "context-1m-2025-08-07")
+            .with_status(200)
+            .with_body(
+                r#"{"id": "msg_1", "type": "message", "role": "assistant",
+                    "model": "claude-3-5-sonnet-20241022", "content": [{"type": "text", "text": "Hi"}], "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 250000, "output_tokens": 1}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_prompt_token_counter(PromptTokenCounter::CountTokens);
+
+        // Over the default limit: the completion is never sent
+        let err = provider.complete(&hello_request()).await.unwrap_err();
+        assert!(matches!(
+            err.root(),
+            SimpleAgentsError::Validation(ValidationError::PromptTooLong {
+                estimated_tokens: 250_000,
+                limit: 200_000
+            })
+        ));
+
+        let response = provider.with_long_context(true).complete(&hello_request()).await.unwrap();
+        assert_eq!(response.content(), Some("Hi"));
+        count.assert_async().await;
+        messages.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_json_prefill_round_trip() {
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Reply with a JSON object holding the answer."))
+            .assistant_prefill("{\"answer\": ")
+            .prepend_prefill(true)
+            .build()
+            .unwrap();
+
+        let body = provider().transform_request(&request).unwrap().body;
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "{\"answer\":");
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "messages": [{"role": "user"}, {"role": "assistant", "content": "{\"answer\":"}]
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "text", "text": " 42}"}],
+                    "model": "claude-3-5-sonnet-20241022",
+                    "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 12, "output_tokens": 3}
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url()).unwrap();
+
+        let response = provider.complete(&request).await.unwrap();
+        assert_eq!(response.content(), Some("{\"answer\": 42}"));
+        let parsed: serde_json::Value = serde_json::from_str(response.content().unwrap()).unwrap();
+        assert_eq!(parsed["answer"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_overloaded_is_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_status(529)
+            .with_body(r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+
+        let result = provider.complete(&hello_request()).await.map_err(SimpleAgentsError::into_root);
+        assert!(matches!(
+            result,
+            Err(SimpleAgentsError::Provider(ProviderError::ServerError(msg))) if msg == "Overloaded"
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_attempts_records_every_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_status(529)
+            .with_body(r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        // The caller's policy, not the provider's own retries, sets the count
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(5));
+        let delays = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = delays.clone();
+        let policy = RetryPolicy::new(fast_retry(3), move |delay: Duration| {
+            recorded.lock().unwrap().push(delay);
+            async {}
+        });
+
+        let err = provider.execute_with_attempts(&hello_request(), &policy).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ServerError);
+        let attempts = err.context().unwrap().attempts.attempts();
+        assert_eq!(attempts.iter().map(|a| a.attempt).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(attempts.iter().all(|a| a.error_code == Some(ErrorCode::ServerError)));
+        let mut backoffs = delays.lock().unwrap().clone();
+        backoffs.push(Duration::ZERO);
+        assert_eq!(attempts.iter().map(|a| a.backoff).collect::<Vec<_>>(), backoffs);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_parses_retry_after() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .with_body(r#"{"type": "error", "error": {"type": "rate_limit_error", "message": "Rate limited"}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(2));
+
+        let result = provider.complete(&hello_request()).await.map_err(SimpleAgentsError::into_root);
+        assert!(matches!(
+            result,
+            Err(SimpleAgentsError::Provider(ProviderError::RateLimit { retry_after: Some(d) })) if d == Duration::ZERO
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_is_not_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_status(400)
+            .with_body(r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "messages: Field required"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+
+        let result = provider.complete(&hello_request()).await.map_err(SimpleAgentsError::into_root);
+        assert!(matches!(
+            result,
+            Err(SimpleAgentsError::Provider(ProviderError::BadRequest(_)))
+        ));
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_transform_request_auth_schemes() {
+        let header = |request: &ProviderRequest, name: &str| {
+            request.headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.to_string())
+        };
+
+        let provider_request = provider().transform_request(&hello_request()).unwrap();
+        assert_eq!(
+            header(&provider_request, "x-api-key").as_deref(),
+            Some("sk-ant-REDACTED")
+        );
+        assert_eq!(header(&provider_request, "Authorization"), None);
+
+        let provider = provider()
+            .with_auth_scheme(AuthScheme::Bearer)
+            .with_default_header("x-tenant-id", "acme");
+        let provider_request = provider.transform_request(&hello_request()).unwrap();
+        assert_eq!(
+            header(&provider_request, "Authorization").as_deref(),
+            Some("Bearer sk-ant-REDACTED")
+        );
+        assert_eq!(header(&provider_request, "x-api-key"), None);
+        assert_eq!(header(&provider_request, "x-tenant-id").as_deref(), Some("acme"));
+        assert_eq!(header(&provider_request, "anthropic-version").as_deref(), Some("2023-06-01"));
+    }
+
+    #[test]
+    fn test_dry_run_redacts_secrets() {
+        let provider = provider()
+            .with_auth_scheme(AuthScheme::Bearer)
+            .with_default_header("x-gateway-token", "gw-secret")
+            .with_default_header("x-tenant-id", "acme")
+            .with_sensitive_header("X-Gateway-Token")
+            .with_debug_requests(true);
+
+        let dry_run = provider.dry_run(&hello_request()).unwrap();
+        assert_eq!(dry_run.url, "https://api.anthropic.com/v1/messages");
+        let header = |name: &str| {
+            dry_run.headers_redacted.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+        };
+        assert_eq!(header("Authorization"), Some("[REDACTED]"));
+        assert_eq!(header("x-gateway-token"), Some("[REDACTED]"));
+        assert_eq!(header("x-tenant-id"), Some("acme"));
+        assert!(dry_run.body_pretty.contains("\"max_tokens\": 4096"));
+        assert!(dry_run.estimated_prompt_tokens > 0);
+
+        let rendered = format!("{} {:?}", dry_run, provider);
+        assert!(!rendered.contains("sk-ant-test"));
+        assert!(!rendered.contains("gw-secret"));
+    }
+
+    #[tokio::test]
+    async fn test_bearer_auth_through_gateway() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .match_header("authorization", "Bearer sk-ant-REDACTED")
+            .match_header("x-tenant-id", "acme")
+            .match_header("x-api-key", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "text", "text": "Hi"}],
+                    "model": "claude-3-5-sonnet-20241022",
+                    "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 5, "output_tokens": 1}
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_auth_scheme(AuthScheme::Bearer)
+            .with_default_header("x-tenant-id", "acme");
+
+        let response = provider.complete(&hello_request()).await.unwrap();
+        assert_eq!(response.content(), Some("Hi"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rejected_key_is_refreshed_and_retried() {
+        use crate::credentials::RefreshingCredentials;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let stale = "sk-ant-REDACTED";
+        let rotated = "sk-ant-REDACTED";
+
+        let mut server = mockito::Server::new_async().await;
+        let rejected = server
+            .mock("POST", "/messages")
+            .match_header("x-api-key", stale)
+            .with_status(401)
+            .with_body(r#"{"type": "error", "error": {"type": "authentication_error", "message": "invalid x-api-key"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("POST", "/messages")
+            .match_header("x-api-key", rotated)
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "text", "text": "Hi"}],
+                    "model": "claude-3-5-sonnet-20241022",
+                    "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 5, "output_tokens": 1}
+                }"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        let credentials = RefreshingCredentials::new(Duration::from_secs(300), move || {
+            let key = if counter.fetch_add(1, Ordering::SeqCst) == 0 { stale } else { rotated };
+            async move { ApiKey::new(key) }
+        });
+        let provider = AnthropicProvider::with_base_url(credentials, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+
+        let response = provider.complete(&hello_request()).await.unwrap();
+        assert_eq!(response.content(), Some("Hi"));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_raw_passthrough_is_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let overloaded = server
+            .mock("POST", "/messages/count_tokens")
+            .with_status(529)
+            .with_body(r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let counted = server
+            .mock("POST", "/messages/count_tokens")
+            .match_header("x-api-key", "sk-ant-REDACTED")
+            .match_header("anthropic-version", "2023-06-01")
+            .match_header("anthropic-beta", "token-counting-2024-11-01")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "claude-3-5-sonnet-20241022"})))
+            .with_status(200)
+            .with_body(r#"{"input_tokens": 9}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(3))
+            .with_beta(AnthropicBeta::TokensApi);
+
+        let response = provider
+            .raw(
+                reqwest::Method::POST,
+                "/messages/count_tokens",
+                serde_json::json!({
+                    "model": "claude-3-5-sonnet-20241022",
+                    "messages": [{"role": "user", "content": "Hello"}]
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.body["input_tokens"], 9);
+        overloaded.assert_async().await;
+        counted.assert_async().await;
+
+        let err = provider
+            .raw(reqwest::Method::GET, "https://evil.example.com/models", serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_custom_classifier_stops_retries() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_status(529)
+            .with_body(r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicProvider::with_base_url(api_key, server.url())
+            .unwrap()
+            .with_retry_config(fast_retry(3))
+            .with_classifier(std::sync::Arc::new(|_| crate::retry::ErrorClass::Fatal));
+
+        assert!(provider.complete(&hello_request()).await.is_err());
+        mock.assert_async().await;
+    }
 
-pub use error::AnthropicError;
+    #[test]
+    fn test_supported_models() {
+        let provider = provider();
+        assert!(provider.supports_model("claude-3-5-sonnet-20241022"));
+        assert!(provider.supports_model("claude-3-haiku-20240307"));
+        assert!(!provider.supports_model("gpt-4"));
 
-// TODO: Implement AnthropicProvider
+        // A snapshot newer than the list validates through its family
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20250101")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap();
+        assert!(request.validate_model(&provider).is_ok());
+    }
+}
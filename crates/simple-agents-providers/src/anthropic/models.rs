@@ -1,5 +1,465 @@
 //! Anthropic API request and response types.
-//!
-//! Implementation coming in Phase 2.
 
-// TODO: Implement Anthropic request/response types
+use serde::{Deserialize, Serialize};
+use simple_agents_types::annotation::Annotation;
+use simple_agents_types::message::{CacheControlType, Document};
+use simple_agents_types::response::Usage;
+
+/// Anthropic messages API request
+///
+/// Borrows message content from the unified request to avoid cloning.
+#[derive(Debug, Serialize)]
+pub struct AnthropicCompletionRequest<'a> {
+    /// Model identifier (e.g., "claude-3-5-sonnet-20241022")
+    pub model: &'a str,
+
+    /// Conversation turns (user/assistant only)
+    pub messages: Vec<AnthropicMessage<'a>>,
+
+    /// System prompt (Anthropic takes this outside the message list)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<AnthropicSystem<'a>>,
+
+    /// Maximum tokens to generate (required by Anthropic)
+    pub max_tokens: u32,
+
+    /// Temperature (0.0-1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Top-p sampling (0.0-1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Custom stop sequences
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<&'a Vec<String>>,
+
+    /// Whether to stream the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+/// A single Anthropic conversation turn
+#[derive(Debug, Serialize)]
+pub struct AnthropicMessage<'a> {
+    /// Either "user" or "assistant"
+    pub role: &'static str,
+
+    /// Content of the turn
+    pub content: AnthropicContent<'a>,
+}
+
+/// Message content: a plain string, or blocks when cache control is set
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum AnthropicContent<'a> {
+    /// Plain text
+    Text(&'a str),
+    /// Content blocks
+    Blocks(Vec<AnthropicRequestBlock<'a>>),
+}
+
+/// System prompt: a plain string, or one block per system message when
+/// any of them sets cache control
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum AnthropicSystem<'a> {
+    /// System messages joined with blank lines
+    Text(String),
+    /// One block per system message
+    Blocks(Vec<AnthropicRequestBlock<'a>>),
+}
+
+/// A content block in an Anthropic request
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicRequestBlock<'a> {
+    /// Plain text
+    Text {
+        /// Text content
+        text: &'a str,
+        /// Prompt caching breakpoint
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControlType>,
+    },
+    /// Document (e.g. PDF), inline or uploaded
+    Document {
+        /// Base64 data or file reference
+        source: &'a Document,
+        /// Prompt caching breakpoint
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControlType>,
+        /// Whether Claude may cite this document
+        #[serde(skip_serializing_if = "Option::is_none")]
+        citations: Option<AnthropicCitationsConfig>,
+    },
+}
+
+/// Citation settings of a document block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AnthropicCitationsConfig {
+    /// Cite passages of the document in the response
+    pub enabled: bool,
+}
+
+/// File metadata returned by the Files API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnthropicFile {
+    /// File identifier, used in [`Document::File`]
+    pub id: String,
+    /// Name given at upload
+    #[serde(default)]
+    pub filename: String,
+    /// MIME type detected by Anthropic
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    /// Size in bytes
+    #[serde(default)]
+    pub size_bytes: u64,
+}
+
+/// Anthropic messages API response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicCompletionResponse {
+    /// Unique message identifier
+    pub id: String,
+
+    /// Object type (always "message")
+    #[serde(rename = "type")]
+    pub object_type: String,
+
+    /// Role of the generated message (always "assistant")
+    pub role: String,
+
+    /// Generated content blocks
+    pub content: Vec<AnthropicContentBlock>,
+
+    /// Model that handled the request
+    pub model: String,
+
+    /// Why generation stopped
+    pub stop_reason: Option<String>,
+
+    /// Which custom stop sequence was generated, if any
+    #[serde(default)]
+    pub stop_sequence: Option<String>,
+
+    /// Token usage information
+    pub usage: AnthropicUsage,
+}
+
+impl AnthropicCompletionResponse {
+    /// Concatenate all text blocks into a single string.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                AnthropicContentBlock::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Citations of the text blocks as unified annotations.
+    ///
+    /// Each annotation spans its text block within [`text`](Self::text),
+    /// in bytes.
+    pub fn annotations(&self) -> Vec<Annotation> {
+        let text = self.text();
+        let mut annotations = Vec::new();
+        let mut offset = 0;
+        for block in &self.content {
+            let AnthropicContentBlock::Text { text: block_text, citations } = block else {
+                continue;
+            };
+            let end = offset + block_text.len();
+            for citation in citations.iter().flatten() {
+                annotations.push(citation.to_annotation().with_byte_range(&text, offset, end));
+            }
+            offset = end;
+        }
+        annotations
+    }
+}
+
+/// A content block in an Anthropic response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicContentBlock {
+    /// Plain text
+    Text {
+        /// Text content
+        text: String,
+        /// Document passages supporting this text, when citations are
+        /// enabled
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        citations: Option<Vec<AnthropicCitation>>,
+    },
+
+    /// Tool invocation requested by the model
+    ToolUse {
+        /// Tool call identifier
+        id: String,
+        /// Tool name
+        name: String,
+        /// Tool input arguments
+        input: serde_json::Value,
+    },
+
+    /// A block type this version does not handle (e.g. `thinking`)
+    #[serde(other)]
+    Unknown,
+}
+
+/// A passage of a request document cited by a text block
+///
+/// The location fields present depend on `citation_type`: character
+/// offsets for plain text documents, page numbers for PDFs, and block
+/// indices for custom content documents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicCitation {
+    /// `char_location`, `page_location` or `content_block_location`
+    #[serde(rename = "type")]
+    pub citation_type: String,
+
+    /// The cited passage
+    pub cited_text: String,
+
+    /// Position of the document among those in the request, from 0
+    pub document_index: usize,
+
+    /// Title of the document, if it was given one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_title: Option<String>,
+
+    /// First cited character (`char_location`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_char_index: Option<usize>,
+
+    /// End of the cited characters, exclusive (`char_location`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_char_index: Option<usize>,
+
+    /// First cited page, from 1 (`page_location`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_page_number: Option<usize>,
+
+    /// End of the cited pages, exclusive (`page_location`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_page_number: Option<usize>,
+
+    /// First cited content block (`content_block_location`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_block_index: Option<usize>,
+
+    /// End of the cited content blocks, exclusive (`content_block_location`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_block_index: Option<usize>,
+}
+
+impl AnthropicCitation {
+    /// Convert to a unified annotation, without a span in the content.
+    pub fn to_annotation(&self) -> Annotation {
+        let annotation = Annotation::document(self.document_index).with_quote(&self.cited_text);
+        match &self.document_title {
+            Some(title) => annotation.with_title(title),
+            None => annotation,
+        }
+    }
+}
+
+/// Token usage information
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicUsage {
+    /// Number of input (prompt) tokens
+    #[serde(default)]
+    pub input_tokens: u32,
+
+    /// Number of output (completion) tokens
+    #[serde(default)]
+    pub output_tokens: u32,
+
+    /// Input tokens written to the prompt cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u32>,
+
+    /// Input tokens read from the prompt cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u32>,
+}
+
+impl AnthropicUsage {
+    /// Convert to unified usage.
+    ///
+    /// Anthropic's `input_tokens` excludes cached tokens, so
+    /// `prompt_tokens` adds the cache creation and read counts back in.
+    pub fn to_usage(&self) -> Usage {
+        let prompt_tokens = self
+            .input_tokens
+            .saturating_add(self.cache_creation_input_tokens.unwrap_or(0))
+            .saturating_add(self.cache_read_input_tokens.unwrap_or(0));
+
+        Usage {
+            cache_creation_input_tokens: self.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens,
+            ..Usage::new(prompt_tokens, self.output_tokens)
+        }
+    }
+}
+
+/// Anthropic error response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicErrorResponse {
+    /// Error details
+    pub error: AnthropicErrorDetails,
+}
+
+/// Anthropic error details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicErrorDetails {
+    /// Error type (e.g. "invalid_request_error", "overloaded_error")
+    #[serde(rename = "type")]
+    pub error_type: String,
+
+    /// Human-readable error message
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_request() {
+        let stop = vec!["END".to_string()];
+        let request = AnthropicCompletionRequest {
+            model: "claude-3-5-sonnet-20241022",
+            messages: vec![AnthropicMessage {
+                role: "user",
+                content: AnthropicContent::Text("Hello"),
+            }],
+            system: Some(AnthropicSystem::Text("Be brief.".to_string())),
+            max_tokens: 1024,
+            temperature: Some(0.5),
+            top_p: None,
+            stop_sequences: Some(&stop),
+            stream: None,
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["model"], "claude-3-5-sonnet-20241022");
+        assert_eq!(json["system"], "Be brief.");
+        assert_eq!(json["max_tokens"], 1024);
+        assert_eq!(json["messages"][0]["role"], "user");
+        assert_eq!(json["stop_sequences"][0], "END");
+        assert!(json.get("top_p").is_none());
+        assert!(json.get("stream").is_none());
+    }
+
+    #[test]
+    fn test_deserialize_response() {
+        let json = r#"{
+            "id": "msg_01XFDUDYJgAACzvnptvVoYEL",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "text", "text": "Hello"},
+                {"type": "text", "text": " there!"}
+            ],
+            "model": "claude-3-5-sonnet-20241022",
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 12, "output_tokens": 6}
+        }"#;
+
+        let response: AnthropicCompletionResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.id, "msg_01XFDUDYJgAACzvnptvVoYEL");
+        assert_eq!(response.text(), "Hello there!");
+        assert_eq!(response.stop_reason.as_deref(), Some("end_turn"));
+        assert_eq!(response.usage.input_tokens, 12);
+        assert_eq!(response.usage.output_tokens, 6);
+        assert_eq!(response.usage.cache_read_input_tokens, None);
+    }
+
+    #[test]
+    fn test_serialize_cache_control_blocks() {
+        let request = AnthropicCompletionRequest {
+            model: "claude-3-5-sonnet-20241022",
+            messages: vec![AnthropicMessage {
+                role: "user",
+                content: AnthropicContent::Blocks(vec![AnthropicRequestBlock::Text {
+                    text: "Summarize the document.",
+                    cache_control: Some(CacheControlType::Ephemeral),
+                }]),
+            }],
+            system: Some(AnthropicSystem::Blocks(vec![
+                AnthropicRequestBlock::Text {
+                    text: "You are a helpful assistant.",
+                    cache_control: None,
+                },
+                AnthropicRequestBlock::Text {
+                    text: "<document>",
+                    cache_control: Some(CacheControlType::Ephemeral),
+                },
+            ])),
+            max_tokens: 1024,
+            temperature: None,
+            top_p: None,
+            stop_sequences: None,
+            stream: None,
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["system"],
+            serde_json::json!([
+                {"type": "text", "text": "You are a helpful assistant."},
+                {"type": "text", "text": "<document>", "cache_control": {"type": "ephemeral"}}
+            ])
+        );
+        assert_eq!(
+            json["messages"][0]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "Summarize the document.", "cache_control": {"type": "ephemeral"}}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_cache_usage() {
+        // Usage block from Anthropic's prompt caching documentation
+        let json = r#"{
+            "input_tokens": 21,
+            "cache_creation_input_tokens": 188086,
+            "cache_read_input_tokens": 0,
+            "output_tokens": 393
+        }"#;
+
+        let usage: AnthropicUsage = serde_json::from_str(json).unwrap();
+        assert_eq!(usage.cache_creation_input_tokens, Some(188086));
+        assert_eq!(usage.cache_read_input_tokens, Some(0));
+
+        let unified = usage.to_usage();
+        assert_eq!(unified.prompt_tokens, 188107);
+        assert_eq!(unified.completion_tokens, 393);
+        assert_eq!(unified.total_tokens, 188500);
+        assert_eq!(unified.cache_creation_input_tokens, Some(188086));
+        assert_eq!(unified.cache_read_input_tokens, Some(0));
+
+        let plain: AnthropicUsage = serde_json::from_str(r#"{"input_tokens": 5, "output_tokens": 1}"#).unwrap();
+        assert_eq!(plain.to_usage(), Usage::new(5, 1));
+    }
+
+    #[test]
+    fn test_deserialize_error_response() {
+        let json = r#"{
+            "type": "error",
+            "error": {"type": "not_found_error", "message": "model: claude-9"}
+        }"#;
+
+        let response: AnthropicErrorResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.error.error_type, "not_found_error");
+        assert_eq!(response.error.message, "model: claude-9");
+    }
+}
@@ -0,0 +1,689 @@
+//! Anthropic streaming support.
+//!
+//! Anthropic streams a richer set of SSE events than OpenAI: `message_start`
+//! carries the input token count, `message_delta` carries the output token
+//! count and stop reason. [`AnthropicStreamEvent`] models every event type so
+//! callers can use that information instead of only the text deltas.
+
+use super::{
+    map_stop_reason, AnthropicCitation, AnthropicCompletionResponse, AnthropicContentBlock, AnthropicError,
+    AnthropicErrorDetails, AnthropicProvider, AnthropicUsage,
+};
+use crate::streaming::SseEvent;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use simple_agents_types::prelude::*;
+use std::time::Duration;
+
+/// A typed Anthropic streaming event
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnthropicStreamEvent {
+    /// Start of the message, with metadata and input token usage
+    MessageStart(AnthropicMessageStart),
+    /// Start of a content block
+    ContentBlockStart(AnthropicContentBlockStart),
+    /// Incremental content for a block
+    ContentBlockDelta(AnthropicContentBlockDelta),
+    /// End of a content block
+    ContentBlockStop(AnthropicContentBlockStop),
+    /// Top-level message changes (stop reason, output token usage)
+    MessageDelta(AnthropicMessageDelta),
+    /// End of the message
+    MessageStop,
+    /// Keep-alive
+    Ping,
+    /// Error delivered inside the stream (e.g. `overloaded_error`)
+    Error(AnthropicError),
+}
+
+/// Payload of a `message_start` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessageStart {
+    /// The message shell (empty content, input token usage)
+    pub message: AnthropicCompletionResponse,
+}
+
+/// Payload of a `content_block_start` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicContentBlockStart {
+    /// Index of the content block
+    pub index: u32,
+    /// Initial (usually empty) block content
+    pub content_block: AnthropicContentBlock,
+}
+
+/// Payload of a `content_block_delta` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicContentBlockDelta {
+    /// Index of the content block
+    pub index: u32,
+    /// The incremental content
+    pub delta: AnthropicDelta,
+}
+
+/// Incremental content within a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicDelta {
+    /// Text fragment
+    TextDelta {
+        /// Text to append
+        text: String,
+    },
+    /// Fragment of a tool-use input JSON document
+    InputJsonDelta {
+        /// Partial JSON to append
+        partial_json: String,
+    },
+    /// Citation supporting the text block
+    CitationsDelta {
+        /// Citation to append
+        citation: AnthropicCitation,
+    },
+    /// A delta type this version does not handle (e.g. `thinking_delta`,
+    /// `signature_delta`); skipped
+    #[serde(other)]
+    Unknown,
+}
+
+/// Payload of a `content_block_stop` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicContentBlockStop {
+    /// Index of the finished content block
+    pub index: u32,
+}
+
+/// Payload of a `message_delta` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessageDelta {
+    /// Changed message fields
+    pub delta: AnthropicMessageDeltaBody,
+    /// Cumulative output token usage
+    #[serde(default)]
+    pub usage: AnthropicUsage,
+}
+
+/// Message fields updated by a `message_delta` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessageDeltaBody {
+    /// Why generation stopped
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    /// Which custom stop sequence was generated, if any
+    #[serde(default)]
+    pub stop_sequence: Option<String>,
+}
+
+/// Wire format of a stream event, tagged by `type`.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RawStreamEvent {
+    MessageStart(AnthropicMessageStart),
+    ContentBlockStart(AnthropicContentBlockStart),
+    ContentBlockDelta(AnthropicContentBlockDelta),
+    ContentBlockStop(AnthropicContentBlockStop),
+    MessageDelta(AnthropicMessageDelta),
+    MessageStop,
+    Ping,
+    Error { error: AnthropicErrorDetails },
+    #[serde(other)]
+    Unknown,
+}
+
+impl AnthropicStreamEvent {
+    /// Parse an event from the JSON `data` of an SSE event.
+    ///
+    /// Returns `Ok(None)` for event types this version does not know about;
+    /// Anthropic may add new event types and clients are expected to skip them.
+    pub fn parse(data: &str) -> Result<Option<Self>> {
+        let raw: RawStreamEvent = serde_json::from_str(data).map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to parse stream event: {}",
+                e
+            )))
+        })?;
+
+        Ok(match raw {
+            RawStreamEvent::MessageStart(start) => Some(Self::MessageStart(start)),
+            RawStreamEvent::ContentBlockStart(start) => Some(Self::ContentBlockStart(start)),
+            RawStreamEvent::ContentBlockDelta(delta) => Some(Self::ContentBlockDelta(delta)),
+            RawStreamEvent::ContentBlockStop(stop) => Some(Self::ContentBlockStop(stop)),
+            RawStreamEvent::MessageDelta(delta) => Some(Self::MessageDelta(delta)),
+            RawStreamEvent::MessageStop => Some(Self::MessageStop),
+            RawStreamEvent::Ping => Some(Self::Ping),
+            RawStreamEvent::Error { error } => {
+                Some(Self::Error(AnthropicError::from_error_details(0, &error)))
+            }
+            RawStreamEvent::Unknown => None,
+        })
+    }
+
+    /// Parse an event from a decoded SSE event.
+    pub fn from_sse(event: &SseEvent) -> Result<Option<Self>> {
+        if event.data.is_empty() {
+            return Ok(None);
+        }
+        Self::parse(&event.data)
+    }
+}
+
+/// Converts typed events into unified [`CompletionChunk`]s.
+///
+/// Keeps the message id and model from `message_start`, since later events
+/// do not repeat them.
+#[derive(Debug, Default)]
+pub struct AnthropicChunkMapper {
+    id: String,
+    model: String,
+}
+
+impl AnthropicChunkMapper {
+    /// Create a new mapper.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map one event to a chunk, if it carries anything a chunk can express.
+    pub fn map(&mut self, event: AnthropicStreamEvent) -> Option<Result<CompletionChunk>> {
+        let (role, content, finish_reason) = match event {
+            AnthropicStreamEvent::MessageStart(start) => {
+                self.id = start.message.id;
+                self.model = start.message.model;
+                (Some(Role::Assistant), None, None)
+            }
+            AnthropicStreamEvent::ContentBlockDelta(AnthropicContentBlockDelta {
+                delta: AnthropicDelta::TextDelta { text },
+                ..
+            }) => (None, Some(text), None),
+            AnthropicStreamEvent::MessageDelta(delta) => {
+                let stop_reason = delta.delta.stop_reason.as_deref()?;
+                (None, None, Some(map_stop_reason(stop_reason)))
+            }
+            AnthropicStreamEvent::Error(error) => {
+                return Some(Err(SimpleAgentsError::Provider(error.into())));
+            }
+            _ => return None,
+        };
+
+        Some(Ok(CompletionChunk {
+            id: self.id.clone(),
+            model: self.model.clone(),
+            choices: vec![ChoiceDelta {
+                index: 0,
+                delta: MessageDelta { role, content },
+                finish_reason,
+            }],
+            created: None,
+            usage: None,
+        }))
+    }
+}
+
+/// Convert a stream of typed events into unified completion chunks.
+pub fn chunk_stream<S>(events: S) -> impl Stream<Item = Result<CompletionChunk>> + Send + Unpin
+where
+    S: Stream<Item = Result<AnthropicStreamEvent>> + Send + Unpin,
+{
+    let mut mapper = AnthropicChunkMapper::new();
+    events.filter_map(move |event| {
+        let chunk = match event {
+            Ok(event) => mapper.map(event),
+            Err(e) => Some(Err(e)),
+        };
+        futures::future::ready(chunk)
+    })
+}
+
+/// Rebuilds a complete [`AnthropicCompletionResponse`] from stream events.
+#[derive(Debug, Default)]
+pub struct AnthropicMessageAccumulator {
+    message: Option<AnthropicCompletionResponse>,
+    partial_json: Vec<String>,
+}
+
+impl AnthropicMessageAccumulator {
+    /// Create a new accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply one event. Stream errors are returned as errors.
+    pub fn apply(&mut self, event: AnthropicStreamEvent) -> Result<()> {
+        match event {
+            AnthropicStreamEvent::MessageStart(start) => self.message = Some(start.message),
+            AnthropicStreamEvent::ContentBlockStart(start) => {
+                let message = started(&mut self.message)?;
+                let index = start.index as usize;
+                if message.content.len() <= index {
+                    let empty = AnthropicContentBlock::Text { text: String::new(), citations: None };
+                    message.content.resize(index + 1, empty);
+                    self.partial_json.resize(index + 1, String::new());
+                }
+                message.content[index] = start.content_block;
+            }
+            AnthropicStreamEvent::ContentBlockDelta(delta) => {
+                let index = delta.index as usize;
+                let message = started(&mut self.message)?;
+                match (message.content.get_mut(index), delta.delta) {
+                    (_, AnthropicDelta::Unknown) => {}
+                    (Some(AnthropicContentBlock::Text { text, .. }), AnthropicDelta::TextDelta { text: fragment }) => {
+                        text.push_str(&fragment);
+                    }
+                    (
+                        Some(AnthropicContentBlock::Text { citations, .. }),
+                        AnthropicDelta::CitationsDelta { citation },
+                    ) => {
+                        citations.get_or_insert_with(Vec::new).push(citation);
+                    }
+                    (Some(AnthropicContentBlock::ToolUse { .. }), AnthropicDelta::InputJsonDelta { partial_json }) => {
+                        self.partial_json[index].push_str(&partial_json);
+                    }
+                    _ => return Err(invalid_stream("delta does not match its content block")),
+                }
+            }
+            AnthropicStreamEvent::ContentBlockStop(stop) => {
+                let index = stop.index as usize;
+                let json = self.partial_json.get_mut(index).map(std::mem::take).unwrap_or_default();
+                if let Some(AnthropicContentBlock::ToolUse { input, .. }) =
+                    started(&mut self.message)?.content.get_mut(index)
+                {
+                    if !json.is_empty() {
+                        *input = serde_json::from_str(&json)
+                            .map_err(|e| invalid_stream(&format!("invalid tool input JSON: {}", e)))?;
+                    }
+                }
+            }
+            AnthropicStreamEvent::MessageDelta(delta) => {
+                let message = started(&mut self.message)?;
+                message.stop_reason = delta.delta.stop_reason;
+                message.stop_sequence = delta.delta.stop_sequence;
+                message.usage.output_tokens = delta.usage.output_tokens;
+            }
+            AnthropicStreamEvent::MessageStop | AnthropicStreamEvent::Ping => {}
+            AnthropicStreamEvent::Error(error) => {
+                return Err(SimpleAgentsError::Provider(error.into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Finish accumulation, returning the complete message.
+    pub fn finish(self) -> Result<AnthropicCompletionResponse> {
+        self.message.ok_or_else(|| invalid_stream("stream ended before message_start"))
+    }
+}
+
+fn started(message: &mut Option<AnthropicCompletionResponse>) -> Result<&mut AnthropicCompletionResponse> {
+    message.as_mut().ok_or_else(|| invalid_stream("event received before message_start"))
+}
+
+fn invalid_stream(reason: &str) -> SimpleAgentsError {
+    SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+        "Invalid Anthropic stream: {}",
+        reason
+    )))
+}
+
+/// Anthropic provider that always uses the streaming API.
+///
+/// [`Provider::execute`] consumes the event stream and reassembles the full
+/// message, so callers of the non-streaming interface still receive
+/// in-stream errors such as `overloaded_error`. Use
+/// [`AnthropicStreamingProvider::stream_events`] for the typed events.
+#[derive(Debug, Clone)]
+pub struct AnthropicStreamingProvider {
+    inner: AnthropicProvider,
+}
+
+impl AnthropicStreamingProvider {
+    /// Create a streaming provider with default configuration
+    pub fn new(api_key: ApiKey) -> Result<Self> {
+        Ok(Self::from_provider(AnthropicProvider::new(api_key)?))
+    }
+
+    /// Wrap an existing Anthropic provider
+    pub fn from_provider(inner: AnthropicProvider) -> Self {
+        Self { inner }
+    }
+
+    /// Get the wrapped provider
+    pub fn inner(&self) -> &AnthropicProvider {
+        &self.inner
+    }
+
+    /// Stream typed events for a request built by `transform_request`.
+    pub async fn stream_events(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<impl Stream<Item = Result<AnthropicStreamEvent>> + Send + Unpin> {
+        self.inner.stream_events(req).await
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicStreamingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let mut provider_request = self.inner.transform_request(req)?;
+        provider_request.body["stream"] = serde_json::Value::Bool(true);
+        Ok(provider_request)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        let mut events = self.stream_events(req).await?;
+        let mut accumulator = AnthropicMessageAccumulator::new();
+        while let Some(event) = events.next().await {
+            accumulator.apply(event?)?;
+        }
+
+        let body = serde_json::to_value(accumulator.finish()?)?;
+        Ok(ProviderResponse::new(200, body))
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE_START: &str = r#"{"type": "message_start", "message": {"id": "msg_1nZdL29xx5MUA1yADyHTEsnR8uuvGzszyY", "type": "message", "role": "assistant", "content": [], "model": "claude-3-5-sonnet-20241022", "stop_reason": null, "stop_sequence": null, "usage": {"input_tokens": 25, "output_tokens": 1}}}"#;
+    const TEXT_BLOCK_START: &str = r#"{"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}"#;
+    const TOOL_BLOCK_START: &str = r#"{"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_01T1x1fJ34qAmk2tNTrN7Up6", "name": "get_weather", "input": {}}}"#;
+    const PING: &str = r#"{"type": "ping"}"#;
+    const TEXT_DELTA: &str = r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hello"}}"#;
+    const JSON_DELTA: &str = r#"{"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"location\": \"San Fra"}}"#;
+    const BLOCK_STOP: &str = r#"{"type": "content_block_stop", "index": 0}"#;
+    const MESSAGE_DELTA: &str = r#"{"type": "message_delta", "delta": {"stop_reason": "end_turn", "stop_sequence": null}, "usage": {"output_tokens": 15}}"#;
+    const MESSAGE_STOP: &str = r#"{"type": "message_stop"}"#;
+    const ERROR: &str = r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#;
+
+    fn parse(json: &str) -> AnthropicStreamEvent {
+        AnthropicStreamEvent::parse(json).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_parse_message_start() {
+        match parse(MESSAGE_START) {
+            AnthropicStreamEvent::MessageStart(start) => {
+                assert_eq!(start.message.id, "msg_1nZdL29xx5MUA1yADyHTEsnR8uuvGzszyY");
+                assert_eq!(start.message.model, "claude-3-5-sonnet-20241022");
+                assert!(start.message.content.is_empty());
+                assert_eq!(start.message.stop_reason, None);
+                assert_eq!(start.message.usage.input_tokens, 25);
+                assert_eq!(start.message.usage.output_tokens, 1);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_content_block_start_text() {
+        assert_eq!(
+            parse(TEXT_BLOCK_START),
+            AnthropicStreamEvent::ContentBlockStart(AnthropicContentBlockStart {
+                index: 0,
+                content_block: AnthropicContentBlock::Text { text: String::new(), citations: None },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_content_block_start_tool_use() {
+        assert_eq!(
+            parse(TOOL_BLOCK_START),
+            AnthropicStreamEvent::ContentBlockStart(AnthropicContentBlockStart {
+                index: 1,
+                content_block: AnthropicContentBlock::ToolUse {
+                    id: "toolu_01T1x1fJ34qAmk2tNTrN7Up6".to_string(),
+                    name: "get_weather".to_string(),
+                    input: serde_json::json!({}),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_content_block_delta_text() {
+        assert_eq!(
+            parse(TEXT_DELTA),
+            AnthropicStreamEvent::ContentBlockDelta(AnthropicContentBlockDelta {
+                index: 0,
+                delta: AnthropicDelta::TextDelta { text: "Hello".to_string() },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_content_block_delta_input_json() {
+        assert_eq!(
+            parse(JSON_DELTA),
+            AnthropicStreamEvent::ContentBlockDelta(AnthropicContentBlockDelta {
+                index: 1,
+                delta: AnthropicDelta::InputJsonDelta {
+                    partial_json: "{\"location\": \"San Fra".to_string(),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_content_block_stop() {
+        assert_eq!(
+            parse(BLOCK_STOP),
+            AnthropicStreamEvent::ContentBlockStop(AnthropicContentBlockStop { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_parse_message_delta() {
+        assert_eq!(
+            parse(MESSAGE_DELTA),
+            AnthropicStreamEvent::MessageDelta(AnthropicMessageDelta {
+                delta: AnthropicMessageDeltaBody {
+                    stop_reason: Some("end_turn".to_string()),
+                    stop_sequence: None,
+                },
+                usage: AnthropicUsage {
+                    output_tokens: 15,
+                    ..Default::default()
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_message_stop_and_ping() {
+        assert_eq!(parse(MESSAGE_STOP), AnthropicStreamEvent::MessageStop);
+        assert_eq!(parse(PING), AnthropicStreamEvent::Ping);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let event = parse(ERROR);
+        assert_eq!(
+            event,
+            AnthropicStreamEvent::Error(AnthropicError::Api {
+                status: 0,
+                error_type: "overloaded_error".to_string(),
+                message: "Overloaded".to_string(),
+                retry_after: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_and_malformed() {
+        assert_eq!(AnthropicStreamEvent::parse(r#"{"type": "future_event"}"#).unwrap(), None);
+        assert!(AnthropicStreamEvent::parse("not json").is_err());
+        assert!(AnthropicStreamEvent::parse(r#"{"type": "content_block_stop"}"#).is_err());
+    }
+
+    #[test]
+    fn test_from_sse_skips_empty_data() {
+        let event = SseEvent {
+            event: Some("ping".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(AnthropicStreamEvent::from_sse(&event).unwrap(), None);
+    }
+
+    #[test]
+    fn test_chunk_mapper() {
+        let mut mapper = AnthropicChunkMapper::new();
+
+        let first = mapper.map(parse(MESSAGE_START)).unwrap().unwrap();
+        assert_eq!(first.id, "msg_1nZdL29xx5MUA1yADyHTEsnR8uuvGzszyY");
+        assert_eq!(first.choices[0].delta.role, Some(Role::Assistant));
+
+        assert!(mapper.map(parse(TEXT_BLOCK_START)).is_none());
+        assert!(mapper.map(parse(PING)).is_none());
+
+        let text = mapper.map(parse(TEXT_DELTA)).unwrap().unwrap();
+        assert_eq!(text.model, "claude-3-5-sonnet-20241022");
+        assert_eq!(text.choices[0].delta.content.as_deref(), Some("Hello"));
+
+        let last = mapper.map(parse(MESSAGE_DELTA)).unwrap().unwrap();
+        assert_eq!(last.choices[0].finish_reason, Some(FinishReason::Stop));
+
+        assert!(mapper.map(parse(MESSAGE_STOP)).is_none());
+        assert!(mapper.map(parse(ERROR)).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_accumulator() {
+        let tool_delta_rest = r#"{"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "ncisco\"}"}}"#;
+        let tool_stop = r#"{"type": "content_block_stop", "index": 1}"#;
+
+        let mut accumulator = AnthropicMessageAccumulator::new();
+        for json in [
+            MESSAGE_START, TEXT_BLOCK_START, PING, TEXT_DELTA, TEXT_DELTA, BLOCK_STOP,
+            TOOL_BLOCK_START, JSON_DELTA, tool_delta_rest, tool_stop, MESSAGE_DELTA, MESSAGE_STOP,
+        ] {
+            accumulator.apply(parse(json)).unwrap();
+        }
+
+        let message = accumulator.finish().unwrap();
+        assert_eq!(message.text(), "HelloHello");
+        assert_eq!(message.stop_reason.as_deref(), Some("end_turn"));
+        assert_eq!(message.usage.input_tokens, 25);
+        assert_eq!(message.usage.output_tokens, 15);
+        assert!(matches!(
+            &message.content[1],
+            AnthropicContentBlock::ToolUse { input, .. } if input["location"] == "San Francisco"
+        ));
+    }
+
+    #[test]
+    fn test_thinking_blocks_are_skipped() {
+        let thinking_start = r#"{"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}"#;
+        let thinking_delta = r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Let me think"}}"#;
+        let signature_delta = r#"{"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "EqQBCgIYAhIM"}}"#;
+        let text_start = r#"{"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}"#;
+        let text_delta = r#"{"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Hello"}}"#;
+        let events = [
+            MESSAGE_START, thinking_start, thinking_delta, signature_delta, BLOCK_STOP,
+            text_start, text_delta, MESSAGE_DELTA, MESSAGE_STOP,
+        ];
+
+        let mut mapper = AnthropicChunkMapper::new();
+        let mut accumulator = AnthropicMessageAccumulator::new();
+        let mut content = String::new();
+        for json in events {
+            if let Some(chunk) = mapper.map(parse(json)) {
+                content.extend(chunk.unwrap().choices[0].delta.content.clone());
+            }
+            accumulator.apply(parse(json)).unwrap();
+        }
+
+        assert_eq!(content, "Hello");
+        let message = accumulator.finish().unwrap();
+        assert_eq!(message.content[0], AnthropicContentBlock::Unknown);
+        assert_eq!(message.text(), "Hello");
+    }
+
+    #[test]
+    fn test_accumulator_errors() {
+        let mut accumulator = AnthropicMessageAccumulator::new();
+        assert!(accumulator.apply(parse(TEXT_DELTA)).is_err());
+        assert!(accumulator.apply(parse(ERROR)).is_err());
+        assert!(AnthropicMessageAccumulator::new().finish().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_provider_against_mock_server() {
+        let body: String = [MESSAGE_START, TEXT_BLOCK_START, TEXT_DELTA, BLOCK_STOP, MESSAGE_DELTA, MESSAGE_STOP]
+            .iter()
+            .map(|data| format!("event: x\ndata: {}\n\n", data))
+            .collect();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"stream": true})))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = AnthropicStreamingProvider::from_provider(
+            AnthropicProvider::with_base_url(api_key, server.url()).unwrap(),
+        );
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Hi"))
+            .build()
+            .unwrap();
+
+        let response = provider.complete(&request).await.unwrap();
+        assert_eq!(response.content(), Some("Hello"));
+        assert_eq!(response.usage.total_tokens, 40);
+
+        let provider_request = provider.transform_request(&request).unwrap();
+        let chunks: Vec<_> = provider.execute_stream(provider_request).await.unwrap().collect().await;
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].as_ref().unwrap().choices[0].delta.content.as_deref(), Some("Hello"));
+
+        mock.assert_async().await;
+    }
+}
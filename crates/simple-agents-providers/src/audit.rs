@@ -0,0 +1,379 @@
+//! SQLite audit trail of completions.
+//!
+//! [`AuditLog`] stores every request/response pair in one table, with the
+//! model, provider, fingerprint and token counts in their own columns for
+//! querying. Requests and responses are kept as JSON, so entries read back
+//! are the values that were recorded.
+//!
+//! Requires the `sqlite-audit` feature.
+//!
+//! # Example
+//! ```no_run
+//! use simple_agents_providers::audit::AuditLog;
+//! use simple_agents_types::prelude::*;
+//! use std::path::Path;
+//!
+//! # async fn example(provider: impl Provider, request: CompletionRequest) -> Result<()> {
+//! let audit = AuditLog::new(Path::new("audit.sqlite3"))?;
+//! let response = provider.complete(&request).await?;
+//! let id = audit.record(&request, &response)?;
+//!
+//! for entry in audit.query_by_model("gpt-4o")? {
+//!     println!("#{} {} tokens", entry.id, entry.response.usage.total_tokens);
+//! }
+//! audit.export_csv(std::fs::File::create("audit.csv").unwrap())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use rusqlite::{params, Connection, OptionalExtension};
+use simple_agents_types::prelude::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS completions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at INTEGER NOT NULL,
+    provider TEXT,
+    model TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    prompt_tokens INTEGER NOT NULL,
+    completion_tokens INTEGER NOT NULL,
+    request TEXT NOT NULL,
+    response TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS completions_model ON completions (model);
+";
+
+const COLUMNS: &str =
+    "id, recorded_at, provider, model, fingerprint, prompt_tokens, completion_tokens, request, response";
+
+/// One recorded request/response pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// Row ID, increasing in recording order
+    pub id: u64,
+    /// When the pair was recorded, in seconds since the Unix epoch
+    pub recorded_at: u64,
+    /// Provider that answered, from the response
+    pub provider: Option<String>,
+    /// Model requested
+    pub model: String,
+    /// [`CompletionRequest::fingerprint`] of the request
+    pub fingerprint: String,
+    /// The request
+    pub request: CompletionRequest,
+    /// The response
+    pub response: CompletionResponse,
+}
+
+/// Audit log backed by a SQLite database.
+///
+/// Calls block on SQLite; a connection is shared behind a mutex, so one
+/// log can be used from several tasks.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    connection: Mutex<Connection>,
+}
+
+impl AuditLog {
+    /// Open (or create) the database at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Cache`] if the database cannot be
+    /// opened or its table created.
+    pub fn new(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path).map_err(|e| database_error(path, e))?;
+        connection.execute_batch(SCHEMA).map_err(|e| database_error(path, e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Store `req` and `resp`, returning the new row ID.
+    pub fn record(&self, req: &CompletionRequest, resp: &CompletionResponse) -> Result<u64> {
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let connection = self.connection();
+        connection
+            .execute(
+                "INSERT INTO completions (recorded_at, provider, model, fingerprint, prompt_tokens, \
+                 completion_tokens, request, response) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    recorded_at as i64,
+                    resp.provider,
+                    req.model,
+                    req.fingerprint(),
+                    resp.usage.prompt_tokens,
+                    resp.usage.completion_tokens,
+                    serde_json::to_string(req)?,
+                    serde_json::to_string(resp)?,
+                ],
+            )
+            .map_err(|e| database_error(&self.path, e))?;
+        Ok(connection.last_insert_rowid() as u64)
+    }
+
+    /// The entry with row ID `id`.
+    pub fn get(&self, id: u64) -> Result<Option<AuditEntry>> {
+        let sql = format!("SELECT {} FROM completions WHERE id = ?1", COLUMNS);
+        let row = self
+            .connection()
+            .query_row(&sql, params![id as i64], Row::read)
+            .optional()
+            .map_err(|e| database_error(&self.path, e))?;
+        row.map(Row::into_entry).transpose()
+    }
+
+    /// Entries requested for `model`, oldest first.
+    pub fn query_by_model(&self, model: &str) -> Result<Vec<AuditEntry>> {
+        let sql = format!("SELECT {} FROM completions WHERE model = ?1 ORDER BY id", COLUMNS);
+        self.rows(&sql, params![model])?.into_iter().map(Row::into_entry).collect()
+    }
+
+    /// Number of recorded entries.
+    pub fn len(&self) -> Result<u64> {
+        let count: i64 = self
+            .connection()
+            .query_row("SELECT COUNT(*) FROM completions", [], |row| row.get(0))
+            .map_err(|e| database_error(&self.path, e))?;
+        Ok(count as u64)
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Write every entry as CSV, oldest first.
+    ///
+    /// Columns are `id`, `recorded_at`, `provider`, `model`,
+    /// `fingerprint`, `prompt_tokens`, `completion_tokens`, `request` and
+    /// `response`, after a header row; the last two hold the JSON as
+    /// recorded. Fields are quoted as in RFC 4180 when needed.
+    pub fn export_csv(&self, mut writer: impl Write) -> Result<()> {
+        let sql = format!("SELECT {} FROM completions ORDER BY id", COLUMNS);
+        let rows = self.rows(&sql, [])?;
+
+        let io_error = |e: std::io::Error| {
+            SimpleAgentsError::Cache(format!("Failed to write audit CSV: {}", e))
+        };
+        writeln!(writer, "{}", COLUMNS.replace(' ', "")).map_err(io_error)?;
+        for row in rows {
+            let fields = [
+                row.id.to_string(),
+                row.recorded_at.to_string(),
+                row.provider.unwrap_or_default(),
+                row.model,
+                row.fingerprint,
+                row.prompt_tokens.to_string(),
+                row.completion_tokens.to_string(),
+                row.request,
+                row.response,
+            ];
+            let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            writeln!(writer, "{}", line.join(",")).map_err(io_error)?;
+        }
+        writer.flush().map_err(io_error)
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // A panic while holding the lock cannot leave SQLite inconsistent
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn rows(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Row>> {
+        let connection = self.connection();
+        let database_error = |e| database_error(&self.path, e);
+        let mut statement = connection.prepare(sql).map_err(database_error)?;
+        let rows = statement.query_map(params, Row::read).map_err(database_error)?;
+        rows.collect::<std::result::Result<_, _>>().map_err(database_error)
+    }
+}
+
+/// A row as stored, before the JSON columns are parsed
+struct Row {
+    id: i64,
+    recorded_at: i64,
+    provider: Option<String>,
+    model: String,
+    fingerprint: String,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    request: String,
+    response: String,
+}
+
+impl Row {
+    fn read(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            recorded_at: row.get(1)?,
+            provider: row.get(2)?,
+            model: row.get(3)?,
+            fingerprint: row.get(4)?,
+            prompt_tokens: row.get(5)?,
+            completion_tokens: row.get(6)?,
+            request: row.get(7)?,
+            response: row.get(8)?,
+        })
+    }
+
+    fn into_entry(self) -> Result<AuditEntry> {
+        Ok(AuditEntry {
+            id: self.id as u64,
+            recorded_at: self.recorded_at as u64,
+            provider: self.provider,
+            model: self.model,
+            fingerprint: self.fingerprint,
+            request: serde_json::from_str(&self.request)?,
+            response: serde_json::from_str(&self.response)?,
+        })
+    }
+}
+
+fn database_error(path: &Path, error: rusqlite::Error) -> SimpleAgentsError {
+    SimpleAgentsError::Cache(format!("Audit log {}: {}", path.display(), error))
+}
+
+/// `field` quoted for CSV if it contains a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Database file removed when dropped
+    struct TempDatabase(PathBuf);
+
+    impl TempDatabase {
+        fn new() -> Self {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+            let name = format!("simple-agents-audit-{}-{}.sqlite3", std::process::id(), nanos);
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempDatabase {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn exchange(model: &str, prompt: &str, answer: &str) -> (CompletionRequest, CompletionResponse) {
+        let request = CompletionRequest::builder()
+            .model(model)
+            .message(Message::user(prompt))
+            .build()
+            .unwrap();
+        let response = CompletionResponse {
+            id: "resp-1".to_string(),
+            model: model.to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(answer),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(12, 3),
+            created: None,
+            created_synthesized: false,
+            provider: Some("openai".to_string()),
+        };
+        (request, response)
+    }
+
+    #[test]
+    fn test_record_and_query_by_model() {
+        let database = TempDatabase::new();
+        let audit = AuditLog::new(&database.0).unwrap();
+        assert!(audit.is_empty().unwrap());
+
+        let (first, first_response) = exchange("gpt-4o", "Hi", "Hello!");
+        let (other, other_response) = exchange("gpt-4o-mini", "Hi", "Hey");
+        let (second, second_response) = exchange("gpt-4o", "Bye", "Goodbye!");
+        let first_id = audit.record(&first, &first_response).unwrap();
+        audit.record(&other, &other_response).unwrap();
+        let second_id = audit.record(&second, &second_response).unwrap();
+        assert!(second_id > first_id);
+
+        let entries = audit.query_by_model("gpt-4o").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, first_id);
+        assert_eq!(entries[0].request, first);
+        assert_eq!(entries[0].response, first_response);
+        assert_eq!(entries[0].fingerprint, first.fingerprint());
+        assert_eq!(entries[0].provider.as_deref(), Some("openai"));
+        assert_eq!(entries[1].request, second);
+        assert!(audit.query_by_model("claude-3").unwrap().is_empty());
+
+        assert_eq!(audit.get(second_id).unwrap().unwrap().response, second_response);
+        assert_eq!(audit.get(999).unwrap(), None);
+    }
+
+    #[test]
+    fn test_entries_survive_reopening() {
+        let database = TempDatabase::new();
+        let (request, response) = exchange("gpt-4o", "Hi", "Hello!");
+        {
+            let audit = AuditLog::new(&database.0).unwrap();
+            audit.record(&request, &response).unwrap();
+        }
+
+        let audit = AuditLog::new(&database.0).unwrap();
+        assert_eq!(audit.len().unwrap(), 1);
+        assert_eq!(audit.query_by_model("gpt-4o").unwrap()[0].response, response);
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let database = TempDatabase::new();
+        let audit = AuditLog::new(&database.0).unwrap();
+        let (request, response) = exchange("gpt-4o", "Say \"hi\",\nplease", "hi");
+        audit.record(&request, &response).unwrap();
+
+        let mut csv = Vec::new();
+        audit.export_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let (header, row) = csv.split_once('\n').unwrap();
+        assert_eq!(
+            header,
+            "id,recorded_at,provider,model,fingerprint,prompt_tokens,completion_tokens,request,response"
+        );
+        assert!(row.starts_with("1,"), "{}", row);
+        assert!(row.contains(&format!(",openai,gpt-4o,{},12,3,\"", request.fingerprint())), "{}", row);
+
+        // The request JSON is quoted, with its quotes doubled
+        let request_json = serde_json::to_string(&request).unwrap();
+        assert!(row.contains(&csv_field(&request_json)), "{}", row);
+        assert!(csv_field(&request_json).contains(r#"Say \""hi\"",\nplease"#));
+    }
+
+    #[test]
+    fn test_unopenable_database() {
+        let err = AuditLog::new(Path::new("/nonexistent-dir/audit.sqlite3")).unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Cache(_)), "{:?}", err);
+        assert!(err.to_string().contains("/nonexistent-dir/audit.sqlite3"), "{}", err);
+    }
+}
@@ -0,0 +1,206 @@
+//! Microsoft Entra ID (Azure AD) token authentication.
+
+use async_trait::async_trait;
+use base64::Engine;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use simple_agents_types::prelude::*;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type TokenRefreshFn = dyn Fn() -> BoxFuture<'static, Result<String>> + Send + Sync;
+
+/// Azure AD access tokens for [`AzureOpenAIProvider`](super::AzureOpenAIProvider),
+/// refreshed shortly before they expire.
+///
+/// The token is a JWT obtained from Microsoft Entra ID (e.g. with MSAL or
+/// the Azure CLI) for the `https://cognitiveservices.azure.com/.default`
+/// scope. Before each request the `exp` claim is checked, and the refresh
+/// function is called when less than [`REFRESH_MARGIN`](Self::REFRESH_MARGIN)
+/// remains. Tokens without a readable `exp` claim are used until the API
+/// rejects them, at which point they are refreshed and the request retried
+/// once.
+///
+/// Concurrent requests that find the token expiring share a single refresh.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::azure::{AzureAdAuth, AzureOpenAIProvider};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn acquire_token_with_msal() -> Result<String> { unimplemented!() }
+/// # fn example() -> Result<()> {
+/// let auth = AzureAdAuth::new(|| async { acquire_token_with_msal().await });
+/// let provider = AzureOpenAIProvider::with_ad_auth("my-resource", "gpt-4o", "2024-06-01", auth)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AzureAdAuth {
+    token: Arc<RwLock<String>>,
+    refresh_fn: Box<TokenRefreshFn>,
+    /// Held while a refresh is in flight
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl AzureAdAuth {
+    /// Refresh tokens with less than this much lifetime left
+    pub const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+    /// Create auth that calls `refresh` for a new token when the current
+    /// one is about to expire.
+    ///
+    /// The first token is fetched lazily, on the first request, unless one
+    /// is supplied with [`with_token`](Self::with_token).
+    pub fn new<F, Fut>(refresh: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        Self {
+            token: Arc::new(RwLock::new(String::new())),
+            refresh_fn: Box::new(move || Box::pin(refresh())),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Start with an already acquired token (builder pattern).
+    pub fn with_token(self, token: impl Into<String>) -> Self {
+        *self.token.write().unwrap_or_else(|e| e.into_inner()) = token.into();
+        self
+    }
+
+    /// The current token, unless it is missing or about to expire.
+    fn usable_token(&self) -> Option<String> {
+        let token = self.token.read().unwrap_or_else(|e| e.into_inner());
+        (!token.is_empty() && !expires_within(&token, Self::REFRESH_MARGIN, SystemTime::now()))
+            .then(|| token.clone())
+    }
+}
+
+impl std::fmt::Debug for AzureAdAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let token = self.token.read().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("AzureAdAuth")
+            .field("expires_at", &jwt_expiry(&token))
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CredentialSource for AzureAdAuth {
+    async fn current_key(&self) -> Result<ApiKey> {
+        if let Some(token) = self.usable_token() {
+            return ApiKey::new(token);
+        }
+
+        let _refreshing = self.refreshing.lock().await;
+        // Another request may have refreshed while we waited
+        if let Some(token) = self.usable_token() {
+            return ApiKey::new(token);
+        }
+
+        let token = (self.refresh_fn)().await?;
+        let key = ApiKey::new(token.as_str())?;
+        *self.token.write().unwrap_or_else(|e| e.into_inner()) = token;
+        Ok(key)
+    }
+
+    fn cached_key(&self) -> Option<ApiKey> {
+        self.usable_token().and_then(|token| ApiKey::new(token).ok())
+    }
+
+    fn invalidate(&self) -> bool {
+        self.token.write().unwrap_or_else(|e| e.into_inner()).clear();
+        true
+    }
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    exp: Option<u64>,
+}
+
+/// Expiry time from the `exp` claim of a JWT.
+///
+/// The signature is not verified. Returns `None` if `token` is not a JWT
+/// or has no `exp` claim.
+pub fn jwt_expiry(token: &str) -> Option<SystemTime> {
+    let payload = token.split('.').nth(1)?;
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: Claims = serde_json::from_slice(&payload).ok()?;
+    UNIX_EPOCH.checked_add(Duration::from_secs(claims.exp?))
+}
+
+/// Whether `token` expires less than `margin` after `now`.
+///
+/// Tokens whose expiry cannot be read are assumed not to expire.
+pub fn expires_within(token: &str, margin: Duration, now: SystemTime) -> bool {
+    jwt_expiry(token).is_some_and(|expiry| expiry <= now + margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Unsigned JWT with the given claims
+    fn jwt(claims: serde_json::Value) -> String {
+        let encode = |value: &serde_json::Value| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string())
+        };
+        let header = serde_json::json!({"alg": "RS256", "typ": "JWT"});
+        format!("{}.{}.signature", encode(&header), encode(&claims))
+    }
+
+    fn jwt_expiring_at(time: SystemTime) -> String {
+        let exp = time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        jwt(serde_json::json!({"aud": "https://cognitiveservices.azure.com", "exp": exp}))
+    }
+
+    #[test]
+    fn test_jwt_expiry() {
+        let token = jwt(serde_json::json!({"exp": 1_700_000_000}));
+        assert_eq!(jwt_expiry(&token), Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+
+        assert_eq!(jwt_expiry(&jwt(serde_json::json!({"sub": "me"}))), None);
+        assert_eq!(jwt_expiry("not-a-jwt-0123456789abcdef"), None);
+        assert_eq!(jwt_expiry("a.!!!.c"), None);
+    }
+
+    #[test]
+    fn test_expires_within_margin() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let margin = AzureAdAuth::REFRESH_MARGIN;
+
+        assert!(expires_within(&jwt_expiring_at(now - Duration::from_secs(1)), margin, now));
+        assert!(expires_within(&jwt_expiring_at(now + Duration::from_secs(59)), margin, now));
+        assert!(!expires_within(&jwt_expiring_at(now + Duration::from_secs(61)), margin, now));
+        // Opaque tokens are used until rejected
+        assert!(!expires_within("opaque-token-0123456789abcdef", margin, now));
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_expiring_token_once() {
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        let auth = AzureAdAuth::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Ok(jwt_expiring_at(SystemTime::now() + Duration::from_secs(3600))) }
+        })
+        .with_token(jwt_expiring_at(SystemTime::now() + Duration::from_secs(30)));
+
+        assert!(auth.cached_key().is_none());
+        let key = auth.current_key().await.unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(auth.current_key().await.unwrap(), key);
+        assert_eq!(auth.cached_key(), Some(key));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        assert!(auth.invalidate());
+        auth.current_key().await.unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,722 @@
+//! Weighted failover across Azure OpenAI deployments.
+//!
+//! Azure OpenAI quotas are per deployment, key and region, so high-volume
+//! callers spread traffic over several of them. [`AzureOpenAIPool`] routes
+//! requests across a set of [`AzureTarget`]s by weight and takes a target
+//! out of rotation while it is throttled or failing.
+
+use super::AzureOpenAIProvider;
+use crate::openai::AuthScheme;
+use crate::retry::{classify, ErrorClass};
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// One Azure OpenAI deployment an [`AzureOpenAIPool`] can route to.
+#[derive(Debug, Clone)]
+pub struct AzureTarget {
+    endpoint: String,
+    deployment: String,
+    api_key: ApiKey,
+    weight: u32,
+    api_version: String,
+}
+
+impl AzureTarget {
+    /// Target `deployment` on the resource at `endpoint`
+    /// (e.g. `https://my-resource.openai.azure.com`), with weight 1.
+    pub fn new(endpoint: impl Into<String>, deployment: impl Into<String>, api_key: ApiKey) -> Self {
+        Self {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            deployment: deployment.into(),
+            api_key,
+            weight: 1,
+            api_version: AzureOpenAIProvider::DEFAULT_API_VERSION.to_string(),
+        }
+    }
+
+    /// Share of traffic relative to the other targets
+    ///
+    /// A target with weight 3 gets three times the requests of a target
+    /// with weight 1 while both are healthy.
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the `api-version` query parameter (default
+    /// [`AzureOpenAIProvider::DEFAULT_API_VERSION`])
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Resource endpoint
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Deployment name
+    pub fn deployment(&self) -> &str {
+        &self.deployment
+    }
+
+    /// Routing weight
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    /// URL chat completions are sent to
+    pub fn chat_url(&self) -> String {
+        super::chat_url(&self.endpoint, &self.deployment, &self.api_version)
+    }
+
+    fn provider(&self) -> Result<AzureOpenAIProvider> {
+        AzureOpenAIProvider::with_endpoint(
+            &self.endpoint,
+            &self.deployment,
+            &self.api_version,
+            Arc::new(self.api_key.clone()),
+            AuthScheme::ApiKey,
+        )
+    }
+}
+
+/// Health and usage of one target, as reported by [`AzureOpenAIPool::status`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AzureTargetStatus {
+    /// Resource endpoint
+    pub endpoint: String,
+    /// Deployment name
+    pub deployment: String,
+    /// Routing weight
+    pub weight: u32,
+    /// Request counts, latency and health
+    ///
+    /// Health is [`ProviderHealth::Unavailable`] while the target is cooling
+    /// down and [`ProviderHealth::Degraded`] once it is waiting for a
+    /// recovery probe to succeed.
+    pub metrics: ProviderMetrics,
+    /// Requests rejected with HTTP 429
+    pub rate_limited: u64,
+    /// Prompt tokens used by successful requests
+    pub prompt_tokens: u64,
+    /// Completion tokens used by successful requests
+    pub completion_tokens: u64,
+    /// Time until the target is probed again, while it is cooling down
+    pub available_in: Option<Duration>,
+}
+
+/// Routing state of one target.
+#[derive(Debug, Default)]
+struct TargetState {
+    /// Smooth weighted round-robin counter
+    current_weight: i64,
+    /// Out of rotation until then; once passed, the next request is a probe
+    cooldown_until: Option<Instant>,
+    /// Length of the last cooldown, doubled on each failed probe
+    cooldown: Duration,
+    /// A recovery probe is in flight
+    probing: bool,
+    metrics: ProviderMetrics,
+    rate_limited: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+impl TargetState {
+    fn health(&self, now: Instant) -> ProviderHealth {
+        match self.cooldown_until {
+            None => ProviderHealth::Healthy,
+            Some(until) if now < until => ProviderHealth::Unavailable,
+            Some(_) => ProviderHealth::Degraded,
+        }
+    }
+
+    fn record_latency(&mut self, latency: Duration) {
+        self.metrics.total_requests += 1;
+        let n = u32::try_from(self.metrics.total_requests).unwrap_or(u32::MAX);
+        self.metrics.avg_latency = (self.metrics.avg_latency * (n - 1) + latency) / n;
+    }
+}
+
+/// Provider that spreads requests over several Azure OpenAI deployments.
+///
+/// Requests are routed by smooth weighted round-robin over the targets in
+/// rotation. A target that answers with a rate limit (429), a server error
+/// (5xx), a timeout or a network failure is taken out of rotation for a
+/// cooldown and the request moves on to the next target. The cooldown
+/// starts at [`DEFAULT_BASE_COOLDOWN`](Self::DEFAULT_BASE_COOLDOWN), is
+/// raised to the rate limit's `retry_after` hint if any, and doubles (up to the
+/// maximum) each time the target fails again.
+///
+/// Once a cooldown expires the target receives a single probe request; it
+/// rejoins the rotation if the probe succeeds and cools down again if it
+/// fails. Errors classified as [`ErrorClass::FailoverToNextProvider`], such
+/// as a rejected key, move on to the next target without a cooldown, and
+/// [`ErrorClass::Fatal`] errors are returned at once.
+///
+/// Only [`Provider::complete`] is routed. The lower-level hooks, streaming
+/// included, delegate to the first target, since a [`ProviderRequest`] is specific to the target
+/// that built it.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::azure::{AzureOpenAIPool, AzureTarget};
+/// use simple_agents_types::prelude::*;
+///
+/// let east_key = ApiKey::new("0123456789abcdef0123456789abcdef").unwrap();
+/// let west_key = ApiKey::new("fedcba9876543210fedcba9876543210").unwrap();
+/// let pool = AzureOpenAIPool::new(vec![
+///     AzureTarget::new("https://eastus.openai.azure.com", "gpt-4o", east_key).with_weight(3),
+///     AzureTarget::new("https://westeurope.openai.azure.com", "gpt-4o", west_key),
+/// ])
+/// .unwrap();
+/// # let _ = pool;
+/// ```
+pub struct AzureOpenAIPool {
+    targets: Vec<(AzureTarget, AzureOpenAIProvider)>,
+    state: Mutex<Vec<TargetState>>,
+    base_cooldown: Duration,
+    max_cooldown: Duration,
+}
+
+impl std::fmt::Debug for AzureOpenAIPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzureOpenAIPool")
+            .field("targets", &self.targets.iter().map(|(target, _)| target).collect::<Vec<_>>())
+            .field("base_cooldown", &self.base_cooldown)
+            .field("max_cooldown", &self.max_cooldown)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Outcome of picking a target for the next attempt.
+enum Selection {
+    /// Send to this target; `probe` if it is being tested for recovery
+    Target { index: usize, probe: bool },
+    /// Every untried target is cooling down; the first is back after this
+    CoolingDown(Option<Duration>),
+}
+
+impl AzureOpenAIPool {
+    /// Initial cooldown of a failing target
+    pub const DEFAULT_BASE_COOLDOWN: Duration = Duration::from_secs(10);
+
+    /// Longest cooldown of a repeatedly failing target
+    pub const DEFAULT_MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+    /// Create a pool routing across `targets`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `targets` is empty, a target
+    /// has weight 0, or a target's endpoint is not an http(s) URL.
+    pub fn new(targets: Vec<AzureTarget>) -> Result<Self> {
+        if targets.is_empty() {
+            return Err(SimpleAgentsError::Config(
+                "AzureOpenAIPool needs at least one target".to_string(),
+            ));
+        }
+
+        let targets = targets
+            .into_iter()
+            .map(|target| {
+                if target.weight == 0 {
+                    return Err(SimpleAgentsError::Config(format!(
+                        "Azure target {}/{} has weight 0",
+                        target.endpoint, target.deployment
+                    )));
+                }
+                let provider = target.provider()?;
+                Ok((target, provider))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            state: Mutex::new(targets.iter().map(|_| TargetState::default()).collect()),
+            targets,
+            base_cooldown: Self::DEFAULT_BASE_COOLDOWN,
+            max_cooldown: Self::DEFAULT_MAX_COOLDOWN,
+        })
+    }
+
+    /// Set the initial and maximum cooldown of a failing target.
+    pub fn with_cooldown(mut self, base: Duration, max: Duration) -> Self {
+        self.base_cooldown = base;
+        self.max_cooldown = max.max(base);
+        self
+    }
+
+    /// The targets, in the order given to [`new`](Self::new).
+    pub fn targets(&self) -> impl Iterator<Item = &AzureTarget> {
+        self.targets.iter().map(|(target, _)| target)
+    }
+
+    /// Health and usage of every target, in the order given to
+    /// [`new`](Self::new).
+    pub fn status(&self) -> Vec<AzureTargetStatus> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.targets
+            .iter()
+            .zip(state.iter())
+            .map(|((target, _), state)| AzureTargetStatus {
+                endpoint: target.endpoint.clone(),
+                deployment: target.deployment.clone(),
+                weight: target.weight,
+                metrics: ProviderMetrics {
+                    health: state.health(now),
+                    ..state.metrics
+                },
+                rate_limited: state.rate_limited,
+                prompt_tokens: state.prompt_tokens,
+                completion_tokens: state.completion_tokens,
+                available_in: state
+                    .cooldown_until
+                    .filter(|until| now < *until)
+                    .map(|until| until - now),
+            })
+            .collect()
+    }
+
+    fn primary(&self) -> &AzureOpenAIProvider {
+        &self.targets[0].1
+    }
+
+    /// Pick the next target by smooth weighted round-robin, skipping
+    /// `tried` targets, targets cooling down and targets with a probe in
+    /// flight.
+    fn select(&self, tried: &[usize]) -> Selection {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut total = 0;
+        let mut best: Option<(usize, i64)> = None;
+        let mut back_in: Option<Duration> = None;
+        for (index, (target, _)) in self.targets.iter().enumerate() {
+            if tried.contains(&index) {
+                continue;
+            }
+            let entry = &mut state[index];
+            match entry.health(now) {
+                ProviderHealth::Unavailable => {
+                    let remaining = entry.cooldown_until.map(|until| until - now).unwrap_or_default();
+                    back_in = Some(back_in.map_or(remaining, |d| d.min(remaining)));
+                    continue;
+                }
+                ProviderHealth::Degraded if entry.probing => continue,
+                _ => {}
+            }
+            entry.current_weight += i64::from(target.weight);
+            total += i64::from(target.weight);
+            if !matches!(best, Some((_, weight)) if weight >= entry.current_weight) {
+                best = Some((index, entry.current_weight));
+            }
+        }
+
+        match best {
+            Some((index, _)) => {
+                let entry = &mut state[index];
+                entry.current_weight -= total;
+                let probe = entry.cooldown_until.is_some();
+                entry.probing |= probe;
+                Selection::Target { index, probe }
+            }
+            None => Selection::CoolingDown(back_in),
+        }
+    }
+
+    fn record_success(&self, index: usize, probe: bool, latency: Duration, usage: &Usage) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = &mut state[index];
+        entry.record_latency(latency);
+        entry.metrics.successful_requests += 1;
+        entry.prompt_tokens += u64::from(usage.prompt_tokens);
+        entry.completion_tokens += u64::from(usage.completion_tokens);
+        // A request sent before the target started cooling down says
+        // nothing about whether it has recovered
+        if probe || entry.cooldown_until.is_none() {
+            entry.cooldown_until = None;
+            entry.cooldown = Duration::ZERO;
+            entry.probing = false;
+        }
+    }
+
+    /// Record a failure, starting a cooldown if the target is at fault.
+    fn record_failure(&self, index: usize, probe: bool, latency: Duration, class: &ErrorClass) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = &mut state[index];
+        entry.record_latency(latency);
+        entry.metrics.failed_requests += 1;
+        if probe {
+            entry.probing = false;
+        }
+
+        let retry_after = match class {
+            ErrorClass::RateLimited { retry_after } => {
+                entry.rate_limited += 1;
+                *retry_after
+            }
+            ErrorClass::RetryableSameProvider => None,
+            ErrorClass::FailoverToNextProvider | ErrorClass::Fatal => return,
+        };
+
+        let now = Instant::now();
+        if entry.cooldown_until.is_some_and(|until| now < until) {
+            // Already cooling down because of a concurrent request
+            return;
+        }
+        entry.cooldown = if entry.cooldown.is_zero() {
+            self.base_cooldown
+        } else {
+            (entry.cooldown * 2).min(self.max_cooldown)
+        };
+        entry.cooldown_until = Some(now + entry.cooldown.max(retry_after.unwrap_or_default()));
+    }
+}
+
+#[async_trait]
+impl Provider for AzureOpenAIPool {
+    fn name(&self) -> &str {
+        "azure-openai"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.primary().transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.primary().execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.primary().transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.primary().retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.primary().capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.primary().timeout()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.primary().sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.primary().model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.primary().pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.primary().execute_stream(req).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        req.ensure_not_streaming()?;
+
+        let mut tried = Vec::new();
+        let mut last_error = None;
+
+        loop {
+            let (index, probe) = match self.select(&tried) {
+                Selection::Target { index, probe } => (index, probe),
+                Selection::CoolingDown(back_in) => {
+                    return Err(last_error.unwrap_or_else(|| {
+                        ProviderError::RateLimit { retry_after: back_in }.into()
+                    }));
+                }
+            };
+            tried.push(index);
+
+            let (target, provider) = &self.targets[index];
+            let started = Instant::now();
+            match provider.complete(req).await {
+                Ok(response) => {
+                    self.record_success(index, probe, started.elapsed(), &response.usage);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    let class = classify(&e);
+                    self.record_failure(index, probe, started.elapsed(), &class);
+                    if !class.allows_failover() {
+                        return Err(e);
+                    }
+                    tracing::warn!(
+                        endpoint = %target.endpoint,
+                        deployment = %target.deployment,
+                        error = %e,
+                        "Azure target failed, trying next"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT_RESPONSE: &str = r#"{
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1700000000,
+        "model": "gpt-4o",
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4}
+    }"#;
+
+    const PATH: &str = "/openai/deployments/gpt-4o/chat/completions?api-version=2024-06-01";
+
+    fn key() -> ApiKey {
+        ApiKey::new("0123456789abcdef0123456789abcdef").unwrap()
+    }
+
+    fn request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("gpt-4o")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap()
+    }
+
+    async fn healthy_server(hits: usize) -> (mockito::ServerGuard, mockito::Mock) {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", PATH)
+            .match_header("api-key", "0123456789abcdef0123456789abcdef")
+            .with_status(200)
+            .with_body(CHAT_RESPONSE)
+            .expect(hits)
+            .create_async()
+            .await;
+        (server, mock)
+    }
+
+    #[tokio::test]
+    async fn test_streams_through_first_target() {
+        use futures::StreamExt;
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", PATH)
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"stream": true})))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,",
+                "\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},",
+                "\"finish_reason\":\"stop\"}]}\n\n",
+                "data: [DONE]\n\n",
+            ))
+            .create_async()
+            .await;
+        let pool = AzureOpenAIPool::new(vec![AzureTarget::new(server.url(), "gpt-4o", key())]).unwrap();
+
+        let mut req = request();
+        req.stream = Some(true);
+        let stream = pool.execute_stream(pool.transform_request(&req).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().choices[0].delta.content.as_deref(), Some("Hi"));
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_new_rejects_empty_and_zero_weight() {
+        assert!(AzureOpenAIPool::new(vec![]).is_err());
+        let target = AzureTarget::new("https://eastus.openai.azure.com", "gpt-4o", key()).with_weight(0);
+        assert!(AzureOpenAIPool::new(vec![target]).is_err());
+    }
+
+    #[test]
+    fn test_chat_url() {
+        let target = AzureTarget::new("https://eastus.openai.azure.com/", "gpt-4o", key())
+            .with_api_version("2024-10-21");
+        assert_eq!(
+            target.chat_url(),
+            "https://eastus.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weighted_round_robin() {
+        let (heavy, heavy_mock) = healthy_server(6).await;
+        let (light, light_mock) = healthy_server(2).await;
+        let pool = AzureOpenAIPool::new(vec![
+            AzureTarget::new(heavy.url(), "gpt-4o", key()).with_weight(3),
+            AzureTarget::new(light.url(), "gpt-4o", key()),
+        ])
+        .unwrap();
+
+        for _ in 0..8 {
+            pool.complete(&request()).await.unwrap();
+        }
+
+        let status = pool.status();
+        assert_eq!(status[0].metrics.successful_requests, 6);
+        assert_eq!(status[1].metrics.successful_requests, 2);
+        assert_eq!(status[0].prompt_tokens, 18);
+        assert_eq!(status[1].completion_tokens, 2);
+        heavy_mock.assert_async().await;
+        light_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_target_cools_down_and_recovers() {
+        let mut throttled = mockito::Server::new_async().await;
+        let rejected = throttled
+            .mock("POST", PATH)
+            .with_status(429)
+            .with_body(r#"{"error": {"message": "Rate limit exceeded", "code": "429"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let (healthy, healthy_mock) = healthy_server(6).await;
+
+        let pool = AzureOpenAIPool::new(vec![
+            AzureTarget::new(throttled.url(), "gpt-4o", key()),
+            AzureTarget::new(healthy.url(), "gpt-4o", key()),
+        ])
+        .unwrap();
+
+        // The first request fails over; traffic then stays on the healthy target
+        for _ in 0..4 {
+            assert_eq!(pool.complete(&request()).await.unwrap().content(), Some("Hi"));
+        }
+        rejected.assert_async().await;
+
+        let status = pool.status();
+        assert_eq!(status[0].metrics.health, ProviderHealth::Unavailable);
+        assert_eq!(status[0].rate_limited, 1);
+        assert_eq!(status[0].metrics.failed_requests, 1);
+        assert!(status[0].available_in.unwrap() > Duration::from_secs(9));
+        assert_eq!(status[1].metrics.health, ProviderHealth::Healthy);
+
+        // After the cooldown the next request probes the recovered target
+        rejected.remove_async().await;
+        let recovered = throttled
+            .mock("POST", PATH)
+            .with_status(200)
+            .with_body(CHAT_RESPONSE)
+            .expect(2)
+            .create_async()
+            .await;
+        tokio::time::pause();
+        tokio::time::advance(Duration::from_secs(11)).await;
+        tokio::time::resume();
+        assert_eq!(pool.status()[0].metrics.health, ProviderHealth::Degraded);
+
+        for _ in 0..4 {
+            pool.complete(&request()).await.unwrap();
+        }
+        recovered.assert_async().await;
+        healthy_mock.assert_async().await;
+        let status = pool.status();
+        assert_eq!(status[0].metrics.health, ProviderHealth::Healthy);
+        assert_eq!(status[0].metrics.successful_requests, 2);
+        assert_eq!(status[1].metrics.successful_requests, 6);
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_doubles_cooldown() {
+        let mut failing = mockito::Server::new_async().await;
+        let errors = failing
+            .mock("POST", PATH)
+            .with_status(503)
+            .with_body(r#"{"error": {"message": "Service unavailable"}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let (healthy, healthy_mock) = healthy_server(3).await;
+
+        let pool = AzureOpenAIPool::new(vec![
+            AzureTarget::new(failing.url(), "gpt-4o", key()),
+            AzureTarget::new(healthy.url(), "gpt-4o", key()),
+        ])
+        .unwrap()
+        .with_cooldown(Duration::from_secs(5), Duration::from_secs(60));
+
+        pool.complete(&request()).await.unwrap();
+        let first = pool.status()[0].available_in.unwrap();
+        assert!(first <= Duration::from_secs(5) && first > Duration::from_secs(4));
+
+        tokio::time::pause();
+        tokio::time::advance(Duration::from_secs(6)).await;
+        tokio::time::resume();
+
+        // The first request after the cooldown goes to the healthy target
+        // by rotation, the second probes the failing one
+        pool.complete(&request()).await.unwrap();
+        pool.complete(&request()).await.unwrap();
+        errors.assert_async().await;
+        healthy_mock.assert_async().await;
+        let status = pool.status();
+        assert_eq!(status[0].metrics.health, ProviderHealth::Unavailable);
+        assert!(status[0].available_in.unwrap() > Duration::from_secs(9));
+    }
+
+    #[tokio::test]
+    async fn test_all_targets_cooling_down_is_rate_limited() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", PATH)
+            .with_status(500)
+            .with_body(r#"{"error": {"message": "Internal error"}}"#)
+            .create_async()
+            .await;
+        let pool = AzureOpenAIPool::new(vec![AzureTarget::new(server.url(), "gpt-4o", key())]).unwrap();
+
+        let first = pool.complete(&request()).await.unwrap_err();
+        assert!(matches!(first.root(), SimpleAgentsError::Provider(ProviderError::ServerError(_))));
+
+        let second = pool.complete(&request()).await.unwrap_err();
+        match second {
+            SimpleAgentsError::Provider(ProviderError::RateLimit { retry_after }) => {
+                assert!(retry_after.unwrap() <= AzureOpenAIPool::DEFAULT_BASE_COOLDOWN)
+            }
+            other => panic!("expected RateLimit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_is_not_failed_over() {
+        let mut bad = mockito::Server::new_async().await;
+        let rejected = bad
+            .mock("POST", PATH)
+            .with_status(400)
+            .with_body(r#"{"error": {"message": "Invalid request", "type": "invalid_request_error"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let (other, other_mock) = healthy_server(0).await;
+
+        let pool = AzureOpenAIPool::new(vec![
+            AzureTarget::new(bad.url(), "gpt-4o", key()),
+            AzureTarget::new(other.url(), "gpt-4o", key()),
+        ])
+        .unwrap();
+
+        assert!(pool.complete(&request()).await.is_err());
+        rejected.assert_async().await;
+        other_mock.assert_async().await;
+        assert_eq!(pool.status()[0].metrics.health, ProviderHealth::Healthy);
+    }
+}
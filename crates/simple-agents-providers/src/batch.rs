@@ -0,0 +1,289 @@
+//! Concurrent execution of many completion requests.
+
+use futures::StreamExt;
+use simple_agents_types::error::BatchResult;
+use simple_agents_types::event::BatchState;
+use simple_agents_types::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Source of `local-N` batch ids for events
+static NEXT_BATCH_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Runs batches of requests against a provider with bounded concurrency.
+///
+/// Results are returned in the same order as the requests; one failing
+/// request does not abort the rest of the batch.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::batch::BatchExecutor;
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_types::prelude::*;
+/// use std::sync::Arc;
+///
+/// # async fn example() -> Result<()> {
+/// let provider = OpenAIProvider::new(ApiKey::new("sk-...")?)?;
+/// let executor = BatchExecutor::new(Arc::new(provider), 4);
+///
+/// let requests = vec![
+///     CompletionRequest::builder().model("gpt-4").message(Message::user("One")).build()?,
+///     CompletionRequest::builder().model("gpt-4").message(Message::user("Two")).build()?,
+/// ];
+/// for result in executor.execute(requests).await {
+///     println!("{:?}", result.map(|r| r.content().map(str::to_string)));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct BatchExecutor {
+    provider: Arc<dyn Provider>,
+    concurrency: usize,
+    notifier: Option<Arc<dyn EventNotifier>>,
+}
+
+impl BatchExecutor {
+    /// Default number of requests in flight at once
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    /// Create an executor running at most `concurrency` requests at once.
+    ///
+    /// A concurrency of 0 is treated as 1.
+    pub fn new(provider: Arc<dyn Provider>, concurrency: usize) -> Self {
+        Self {
+            provider,
+            concurrency: concurrency.max(1),
+            notifier: None,
+        }
+    }
+
+    /// Report batch progress to `notifier` (builder pattern).
+    ///
+    /// Each call to [`execute`](Self::execute) (and the methods built on
+    /// it) sends [`Event::BatchStateChanged`] when it starts and when every
+    /// request has a result, under a `local-N` id unique within the process.
+    pub fn with_notifier(mut self, notifier: Arc<dyn EventNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Maximum number of requests in flight at once.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Execute all requests, returning one result per request in order.
+    pub async fn execute(&self, requests: Vec<CompletionRequest>) -> Vec<Result<CompletionResponse>> {
+        let Some(notifier) = &self.notifier else {
+            return self.run(requests).await;
+        };
+
+        let batch_id = format!("local-{}", NEXT_BATCH_ID.fetch_add(1, Ordering::Relaxed));
+        let total = requests.len();
+        notifier
+            .notify(Event::BatchStateChanged {
+                batch_id: batch_id.clone(),
+                state: BatchState::InProgress,
+                total,
+                completed: 0,
+                failed: 0,
+            })
+            .await;
+
+        let results = self.run(requests).await;
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        notifier
+            .notify(Event::BatchStateChanged {
+                batch_id,
+                state: BatchState::Completed,
+                total,
+                completed: total - failed,
+                failed,
+            })
+            .await;
+        results
+    }
+
+    /// Run requests with bounded concurrency, keeping their order.
+    async fn run(&self, requests: Vec<CompletionRequest>) -> Vec<Result<CompletionResponse>> {
+        futures::stream::iter(requests)
+            .map(|request| {
+                let provider = self.provider.clone();
+                async move { provider.complete(&request).await }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Execute `(id, request)` pairs, separating successes from failures.
+    ///
+    /// Both halves of the result keep the input order.
+    pub async fn execute_all(&self, requests: Vec<(String, CompletionRequest)>) -> BatchResult<CompletionResponse> {
+        let (ids, requests): (Vec<_>, Vec<_>) = requests.into_iter().unzip();
+        let results = self.execute(requests).await;
+        BatchResult::from_provider_results(ids.into_iter().zip(results).collect())
+    }
+
+    /// Execute the same request `n` times.
+    pub async fn execute_repeated(&self, request: &CompletionRequest, n: usize) -> Vec<Result<CompletionResponse>> {
+        self.execute(vec![request.clone(); n]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct MockProvider {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "content": req.messages[0].content })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if req.body["content"] == "fail" {
+                return Err(SimpleAgentsError::Provider(ProviderError::ServerError("boom".to_string())));
+            }
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: "mock-model".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant(resp.body["content"].as_str().unwrap_or_default()),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+    }
+
+    fn request(content: &str) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::user(content))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_results_in_order_with_bounded_concurrency() {
+        let provider = Arc::new(MockProvider::default());
+        let executor = BatchExecutor::new(provider.clone(), 2);
+
+        let requests = ["a", "b", "fail", "d", "e"].iter().map(|c| request(c)).collect();
+        let results = executor.execute(requests).await;
+
+        let contents: Vec<_> = results
+            .iter()
+            .map(|r| r.as_ref().ok().and_then(|r| r.content()).map(str::to_string))
+            .collect();
+        assert_eq!(
+            contents,
+            vec![Some("a".into()), Some("b".into()), None, Some("d".into()), Some("e".into())]
+        );
+        assert!(provider.max_in_flight.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_repeated() {
+        let executor = BatchExecutor::new(Arc::new(MockProvider::default()), 0);
+        assert_eq!(executor.concurrency(), 1);
+
+        let results = executor.execute_repeated(&request("x"), 3).await;
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_separates_failures() {
+        let executor = BatchExecutor::new(Arc::new(MockProvider::default()), 3);
+
+        let requests = ["a", "fail", "c", "fail"]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (format!("req-{}", i), request(c)))
+            .collect();
+        let batch = executor.execute_all(requests).await;
+
+        assert!(!batch.is_complete_success());
+        assert_eq!(batch.success_rate(), 0.5);
+        let failed: Vec<_> = batch.failures.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(failed, vec!["req-1", "req-3"]);
+        assert!(matches!(
+            batch.failures[0].1.root(),
+            SimpleAgentsError::Provider(ProviderError::ServerError(_))
+        ));
+        assert_eq!(batch.successes[1].0, "req-2");
+        let contents: Vec<_> = batch
+            .into_successes()
+            .iter()
+            .map(|r| r.content().unwrap_or_default().to_string())
+            .collect();
+        assert_eq!(contents, vec!["a", "c"]);
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        events: std::sync::Mutex<Vec<Event>>,
+    }
+
+    #[async_trait]
+    impl EventNotifier for RecordingNotifier {
+        async fn notify(&self, event: Event) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifier_reports_batch_progress() {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let executor =
+            BatchExecutor::new(Arc::new(MockProvider::default()), 2).with_notifier(notifier.clone());
+
+        executor.execute(vec![request("a"), request("fail"), request("c")]).await;
+
+        let events = notifier.events.lock().unwrap();
+        let states: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Event::BatchStateChanged { batch_id, state, total, completed, failed } => {
+                    assert!(batch_id.starts_with("local-"));
+                    (*state, *total, *completed, *failed)
+                }
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(states, vec![(BatchState::InProgress, 3, 0, 0), (BatchState::Completed, 3, 2, 1)]);
+    }
+}
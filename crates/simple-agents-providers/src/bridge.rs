@@ -0,0 +1,237 @@
+//! Bridging completion streams to synchronous consumers.
+//!
+//! UI threads and other non-async code cannot poll a [`Stream`].
+//! [`stream_to_channel`] drives the stream on the Tokio runtime and hands
+//! chunks over a bounded [`std::sync::mpsc`] channel instead.
+
+use futures::{Stream, StreamExt};
+use simple_agents_types::prelude::*;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use tokio::task::JoinHandle;
+
+/// A message on the channel returned by [`stream_to_channel`].
+///
+/// Zero or more [`Chunk`](Self::Chunk)s are followed by exactly one
+/// terminal [`Done`](Self::Done) or [`Error`](Self::Error), after which
+/// the channel disconnects.
+#[derive(Debug)]
+pub enum StreamMessage {
+    /// The next chunk of the stream
+    Chunk(CompletionChunk),
+    /// The stream ended; the response accumulated from every chunk
+    Done(CompletionResponse),
+    /// The stream failed; no further messages follow
+    Error(SimpleAgentsError),
+}
+
+/// Drive `stream` on the Tokio runtime, sending its chunks to a bounded
+/// channel of `capacity` messages.
+///
+/// When the channel is full the task waits for the consumer instead of
+/// dropping chunks, so a slow consumer slows the stream down rather than
+/// losing tokens. The stream ends with [`StreamMessage::Done`] or
+/// [`StreamMessage::Error`].
+///
+/// Dropping the receiver stops the task at its next send and drops the
+/// stream, which cancels the upstream request. The returned handle
+/// completes once the task has stopped.
+///
+/// # Panics
+///
+/// Panics if called outside a Tokio runtime.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::bridge::{stream_to_channel, StreamMessage};
+/// use simple_agents_types::prelude::*;
+/// # async fn example(
+/// #     stream: impl futures::Stream<Item = Result<CompletionChunk>> + Send + 'static,
+/// # ) {
+/// let (receiver, _handle) = stream_to_channel(stream, 16);
+/// std::thread::spawn(move || {
+///     for message in receiver {
+///         match message {
+///             StreamMessage::Chunk(chunk) => print!("{:?}", chunk.choices[0].delta.content),
+///             StreamMessage::Done(response) => println!("\n{}", response.usage.total_tokens),
+///             StreamMessage::Error(error) => eprintln!("\n{}", error),
+///         }
+///     }
+/// });
+/// # }
+/// ```
+pub fn stream_to_channel<S>(stream: S, capacity: usize) -> (Receiver<StreamMessage>, JoinHandle<()>)
+where
+    S: Stream<Item = Result<CompletionChunk>> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let handle = tokio::spawn(async move {
+        let mut stream = std::pin::pin!(stream);
+        let mut accumulator = ChunkAccumulator::default();
+
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(chunk) => {
+                    accumulator.push(chunk.clone());
+                    if !send(&sender, StreamMessage::Chunk(chunk)).await {
+                        return;
+                    }
+                }
+                Err(error) => {
+                    send(&sender, StreamMessage::Error(error)).await;
+                    return;
+                }
+            }
+        }
+        send(&sender, StreamMessage::Done(accumulator.finish())).await;
+    });
+    (receiver, handle)
+}
+
+/// Send `message`, waiting while the channel is full.
+///
+/// Returns `false` if the receiver has been dropped.
+async fn send(sender: &SyncSender<StreamMessage>, message: StreamMessage) -> bool {
+    match sender.try_send(message) {
+        Ok(()) => true,
+        Err(TrySendError::Disconnected(_)) => false,
+        Err(TrySendError::Full(message)) => {
+            // A blocking send wakes on either free space or disconnection;
+            // run it off the async workers so the runtime keeps going
+            let sender = sender.clone();
+            tokio::task::spawn_blocking(move || sender.send(message).is_ok())
+                .await
+                .unwrap_or(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Sets its flag when dropped
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn chunk(content: &str) -> CompletionChunk {
+        CompletionChunk {
+            id: "chatcmpl-1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            choices: vec![ChoiceDelta {
+                index: 0,
+                delta: MessageDelta {
+                    role: None,
+                    content: Some(content.to_string()),
+                },
+                finish_reason: None,
+            }],
+            created: None,
+            usage: None,
+        }
+    }
+
+    /// `limit` chunks "0", "1", ... (endless without a limit), failing
+    /// instead of producing chunk `fail_at`.
+    fn numbered_stream(
+        limit: Option<usize>,
+        fail_at: Option<usize>,
+        pulled: Arc<AtomicUsize>,
+        dropped: Arc<AtomicBool>,
+    ) -> impl Stream<Item = Result<CompletionChunk>> + Send + 'static {
+        futures::stream::unfold((0, DropFlag(dropped)), move |(i, flag)| {
+            let pulled = pulled.clone();
+            async move {
+                if limit.is_some_and(|limit| i >= limit) {
+                    return None;
+                }
+                pulled.fetch_add(1, Ordering::SeqCst);
+                let item = if fail_at == Some(i) {
+                    Err(ProviderError::ServerError("connection reset".to_string()).into())
+                } else {
+                    Ok(chunk(&i.to_string()))
+                };
+                Some((item, (i + 1, flag)))
+            }
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_slow_consumer_applies_backpressure() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let stream = numbered_stream(Some(20), None, pulled.clone(), Arc::default());
+        let (receiver, handle) = stream_to_channel(stream, 2);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // Two chunks buffered plus one waiting to be sent; nothing dropped
+        assert!(pulled.load(Ordering::SeqCst) <= 3, "pulled {}", pulled.load(Ordering::SeqCst));
+
+        let messages = tokio::task::spawn_blocking(move || {
+            receiver
+                .into_iter()
+                .inspect(|_| std::thread::sleep(Duration::from_millis(2)))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap();
+        handle.await.unwrap();
+
+        assert_eq!(messages.len(), 21);
+        for (i, message) in messages[..20].iter().enumerate() {
+            match message {
+                StreamMessage::Chunk(chunk) => {
+                    assert_eq!(chunk.choices[0].delta.content.as_deref(), Some(i.to_string().as_str()))
+                }
+                other => panic!("expected chunk {}, got {:?}", i, other),
+            }
+        }
+        let expected: String = (0..20).map(|i| i.to_string()).collect();
+        match &messages[20] {
+            StreamMessage::Done(response) => assert_eq!(response.content(), Some(expected.as_str())),
+            other => panic!("expected Done, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_dropped_receiver_cancels_stream() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let dropped = Arc::new(AtomicBool::new(false));
+        let stream = numbered_stream(None, None, pulled.clone(), dropped.clone());
+        let (receiver, handle) = stream_to_channel(stream, 1);
+
+        assert!(matches!(receiver.recv().unwrap(), StreamMessage::Chunk(_)));
+        drop(receiver);
+
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("bridge task did not stop")
+            .unwrap();
+        assert!(dropped.load(Ordering::SeqCst));
+        assert!(pulled.load(Ordering::SeqCst) < 10);
+    }
+
+    #[tokio::test]
+    async fn test_mid_stream_error_is_terminal() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let stream = numbered_stream(Some(10), Some(2), Arc::default(), dropped.clone());
+        let (receiver, handle) = stream_to_channel(stream, 16);
+        handle.await.unwrap();
+
+        let messages: Vec<_> = receiver.into_iter().collect();
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[0], StreamMessage::Chunk(_)));
+        assert!(matches!(messages[1], StreamMessage::Chunk(_)));
+        match &messages[2] {
+            StreamMessage::Error(error) => assert!(error.to_string().contains("connection reset")),
+            other => panic!("expected Error, got {:?}", other),
+        }
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,206 @@
+//! Refreshing credentials and 401 handling for providers.
+//!
+//! [`RefreshingCredentials`] wraps a refresh closure (e.g. a Vault lookup)
+//! and caches its key for a TTL. Providers built from any
+//! [`CredentialSource`] fetch the key at request time and, when the API
+//! rejects it, invalidate the source and retry once with a fresh key.
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use simple_agents_types::prelude::*;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+type RefreshFn = dyn Fn() -> BoxFuture<'static, Result<ApiKey>> + Send + Sync;
+
+/// Credential source that re-fetches its key after a TTL.
+///
+/// Concurrent requests that find the cache empty or expired share a
+/// single refresh. [`CredentialSource::invalidate`] drops the cached key
+/// so the next request fetches a new one.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::credentials::RefreshingCredentials;
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_types::prelude::*;
+/// use std::time::Duration;
+///
+/// # async fn read_key_from_vault() -> Result<String> { unimplemented!() }
+/// # fn example() -> Result<()> {
+/// let credentials = RefreshingCredentials::new(Duration::from_secs(300), || async {
+///     ApiKey::new(read_key_from_vault().await?)
+/// });
+/// let provider = OpenAIProvider::new(credentials)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RefreshingCredentials {
+    refresh: Box<RefreshFn>,
+    ttl: Duration,
+    /// Cached key and when it was fetched
+    cached: Mutex<Option<(ApiKey, Instant)>>,
+    /// Held while a refresh is in flight
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl RefreshingCredentials {
+    /// Create a source that calls `refresh` when its cached key is older than `ttl`.
+    ///
+    /// The first key is fetched lazily, on the first request.
+    pub fn new<F, Fut>(ttl: Duration, refresh: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<ApiKey>> + Send + 'static,
+    {
+        Self {
+            refresh: Box::new(move || Box::pin(refresh())),
+            ttl,
+            cached: Mutex::new(None),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// How long a fetched key is reused.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn fresh_key(&self) -> Option<ApiKey> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        cached
+            .as_ref()
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < self.ttl)
+            .map(|(key, _)| key.clone())
+    }
+}
+
+impl std::fmt::Debug for RefreshingCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RefreshingCredentials")
+            .field("ttl", &self.ttl)
+            .field("cached", &self.fresh_key().is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CredentialSource for RefreshingCredentials {
+    async fn current_key(&self) -> Result<ApiKey> {
+        if let Some(key) = self.fresh_key() {
+            return Ok(key);
+        }
+
+        let _refreshing = self.refreshing.lock().await;
+        // Another request may have refreshed while we waited
+        if let Some(key) = self.fresh_key() {
+            return Ok(key);
+        }
+
+        let key = (self.refresh)().await?;
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = Some((key.clone(), Instant::now()));
+        Ok(key)
+    }
+
+    fn cached_key(&self) -> Option<ApiKey> {
+        self.fresh_key()
+    }
+
+    fn invalidate(&self) -> bool {
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        true
+    }
+}
+
+/// Run `send` with the current key, retrying once with a fresh key if the
+/// API rejects it and the source can refresh.
+pub(crate) async fn send_with_refresh<T, F, Fut>(credentials: &dyn CredentialSource, mut send: F) -> Result<T>
+where
+    F: FnMut(ApiKey) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let key = credentials.current_key().await?;
+    match send(key).await {
+        Err(error) if is_invalid_key(&error) && credentials.invalidate() => {
+            tracing::info!("API key rejected; retrying with refreshed credentials");
+            send(credentials.current_key().await?).await
+        }
+        result => result,
+    }
+}
+
+fn is_invalid_key(error: &SimpleAgentsError) -> bool {
+    matches!(error.root(), SimpleAgentsError::Provider(ProviderError::InvalidApiKey))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Source yielding `sk-rotated-key-000…N` for the Nth refresh
+    fn counting_source(ttl: Duration) -> (RefreshingCredentials, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let source = RefreshingCredentials::new(ttl, move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { ApiKey::new(format!("sk-rotated-key-{:020}", n)) }
+        });
+        (source, calls)
+    }
+
+    #[tokio::test]
+    async fn test_key_is_cached_for_ttl() {
+        let (source, calls) = counting_source(Duration::from_secs(60));
+        assert!(source.cached_key().is_none());
+
+        let first = source.current_key().await.unwrap();
+        let second = source.current_key().await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(source.cached_key(), Some(first));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_invalidated_keys_are_refetched() {
+        let (source, calls) = counting_source(Duration::from_millis(20));
+        let first = source.current_key().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(source.cached_key().is_none());
+        let second = source.current_key().await.unwrap();
+        assert_ne!(first, second);
+
+        assert!(source.invalidate());
+        let third = source.current_key().await.unwrap();
+        assert_ne!(second, third);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_one_refresh() {
+        let (source, calls) = counting_source(Duration::from_secs(60));
+        let source = Arc::new(source);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let source = source.clone();
+                tokio::spawn(async move { source.current_key().await.unwrap() })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_errors_propagate() {
+        let source = RefreshingCredentials::new(Duration::from_secs(60), || async {
+            Err(SimpleAgentsError::Config("vault unavailable".to_string()))
+        });
+        assert!(matches!(source.current_key().await, Err(SimpleAgentsError::Config(_))));
+    }
+}
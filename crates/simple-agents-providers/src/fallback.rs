@@ -0,0 +1,718 @@
+//! Failover across an ordered list of providers.
+
+use crate::retry::{classify, ClassifyFn, ErrorClass};
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Provider that tries each wrapped provider in order until one succeeds.
+///
+/// A provider is skipped without being called when its [`Capabilities`]
+/// lack a feature the request needs (see
+/// [`CompletionRequest::required_features`]). Errors are classified with
+/// [`classify`] (or a [`ClassifyFn`] override): anything but
+/// [`ErrorClass::Fatal`] moves on to the next provider, including
+/// [`ProviderError::Unsupported`].
+///
+/// [`Provider::complete`] and [`Provider::execute_stream`] fail over;
+/// [`Provider::execute_with_attempts`] retries each provider per the
+/// caller's policy before failing over to the next. For a
+/// streaming request, [`transform_request`](Provider::transform_request)
+/// builds the request with the first capable provider and keeps the
+/// [`CompletionRequest`] in the request's extensions, so `execute_stream`
+/// can rebuild it for the providers after that one. A stream fails over
+/// only until it is returned; errors in the middle of a stream are passed
+/// on. The other hooks delegate to the first (primary) provider, since a
+/// [`ProviderRequest`] is specific to the provider that built it.
+pub struct FallbackProvider {
+    providers: Vec<Box<dyn Provider>>,
+    classifier: Option<ClassifyFn>,
+}
+
+impl FallbackProvider {
+    /// Create a fallback chain, tried in the given order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `providers` is empty.
+    pub fn new(providers: Vec<Box<dyn Provider>>) -> Result<Self> {
+        if providers.is_empty() {
+            return Err(SimpleAgentsError::Config(
+                "FallbackProvider needs at least one provider".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            providers,
+            classifier: None,
+        })
+    }
+
+    /// Override how errors are classified for failover.
+    pub fn with_classifier(mut self, classifier: ClassifyFn) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
+
+    /// The wrapped providers, in order.
+    pub fn providers(&self) -> &[Box<dyn Provider>] {
+        &self.providers
+    }
+
+    fn primary(&self) -> &dyn Provider {
+        self.providers[0].as_ref()
+    }
+
+    fn classify(&self, error: &SimpleAgentsError) -> ErrorClass {
+        match &self.classifier {
+            Some(classifier) => classifier(error),
+            None => classify(error),
+        }
+    }
+}
+
+/// Extension holding the index of the provider that built a streaming
+/// request
+const PROVIDER_INDEX: &str = "fallback.provider";
+
+/// Extension holding the streaming request, to rebuild it for the next
+/// provider
+const ORIGINAL_REQUEST: &str = "fallback.request";
+
+#[async_trait]
+impl Provider for FallbackProvider {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        if !req.is_streaming() {
+            return self.primary().transform_request(req);
+        }
+        let index = self
+            .providers
+            .iter()
+            .position(|provider| req.check_features(provider.as_ref()).is_ok())
+            .unwrap_or(0);
+        let mut request = self.providers[index].transform_request(req)?;
+        request.extensions.insert(PROVIDER_INDEX, index);
+        request.extensions.insert(ORIGINAL_REQUEST, serde_json::to_value(req)?);
+        Ok(request)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.primary().execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.primary().transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.primary().retry_config()
+    }
+
+    /// Union of the wrapped providers' capabilities.
+    fn capabilities(&self) -> Capabilities {
+        self.providers.iter().map(|p| p.capabilities()).fold(
+            Capabilities::default(),
+            |acc, caps| Capabilities {
+                streaming: acc.streaming || caps.streaming,
+                function_calling: acc.function_calling || caps.function_calling,
+                vision: acc.vision || caps.vision,
+                json_schema: acc.json_schema || caps.json_schema,
+                embeddings: acc.embeddings || caps.embeddings,
+                assistant_prefill: acc.assistant_prefill || caps.assistant_prefill,
+                documents: acc.documents || caps.documents,
+                max_tokens: acc.max_tokens.max(caps.max_tokens),
+            },
+        )
+    }
+
+    fn timeout(&self) -> Duration {
+        self.primary().timeout()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.primary().sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.primary().model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.primary().pricing(model)
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.primary().prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        // Keep the history on errors so retries around the chain can see it
+        self.complete_with_attempts(req).await.map(|outcome| outcome.response)
+    }
+
+    /// Retry each provider per `policy` before failing over to the next,
+    /// recording every attempt of every provider.
+    async fn execute_with_attempts(
+        &self,
+        req: &CompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<CompleteOutcome> {
+        let (result, attempts) = self.run(req, Some(policy)).await;
+        self.outcome(req, result, attempts)
+    }
+
+    /// Stream with the provider that built `req`, failing over to the
+    /// providers after it. Requests not built by this provider's
+    /// [`transform_request`](Provider::transform_request) go to the primary
+    /// only.
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        let start = req.extensions.get_as::<usize>(PROVIDER_INDEX).unwrap_or(0).min(self.providers.len() - 1);
+        let Some(mut original) = req.extensions.get_as::<CompletionRequest>(ORIGINAL_REQUEST) else {
+            return self.primary().execute_stream(req).await;
+        };
+        // Extensions are not serialized; restore the caller's
+        original.extensions = req.extensions.clone();
+        original.extensions.remove(PROVIDER_INDEX);
+        original.extensions.remove(ORIGINAL_REQUEST);
+
+        let first = &self.providers[start];
+        let mut last_error = match first.execute_stream(req).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) if !self.classify(&e).allows_failover() => return Err(e),
+            Err(e) => e,
+        };
+        for provider in &self.providers[start + 1..] {
+            if let Err(e) = original.check_features(provider.as_ref()) {
+                tracing::debug!(provider = provider.name(), error = %e, "Skipping provider");
+                last_error = e;
+                continue;
+            }
+            tracing::warn!(error = %last_error, "Provider failed to stream, trying {}", provider.name());
+            let result = match provider.transform_request(&original) {
+                Ok(request) => provider.execute_stream(request).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(stream) => return Ok(stream),
+                Err(e) if !self.classify(&e).allows_failover() => return Err(e),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+impl FallbackProvider {
+    /// [`complete`](Provider::complete), also returning every provider
+    /// call made.
+    ///
+    /// Providers skipped for lacking a feature are recorded with their
+    /// `UNSUPPORTED` error and no latency. A provider whose error carries
+    /// its own history (e.g. a retrying wrapper) contributes that history
+    /// instead of a single attempt. On failure the history is in the
+    /// error's [`ErrorContext::attempts`].
+    pub async fn complete_with_attempts(&self, req: &CompletionRequest) -> Result<CompleteOutcome> {
+        let (result, attempts) = self.run(req, None).await;
+        self.outcome(req, result, attempts)
+    }
+
+    /// Attach `attempts` to the chain's result.
+    fn outcome(
+        &self,
+        req: &CompletionRequest,
+        result: Result<CompletionResponse>,
+        attempts: AttemptHistory,
+    ) -> Result<CompleteOutcome> {
+        match result {
+            Ok(response) => Ok(CompleteOutcome { response, attempts }),
+            Err(SimpleAgentsError::WithContext(mut ctx)) => {
+                ctx.attempts = attempts;
+                Err(SimpleAgentsError::WithContext(ctx))
+            }
+            Err(error) => Err(SimpleAgentsError::WithContext(Box::new(error.with_context(
+                ErrorContext::new().provider(self.name()).model(req.model.clone()).attempts(attempts),
+            )))),
+        }
+    }
+
+    /// Try each provider in turn, calling it once, or with its retries per
+    /// `policy` when one is given.
+    async fn run(
+        &self,
+        req: &CompletionRequest,
+        policy: Option<&RetryPolicy>,
+    ) -> (Result<CompletionResponse>, AttemptHistory) {
+        let mut attempts = AttemptHistory::new();
+        if let Err(e) = req.ensure_not_streaming() {
+            return (Err(e), attempts);
+        }
+
+        let mut last_error = None;
+
+        for (position, provider) in (1..).zip(&self.providers) {
+            if let Err(e) = req.check_features(provider.as_ref()) {
+                tracing::debug!(provider = provider.name(), error = %e, "Skipping provider");
+                attempts.push(Attempt::failed(provider.name(), 1, e.code(), Duration::ZERO));
+                last_error = Some(at_position(e, provider.name(), req, position));
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = match policy {
+                Some(policy) => provider.execute_with_attempts(req, policy).await,
+                None => provider.complete(req).await.map(|response| CompleteOutcome {
+                    response,
+                    attempts: AttemptHistory::new(),
+                }),
+            };
+            let latency = started.elapsed();
+            match result {
+                Ok(outcome) if outcome.attempts.is_empty() => {
+                    attempts.push(Attempt::succeeded(provider.name(), 1, latency));
+                    return (Ok(outcome.response), attempts);
+                }
+                Ok(outcome) => {
+                    attempts.extend(outcome.attempts);
+                    return (Ok(outcome.response), attempts);
+                }
+                Err(e) => {
+                    match e.context().filter(|ctx| !ctx.attempts.is_empty()) {
+                        Some(ctx) => attempts.extend(ctx.attempts.clone()),
+                        None => attempts.push(Attempt::failed(provider.name(), 1, e.code(), latency)),
+                    }
+                    let failover = self.classify(&e).allows_failover();
+                    let e = at_position(e, provider.name(), req, position);
+                    if !failover {
+                        return (Err(e), attempts);
+                    }
+                    tracing::warn!(provider = provider.name(), error = %e, "Provider failed, trying next");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // `new` guarantees at least one provider, so an error was recorded
+        (Err(last_error.expect("fallback chain is never empty")), attempts)
+    }
+}
+
+/// Set the context attempt of `error`, from the `provider` at `position`
+/// (1-based) in the chain, to that position.
+fn at_position(
+    error: SimpleAgentsError,
+    provider: &str,
+    req: &CompletionRequest,
+    position: u32,
+) -> SimpleAgentsError {
+    match error {
+        SimpleAgentsError::WithContext(mut ctx) => {
+            ctx.attempt = position;
+            SimpleAgentsError::WithContext(ctx)
+        }
+        error => SimpleAgentsError::WithContext(Box::new(error.with_context(
+            ErrorContext::new().provider(provider).model(req.model.clone()).attempt(position),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_agents_types::config::Feature;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    struct MockProvider {
+        name: &'static str,
+        capabilities: Capabilities,
+        error: Option<fn() -> ProviderError>,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl MockProvider {
+        fn new(name: &'static str) -> Self {
+            Self {
+                name,
+                capabilities: Capabilities::default(),
+                error: None,
+                calls: Arc::new(AtomicU32::new(0)),
+            }
+        }
+
+        fn with_tools(mut self) -> Self {
+            self.capabilities.function_calling = true;
+            self
+        }
+
+        fn failing(mut self, error: fn() -> ProviderError) -> Self {
+            self.error = Some(error);
+            self
+        }
+
+        fn streaming(mut self) -> Self {
+            self.capabilities.streaming = true;
+            self
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            self.capabilities.clone()
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({"model": req.model}))
+                .with_extensions(req.extensions.clone()))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.error {
+                Some(error) => Err(error().into()),
+                None => Ok(ProviderResponse::new(200, serde_json::json!({}))),
+            }
+        }
+
+        fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: "mock-model".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant(self.name),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: Some(self.name.to_string()),
+            })
+        }
+
+        /// One chunk holding the provider's name and the request's model
+        async fn execute_stream(
+            &self,
+            req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if !self.capabilities.streaming {
+                return Err(ProviderError::Unsupported {
+                    provider: self.name.to_string(),
+                    feature: Feature::Streaming,
+                }
+                .into());
+            }
+            if let Some(error) = self.error {
+                return Err(error().into());
+            }
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: req.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: vec![ChoiceDelta {
+                    index: 0,
+                    delta: MessageDelta {
+                        role: None,
+                        content: Some(self.name.to_string()),
+                    },
+                    finish_reason: Some(FinishReason::Stop),
+                }],
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    fn plain_request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap()
+    }
+
+    fn tools_request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::user("Weather in Paris?"))
+            .message(Message::tool("{\"temp\": 21}", "call_1"))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_requires_providers() {
+        assert!(matches!(
+            FallbackProvider::new(vec![]),
+            Err(SimpleAgentsError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tools_request_skips_incapable_provider() {
+        let basic = MockProvider::new("basic");
+        let basic_calls = basic.calls.clone();
+        let fallback = FallbackProvider::new(vec![
+            Box::new(basic),
+            Box::new(MockProvider::new("tools").with_tools()),
+        ])
+        .unwrap();
+
+        let response = fallback.complete(&tools_request()).await.unwrap();
+        assert_eq!(response.provider.as_deref(), Some("tools"));
+        assert_eq!(basic_calls.load(Ordering::SeqCst), 0);
+
+        // Requests without tools still go to the primary
+        let response = fallback.complete(&plain_request()).await.unwrap();
+        assert_eq!(response.provider.as_deref(), Some("basic"));
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_error_fails_over() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("declines").with_tools().failing(|| ProviderError::Unsupported {
+                provider: "declines".to_string(),
+                feature: Feature::FunctionCalling,
+            })),
+            Box::new(MockProvider::new("tools").with_tools()),
+        ])
+        .unwrap();
+
+        let response = fallback.complete(&tools_request()).await.unwrap();
+        assert_eq!(response.provider.as_deref(), Some("tools"));
+    }
+
+    #[tokio::test]
+    async fn test_no_capable_provider_reports_unsupported() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("a")),
+            Box::new(MockProvider::new("b")),
+        ])
+        .unwrap();
+
+        let err = fallback.complete(&tools_request()).await.unwrap_err();
+        assert!(matches!(
+            err.root(),
+            SimpleAgentsError::Provider(ProviderError::Unsupported { provider, feature: Feature::FunctionCalling })
+                if provider == "b"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_server_error_fails_over() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("down").failing(|| ProviderError::ServerError("503".to_string()))),
+            Box::new(MockProvider::new("up")),
+        ])
+        .unwrap();
+
+        let response = fallback.complete(&plain_request()).await.unwrap();
+        assert_eq!(response.provider.as_deref(), Some("up"));
+    }
+
+    #[tokio::test]
+    async fn test_fatal_error_does_not_fail_over() {
+        let backup = MockProvider::new("backup");
+        let backup_calls = backup.calls.clone();
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("strict").failing(|| ProviderError::BadRequest("bad".to_string()))),
+            Box::new(backup),
+        ])
+        .unwrap();
+
+        let err = fallback.complete(&plain_request()).await.unwrap_err();
+        assert!(matches!(err.root(), SimpleAgentsError::Provider(ProviderError::BadRequest(_))));
+        assert_eq!(backup_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_attempt_history_lists_every_provider() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("basic")),
+            Box::new(
+                MockProvider::new("down")
+                    .with_tools()
+                    .failing(|| ProviderError::ServerError("503".to_string())),
+            ),
+            Box::new(MockProvider::new("up").with_tools()),
+        ])
+        .unwrap();
+
+        let outcome = fallback.complete_with_attempts(&tools_request()).await.unwrap();
+        assert_eq!(outcome.response.provider.as_deref(), Some("up"));
+        let attempts = outcome.attempts.attempts();
+        assert_eq!(attempts.iter().map(|a| a.provider.as_str()).collect::<Vec<_>>(), ["basic", "down", "up"]);
+        assert_eq!(
+            attempts.iter().map(|a| a.error_code).collect::<Vec<_>>(),
+            [Some(ErrorCode::Unsupported), Some(ErrorCode::ServerError), None]
+        );
+        assert_eq!(attempts[0].latency, Duration::ZERO);
+
+        // Errors carry the history too
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("down").failing(|| ProviderError::ServerError("503".to_string()))),
+            Box::new(MockProvider::new("strict").failing(|| ProviderError::BadRequest("bad".to_string()))),
+            Box::new(MockProvider::new("unused")),
+        ])
+        .unwrap();
+        let err = fallback.complete_with_attempts(&plain_request()).await.unwrap_err();
+        assert!(matches!(err.root(), SimpleAgentsError::Provider(ProviderError::BadRequest(_))));
+        let history = &err.context().unwrap().attempts;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.attempts()[1].error_code, Some(ErrorCode::InvalidRequest));
+    }
+
+    #[tokio::test]
+    async fn test_error_context_records_chain_position() {
+        let down = MockProvider::new("down")
+            .with_tools()
+            .failing(|| ProviderError::ServerError("503".to_string()));
+        let strict = MockProvider::new("strict")
+            .with_tools()
+            .failing(|| ProviderError::BadRequest("bad".to_string()));
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("basic")),
+            Box::new(down),
+            Box::new(strict),
+        ])
+        .unwrap();
+
+        let err = fallback.complete(&tools_request()).await.unwrap_err();
+        let ctx = err.context().unwrap();
+        assert_eq!(ctx.attempt, 3);
+        assert_eq!(ctx.provider.as_deref(), Some("strict"));
+
+        // A chain where every provider is skipped reports the last one
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("basic")),
+            Box::new(MockProvider::new("plain")),
+        ])
+        .unwrap();
+        let err = fallback.complete(&tools_request()).await.unwrap_err();
+        assert_eq!(err.context().unwrap().attempt, 2);
+        assert_eq!(err.code(), ErrorCode::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_attempts_retries_each_provider() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("a").failing(|| ProviderError::ServerError("503".to_string()))),
+            Box::new(MockProvider::new("b").failing(|| ProviderError::Timeout(Duration::from_secs(1)))),
+        ])
+        .unwrap();
+        let policy = RetryPolicy::new(
+            RetryConfig { max_attempts: 2, jitter: false, ..RetryConfig::default() },
+            |_: Duration| async {},
+        );
+
+        let err = fallback.execute_with_attempts(&plain_request(), &policy).await.unwrap_err();
+        let history = &err.context().unwrap().attempts;
+        let providers: Vec<_> = history.attempts().iter().map(|a| a.provider.as_str()).collect();
+        assert_eq!(providers, ["a", "a", "b", "b"]);
+        assert!(history.attempts()[0].backoff > Duration::ZERO);
+        assert_eq!(history.attempts()[1].backoff, Duration::ZERO);
+        assert_eq!(history.attempts()[3].backoff, Duration::ZERO);
+        assert_eq!(err.context().unwrap().attempt, 2);
+    }
+
+    async fn streamed_by(fallback: &FallbackProvider, req: &CompletionRequest) -> Result<String> {
+        use futures::StreamExt;
+
+        let stream = fallback.execute_stream(fallback.transform_request(req)?).await?;
+        let chunks: Vec<_> = stream.collect().await;
+        let chunk = chunks.into_iter().next().unwrap()?;
+        Ok(format!("{}/{}", chunk.choices[0].delta.content.as_deref().unwrap(), chunk.model))
+    }
+
+    fn streaming_request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::user("Hello"))
+            .stream(true)
+            .extension("trace.id", "abc")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stream_skips_providers_that_cannot_stream() {
+        let basic = MockProvider::new("basic");
+        let basic_calls = basic.calls.clone();
+        let live = MockProvider::new("live").streaming();
+        let fallback = FallbackProvider::new(vec![Box::new(basic), Box::new(live)]).unwrap();
+        assert!(fallback.capabilities().supports(Feature::Streaming));
+
+        let request = fallback.transform_request(&streaming_request()).unwrap();
+        assert_eq!(request.extensions.get_as::<usize>(PROVIDER_INDEX), Some(1));
+        assert_eq!(request.extensions.get_as::<String>("trace.id").as_deref(), Some("abc"));
+        assert_eq!(streamed_by(&fallback, &streaming_request()).await.unwrap(), "live/mock-model");
+        assert_eq!(basic_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_fails_over() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(
+                MockProvider::new("down")
+                    .streaming()
+                    .failing(|| ProviderError::ServerError("503".to_string())),
+            ),
+            Box::new(MockProvider::new("basic")),
+            Box::new(MockProvider::new("up").streaming()),
+        ])
+        .unwrap();
+        assert_eq!(streamed_by(&fallback, &streaming_request()).await.unwrap(), "up/mock-model");
+
+        let fallback = FallbackProvider::new(vec![
+            Box::new(
+                MockProvider::new("strict")
+                    .streaming()
+                    .failing(|| ProviderError::BadRequest("bad".to_string())),
+            ),
+            Box::new(MockProvider::new("up").streaming()),
+        ])
+        .unwrap();
+        let err = streamed_by(&fallback, &streaming_request()).await.unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Provider(ProviderError::BadRequest(_))));
+
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("a")),
+            Box::new(MockProvider::new("b")),
+        ])
+        .unwrap();
+        let err = streamed_by(&fallback, &streaming_request()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SimpleAgentsError::Provider(ProviderError::Unsupported { feature: Feature::Streaming, .. })
+        ));
+    }
+
+    #[test]
+    fn test_capabilities_union() {
+        let fallback = FallbackProvider::new(vec![
+            Box::new(MockProvider::new("a")),
+            Box::new(MockProvider::new("b").with_tools()),
+        ])
+        .unwrap();
+
+        assert!(fallback.capabilities().supports(Feature::FunctionCalling));
+        assert!(!fallback.capabilities().supports(Feature::Streaming));
+    }
+}
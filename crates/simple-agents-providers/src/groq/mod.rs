@@ -0,0 +1,328 @@
+//! Groq provider implementation.
+//!
+//! Groq serves an OpenAI-compatible chat completions API, so this provider
+//! reuses the OpenAI request/response models, error mapping and SSE chunk
+//! parsing. On top of that it tracks the `x-ratelimit-*` headers Groq returns
+//! on every response, streamed or not.
+
+mod rate_limit;
+
+pub use rate_limit::GroqRateLimitState;
+
+use crate::openai::{self, OpenAIProvider};
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::Client;
+use simple_agents_types::prelude::*;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Groq API provider
+#[derive(Debug, Clone)]
+pub struct GroqProvider {
+    inner: OpenAIProvider,
+    rate_limit: Arc<RwLock<GroqRateLimitState>>,
+}
+
+impl GroqProvider {
+    /// Default Groq API base URL
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.groq.com/openai/v1";
+
+    /// Chat completion models served by Groq
+    pub const SUPPORTED_MODELS: &'static [&'static str] = &[
+        "llama-3.3-70b-versatile",
+        "llama-3.1-8b-instant",
+        "llama3-70b-8192",
+        "llama3-8b-8192",
+        "mixtral-8x7b-32768",
+        "gemma2-9b-it",
+    ];
+
+    /// Create a new Groq provider with default configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - Groq API key (starts with "gsk_") or [`CredentialSource`]
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, Self::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a new Groq provider with custom base URL
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - Groq API key or [`CredentialSource`]
+    /// * `base_url` - Custom base URL (e.g., for a proxy), normalized as in
+    ///   [`OpenAIProvider::with_base_url`]
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(10)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()
+            .map_err(|e| SimpleAgentsError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        Ok(Self {
+            inner: OpenAIProvider::with_client(Arc::new(credentials), base_url, client),
+            rate_limit: Arc::new(RwLock::new(GroqRateLimitState::default())),
+        })
+    }
+
+    /// Log every request at debug level before it is sent
+    ///
+    /// See [`OpenAIProvider::with_debug_requests`].
+    pub fn with_debug_requests(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.with_debug_requests(enabled);
+        self
+    }
+
+    /// Send chat completions to `url` as-is
+    ///
+    /// See [`OpenAIProvider::with_full_chat_url`].
+    pub fn with_full_chat_url(mut self, url: impl Into<String>) -> Result<Self> {
+        self.inner = self.inner.with_full_chat_url(url)?;
+        Ok(self)
+    }
+
+    /// Get the base URL for this provider
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// URL chat completions are sent to
+    pub fn chat_url(&self) -> std::borrow::Cow<'_, str> {
+        self.inner.chat_url()
+    }
+
+    /// Snapshot of the most recently reported rate limits.
+    pub fn rate_limit_state(&self) -> GroqRateLimitState {
+        self.rate_limit
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Requests left in the current window, if Groq has reported it.
+    pub fn remaining_requests(&self) -> Option<u64> {
+        self.rate_limit_state().remaining_requests
+    }
+
+    /// Tokens left in the current window, if Groq has reported it.
+    pub fn remaining_tokens(&self) -> Option<u64> {
+        self.rate_limit_state().remaining_tokens
+    }
+
+    /// Stream completion chunks for a request built by `transform_request`.
+    ///
+    /// Rate-limit headers are recorded as soon as the response starts, so
+    /// [`remaining_requests`](Self::remaining_requests) reflects this request
+    /// by the time the stream is consumed.
+    pub async fn execute_streaming(
+        &self,
+        mut req: ProviderRequest,
+    ) -> Result<impl futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin> {
+        req.body["stream"] = serde_json::Value::Bool(true);
+        let response = self.send(req).await?;
+        let events = crate::streaming::sse_events(response.bytes_stream());
+        Ok(openai::chunk_stream(events.boxed()))
+    }
+
+    /// Send a request to an endpoint not modeled by this crate.
+    ///
+    /// See [`OpenAIProvider::raw`]; rate-limit headers are recorded as for
+    /// completions.
+    pub async fn raw(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<ProviderResponse> {
+        let response = self.send_with_method(method, self.inner.raw_request(path, body)?).await?;
+        crate::utils::raw_response(response).await
+    }
+
+    /// Send a request and record its rate-limit headers.
+    async fn send(&self, req: ProviderRequest) -> Result<reqwest::Response> {
+        self.send_with_method(reqwest::Method::POST, req).await
+    }
+
+    /// Send a request with `method` and record its rate-limit headers.
+    async fn send_with_method(&self, method: reqwest::Method, req: ProviderRequest) -> Result<reqwest::Response> {
+        let response = self.inner.send_with_method(method, req).await?;
+        self.rate_limit
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .update_from_headers(response.headers());
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Provider for GroqProvider {
+    fn name(&self) -> &str {
+        "groq"
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        Self::SUPPORTED_MODELS
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            function_calling: true,
+            vision: false,
+            json_schema: false,
+            embeddings: false,
+            assistant_prefill: false,
+            documents: false,
+            max_tokens: 32768,
+        }
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        // Checked here so the error names groq rather than the inner provider
+        if req.assistant_prefill.is_some() {
+            return Err(ProviderError::Unsupported {
+                provider: self.name().to_string(),
+                feature: Feature::AssistantPrefill,
+            }
+            .into());
+        }
+
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        let response = self.send(req).await?;
+        let status = response.status();
+
+        let body = response.json::<serde_json::Value>().await
+            .map_err(|e| SimpleAgentsError::Provider(
+                ProviderError::InvalidResponse(format!("Failed to parse JSON response: {}", e))
+            ))?;
+
+        Ok(ProviderResponse {
+            status: status.as_u16(),
+            body,
+            headers: None,
+        })
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let mut response = self.inner.transform_response(resp)?;
+        response.provider = Some(self.name().to_string());
+        Ok(response)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        Ok(Box::new(self.execute_streaming(req).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SSE_BODY: &str = concat!(
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"llama-3.1-8b-instant\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"llama-3.1-8b-instant\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    fn provider(base_url: String) -> GroqProvider {
+        let api_key = ApiKey::new("gsk_test1234567890123456789012345678901234567890").unwrap();
+        GroqProvider::with_base_url(api_key, base_url).unwrap()
+    }
+
+    fn hello_request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("llama-3.1-8b-instant")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = provider(GroqProvider::DEFAULT_BASE_URL.to_string());
+        assert_eq!(provider.name(), "groq");
+        assert!(provider.supports_model("llama-3.1-8b-instant"));
+        assert!(provider.supports_model("gemma2-9b-it-2024-06-27"));
+        assert_eq!(provider.remaining_requests(), None);
+
+        let request = provider.transform_request(&hello_request()).unwrap();
+        assert_eq!(request.url, "https://api.groq.com/openai/v1/chat/completions");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_updates_rate_limits() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"stream": true})))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_header("x-ratelimit-limit-requests", "14400")
+            .with_header("x-ratelimit-remaining-requests", "14370")
+            .with_header("x-ratelimit-remaining-tokens", "17997")
+            .with_header("x-ratelimit-reset-requests", "2m59.56s")
+            .with_body(SSE_BODY)
+            .create_async()
+            .await;
+
+        let provider = provider(server.url());
+        let request = provider.transform_request(&hello_request()).unwrap();
+        let chunks: Vec<_> = provider
+            .execute_streaming(request)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        let content: String = chunks
+            .iter()
+            .map(|chunk| chunk.as_ref().unwrap().choices[0].delta.content.clone().unwrap_or_default())
+            .collect();
+        assert_eq!(content, "Hello");
+        assert_eq!(
+            chunks.last().unwrap().as_ref().unwrap().choices[0].finish_reason,
+            Some(FinishReason::Stop)
+        );
+
+        assert_eq!(provider.remaining_requests(), Some(14370));
+        assert_eq!(provider.remaining_tokens(), Some(17997));
+        let state = provider.rate_limit_state();
+        assert_eq!(state.limit_requests, Some(14400));
+        assert_eq!(state.reset_requests, Some(Duration::from_millis(179_560)));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_complete_updates_rate_limits() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_header("x-ratelimit-remaining-requests", "99")
+            .with_body(r#"{"id":"chatcmpl-2","object":"chat.completion","created":1700000000,"model":"llama-3.1-8b-instant","choices":[{"index":0,"message":{"role":"assistant","content":"Hi"},"finish_reason":"stop"}],"usage":{"prompt_tokens":3,"completion_tokens":1,"total_tokens":4}}"#)
+            .create_async()
+            .await;
+
+        let provider = provider(server.url());
+        let response = provider.complete(&hello_request()).await.unwrap();
+
+        assert_eq!(response.content(), Some("Hi"));
+        assert_eq!(response.provider.as_deref(), Some("groq"));
+        assert_eq!(provider.remaining_requests(), Some(99));
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,77 @@
+//! Rate-limit tracking from Groq response headers.
+
+use reqwest::header::HeaderMap;
+use simple_agents_types::rate_limit::parse_reset_duration;
+use std::time::Duration;
+
+/// Most recent rate-limit information reported by Groq.
+///
+/// Groq sends `x-ratelimit-*` headers on every response, including streamed
+/// ones. Fields are `None` until a response has reported them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroqRateLimitState {
+    /// Requests allowed per day
+    pub limit_requests: Option<u64>,
+    /// Tokens allowed per minute
+    pub limit_tokens: Option<u64>,
+    /// Requests left in the current window
+    pub remaining_requests: Option<u64>,
+    /// Tokens left in the current window
+    pub remaining_tokens: Option<u64>,
+    /// Time until the request limit resets
+    pub reset_requests: Option<Duration>,
+    /// Time until the token limit resets
+    pub reset_tokens: Option<Duration>,
+}
+
+impl GroqRateLimitState {
+    /// Update from response headers, keeping fields the headers omit.
+    pub fn update_from_headers(&mut self, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let count = |name: &str| header(name).and_then(|v| v.trim().parse::<u64>().ok());
+        let reset = |name: &str| header(name).and_then(parse_reset_duration);
+
+        if let Some(v) = count("x-ratelimit-limit-requests") {
+            self.limit_requests = Some(v);
+        }
+        if let Some(v) = count("x-ratelimit-limit-tokens") {
+            self.limit_tokens = Some(v);
+        }
+        if let Some(v) = count("x-ratelimit-remaining-requests") {
+            self.remaining_requests = Some(v);
+        }
+        if let Some(v) = count("x-ratelimit-remaining-tokens") {
+            self.remaining_tokens = Some(v);
+        }
+        if let Some(v) = reset("x-ratelimit-reset-requests") {
+            self.reset_requests = Some(v);
+        }
+        if let Some(v) = reset("x-ratelimit-reset-tokens") {
+            self.reset_tokens = Some(v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_update_keeps_missing_fields() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining-requests", HeaderValue::from_static("14370"));
+        headers.insert("x-ratelimit-reset-tokens", HeaderValue::from_static("7.66s"));
+
+        let mut state = GroqRateLimitState {
+            limit_requests: Some(14400),
+            ..Default::default()
+        };
+        state.update_from_headers(&headers);
+
+        assert_eq!(state.limit_requests, Some(14400));
+        assert_eq!(state.remaining_requests, Some(14370));
+        assert_eq!(state.reset_tokens, Some(Duration::from_millis(7660)));
+        assert_eq!(state.remaining_tokens, None);
+    }
+}
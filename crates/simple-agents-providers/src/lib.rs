@@ -9,6 +9,17 @@
 //!
 //! - [`openai`]: OpenAI API (GPT-4, GPT-3.5-Turbo, etc.)
 //! - [`anthropic`]: Anthropic API (Claude 3 Opus, Sonnet, Haiku)
+//! - [`groq`]: Groq API (Llama, Mixtral, Gemma)
+//! - [`lmstudio`]: Local LM Studio server
+//! - [`ollama`]: Self-hosted Ollama server
+//! - [`vllm`]: Self-hosted vLLM server
+//!
+//! # Writing a provider
+//!
+//! Providers in other crates implement [`Provider`] with the building
+//! blocks in [`provider_kit`]: request assembly, sending with the standard
+//! error handling, and HTTP status classification. The built-in providers
+//! use the same pieces.
 //!
 //! # Examples
 //!
@@ -36,8 +47,36 @@
 
 pub mod openai;
 pub mod anthropic;
+pub mod azure;
+pub mod adaptive;
+#[cfg(feature = "sqlite-audit")]
+pub mod audit;
+pub mod batch;
+pub mod bridge;
+pub mod credentials;
+pub mod fallback;
+pub mod groq;
+pub mod lmstudio;
+pub mod notifier;
+pub mod ollama;
+pub mod optimization;
+pub mod pacing;
+pub mod provider_kit;
+#[cfg(feature = "proxy")]
+pub mod proxy;
+pub mod reconnect;
 pub mod retry;
-mod utils;
+pub mod scheduler;
+pub mod shutdown;
+pub mod store;
+pub mod streaming;
+pub mod telemetry;
+pub mod tiered;
+pub mod tls;
+pub mod vllm;
+pub mod warmup;
+pub mod utils;
+pub mod webhook;
 
 // Re-export common types from simple-agents-types
 pub use simple_agents_types::prelude::{Provider, ProviderRequest, ProviderResponse};
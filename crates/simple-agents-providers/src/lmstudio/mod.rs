@@ -0,0 +1,237 @@
+//! LM Studio provider implementation.
+//!
+//! LM Studio's local server speaks the OpenAI chat completions protocol
+//! exactly and needs no API key, so this provider delegates to
+//! [`OpenAIProvider`]. It exists as its own type so local traffic is
+//! reported as `lmstudio` in logs and responses rather than `openai`.
+
+use crate::openai::{self, ModelInfo, OpenAIModelList, OpenAIProvider};
+use crate::utils::LOCAL_SERVER_TIMEOUT;
+use async_trait::async_trait;
+use futures::StreamExt;
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// LM Studio local server provider
+#[derive(Debug, Clone)]
+pub struct LmStudioProvider {
+    inner: OpenAIProvider,
+}
+
+impl LmStudioProvider {
+    /// Default address of the LM Studio server
+    pub const DEFAULT_BASE_URL: &'static str = "http://localhost:1234/v1";
+
+    /// Create a provider for the server at its default address
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new() -> Result<Self> {
+        Self::with_base_url(Self::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a provider for a server at `base_url`
+    ///
+    /// The URL is normalized as in [`OpenAIProvider::with_base_url`].
+    pub fn with_base_url(base_url: String) -> Result<Self> {
+        let credentials = ApiKey::new(crate::utils::LOCAL_SERVER_KEY)?;
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        let client = crate::utils::local_server_client()?;
+        Ok(Self {
+            inner: OpenAIProvider::with_client(Arc::new(credentials), base_url, client),
+        })
+    }
+
+    /// Connect with custom TLS settings, e.g. for a server behind an
+    /// internal CA or one requiring client certificates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `tls` is invalid or not
+    /// supported by the enabled TLS backend; see [`TlsConfig::apply`].
+    pub fn with_tls(mut self, tls: &crate::tls::TlsConfig) -> Result<Self> {
+        let client = crate::utils::build_client(tls.apply(crate::utils::local_server_client_builder())?)?;
+        self.inner = self.inner.with_http_client(client);
+        Ok(self)
+    }
+
+    /// Log every request at debug level before it is sent
+    ///
+    /// See [`OpenAIProvider::with_debug_requests`].
+    pub fn with_debug_requests(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.with_debug_requests(enabled);
+        self
+    }
+
+    /// Get the base URL for this provider
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// URL chat completions are sent to
+    pub fn chat_url(&self) -> std::borrow::Cow<'_, str> {
+        self.inner.chat_url()
+    }
+
+    /// A provider for the raw `/completions` endpoint of the same server,
+    /// rendering prompts with the chat template
+    /// [guessed](openai::ChatTemplate::for_model) from `model`.
+    pub fn text_completions(&self, model: &str) -> openai::TextCompletionProvider {
+        openai::TextCompletionProvider::new(self.inner.clone())
+            .with_template(openai::ChatTemplate::for_model(model))
+            .with_name("lmstudio")
+    }
+
+    /// Models the server can answer with right now.
+    ///
+    /// These are the loaded models, or every downloaded model when
+    /// just-in-time loading is enabled in LM Studio.
+    pub async fn list_loaded_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = self.inner.raw(reqwest::Method::GET, "/models", serde_json::Value::Null).await?;
+        let list: OpenAIModelList = serde_json::from_value(response.body).map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to deserialize model list: {}",
+                e
+            )))
+        })?;
+        Ok(list.data)
+    }
+
+    /// Stream completion chunks for a request built by `transform_request`.
+    pub async fn execute_streaming(
+        &self,
+        mut req: ProviderRequest,
+    ) -> Result<impl futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin> {
+        req.body["stream"] = serde_json::Value::Bool(true);
+        let response = self.inner.send(req).await?;
+        let events = crate::streaming::sse_events(response.bytes_stream());
+        Ok(openai::chunk_stream(events.boxed()))
+    }
+}
+
+#[async_trait]
+impl Provider for LmStudioProvider {
+    fn name(&self) -> &str {
+        "lmstudio"
+    }
+
+    fn model_info(&self, _model: &str) -> Option<ModelInfoStatic> {
+        // Limits depend on how the server loaded the model, and runs are free
+        None
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            function_calling: true,
+            vision: false,
+            json_schema: false,
+            embeddings: false,
+            assistant_prefill: false,
+            documents: false,
+            max_tokens: 32768,
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        LOCAL_SERVER_TIMEOUT
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        // Checked here so the error names lmstudio rather than the inner provider
+        if req.assistant_prefill.is_some() {
+            return Err(ProviderError::Unsupported {
+                provider: self.name().to_string(),
+                feature: Feature::AssistantPrefill,
+            }
+            .into());
+        }
+
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let mut response = self.inner.transform_response(resp)?;
+        response.provider = Some(self.name().to_string());
+        Ok(response)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        Ok(Box::new(self.execute_streaming(req).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = LmStudioProvider::new().unwrap();
+        assert_eq!(provider.name(), "lmstudio");
+        assert_eq!(provider.base_url(), "http://localhost:1234/v1");
+        assert_eq!(provider.chat_url(), "http://localhost:1234/v1/chat/completions");
+        assert!(provider.supports_model("qwen2.5-7b-instruct"));
+
+        let custom = LmStudioProvider::with_base_url("http://192.168.1.20:1234/v1/".to_string()).unwrap();
+        assert_eq!(custom.chat_url(), "http://192.168.1.20:1234/v1/chat/completions");
+    }
+
+    #[tokio::test]
+    async fn test_list_loaded_models() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/models")
+            .with_status(200)
+            .with_body(
+                r#"{"object": "list", "data": [
+                    {"id": "qwen2.5-7b-instruct", "object": "model", "owned_by": "organization_owner"},
+                    {"id": "text-embedding-nomic-embed-text-v1.5", "object": "model"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let provider = LmStudioProvider::with_base_url(format!("{}/v1", server.url())).unwrap();
+        let models = provider.list_loaded_models().await.unwrap();
+        mock.assert_async().await;
+
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["qwen2.5-7b-instruct", "text-embedding-nomic-embed-text-v1.5"]);
+        assert_eq!(models[0].owned_by.as_deref(), Some("organization_owner"));
+    }
+
+    #[test]
+    fn test_transform_response_names_provider() {
+        let provider = LmStudioProvider::new().unwrap();
+        let response = provider
+            .transform_response(ProviderResponse {
+                status: 200,
+                body: serde_json::json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 1700000000,
+                    "model": "qwen2.5-7b-instruct",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "Hi"},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6}
+                }),
+                headers: None,
+            })
+            .unwrap();
+        assert_eq!(response.provider.as_deref(), Some("lmstudio"));
+        assert_eq!(response.content(), Some("Hi"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Background delivery of [`Event`]s.
+//!
+//! [`EventNotifier::notify`] is awaited by the operation that raised the
+//! event, so a notifier that retries a slow endpoint (such as
+//! [`WebhookNotifier`](crate::webhook::WebhookNotifier)) holds that
+//! operation up. [`BackgroundNotifier`] queues events instead and delivers
+//! them from a background task.
+
+use crate::shutdown::{CancellationToken, Close};
+use async_trait::async_trait;
+use simple_agents_types::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Notifier that queues events for delivery by a background task.
+///
+/// Events are delivered one at a time, in order. When the queue is full,
+/// new events are logged and dropped rather than blocking the caller.
+///
+/// [`Close::close`] stops accepting events, waits for the queued ones to be
+/// delivered (up to [`with_close_deadline`](Self::with_close_deadline)) and
+/// stops the task. Cancelling the shutdown token passed to
+/// [`with_shutdown`](Self::with_shutdown) does the same without waiting.
+/// Dropping the notifier also stops it without waiting; queued events are
+/// delivered on a best-effort basis (see [`crate::shutdown`]).
+pub struct BackgroundNotifier {
+    sender: mpsc::Sender<Event>,
+    token: CancellationToken,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    close_deadline: Duration,
+}
+
+impl std::fmt::Debug for BackgroundNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackgroundNotifier")
+            .field("pending", &self.pending())
+            .field("close_deadline", &self.close_deadline)
+            .field("closed", &self.token.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackgroundNotifier {
+    /// Time [`Close::close`] waits for queued events unless set with
+    /// [`with_close_deadline`](Self::with_close_deadline)
+    pub const DEFAULT_CLOSE_DEADLINE: Duration = Duration::from_secs(30);
+
+    /// Deliver events to `inner` from a background task, queueing up to
+    /// `capacity` of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime, or if `capacity` is 0.
+    pub fn new(inner: Arc<dyn EventNotifier>, capacity: usize) -> Self {
+        Self::with_shutdown(inner, capacity, CancellationToken::new())
+    }
+
+    /// Like [`new`](Self::new), but also stop when `shutdown` is cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime, or if `capacity` is 0.
+    pub fn with_shutdown(
+        inner: Arc<dyn EventNotifier>,
+        capacity: usize,
+        shutdown: CancellationToken,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity);
+        // A child token, so closing this notifier does not cancel its siblings
+        let token = shutdown.child_token();
+        let worker = tokio::spawn(deliver(inner, receiver, token.clone()));
+        Self {
+            sender,
+            token,
+            worker: Mutex::new(Some(worker)),
+            close_deadline: Self::DEFAULT_CLOSE_DEADLINE,
+        }
+    }
+
+    /// Set how long [`Close::close`] waits for queued events (builder pattern).
+    pub fn with_close_deadline(mut self, deadline: Duration) -> Self {
+        self.close_deadline = deadline;
+        self
+    }
+
+    /// Events queued but not yet handed to the inner notifier.
+    pub fn pending(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+}
+
+/// Deliver events until cancelled, then deliver whatever is still queued.
+async fn deliver(
+    inner: Arc<dyn EventNotifier>,
+    mut receiver: mpsc::Receiver<Event>,
+    token: CancellationToken,
+) {
+    loop {
+        tokio::select! {
+            biased;
+            event = receiver.recv() => match event {
+                Some(event) => inner.notify(event).await,
+                None => return,
+            },
+            _ = token.cancelled() => break,
+        }
+    }
+
+    receiver.close();
+    while let Some(event) = receiver.recv().await {
+        inner.notify(event).await;
+    }
+}
+
+#[async_trait]
+impl EventNotifier for BackgroundNotifier {
+    async fn notify(&self, event: Event) {
+        if self.token.is_cancelled() {
+            tracing::debug!(event = event.kind(), "Notifier is closed; dropping event");
+            return;
+        }
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                tracing::warn!(event = event.kind(), "Notifier queue is full; dropping event");
+            }
+            Err(mpsc::error::TrySendError::Closed(event)) => {
+                tracing::debug!(event = event.kind(), "Notifier is closed; dropping event");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Close for BackgroundNotifier {
+    async fn close(&self) {
+        self.token.cancel();
+        let worker = self.worker.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let Some(mut worker) = worker else {
+            return;
+        };
+        if tokio::time::timeout(self.close_deadline, &mut worker).await.is_err() {
+            worker.abort();
+            tracing::warn!(
+                pending = self.pending(),
+                deadline = ?self.close_deadline,
+                "Notifier did not finish delivering before its deadline; dropping queued events"
+            );
+        }
+    }
+}
+
+impl Drop for BackgroundNotifier {
+    fn drop(&mut self) {
+        // Let the task finish the queue on its own; never block here
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown::ShutdownCoordinator;
+
+    /// Records events after a delay per event
+    struct Recorder {
+        delay: Duration,
+        events: Mutex<Vec<Event>>,
+    }
+
+    #[async_trait]
+    impl EventNotifier for Recorder {
+        async fn notify(&self, event: Event) {
+            tokio::time::sleep(self.delay).await;
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    fn recorder(delay: Duration) -> Arc<Recorder> {
+        Arc::new(Recorder {
+            delay,
+            events: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn event(failures: u32) -> Event {
+        Event::CircuitOpened {
+            provider: "openai".to_string(),
+            failures,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_close_flushes_queued_events() {
+        let inner = recorder(Duration::from_millis(100));
+        let notifier = BackgroundNotifier::new(inner.clone(), 16);
+
+        for failures in 0..5 {
+            notifier.notify(event(failures)).await;
+        }
+        assert!(inner.events.lock().unwrap().len() < 5);
+
+        notifier.close().await;
+        assert_eq!(*inner.events.lock().unwrap(), (0..5).map(event).collect::<Vec<_>>());
+        assert_eq!(notifier.pending(), 0);
+
+        // Closed notifiers drop new events
+        notifier.notify(event(5)).await;
+        notifier.close().await;
+        assert_eq!(inner.events.lock().unwrap().len(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_close_respects_deadline() {
+        let inner = recorder(Duration::from_secs(3600));
+        let notifier = BackgroundNotifier::new(inner.clone(), 16).with_close_deadline(Duration::from_secs(2));
+        notifier.notify(event(1)).await;
+        notifier.notify(event(2)).await;
+
+        let started = tokio::time::Instant::now();
+        notifier.close().await;
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        assert!(inner.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_full_queue_drops_events() {
+        let inner = recorder(Duration::from_secs(1));
+        let notifier = BackgroundNotifier::new(inner.clone(), 2);
+        for failures in 0..10 {
+            notifier.notify(event(failures)).await;
+        }
+        notifier.close().await;
+        // At most one in delivery when the queue filled and two queued behind it
+        assert!(inner.events.lock().unwrap().len() <= 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_coordinator_shutdown_drains_notifier() {
+        let coordinator = ShutdownCoordinator::new().with_deadline(Duration::from_secs(10));
+        let inner = recorder(Duration::from_millis(100));
+        let notifier = Arc::new(BackgroundNotifier::with_shutdown(inner.clone(), 16, coordinator.token()));
+        coordinator.register("events", notifier.clone());
+
+        for failures in 0..3 {
+            notifier.notify(event(failures)).await;
+        }
+        let report = coordinator.shutdown().await;
+
+        assert!(report.is_clean());
+        assert_eq!(report.closed, vec!["events".to_string()]);
+        assert_eq!(inner.events.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,242 @@
+//! Ollama provider implementation.
+//!
+//! Ollama serves an OpenAI-compatible chat completions API under `/v1`, so
+//! this provider reuses the OpenAI request/response models, error mapping and
+//! SSE chunk parsing. Ollama keeps the KV cache of the last prompt evaluated
+//! for a loaded model; [`Provider::prefill_cache`] loads the model and
+//! evaluates the system prompt ahead of the first real request.
+
+use crate::openai::{self, OpenAIProvider};
+use crate::utils::LOCAL_SERVER_TIMEOUT;
+use async_trait::async_trait;
+use futures::StreamExt;
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Ollama server provider
+#[derive(Debug, Clone)]
+pub struct OllamaProvider {
+    inner: OpenAIProvider,
+    model: String,
+}
+
+impl OllamaProvider {
+    /// Default address of `ollama serve`
+    pub const DEFAULT_BASE_URL: &'static str = "http://localhost:11434/v1";
+
+    /// Create a provider for a local server started without an API key
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - Name of the served model, used by
+    ///   [`prefill_cache`](Provider::prefill_cache)
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(model: impl Into<String>) -> Result<Self> {
+        Self::with_base_url(Self::DEFAULT_BASE_URL.to_string(), model)
+    }
+
+    /// Create a provider for a server at `base_url`
+    ///
+    /// The URL is normalized as in [`OpenAIProvider::with_base_url`].
+    pub fn with_base_url(base_url: String, model: impl Into<String>) -> Result<Self> {
+        let key = ApiKey::new(crate::utils::LOCAL_SERVER_KEY)?;
+        Self::with_credentials(key, base_url, model)
+    }
+
+    /// Create a provider for a server behind an authenticating proxy
+    pub fn with_credentials(
+        credentials: impl CredentialSource + 'static,
+        base_url: String,
+        model: impl Into<String>,
+    ) -> Result<Self> {
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        let client = crate::utils::local_server_client()?;
+        Ok(Self {
+            inner: OpenAIProvider::with_client(Arc::new(credentials), base_url, client),
+            model: model.into(),
+        })
+    }
+
+    /// Connect with custom TLS settings, e.g. for a server behind an
+    /// internal CA or one requiring client certificates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `tls` is invalid or not
+    /// supported by the enabled TLS backend; see [`TlsConfig::apply`].
+    pub fn with_tls(mut self, tls: &crate::tls::TlsConfig) -> Result<Self> {
+        let client = crate::utils::build_client(tls.apply(crate::utils::local_server_client_builder())?)?;
+        self.inner = self.inner.with_http_client(client);
+        Ok(self)
+    }
+
+    /// Log every request at debug level before it is sent
+    ///
+    /// See [`OpenAIProvider::with_debug_requests`].
+    pub fn with_debug_requests(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.with_debug_requests(enabled);
+        self
+    }
+
+    /// Get the base URL for this provider
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// URL chat completions are sent to
+    pub fn chat_url(&self) -> std::borrow::Cow<'_, str> {
+        self.inner.chat_url()
+    }
+
+    /// Name of the served model
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Stream completion chunks for a request built by `transform_request`.
+    pub async fn execute_streaming(
+        &self,
+        mut req: ProviderRequest,
+    ) -> Result<impl futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin> {
+        req.body["stream"] = serde_json::Value::Bool(true);
+        let response = self.inner.send(req).await?;
+        let events = crate::streaming::sse_events(response.bytes_stream());
+        Ok(openai::chunk_stream(events.boxed()))
+    }
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn model_info(&self, _model: &str) -> Option<ModelInfoStatic> {
+        // Limits depend on how the server loaded the model, and runs are free
+        None
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            function_calling: true,
+            vision: false,
+            json_schema: false,
+            embeddings: false,
+            assistant_prefill: false,
+            documents: false,
+            max_tokens: 32768,
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        LOCAL_SERVER_TIMEOUT
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        // Checked here so the error names ollama rather than the inner provider
+        if req.assistant_prefill.is_some() {
+            return Err(ProviderError::Unsupported {
+                provider: self.name().to_string(),
+                feature: Feature::AssistantPrefill,
+            }
+            .into());
+        }
+
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let mut response = self.inner.transform_response(resp)?;
+        response.provider = Some(self.name().to_string());
+        Ok(response)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        Ok(Box::new(self.execute_streaming(req).await?))
+    }
+
+    /// Generate one token after `system_prompt` so Ollama caches its prefix.
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        crate::utils::prefill_system_prompt(self, &self.model, system_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPLETION: &str = r#"{"id":"cmpl-1","object":"chat.completion","created":1700000000,"model":"llama3.1","choices":[{"index":0,"message":{"role":"assistant","content":"OK"},"finish_reason":"length"}],"usage":{"prompt_tokens":12,"completion_tokens":1,"total_tokens":13}}"#;
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = OllamaProvider::new("llama3.1").unwrap();
+        assert_eq!(provider.name(), "ollama");
+        assert_eq!(provider.model(), "llama3.1");
+        assert!(provider.supports_model("any-served-model"));
+        assert_eq!(provider.chat_url(), "http://localhost:11434/v1/chat/completions");
+    }
+
+    #[tokio::test]
+    async fn test_prefill_cache_token_attaches_prompt() {
+        let prompt = "You are a support agent for Acme. Be brief.";
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "llama3.1",
+                "max_tokens": 1,
+                "messages": [{"role": "system", "content": prompt}]
+            })))
+            .with_status(200)
+            .with_body(COMPLETION)
+            .create_async()
+            .await;
+
+        let base_url = format!("{}/v1", server.url());
+        let provider = OllamaProvider::with_base_url(base_url, "llama3.1").unwrap();
+        let token = provider.prefill_cache(prompt).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(token.provider(), Some("ollama"));
+        assert_eq!(token.model(), Some("llama3.1"));
+        assert_eq!(token.system_prompt(), Some(prompt));
+
+        let request = CompletionRequest::builder()
+            .model("llama3.1")
+            .message(Message::user("Where is my order?"))
+            .build()
+            .unwrap()
+            .with_prefill_token(&token);
+        let body = provider.transform_request(&request).unwrap().body;
+        assert_eq!(
+            body["messages"][0],
+            serde_json::json!({"role": "system", "content": prompt})
+        );
+        assert_eq!(body["messages"][1]["content"], "Where is my order?");
+    }
+
+    #[tokio::test]
+    async fn test_prefill_cache_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(404)
+            .with_body(r#"{"error":{"message":"model \"other\" not found, try pulling it first"}}"#)
+            .create_async()
+            .await;
+
+        let provider = OllamaProvider::with_base_url(format!("{}/v1", server.url()), "other").unwrap();
+        assert!(provider.prefill_cache("Be brief.").await.is_err());
+    }
+}
@@ -0,0 +1,597 @@
+//! Client for OpenAI batch jobs.
+//!
+//! A batch runs a JSONL file of requests (see
+//! [`BatchRequestFile`](simple_agents_types::batch::BatchRequestFile))
+//! asynchronously within a completion window. Results are written to an
+//! output file, readable with
+//! [`BatchResponseFile`](simple_agents_types::batch::BatchResponseFile).
+
+use super::{error_for_status, map_request_error, OpenAIProvider};
+use crate::credentials::send_with_refresh;
+use crate::provider_kit;
+use crate::utils::auth::{self, AuthScheme};
+use futures::Stream;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::batch::BatchResult;
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Endpoint the requests in a batch are sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BatchEndpoint {
+    /// `/v1/chat/completions`
+    #[serde(rename = "/v1/chat/completions")]
+    ChatCompletions,
+    /// `/v1/embeddings`
+    #[serde(rename = "/v1/embeddings")]
+    Embeddings,
+    /// `/v1/completions`
+    #[serde(rename = "/v1/completions")]
+    Completions,
+}
+
+impl BatchEndpoint {
+    /// Endpoint path, as sent to the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChatCompletions => "/v1/chat/completions",
+            Self::Embeddings => "/v1/embeddings",
+            Self::Completions => "/v1/completions",
+        }
+    }
+}
+
+/// Time within which a batch is processed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CompletionWindow {
+    /// 24 hours, the only window the API currently offers
+    #[default]
+    #[serde(rename = "24h")]
+    TwentyFourHours,
+}
+
+impl CompletionWindow {
+    /// Window, as sent to the API (e.g. "24h").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TwentyFourHours => "24h",
+        }
+    }
+}
+
+/// Status of a [`Batch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// Input file being validated
+    Validating,
+    /// Input file failed validation
+    Failed,
+    /// Requests being processed
+    InProgress,
+    /// Results being written
+    Finalizing,
+    /// Finished; results are in the output file
+    Completed,
+    /// Not finished within the completion window
+    Expired,
+    /// Cancellation requested
+    Cancelling,
+    /// Cancelled
+    Cancelled,
+    /// Status this client does not know about
+    #[serde(other)]
+    Unknown,
+}
+
+impl BatchStatus {
+    /// Whether the batch has stopped and will not change status again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Expired | Self::Cancelled)
+    }
+}
+
+/// Request counts of a [`Batch`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRequestCounts {
+    /// Requests in the batch
+    pub total: u32,
+    /// Requests that completed
+    pub completed: u32,
+    /// Requests that failed
+    pub failed: u32,
+}
+
+/// A batch job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Batch {
+    /// Batch identifier (e.g. "batch_abc123")
+    pub id: String,
+    /// Current status
+    pub status: BatchStatus,
+    /// Endpoint the requests are sent to
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Input file
+    #[serde(default)]
+    pub input_file_id: Option<String>,
+    /// File with successful results, once available
+    #[serde(default)]
+    pub output_file_id: Option<String>,
+    /// File with failed requests, once available
+    #[serde(default)]
+    pub error_file_id: Option<String>,
+    /// Unix timestamp of creation, in seconds
+    #[serde(default)]
+    pub created_at: i64,
+    /// Progress counts
+    #[serde(default)]
+    pub request_counts: Option<BatchRequestCounts>,
+}
+
+/// Uploaded file, as returned by `POST /files`
+#[derive(Debug, Deserialize)]
+struct UploadedFile {
+    id: String,
+}
+
+/// Paginated list wrapper used by list endpoints
+#[derive(Debug, Deserialize)]
+struct ListResponse<T> {
+    data: Vec<T>,
+}
+
+/// Request body accepted by [`BatchClient::send`]
+enum Body {
+    Empty,
+    Json(serde_json::Value),
+    /// multipart/form-data with the given boundary
+    Multipart { boundary: String, bytes: Vec<u8> },
+}
+
+/// OpenAI batch jobs client.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::{BatchClient, BatchEndpoint, BatchStatus, CompletionWindow};
+/// use simple_agents_types::batch::{BatchRequestFile, BatchResponseFile};
+/// use simple_agents_types::prelude::*;
+/// use std::time::Duration;
+///
+/// # async fn example(requests: Vec<(String, CompletionRequest)>) -> Result<()> {
+/// let client = BatchClient::new(ApiKey::new("sk-...")?)?;
+///
+/// let file = BatchRequestFile::from_requests(requests);
+/// let batch = client
+///     .create(&file, BatchEndpoint::ChatCompletions, CompletionWindow::TwentyFourHours)
+///     .await?;
+///
+/// let batch = client
+///     .wait_for_completion(&batch.id, Duration::from_secs(60), Duration::from_secs(24 * 3600))
+///     .await?;
+/// if let (BatchStatus::Completed, Some(output)) = (batch.status, &batch.output_file_id) {
+///     for result in BatchResponseFile::parse(&client.file_content(output).await?)? {
+///         println!("{}: {:?}", result.custom_id, result.completion()?.content());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct BatchClient {
+    credentials: Arc<dyn CredentialSource>,
+    base_url: String,
+    client: Client,
+}
+
+impl std::fmt::Debug for BatchClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchClient")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BatchClient {
+    /// Create a client for the default OpenAI API base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, OpenAIProvider::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a client with a custom base URL.
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        let client = Client::builder()
+            // Input files can be large
+            .timeout(Duration::from_secs(300))
+            .pool_max_idle_per_host(10)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()
+            .map_err(|e| SimpleAgentsError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        Ok(Self::with_client(Arc::new(credentials), base_url, client))
+    }
+
+    /// Create a client that sends requests with `client`.
+    pub(crate) fn with_client(credentials: Arc<dyn CredentialSource>, base_url: String, client: Client) -> Self {
+        Self {
+            credentials,
+            base_url,
+            client,
+        }
+    }
+
+    /// Get the base URL for this client
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Upload `file_content` (a JSONL batch input file) and start a batch.
+    pub async fn create(
+        &self,
+        file_content: &str,
+        endpoint: BatchEndpoint,
+        completion_window: CompletionWindow,
+    ) -> Result<Batch> {
+        let file: UploadedFile = self.send(reqwest::Method::POST, "files", upload_body(file_content)).await?;
+        tracing::debug!(file_id = %file.id, "Uploaded batch input file");
+
+        let body = serde_json::json!({
+            "input_file_id": file.id,
+            "endpoint": endpoint.as_str(),
+            "completion_window": completion_window.as_str(),
+        });
+        self.send(reqwest::Method::POST, "batches", Body::Json(body)).await
+    }
+
+    /// Fetch the current state of a batch.
+    pub async fn retrieve(&self, id: &str) -> Result<Batch> {
+        self.send(reqwest::Method::GET, &format!("batches/{}", id), Body::Empty).await
+    }
+
+    /// Request cancellation of a batch.
+    ///
+    /// The batch moves to [`BatchStatus::Cancelling`] and, shortly after,
+    /// to [`BatchStatus::Cancelled`].
+    pub async fn cancel(&self, id: &str) -> Result<Batch> {
+        self.send(reqwest::Method::POST, &format!("batches/{}/cancel", id), Body::Empty).await
+    }
+
+    /// List up to `limit` batches, newest first.
+    pub async fn list(&self, limit: u32) -> Result<Vec<Batch>> {
+        let list: ListResponse<Batch> = self
+            .send(reqwest::Method::GET, &format!("batches?limit={}", limit), Body::Empty)
+            .await?;
+        Ok(list.data)
+    }
+
+    /// Poll a batch every `poll_interval` until it is completed, failed,
+    /// expired or cancelled (see [`BatchStatus::is_terminal`]).
+    ///
+    /// Failed and expired batches are returned as `Ok`; inspect
+    /// [`Batch::status`] and [`Batch::error_file_id`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::Timeout`] if the batch has not finished
+    /// within `timeout`.
+    pub async fn wait_for_completion(&self, id: &str, poll_interval: Duration, timeout: Duration) -> Result<Batch> {
+        let started = Instant::now();
+        loop {
+            let batch = self.retrieve(id).await?;
+            if batch.status.is_terminal() {
+                return Ok(batch);
+            }
+
+            tracing::debug!(batch_id = id, status = ?batch.status, "Batch not finished yet");
+            if started.elapsed() + poll_interval > timeout {
+                return Err(ProviderError::Timeout(timeout).into());
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Download a file, such as a batch's output or error file.
+    pub async fn file_content(&self, file_id: &str) -> Result<String> {
+        let response = self
+            .send_raw(reqwest::Method::GET, &format!("files/{}/content", file_id), &Body::Empty)
+            .await?;
+        response.text().await.map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!("Failed to read file content: {}", e)))
+        })
+    }
+
+    /// Stream the results in a batch's output (or error) file as it
+    /// downloads.
+    ///
+    /// Unlike [`file_content`](Self::file_content) with
+    /// [`BatchResponseFile::parse`](simple_agents_types::batch::BatchResponseFile::parse),
+    /// only one line is held at a time, which keeps memory flat for output
+    /// files of many megabytes. A line that fails to parse yields an error
+    /// and the following lines are still read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use futures::StreamExt;
+    /// use simple_agents_providers::openai::BatchClient;
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// # async fn example(client: BatchClient, output_file_id: &str) -> Result<()> {
+    /// let mut results = std::pin::pin!(client.results(output_file_id).await?);
+    /// while let Some(result) = results.next().await {
+    ///     let result = result?;
+    ///     println!("{}: {:?}", result.custom_id, result.completion()?.content());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn results(&self, file_id: &str) -> Result<impl Stream<Item = Result<BatchResult>> + Send> {
+        let response = self
+            .send_raw(reqwest::Method::GET, &format!("files/{}/content", file_id), &Body::Empty)
+            .await?;
+        Ok(provider_kit::json_lines(response))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, method: reqwest::Method, path: &str, body: Body) -> Result<T> {
+        let response = self.send_raw(method, path, &body).await?;
+        provider_kit::typed_response(response, provider_kit::DEFAULT_RAW_BODY_LIMIT).await
+    }
+
+    async fn send_raw(&self, method: reqwest::Method, path: &str, body: &Body) -> Result<reqwest::Response> {
+        let url = format!("{}/{}", self.base_url, path);
+        send_with_refresh(self.credentials.as_ref(), |key| {
+            let auth = auth::header(AuthScheme::Bearer, &key);
+            let request = self.client.request(method.clone(), &url);
+            let request = match body {
+                Body::Empty => request,
+                Body::Json(json) => request.json(json),
+                Body::Multipart { boundary, bytes } => request
+                    .header(
+                        reqwest::header::CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={}", boundary),
+                    )
+                    .body(bytes.clone()),
+            };
+            async move {
+                let (name, value) = auth?;
+                let response = request.header(name, value).send().await.map_err(map_request_error)?;
+                error_for_status(response).await
+            }
+        })
+        .await
+    }
+}
+
+/// Build the multipart body uploading `content` as a batch input file.
+fn upload_body(content: &str) -> Body {
+    // The boundary must not occur in the content
+    let boundary = (0u32..)
+        .map(|n| format!("simple-agents-batch-{}", n))
+        .find(|boundary| !content.contains(boundary.as_str()))
+        .expect("some boundary is absent from the content");
+
+    let mut bytes = Vec::with_capacity(content.len() + 256);
+    bytes.extend_from_slice(
+        format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"purpose\"\r\n\r\nbatch\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"batch.jsonl\"\r\n\
+             Content-Type: application/jsonl\r\n\r\n",
+            b = boundary
+        )
+        .as_bytes(),
+    );
+    bytes.extend_from_slice(content.as_bytes());
+    bytes.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    Body::Multipart { boundary, bytes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API_KEY: &str = "sk-test1234567890123456789012345678901234567890";
+
+    fn client(server: &mockito::ServerGuard) -> BatchClient {
+        BatchClient::with_base_url(ApiKey::new(API_KEY).unwrap(), server.url()).unwrap()
+    }
+
+    fn batch_body(status: &str) -> String {
+        let finished = status == "completed";
+        serde_json::json!({
+            "id": "batch_1",
+            "object": "batch",
+            "endpoint": "/v1/chat/completions",
+            "input_file_id": "file-in",
+            "completion_window": "24h",
+            "status": status,
+            "output_file_id": finished.then_some("file-out"),
+            "error_file_id": null,
+            "created_at": 1_700_000_000,
+            "request_counts": {"total": 2, "completed": if finished { 2 } else { 0 }, "failed": 0}
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_completion_polls_until_terminal() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        // mockito serves the first matching mock that still expects hits
+        for status in ["validating", "in_progress", "finalizing", "completed"] {
+            mocks.push(
+                server
+                    .mock("GET", "/batches/batch_1")
+                    .match_header("authorization", format!("Bearer {}", API_KEY).as_str())
+                    .with_status(200)
+                    .with_body(batch_body(status))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let batch = client(&server)
+            .wait_for_completion("batch_1", Duration::from_millis(1), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(batch.status, BatchStatus::Completed);
+        assert_eq!(batch.output_file_id.as_deref(), Some("file-out"));
+        assert_eq!(batch.request_counts.unwrap().completed, 2);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_completion_returns_failed_and_expired() {
+        for status in [BatchStatus::Failed, BatchStatus::Expired] {
+            let mut server = mockito::Server::new_async().await;
+            let name = serde_json::to_value(status).unwrap();
+            server
+                .mock("GET", "/batches/batch_1")
+                .with_status(200)
+                .with_body(batch_body(name.as_str().unwrap()))
+                .expect(1)
+                .create_async()
+                .await;
+
+            let batch = client(&server)
+                .wait_for_completion("batch_1", Duration::from_millis(1), Duration::from_secs(5))
+                .await
+                .unwrap();
+            assert_eq!(batch.status, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_completion_times_out() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/batches/batch_1")
+            .with_status(200)
+            .with_body(batch_body("in_progress"))
+            .create_async()
+            .await;
+
+        let result = client(&server)
+            .wait_for_completion("batch_1", Duration::from_millis(1), Duration::from_millis(20))
+            .await;
+        assert!(matches!(
+            result,
+            Err(SimpleAgentsError::Provider(ProviderError::Timeout(t))) if t == Duration::from_millis(20)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_uploads_file_then_starts_batch() {
+        let file = "{\"custom_id\": \"req-1\"}\n";
+        let mut server = mockito::Server::new_async().await;
+        let upload = server
+            .mock("POST", "/files")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Exact("multipart/form-data; boundary=simple-agents-batch-0".to_string()),
+            )
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"purpose\"\r\n\r\nbatch\r\n".to_string()),
+                mockito::Matcher::Regex("filename=\"batch.jsonl\"".to_string()),
+                mockito::Matcher::Regex("custom_id".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"id": "file-in", "object": "file", "purpose": "batch"}"#)
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/batches")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "input_file_id": "file-in",
+                "endpoint": "/v1/chat/completions",
+                "completion_window": "24h"
+            })))
+            .with_status(200)
+            .with_body(batch_body("validating"))
+            .create_async()
+            .await;
+
+        let batch = client(&server)
+            .create(file, BatchEndpoint::ChatCompletions, CompletionWindow::TwentyFourHours)
+            .await
+            .unwrap();
+        assert_eq!(batch.status, BatchStatus::Validating);
+        assert_eq!(batch.input_file_id.as_deref(), Some("file-in"));
+        upload.assert_async().await;
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_list() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/batches/batch_1/cancel")
+            .with_status(200)
+            .with_body(batch_body("cancelling"))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/batches")
+            .match_query(mockito::Matcher::UrlEncoded("limit".into(), "2".into()))
+            .with_status(200)
+            .with_body(format!(r#"{{"object": "list", "data": [{}], "has_more": false}}"#, batch_body("completed")))
+            .create_async()
+            .await;
+
+        let client = client(&server);
+        let batch = client.cancel("batch_1").await.unwrap();
+        assert_eq!(batch.status, BatchStatus::Cancelling);
+        assert!(!batch.status.is_terminal());
+
+        let batches = client.list(2).await.unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].id, "batch_1");
+    }
+
+    #[tokio::test]
+    async fn test_results_stream_line_by_line() {
+        use futures::StreamExt;
+
+        let line = |id: &str| {
+            serde_json::json!({
+                "id": format!("batch_req_{}", id),
+                "custom_id": id,
+                "response": {"status_code": 200, "request_id": "req", "body": {}},
+                "error": null
+            })
+            .to_string()
+        };
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/files/file-out/content")
+            .with_status(200)
+            .with_body(format!("{}\nnot json\n\n{}", line("a"), line("b")))
+            .create_async()
+            .await;
+
+        let results: Vec<_> = client(&server).results("file-out").await.unwrap().collect().await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().custom_id, "a");
+        let error = results[1].as_ref().unwrap_err().to_string();
+        assert!(error.contains("line 2") && error.contains("line: not json"), "{}", error);
+        // The last line needs no trailing newline
+        assert_eq!(results[2].as_ref().unwrap().custom_id, "b");
+    }
+
+    #[test]
+    fn test_upload_boundary_avoids_content() {
+        match upload_body("--simple-agents-batch-0 appears here") {
+            Body::Multipart { boundary, .. } => assert_eq!(boundary, "simple-agents-batch-1"),
+            _ => panic!("expected a multipart body"),
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! Chat templates: rendering a conversation into a single prompt.
+//!
+//! Servers running a model in raw mode (`/completions` on vLLM or
+//! llama.cpp) expect the prompt formatted the way the model was trained,
+//! with its special role tokens. [`ChatTemplate`] covers the common
+//! formats; [`ChatTemplate::for_model`] picks one from a model name.
+
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+
+/// Renders a chat history into a text-completion prompt
+pub type RenderFn = dyn Fn(&[Message]) -> String + Send + Sync;
+
+/// How a conversation is turned into a prompt.
+///
+/// Every template ends the prompt where the assistant's reply starts and
+/// brings stop sequences that keep the model from writing the next user
+/// turn itself.
+#[derive(Clone, Default)]
+pub enum ChatTemplate {
+    /// `System: ...`, `User: ...` and `Assistant: ...` lines, ending with
+    /// `Assistant:` (the default). Stops at `\nUser:`.
+    #[default]
+    RolePrefix,
+    /// ChatML `<|im_start|>role` blocks (Qwen, Yi, Hermes), ending with an
+    /// open assistant block. Stops at `<|im_end|>`.
+    ChatMl,
+    /// Llama 3 header blocks after `<|begin_of_text|>`, each closed by
+    /// `<|eot_id|>`, where it stops. Tool results use the `ipython` role.
+    Llama3,
+    /// Mistral instruct `<s>[INST] ... [/INST]` turns, each assistant reply
+    /// closed by `</s>`, where it stops. The system prompt is prepended to
+    /// the first user message and tool results are sent as user turns.
+    MistralInstruct,
+    /// A custom rendering function and its stop sequences
+    Custom {
+        /// Renders the prompt
+        render: Arc<RenderFn>,
+        /// Stop sequences added to every request
+        stop: Vec<String>,
+    },
+    /// A Jinja template, as shipped in Hugging Face `tokenizer_config.json`
+    #[cfg(feature = "jinja-templates")]
+    Jinja(JinjaTemplate),
+}
+
+impl ChatTemplate {
+    /// A template rendered by `render`, stopping at `stop`.
+    pub fn custom(
+        render: impl Fn(&[Message]) -> String + Send + Sync + 'static,
+        stop: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        ChatTemplate::Custom {
+            render: Arc::new(render),
+            stop: stop.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// The template a model was most likely trained with, guessed from its
+    /// name; [`RolePrefix`](Self::RolePrefix) when unknown.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_providers::openai::ChatTemplate;
+    ///
+    /// let template = ChatTemplate::for_model("meta-llama/Meta-Llama-3.1-8B-Instruct");
+    /// assert!(matches!(template, ChatTemplate::Llama3));
+    /// assert!(matches!(ChatTemplate::for_model("Qwen/Qwen2.5-7B-Instruct"), ChatTemplate::ChatMl));
+    /// ```
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| model.contains(needle));
+        // Fine-tunes such as Hermes use ChatML whatever their base model
+        if has(&["qwen", "chatml", "hermes", "yi-"]) {
+            ChatTemplate::ChatMl
+        } else if has(&["llama-3", "llama3"]) {
+            ChatTemplate::Llama3
+        } else if has(&["mistral", "mixtral"]) {
+            ChatTemplate::MistralInstruct
+        } else {
+            ChatTemplate::RolePrefix
+        }
+    }
+
+    /// Render `messages` into a prompt.
+    ///
+    /// # Errors
+    ///
+    /// Only `Jinja` templates fail, with
+    /// [`SimpleAgentsError::Config`] if the template is invalid or raises
+    /// an exception.
+    pub fn render(&self, messages: &[Message]) -> Result<String> {
+        let mut prompt = String::new();
+        match self {
+            ChatTemplate::RolePrefix => {
+                for message in messages {
+                    prompt.push_str(&format!("{}: {}\n", speaker(message.role), message.content));
+                }
+                prompt.push_str("Assistant:");
+            }
+            ChatTemplate::ChatMl => {
+                for message in messages {
+                    let role = role_name(message.role);
+                    prompt.push_str(&format!("<|im_start|>{}\n{}<|im_end|>\n", role, message.content));
+                }
+                prompt.push_str("<|im_start|>assistant\n");
+            }
+            ChatTemplate::Llama3 => {
+                prompt.push_str("<|begin_of_text|>");
+                for message in messages {
+                    let role = match message.role {
+                        Role::Tool => "ipython",
+                        role => role_name(role),
+                    };
+                    prompt.push_str(&format!(
+                        "<|start_header_id|>{}<|end_header_id|>\n\n{}<|eot_id|>",
+                        role, message.content
+                    ));
+                }
+                prompt.push_str("<|start_header_id|>assistant<|end_header_id|>\n\n");
+            }
+            ChatTemplate::MistralInstruct => {
+                prompt.push_str("<s>");
+                let mut system = Vec::new();
+                for message in messages {
+                    match message.role {
+                        Role::System => system.push(message.content.as_str()),
+                        Role::Assistant => prompt.push_str(&format!(" {}</s>", message.content)),
+                        Role::User | Role::Tool => {
+                            let mut content = std::mem::take(&mut system).join("\n\n");
+                            if !content.is_empty() {
+                                content.push_str("\n\n");
+                            }
+                            content.push_str(&message.content);
+                            prompt.push_str(&format!("[INST] {} [/INST]", content));
+                        }
+                    }
+                }
+            }
+            ChatTemplate::Custom { render, .. } => prompt = render(messages),
+            #[cfg(feature = "jinja-templates")]
+            ChatTemplate::Jinja(template) => prompt = template.render(messages)?,
+        }
+        Ok(prompt)
+    }
+
+    /// Stop sequences added to every request.
+    pub fn stop_sequences(&self) -> Vec<String> {
+        let stop = match self {
+            ChatTemplate::RolePrefix => "\nUser:",
+            ChatTemplate::ChatMl => "<|im_end|>",
+            ChatTemplate::Llama3 => "<|eot_id|>",
+            ChatTemplate::MistralInstruct => "</s>",
+            ChatTemplate::Custom { stop, .. } => return stop.clone(),
+            #[cfg(feature = "jinja-templates")]
+            ChatTemplate::Jinja(template) => return template.stop.clone(),
+        };
+        vec![stop.to_string()]
+    }
+}
+
+impl std::fmt::Debug for ChatTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatTemplate::RolePrefix => f.write_str("RolePrefix"),
+            ChatTemplate::ChatMl => f.write_str("ChatMl"),
+            ChatTemplate::Llama3 => f.write_str("Llama3"),
+            ChatTemplate::MistralInstruct => f.write_str("MistralInstruct"),
+            ChatTemplate::Custom { stop, .. } => {
+                f.debug_struct("Custom").field("stop", stop).finish_non_exhaustive()
+            }
+            #[cfg(feature = "jinja-templates")]
+            ChatTemplate::Jinja(template) => f.debug_tuple("Jinja").field(template).finish(),
+        }
+    }
+}
+
+/// Role label of the [`ChatTemplate::RolePrefix`] format
+fn speaker(role: Role) -> &'static str {
+    match role {
+        Role::System => "System",
+        Role::User => "User",
+        Role::Assistant => "Assistant",
+        Role::Tool => "Tool",
+    }
+}
+
+/// Wire name of `role` (`system`, `user`, ...)
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// A Jinja chat template rendered with [minijinja](https://docs.rs/minijinja).
+///
+/// The template sees the variables Hugging Face chat templates use:
+/// `messages` (each with `role` and `content`), `bos_token`, `eos_token`
+/// and `add_generation_prompt` (always true), plus a `raise_exception`
+/// function.
+///
+/// # Example
+/// ```
+/// use simple_agents_providers::openai::{ChatTemplate, JinjaTemplate};
+/// use simple_agents_types::prelude::*;
+///
+/// let source = "{{ bos_token }}{% for m in messages %}<{{ m.role }}>{{ m.content }}\n{% endfor %}\
+///               <assistant>";
+/// let template = ChatTemplate::Jinja(JinjaTemplate::new(source).with_special_tokens("<s>", "</s>"));
+/// let prompt = template.render(&[Message::user("Hi")])?;
+/// assert_eq!(prompt, "<s><user>Hi\n<assistant>");
+/// # Ok::<(), SimpleAgentsError>(())
+/// ```
+#[cfg(feature = "jinja-templates")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JinjaTemplate {
+    source: String,
+    bos_token: String,
+    eos_token: String,
+    stop: Vec<String>,
+}
+
+#[cfg(feature = "jinja-templates")]
+impl JinjaTemplate {
+    /// A template with empty special tokens and no stop sequences.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            bos_token: String::new(),
+            eos_token: String::new(),
+            stop: Vec::new(),
+        }
+    }
+
+    /// Set `bos_token` and `eos_token`.
+    pub fn with_special_tokens(mut self, bos_token: impl Into<String>, eos_token: impl Into<String>) -> Self {
+        self.bos_token = bos_token.into();
+        self.eos_token = eos_token.into();
+        self
+    }
+
+    /// Stop sequences added to every request (usually the end-of-turn
+    /// token).
+    pub fn with_stop(mut self, stop: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.stop = stop.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The template source.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn render(&self, messages: &[Message]) -> Result<String> {
+        let invalid = |e: minijinja::Error| {
+            SimpleAgentsError::Config(format!("Invalid chat template: {:#}", e))
+        };
+
+        let mut env = minijinja::Environment::new();
+        env.add_function("raise_exception", |message: String| -> std::result::Result<String, _> {
+            Err(minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, message))
+        });
+        let template = env.template_from_str(&self.source).map_err(invalid)?;
+
+        let messages: Vec<_> = messages
+            .iter()
+            .map(|m| minijinja::context! { role => role_name(m.role), content => m.content })
+            .collect();
+        template
+            .render(minijinja::context! {
+                messages,
+                bos_token => self.bos_token,
+                eos_token => self.eos_token,
+                add_generation_prompt => true,
+            })
+            .map_err(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// System prompt and two user turns
+    fn conversation() -> Vec<Message> {
+        vec![
+            Message::system("You are terse."),
+            Message::user("Hi"),
+            Message::assistant("Hello."),
+            Message::user("Name a fjord."),
+        ]
+    }
+
+    fn render(template: ChatTemplate) -> String {
+        template.render(&conversation()).unwrap()
+    }
+
+    #[test]
+    fn test_role_prefix_golden() {
+        assert_eq!(
+            render(ChatTemplate::default()),
+            "System: You are terse.\nUser: Hi\nAssistant: Hello.\nUser: Name a fjord.\nAssistant:"
+        );
+    }
+
+    #[test]
+    fn test_chatml_golden() {
+        assert_eq!(
+            render(ChatTemplate::ChatMl),
+            "<|im_start|>system\nYou are terse.<|im_end|>\n\
+             <|im_start|>user\nHi<|im_end|>\n\
+             <|im_start|>assistant\nHello.<|im_end|>\n\
+             <|im_start|>user\nName a fjord.<|im_end|>\n\
+             <|im_start|>assistant\n"
+        );
+        assert_eq!(ChatTemplate::ChatMl.stop_sequences(), vec!["<|im_end|>"]);
+    }
+
+    #[test]
+    fn test_llama3_golden() {
+        assert_eq!(
+            render(ChatTemplate::Llama3),
+            "<|begin_of_text|><|start_header_id|>system<|end_header_id|>\n\nYou are terse.<|eot_id|>\
+             <|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|>\
+             <|start_header_id|>assistant<|end_header_id|>\n\nHello.<|eot_id|>\
+             <|start_header_id|>user<|end_header_id|>\n\nName a fjord.<|eot_id|>\
+             <|start_header_id|>assistant<|end_header_id|>\n\n"
+        );
+        assert_eq!(ChatTemplate::Llama3.stop_sequences(), vec!["<|eot_id|>"]);
+    }
+
+    #[test]
+    fn test_mistral_instruct_golden() {
+        assert_eq!(
+            render(ChatTemplate::MistralInstruct),
+            "<s>[INST] You are terse.\n\nHi [/INST] Hello.</s>[INST] Name a fjord. [/INST]"
+        );
+        assert_eq!(ChatTemplate::MistralInstruct.stop_sequences(), vec!["</s>"]);
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let template = ChatTemplate::custom(
+            |messages: &[Message]| format!("Q: {}\nA:", messages.last().unwrap().content),
+            ["\nQ:"],
+        );
+        assert_eq!(render(template.clone()), "Q: Name a fjord.\nA:");
+        assert_eq!(format!("{:?}", template), r#"Custom { stop: ["\nQ:"], .. }"#);
+    }
+
+    #[test]
+    fn test_for_model() {
+        let cases = [
+            ("meta-llama/Llama-3.2-3B-Instruct", "Llama3"),
+            ("llama3.1:8b", "Llama3"),
+            ("mistralai/Mistral-7B-Instruct-v0.3", "MistralInstruct"),
+            ("mixtral-8x7b", "MistralInstruct"),
+            ("Qwen/Qwen2.5-0.5B-Instruct", "ChatMl"),
+            ("NousResearch/Hermes-3-Llama-3.1-8B", "ChatMl"),
+            ("gpt2", "RolePrefix"),
+        ];
+        for (model, expected) in cases {
+            assert_eq!(format!("{:?}", ChatTemplate::for_model(model)), expected, "{}", model);
+        }
+    }
+
+    #[cfg(feature = "jinja-templates")]
+    #[test]
+    fn test_jinja_golden() {
+        // The ChatML template shipped with Qwen2.5, without tool support
+        let source = "{% for message in messages %}\
+            {{ '<|im_start|>' + message.role + '\\n' + message.content + '<|im_end|>' + '\\n' }}\
+            {% endfor %}\
+            {% if add_generation_prompt %}{{ '<|im_start|>assistant\\n' }}{% endif %}";
+        let template = ChatTemplate::Jinja(JinjaTemplate::new(source).with_stop(["<|im_end|>"]));
+        assert_eq!(render(template.clone()), render(ChatTemplate::ChatMl));
+        assert_eq!(template.stop_sequences(), vec!["<|im_end|>"]);
+
+        let source = "{{ bos_token }}{% for m in messages %}{% if m.role == 'system' %}\
+            {{ raise_exception('System role not supported') }}{% endif %}\
+            {{ m.content }}{{ eos_token }}{% endfor %}";
+        let template = ChatTemplate::Jinja(JinjaTemplate::new(source).with_special_tokens("<s>", "</s>"));
+        assert_eq!(template.render(&conversation()[1..3]).unwrap(), "<s>Hi</s>Hello.</s>");
+        let err = template.render(&conversation()).unwrap_err();
+        assert!(err.to_string().contains("System role not supported"), "{}", err);
+
+        let err = ChatTemplate::Jinja(JinjaTemplate::new("{% for %}")).render(&[]).unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Config(_)), "{:?}", err);
+    }
+}
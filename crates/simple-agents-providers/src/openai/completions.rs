@@ -0,0 +1,440 @@
+//! Legacy text completions (`POST /completions`).
+//!
+//! Some OpenAI-compatible servers (and base models without a chat
+//! template) only expose the plain-prompt endpoint. [`TextCompletionProvider`]
+//! renders a chat-style [`CompletionRequest`] into a prompt with a
+//! [`ChatTemplate`] and maps the generated text back to an assistant
+//! message, so it can be used anywhere a [`Provider`] is expected.
+//! [`OpenAIProvider::text_completion`] sends a [`TextCompletionRequest`]
+//! as is.
+
+use super::{map_finish_reason, ChatTemplate, OpenAIProvider, OpenAIUsage};
+use crate::provider_kit::{self, RequestBuilder};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::prelude::*;
+use simple_agents_types::response::normalize_unix_timestamp;
+
+/// Request body of `POST /completions`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextCompletionRequest {
+    /// Model identifier
+    pub model: String,
+
+    /// Text to continue
+    pub prompt: String,
+
+    /// Maximum tokens to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    /// Sampling temperature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Number of completions to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+
+    /// Sequences that end generation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+
+    /// Presence penalty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+
+    /// Frequency penalty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+
+    /// End-user identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl TextCompletionRequest {
+    /// Render `req` into a prompt with `template`.
+    ///
+    /// The template's stop sequences are added to the request's own.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::Unsupported`] if `req` uses tools, images,
+    /// documents, a JSON schema or an assistant prefill, none of which
+    /// have a text-completion equivalent, and the errors of
+    /// [`ChatTemplate::render`].
+    pub fn from_chat(req: &CompletionRequest, template: &ChatTemplate) -> Result<Self> {
+        let unsupported = if req.tools.as_ref().is_some_and(|tools| !tools.is_empty()) {
+            Some(Feature::FunctionCalling)
+        } else if req.messages.iter().any(|m| !m.images.is_empty()) {
+            Some(Feature::Vision)
+        } else if req.messages.iter().any(|m| !m.documents.is_empty()) {
+            Some(Feature::Documents)
+        } else if matches!(req.response_format, Some(ResponseFormat::JsonSchema { .. })) {
+            Some(Feature::JsonSchema)
+        } else if req.assistant_prefill.is_some() {
+            Some(Feature::AssistantPrefill)
+        } else {
+            None
+        };
+        if let Some(feature) = unsupported {
+            return Err(ProviderError::Unsupported {
+                provider: TextCompletionProvider::NAME.to_string(),
+                feature,
+            }
+            .into());
+        }
+
+        let mut stop = req.stop.clone().unwrap_or_default();
+        for sequence in template.stop_sequences() {
+            if !stop.contains(&sequence) {
+                stop.push(sequence);
+            }
+        }
+        Ok(Self {
+            model: req.model.clone(),
+            prompt: template.render(&req.messages)?,
+            max_tokens: req.max_tokens,
+            temperature: req.temperature,
+            top_p: req.top_p,
+            n: req.n,
+            stop: (!stop.is_empty()).then_some(stop),
+            presence_penalty: req.presence_penalty,
+            frequency_penalty: req.frequency_penalty,
+            user: req.user.clone(),
+        })
+    }
+}
+
+/// One generated text in a [`TextCompletionResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextCompletionChoice {
+    /// Generated text
+    pub text: String,
+
+    /// Index of this choice
+    #[serde(default)]
+    pub index: u32,
+
+    /// Why generation stopped (`stop`, `length`, ...)
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+}
+
+/// Response of `POST /completions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextCompletionResponse {
+    /// Completion identifier
+    pub id: String,
+
+    /// Model that generated the text
+    pub model: String,
+
+    /// Unix timestamp of creation
+    #[serde(default)]
+    pub created: u64,
+
+    /// Generated texts
+    pub choices: Vec<TextCompletionChoice>,
+
+    /// Token usage; some compatible servers omit it
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+impl TextCompletionResponse {
+    /// Convert to a unified response with each text as an assistant
+    /// message.
+    ///
+    /// Whitespace around the text (typically the space after the
+    /// template's `Assistant:`) is trimmed. Missing usage is reported as
+    /// zero tokens.
+    pub fn into_completion_response(self, provider: &str) -> CompletionResponse {
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|choice| CompletionChoice {
+                index: choice.index,
+                message: Message::assistant(choice.text.trim()),
+                finish_reason: choice
+                    .finish_reason
+                    .as_deref()
+                    .map(map_finish_reason)
+                    .unwrap_or(FinishReason::Stop),
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            })
+            .collect();
+        let usage = self.usage.map_or_else(
+            || Usage::new(0, 0),
+            |usage| Usage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                total_tokens: usage.total_tokens,
+                cache_creation_input_tokens: None,
+                cache_read_input_tokens: None,
+            },
+        );
+
+        CompletionResponse {
+            id: self.id,
+            model: self.model,
+            choices,
+            usage,
+            created: Some(normalize_unix_timestamp(self.created as i64)),
+            created_synthesized: false,
+            provider: Some(provider.to_string()),
+        }
+    }
+}
+
+impl OpenAIProvider {
+    /// Send a request to the legacy `/completions` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same API errors as chat completions, and
+    /// [`ProviderError::InvalidResponse`] if the response cannot be parsed.
+    pub async fn text_completion(&self, request: &TextCompletionRequest) -> Result<TextCompletionResponse> {
+        let body = serde_json::to_value(request)?;
+        self.raw_json(reqwest::Method::POST, "/completions", body).await
+    }
+}
+
+/// Chat-style [`Provider`] backed by the legacy `/completions` endpoint.
+///
+/// Each request is rendered with a [`ChatTemplate`] (see
+/// [`TextCompletionRequest::from_chat`]) and each generated text comes
+/// back as an assistant message. Streaming, tools and images are not
+/// supported.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::{ChatTemplate, OpenAIProvider, TextCompletionProvider};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let inner = OpenAIProvider::with_base_url(ApiKey::new("sk-local")?, "http://localhost:8000/v1".into())?;
+/// let provider = TextCompletionProvider::new(inner).with_template(ChatTemplate::ChatMl);
+///
+/// let request = CompletionRequest::builder()
+///     .model("my-base-model")
+///     .message(Message::user("Name three fjords."))
+///     .build()?;
+/// let response = provider.complete(&request).await?;
+/// println!("{}", response.content().unwrap_or_default());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextCompletionProvider {
+    inner: OpenAIProvider,
+    template: ChatTemplate,
+    name: &'static str,
+}
+
+impl TextCompletionProvider {
+    /// Provider name reported in responses and errors
+    pub const NAME: &'static str = "openai-completions";
+
+    /// Send requests through `inner` with the [`ChatTemplate::RolePrefix`]
+    /// template.
+    pub fn new(inner: OpenAIProvider) -> Self {
+        Self {
+            inner,
+            template: ChatTemplate::default(),
+            name: Self::NAME,
+        }
+    }
+
+    /// Report responses as coming from `name` (a server preset).
+    pub(crate) fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Set the chat template.
+    pub fn with_template(mut self, template: ChatTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// The chat template.
+    pub fn template(&self) -> &ChatTemplate {
+        &self.template
+    }
+
+    /// Access the wrapped provider.
+    pub fn provider(&self) -> &OpenAIProvider {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl Provider for TextCompletionProvider {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: false,
+            function_calling: false,
+            vision: false,
+            ..self.inner.capabilities()
+        }
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let text_request = TextCompletionRequest::from_chat(req, &self.template)?;
+        let body = serde_json::to_value(&text_request)?;
+
+        let mut request = RequestBuilder::new(format!("{}/completions", self.inner.base_url));
+        if let Some(key) = self.inner.credentials.cached_key() {
+            request = request.auth(self.inner.auth_scheme, &key);
+        }
+        Ok(request.body(body).extensions(req.extensions.clone()).build())
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        provider_kit::json_response(self.inner.send(req).await?).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let response: TextCompletionResponse = serde_json::from_value(resp.body).map_err(|e| {
+            ProviderError::InvalidResponse(format!("Failed to deserialize response: {}", e))
+        })?;
+        Ok(response.into_completion_response(self.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API_KEY: &str = "sk-test1234567890123456789012345678901234567890";
+
+    fn chat() -> Vec<Message> {
+        vec![
+            Message::system("You are terse."),
+            Message::user("Hi"),
+            Message::assistant("Hello."),
+            Message::user("Name a fjord."),
+        ]
+    }
+
+    #[test]
+    fn test_from_chat_merges_stop_sequences() {
+        let request = CompletionRequest::builder()
+            .model("base-1")
+            .messages(chat())
+            .max_tokens(20)
+            .temperature(0.2)
+            .stop(vec!["\n\n".to_string(), "\nUser:".to_string()])
+            .build()
+            .unwrap();
+
+        let text_request = TextCompletionRequest::from_chat(&request, &ChatTemplate::RolePrefix).unwrap();
+        assert_eq!(text_request.model, "base-1");
+        assert!(text_request.prompt.ends_with("User: Name a fjord.\nAssistant:"));
+        assert_eq!(text_request.max_tokens, Some(20));
+        assert_eq!(text_request.stop, Some(vec!["\n\n".to_string(), "\nUser:".to_string()]));
+
+        let json = serde_json::to_value(&text_request).unwrap();
+        assert!(json.get("messages").is_none());
+        assert!(json.get("top_p").is_none());
+    }
+
+    #[test]
+    fn test_from_chat_rejects_tools() {
+        let parameters = serde_json::json!({"type": "object"});
+        let tool = ToolDefinition::function("weather", "Current weather", parameters);
+        let request = CompletionRequest::builder()
+            .model("base-1")
+            .message(Message::user("Weather?"))
+            .tools(vec![tool])
+            .build()
+            .unwrap();
+
+        let err = TextCompletionRequest::from_chat(&request, &ChatTemplate::RolePrefix).unwrap_err();
+        assert!(matches!(
+            err,
+            SimpleAgentsError::Provider(ProviderError::Unsupported { feature: Feature::FunctionCalling, .. })
+        ));
+    }
+
+    #[test]
+    fn test_response_shape() {
+        let response: TextCompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "cmpl-1",
+            "object": "text_completion",
+            "created": 1_700_000_000,
+            "model": "base-1",
+            "choices": [
+                {"text": " Geirangerfjord.", "index": 0, "logprobs": null, "finish_reason": "stop"},
+                {"text": " Sognefjord", "index": 1, "logprobs": null, "finish_reason": "length"}
+            ],
+            "usage": {"prompt_tokens": 20, "completion_tokens": 4, "total_tokens": 24}
+        }))
+        .unwrap();
+
+        let response = response.into_completion_response("openai-completions");
+        assert_eq!(response.content(), Some("Geirangerfjord."));
+        assert_eq!(response.choices[0].message.role, Role::Assistant);
+        assert_eq!(response.choices[1].finish_reason, FinishReason::Length);
+        assert_eq!(response.usage.total_tokens, 24);
+        assert_eq!(response.provider.as_deref(), Some("openai-completions"));
+
+        // Usage is optional on compatible servers
+        let response: TextCompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "cmpl-2",
+            "model": "base-1",
+            "choices": [{"text": "Hi"}]
+        }))
+        .unwrap();
+        let response = response.into_completion_response("openai-completions");
+        assert_eq!(response.usage.total_tokens, 0);
+        assert_eq!(response.choices[0].finish_reason, FinishReason::Stop);
+    }
+
+    #[tokio::test]
+    async fn test_complete_via_completions_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/completions")
+            .match_header("authorization", format!("Bearer {}", API_KEY).as_str())
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "base-1",
+                "prompt": "User: Name a fjord.\nAssistant:",
+                "stop": ["\nUser:"]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"id": "cmpl-1", "model": "base-1", "created": 1700000000,
+                    "choices": [{"text": " Nærøyfjord.", "index": 0, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 9, "completion_tokens": 5, "total_tokens": 14}}"#,
+            )
+            .create_async()
+            .await;
+
+        let inner = OpenAIProvider::with_base_url(ApiKey::new(API_KEY).unwrap(), server.url()).unwrap();
+        let provider = TextCompletionProvider::new(inner);
+        let request = CompletionRequest::builder()
+            .model("base-1")
+            .message(Message::user("Name a fjord."))
+            .build()
+            .unwrap();
+
+        let response = provider.complete(&request).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(response.content(), Some("Nærøyfjord."));
+        assert_eq!(response.usage.completion_tokens, 5);
+    }
+}
@@ -0,0 +1,159 @@
+//! Client for the OpenAI embeddings endpoint.
+
+use super::OpenAIProvider;
+use async_trait::async_trait;
+use serde::Deserialize;
+use simple_agents_types::embedding::EmbeddingProvider;
+use simple_agents_types::prelude::*;
+
+/// One embedding in an embeddings response
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Response of `POST /embeddings`
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+}
+
+/// OpenAI embeddings client.
+///
+/// Implements [`EmbeddingProvider`], so it can back a semantic cache.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::EmbeddingsClient;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let client = EmbeddingsClient::new(ApiKey::new("sk-...")?)?.with_model("text-embedding-3-large");
+/// let vectors = client.embed_batch(vec!["first document", "second document"]).await?;
+/// assert_eq!(vectors.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EmbeddingsClient {
+    inner: OpenAIProvider,
+    model: String,
+}
+
+impl EmbeddingsClient {
+    /// Embedding model used unless overridden
+    pub const DEFAULT_MODEL: &'static str = "text-embedding-3-small";
+
+    /// Create a client for the default OpenAI API base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, OpenAIProvider::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a client with a custom base URL.
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        Ok(Self::with_provider(OpenAIProvider::with_base_url(credentials, base_url)?))
+    }
+
+    /// Create a client that sends requests through `inner`.
+    pub(crate) fn with_provider(inner: OpenAIProvider) -> Self {
+        Self {
+            inner,
+            model: Self::DEFAULT_MODEL.to_string(),
+        }
+    }
+
+    /// Set the embedding model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Get the base URL for this client
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// Get the embedding model
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Embed several inputs in one request.
+    ///
+    /// Vectors are in input order. An empty batch returns without a request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::InvalidResponse`] if the API returns a
+    /// different number of embeddings than inputs.
+    pub async fn embed_batch(&self, inputs: Vec<&str>) -> Result<Vec<Vec<f32>>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({ "model": self.model, "input": inputs });
+        let mut response: EmbeddingsResponse =
+            self.inner.raw_json(reqwest::Method::POST, "/embeddings", body).await?;
+        if response.data.len() != inputs.len() {
+            return Err(ProviderError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                inputs.len(),
+                response.data.len()
+            ))
+            .into());
+        }
+
+        response.data.sort_by_key(|data| data.index);
+        Ok(response.data.into_iter().map(|data| data.embedding).collect())
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for EmbeddingsClient {
+    async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+        let mut vectors = self.embed_batch(vec![input]).await?;
+        Ok(vectors.remove(0))
+    }
+
+    fn name(&self) -> &str {
+        "openai"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_embed_batch_orders_by_index() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/embeddings")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "text-embedding-3-small",
+                "input": ["a", "b"]
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"object": "list", "model": "text-embedding-3-small", "data": [
+                    {"object": "embedding", "index": 1, "embedding": [0.0, 1.0]},
+                    {"object": "embedding", "index": 0, "embedding": [1.0, 0.0]}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+        let client = EmbeddingsClient::with_base_url(api_key, server.url()).unwrap();
+        let vectors = client.embed_batch(vec!["a", "b"]).await.unwrap();
+        mock.assert_async().await;
+
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert!(client.embed_batch(Vec::new()).await.unwrap().is_empty());
+    }
+}
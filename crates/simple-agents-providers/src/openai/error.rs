@@ -1,5 +1,6 @@
 //! OpenAI-specific error handling.
 
+use serde::Deserialize;
 use simple_agents_types::ProviderError;
 use std::time::Duration;
 use thiserror::Error;
@@ -56,6 +57,48 @@ impl OpenAIError {
         Self::from_error_details(status, body)
     }
 
+    /// Parse an error event from an SSE `data:` payload.
+    ///
+    /// Once a stream has started, OpenAI reports failures as a
+    /// `{"error": {"message": ..., "type": ..., "code": ...}}` object in
+    /// place of a chunk. `data` may be the payload or the whole `data:`
+    /// line. Returns `None` for anything else, including ordinary chunks.
+    ///
+    /// Unrecognized errors are treated as server errors: the request was
+    /// accepted, so the failure happened on OpenAI's side.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_providers::openai::OpenAIError;
+    ///
+    /// let line = r#"data: {"error": {"message": "Slow down", "code": "rate_limit_exceeded"}}"#;
+    /// assert!(matches!(OpenAIError::from_streaming_event(line), Some(OpenAIError::RateLimit { .. })));
+    /// assert!(OpenAIError::from_streaming_event("[DONE]").is_none());
+    /// ```
+    pub fn from_streaming_event(data: &str) -> Option<Self> {
+        let data = data.trim();
+        let data = data.strip_prefix("data:").map(str::trim_start).unwrap_or(data);
+        let event: StreamErrorEvent = serde_json::from_str(data).ok()?;
+        let StreamErrorDetails {
+            message,
+            error_type,
+            code,
+        } = event.error;
+
+        let error = match (code.as_deref().unwrap_or_default(), error_type.as_deref().unwrap_or_default()) {
+            ("invalid_api_key", _) | (_, "authentication_error") => Self::InvalidApiKey,
+            ("model_not_found", _) => Self::ModelNotFound(message),
+            ("rate_limit_exceeded", _) | (_, "requests" | "tokens") => Self::RateLimit { retry_after: None },
+            ("context_length_exceeded", _) => Self::ContextLengthExceeded(message),
+            // Out of credit: retrying will not help
+            ("insufficient_quota", _) => Self::BadRequest(message),
+            ("server_error", _) | (_, "server_error") => Self::ServerError(message),
+            (_, "invalid_request_error") => Self::BadRequest(message),
+            _ => Self::from_error_details(500, &message),
+        };
+        Some(error)
+    }
+
     /// Parse error from error details
     fn from_error_details(status: u16, message: &str) -> Self {
         let message_lower = message.to_lowercase();
@@ -89,6 +132,24 @@ impl OpenAIError {
     }
 }
 
+/// Error object sent in place of a chunk in a stream
+#[derive(Debug, Deserialize)]
+struct StreamErrorEvent {
+    error: StreamErrorDetails,
+}
+
+/// Like [`super::OpenAIErrorDetails`], but tolerant of the missing or null
+/// fields seen in stream errors
+#[derive(Debug, Deserialize)]
+struct StreamErrorDetails {
+    #[serde(default)]
+    message: String,
+    #[serde(rename = "type", default)]
+    error_type: Option<String>,
+    #[serde(default)]
+    code: Option<String>,
+}
+
 /// Convert OpenAIError to ProviderError
 impl From<OpenAIError> for ProviderError {
     fn from(error: OpenAIError) -> Self {
@@ -154,4 +215,56 @@ mod tests {
         );
         assert!(matches!(error, OpenAIError::ContextLengthExceeded(_)));
     }
+
+    fn streaming(code: Option<&str>, error_type: &str) -> OpenAIError {
+        let event = serde_json::json!({
+            "error": {"message": "stream failed", "type": error_type, "code": code}
+        });
+        OpenAIError::from_streaming_event(&format!("data: {}", event)).unwrap()
+    }
+
+    #[test]
+    fn test_streaming_event_codes() {
+        assert!(matches!(
+            streaming(Some("invalid_api_key"), "invalid_request_error"),
+            OpenAIError::InvalidApiKey
+        ));
+        assert!(matches!(
+            streaming(Some("model_not_found"), "invalid_request_error"),
+            OpenAIError::ModelNotFound(_)
+        ));
+        assert!(matches!(
+            streaming(Some("rate_limit_exceeded"), "requests"),
+            OpenAIError::RateLimit { .. }
+        ));
+        assert!(matches!(streaming(None, "tokens"), OpenAIError::RateLimit { .. }));
+        assert!(matches!(
+            streaming(Some("context_length_exceeded"), "invalid_request_error"),
+            OpenAIError::ContextLengthExceeded(_)
+        ));
+        assert!(matches!(
+            streaming(Some("insufficient_quota"), "insufficient_quota"),
+            OpenAIError::BadRequest(_)
+        ));
+        assert!(matches!(streaming(Some("server_error"), "server_error"), OpenAIError::ServerError(_)));
+        assert!(matches!(streaming(None, "server_error"), OpenAIError::ServerError(_)));
+        assert!(matches!(streaming(None, "invalid_request_error"), OpenAIError::BadRequest(_)));
+        // Unrecognized mid-stream errors are the server's fault
+        let unknown = streaming(None, "mystery_error");
+        assert!(matches!(unknown, OpenAIError::ServerError(m) if m == "stream failed"));
+    }
+
+    #[test]
+    fn test_streaming_event_ignores_other_payloads() {
+        assert!(OpenAIError::from_streaming_event("[DONE]").is_none());
+        assert!(OpenAIError::from_streaming_event("").is_none());
+        assert!(OpenAIError::from_streaming_event(
+            r#"{"id":"c","model":"m","choices":[{"index":0,"delta":{"content":"error"}}]}"#
+        )
+        .is_none());
+
+        // Bare payload without the `data:` prefix, with sparse fields
+        let error = OpenAIError::from_streaming_event(r#"{"error": {"message": "boom", "code": null}}"#);
+        assert!(matches!(error, Some(OpenAIError::ServerError(m)) if m == "boom"));
+    }
 }
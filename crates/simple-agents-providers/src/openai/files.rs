@@ -0,0 +1,252 @@
+//! Client for OpenAI file storage.
+//!
+//! Files hold inputs for other endpoints: batch request files,
+//! fine-tuning datasets and assistant knowledge.
+
+use super::OpenAIProvider;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::prelude::*;
+
+/// What an uploaded file is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FilePurpose {
+    /// Input file for a batch job
+    #[serde(rename = "batch")]
+    Batch,
+    /// Training or validation data for a fine-tuning job
+    #[serde(rename = "fine-tune")]
+    FineTune,
+    /// Knowledge for the Assistants API
+    #[serde(rename = "assistants")]
+    Assistants,
+    /// Image input for vision requests
+    #[serde(rename = "vision")]
+    Vision,
+    /// Anything else
+    #[serde(rename = "user_data")]
+    UserData,
+}
+
+impl FilePurpose {
+    /// Value sent as the `purpose` form field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Batch => "batch",
+            Self::FineTune => "fine-tune",
+            Self::Assistants => "assistants",
+            Self::Vision => "vision",
+            Self::UserData => "user_data",
+        }
+    }
+}
+
+/// A stored file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenAIFile {
+    /// File identifier (e.g. "file-abc123")
+    pub id: String,
+    /// Size in bytes
+    #[serde(default)]
+    pub bytes: u64,
+    /// Unix timestamp of creation, in seconds
+    #[serde(default)]
+    pub created_at: i64,
+    /// Name the file was uploaded with
+    #[serde(default)]
+    pub filename: String,
+    /// Purpose, as reported by the API (may be an output purpose such as
+    /// "batch_output")
+    #[serde(default)]
+    pub purpose: String,
+}
+
+/// Paginated list wrapper used by list endpoints
+#[derive(Debug, Deserialize)]
+struct ListResponse<T> {
+    data: Vec<T>,
+}
+
+/// OpenAI files client.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::{FilePurpose, FilesClient};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let client = FilesClient::new(ApiKey::new("sk-...")?)?;
+/// let data = std::fs::read("train.jsonl").unwrap();
+/// let file = client.upload(data, "train.jsonl", FilePurpose::FineTune).await?;
+/// println!("uploaded {} ({} bytes)", file.id, file.bytes);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilesClient {
+    inner: OpenAIProvider,
+}
+
+impl FilesClient {
+    /// Create a client for the default OpenAI API base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, OpenAIProvider::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a client with a custom base URL.
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        Ok(Self::with_provider(OpenAIProvider::with_base_url(credentials, base_url)?))
+    }
+
+    /// Create a client that sends requests through `inner`.
+    pub(crate) fn with_provider(inner: OpenAIProvider) -> Self {
+        Self { inner }
+    }
+
+    /// Get the base URL for this client
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// Upload `bytes` as `filename`.
+    pub async fn upload(
+        &self,
+        bytes: impl Into<Vec<u8>>,
+        filename: &str,
+        purpose: FilePurpose,
+    ) -> Result<OpenAIFile> {
+        let bytes = bytes.into();
+        let boundary = format!("simple-agents-{}", &blake3::hash(&bytes).to_hex()[..32]);
+        let body = multipart_upload(&boundary, purpose, filename, &bytes);
+        let response = self.inner.send_multipart("/files", &boundary, body).await?;
+        response.json().await.map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to parse file upload response: {}",
+                e
+            )))
+        })
+    }
+
+    /// Fetch a file's metadata.
+    pub async fn retrieve(&self, id: &str) -> Result<OpenAIFile> {
+        let path = format!("/files/{}", id);
+        self.inner.raw_json(reqwest::Method::GET, &path, serde_json::Value::Null).await
+    }
+
+    /// List stored files.
+    pub async fn list(&self) -> Result<Vec<OpenAIFile>> {
+        let list: ListResponse<OpenAIFile> = self
+            .inner
+            .raw_json(reqwest::Method::GET, "/files", serde_json::Value::Null)
+            .await?;
+        Ok(list.data)
+    }
+
+    /// Download a file's content.
+    pub async fn content(&self, id: &str) -> Result<Vec<u8>> {
+        let path = format!("/files/{}/content", id);
+        let request = self.inner.raw_request(&path, serde_json::Value::Null)?;
+        let response = self.inner.send_with_method(reqwest::Method::GET, request).await?;
+        let bytes = response.bytes().await.map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to read file content: {}",
+                e
+            )))
+        })?;
+        Ok(bytes.to_vec())
+    }
+
+    /// Delete a file.
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let path = format!("/files/{}", id);
+        self.inner.raw(reqwest::Method::DELETE, &path, serde_json::Value::Null).await?;
+        Ok(())
+    }
+}
+
+/// `multipart/form-data` body with `purpose` and `bytes` as the `file` field.
+fn multipart_upload(boundary: &str, purpose: FilePurpose, filename: &str, bytes: &[u8]) -> Vec<u8> {
+    // Quotes and line breaks would end the header early
+    let filename: String = filename
+        .chars()
+        .map(|c| if matches!(c, '"' | '\r' | '\n') { '_' } else { c })
+        .collect();
+    let head = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"purpose\"\r\n\r\n{purpose}\r\n\
+         --{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        purpose = purpose.as_str(),
+    );
+    let tail = format!("\r\n--{boundary}--\r\n");
+
+    let mut body = Vec::with_capacity(head.len() + bytes.len() + tail.len());
+    body.extend_from_slice(head.as_bytes());
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(tail.as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(server: &mockito::ServerGuard) -> FilesClient {
+        let api_key = ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+        FilesClient::with_base_url(api_key, server.url()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_upload() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/files")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data; boundary=simple-agents-".to_string()),
+            )
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"purpose\"\r\n\r\nfine-tune\r\n".to_string()),
+                mockito::Matcher::Regex("filename=\"train.jsonl\"".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"id": "file-abc123", "object": "file", "bytes": 12, "created_at": 1700000000,
+                    "filename": "train.jsonl", "purpose": "fine-tune"}"#,
+            )
+            .create_async()
+            .await;
+
+        let file = client(&server)
+            .upload(b"{\"a\": 1}\n".to_vec(), "train.jsonl", FilePurpose::FineTune)
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert_eq!(file.id, "file-abc123");
+        assert_eq!(file.bytes, 12);
+    }
+
+    #[tokio::test]
+    async fn test_content_and_delete() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/files/file-abc123/content")
+            .with_status(200)
+            .with_body("raw\nlines\n")
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/files/file-abc123")
+            .with_status(200)
+            .with_body(r#"{"id": "file-abc123", "object": "file", "deleted": true}"#)
+            .create_async()
+            .await;
+
+        let client = client(&server);
+        assert_eq!(client.content("file-abc123").await.unwrap(), b"raw\nlines\n");
+        client.delete("file-abc123").await.unwrap();
+        delete.assert_async().await;
+    }
+}
@@ -0,0 +1,185 @@
+//! Client for OpenAI fine-tuning jobs.
+
+use super::OpenAIProvider;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::prelude::*;
+
+/// Lifecycle status of a fine-tuning job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FineTuningStatus {
+    /// Training files are being validated
+    ValidatingFiles,
+    /// Waiting to start
+    Queued,
+    /// Training
+    Running,
+    /// Finished; the fine-tuned model is available
+    Succeeded,
+    /// Training failed
+    Failed,
+    /// The job was cancelled
+    Cancelled,
+}
+
+impl FineTuningStatus {
+    /// Whether the job has stopped changing status.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// A fine-tuning job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FineTuningJob {
+    /// Job identifier (e.g. "ftjob-abc123")
+    pub id: String,
+    /// Base model being fine-tuned
+    pub model: String,
+    /// Current status
+    pub status: FineTuningStatus,
+    /// Training file
+    pub training_file: String,
+    /// Name of the resulting model, once the job succeeds
+    #[serde(default)]
+    pub fine_tuned_model: Option<String>,
+    /// Unix timestamp of creation, in seconds
+    #[serde(default)]
+    pub created_at: i64,
+    /// Unix timestamp of completion, in seconds
+    #[serde(default)]
+    pub finished_at: Option<i64>,
+}
+
+/// Paginated list wrapper used by list endpoints
+#[derive(Debug, Deserialize)]
+struct ListResponse<T> {
+    data: Vec<T>,
+}
+
+/// OpenAI fine-tuning client.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::{FilePurpose, FilesClient, FineTuningClient};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example(training_data: Vec<u8>) -> Result<()> {
+/// let files = FilesClient::new(ApiKey::new("sk-...")?)?;
+/// let file = files.upload(training_data, "train.jsonl", FilePurpose::FineTune).await?;
+///
+/// let fine_tuning = FineTuningClient::new(ApiKey::new("sk-...")?)?;
+/// let job = fine_tuning.create_job(&file.id, "gpt-4o-mini-2024-07-18").await?;
+/// println!("{}: {:?}", job.id, job.status);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FineTuningClient {
+    inner: OpenAIProvider,
+}
+
+impl FineTuningClient {
+    /// Create a client for the default OpenAI API base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, OpenAIProvider::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a client with a custom base URL.
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        Ok(Self::with_provider(OpenAIProvider::with_base_url(credentials, base_url)?))
+    }
+
+    /// Create a client that sends requests through `inner`.
+    pub(crate) fn with_provider(inner: OpenAIProvider) -> Self {
+        Self { inner }
+    }
+
+    /// Get the base URL for this client
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// Start fine-tuning `model` on an uploaded training file.
+    pub async fn create_job(&self, training_file: &str, model: &str) -> Result<FineTuningJob> {
+        let body = serde_json::json!({ "training_file": training_file, "model": model });
+        self.inner.raw_json(reqwest::Method::POST, "/fine_tuning/jobs", body).await
+    }
+
+    /// Fetch the current state of a job.
+    pub async fn retrieve_job(&self, id: &str) -> Result<FineTuningJob> {
+        let path = format!("/fine_tuning/jobs/{}", id);
+        self.inner.raw_json(reqwest::Method::GET, &path, serde_json::Value::Null).await
+    }
+
+    /// List up to `limit` jobs, newest first.
+    pub async fn list_jobs(&self, limit: u32) -> Result<Vec<FineTuningJob>> {
+        let path = format!("/fine_tuning/jobs?limit={}", limit);
+        let list: ListResponse<FineTuningJob> = self
+            .inner
+            .raw_json(reqwest::Method::GET, &path, serde_json::Value::Null)
+            .await?;
+        Ok(list.data)
+    }
+
+    /// Cancel a job.
+    pub async fn cancel_job(&self, id: &str) -> Result<FineTuningJob> {
+        let path = format!("/fine_tuning/jobs/{}/cancel", id);
+        self.inner.raw_json(reqwest::Method::POST, &path, serde_json::Value::Null).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_body(status: &str) -> String {
+        serde_json::json!({
+            "object": "fine_tuning.job",
+            "id": "ftjob-abc123",
+            "model": "gpt-4o-mini-2024-07-18",
+            "status": status,
+            "training_file": "file-abc123",
+            "fine_tuned_model": null,
+            "created_at": 1_700_000_000,
+            "finished_at": null
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_cancel_job() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/fine_tuning/jobs")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "training_file": "file-abc123",
+                "model": "gpt-4o-mini-2024-07-18"
+            })))
+            .with_status(200)
+            .with_body(job_body("validating_files"))
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/fine_tuning/jobs/ftjob-abc123/cancel")
+            .with_status(200)
+            .with_body(job_body("cancelled"))
+            .create_async()
+            .await;
+
+        let api_key = ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+        let client = FineTuningClient::with_base_url(api_key, server.url()).unwrap();
+
+        let job = client.create_job("file-abc123", "gpt-4o-mini-2024-07-18").await.unwrap();
+        create.assert_async().await;
+        assert_eq!(job.status, FineTuningStatus::ValidatingFiles);
+        assert!(!job.status.is_terminal());
+
+        let job = client.cancel_job(&job.id).await.unwrap();
+        assert!(job.status.is_terminal());
+    }
+}
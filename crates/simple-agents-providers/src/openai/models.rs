@@ -1,7 +1,10 @@
 //! OpenAI API request and response types.
 
-use serde::{Deserialize, Serialize};
-use simple_agents_types::prelude::Message;
+use serde::{Deserialize, Serialize, Serializer};
+use simple_agents_types::prelude::{
+    Message, Prediction, ReasoningEffort, ResponseFormat, Result, StreamOptions, ToolChoice,
+    ToolDefinition, ValidationError,
+};
 
 /// OpenAI chat completion request
 ///
@@ -22,6 +25,15 @@ pub struct OpenAICompletionRequest<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
 
+    /// Maximum tokens to generate, including reasoning tokens (reasoning
+    /// models, which reject `max_tokens`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+
+    /// Reasoning effort (reasoning models only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+
     /// Top-p sampling (0.0-1.0)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
@@ -34,9 +46,123 @@ pub struct OpenAICompletionRequest<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
 
+    /// Streaming options (only valid when streaming)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<&'a StreamOptions>,
+
     /// Stop sequences (borrowed when possible)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop: Option<&'a Vec<String>>,
+
+    /// Output format (text, JSON object or JSON schema)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<&'a ResponseFormat>,
+
+    /// Tools the model may call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<&'a [ToolDefinition]>,
+
+    /// Whether and which tool the model calls
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_tool_choice")]
+    pub tool_choice: Option<&'a ToolChoice>,
+
+    /// Return log probabilities of the output tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+
+    /// Number of most likely alternatives to return per token (needs
+    /// `logprobs`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+
+    /// Predicted output, to speed up regenerating mostly-known content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prediction: Option<&'a Prediction>,
+}
+
+/// Serialize a tool choice in OpenAI's format, where a named function is
+/// `{"type": "function", "function": {"name": ...}}`.
+fn serialize_tool_choice<S: Serializer>(
+    choice: &Option<&ToolChoice>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match choice {
+        Some(ToolChoice::Function { name }) => serde_json::json!({
+            "type": "function",
+            "function": { "name": name }
+        })
+        .serialize(serializer),
+        choice => choice.serialize(serializer),
+    }
+}
+
+impl OpenAICompletionRequest<'_> {
+    /// Check the sampling and length fields before the request is sent.
+    ///
+    /// # Rules
+    /// - `temperature` in 0.0-2.0
+    /// - `top_p` greater than 0.0 and at most 1.0
+    /// - `temperature` and `top_p` not both changed from their default of
+    ///   1.0; OpenAI advises adjusting one or the other
+    /// - `n` of 1 (or unset) when streaming
+    /// - `max_tokens` and `max_completion_tokens` greater than 0
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ValidationError`] for the first rule broken.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                return Err(ValidationError::OutOfRange {
+                    field: "temperature".to_string(),
+                    min: 0.0,
+                    max: 2.0,
+                }
+                .into());
+            }
+        }
+        if let Some(top_p) = self.top_p {
+            if !(top_p > 0.0 && top_p <= 1.0) {
+                return Err(ValidationError::InvalidFormat {
+                    field: "top_p".to_string(),
+                    reason: "must be greater than 0.0 and at most 1.0".to_string(),
+                }
+                .into());
+            }
+        }
+        if let (Some(temperature), Some(top_p)) = (self.temperature, self.top_p) {
+            if temperature != 1.0 && top_p != 1.0 {
+                return Err(ValidationError::Conflict {
+                    field: "temperature".to_string(),
+                    related: "top_p".to_string(),
+                    reason: "set only one of them to a non-default value".to_string(),
+                }
+                .into());
+            }
+        }
+        if self.stream == Some(true) && self.n.is_some_and(|n| n != 1) {
+            return Err(ValidationError::Conflict {
+                field: "n".to_string(),
+                related: "stream".to_string(),
+                reason: "streaming returns a single completion".to_string(),
+            }
+            .into());
+        }
+        let lengths = [
+            ("max_tokens", self.max_tokens),
+            ("max_completion_tokens", self.max_completion_tokens),
+        ];
+        for (field, value) in lengths {
+            if value == Some(0) {
+                return Err(ValidationError::InvalidFormat {
+                    field: field.to_string(),
+                    reason: "must be greater than 0".to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
 }
 
 /// OpenAI chat completion response
@@ -63,6 +189,11 @@ pub struct OpenAICompletionResponse {
     /// System fingerprint
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_fingerprint: Option<String>,
+
+    /// URLs of the sources the content cites as `[1]`, `[2]`, ...; a
+    /// Perplexity extension, absent from OpenAI responses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<String>,
 }
 
 /// A single completion choice
@@ -72,10 +203,63 @@ pub struct OpenAIChoice {
     pub index: u32,
 
     /// The message generated by the model
-    pub message: Message,
+    pub message: OpenAIResponseMessage,
 
     /// Reason for completion finish
     pub finish_reason: Option<String>,
+
+    /// Stop sequence (string) or stop token ID (number) that ended
+    /// generation; a vLLM extension, absent from OpenAI responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<serde_json::Value>,
+}
+
+impl OpenAIChoice {
+    /// Matched stop sequence, if the server reported one as a string.
+    pub fn matched_stop(&self) -> Option<&str> {
+        self.stop_reason.as_ref().and_then(|reason| reason.as_str())
+    }
+}
+
+/// Message of a completion choice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIResponseMessage {
+    /// The message itself
+    #[serde(flatten)]
+    pub message: Message,
+
+    /// Citations of web pages (search models)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<OpenAIAnnotation>,
+}
+
+/// An annotation on a response message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIAnnotation {
+    /// Annotation type; only `url_citation` is defined
+    #[serde(rename = "type")]
+    pub annotation_type: String,
+
+    /// The citation, for `url_citation` annotations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_citation: Option<OpenAIUrlCitation>,
+}
+
+/// A web page cited by a span of the content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIUrlCitation {
+    /// Character (not byte) offset where the citing span starts
+    pub start_index: usize,
+
+    /// Character offset where the citing span ends (exclusive)
+    pub end_index: usize,
+
+    /// URL of the page
+    pub url: String,
+
+    /// Title of the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 /// Token usage information
@@ -91,6 +275,77 @@ pub struct OpenAIUsage {
     pub total_tokens: u32,
 }
 
+/// A chunk of a streaming chat completion (`chat.completion.chunk`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIStreamChunk {
+    /// Completion identifier (shared by all chunks)
+    pub id: String,
+
+    /// Model used for completion
+    pub model: String,
+
+    /// Unix timestamp of creation
+    #[serde(default)]
+    pub created: Option<u64>,
+
+    /// Choice deltas in this chunk
+    #[serde(default)]
+    pub choices: Vec<OpenAIStreamChoice>,
+
+    /// Token usage (final chunk only, with `include_usage`)
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+/// A choice delta within a stream chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIStreamChoice {
+    /// Index of this choice
+    pub index: u32,
+
+    /// Incremental message content
+    #[serde(default)]
+    pub delta: OpenAIStreamDelta,
+
+    /// Reason for completion finish (final chunk only)
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+}
+
+/// Incremental message content
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAIStreamDelta {
+    /// Role (first chunk only)
+    #[serde(default)]
+    pub role: Option<simple_agents_types::message::Role>,
+
+    /// Content fragment
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+/// A model listed by the `/models` endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Model identifier, as passed in requests
+    pub id: String,
+
+    /// Unix timestamp of creation (0 when the server does not report it)
+    #[serde(default)]
+    pub created: u64,
+
+    /// Organization or runtime that owns the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owned_by: Option<String>,
+}
+
+/// Response of the `/models` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIModelList {
+    /// Listed models
+    pub data: Vec<ModelInfo>,
+}
+
 /// OpenAI error response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIErrorResponse {
@@ -120,7 +375,7 @@ pub struct OpenAIErrorDetails {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use simple_agents_types::prelude::Role;
+    use simple_agents_types::prelude::{Role, SimpleAgentsError};
 
     #[test]
     fn test_serialize_request() {
@@ -129,6 +384,11 @@ mod tests {
             content: "Hello".to_string(),
             name: None,
             tool_call_id: None,
+            cache_control: None,
+            images: Vec::new(),
+            documents: Vec::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
         }];
 
         let request = OpenAICompletionRequest {
@@ -136,10 +396,19 @@ mod tests {
             messages: &messages,
             temperature: Some(0.7),
             max_tokens: Some(100),
+            max_completion_tokens: None,
+            reasoning_effort: None,
             top_p: None,
             n: None,
             stream: Some(false),
+            stream_options: None,
             stop: None,
+            response_format: None,
+            tools: None,
+            tool_choice: None,
+            logprobs: None,
+            top_logprobs: None,
+            prediction: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -148,6 +417,76 @@ mod tests {
         assert!(json.contains("0.7"));
     }
 
+    fn valid_request(messages: &[Message]) -> OpenAICompletionRequest<'_> {
+        OpenAICompletionRequest {
+            model: "gpt-4",
+            messages,
+            temperature: Some(0.7),
+            max_tokens: Some(100),
+            max_completion_tokens: None,
+            reasoning_effort: None,
+            top_p: Some(1.0),
+            n: Some(1),
+            stream: Some(true),
+            stream_options: None,
+            stop: None,
+            response_format: None,
+            tools: None,
+            tool_choice: None,
+            logprobs: None,
+            top_logprobs: None,
+            prediction: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_request() {
+        let messages = vec![Message::user("Hello")];
+        assert!(valid_request(&messages).validate().is_ok());
+
+        let defaults = OpenAICompletionRequest {
+            temperature: Some(1.0),
+            top_p: Some(0.9),
+            n: None,
+            ..valid_request(&messages)
+        };
+        assert!(defaults.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_fields() {
+        let messages = vec![Message::user("Hello")];
+        let field_of = |request: OpenAICompletionRequest<'_>| match request.validate().unwrap_err() {
+            SimpleAgentsError::Validation(
+                ValidationError::OutOfRange { field, .. }
+                | ValidationError::InvalidFormat { field, .. }
+                | ValidationError::Conflict { field, .. },
+            ) => field,
+            other => panic!("unexpected error {:?}", other),
+        };
+
+        let request = |f: fn(&mut OpenAICompletionRequest<'_>)| {
+            let mut request = valid_request(&messages);
+            f(&mut request);
+            field_of(request)
+        };
+        assert_eq!(request(|r| r.temperature = Some(2.5)), "temperature");
+        assert_eq!(request(|r| r.temperature = Some(-0.1)), "temperature");
+        assert_eq!(request(|r| r.temperature = Some(f32::NAN)), "temperature");
+        assert_eq!(request(|r| r.top_p = Some(0.0)), "top_p");
+        assert_eq!(request(|r| r.top_p = Some(1.5)), "top_p");
+        assert_eq!(request(|r| r.top_p = Some(0.9)), "temperature");
+        assert_eq!(request(|r| r.n = Some(2)), "n");
+        assert_eq!(request(|r| r.max_tokens = Some(0)), "max_tokens");
+        assert_eq!(
+            request(|r| {
+                r.max_tokens = None;
+                r.max_completion_tokens = Some(0);
+            }),
+            "max_completion_tokens"
+        );
+    }
+
     #[test]
     fn test_deserialize_response() {
         let json = r#"{
@@ -174,7 +513,7 @@ mod tests {
         assert_eq!(response.id, "chatcmpl-123");
         assert_eq!(response.model, "gpt-4");
         assert_eq!(response.choices.len(), 1);
-        assert_eq!(response.choices[0].message.content, "Hello there!");
+        assert_eq!(response.choices[0].message.message.content, "Hello there!");
         assert_eq!(response.usage.total_tokens, 30);
     }
 
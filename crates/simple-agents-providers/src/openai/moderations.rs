@@ -0,0 +1,658 @@
+//! Client for the OpenAI Moderations API, and a provider wrapper that
+//! screens user messages with it.
+
+use super::{error_for_status, map_request_error, OpenAIProvider};
+use crate::credentials::send_with_refresh;
+use crate::utils::auth::{self, AuthScheme};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Per-category verdicts of a [`ModerationResult`].
+///
+/// Field names follow the API's category names, with `/` and `-`
+/// replaced by `_` (e.g. `self-harm/intent` is `self_harm_intent`).
+/// Categories missing from a response (older models report fewer) are
+/// `false`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModerationCategories {
+    /// Harassing language towards any target
+    pub harassment: bool,
+    /// Harassment that also includes violence or serious harm
+    #[serde(rename = "harassment/threatening")]
+    pub harassment_threatening: bool,
+    /// Hate based on a protected attribute
+    pub hate: bool,
+    /// Hate that also includes violence or serious harm
+    #[serde(rename = "hate/threatening")]
+    pub hate_threatening: bool,
+    /// Advice or instruction on committing illicit acts
+    pub illicit: bool,
+    /// Illicit content that also references violence or weapons
+    #[serde(rename = "illicit/violent")]
+    pub illicit_violent: bool,
+    /// Promotion or depiction of self-harm
+    #[serde(rename = "self-harm")]
+    pub self_harm: bool,
+    /// Stated intent to self-harm
+    #[serde(rename = "self-harm/intent")]
+    pub self_harm_intent: bool,
+    /// Instructions for self-harm
+    #[serde(rename = "self-harm/instructions")]
+    pub self_harm_instructions: bool,
+    /// Sexual content
+    pub sexual: bool,
+    /// Sexual content involving minors
+    #[serde(rename = "sexual/minors")]
+    pub sexual_minors: bool,
+    /// Depiction of violence
+    pub violence: bool,
+    /// Graphic depiction of violence
+    #[serde(rename = "violence/graphic")]
+    pub violence_graphic: bool,
+}
+
+impl ModerationCategories {
+    /// API names of the flagged categories, in declaration order.
+    pub fn flagged(&self) -> Vec<&'static str> {
+        [
+            (self.harassment, "harassment"),
+            (self.harassment_threatening, "harassment/threatening"),
+            (self.hate, "hate"),
+            (self.hate_threatening, "hate/threatening"),
+            (self.illicit, "illicit"),
+            (self.illicit_violent, "illicit/violent"),
+            (self.self_harm, "self-harm"),
+            (self.self_harm_intent, "self-harm/intent"),
+            (self.self_harm_instructions, "self-harm/instructions"),
+            (self.sexual, "sexual"),
+            (self.sexual_minors, "sexual/minors"),
+            (self.violence, "violence"),
+            (self.violence_graphic, "violence/graphic"),
+        ]
+        .into_iter()
+        .filter_map(|(flagged, name)| flagged.then_some(name))
+        .collect()
+    }
+}
+
+/// Moderation verdict for one input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationResult {
+    /// Whether any category was flagged
+    pub flagged: bool,
+    /// Per-category verdicts
+    pub categories: ModerationCategories,
+    /// Per-category confidence scores (0.0 - 1.0), keyed by API category name
+    #[serde(default)]
+    pub category_scores: HashMap<String, f64>,
+}
+
+/// Response body of `POST /moderations`
+#[derive(Debug, Deserialize)]
+struct ModerationResponse {
+    results: Vec<ModerationResult>,
+}
+
+/// OpenAI Moderations API client.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::ModerationsClient;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let client = ModerationsClient::new(ApiKey::new("sk-...")?)?;
+///
+/// let result = client.check("I want to hurt someone").await?;
+/// if result.flagged {
+///     println!("flagged: {:?}", result.categories.flagged());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ModerationsClient {
+    credentials: Arc<dyn CredentialSource>,
+    base_url: String,
+    client: Client,
+    model: String,
+}
+
+impl std::fmt::Debug for ModerationsClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModerationsClient")
+            .field("base_url", &self.base_url)
+            .field("model", &self.model)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModerationsClient {
+    /// Moderation model used unless overridden
+    pub const DEFAULT_MODEL: &'static str = "omni-moderation-latest";
+
+    /// Create a client for the default OpenAI API base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(credentials: impl CredentialSource + 'static) -> Result<Self> {
+        Self::with_base_url(credentials, OpenAIProvider::DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Create a client with a custom base URL.
+    pub fn with_base_url(credentials: impl CredentialSource + 'static, base_url: String) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(10)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()
+            .map_err(|e| SimpleAgentsError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        Ok(Self::with_client(Arc::new(credentials), base_url, client))
+    }
+
+    /// Create a client that sends requests with `client`.
+    pub(crate) fn with_client(credentials: Arc<dyn CredentialSource>, base_url: String, client: Client) -> Self {
+        Self {
+            credentials,
+            base_url,
+            client,
+            model: Self::DEFAULT_MODEL.to_string(),
+        }
+    }
+
+    /// Set the moderation model (e.g. "text-moderation-latest").
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Get the base URL for this client
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Get the moderation model
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Classify a single input.
+    pub async fn check(&self, input: &str) -> Result<ModerationResult> {
+        let mut results = self.check_batch(vec![input]).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Classify several inputs in one request.
+    ///
+    /// Results are in input order. An empty batch returns without a request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::InvalidResponse`] if the API returns a
+    /// different number of results than inputs.
+    pub async fn check_batch(&self, inputs: Vec<&str>) -> Result<Vec<ModerationResult>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({ "model": self.model, "input": inputs });
+        let url = format!("{}/moderations", self.base_url);
+        let response = send_with_refresh(self.credentials.as_ref(), |key| {
+            let auth = auth::header(AuthScheme::Bearer, &key);
+            let request = self.client.post(&url).json(&body);
+            async move {
+                let (name, value) = auth?;
+                let response = request
+                    .header(name, value)
+                    .send()
+                    .await
+                    .map_err(map_request_error)?;
+                error_for_status(response).await
+            }
+        })
+        .await?;
+
+        let response: ModerationResponse = response.json().await.map_err(|e| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to deserialize response: {}",
+                e
+            )))
+        })?;
+
+        if response.results.len() != inputs.len() {
+            return Err(ProviderError::InvalidResponse(format!(
+                "expected {} moderation results, got {}",
+                inputs.len(),
+                response.results.len()
+            ))
+            .into());
+        }
+        Ok(response.results)
+    }
+}
+
+/// Extension key holding the user messages `execute` has to moderate
+const MODERATION_INPUTS: &str = "moderation.inputs";
+
+/// Provider wrapper that moderates user messages before completing.
+///
+/// [`Provider::complete`] sends every user message to the Moderations API
+/// in one batch and fails with a [`ValidationError`] naming the first
+/// flagged message and its categories, without calling the inner provider.
+///
+/// The lower-level hooks are moderated too, so wrappers that drive them
+/// directly (such as a response cache) cannot skip the check:
+/// `transform_request` records the user messages in the request's
+/// extensions, and `execute` and `execute_stream` moderate them before
+/// calling the inner provider. A request not built by this wrapper's
+/// `transform_request` is refused with a [`ValidationError`].
+pub struct ModerationMiddleware<P> {
+    inner: P,
+    moderations: ModerationsClient,
+}
+
+impl<P: Provider> ModerationMiddleware<P> {
+    /// Wrap `inner`, checking user messages with `moderations`.
+    pub fn new(inner: P, moderations: ModerationsClient) -> Self {
+        Self { inner, moderations }
+    }
+
+    /// Access the moderations client.
+    pub fn moderations(&self) -> &ModerationsClient {
+        &self.moderations
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Index and content of every user message in `req`
+    fn user_messages(req: &CompletionRequest) -> Vec<(usize, &str)> {
+        req.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.role == Role::User)
+            .map(|(index, message)| (index, message.content.as_str()))
+            .collect()
+    }
+
+    /// Moderate the user messages `transform_request` recorded in `req`.
+    async fn moderate_provider_request(&self, req: &mut ProviderRequest) -> Result<()> {
+        let messages = req
+            .extensions
+            .remove(MODERATION_INPUTS)
+            .and_then(|inputs| serde_json::from_value::<Vec<(usize, String)>>(inputs).ok())
+            .ok_or_else(|| {
+                ValidationError::new(
+                    "request was not built by ModerationMiddleware::transform_request; refusing to send it \
+                     unmoderated",
+                )
+            })?;
+        self.moderate(messages.iter().map(|(index, content)| (*index, content.as_str())).collect())
+            .await
+    }
+
+    async fn moderate(&self, messages: Vec<(usize, &str)>) -> Result<()> {
+        let (indices, inputs): (Vec<usize>, Vec<&str>) = messages.into_iter().unzip();
+
+        let results = self.moderations.check_batch(inputs).await?;
+        match indices.into_iter().zip(results).find(|(_, result)| result.flagged) {
+            Some((index, result)) => {
+                tracing::info!(message = index, categories = ?result.categories.flagged(), "Message flagged by moderation");
+                Err(ValidationError::new(format!(
+                    "messages[{}] flagged by moderation ({})",
+                    index,
+                    result.categories.flagged().join(", ")
+                ))
+                .into())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for ModerationMiddleware<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let mut request = self.inner.transform_request(req)?;
+        request.extensions.insert(MODERATION_INPUTS, serde_json::to_value(Self::user_messages(req))?);
+        Ok(request)
+    }
+
+    async fn execute(&self, mut req: ProviderRequest) -> Result<ProviderResponse> {
+        self.moderate_provider_request(&mut req).await?;
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        mut req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.moderate_provider_request(&mut req).await?;
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        req.ensure_not_streaming()?;
+        self.moderate(Self::user_messages(req)).await?;
+        self.inner.complete(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const API_KEY: &str = "sk-test1234567890123456789012345678901234567890";
+
+    fn client(server: &mockito::ServerGuard) -> ModerationsClient {
+        ModerationsClient::with_base_url(ApiKey::new(API_KEY).unwrap(), server.url()).unwrap()
+    }
+
+    fn clean_result() -> serde_json::Value {
+        serde_json::json!({
+            "flagged": false,
+            "categories": {"hate": false, "self-harm": false, "violence": false},
+            "category_scores": {"hate": 0.0001, "self-harm": 0.0002, "violence": 0.0003}
+        })
+    }
+
+    fn violent_result() -> serde_json::Value {
+        serde_json::json!({
+            "flagged": true,
+            "categories": {
+                "harassment": true,
+                "harassment/threatening": true,
+                "hate": false,
+                "self-harm": false,
+                "violence": true,
+                "violence/graphic": false
+            },
+            "category_scores": {"harassment": 0.61, "harassment/threatening": 0.58, "violence": 0.97}
+        })
+    }
+
+    fn response(results: Vec<serde_json::Value>) -> String {
+        serde_json::json!({"id": "modr-1", "model": "omni-moderation-latest", "results": results}).to_string()
+    }
+
+    #[test]
+    fn test_deserialize_categories() {
+        let result: ModerationResult = serde_json::from_value(violent_result()).unwrap();
+        assert!(result.flagged);
+        assert!(result.categories.harassment_threatening);
+        assert!(result.categories.violence);
+        // Missing categories default to false
+        assert!(!result.categories.sexual_minors);
+        assert_eq!(
+            result.categories.flagged(),
+            vec!["harassment", "harassment/threatening", "violence"]
+        );
+        assert_eq!(result.category_scores["violence"], 0.97);
+    }
+
+    #[tokio::test]
+    async fn test_check_sends_input_and_model() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/moderations")
+            .match_header("authorization", format!("Bearer {}", API_KEY).as_str())
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "omni-moderation-latest",
+                "input": ["I will hurt you"]
+            })))
+            .with_status(200)
+            .with_body(response(vec![violent_result()]))
+            .create_async()
+            .await;
+
+        let result = client(&server).check("I will hurt you").await.unwrap();
+        assert!(result.flagged);
+        assert!(result.categories.violence);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_check_batch() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/moderations")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "text-moderation-latest",
+                "input": ["hello", "I will hurt you"]
+            })))
+            .with_status(200)
+            .with_body(response(vec![clean_result(), violent_result()]))
+            .create_async()
+            .await;
+
+        let client = client(&server).with_model("text-moderation-latest");
+        let results = client.check_batch(vec!["hello", "I will hurt you"]).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(!results[0].flagged);
+        assert!(results[0].categories.flagged().is_empty());
+        assert!(results[1].flagged);
+
+        // Empty batches never reach the API
+        assert!(client.check_batch(Vec::new()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_batch_rejects_mismatched_results() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/moderations")
+            .with_status(200)
+            .with_body(response(vec![clean_result()]))
+            .create_async()
+            .await;
+
+        let err = client(&server).check_batch(vec!["a", "b"]).await.unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Provider(ProviderError::InvalidResponse(_))));
+    }
+
+    struct CountingProvider {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn transform_request(&self, _req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com"))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ProviderResponse::new(200, serde_json::json!({})))
+        }
+
+        fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: "mock-model".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("ok"),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: Some("counting".to_string()),
+            })
+        }
+
+        async fn execute_stream(
+            &self,
+            _req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: "mock-model".to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    fn conversation() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::system("You are helpful."))
+            .message(Message::user("hello"))
+            .message(Message::assistant("Hi! How can I help?"))
+            .message(Message::user("I will hurt you"))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_middleware_blocks_flagged_user_message() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/moderations")
+            // Only user messages are moderated
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "input": ["hello", "I will hurt you"]
+            })))
+            .with_status(200)
+            .with_body(response(vec![clean_result(), violent_result()]))
+            .create_async()
+            .await;
+
+        let provider = ModerationMiddleware::new(CountingProvider { calls: AtomicU32::new(0) }, client(&server));
+        let err = provider.complete(&conversation()).await.unwrap_err();
+
+        assert!(matches!(err, SimpleAgentsError::Validation(_)));
+        assert_eq!(
+            err.to_string(),
+            "Validation error: messages[3] flagged by moderation (harassment, harassment/threatening, violence)"
+        );
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 0);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_middleware_forwards_clean_requests() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/moderations")
+            .with_status(200)
+            .with_body(response(vec![clean_result(), clean_result()]))
+            .create_async()
+            .await;
+
+        let provider = ModerationMiddleware::new(CountingProvider { calls: AtomicU32::new(0) }, client(&server));
+        let response = provider.complete(&conversation()).await.unwrap();
+
+        assert_eq!(response.content(), Some("ok"));
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 1);
+    }
+    #[tokio::test]
+    async fn test_execute_moderates_transformed_requests() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/moderations")
+            .with_status(200)
+            .with_body(response(vec![clean_result(), violent_result()]))
+            .create_async()
+            .await;
+
+        let provider = ModerationMiddleware::new(CountingProvider { calls: AtomicU32::new(0) }, client(&server));
+        let request = provider.transform_request(&conversation()).unwrap();
+        let err = provider.execute(request).await.unwrap_err();
+        assert!(err.to_string().contains("messages[3] flagged by moderation"));
+
+        // Bodies built elsewhere carry no moderation inputs and are refused
+        let err = provider.execute(ProviderRequest::new("https://api.example.com")).await.unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Validation(_)));
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_is_moderated() {
+        use futures::StreamExt;
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/moderations")
+            .with_status(200)
+            .with_body(response(vec![clean_result(), clean_result()]))
+            .create_async()
+            .await;
+
+        let provider = ModerationMiddleware::new(CountingProvider { calls: AtomicU32::new(0) }, client(&server));
+        let mut req = conversation();
+        req.stream = Some(true);
+
+        let stream = provider.execute_stream(provider.transform_request(&req).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().model, "mock-model");
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 1);
+
+        let unmoderated = ProviderRequest::new("https://api.example.com");
+        assert!(provider.execute_stream(unmoderated).await.is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! OpenAI streaming support.
+//!
+//! OpenAI streams `chat.completion.chunk` objects as SSE `data:` payloads and
+//! terminates the stream with `data: [DONE]`. The parsing here is shared by
+//! OpenAI-compatible providers.
+
+use super::{map_finish_reason, OpenAIError, OpenAIStreamChunk};
+use crate::streaming::SseEvent;
+use futures::{Stream, StreamExt};
+use simple_agents_types::prelude::*;
+use simple_agents_types::response::normalize_unix_timestamp;
+
+/// Payload that marks the end of an OpenAI stream
+pub const DONE_MARKER: &str = "[DONE]";
+
+/// Parse one SSE event into a completion chunk.
+///
+/// Returns `Ok(None)` for the `[DONE]` marker and for events without data,
+/// and the provider error for an in-stream error event (see
+/// [`OpenAIError::from_streaming_event`]).
+pub fn parse_stream_event(event: &SseEvent) -> Result<Option<CompletionChunk>> {
+    let data = event.data.trim();
+    if data.is_empty() || data == DONE_MARKER {
+        return Ok(None);
+    }
+
+    let chunk: OpenAIStreamChunk = serde_json::from_str(data).map_err(|e| {
+        // Only an unparsable payload can be an error event, so chunks
+        // are not parsed twice
+        match OpenAIError::from_streaming_event(data) {
+            Some(error) => SimpleAgentsError::Provider(error.into()),
+            None => SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Failed to parse stream chunk: {}",
+                e
+            ))),
+        }
+    })?;
+
+    Ok(Some(CompletionChunk {
+        id: chunk.id,
+        model: chunk.model,
+        choices: chunk
+            .choices
+            .into_iter()
+            .map(|choice| ChoiceDelta {
+                index: choice.index,
+                delta: MessageDelta {
+                    role: choice.delta.role,
+                    content: choice.delta.content,
+                },
+                finish_reason: choice.finish_reason.as_deref().map(map_finish_reason),
+            })
+            .collect(),
+        created: chunk.created.map(|created| normalize_unix_timestamp(created as i64)),
+        usage: chunk
+            .usage
+            .map(|usage| Usage::new(usage.prompt_tokens, usage.completion_tokens)),
+    }))
+}
+
+/// Convert a stream of SSE events into completion chunks.
+///
+/// The stream ends after the first error, such as an in-stream error
+/// event, without reading further events.
+pub fn chunk_stream<S>(events: S) -> impl Stream<Item = Result<CompletionChunk>> + Send + Unpin
+where
+    S: Stream<Item = Result<SseEvent>> + Send + Unpin,
+{
+    events
+        .scan(false, |failed, event| {
+            if *failed {
+                return futures::future::ready(None);
+            }
+            let chunk = event.and_then(|event| parse_stream_event(&event));
+            *failed = chunk.is_err();
+            futures::future::ready(Some(chunk))
+        })
+        .filter_map(|chunk| futures::future::ready(chunk.transpose()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(payload: &str) -> SseEvent {
+        SseEvent {
+            data: payload.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_first_chunk() {
+        let chunk = parse_stream_event(&data(
+            r#"{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1700000000,"model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":""},"finish_reason":null}]}"#,
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(chunk.id, "chatcmpl-1");
+        assert_eq!(chunk.created, Some(1700000000));
+        assert_eq!(chunk.choices[0].delta.role, Some(Role::Assistant));
+        assert_eq!(chunk.choices[0].finish_reason, None);
+    }
+
+    #[test]
+    fn test_parse_final_chunk() {
+        let chunk = parse_stream_event(&data(
+            r#"{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1700000000,"model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"length"}]}"#,
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(chunk.choices[0].delta.content, None);
+        assert_eq!(chunk.choices[0].finish_reason, Some(FinishReason::Length));
+    }
+
+    #[test]
+    fn test_parse_done_and_malformed() {
+        assert!(parse_stream_event(&data("[DONE]")).unwrap().is_none());
+        assert!(parse_stream_event(&data("")).unwrap().is_none());
+        assert!(parse_stream_event(&data("{not json")).is_err());
+    }
+
+    #[test]
+    fn test_parse_usage_chunk() {
+        let chunk = parse_stream_event(&data(
+            r#"{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1700000000,"model":"gpt-4o","choices":[],"usage":{"prompt_tokens":9,"completion_tokens":3,"total_tokens":12}}"#,
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert!(chunk.choices.is_empty());
+        assert_eq!(chunk.usage, Some(Usage::new(9, 3)));
+    }
+
+    #[tokio::test]
+    async fn test_chunk_stream() {
+        let events = vec![
+            Ok(data(r#"{"id":"c","model":"m","choices":[{"index":0,"delta":{"content":"Hel"}}]}"#)),
+            Ok(data(r#"{"id":"c","model":"m","choices":[{"index":0,"delta":{"content":"lo"}}]}"#)),
+            Ok(data("[DONE]")),
+        ];
+
+        let content: String = chunk_stream(futures::stream::iter(events))
+            .map(|chunk| chunk.unwrap().choices[0].delta.content.clone().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        assert_eq!(content, "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_error_event_terminates_stream() {
+        let events = vec![
+            Ok(data(r#"{"id":"c","model":"m","choices":[{"index":0,"delta":{"content":"Hel"}}]}"#)),
+            Ok(data(r#"{"error":{"message":"The server had an error","type":"server_error","code":null}}"#)),
+            Ok(data(r#"{"id":"c","model":"m","choices":[{"index":0,"delta":{"content":"lo"}}]}"#)),
+        ];
+
+        let items: Vec<_> = chunk_stream(futures::stream::iter(events)).collect().await;
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        match &items[1] {
+            Err(SimpleAgentsError::Provider(ProviderError::ServerError(message))) => {
+                assert_eq!(message, "The server had an error")
+            }
+            other => panic!("expected a server error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Convenience wrapper for single-image vision requests.
+
+use super::OpenAIProvider;
+use base64::Engine;
+use simple_agents_types::prelude::*;
+use std::path::Path;
+
+/// Prompt used by [`VisionProvider::describe_image`]
+pub const DESCRIBE_PROMPT: &str = "Describe this image in detail.";
+
+/// Asks a vision model questions about one image at a time.
+///
+/// Builds a user message with the question and the image attached (see
+/// [`Message::with_image`]) and returns the reply text. Works with any
+/// [`Provider`] that supports [`Feature::Vision`]; defaults to
+/// [`OpenAIProvider`].
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::{OpenAIProvider, VisionProvider};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let provider = OpenAIProvider::new(ApiKey::new("sk-...")?)?;
+/// let vision = VisionProvider::new(provider, "gpt-4o-mini").with_detail(ImageDetail::Low);
+///
+/// let answer = vision
+///     .analyze_image("https://example.com/receipt.png", "What is the total?")
+///     .await?;
+/// println!("{}", answer);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct VisionProvider<P = OpenAIProvider> {
+    provider: P,
+    model: String,
+    detail: Option<ImageDetail>,
+    max_tokens: Option<u32>,
+}
+
+impl<P: Provider> VisionProvider<P> {
+    /// Send requests for `model` through `provider`.
+    pub fn new(provider: P, model: &str) -> Self {
+        Self {
+            provider,
+            model: model.to_string(),
+            detail: None,
+            max_tokens: None,
+        }
+    }
+
+    /// Set the image fidelity (the provider chooses by default).
+    pub fn with_detail(mut self, detail: ImageDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// Limit the length of replies.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Model requests are sent to.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Access the wrapped provider.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Ask `question` about the image at `image_url`.
+    ///
+    /// `image_url` may be an `https://` URL or a base64 `data:` URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::InvalidResponse`] if the reply has no text,
+    /// in addition to any error from [`Provider::complete`].
+    pub async fn analyze_image(&self, image_url: &str, question: &str) -> Result<String> {
+        let mut image = ImageUrl::new(image_url);
+        image.detail = self.detail;
+
+        let mut builder = CompletionRequest::builder()
+            .model(&self.model)
+            .message(Message::user(question).with_image(image));
+        if let Some(max_tokens) = self.max_tokens {
+            builder = builder.max_tokens(max_tokens);
+        }
+        let request = builder.build()?;
+
+        let response = self.provider.complete(&request).await?;
+        response.content().map(str::to_string).ok_or_else(|| {
+            ProviderError::InvalidResponse(format!("response {} has no content", response.id)).into()
+        })
+    }
+
+    /// Ask `question` about an image file, sent inline as a `data:` URL.
+    ///
+    /// PNG, JPEG, GIF and WebP files are supported, detected by extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidFormat`] for other file types and
+    /// [`SimpleAgentsError::Config`] if the file cannot be read, in addition
+    /// to the errors of [`analyze_image`](Self::analyze_image).
+    pub async fn analyze_local_image(&self, path: &Path, question: &str) -> Result<String> {
+        let mime = image_mime_type(path)?;
+        let bytes = tokio::fs::read(path).await.map_err(|e| {
+            SimpleAgentsError::Config(format!("Failed to read image {}: {}", path.display(), e))
+        })?;
+        let data_url = format!(
+            "data:{};base64,{}",
+            mime,
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        );
+        self.analyze_image(&data_url, question).await
+    }
+
+    /// Describe the image at `image_url` (see [`DESCRIBE_PROMPT`]).
+    pub async fn describe_image(&self, image_url: &str) -> Result<String> {
+        self.analyze_image(image_url, DESCRIBE_PROMPT).await
+    }
+}
+
+/// MIME type of a supported image file, from its extension.
+fn image_mime_type(path: &Path) -> Result<&'static str> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => Ok("image/png"),
+        "jpg" | "jpeg" => Ok("image/jpeg"),
+        "gif" => Ok("image/gif"),
+        "webp" => Ok("image/webp"),
+        _ => Err(ValidationError::InvalidFormat {
+            field: "path".to_string(),
+            reason: format!("unsupported image type {:?} (expected png, jpeg, gif or webp)", extension),
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Records requests and replies with a fixed answer
+    #[derive(Default)]
+    struct MockProvider {
+        requests: Mutex<Vec<CompletionRequest>>,
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            self.requests.lock().unwrap().push(req.clone());
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "model": req.model })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: resp.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("A cat on a sofa."),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(90, 6),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+    }
+
+    impl MockProvider {
+        fn last_message(&self) -> Message {
+            self.requests.lock().unwrap().last().unwrap().messages[0].clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_image() {
+        let vision = VisionProvider::new(MockProvider::default(), "gpt-4o")
+            .with_detail(ImageDetail::High)
+            .with_max_tokens(300);
+
+        let answer = vision
+            .analyze_image("https://example.com/cat.png", "What animal is this?")
+            .await
+            .unwrap();
+        assert_eq!(answer, "A cat on a sofa.");
+
+        let request = vision.provider().requests.lock().unwrap()[0].clone();
+        assert_eq!(request.model, "gpt-4o");
+        assert_eq!(request.max_tokens, Some(300));
+        let message = &request.messages[0];
+        assert_eq!(message.role, Role::User);
+        assert_eq!(message.content, "What animal is this?");
+        assert_eq!(
+            message.images,
+            vec![ImageUrl::new("https://example.com/cat.png").with_detail(ImageDetail::High)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_describe_image() {
+        let vision = VisionProvider::new(MockProvider::default(), "gpt-4o-mini");
+        vision.describe_image("https://example.com/cat.png").await.unwrap();
+
+        let message = vision.provider().last_message();
+        assert_eq!(message.content, DESCRIBE_PROMPT);
+        assert_eq!(message.images[0].detail, None);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_local_image() {
+        let path = std::env::temp_dir().join(format!(
+            "simple-agents-vision-{}-{}.PNG",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n").unwrap();
+
+        let vision = VisionProvider::new(MockProvider::default(), "gpt-4o");
+        vision.analyze_local_image(&path, "Is this blank?").await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let message = vision.provider().last_message();
+        assert_eq!(message.images[0].url, "data:image/png;base64,iVBORw0KGgo=");
+    }
+
+    #[tokio::test]
+    async fn test_analyze_local_image_errors() {
+        let vision = VisionProvider::new(MockProvider::default(), "gpt-4o");
+
+        let err = vision
+            .analyze_local_image(Path::new("notes.txt"), "?")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Validation(_)));
+
+        let err = vision
+            .analyze_local_image(Path::new("/nonexistent/simple-agents.png"), "?")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Config(_)));
+        assert!(vision.provider().requests.lock().unwrap().is_empty());
+    }
+}
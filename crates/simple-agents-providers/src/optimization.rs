@@ -0,0 +1,400 @@
+//! Hill-climbing system prompt optimization.
+//!
+//! [`PromptOptimizer`] repeatedly perturbs a request's system prompt by
+//! adding or removing single sentences, runs each candidate several times
+//! and keeps it only if a [`ModelGradedScorer`] rates it higher on average.
+//! This is a cheap local search, not model training.
+
+use crate::batch::BatchExecutor;
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+
+/// Scores completions by asking a judge model to grade them.
+#[derive(Clone)]
+pub struct ModelGradedScorer {
+    judge: Arc<dyn Provider>,
+    model: String,
+}
+
+impl ModelGradedScorer {
+    /// Create a scorer that grades with `model` on the `judge` provider.
+    pub fn new(judge: Arc<dyn Provider>, model: impl Into<String>) -> Self {
+        Self {
+            judge,
+            model: model.into(),
+        }
+    }
+
+    /// Score a response against `criteria`, from 0.0 (worst) to 1.0 (best).
+    ///
+    /// The judge is asked for an integer grade from 0 to 10; the first
+    /// number in its reply is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns the judge's error, or [`ProviderError::InvalidResponse`] if
+    /// the reply contains no grade.
+    pub async fn score(
+        &self,
+        request: &CompletionRequest,
+        response: &CompletionResponse,
+        criteria: &str,
+    ) -> Result<f32> {
+        let prompt = request
+            .messages
+            .iter()
+            .filter(|m| m.role == Role::User)
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let judge_request = CompletionRequest::builder()
+            .model(self.model.clone())
+            .message(Message::system(
+                "You grade AI responses. Reply with a single integer from 0 to 10 and nothing else.",
+            ))
+            .message(Message::user(format!(
+                "Criteria:\n{}\n\nPrompt:\n{}\n\nResponse:\n{}",
+                criteria,
+                prompt,
+                response.content().unwrap_or("")
+            )))
+            .temperature(0.0)
+            .build()?;
+
+        let verdict = self.judge.complete(&judge_request).await?;
+        let reply = verdict.content().unwrap_or("");
+
+        parse_grade(reply).ok_or_else(|| {
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(format!(
+                "Judge reply contains no grade: {}",
+                reply
+            )))
+        })
+    }
+}
+
+/// Extract the first number in `reply` as a 0.0-1.0 score.
+fn parse_grade(reply: &str) -> Option<f32> {
+    let start = reply.find(|c: char| c.is_ascii_digit())?;
+    let number: String = reply[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let grade: f32 = number.trim_end_matches('.').parse().ok()?;
+    Some((grade / 10.0).clamp(0.0, 1.0))
+}
+
+/// Sentences the optimizer may add to a system prompt.
+const CANDIDATE_SENTENCES: &[&str] = &[
+    "Be concise.",
+    "Answer the question directly.",
+    "Think through the problem step by step before answering.",
+    "Follow the user's instructions exactly.",
+    "If you are unsure, say so.",
+    "Use plain language.",
+];
+
+/// Hill-climbing optimizer for a request's system prompt.
+pub struct PromptOptimizer {
+    executor: BatchExecutor,
+    judge: ModelGradedScorer,
+    n_runs: u32,
+    n_iterations: u32,
+}
+
+impl PromptOptimizer {
+    /// Create an optimizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Provider that runs candidate requests
+    /// * `judge` - Scorer used to grade responses
+    /// * `n_runs` - Runs per candidate; scores are averaged over them
+    /// * `n_iterations` - Maximum number of perturbations to try
+    pub fn new(provider: Arc<dyn Provider>, judge: ModelGradedScorer, n_runs: u32, n_iterations: u32) -> Self {
+        Self {
+            executor: BatchExecutor::new(provider, BatchExecutor::DEFAULT_CONCURRENCY),
+            judge,
+            n_runs,
+            n_iterations,
+        }
+    }
+
+    /// Optimize the system prompt of `request` against `criteria`.
+    ///
+    /// Evaluates at most `n_iterations + 1` prompts (the original plus one
+    /// perturbation per iteration) and returns the request with the best
+    /// scoring system prompt. Stops early when no perturbation is left.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if `n_runs` is 0, or the first provider or
+    /// judge error encountered.
+    pub async fn optimize(&self, request: &CompletionRequest, criteria: &str) -> Result<CompletionRequest> {
+        if self.n_runs == 0 {
+            return Err(SimpleAgentsError::Validation(ValidationError::OutOfRange {
+                field: "n_runs".to_string(),
+                min: 1.0,
+                max: u32::MAX as f32,
+            }));
+        }
+
+        let mut best_sentences = split_sentences(system_prompt(request));
+        let mut best_score = self.evaluate(request, criteria).await?;
+        let mut best_request = request.clone();
+
+        for iteration in 0..self.n_iterations {
+            let candidates = perturbations(&best_sentences);
+            if candidates.is_empty() {
+                break;
+            }
+
+            let candidate = &candidates[iteration as usize % candidates.len()];
+            let candidate_request = with_system_prompt(request, &candidate.join(" "));
+            let score = self.evaluate(&candidate_request, criteria).await?;
+
+            tracing::debug!(iteration, score, best_score, "Evaluated prompt candidate");
+
+            if score > best_score {
+                best_score = score;
+                best_sentences = candidate.clone();
+                best_request = candidate_request;
+            }
+        }
+
+        Ok(best_request)
+    }
+
+    /// Average judge score over `n_runs` executions of `request`.
+    async fn evaluate(&self, request: &CompletionRequest, criteria: &str) -> Result<f32> {
+        let responses = self
+            .executor
+            .execute_repeated(request, self.n_runs as usize)
+            .await;
+
+        let mut total = 0.0;
+        for response in responses {
+            total += self.judge.score(request, &response?, criteria).await?;
+        }
+        Ok(total / self.n_runs as f32)
+    }
+}
+
+/// Content of the first system message, or "" if there is none.
+fn system_prompt(request: &CompletionRequest) -> &str {
+    request
+        .messages
+        .iter()
+        .find(|m| m.role == Role::System)
+        .map(|m| m.content.as_str())
+        .unwrap_or("")
+}
+
+/// Replace the first system message (or prepend one) with `prompt`.
+fn with_system_prompt(request: &CompletionRequest, prompt: &str) -> CompletionRequest {
+    let mut request = request.clone();
+    match request.messages.iter_mut().find(|m| m.role == Role::System) {
+        Some(message) => message.content = prompt.to_string(),
+        None => request.messages.insert(0, Message::system(prompt)),
+    }
+    request
+}
+
+/// Split a prompt into sentences, keeping terminal punctuation.
+fn split_sentences(prompt: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+
+    for c in prompt.chars() {
+        current.push(c);
+        if matches!(c, '.' | '!' | '?') {
+            sentences.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        sentences.push(current.trim().to_string());
+    }
+
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// All single-sentence removals (keeping at least one sentence) and additions.
+fn perturbations(sentences: &[String]) -> Vec<Vec<String>> {
+    let mut candidates = Vec::new();
+
+    if sentences.len() > 1 {
+        for i in 0..sentences.len() {
+            let mut candidate = sentences.to_vec();
+            candidate.remove(i);
+            candidates.push(candidate);
+        }
+    }
+
+    for sentence in CANDIDATE_SENTENCES {
+        if !sentences.iter().any(|s| s == sentence) {
+            let mut candidate = sentences.to_vec();
+            candidate.push(sentence.to_string());
+            candidates.push(candidate);
+        }
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Echoes the system prompt back as the completion.
+    #[derive(Default)]
+    struct EchoSystemProvider {
+        calls: AtomicU32,
+    }
+
+    /// Grades 9 when the graded response mentions "concise", otherwise 3.
+    #[derive(Default)]
+    struct MockJudge {
+        calls: AtomicU32,
+    }
+
+    fn response(content: &str) -> CompletionResponse {
+        CompletionResponse {
+            id: "resp".to_string(),
+            model: "mock-model".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(content),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(1, 1),
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        }
+    }
+
+    #[async_trait]
+    impl Provider for EchoSystemProvider {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "system": system_prompt(req) })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(response(resp.body["system"].as_str().unwrap_or_default()))
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockJudge {
+        fn name(&self) -> &str {
+            "judge"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            let prompt = &req.messages.last().unwrap().content;
+            let graded = prompt.split("Response:").nth(1).unwrap_or("").contains("concise");
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "grade": if graded { "9" } else { "Grade: 3/10" } })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(response(resp.body["grade"].as_str().unwrap_or_default()))
+        }
+    }
+
+    fn request(system: &str) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::system(system))
+            .message(Message::user("Explain Rust lifetimes."))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_grade() {
+        assert_eq!(parse_grade("7"), Some(0.7));
+        assert_eq!(parse_grade("Grade: 10."), Some(1.0));
+        assert_eq!(parse_grade("42"), Some(1.0));
+        assert_eq!(parse_grade("no idea"), None);
+    }
+
+    #[test]
+    fn test_split_sentences_and_perturbations() {
+        let sentences = split_sentences("You are helpful. Use examples!  Why not");
+        assert_eq!(sentences, vec!["You are helpful.", "Use examples!", "Why not"]);
+
+        let candidates = perturbations(&sentences);
+        assert_eq!(candidates.len(), 3 + CANDIDATE_SENTENCES.len());
+        assert!(perturbations(&["Be concise.".to_string()])
+            .iter()
+            .all(|c| c.len() == 2));
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_improves_prompt() {
+        let provider = Arc::new(EchoSystemProvider::default());
+        let judge = ModelGradedScorer::new(Arc::new(MockJudge::default()), "judge-model");
+        let optimizer = PromptOptimizer::new(provider, judge, 2, 5);
+
+        let optimized = optimizer
+            .optimize(&request("You are a Rust tutor."), "Is the answer concise?")
+            .await
+            .unwrap();
+
+        assert_eq!(system_prompt(&optimized), "You are a Rust tutor. Be concise.");
+        assert_eq!(optimized.messages.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_terminates_within_iteration_bound() {
+        let provider = Arc::new(EchoSystemProvider::default());
+        let judge_provider = Arc::new(MockJudge::default());
+        let judge = ModelGradedScorer::new(judge_provider.clone(), "judge-model");
+        let (n_runs, n_iterations) = (3, 4);
+        let optimizer = PromptOptimizer::new(provider.clone(), judge, n_runs, n_iterations);
+
+        optimizer
+            .optimize(&request("You are a tutor. Use examples."), "Is the answer accurate?")
+            .await
+            .unwrap();
+
+        let max_calls = n_runs * (n_iterations + 1);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), max_calls);
+        assert_eq!(judge_provider.calls.load(Ordering::SeqCst), max_calls);
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_rejects_zero_runs() {
+        let judge = ModelGradedScorer::new(Arc::new(MockJudge::default()), "judge-model");
+        let optimizer = PromptOptimizer::new(Arc::new(EchoSystemProvider::default()), judge, 0, 3);
+
+        assert!(matches!(
+            optimizer.optimize(&request("Hi."), "anything").await,
+            Err(SimpleAgentsError::Validation(_))
+        ));
+    }
+}
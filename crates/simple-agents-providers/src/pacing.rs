@@ -0,0 +1,413 @@
+//! Even pacing of streamed text for display.
+//!
+//! Fast providers can deliver hundreds of tokens in a single burst, which
+//! reads as a glitch rather than as typing. [`paced`] re-emits a chunk
+//! stream at a steady rate, splitting large deltas into smaller ones.
+
+use futures::{Stream, StreamExt};
+use simple_agents_types::prelude::*;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Characters per token assumed by [`PaceRate::TokensPerSecond`], as in
+/// [`Message::estimate_tokens`]
+const CHARS_PER_TOKEN: f64 = 4.0;
+
+/// Emissions per second at most; faster rates emit larger pieces
+const FRAMES_PER_SECOND: f64 = 60.0;
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<CompletionChunk>> + Send>>;
+
+/// Display rate for [`paced`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaceRate {
+    /// Estimated tokens per second (four characters per token)
+    TokensPerSecond(f64),
+    /// Characters (grapheme clusters) per second
+    CharsPerSecond(f64),
+}
+
+impl PaceRate {
+    /// Grapheme clusters per second; infinite (no pacing) unless the rate
+    /// is positive and finite.
+    fn graphemes_per_second(&self) -> f64 {
+        let rate = match *self {
+            Self::TokensPerSecond(tokens) => tokens * CHARS_PER_TOKEN,
+            Self::CharsPerSecond(chars) => chars,
+        };
+        if rate.is_finite() && rate > 0.0 {
+            rate
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Re-emit `stream` at `rate`.
+///
+/// Content deltas are split at grapheme cluster boundaries into pieces of
+/// at most one frame (1/60 s) of text, so an emoji or a letter with a
+/// combining accent is never cut in half. A delta's role goes on its first
+/// piece, its finish reason on its last, and a chunk's usage on its last
+/// piece. Chunks without content (usage, finish reason only) pass through
+/// in place. A chunk with several choices is emitted choice by choice, so
+/// no content moves ahead of content that arrived before it.
+///
+/// The upstream is read ahead while pacing. Once it ends or fails,
+/// everything buffered is emitted immediately (followed by the error), so
+/// pacing never delays the end of a response. A rate that is not positive
+/// and finite disables pacing.
+///
+/// The returned stream is cancel-safe: dropping a pending `next()` (for
+/// example in `tokio::select!`) loses no chunks.
+///
+/// # Example
+/// ```no_run
+/// use futures::StreamExt;
+/// use simple_agents_providers::groq::GroqProvider;
+/// use simple_agents_providers::pacing::{paced, PaceRate};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example(request: CompletionRequest) -> Result<()> {
+/// let provider = GroqProvider::new(ApiKey::new("gsk-...")?)?;
+/// let stream = provider.execute_stream(provider.transform_request(&request)?).await?;
+///
+/// let mut stream = paced(stream, PaceRate::TokensPerSecond(60.0));
+/// while let Some(chunk) = stream.next().await {
+///     if let Some(text) = chunk?.choices.first().and_then(|c| c.delta.content.clone()) {
+///         print!("{}", text);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn paced<S>(stream: S, rate: PaceRate) -> PacedStream
+where
+    S: Stream<Item = Result<CompletionChunk>> + Send + 'static,
+{
+    let graphemes_per_second = rate.graphemes_per_second();
+    let state = State {
+        upstream: Box::pin(stream),
+        upstream_done: false,
+        pending: VecDeque::new(),
+        graphemes_per_second,
+        // Saturates to usize::MAX (no splitting) without pacing
+        piece_len: ((graphemes_per_second / FRAMES_PER_SECOND).ceil() as usize).max(1),
+        next_at: None,
+    };
+    let inner = futures::stream::unfold(state, |mut state| async move {
+        let item = state.next_item().await?;
+        Some((item, state))
+    });
+
+    PacedStream {
+        inner: Box::pin(inner),
+        rate,
+    }
+}
+
+/// Stream returned by [`paced`].
+pub struct PacedStream {
+    inner: ChunkStream,
+    rate: PaceRate,
+}
+
+impl PacedStream {
+    /// Rate the stream is paced at.
+    pub fn rate(&self) -> PaceRate {
+        self.rate
+    }
+}
+
+impl std::fmt::Debug for PacedStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PacedStream")
+            .field("rate", &self.rate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for PacedStream {
+    type Item = Result<CompletionChunk>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+struct State {
+    upstream: ChunkStream,
+    upstream_done: bool,
+    /// Items waiting to be emitted, with their length in grapheme clusters
+    pending: VecDeque<(Result<CompletionChunk>, usize)>,
+    graphemes_per_second: f64,
+    /// Maximum grapheme clusters per emitted piece
+    piece_len: usize,
+    /// Earliest time the next piece may be emitted
+    next_at: Option<Instant>,
+}
+
+impl State {
+    async fn next_item(&mut self) -> Option<Result<CompletionChunk>> {
+        loop {
+            if self.upstream_done {
+                return self.pending.pop_front().map(|(item, _)| item);
+            }
+            if self.pending.is_empty() {
+                let item = self.upstream.next().await;
+                self.receive(item);
+                continue;
+            }
+
+            // Never catch up on time spent waiting for the upstream
+            let now = Instant::now();
+            let due = self.next_at.map_or(now, |at| at.max(now));
+            if due > now {
+                tokio::select! {
+                    biased;
+                    item = self.upstream.next() => {
+                        self.receive(item);
+                        continue;
+                    }
+                    () = tokio::time::sleep_until(due) => {}
+                }
+            }
+
+            let (item, graphemes) = self.pending.pop_front()?;
+            self.next_at = Some(due + Duration::from_secs_f64(graphemes as f64 / self.graphemes_per_second));
+            return Some(item);
+        }
+    }
+
+    fn receive(&mut self, item: Option<Result<CompletionChunk>>) {
+        match item {
+            Some(Ok(chunk)) => {
+                let pieces = split(chunk, self.piece_len);
+                self.pending.extend(pieces.into_iter().map(|(piece, len)| (Ok(piece), len)));
+            }
+            Some(Err(error)) => {
+                self.pending.push_back((Err(error), 0));
+                self.upstream_done = true;
+            }
+            None => self.upstream_done = true,
+        }
+    }
+}
+
+/// Split `chunk` into single-choice chunks of at most `piece_len` grapheme
+/// clusters each, paired with their length.
+fn split(chunk: CompletionChunk, piece_len: usize) -> Vec<(CompletionChunk, usize)> {
+    if chunk.choices.is_empty() {
+        return vec![(chunk, 0)];
+    }
+
+    let CompletionChunk {
+        id,
+        model,
+        choices,
+        created,
+        usage,
+    } = chunk;
+    let mut pieces = Vec::new();
+    for delta in choices {
+        let graphemes: Vec<&str> = delta
+            .delta
+            .content
+            .as_deref()
+            .map(|content| content.graphemes(true).collect())
+            .unwrap_or_default();
+        if graphemes.len() <= piece_len {
+            pieces.push((delta.clone(), graphemes.len()));
+            continue;
+        }
+
+        let groups = graphemes.chunks(piece_len).collect::<Vec<_>>();
+        let last = groups.len() - 1;
+        for (i, group) in groups.into_iter().enumerate() {
+            let piece = ChoiceDelta {
+                index: delta.index,
+                delta: MessageDelta {
+                    role: if i == 0 { delta.delta.role } else { None },
+                    content: Some(group.concat()),
+                },
+                finish_reason: if i == last { delta.finish_reason } else { None },
+            };
+            pieces.push((piece, group.len()));
+        }
+    }
+
+    let last = pieces.len() - 1;
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(i, (delta, len))| {
+            let chunk = CompletionChunk {
+                id: id.clone(),
+                model: model.clone(),
+                choices: vec![delta],
+                created,
+                usage: if i == last { usage } else { None },
+            };
+            (chunk, len)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(deltas: &[(u32, &str)], finish_reason: Option<FinishReason>) -> CompletionChunk {
+        CompletionChunk {
+            id: "chatcmpl-1".to_string(),
+            model: "llama-3.1-8b-instant".to_string(),
+            choices: deltas
+                .iter()
+                .map(|(index, content)| ChoiceDelta {
+                    index: *index,
+                    delta: MessageDelta {
+                        role: None,
+                        content: Some(content.to_string()),
+                    },
+                    finish_reason,
+                })
+                .collect(),
+            created: None,
+            usage: None,
+        }
+    }
+
+    /// `items`, then an upstream that stays open for `open_for` before ending
+    fn upstream(
+        items: Vec<Result<CompletionChunk>>,
+        open_for: Duration,
+    ) -> impl Stream<Item = Result<CompletionChunk>> + Send + 'static {
+        let end = futures::stream::once(tokio::time::sleep(open_for)).filter_map(|()| async { None });
+        futures::stream::iter(items).chain(end)
+    }
+
+    /// Every item with the time since `start` it was emitted at
+    async fn collect_timed(mut stream: PacedStream) -> Vec<(Duration, Result<CompletionChunk>)> {
+        let start = Instant::now();
+        let mut items = Vec::new();
+        while let Some(item) = stream.next().await {
+            items.push((start.elapsed(), item));
+        }
+        items
+    }
+
+    fn text(item: &Result<CompletionChunk>) -> &str {
+        item.as_ref().unwrap().choices[0].delta.content.as_deref().unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_chars_per_second_cadence() {
+        let stream = upstream(vec![Ok(chunk(&[(0, "abcde")], None))], Duration::from_secs(10));
+        let items = collect_timed(paced(stream, PaceRate::CharsPerSecond(10.0))).await;
+
+        let texts: Vec<&str> = items.iter().map(|(_, item)| text(item)).collect();
+        assert_eq!(texts, ["a", "b", "c", "d", "e"]);
+        for (i, (at, _)) in items.iter().enumerate() {
+            assert_eq!(*at, Duration::from_millis(100) * i as u32);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tokens_per_second_cadence() {
+        // 60 tokens/s = 240 chars/s: four characters per 1/60 s frame
+        let stream = upstream(vec![Ok(chunk(&[(0, "abcdefghij")], None))], Duration::from_secs(10));
+        let items = collect_timed(paced(stream, PaceRate::TokensPerSecond(60.0))).await;
+
+        let texts: Vec<&str> = items.iter().map(|(_, item)| text(item)).collect();
+        assert_eq!(texts, ["abcd", "efgh", "ij"]);
+        let frame = Duration::from_secs_f64(4.0 / 240.0);
+        for (i, (at, _)) in items.iter().enumerate() {
+            let expected = frame * i as u32;
+            let tolerance = Duration::from_millis(1);
+            assert!(*at + tolerance >= expected && *at <= expected + tolerance, "{:?} vs {:?}", at, expected);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flushes_when_upstream_ends() {
+        let stream = upstream(
+            vec![Ok(chunk(&[(0, "abcdefghij")], Some(FinishReason::Stop)))],
+            Duration::from_millis(250),
+        );
+        let items = collect_timed(paced(stream, PaceRate::CharsPerSecond(10.0))).await;
+
+        assert_eq!(items.len(), 10);
+        assert_eq!(items[2].0, Duration::from_millis(200));
+        // Everything after the upstream ended is emitted at once
+        assert!(items[3..].iter().all(|(at, _)| *at == Duration::from_millis(250)));
+        let content: String = items.iter().map(|(_, item)| text(item)).collect();
+        assert_eq!(content, "abcdefghij");
+
+        let finish: Vec<_> = items
+            .iter()
+            .map(|(_, item)| item.as_ref().unwrap().choices[0].finish_reason)
+            .collect();
+        assert!(finish[..9].iter().all(Option::is_none));
+        assert_eq!(finish[9], Some(FinishReason::Stop));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_splits_at_grapheme_boundaries() {
+        // Skin-toned thumbs up, a family joined by zero-width joiners and an
+        // 'e' with a combining acute accent are one grapheme cluster each
+        let content = "👍🏽a👨‍👩‍👧‍👦e\u{301}!";
+        let stream = upstream(vec![Ok(chunk(&[(0, content)], None))], Duration::from_secs(10));
+        let items = collect_timed(paced(stream, PaceRate::CharsPerSecond(20.0))).await;
+
+        let texts: Vec<&str> = items.iter().map(|(_, item)| text(item)).collect();
+        assert_eq!(texts, ["👍🏽", "a", "👨‍👩‍👧‍👦", "e\u{301}", "!"]);
+        assert_eq!(items[4].0, Duration::from_millis(200));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keeps_order_across_choices_and_errors() {
+        let stream = upstream(
+            vec![
+                Ok(chunk(&[(0, "ab"), (1, "cd")], None)),
+                Ok(chunk(&[(0, "e")], None)),
+                Err(ProviderError::ServerError("connection reset".to_string()).into()),
+            ],
+            Duration::from_secs(10),
+        );
+        let items = collect_timed(paced(stream, PaceRate::CharsPerSecond(10.0))).await;
+
+        let emitted: Vec<(u32, &str)> = items[..5]
+            .iter()
+            .map(|(_, item)| (item.as_ref().unwrap().choices[0].index, text(item)))
+            .collect();
+        assert_eq!(emitted, [(0, "a"), (0, "b"), (1, "c"), (1, "d"), (0, "e")]);
+        // The error ends the upstream, so nothing waits on pacing
+        assert!(items.iter().all(|(at, _)| at.is_zero()));
+        assert_eq!(items.len(), 6);
+        assert!(items[5].1.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancelled_next_loses_nothing() {
+        let stream = upstream(vec![Ok(chunk(&[(0, "abcdef")], None))], Duration::from_secs(1));
+        let mut stream = paced(stream, PaceRate::CharsPerSecond(10.0));
+
+        let mut content = String::new();
+        let mut timeouts = 0;
+        loop {
+            tokio::select! {
+                item = stream.next() => match item {
+                    Some(item) => content.push_str(text(&item)),
+                    None => break,
+                },
+                () = tokio::time::sleep(Duration::from_millis(30)) => timeouts += 1,
+            }
+        }
+        assert_eq!(content, "abcdef");
+        assert!(timeouts > 0);
+    }
+}
@@ -0,0 +1,536 @@
+//! OpenAI-compatible HTTP proxy in front of any [`Provider`].
+//!
+//! [`OpenAIProxyServer`] accepts OpenAI Chat Completions requests, sends
+//! them to the configured provider and answers in OpenAI's format, so
+//! tools written against the OpenAI API can talk to Anthropic, Ollama or a
+//! wrapped provider (fallback, caching, ...) without changes.
+//!
+//! # Routes
+//!
+//! - `POST /v1/chat/completions`: completions, streamed as server-sent
+//!   events when the request sets `"stream": true`
+//! - `GET /v1/models`: the provider's [`supported_models`](Provider::supported_models)
+//!   plus configured aliases
+//!
+//! Errors are answered with OpenAI's `{"error": {...}}` body and a status
+//! chosen by the error's [`ErrorClass`].
+//!
+//! # Authentication
+//!
+//! **The proxy accepts every request unless
+//! [`ProxyConfig::with_api_key`] is set**, and anyone who can reach it
+//! spends the upstream provider's credentials. Without a key, bind it to
+//! a loopback address such as `127.0.0.1`. With a key, clients send it as
+//! `Authorization: Bearer <key>` (the OpenAI SDKs' `api_key`) and other
+//! requests are answered with `401 Unauthorized`.
+//!
+//! # Content parts
+//!
+//! Message `content` given as an array of parts is flattened: text parts
+//! are joined with newlines and `image_url` parts become
+//! [`Message::images`]. Other part types are rejected.
+//!
+//! # Example
+//! ```no_run
+//! use simple_agents_providers::anthropic::AnthropicProvider;
+//! use simple_agents_providers::proxy::{OpenAIProxyServer, ProxyConfig};
+//! use simple_agents_types::prelude::*;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<()> {
+//! let provider = AnthropicProvider::new(ApiKey::new("sk-ant-...")?)?;
+//! let config = ProxyConfig::new(Arc::new(provider))
+//!     .with_api_key(ApiKey::new("proxy-key-0123456789abcdef")?)
+//!     .with_model_alias("gpt-4o", "claude-3-5-sonnet-20241022")
+//!     .with_rate_limit(60, Duration::from_secs(60))
+//!     .with_logging(true);
+//!
+//! OpenAIProxyServer::new(config).run("127.0.0.1:8080".parse().unwrap()).await
+//! # }
+//! ```
+
+use crate::shutdown::CancellationToken;
+use axum::body::Bytes;
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use simple_agents_types::cache::CacheKey;
+use simple_agents_types::prelude::*;
+use simple_agents_types::user_facing::ErrorClass;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// Configuration for [`OpenAIProxyServer`].
+///
+/// Caching, rate limiting and logging are off unless enabled with the
+/// builder methods.
+#[derive(Clone)]
+pub struct ProxyConfig {
+    provider: Arc<dyn Provider>,
+    cache: Option<(Arc<dyn Cache>, Duration)>,
+    rate_limit: Option<(u32, Duration)>,
+    logging: bool,
+    model_aliases: HashMap<String, String>,
+    api_key: Option<ApiKey>,
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("provider", &self.provider.name())
+            .field("cache", &self.cache.as_ref().map(|(cache, ttl)| (cache.name(), ttl)))
+            .field("rate_limit", &self.rate_limit)
+            .field("logging", &self.logging)
+            .field("model_aliases", &self.model_aliases)
+            .field("api_key", &self.api_key.is_some())
+            .finish()
+    }
+}
+
+impl ProxyConfig {
+    /// Send every request to `provider`.
+    pub fn new(provider: Arc<dyn Provider>) -> Self {
+        Self {
+            provider,
+            cache: None,
+            rate_limit: None,
+            logging: false,
+            model_aliases: HashMap::new(),
+            api_key: None,
+        }
+    }
+
+    /// Require clients to send `Authorization: Bearer <api_key>`
+    /// (builder pattern).
+    ///
+    /// Requests without the key are answered with `401 Unauthorized`
+    /// before they reach the rate limiter or the provider.
+    pub fn with_api_key(mut self, api_key: ApiKey) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    /// Serve repeated non-streaming requests from `cache` for `ttl`
+    /// (builder pattern).
+    ///
+    /// Entries are keyed by [`CacheKey::from_request`] after alias
+    /// rewriting, in the `proxy` namespace. Cache failures are logged and
+    /// the request goes to the provider.
+    pub fn with_cache(mut self, cache: Arc<dyn Cache>, ttl: Duration) -> Self {
+        self.cache = Some((cache, ttl));
+        self
+    }
+
+    /// Accept at most `requests` requests per `per`, across all clients
+    /// (builder pattern).
+    ///
+    /// Requests are admitted from a token bucket holding `requests` tokens
+    /// that refills evenly over `per`. Requests over the limit are answered
+    /// with `429 Too Many Requests` and a `Retry-After` header.
+    pub fn with_rate_limit(mut self, requests: u32, per: Duration) -> Self {
+        self.rate_limit = Some((requests, per));
+        self
+    }
+
+    /// Log method, path, status and latency of every request at `info`
+    /// level (builder pattern).
+    pub fn with_logging(mut self, enabled: bool) -> Self {
+        self.logging = enabled;
+        self
+    }
+
+    /// Send requests for model `alias` to `model` (builder pattern).
+    pub fn with_model_alias(mut self, alias: impl Into<String>, model: impl Into<String>) -> Self {
+        self.model_aliases.insert(alias.into(), model.into());
+        self
+    }
+}
+
+/// HTTP server exposing a [`Provider`] through the OpenAI Chat Completions
+/// API.
+///
+/// See the [module documentation](self) for the routes and an example.
+#[derive(Debug, Clone)]
+pub struct OpenAIProxyServer {
+    config: ProxyConfig,
+}
+
+impl OpenAIProxyServer {
+    /// Create a server with `config`.
+    pub fn new(config: ProxyConfig) -> Self {
+        Self { config }
+    }
+
+    /// The server's routes and middleware, for embedding in another axum
+    /// application or driving directly in tests.
+    pub fn router(&self) -> Router {
+        let state = Arc::new(ProxyState {
+            provider: self.config.provider.clone(),
+            cache: self.config.cache.clone(),
+            model_aliases: self.config.model_aliases.clone(),
+        });
+        let mut router = Router::new()
+            .route("/v1/chat/completions", post(chat_completions))
+            .route("/v1/models", get(list_models))
+            .with_state(state);
+
+        if let Some((requests, per)) = self.config.rate_limit {
+            let limiter = Arc::new(RateLimiter::new(requests, per));
+            router = router.layer(middleware::from_fn_with_state(limiter, rate_limit));
+        }
+        if let Some(api_key) = &self.config.api_key {
+            router = router.layer(middleware::from_fn_with_state(Arc::new(api_key.clone()), authenticate));
+        }
+        // Outermost, so rejected requests are logged too
+        if self.config.logging {
+            router = router.layer(middleware::from_fn(log_request));
+        }
+        router
+    }
+
+    /// Serve on `addr` until the process exits.
+    ///
+    /// Returns [`SimpleAgentsError::Network`] if `addr` cannot be bound.
+    pub async fn run(self, addr: SocketAddr) -> Result<()> {
+        self.run_with_shutdown(addr, CancellationToken::new()).await
+    }
+
+    /// Serve on `addr` until `shutdown` is cancelled, then finish in-flight
+    /// requests and return.
+    pub async fn run_with_shutdown(self, addr: SocketAddr, shutdown: CancellationToken) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| SimpleAgentsError::Network(format!("Failed to bind proxy to {}: {}", addr, e)))?;
+        tracing::info!(%addr, provider = self.config.provider.name(), "Proxy listening");
+
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(async move { shutdown.cancelled().await })
+            .await
+            .map_err(|e| SimpleAgentsError::Network(format!("Proxy server failed: {}", e)))
+    }
+}
+
+struct ProxyState {
+    provider: Arc<dyn Provider>,
+    cache: Option<(Arc<dyn Cache>, Duration)>,
+    model_aliases: HashMap<String, String>,
+}
+
+async fn chat_completions(State(state): State<Arc<ProxyState>>, body: Bytes) -> Response {
+    let request = match parse_request(&body, &state.model_aliases) {
+        Ok(request) => request,
+        Err(error) => return error_response(&error),
+    };
+
+    if request.stream == Some(true) {
+        return match stream_completion(&state, &request).await {
+            Ok(response) => response,
+            Err(error) => error_response(&error),
+        };
+    }
+
+    let cache_key = CacheKey::from_request(state.provider.name(), &request);
+    let cache_key = CacheKey::with_namespace("proxy", &cache_key);
+    if let Some((cache, _)) = &state.cache {
+        match cache.get(&cache_key).await {
+            Ok(Some(body)) => return json_bytes_response(body),
+            Ok(None) => {}
+            Err(error) => tracing::warn!(%error, "Proxy cache lookup failed"),
+        }
+    }
+
+    let response = match state.provider.complete(&request).await {
+        Ok(response) => response,
+        Err(error) => return error_response(&error),
+    };
+    let body = serde_json::to_vec(&openai_response(&response)).unwrap_or_default();
+
+    if let Some((cache, ttl)) = &state.cache {
+        if let Err(error) = cache.set(&cache_key, body.clone(), *ttl).await {
+            tracing::warn!(%error, "Proxy cache store failed");
+        }
+    }
+    json_bytes_response(body)
+}
+
+async fn stream_completion(state: &ProxyState, request: &CompletionRequest) -> Result<Response> {
+    let provider_request = state.provider.transform_request(request)?;
+    let chunks = state.provider.execute_stream(provider_request).await?;
+
+    let events = chunks
+        .map(|chunk| match chunk {
+            Ok(chunk) => (openai_chunk(&chunk), true),
+            Err(error) => (error_body(ErrorClass::of(&error), &error.to_string()), false),
+        })
+        // Stop after the first error; it is the last event sent
+        .scan(true, |open, (data, ok)| {
+            let item = open.then_some(data);
+            *open = ok;
+            futures::future::ready(item)
+        })
+        .map(|data| Event::default().data(data.to_string()))
+        .chain(futures::stream::once(async { Event::default().data("[DONE]") }))
+        .map(Ok::<_, Infallible>);
+
+    Ok(Sse::new(events).into_response())
+}
+
+async fn list_models(State(state): State<Arc<ProxyState>>) -> Json<Value> {
+    let owner = state.provider.name();
+    let mut models: Vec<&str> = state.provider.supported_models().to_vec();
+    models.extend(state.model_aliases.keys().map(String::as_str));
+    models.sort_unstable();
+    models.dedup();
+
+    let data: Vec<Value> = models
+        .into_iter()
+        .map(|id| json!({"id": id, "object": "model", "created": 0, "owned_by": owner}))
+        .collect();
+    Json(json!({"object": "list", "data": data}))
+}
+
+/// Parse an OpenAI request body, flattening content parts and resolving
+/// model aliases.
+fn parse_request(body: &[u8], aliases: &HashMap<String, String>) -> Result<CompletionRequest> {
+    let invalid = |message: String| SimpleAgentsError::Validation(ValidationError::new(message));
+    let mut body: Value =
+        serde_json::from_slice(body).map_err(|e| invalid(format!("Request body is not valid JSON: {}", e)))?;
+    let object = body
+        .as_object_mut()
+        .ok_or_else(|| invalid("Request body must be a JSON object".to_string()))?;
+
+    // The newer name for max_tokens
+    if let Some(max_tokens) = object.remove("max_completion_tokens") {
+        object.entry("max_tokens").or_insert(max_tokens);
+    }
+    // OpenAI accepts a single stop sequence as a plain string
+    if let Some(stop) = object.get_mut("stop").filter(|stop| stop.is_string()) {
+        *stop = Value::Array(vec![stop.take()]);
+    }
+    if let Some(messages) = object.get_mut("messages").and_then(Value::as_array_mut) {
+        for message in messages {
+            flatten_content_parts(message).map_err(invalid)?;
+        }
+    }
+
+    let mut request: CompletionRequest =
+        serde_json::from_value(body).map_err(|e| invalid(format!("Invalid chat completion request: {}", e)))?;
+    if let Some(model) = aliases.get(&request.model) {
+        request.model = model.clone();
+    }
+    request.validate()?;
+    Ok(request)
+}
+
+/// Replace an array `content` with its text, moving images to `images`.
+fn flatten_content_parts(message: &mut Value) -> std::result::Result<(), String> {
+    let Some(parts) = message.get("content").and_then(Value::as_array) else {
+        return Ok(());
+    };
+
+    let mut text = Vec::new();
+    let mut images = Vec::new();
+    for part in parts {
+        match part.get("type").and_then(Value::as_str) {
+            Some("text") => text.push(part.get("text").and_then(Value::as_str).unwrap_or_default()),
+            Some("image_url") => match part.get("image_url") {
+                Some(Value::String(url)) => images.push(json!({"url": url})),
+                Some(image_url) => images.push(image_url.clone()),
+                None => return Err("image_url content part without an image_url".to_string()),
+            },
+            other => return Err(format!("Unsupported content part type {:?}", other.unwrap_or("<missing>"))),
+        }
+    }
+
+    let text = text.join("\n");
+    message["content"] = Value::String(text);
+    if !images.is_empty() {
+        message["images"] = Value::Array(images);
+    }
+    Ok(())
+}
+
+/// A response in OpenAI's `chat.completion` format.
+fn openai_response(response: &CompletionResponse) -> Value {
+    let choices: Vec<Value> = response
+        .choices
+        .iter()
+        .map(|choice| {
+            json!({
+                "index": choice.index,
+                "message": openai_message(&choice.message),
+                "finish_reason": choice.finish_reason,
+                "logprobs": choice.logprobs,
+            })
+        })
+        .collect();
+
+    json!({
+        "id": response.id,
+        "object": "chat.completion",
+        "created": response.created.unwrap_or_else(unix_now),
+        "model": response.model,
+        "choices": choices,
+        "usage": response.usage,
+    })
+}
+
+fn openai_message(message: &Message) -> Value {
+    // OpenAI sends null content alongside tool calls
+    let content = if message.content.is_empty() && !message.tool_calls.is_empty() {
+        Value::Null
+    } else {
+        Value::String(message.content.clone())
+    };
+    let mut value = json!({"role": message.role, "content": content});
+    if !message.tool_calls.is_empty() {
+        value["tool_calls"] = json!(message.tool_calls);
+    }
+    if let Some(refusal) = &message.refusal {
+        value["refusal"] = json!(refusal);
+    }
+    value
+}
+
+/// A chunk in OpenAI's `chat.completion.chunk` format.
+fn openai_chunk(chunk: &CompletionChunk) -> Value {
+    let mut value = json!({
+        "id": chunk.id,
+        "object": "chat.completion.chunk",
+        "created": chunk.created.unwrap_or_else(unix_now),
+        "model": chunk.model,
+        "choices": chunk.choices,
+    });
+    if let Some(usage) = &chunk.usage {
+        value["usage"] = json!(usage);
+    }
+    value
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn json_bytes_response(body: Vec<u8>) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// Status, OpenAI error `type` and `code` for an error class.
+fn error_kind(class: ErrorClass) -> (StatusCode, &'static str, &'static str) {
+    use ErrorClass::*;
+    match class {
+        RateLimited => (StatusCode::TOO_MANY_REQUESTS, "rate_limit_error", "rate_limited"),
+        Authentication => (StatusCode::UNAUTHORIZED, "authentication_error", "invalid_api_key"),
+        ModelUnavailable => (StatusCode::NOT_FOUND, "invalid_request_error", "model_not_found"),
+        Timeout => (StatusCode::GATEWAY_TIMEOUT, "server_error", "timeout"),
+        ServiceUnavailable => (StatusCode::SERVICE_UNAVAILABLE, "server_error", "service_unavailable"),
+        InvalidRequest => (StatusCode::BAD_REQUEST, "invalid_request_error", "invalid_request"),
+        PayloadTooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "invalid_request_error", "payload_too_large"),
+        Unsupported => (StatusCode::BAD_REQUEST, "invalid_request_error", "unsupported"),
+        InvalidResponse => (StatusCode::BAD_GATEWAY, "server_error", "invalid_upstream_response"),
+        Network => (StatusCode::BAD_GATEWAY, "server_error", "upstream_unreachable"),
+        Configuration => (StatusCode::INTERNAL_SERVER_ERROR, "server_error", "configuration_error"),
+        Internal => (StatusCode::INTERNAL_SERVER_ERROR, "server_error", "internal_error"),
+    }
+}
+
+/// OpenAI's error body.
+fn error_body(class: ErrorClass, message: &str) -> Value {
+    let (_, error_type, code) = error_kind(class);
+    json!({"error": {"message": message, "type": error_type, "code": code}})
+}
+
+fn error_response(error: &SimpleAgentsError) -> Response {
+    let class = ErrorClass::of(error);
+    (error_kind(class).0, Json(error_body(class, &error.to_string()))).into_response()
+}
+
+/// Token bucket shared by all requests.
+struct RateLimiter {
+    capacity: f64,
+    per_second: f64,
+    /// Available tokens and when they were counted
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(requests: u32, per: Duration) -> Self {
+        let capacity = f64::from(requests.max(1));
+        Self {
+            capacity,
+            per_second: capacity / per.as_secs_f64().max(f64::EPSILON),
+            bucket: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Take a token, or return how long until one is available.
+    fn try_acquire(&self) -> std::result::Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refilled = now.duration_since(bucket.1).as_secs_f64() * self.per_second;
+        let tokens = (bucket.0 + refilled).min(self.capacity);
+        if tokens >= 1.0 {
+            *bucket = (tokens - 1.0, now);
+            Ok(())
+        } else {
+            *bucket = (tokens, now);
+            Err(Duration::from_secs_f64((1.0 - tokens) / self.per_second))
+        }
+    }
+}
+
+async fn rate_limit(State(limiter): State<Arc<RateLimiter>>, request: Request, next: Next) -> Response {
+    match limiter.try_acquire() {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            let body = error_body(ErrorClass::RateLimited, "Proxy rate limit exceeded");
+            let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, retry_after.into());
+            response
+        }
+    }
+}
+
+async fn authenticate(State(api_key): State<Arc<ApiKey>>, request: Request, next: Next) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|key| ApiKey::new(key.trim()).ok());
+    if presented.as_ref() == Some(api_key.as_ref()) {
+        return next.run(request).await;
+    }
+    let body = error_body(ErrorClass::Authentication, "Missing or invalid proxy API key");
+    (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+}
+
+async fn log_request(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let started = Instant::now();
+    let response = next.run(request).await;
+    tracing::info!(
+        %method,
+        %path,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "Proxy request"
+    );
+    response
+}
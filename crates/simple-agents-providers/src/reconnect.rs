@@ -0,0 +1,600 @@
+//! Streams that reconnect when the connection drops mid-response.
+//!
+//! A stream that ends (or fails with a retryable error) before any chunk
+//! carried a finish reason is treated as dropped. [`ReconnectingStream`]
+//! re-issues the request and stitches the new chunks onto the ones already
+//! delivered, so the consumer sees one logical stream.
+
+use crate::retry::classify;
+use futures::{Stream, StreamExt};
+use simple_agents_types::prelude::*;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// User message sent after the partial reply when the provider cannot
+/// continue an assistant message directly
+pub const CONTINUE_PROMPT: &str =
+    "Your previous reply was cut off. Continue exactly where it stopped, without repeating anything.";
+
+type ChunkStream = Box<dyn Stream<Item = Result<CompletionChunk>> + Send + Unpin>;
+
+/// How a dropped stream is resumed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReconnectStrategy {
+    /// Ask the model to continue from the content received so far.
+    ///
+    /// The partial reply is sent as an assistant prefill when the provider
+    /// supports [`Feature::AssistantPrefill`], otherwise as an assistant
+    /// message followed by [`CONTINUE_PROMPT`].
+    #[default]
+    Continue,
+    /// Re-issue the original request and skip the content already delivered.
+    ///
+    /// Only stitches cleanly when the output is deterministic (e.g.
+    /// temperature 0 with a fixed seed).
+    Restart,
+}
+
+/// Limits and strategy for [`ReconnectingStream`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectConfig {
+    /// How to resume a dropped stream
+    pub strategy: ReconnectStrategy,
+    /// Maximum number of reconnects per stream
+    pub max_reconnects: u32,
+    /// No reconnect is attempted once this long has passed since the
+    /// stream was started
+    pub max_duration: Duration,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ReconnectStrategy::Continue,
+            max_reconnects: 3,
+            max_duration: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Completion stream that transparently reconnects after a dropped connection.
+///
+/// Only the first choice is tracked; requests with `n > 1` are resumed from
+/// the first choice's content. Role deltas are not repeated after a
+/// reconnect. Each reconnect logs a `warn` event and is counted in
+/// [`reconnects`](Self::reconnects).
+///
+/// # Example
+/// ```no_run
+/// use futures::StreamExt;
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_providers::reconnect::{ReconnectConfig, ReconnectingStream};
+/// use simple_agents_types::prelude::*;
+/// use std::sync::Arc;
+///
+/// # async fn example() -> Result<()> {
+/// let provider = Arc::new(OpenAIProvider::new(ApiKey::new("sk-...")?)?);
+/// let request = CompletionRequest::builder()
+///     .model("gpt-4o-mini")
+///     .message(Message::user("Tell me a story."))
+///     .build()?;
+///
+/// let mut stream = ReconnectingStream::new(provider, request, ReconnectConfig::default());
+/// while let Some(chunk) = stream.next().await {
+///     if let Some(text) = chunk?.choices.first().and_then(|c| c.delta.content.clone()) {
+///         print!("{}", text);
+///     }
+/// }
+/// println!("\n({} reconnects)", stream.reconnects());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReconnectingStream {
+    inner: Pin<Box<dyn Stream<Item = Result<CompletionChunk>> + Send>>,
+    reconnects: Arc<AtomicU32>,
+}
+
+impl ReconnectingStream {
+    /// Start streaming `request` from `provider`.
+    ///
+    /// The first request is sent when the stream is first polled. Errors
+    /// from that first request are returned as-is; only a stream that was
+    /// established and then dropped is reconnected.
+    pub fn new(provider: Arc<dyn Provider>, request: CompletionRequest, config: ReconnectConfig) -> Self {
+        let reconnects = Arc::new(AtomicU32::new(0));
+        let state = State {
+            provider,
+            request,
+            config,
+            started: Instant::now(),
+            current: None,
+            received: String::new(),
+            skip: 0,
+            trim_leading: false,
+            role_sent: false,
+            finished: false,
+            done: false,
+            reconnects: reconnects.clone(),
+        };
+        let inner = futures::stream::unfold(state, |mut state| async move {
+            let item = state.next_item().await?;
+            Some((item, state))
+        });
+
+        Self {
+            inner: Box::pin(inner),
+            reconnects,
+        }
+    }
+
+    /// Number of reconnects so far.
+    pub fn reconnects(&self) -> u32 {
+        self.reconnects.load(Ordering::SeqCst)
+    }
+}
+
+impl std::fmt::Debug for ReconnectingStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectingStream")
+            .field("reconnects", &self.reconnects())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for ReconnectingStream {
+    type Item = Result<CompletionChunk>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+struct State {
+    provider: Arc<dyn Provider>,
+    request: CompletionRequest,
+    config: ReconnectConfig,
+    started: Instant,
+    current: Option<ChunkStream>,
+    /// First-choice content delivered so far
+    received: String,
+    /// Bytes of regenerated content still to drop (restart strategy)
+    skip: usize,
+    /// Drop leading whitespace of continued content, which a prefill
+    /// (trimmed by providers) makes the model repeat
+    trim_leading: bool,
+    role_sent: bool,
+    /// A finish reason has been seen; the end of the stream is expected
+    finished: bool,
+    done: bool,
+    reconnects: Arc<AtomicU32>,
+}
+
+impl State {
+    async fn next_item(&mut self) -> Option<Result<CompletionChunk>> {
+        loop {
+            if self.done {
+                return None;
+            }
+
+            let stream = match self.current.as_mut() {
+                Some(stream) => stream,
+                None => match self.connect().await {
+                    Ok(stream) => self.current.insert(stream),
+                    Err(error) if self.reconnect_count() > 0 && self.can_reconnect(&error) => {
+                        self.reconnect(&error);
+                        continue;
+                    }
+                    Err(error) => return self.fail(error),
+                },
+            };
+
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    if let Some(chunk) = self.stitch(chunk) {
+                        return Some(Ok(chunk));
+                    }
+                }
+                Some(Err(error)) if !self.finished && self.can_reconnect(&error) => self.reconnect(&error),
+                Some(Err(error)) => return self.fail(error),
+                None if self.finished => {
+                    self.done = true;
+                    return None;
+                }
+                None => {
+                    let error = SimpleAgentsError::Network("Stream ended before a finish reason".to_string());
+                    if !self.can_reconnect(&error) {
+                        return self.fail(error);
+                    }
+                    self.reconnect(&error);
+                }
+            }
+        }
+    }
+
+    fn fail(&mut self, error: SimpleAgentsError) -> Option<Result<CompletionChunk>> {
+        self.done = true;
+        Some(Err(error))
+    }
+
+    fn reconnect_count(&self) -> u32 {
+        self.reconnects.load(Ordering::SeqCst)
+    }
+
+    fn can_reconnect(&self, error: &SimpleAgentsError) -> bool {
+        classify(error).is_retryable()
+            && self.reconnect_count() < self.config.max_reconnects
+            && self.started.elapsed() < self.config.max_duration
+    }
+
+    fn reconnect(&mut self, error: &SimpleAgentsError) {
+        let attempt = self.reconnects.fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::warn!(
+            provider = self.provider.name(),
+            attempt,
+            strategy = ?self.config.strategy,
+            received_bytes = self.received.len(),
+            error = %error,
+            "Stream dropped; reconnecting"
+        );
+        self.current = None;
+    }
+
+    async fn connect(&mut self) -> Result<ChunkStream> {
+        let resumed = !self.received.is_empty();
+        let mut request = self.request.clone();
+        self.skip = 0;
+        self.trim_leading = false;
+
+        match self.config.strategy {
+            ReconnectStrategy::Restart => self.skip = self.received.len(),
+            ReconnectStrategy::Continue if resumed => {
+                if self.provider.capabilities().supports(Feature::AssistantPrefill) {
+                    let mut text = request.assistant_prefill.take().map(|p| p.text).unwrap_or_default();
+                    text.push_str(&self.received);
+                    request.assistant_prefill = Some(AssistantPrefill {
+                        text,
+                        prepend_to_response: false,
+                    });
+                    self.trim_leading = self.received.ends_with(char::is_whitespace);
+                } else {
+                    request.messages.push(Message::assistant(self.received.clone()));
+                    request.messages.push(Message::user(CONTINUE_PROMPT));
+                }
+            }
+            ReconnectStrategy::Continue => {}
+        }
+
+        let provider_request = self.provider.transform_request(&request)?;
+        self.provider.execute_stream(provider_request).await
+    }
+
+    /// Adjust a chunk from the current connection to follow on from what
+    /// was already delivered, or drop it if nothing is left.
+    fn stitch(&mut self, mut chunk: CompletionChunk) -> Option<CompletionChunk> {
+        let mut trimmed = false;
+        for choice in &mut chunk.choices {
+            if choice.finish_reason.is_some() {
+                self.finished = true;
+            }
+            if choice.delta.role.is_some() {
+                if self.role_sent {
+                    choice.delta.role = None;
+                    trimmed = true;
+                }
+                self.role_sent = true;
+            }
+            if choice.index != 0 {
+                continue;
+            }
+
+            let Some(content) = choice.delta.content.take() else {
+                continue;
+            };
+            let mut rest = content.as_str();
+            if self.skip > 0 {
+                let mut end = self.skip.min(rest.len());
+                while !rest.is_char_boundary(end) {
+                    end -= 1;
+                }
+                self.skip -= end;
+                rest = &rest[end..];
+                trimmed = true;
+            }
+            if self.trim_leading && !rest.is_empty() {
+                rest = rest.trim_start();
+                self.trim_leading = rest.is_empty();
+                trimmed = true;
+            }
+            self.received.push_str(rest);
+            choice.delta.content = (!rest.is_empty()).then(|| rest.to_string());
+        }
+
+        let empty = chunk.usage.is_none()
+            && chunk.choices.iter().all(|c| {
+                c.delta.role.is_none() && c.delta.content.is_none() && c.finish_reason.is_none()
+            });
+        if trimmed && empty {
+            return None;
+        }
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anthropic::AnthropicProvider;
+    use crate::groq::GroqProvider;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// One scripted response: the SSE body and, if set, the byte offset at
+    /// which the connection is cut
+    type Script = Vec<(String, Option<usize>)>;
+
+    /// Serve one scripted response per connection, recording request bodies.
+    async fn sse_server(script: Script) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            for (body, cut_at) in script {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request_body(&mut socket).await;
+                recorded.lock().unwrap().push(request);
+
+                let sent = &body.as_bytes()[..cut_at.unwrap_or(body.len())];
+                let mut response = b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                    transfer-encoding: chunked\r\nconnection: close\r\n\r\n"
+                    .to_vec();
+                response.extend_from_slice(format!("{:x}\r\n", sent.len()).as_bytes());
+                response.extend_from_slice(sent);
+                response.extend_from_slice(b"\r\n");
+                if cut_at.is_none() {
+                    response.extend_from_slice(b"0\r\n\r\n");
+                }
+                socket.write_all(&response).await.unwrap();
+                // Dropping the socket without the final chunk cuts the stream
+            }
+        });
+
+        (url, requests)
+    }
+
+    async fn read_request_body(socket: &mut tokio::net::TcpStream) -> serde_json::Value {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buffer.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buffer);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let length: usize = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse().unwrap())
+                    })
+                    .unwrap_or(0);
+                if buffer.len() >= header_end + 4 + length {
+                    return serde_json::from_slice(&buffer[header_end + 4..header_end + 4 + length]).unwrap();
+                }
+            }
+            assert!(n > 0, "connection closed before the request was read");
+        }
+    }
+
+    fn openai_event(content: &str, finish_reason: Option<&str>) -> String {
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1700000000,
+            "model": "llama-3.1-8b-instant",
+            "choices": [{
+                "index": 0,
+                "delta": {"role": "assistant", "content": content},
+                "finish_reason": finish_reason
+            }]
+        });
+        format!("data: {}\n\n", chunk)
+    }
+
+    fn anthropic_event(data: serde_json::Value) -> String {
+        format!("event: {}\ndata: {}\n\n", data["type"].as_str().unwrap(), data)
+    }
+
+    fn anthropic_body(texts: &[&str], stop: bool) -> String {
+        let mut body = anthropic_event(serde_json::json!({
+            "type": "message_start",
+            "message": {"id": "msg_1", "type": "message", "role": "assistant", "content": [],
+                "model": "claude-3-5-sonnet-20241022", "stop_reason": null, "stop_sequence": null,
+                "usage": {"input_tokens": 10, "output_tokens": 1}}
+        }));
+        body.push_str(&anthropic_event(serde_json::json!({
+            "type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}
+        })));
+        for text in texts {
+            body.push_str(&anthropic_event(serde_json::json!({
+                "type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": text}
+            })));
+        }
+        if stop {
+            body.push_str(&anthropic_event(serde_json::json!({"type": "content_block_stop", "index": 0})));
+            body.push_str(&anthropic_event(serde_json::json!({
+                "type": "message_delta", "delta": {"stop_reason": "end_turn", "stop_sequence": null},
+                "usage": {"output_tokens": 5}
+            })));
+            body.push_str(&anthropic_event(serde_json::json!({"type": "message_stop"})));
+        }
+        body
+    }
+
+    /// Offset just inside the event after the first `events` events
+    fn cut_after_events(body: &str, events: usize) -> usize {
+        body.match_indices("\n\n").nth(events - 1).unwrap().0 + 2 + 10
+    }
+
+    fn request(model: &str) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model(model)
+            .message(Message::user("Say hello world."))
+            .build()
+            .unwrap()
+    }
+
+    async fn collect(stream: &mut ReconnectingStream) -> (String, Option<SimpleAgentsError>) {
+        let mut content = String::new();
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    if let Some(text) = chunk.choices.first().and_then(|c| c.delta.content.as_deref()) {
+                        content.push_str(text);
+                    }
+                }
+                Err(error) => return (content, Some(error)),
+            }
+        }
+        (content, None)
+    }
+
+    fn groq(url: String) -> Arc<dyn Provider> {
+        let api_key = ApiKey::new("gsk_test1234567890123456789012345678901234567890").unwrap();
+        Arc::new(GroqProvider::with_base_url(api_key, url).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_continue_with_prompt_after_cut() {
+        let first = [
+            openai_event("Hello", None),
+            openai_event(" wor", None),
+            openai_event("ld", None),
+        ]
+        .concat();
+        let cut = cut_after_events(&first, 2);
+        let second = [openai_event("ld", Some("stop")), "data: [DONE]\n\n".to_string()].concat();
+        let (url, requests) = sse_server(vec![(first, Some(cut)), (second, None)]).await;
+
+        let config = ReconnectConfig::default();
+        let mut stream = ReconnectingStream::new(groq(url), request("llama-3.1-8b-instant"), config);
+        let (content, error) = collect(&mut stream).await;
+
+        assert!(error.is_none(), "{:?}", error);
+        assert_eq!(content, "Hello world");
+        assert_eq!(stream.reconnects(), 1);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let messages = requests[1]["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "Hello wor");
+        assert_eq!(messages[2]["content"], CONTINUE_PROMPT);
+    }
+
+    #[tokio::test]
+    async fn test_continue_with_prefill_after_cut() {
+        let first = anthropic_body(&["Hello, ", "world"], false);
+        let cut = cut_after_events(&first, 3);
+        // The prefill is sent without its trailing space, so the model repeats it
+        let second = anthropic_body(&[" world", "!"], true);
+        let (url, requests) = sse_server(vec![(first, Some(cut)), (second, None)]).await;
+
+        let api_key = ApiKey::new("sk-ant-REDACTED").unwrap();
+        let provider = Arc::new(AnthropicProvider::with_base_url(api_key, url).unwrap());
+        let config = ReconnectConfig::default();
+        let mut stream = ReconnectingStream::new(provider, request("claude-3-5-sonnet-20241022"), config);
+        let (content, error) = collect(&mut stream).await;
+
+        assert!(error.is_none(), "{:?}", error);
+        assert_eq!(content, "Hello, world!");
+        assert_eq!(stream.reconnects(), 1);
+
+        let requests = requests.lock().unwrap();
+        let messages = requests[1]["messages"].as_array().unwrap();
+        assert_eq!(messages.last().unwrap()["role"], "assistant");
+        assert_eq!(messages.last().unwrap()["content"], "Hello,");
+    }
+
+    #[tokio::test]
+    async fn test_restart_skips_delivered_content() {
+        let first = [
+            openai_event("Hello", None),
+            openai_event(" wor", None),
+            openai_event("ld", None),
+        ]
+        .concat();
+        let cut = cut_after_events(&first, 2);
+        let second = [
+            openai_event("Hel", None),
+            openai_event("lo world", Some("stop")),
+            "data: [DONE]\n\n".to_string(),
+        ]
+        .concat();
+        let (url, requests) = sse_server(vec![(first, Some(cut)), (second, None)]).await;
+
+        let config = ReconnectConfig {
+            strategy: ReconnectStrategy::Restart,
+            ..Default::default()
+        };
+        let mut stream = ReconnectingStream::new(groq(url), request("llama-3.1-8b-instant"), config);
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunks.push(chunk.unwrap());
+        }
+
+        let content: String = chunks
+            .iter()
+            .filter_map(|c| c.choices[0].delta.content.as_deref())
+            .collect();
+        assert_eq!(content, "Hello world");
+        // The fully skipped "Hel" chunk is dropped and roles are not repeated
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.iter().filter(|c| c.choices[0].delta.role.is_some()).count(), 1);
+        assert_eq!(chunks[2].choices[0].finish_reason, Some(FinishReason::Stop));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0]["messages"], requests[1]["messages"]);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_are_capped() {
+        let body = [openai_event("Hello", None), openai_event(" wor", None)].concat();
+        let cut = cut_after_events(&body, 1);
+        let (url, requests) = sse_server(vec![(body.clone(), Some(cut)), (body, Some(cut))]).await;
+
+        let config = ReconnectConfig {
+            max_reconnects: 1,
+            ..Default::default()
+        };
+        let mut stream = ReconnectingStream::new(groq(url), request("llama-3.1-8b-instant"), config);
+        let (content, error) = collect(&mut stream).await;
+
+        assert_eq!(content, "HelloHello");
+        assert!(matches!(error, Some(SimpleAgentsError::Network(_))), "{:?}", error);
+        assert_eq!(stream.reconnects(), 1);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_ending_without_finish_reason_reconnects() {
+        // A clean end of the body without a finish reason also counts as a drop
+        let first = openai_event("Hello", None);
+        let second = [openai_event(" world", Some("stop")), "data: [DONE]\n\n".to_string()].concat();
+        let (url, _) = sse_server(vec![(first, None), (second, None)]).await;
+
+        let config = ReconnectConfig::default();
+        let mut stream = ReconnectingStream::new(groq(url), request("llama-3.1-8b-instant"), config);
+        let (content, error) = collect(&mut stream).await;
+
+        assert!(error.is_none(), "{:?}", error);
+        assert_eq!(content, "Hello world");
+        assert_eq!(stream.reconnects(), 1);
+    }
+}
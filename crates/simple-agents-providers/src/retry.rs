@@ -1,15 +1,115 @@
 //! Retry logic with exponential backoff.
+//!
+//! Also home to [`classify`], the single place that decides whether an error
+//! is worth retrying, failing over, or surfacing. Built-in wrappers use it so
+//! their decisions cannot drift apart.
 
 use simple_agents_types::{
-    config::RetryConfig,
-    error::{Result, SimpleAgentsError},
+    config::{RetryConfig, RetryPolicy},
+    error::{ProviderError, Result, SimpleAgentsError},
 };
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How an error should be handled by retrying and failover wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Transient failure; retrying the same provider may succeed
+    RetryableSameProvider,
+    /// This provider cannot serve the request, but another one might
+    FailoverToNextProvider,
+    /// The request itself is at fault; retrying anywhere is pointless
+    Fatal,
+    /// Provider is throttling; retry after the hinted delay if present
+    RateLimited {
+        /// Server-requested delay before retrying
+        retry_after: Option<Duration>,
+    },
+}
+
+impl ErrorClass {
+    /// Whether retrying the same provider is worthwhile.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RetryableSameProvider | Self::RateLimited { .. })
+    }
+
+    /// Whether moving on to another provider is worthwhile.
+    pub fn allows_failover(&self) -> bool {
+        !matches!(self, Self::Fatal)
+    }
+}
+
+/// Override hook for [`classify`], e.g. for proxies with custom error semantics.
+pub type ClassifyFn = Arc<dyn Fn(&SimpleAgentsError) -> ErrorClass + Send + Sync>;
+
+/// Classify an error for retry and failover decisions.
+///
+/// # Example
+/// ```
+/// use simple_agents_providers::retry::{classify, ErrorClass};
+/// use simple_agents_types::error::{ProviderError, SimpleAgentsError};
+///
+/// let err = SimpleAgentsError::Provider(ProviderError::ServerError("503".to_string()));
+/// assert_eq!(classify(&err), ErrorClass::RetryableSameProvider);
+///
+/// let err = SimpleAgentsError::Provider(ProviderError::InvalidApiKey);
+/// assert_eq!(classify(&err), ErrorClass::FailoverToNextProvider);
+/// ```
+pub fn classify(error: &SimpleAgentsError) -> ErrorClass {
+    match error {
+        SimpleAgentsError::Provider(provider_error) => classify_provider_error(provider_error),
+        SimpleAgentsError::Network(_) => ErrorClass::RetryableSameProvider,
+        SimpleAgentsError::WithContext(ctx) => classify(&ctx.error),
+        SimpleAgentsError::Healing(_)
+        | SimpleAgentsError::Config(_)
+        | SimpleAgentsError::Validation(_)
+        | SimpleAgentsError::Cache(_)
+        | SimpleAgentsError::Routing(_)
+        | SimpleAgentsError::Serialization(_) => ErrorClass::Fatal,
+    }
+}
+
+fn classify_provider_error(error: &ProviderError) -> ErrorClass {
+    match error {
+        ProviderError::RateLimit { retry_after } => ErrorClass::RateLimited {
+            retry_after: *retry_after,
+        },
+        ProviderError::Timeout(_) | ProviderError::ServerError(_) => ErrorClass::RetryableSameProvider,
+        ProviderError::InvalidApiKey
+        | ProviderError::ModelNotFound(_)
+        | ProviderError::Unsupported { .. }
+        | ProviderError::InvalidResponse(_) => ErrorClass::FailoverToNextProvider,
+        ProviderError::BadRequest(_) | ProviderError::PayloadTooLarge(_) | ProviderError::Refusal(_) => {
+            ErrorClass::Fatal
+        }
+    }
+}
+
+/// A [`RetryPolicy`] that waits on the Tokio timer, for
+/// [`Provider::execute_with_retries`](simple_agents_types::provider::Provider::execute_with_retries).
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::retry;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example(provider: &dyn Provider, request: CompletionRequest) -> Result<()> {
+/// let policy = retry::policy(provider.retry_config());
+/// let response = provider.execute_with_retries(&request, &policy).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn policy(config: RetryConfig) -> RetryPolicy {
+    RetryPolicy::new(config, tokio::time::sleep)
+}
 
 /// Execute an operation with retry logic.
 ///
 /// This function will retry the operation according to the retry configuration,
-/// applying exponential backoff between attempts.
+/// applying exponential backoff between attempts. When a rate-limit error
+/// carries a `retry_after` hint, that delay is used if it is longer than the
+/// computed backoff.
 ///
 /// # Arguments
 /// - `config`: Retry configuration (max attempts, backoff, etc.)
@@ -41,6 +141,31 @@ pub async fn execute_with_retry<F, Fut, T>(
     error_is_retryable: impl Fn(&SimpleAgentsError) -> bool,
     operation: F,
 ) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let classifier = |e: &SimpleAgentsError| {
+        if !error_is_retryable(e) {
+            return ErrorClass::Fatal;
+        }
+        match classify(e) {
+            rate_limited @ ErrorClass::RateLimited { .. } => rate_limited,
+            _ => ErrorClass::RetryableSameProvider,
+        }
+    };
+
+    execute_with_classifier(config, classifier, operation).await
+}
+
+/// Execute an operation, retrying errors that `classifier` marks retryable.
+///
+/// Pass [`classify`] for the built-in policy, or a [`ClassifyFn`] override.
+pub async fn execute_with_classifier<F, Fut, T>(
+    config: &RetryConfig,
+    classifier: impl Fn(&SimpleAgentsError) -> ErrorClass,
+    operation: F,
+) -> Result<T>
 where
     F: Fn() -> Fut,
     Fut: Future<Output = Result<T>>,
@@ -52,7 +177,8 @@ where
             Ok(result) => return Ok(result),
             Err(e) => {
                 // Check if this error is retryable
-                if !error_is_retryable(&e) {
+                let class = classifier(&e);
+                if !class.is_retryable() {
                     return Err(e);
                 }
 
@@ -63,7 +189,12 @@ where
                 }
 
                 // Calculate backoff and sleep
-                let backoff = config.calculate_backoff(attempt);
+                let backoff = match class {
+                    ErrorClass::RateLimited {
+                        retry_after: Some(delay),
+                    } => delay.max(config.calculate_backoff(attempt)),
+                    _ => config.calculate_backoff(attempt),
+                };
                 tracing::debug!(
                     "Attempt {} failed, retrying after {:?}: {}",
                     attempt + 1,
@@ -83,9 +214,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use simple_agents_types::error::ProviderError;
     use std::sync::{Arc, Mutex};
-    use std::time::Duration;
 
     #[tokio::test]
     async fn test_retry_success_first_attempt() {
@@ -204,4 +333,185 @@ mod tests {
         // Should only attempt once for non-retryable errors
         assert_eq!(*attempt_count.lock().unwrap(), 1);
     }
+
+    #[tokio::test]
+    async fn test_retry_honors_retry_after() {
+        let config = RetryConfig {
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            backoff_multiplier: 1.0,
+            jitter: false,
+        };
+
+        let attempt_count = Arc::new(Mutex::new(0));
+        let attempt_count_clone = attempt_count.clone();
+        let started = std::time::Instant::now();
+
+        let result = execute_with_retry(
+            &config,
+            |_| true,
+            || {
+                let count = attempt_count_clone.clone();
+                async move {
+                    let mut attempts = count.lock().unwrap();
+                    *attempts += 1;
+
+                    if *attempts == 1 {
+                        Err(SimpleAgentsError::Provider(ProviderError::RateLimit {
+                            retry_after: Some(Duration::from_millis(50)),
+                        }))
+                    } else {
+                        Ok("success")
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "success");
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    /// Expected class for every `ProviderError` variant.
+    ///
+    /// The match has no wildcard arm, so adding a variant fails to compile
+    /// here until someone decides how it should be classified.
+    fn expected_provider_class(error: &ProviderError) -> ErrorClass {
+        match error {
+            ProviderError::RateLimit { retry_after } => ErrorClass::RateLimited {
+                retry_after: *retry_after,
+            },
+            ProviderError::InvalidApiKey => ErrorClass::FailoverToNextProvider,
+            ProviderError::ModelNotFound(_) => ErrorClass::FailoverToNextProvider,
+            ProviderError::Timeout(_) => ErrorClass::RetryableSameProvider,
+            ProviderError::ServerError(_) => ErrorClass::RetryableSameProvider,
+            ProviderError::BadRequest(_) => ErrorClass::Fatal,
+            ProviderError::PayloadTooLarge(_) => ErrorClass::Fatal,
+            ProviderError::Unsupported { .. } => ErrorClass::FailoverToNextProvider,
+            ProviderError::InvalidResponse(_) => ErrorClass::FailoverToNextProvider,
+            ProviderError::Refusal(_) => ErrorClass::Fatal,
+        }
+    }
+
+    /// Expected class for every `SimpleAgentsError` variant (no wildcard arm).
+    fn expected_class(error: &SimpleAgentsError) -> ErrorClass {
+        match error {
+            SimpleAgentsError::Provider(e) => expected_provider_class(e),
+            SimpleAgentsError::Network(_) => ErrorClass::RetryableSameProvider,
+            SimpleAgentsError::Healing(_) => ErrorClass::Fatal,
+            SimpleAgentsError::Config(_) => ErrorClass::Fatal,
+            SimpleAgentsError::Validation(_) => ErrorClass::Fatal,
+            SimpleAgentsError::Cache(_) => ErrorClass::Fatal,
+            SimpleAgentsError::Routing(_) => ErrorClass::Fatal,
+            SimpleAgentsError::Serialization(_) => ErrorClass::Fatal,
+            SimpleAgentsError::WithContext(ctx) => expected_class(&ctx.error),
+        }
+    }
+
+    #[test]
+    fn test_classify_every_variant() {
+        let provider_errors = vec![
+            ProviderError::RateLimit { retry_after: None },
+            ProviderError::RateLimit {
+                retry_after: Some(Duration::from_secs(5)),
+            },
+            ProviderError::InvalidApiKey,
+            ProviderError::ModelNotFound("gpt-5".to_string()),
+            ProviderError::Timeout(Duration::from_secs(30)),
+            ProviderError::ServerError("500".to_string()),
+            ProviderError::BadRequest("bad".to_string()),
+            ProviderError::PayloadTooLarge("too large".to_string()),
+            ProviderError::Unsupported {
+                provider: "mock".to_string(),
+                feature: simple_agents_types::config::Feature::Streaming,
+            },
+            ProviderError::InvalidResponse("garbage".to_string()),
+            ProviderError::Refusal("I can't help with that".to_string()),
+        ];
+
+        let mut errors: Vec<SimpleAgentsError> = provider_errors
+            .into_iter()
+            .map(SimpleAgentsError::Provider)
+            .collect();
+        errors.extend([
+            SimpleAgentsError::Network("connection reset".to_string()),
+            SimpleAgentsError::Healing(simple_agents_types::error::HealingError::MaxAttemptsExceeded(3)),
+            SimpleAgentsError::Config("bad".to_string()),
+            SimpleAgentsError::Validation(simple_agents_types::error::ValidationError::Empty {
+                field: "model".to_string(),
+            }),
+            SimpleAgentsError::Cache("full".to_string()),
+            SimpleAgentsError::Routing("no providers".to_string()),
+            SimpleAgentsError::Serialization(serde_json::from_str::<u32>("x").unwrap_err()),
+            SimpleAgentsError::WithContext(Box::new(
+                SimpleAgentsError::Provider(ProviderError::Timeout(Duration::from_secs(1)))
+                    .with_context(simple_agents_types::error::ErrorContext::new().provider("mock")),
+            )),
+        ]);
+
+        for error in &errors {
+            assert_eq!(classify(error), expected_class(error), "{:?}", error);
+            assert_eq!(classify(error).is_retryable(), error.is_retryable(), "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_classify_agrees_with_is_retryable() {
+        for error in [
+            ProviderError::RateLimit { retry_after: None },
+            ProviderError::InvalidApiKey,
+            ProviderError::Timeout(Duration::from_secs(1)),
+            ProviderError::ServerError("500".to_string()),
+            ProviderError::BadRequest("bad".to_string()),
+        ] {
+            let class = classify(&SimpleAgentsError::Provider(error.clone()));
+            assert_eq!(class.is_retryable(), error.is_retryable(), "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_error_class_failover() {
+        assert!(ErrorClass::RetryableSameProvider.allows_failover());
+        assert!(ErrorClass::FailoverToNextProvider.allows_failover());
+        assert!(ErrorClass::RateLimited { retry_after: None }.allows_failover());
+        assert!(!ErrorClass::Fatal.allows_failover());
+        assert!(!ErrorClass::FailoverToNextProvider.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_custom_classifier_override() {
+        let config = RetryConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            backoff_multiplier: 2.0,
+            jitter: false,
+        };
+
+        // A proxy that reports transient upstream failures as 400s
+        let classifier: ClassifyFn = Arc::new(|e| match e {
+            SimpleAgentsError::Provider(ProviderError::BadRequest(msg)) if msg.contains("upstream") => {
+                ErrorClass::RetryableSameProvider
+            }
+            other => classify(other),
+        });
+
+        let attempt_count = Arc::new(Mutex::new(0));
+        let attempt_count_clone = attempt_count.clone();
+
+        let result = execute_with_classifier(&config, classifier.as_ref(), || {
+            let count = attempt_count_clone.clone();
+            async move {
+                *count.lock().unwrap() += 1;
+                Err::<(), _>(SimpleAgentsError::Provider(ProviderError::BadRequest(
+                    "upstream unavailable".to_string(),
+                )))
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(*attempt_count.lock().unwrap(), 3);
+    }
 }
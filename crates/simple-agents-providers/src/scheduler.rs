@@ -0,0 +1,810 @@
+//! Priority scheduling of requests sharing one provider.
+//!
+//! [`ScheduledProvider`] caps the number of concurrent upstream calls and
+//! decides which queued request gets the next free slot, so interactive
+//! traffic is not starved by background jobs on the same API key.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Scheduling class of a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// User-facing requests someone is waiting on
+    Interactive,
+    /// Everything else
+    #[default]
+    Normal,
+    /// Bulk work that can wait
+    Background,
+}
+
+impl Priority {
+    /// All classes, highest priority first
+    pub const ALL: [Priority; 3] = [Priority::Interactive, Priority::Normal, Priority::Background];
+
+    fn rank(self) -> usize {
+        match self {
+            Priority::Interactive => 0,
+            Priority::Normal => 1,
+            Priority::Background => 2,
+        }
+    }
+}
+
+/// One value per [`Priority`] class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerClass<T> {
+    /// Value for [`Priority::Interactive`]
+    pub interactive: T,
+    /// Value for [`Priority::Normal`]
+    pub normal: T,
+    /// Value for [`Priority::Background`]
+    pub background: T,
+}
+
+impl<T> PerClass<T> {
+    /// Value for `priority`.
+    pub fn get(&self, priority: Priority) -> &T {
+        match priority {
+            Priority::Interactive => &self.interactive,
+            Priority::Normal => &self.normal,
+            Priority::Background => &self.background,
+        }
+    }
+
+    /// Mutable value for `priority`.
+    pub fn get_mut(&mut self, priority: Priority) -> &mut T {
+        match priority {
+            Priority::Interactive => &mut self.interactive,
+            Priority::Normal => &mut self.normal,
+            Priority::Background => &mut self.background,
+        }
+    }
+}
+
+/// How the next queued request is chosen when a slot frees up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DispatchPolicy {
+    /// Always serve the highest waiting class first
+    #[default]
+    StrictPriority,
+    /// Share slots between waiting classes in proportion to their weights
+    /// (smooth weighted round-robin)
+    WeightedFair(PerClass<u32>),
+}
+
+/// Tuning for [`ScheduledProvider`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulerConfig {
+    /// Upstream calls allowed in flight at once
+    pub max_concurrent: usize,
+    /// Choice of the next request to dispatch
+    pub policy: DispatchPolicy,
+    /// Requests each class may have queued before new ones are rejected
+    pub queue_limits: PerClass<usize>,
+    /// Promote a queued request by one class for every `aging` it waits;
+    /// `None` disables starvation protection
+    pub aging: Option<Duration>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            policy: DispatchPolicy::StrictPriority,
+            queue_limits: PerClass {
+                interactive: 256,
+                normal: 256,
+                background: 1024,
+            },
+            aging: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// Counters for one priority class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassMetrics {
+    /// Requests currently waiting for a slot
+    pub queued: usize,
+    /// Requests that were given a slot
+    pub dispatched: u64,
+    /// Requests rejected because the class queue was full
+    pub rejected: u64,
+    /// Summed time dispatched requests spent queued
+    pub total_wait: Duration,
+    /// Longest time a dispatched request spent queued
+    pub max_wait: Duration,
+}
+
+impl ClassMetrics {
+    /// Average time dispatched requests spent queued.
+    pub fn mean_wait(&self) -> Duration {
+        match u32::try_from(self.dispatched) {
+            Ok(0) => Duration::ZERO,
+            Ok(dispatched) => self.total_wait / dispatched,
+            Err(_) => Duration::from_secs_f64(self.total_wait.as_secs_f64() / self.dispatched as f64),
+        }
+    }
+}
+
+/// Snapshot of the scheduler's queues and counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerMetrics {
+    /// Upstream calls currently running
+    pub in_flight: usize,
+    /// Counters per class
+    pub classes: PerClass<ClassMetrics>,
+}
+
+/// Chooses the priority of requests sent through [`Provider::complete`].
+pub type PriorityFn = Arc<dyn Fn(&CompletionRequest) -> Priority + Send + Sync>;
+
+/// Provider wrapper that queues requests by priority.
+///
+/// At most [`max_concurrent`](SchedulerConfig::max_concurrent) calls run
+/// against the inner provider; the rest wait in per-class queues and are
+/// dispatched according to the [`DispatchPolicy`]. With
+/// [`aging`](SchedulerConfig::aging) set, a request that has waited long
+/// enough is treated as the next class up, so background work still makes
+/// progress under sustained interactive load.
+///
+/// [`Provider::complete`] classifies requests with the function given to
+/// [`with_priority_fn`](Self::with_priority_fn) (everything is
+/// [`Priority::Normal`] by default); use
+/// [`complete_with_priority`](Self::complete_with_priority) to choose
+/// explicitly. A request whose class queue is full fails immediately with
+/// [`ProviderError::RateLimit`]. The lower-level hooks, `execute_stream`
+/// included, delegate to the inner provider and are not scheduled.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_providers::scheduler::{Priority, ScheduledProvider, SchedulerConfig};
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// let provider = ScheduledProvider::new(
+///     OpenAIProvider::new(ApiKey::new("sk-...")?)?,
+///     SchedulerConfig {
+///         max_concurrent: 8,
+///         ..Default::default()
+///     },
+/// );
+///
+/// let request = CompletionRequest::builder()
+///     .model("gpt-4o-mini")
+///     .message(Message::user("Hello!"))
+///     .build()?;
+/// provider.complete_with_priority(&request, Priority::Interactive).await?;
+///
+/// let waiting = provider.metrics().classes.background.queued;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ScheduledProvider<P> {
+    inner: P,
+    priority_fn: Option<PriorityFn>,
+    shared: Arc<Shared>,
+}
+
+impl<P: Provider> ScheduledProvider<P> {
+    /// Wrap `inner` with an empty queue.
+    pub fn new(inner: P, config: SchedulerConfig) -> Self {
+        Self {
+            inner,
+            priority_fn: None,
+            shared: Arc::new(Shared {
+                config,
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+
+    /// Classify requests sent through [`Provider::complete`].
+    pub fn with_priority_fn(mut self, priority_fn: PriorityFn) -> Self {
+        self.priority_fn = Some(priority_fn);
+        self
+    }
+
+    /// The active configuration.
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.shared.config
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Priority [`Provider::complete`] would use for `req`.
+    pub fn priority(&self, req: &CompletionRequest) -> Priority {
+        match &self.priority_fn {
+            Some(priority_fn) => priority_fn(req),
+            None => Priority::Normal,
+        }
+    }
+
+    /// Snapshot of queue depths and wait times.
+    pub fn metrics(&self) -> SchedulerMetrics {
+        let mut state = self.shared.lock();
+        state.prune();
+        let mut metrics = SchedulerMetrics {
+            in_flight: state.in_flight,
+            classes: state.metrics,
+        };
+        for waiter in &state.waiters {
+            metrics.classes.get_mut(waiter.priority).queued += 1;
+        }
+        metrics
+    }
+
+    /// Complete `req` once a slot is available to `priority`.
+    pub async fn complete_with_priority(
+        &self,
+        req: &CompletionRequest,
+        priority: Priority,
+    ) -> Result<CompletionResponse> {
+        let _slot = self.shared.acquire(priority).await?;
+        self.inner.complete(req).await
+    }
+}
+
+/// State shared with the slots handed to running requests
+struct Shared {
+    config: SchedulerConfig,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    in_flight: usize,
+    waiters: Vec<Waiter>,
+    next_seq: u64,
+    credits: [i64; 3],
+    metrics: PerClass<ClassMetrics>,
+}
+
+struct Waiter {
+    seq: u64,
+    priority: Priority,
+    enqueued: Instant,
+    tx: oneshot::Sender<Slot>,
+}
+
+/// A claim on one upstream slot, released on drop
+struct Slot {
+    shared: Arc<Shared>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.shared.release();
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn max_concurrent(&self) -> usize {
+        self.config.max_concurrent.max(1)
+    }
+
+    async fn acquire(self: &Arc<Self>, priority: Priority) -> Result<Slot> {
+        let rx = {
+            let mut state = self.lock();
+            state.prune();
+            if state.waiters.is_empty() && state.in_flight < self.max_concurrent() {
+                state.in_flight += 1;
+                state.record_dispatch(priority, Duration::ZERO);
+                return Ok(Slot { shared: self.clone() });
+            }
+
+            let queued = state.waiters.iter().filter(|w| w.priority == priority).count();
+            if queued >= *self.config.queue_limits.get(priority) {
+                state.metrics.get_mut(priority).rejected += 1;
+                tracing::debug!(?priority, queued, "Scheduler queue full; rejecting request");
+                return Err(ProviderError::RateLimit { retry_after: None }.into());
+            }
+
+            let (tx, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiters.push(Waiter {
+                seq,
+                priority,
+                enqueued: Instant::now(),
+                tx,
+            });
+            rx
+        };
+
+        rx.await
+            .map_err(|_| SimpleAgentsError::Config("scheduler dropped a queued request".to_string()))
+    }
+
+    fn release(self: &Arc<Self>) {
+        let ready = {
+            let mut state = self.lock();
+            state.in_flight -= 1;
+            self.take_ready(&mut state)
+        };
+        // Sent outside the lock: a slot whose receiver is gone is dropped
+        // here and releases itself again
+        for (tx, slot) in ready {
+            let _ = tx.send(slot);
+        }
+    }
+
+    /// Claim slots for as many waiters as capacity allows.
+    fn take_ready(self: &Arc<Self>, state: &mut State) -> Vec<(oneshot::Sender<Slot>, Slot)> {
+        state.prune();
+        let mut ready = Vec::new();
+        let now = Instant::now();
+        while state.in_flight < self.max_concurrent() {
+            let Some(index) = self.next_waiter(state, now) else {
+                break;
+            };
+            let waiter = state.waiters.remove(index);
+            state.in_flight += 1;
+            state.record_dispatch(waiter.priority, now.saturating_duration_since(waiter.enqueued));
+            ready.push((waiter.tx, Slot { shared: self.clone() }));
+        }
+        ready
+    }
+
+    /// Class `waiter` competes in after aging.
+    fn effective_rank(&self, waiter: &Waiter, now: Instant) -> usize {
+        let promotions = match self.config.aging {
+            Some(aging) if !aging.is_zero() => {
+                (now.saturating_duration_since(waiter.enqueued).as_nanos() / aging.as_nanos()) as usize
+            }
+            _ => 0,
+        };
+        waiter.priority.rank().saturating_sub(promotions)
+    }
+
+    fn next_waiter(&self, state: &mut State, now: Instant) -> Option<usize> {
+        let ranks: Vec<usize> = state.waiters.iter().map(|w| self.effective_rank(w, now)).collect();
+        let rank = match self.config.policy {
+            DispatchPolicy::StrictPriority => *ranks.iter().min()?,
+            DispatchPolicy::WeightedFair(weights) => {
+                let mut waiting = [false; 3];
+                for &rank in &ranks {
+                    waiting[rank] = true;
+                }
+                let mut total = 0;
+                let mut best: Option<usize> = None;
+                for class in Priority::ALL {
+                    let rank = class.rank();
+                    if !waiting[rank] {
+                        continue;
+                    }
+                    let weight = i64::from(*weights.get(class));
+                    state.credits[rank] += weight;
+                    total += weight;
+                    let ahead = match best {
+                        Some(best) => state.credits[rank] > state.credits[best],
+                        None => true,
+                    };
+                    if ahead {
+                        best = Some(rank);
+                    }
+                }
+                let best = best?;
+                state.credits[best] -= total;
+                best
+            }
+        };
+
+        // Oldest request of the chosen class
+        state
+            .waiters
+            .iter()
+            .zip(&ranks)
+            .filter(|(_, &r)| r == rank)
+            .min_by_key(|(w, _)| w.seq)
+            .map(|(w, _)| w.seq)
+            .and_then(|seq| state.waiters.iter().position(|w| w.seq == seq))
+    }
+}
+
+impl State {
+    /// Forget waiters whose caller gave up.
+    fn prune(&mut self) {
+        self.waiters.retain(|w| !w.tx.is_closed());
+    }
+
+    fn record_dispatch(&mut self, priority: Priority, wait: Duration) {
+        let metrics = self.metrics.get_mut(priority);
+        metrics.dispatched += 1;
+        metrics.total_wait += wait;
+        metrics.max_wait = metrics.max_wait.max(wait);
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for ScheduledProvider<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        self.complete_with_priority(req, self.priority(req)).await
+    }
+
+    /// Hold one slot across every attempt, so retries are not queued
+    /// behind new requests.
+    async fn execute_with_attempts(
+        &self,
+        req: &CompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<CompleteOutcome> {
+        let _slot = self.shared.acquire(self.priority(req)).await?;
+        self.inner.execute_with_attempts(req, policy).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Semaphore;
+
+    /// Holds every call until the test opens the gate, then records the
+    /// order requests reached the upstream
+    struct GatedProvider {
+        gate: Semaphore,
+        order: Mutex<Vec<String>>,
+    }
+
+    impl GatedProvider {
+        fn new() -> Self {
+            Self {
+                gate: Semaphore::new(0),
+                order: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn order(&self) -> Vec<String> {
+            self.order.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Provider for GatedProvider {
+        fn name(&self) -> &str {
+            "gated"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "content": req.messages[0].content })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            self.gate.acquire().await.unwrap().forget();
+            let content = req.body["content"].as_str().unwrap().to_string();
+            self.order.lock().unwrap().push(content);
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: "test-model".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant(resp.body["content"].as_str().unwrap()),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+
+        async fn execute_stream(
+            &self,
+            req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            let chunk = CompletionChunk {
+                id: req.body["content"].as_str().unwrap().to_string(),
+                model: "test-model".to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    fn request(content: &str) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("test-model")
+            .message(Message::user(content))
+            .build()
+            .unwrap()
+    }
+
+    fn single_slot(
+        policy: DispatchPolicy,
+        aging: Option<Duration>,
+    ) -> Arc<ScheduledProvider<GatedProvider>> {
+        Arc::new(ScheduledProvider::new(
+            GatedProvider::new(),
+            SchedulerConfig {
+                max_concurrent: 1,
+                policy,
+                aging,
+                ..Default::default()
+            },
+        ))
+    }
+
+    fn spawn(
+        provider: &Arc<ScheduledProvider<GatedProvider>>,
+        content: &str,
+        priority: Priority,
+    ) -> tokio::task::JoinHandle<Result<CompletionResponse>> {
+        let provider = provider.clone();
+        let request = request(content);
+        tokio::spawn(async move { provider.complete_with_priority(&request, priority).await })
+    }
+
+    /// Wait until the scheduler's metrics satisfy `done`
+    async fn wait_for(provider: &ScheduledProvider<GatedProvider>, done: impl Fn(&SchedulerMetrics) -> bool) {
+        for _ in 0..200 {
+            if done(&provider.metrics()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        panic!("scheduler did not reach the expected state");
+    }
+
+    /// Wait until one request is running and `n` are queued behind it
+    async fn wait_queued(provider: &ScheduledProvider<GatedProvider>, n: usize) {
+        wait_for(provider, |m| {
+            let queued = Priority::ALL.iter().map(|&p| m.classes.get(p).queued).sum::<usize>();
+            m.in_flight == 1 && queued == n
+        })
+        .await;
+    }
+
+    /// Saturate one slot with "running", queue `queued` in order, then
+    /// release everything and return the upstream order
+    async fn run(
+        provider: &Arc<ScheduledProvider<GatedProvider>>,
+        queued: &[(&str, Priority)],
+        before_release: Duration,
+    ) -> Vec<String> {
+        let mut handles = vec![spawn(provider, "running", Priority::Background)];
+        wait_queued(provider, 0).await;
+        for (i, (content, priority)) in queued.iter().enumerate() {
+            handles.push(spawn(provider, content, *priority));
+            wait_queued(provider, i + 1).await;
+        }
+        tokio::time::sleep(before_release).await;
+
+        provider.inner().gate.add_permits(queued.len() + 1);
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        provider.inner().order()
+    }
+
+    #[tokio::test]
+    async fn test_interactive_overtakes_queued_background() {
+        let provider = single_slot(DispatchPolicy::StrictPriority, None);
+        let order = run(
+            &provider,
+            &[
+                ("bg-1", Priority::Background),
+                ("bg-2", Priority::Background),
+                ("normal", Priority::Normal),
+                ("chat-1", Priority::Interactive),
+                ("chat-2", Priority::Interactive),
+            ],
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(order, ["running", "chat-1", "chat-2", "normal", "bg-1", "bg-2"]);
+
+        let metrics = provider.metrics();
+        assert_eq!(metrics.in_flight, 0);
+        assert_eq!(metrics.classes.interactive.dispatched, 2);
+        assert_eq!(metrics.classes.background.dispatched, 3);
+        assert_eq!(metrics.classes.background.queued, 0);
+        assert!(metrics.classes.background.max_wait >= metrics.classes.interactive.max_wait);
+        assert!(metrics.classes.background.mean_wait() > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_fair_shares_slots() {
+        let weights = PerClass {
+            interactive: 2,
+            normal: 1,
+            background: 1,
+        };
+        let provider = single_slot(DispatchPolicy::WeightedFair(weights), None);
+        let order = run(
+            &provider,
+            &[
+                ("bg-1", Priority::Background),
+                ("bg-2", Priority::Background),
+                ("chat-1", Priority::Interactive),
+                ("chat-2", Priority::Interactive),
+                ("chat-3", Priority::Interactive),
+                ("chat-4", Priority::Interactive),
+            ],
+            Duration::ZERO,
+        )
+        .await;
+        // Two interactive requests per background one while both wait
+        assert_eq!(order, ["running", "chat-1", "bg-1", "chat-2", "chat-3", "bg-2", "chat-4"]);
+    }
+
+    #[tokio::test]
+    async fn test_aging_prevents_starvation() {
+        let provider = single_slot(DispatchPolicy::StrictPriority, Some(Duration::from_millis(20)));
+        let order = run(
+            &provider,
+            &[("bg", Priority::Background), ("chat", Priority::Interactive)],
+            Duration::from_millis(60),
+        )
+        .await;
+        // The background request waited two aging periods and now ties with
+        // (and, being older, beats) the interactive one
+        assert_eq!(order, ["running", "bg", "chat"]);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_rejects() {
+        let provider = Arc::new(ScheduledProvider::new(
+            GatedProvider::new(),
+            SchedulerConfig {
+                max_concurrent: 1,
+                queue_limits: PerClass {
+                    interactive: 1,
+                    normal: 1,
+                    background: 1,
+                },
+                ..Default::default()
+            },
+        ));
+        let running = spawn(&provider, "running", Priority::Background);
+        wait_queued(&provider, 0).await;
+        let queued = spawn(&provider, "bg-1", Priority::Background);
+        wait_queued(&provider, 1).await;
+
+        let err = provider
+            .complete_with_priority(&request("bg-2"), Priority::Background)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Provider(ProviderError::RateLimit { .. })));
+        assert_eq!(provider.metrics().classes.background.rejected, 1);
+
+        provider.inner().gate.add_permits(2);
+        running.await.unwrap().unwrap();
+        queued.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_priority_fn_and_cancelled_waiters() {
+        let provider = Arc::new(
+            ScheduledProvider::new(
+                GatedProvider::new(),
+                SchedulerConfig {
+                    max_concurrent: 1,
+                    ..Default::default()
+                },
+            )
+            .with_priority_fn(Arc::new(|req: &CompletionRequest| {
+                if req.messages[0].content.starts_with("chat") {
+                    Priority::Interactive
+                } else {
+                    Priority::Background
+                }
+            })),
+        );
+        assert_eq!(provider.priority(&request("chat")), Priority::Interactive);
+
+        let running = {
+            let provider = provider.clone();
+            tokio::spawn(async move { provider.complete(&request("running")).await })
+        };
+        wait_queued(&provider, 0).await;
+        let abandoned = {
+            let provider = provider.clone();
+            tokio::spawn(async move { provider.complete(&request("chat")).await })
+        };
+        wait_queued(&provider, 1).await;
+        assert_eq!(provider.metrics().classes.interactive.queued, 1);
+
+        abandoned.abort();
+        let _ = abandoned.await;
+        wait_for(&provider, |m| m.classes.interactive.queued == 0).await;
+
+        provider.inner().gate.add_permits(1);
+        running.await.unwrap().unwrap();
+        assert_eq!(provider.metrics().in_flight, 0);
+        assert_eq!(provider.inner().order(), ["running"]);
+    }
+    #[tokio::test]
+    async fn test_streams_through_inner() {
+        use futures::StreamExt;
+
+        let provider = single_slot(DispatchPolicy::StrictPriority, None);
+        let mut req = request("chat");
+        req.stream = Some(true);
+
+        let stream = provider.execute_stream(provider.transform_request(&req).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().id, "chat");
+        assert_eq!(provider.metrics().in_flight, 0);
+    }
+}
@@ -0,0 +1,208 @@
+//! Graceful shutdown of components that do background work.
+//!
+//! Components that run background tasks (such as
+//! [`BackgroundNotifier`](crate::notifier::BackgroundNotifier)) accept an
+//! optional [`CancellationToken`] at construction and implement [`Close`].
+//! [`ShutdownCoordinator`] hands out the token, keeps track of registered
+//! components and closes them all, each within a deadline.
+//!
+//! # Drop behavior
+//!
+//! Dropping a component never blocks. It signals its background task to
+//! stop; work already queued is finished on a best-effort basis while the
+//! runtime keeps running, and lost if the runtime shuts down first. Call
+//! [`Close::close`] (or [`ShutdownCoordinator::shutdown`]) to wait for
+//! pending work before exiting.
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// A component with background work to flush on shutdown.
+#[async_trait]
+pub trait Close: Send + Sync {
+    /// Finish pending work and stop background tasks.
+    ///
+    /// Returns once the component is idle, or once its own deadline has
+    /// passed. Calling it more than once is harmless.
+    async fn close(&self);
+}
+
+/// Outcome of [`ShutdownCoordinator::shutdown`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Components that closed within the deadline
+    pub closed: Vec<String>,
+    /// Components still busy when the deadline passed
+    pub timed_out: Vec<String>,
+}
+
+impl ShutdownReport {
+    /// Whether every component closed within the deadline.
+    pub fn is_clean(&self) -> bool {
+        self.timed_out.is_empty()
+    }
+}
+
+/// Closes registered components together on shutdown.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::notifier::BackgroundNotifier;
+/// use simple_agents_providers::shutdown::ShutdownCoordinator;
+/// use simple_agents_providers::webhook::WebhookNotifier;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// # async fn example() -> simple_agents_types::Result<()> {
+/// let coordinator = ShutdownCoordinator::new().with_deadline(Duration::from_secs(10));
+///
+/// let webhook = WebhookNotifier::new("https://ops.example.com/hooks/llm", "whsec_shared_secret")?;
+/// let notifier = Arc::new(BackgroundNotifier::with_shutdown(Arc::new(webhook), 1024, coordinator.token()));
+/// coordinator.register("webhook", notifier.clone());
+///
+/// // ... on SIGTERM:
+/// let report = coordinator.shutdown().await;
+/// assert!(report.is_clean());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShutdownCoordinator {
+    token: CancellationToken,
+    deadline: Duration,
+    components: Mutex<Vec<(String, Arc<dyn Close>)>>,
+}
+
+impl std::fmt::Debug for ShutdownCoordinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let components = self.components.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("ShutdownCoordinator")
+            .field("deadline", &self.deadline)
+            .field("components", &components.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .field("cancelled", &self.token.is_cancelled())
+            .finish()
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownCoordinator {
+    /// Time each component gets to close unless set with
+    /// [`with_deadline`](Self::with_deadline)
+    pub const DEFAULT_DEADLINE: Duration = Duration::from_secs(30);
+
+    /// Create a coordinator with no components.
+    pub fn new() -> Self {
+        Self {
+            token: CancellationToken::new(),
+            deadline: Self::DEFAULT_DEADLINE,
+            components: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Set how long each component gets to close (builder pattern).
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Token cancelled when shutdown starts, for components to watch.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Close `component` on shutdown; `name` identifies it in the report.
+    pub fn register(&self, name: impl Into<String>, component: Arc<dyn Close>) {
+        self.components
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((name.into(), component));
+    }
+
+    /// Cancel the token and close every registered component concurrently.
+    ///
+    /// Each component gets the deadline to close; one that takes longer is
+    /// abandoned and listed in [`ShutdownReport::timed_out`]. Components are
+    /// unregistered, so a second call closes nothing.
+    pub async fn shutdown(&self) -> ShutdownReport {
+        self.token.cancel();
+        let components = std::mem::take(&mut *self.components.lock().unwrap_or_else(|e| e.into_inner()));
+
+        let results = futures::future::join_all(components.into_iter().map(|(name, component)| async move {
+            let closed = tokio::time::timeout(self.deadline, component.close()).await.is_ok();
+            (name, closed)
+        }))
+        .await;
+
+        let mut report = ShutdownReport::default();
+        for (name, closed) in results {
+            if closed {
+                report.closed.push(name);
+            } else {
+                tracing::warn!(
+                    component = %name,
+                    deadline = ?self.deadline,
+                    "Component did not close in time"
+                );
+                report.timed_out.push(name);
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Component {
+        delay: Duration,
+        closed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl Close for Component {
+        async fn close(&self) {
+            tokio::time::sleep(self.delay).await;
+            self.closed.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn component(delay: Duration) -> Arc<Component> {
+        Arc::new(Component {
+            delay,
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_closes_all_and_reports_timeouts() {
+        let coordinator = ShutdownCoordinator::new().with_deadline(Duration::from_secs(5));
+        let token = coordinator.token();
+        let fast = component(Duration::from_secs(1));
+        let slow = component(Duration::from_secs(60));
+        coordinator.register("fast", fast.clone());
+        coordinator.register("slow", slow.clone());
+
+        let started = tokio::time::Instant::now();
+        let report = coordinator.shutdown().await;
+
+        assert!(token.is_cancelled());
+        assert_eq!(report.closed, vec!["fast".to_string()]);
+        assert_eq!(report.timed_out, vec!["slow".to_string()]);
+        assert!(!report.is_clean());
+        assert!(fast.closed.load(Ordering::SeqCst));
+        assert!(!slow.closed.load(Ordering::SeqCst));
+        // Components close concurrently, so the whole shutdown takes one deadline
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+
+        assert_eq!(coordinator.shutdown().await, ShutdownReport::default());
+    }
+}
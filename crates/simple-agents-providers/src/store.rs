@@ -0,0 +1,563 @@
+//! Content-addressable prompt/response store for reproducibility.
+//!
+//! Every request is identified by its [`CompletionRequest::fingerprint`].
+//! A [`ResponseStore`] keeps `(fingerprint → request, response)` pairs so
+//! runs can be replayed exactly, and [`ReplayFromStoreProvider`] serves
+//! stored responses before falling through to a real provider.
+//!
+//! # Example
+//! ```no_run
+//! use simple_agents_providers::openai::OpenAIProvider;
+//! use simple_agents_providers::store::{InMemoryResponseStore, ReplayFromStoreProvider};
+//! use simple_agents_types::prelude::*;
+//!
+//! # async fn example() -> Result<()> {
+//! let provider = OpenAIProvider::new(ApiKey::new("sk-...")?)?;
+//! let replay = ReplayFromStoreProvider::new(provider, InMemoryResponseStore::new());
+//!
+//! let request = CompletionRequest::builder()
+//!     .model("gpt-4")
+//!     .message(Message::user("Hello!"))
+//!     .build()?;
+//!
+//! // First call hits the API, the second is served from the store.
+//! let first = replay.complete(&request).await?;
+//! let second = replay.complete(&request).await?;
+//! assert_eq!(first, second);
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+/// A stored request/response pair with provenance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredExchange {
+    /// Fingerprint of `request`
+    pub fingerprint: String,
+    /// The request that produced `response`
+    pub request: CompletionRequest,
+    /// The response returned by the provider
+    pub response: CompletionResponse,
+    /// Unix timestamp (seconds) when the pair was stored
+    pub stored_at: u64,
+}
+
+impl StoredExchange {
+    /// Create a new exchange stamped with the current time.
+    pub fn new(
+        fingerprint: impl Into<String>,
+        request: CompletionRequest,
+        response: CompletionResponse,
+    ) -> Self {
+        let stored_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+
+        Self {
+            fingerprint: fingerprint.into(),
+            request,
+            response,
+            stored_at,
+        }
+    }
+}
+
+/// Storage backend for request/response pairs.
+#[async_trait]
+pub trait ResponseStore: Send + Sync {
+    /// Store a response under the given request fingerprint.
+    ///
+    /// Storing the same fingerprint twice replaces the earlier entry.
+    async fn put(
+        &self,
+        fingerprint: &str,
+        request: &CompletionRequest,
+        response: &CompletionResponse,
+    ) -> Result<()>;
+
+    /// Look up the exchange stored under a fingerprint.
+    async fn get(&self, fingerprint: &str) -> Result<Option<StoredExchange>>;
+
+    /// List every stored exchange, ordered by fingerprint.
+    async fn entries(&self) -> Result<Vec<StoredExchange>>;
+}
+
+/// Export every exchange in a store as portable JSONL (one exchange per line).
+pub async fn export_jsonl(store: &dyn ResponseStore) -> Result<String> {
+    let mut output = String::new();
+    for entry in store.entries().await? {
+        output.push_str(&serde_json::to_string(&entry)?);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Parse a JSONL dump produced by [`export_jsonl`].
+///
+/// Blank lines are ignored.
+pub fn import_jsonl(jsonl: &str) -> Result<Vec<StoredExchange>> {
+    jsonl
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(SimpleAgentsError::from))
+        .collect()
+}
+
+/// In-memory response store.
+#[derive(Debug, Default)]
+pub struct InMemoryResponseStore {
+    entries: RwLock<HashMap<String, StoredExchange>>,
+}
+
+impl InMemoryResponseStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a store pre-populated with exchanges (e.g. from [`import_jsonl`]).
+    pub fn from_exchanges(exchanges: Vec<StoredExchange>) -> Self {
+        let entries = exchanges
+            .into_iter()
+            .map(|entry| (entry.fingerprint.clone(), entry))
+            .collect();
+
+        Self {
+            entries: RwLock::new(entries),
+        }
+    }
+}
+
+#[async_trait]
+impl ResponseStore for InMemoryResponseStore {
+    async fn put(
+        &self,
+        fingerprint: &str,
+        request: &CompletionRequest,
+        response: &CompletionResponse,
+    ) -> Result<()> {
+        let entry = StoredExchange::new(fingerprint, request.clone(), response.clone());
+        self.entries
+            .write()
+            .await
+            .insert(fingerprint.to_string(), entry);
+        Ok(())
+    }
+
+    async fn get(&self, fingerprint: &str) -> Result<Option<StoredExchange>> {
+        Ok(self.entries.read().await.get(fingerprint).cloned())
+    }
+
+    async fn entries(&self) -> Result<Vec<StoredExchange>> {
+        let mut entries: Vec<_> = self.entries.read().await.values().cloned().collect();
+        entries.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+        Ok(entries)
+    }
+}
+
+#[cfg(feature = "file-store")]
+pub use file::FileResponseStore;
+
+#[cfg(feature = "file-store")]
+mod file {
+    use super::*;
+    use std::path::{Path, PathBuf};
+    use tokio::io::AsyncWriteExt;
+
+    /// Append-only JSONL file store.
+    ///
+    /// The file uses the same format as [`export_jsonl`], so it can be
+    /// shared or archived as-is. The full index is loaded into memory on
+    /// open; later lines win when a fingerprint appears more than once.
+    #[derive(Debug)]
+    pub struct FileResponseStore {
+        path: PathBuf,
+        index: InMemoryResponseStore,
+        writer: tokio::sync::Mutex<()>,
+    }
+
+    impl FileResponseStore {
+        /// Open (or create) a store backed by the file at `path`.
+        pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+            let path = path.as_ref().to_path_buf();
+
+            let exchanges = match tokio::fs::read_to_string(&path).await {
+                Ok(contents) => import_jsonl(&contents)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => {
+                    return Err(SimpleAgentsError::Cache(format!(
+                        "Failed to read response store {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            };
+
+            Ok(Self {
+                path,
+                index: InMemoryResponseStore::from_exchanges(exchanges),
+                writer: tokio::sync::Mutex::new(()),
+            })
+        }
+
+        /// Path of the backing file.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    #[async_trait]
+    impl ResponseStore for FileResponseStore {
+        async fn put(
+            &self,
+            fingerprint: &str,
+            request: &CompletionRequest,
+            response: &CompletionResponse,
+        ) -> Result<()> {
+            let entry = StoredExchange::new(fingerprint, request.clone(), response.clone());
+            let mut line = serde_json::to_string(&entry)?;
+            line.push('\n');
+
+            let io_error = |e: std::io::Error| {
+                SimpleAgentsError::Cache(format!(
+                    "Failed to write response store {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            };
+
+            let _guard = self.writer.lock().await;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await
+                .map_err(io_error)?;
+            file.write_all(line.as_bytes()).await.map_err(io_error)?;
+            file.flush().await.map_err(io_error)?;
+
+            self.index.put(fingerprint, request, response).await
+        }
+
+        async fn get(&self, fingerprint: &str) -> Result<Option<StoredExchange>> {
+            self.index.get(fingerprint).await
+        }
+
+        async fn entries(&self) -> Result<Vec<StoredExchange>> {
+            self.index.entries().await
+        }
+    }
+}
+
+/// Provider wrapper that replays stored responses.
+///
+/// [`Provider::complete`] looks up the request fingerprint in the store and
+/// returns the stored response when present; otherwise it calls the inner
+/// provider and records the result. The lower-level `transform_request`,
+/// `execute`, `transform_response` and `execute_stream` hooks delegate to
+/// the inner provider unchanged, so streamed responses are never stored.
+pub struct ReplayFromStoreProvider<P, S> {
+    inner: P,
+    store: S,
+}
+
+impl<P: Provider, S: ResponseStore> ReplayFromStoreProvider<P, S> {
+    /// Wrap `inner`, replaying from and recording into `store`.
+    pub fn new(inner: P, store: S) -> Self {
+        Self { inner, store }
+    }
+
+    /// Access the underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<P: Provider, S: ResponseStore> Provider for ReplayFromStoreProvider<P, S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        // Streamed responses are never stored
+        req.ensure_not_streaming()?;
+
+        let fingerprint = req.fingerprint();
+
+        if let Some(entry) = self.store.get(&fingerprint).await? {
+            tracing::debug!(fingerprint = %fingerprint, "Serving response from store");
+            return Ok(entry.response);
+        }
+
+        let response = self.inner.complete(req).await?;
+        self.store.put(&fingerprint, req, &response).await?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct CountingProvider {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "model": req.model })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ProviderResponse::new(
+                200,
+                serde_json::json!({ "model": req.body["model"], "call": call }),
+            ))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: format!("resp_{}", resp.body["call"]),
+                model: resp.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("stored"),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(3, 1),
+                created: None,
+                created_synthesized: false,
+                provider: Some("counting".to_string()),
+            })
+        }
+
+        async fn execute_stream(
+            &self,
+            req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: req.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    fn request(content: &str) -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("test-model")
+            .message(Message::user(content))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replay_serves_stored_response() {
+        let provider = ReplayFromStoreProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            InMemoryResponseStore::new(),
+        );
+
+        let first = provider.complete(&request("Hello")).await.unwrap();
+        let second = provider.complete(&request("Hello")).await.unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_falls_through_on_miss() {
+        let provider = ReplayFromStoreProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            InMemoryResponseStore::new(),
+        );
+
+        let first = provider.complete(&request("Hello")).await.unwrap();
+        let second = provider.complete(&request("Goodbye")).await.unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 2);
+        assert_eq!(provider.store().entries().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_store_records_provenance() {
+        let store = InMemoryResponseStore::new();
+        let provider = ReplayFromStoreProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            store,
+        );
+
+        let req = request("Hello");
+        provider.complete(&req).await.unwrap();
+
+        let entry = provider
+            .store()
+            .get(&req.fingerprint())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.request, req);
+        assert_eq!(entry.response.provider.as_deref(), Some("counting"));
+        assert!(entry.stored_at > 0);
+    }
+
+    #[tokio::test]
+    async fn test_streams_through_inner() {
+        use futures::StreamExt;
+
+        let provider = ReplayFromStoreProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            InMemoryResponseStore::new(),
+        );
+        let req = CompletionRequest::builder()
+            .model("test-model")
+            .message(Message::user("Hello"))
+            .stream(true)
+            .build()
+            .unwrap();
+
+        let stream = provider.execute_stream(provider.transform_request(&req).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().model, "test-model");
+        assert!(provider.store().entries().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let store = InMemoryResponseStore::new();
+        let provider = ReplayFromStoreProvider::new(
+            CountingProvider {
+                calls: AtomicU32::new(0),
+            },
+            store,
+        );
+        provider.complete(&request("one")).await.unwrap();
+        provider.complete(&request("two")).await.unwrap();
+
+        let jsonl = export_jsonl(provider.store()).await.unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+
+        let imported = import_jsonl(&jsonl).unwrap();
+        assert_eq!(imported, provider.store().entries().await.unwrap());
+    }
+
+    #[cfg(feature = "file-store")]
+    #[tokio::test]
+    async fn test_file_store_persists_across_opens() {
+        let path = std::env::temp_dir().join(format!(
+            "simple-agents-store-{}-{}.jsonl",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        let req = request("persist me");
+        let response = CountingProvider {
+            calls: AtomicU32::new(0),
+        }
+        .complete(&req)
+        .await
+        .unwrap();
+
+        {
+            let store = FileResponseStore::open(&path).await.unwrap();
+            store.put(&req.fingerprint(), &req, &response).await.unwrap();
+        }
+
+        let reopened = FileResponseStore::open(&path).await.unwrap();
+        let entry = reopened.get(&req.fingerprint()).await.unwrap().unwrap();
+        assert_eq!(entry.response, response);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
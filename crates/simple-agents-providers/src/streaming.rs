@@ -0,0 +1,459 @@
+//! Server-Sent Events (SSE) parsing shared by streaming providers, and
+//! latency measurement of completion streams with [`StreamMetrics`].
+
+use futures::{Stream, StreamExt};
+use simple_agents_types::error::{Result, SimpleAgentsError};
+use simple_agents_types::response::CompletionChunk;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A single Server-Sent Event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event name from the `event:` field, if present
+    pub event: Option<String>,
+    /// Event payload (multiple `data:` lines joined with `\n`)
+    pub data: String,
+    /// Event id from the `id:` field, if present
+    pub id: Option<String>,
+}
+
+/// Incremental SSE parser.
+///
+/// Feed raw bytes as they arrive; complete events are returned once their
+/// terminating blank line has been seen. Multi-byte UTF-8 sequences split
+/// across chunks are handled because parsing happens at the byte level.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Create a new parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of bytes, returning any events it completed.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some((end, separator_len)) = find_event_boundary(&self.buffer) {
+            let raw: Vec<u8> = self.buffer.drain(..end + separator_len).take(end).collect();
+            if let Some(event) = parse_event(&raw) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Flush any trailing event that was not terminated by a blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let raw = std::mem::take(&mut self.buffer);
+        parse_event(&raw)
+    }
+}
+
+/// Find the first blank-line event separator, returning (event_end, separator_len).
+fn find_event_boundary(buffer: &[u8]) -> Option<(usize, usize)> {
+    (0..buffer.len()).find_map(|i| {
+        let rest = &buffer[i..];
+        if rest.starts_with(b"\r\n\r\n") {
+            Some((i, 4))
+        } else if rest.starts_with(b"\n\n") || rest.starts_with(b"\r\r") {
+            Some((i, 2))
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse the lines of one event. Returns `None` for comment-only or empty blocks.
+fn parse_event(raw: &[u8]) -> Option<SseEvent> {
+    let text = String::from_utf8_lossy(raw);
+    let mut event = SseEvent::default();
+    let mut data_lines = Vec::new();
+
+    for line in text.lines() {
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => event.event = Some(value.to_string()),
+            "data" => data_lines.push(value),
+            "id" => event.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    if data_lines.is_empty() && event.event.is_none() {
+        return None;
+    }
+
+    event.data = data_lines.join("\n");
+    Some(event)
+}
+
+/// Turn a byte stream (e.g. `reqwest::Response::bytes_stream`) into SSE events.
+///
+/// Transport errors are surfaced as [`SimpleAgentsError::Network`] and end
+/// the stream.
+pub fn sse_events<S, B, E>(bytes: S) -> impl Stream<Item = Result<SseEvent>> + Send
+where
+    S: Stream<Item = std::result::Result<B, E>> + Send + Unpin,
+    B: AsRef<[u8]> + Send,
+    E: std::fmt::Display + Send,
+{
+    let state = (bytes, SseParser::new(), VecDeque::new(), false);
+
+    futures::stream::unfold(state, |(mut bytes, mut parser, mut pending, mut done)| async move {
+        loop {
+            if let Some(event) = pending.pop_front() {
+                return Some((Ok(event), (bytes, parser, pending, done)));
+            }
+            if done {
+                return None;
+            }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => pending.extend(parser.feed(chunk.as_ref())),
+                Some(Err(e)) => {
+                    let error = SimpleAgentsError::Network(format!("Stream error: {}", e));
+                    return Some((Err(error), (bytes, parser, pending, true)));
+                }
+                None => {
+                    done = true;
+                    pending.extend(parser.finish());
+                }
+            }
+        }
+    })
+}
+
+/// Characters per token used to estimate throughput when the stream
+/// reports no usage
+const CHARS_PER_TOKEN: f64 = 4.0;
+
+/// Completion stream wrapper that measures latency and throughput.
+///
+/// Chunks pass through unchanged. Call [`finish`](Self::finish) once the
+/// stream has ended to get a [`StreamMetricsReport`].
+///
+/// Timing starts when the wrapper is created; use
+/// [`with_start`](Self::with_start) to include the time spent sending the
+/// request.
+///
+/// # Example
+/// ```no_run
+/// use futures::StreamExt;
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_providers::streaming::StreamMetrics;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example(provider: OpenAIProvider, request: CompletionRequest) -> Result<()> {
+/// let started = tokio::time::Instant::now();
+/// let stream = provider.execute_stream(provider.transform_request(&request)?).await?;
+///
+/// let mut stream = StreamMetrics::with_start(stream, started);
+/// while let Some(chunk) = stream.next().await {
+///     chunk?;
+/// }
+/// let report = stream.finish();
+/// println!("{:?} to first token, {:.1} tokens/s", report.time_to_first_token, report.tokens_per_second());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamMetrics<S> {
+    inner: S,
+    started: Instant,
+    first_token_at: Option<Instant>,
+    ended_at: Option<Instant>,
+    chunk_count: u32,
+    total_chars: u64,
+    completion_tokens: Option<u32>,
+}
+
+impl<S> StreamMetrics<S> {
+    /// Measure `stream` from now.
+    pub fn new(stream: S) -> Self {
+        Self::with_start(stream, Instant::now())
+    }
+
+    /// Measure `stream` from `started`, e.g. the moment the request was sent.
+    pub fn with_start(stream: S, started: Instant) -> Self {
+        Self {
+            inner: stream,
+            started,
+            first_token_at: None,
+            ended_at: None,
+            chunk_count: 0,
+            total_chars: 0,
+            completion_tokens: None,
+        }
+    }
+
+    /// Measurements so far.
+    ///
+    /// Durations end when the stream ended, or now if it has not.
+    pub fn finish(self) -> StreamMetricsReport {
+        let ended_at = self.ended_at.unwrap_or_else(Instant::now);
+        let total_duration = ended_at.duration_since(self.started);
+        StreamMetricsReport {
+            time_to_first_token: self
+                .first_token_at
+                .map_or(total_duration, |at| at.duration_since(self.started)),
+            total_duration,
+            chunk_count: self.chunk_count,
+            total_chars: self.total_chars,
+            completion_tokens: self.completion_tokens,
+        }
+    }
+}
+
+impl<S> Stream for StreamMetrics<S>
+where
+    S: Stream<Item = Result<CompletionChunk>> + Unpin,
+{
+    type Item = Result<CompletionChunk>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = std::task::ready!(self.inner.poll_next_unpin(cx));
+        let now = Instant::now();
+        match &item {
+            Some(Ok(chunk)) => {
+                self.chunk_count += 1;
+                let chars: usize = chunk
+                    .choices
+                    .iter()
+                    .filter_map(|choice| choice.delta.content.as_deref())
+                    .map(|content| content.chars().count())
+                    .sum();
+                if chars > 0 && self.first_token_at.is_none() {
+                    self.first_token_at = Some(now);
+                }
+                self.total_chars += chars as u64;
+                if let Some(usage) = &chunk.usage {
+                    self.completion_tokens = Some(usage.completion_tokens);
+                }
+            }
+            // The stream ends with its first error
+            Some(Err(_)) | None => {
+                self.ended_at.get_or_insert(now);
+            }
+        }
+        Poll::Ready(item)
+    }
+}
+
+/// Latency and throughput of one streamed completion, from
+/// [`StreamMetrics::finish`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamMetricsReport {
+    /// Time until the first chunk with content (the whole duration if
+    /// none had content)
+    pub time_to_first_token: Duration,
+    /// Time until the stream ended
+    pub total_duration: Duration,
+    /// Number of chunks received
+    pub chunk_count: u32,
+    /// Characters of content received, across all choices
+    pub total_chars: u64,
+    /// Completion tokens from the stream's usage chunk, if it sent one
+    pub completion_tokens: Option<u32>,
+}
+
+impl StreamMetricsReport {
+    /// Generation speed after the first token.
+    ///
+    /// Uses [`completion_tokens`](Self::completion_tokens) when reported,
+    /// otherwise estimates four characters per token. Returns 0 when no
+    /// time passed after the first token.
+    pub fn tokens_per_second(&self) -> f64 {
+        let generation = self.total_duration.saturating_sub(self.time_to_first_token);
+        if generation.is_zero() {
+            return 0.0;
+        }
+        let tokens = self
+            .completion_tokens
+            .map_or(self.total_chars as f64 / CHARS_PER_TOKEN, f64::from);
+        tokens / generation.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_event() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"event: ping\ndata: {\"type\": \"ping\"}\n\n");
+
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some("ping".to_string()),
+                data: "{\"type\": \"ping\"}".to_string(),
+                id: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_split_across_chunks() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: hel").is_empty());
+        assert!(parser.feed(b"lo\n").is_empty());
+        let events = parser.feed(b"\ndata: world\n\n");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data, "hello");
+        assert_eq!(events[1].data, "world");
+    }
+
+    #[test]
+    fn test_parse_crlf_and_comments() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b": keep-alive\r\n\r\nid: 7\r\ndata: a\r\ndata: b\r\n\r\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "a\nb");
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn test_utf8_split_across_chunks() {
+        let bytes = "data: héllo\n\n".as_bytes();
+        let split = bytes.iter().position(|&b| b == 0xC3).unwrap() + 1;
+
+        let mut parser = SseParser::new();
+        assert!(parser.feed(&bytes[..split]).is_empty());
+        let events = parser.feed(&bytes[split..]);
+        assert_eq!(events[0].data, "héllo");
+    }
+
+    #[test]
+    fn test_finish_flushes_trailing_event() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: [DONE]").is_empty());
+        assert_eq!(parser.finish().unwrap().data, "[DONE]");
+        assert!(parser.finish().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_stream() {
+        let chunks: Vec<std::result::Result<&[u8], String>> =
+            vec![Ok(b"data: one\n\nda"), Ok(b"ta: two\n\n"), Ok(b"data: three")];
+        let events: Vec<_> = sse_events(futures::stream::iter(chunks))
+            .map(|e| e.unwrap().data)
+            .collect()
+            .await;
+
+        assert_eq!(events, vec!["one", "two", "three"]);
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_stream_error() {
+        let chunks: Vec<std::result::Result<&[u8], String>> =
+            vec![Ok(b"data: one\n\n"), Err("connection reset".to_string())];
+        let events: Vec<_> = sse_events(futures::stream::iter(chunks)).collect().await;
+
+        assert_eq!(events.len(), 2);
+        assert!(events[0].is_ok());
+        assert!(matches!(&events[1], Err(SimpleAgentsError::Network(msg)) if msg.contains("connection reset")));
+    }
+
+    fn content_chunk(content: &str) -> Result<CompletionChunk> {
+        use simple_agents_types::response::{ChoiceDelta, MessageDelta};
+
+        Ok(CompletionChunk {
+            id: "chatcmpl-1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            choices: vec![ChoiceDelta {
+                index: 0,
+                delta: MessageDelta {
+                    role: None,
+                    content: Some(content.to_string()),
+                },
+                finish_reason: None,
+            }],
+            created: None,
+            usage: None,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stream_metrics_time_to_first_token() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let mut stream = StreamMetrics::new(receiver);
+
+        // A role-only chunk does not count as the first token
+        let mut role_chunk = content_chunk("").unwrap();
+        role_chunk.choices[0].delta.content = None;
+        tokio::time::advance(Duration::from_millis(100)).await;
+        sender.unbounded_send(Ok(role_chunk)).unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        tokio::time::advance(Duration::from_millis(250)).await;
+        sender.unbounded_send(content_chunk("Hello")).unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        sender.unbounded_send(content_chunk(", world!")).unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        tokio::time::advance(Duration::from_millis(150)).await;
+        drop(sender);
+        assert!(stream.next().await.is_none());
+        // Time after the end is not counted
+        tokio::time::advance(Duration::from_secs(5)).await;
+
+        let report = stream.finish();
+        assert_eq!(report.time_to_first_token, Duration::from_millis(350));
+        assert_eq!(report.total_duration, Duration::from_millis(1000));
+        assert_eq!(report.chunk_count, 3);
+        assert_eq!(report.total_chars, 13);
+        assert_eq!(report.completion_tokens, None);
+        // 13 chars = 3.25 estimated tokens over 650ms
+        assert!((report.tokens_per_second() - 5.0).abs() < 1e-9);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stream_metrics_reported_usage_and_error() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let mut stream = StreamMetrics::new(receiver);
+
+        sender.unbounded_send(content_chunk("Hi")).unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        let mut usage_chunk = content_chunk("").unwrap();
+        usage_chunk.choices.clear();
+        usage_chunk.usage = Some(simple_agents_types::response::Usage::new(10, 20));
+        tokio::time::advance(Duration::from_secs(2)).await;
+        sender.unbounded_send(Ok(usage_chunk)).unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        sender
+            .unbounded_send(Err(SimpleAgentsError::Network("connection reset".to_string())))
+            .unwrap();
+        assert!(stream.next().await.unwrap().is_err());
+        tokio::time::advance(Duration::from_secs(1)).await;
+
+        let report = stream.finish();
+        assert_eq!(report.time_to_first_token, Duration::ZERO);
+        assert_eq!(report.total_duration, Duration::from_secs(2));
+        assert_eq!(report.completion_tokens, Some(20));
+        assert!((report.tokens_per_second() - 10.0).abs() < 1e-9);
+    }
+}
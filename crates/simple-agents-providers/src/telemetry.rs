@@ -0,0 +1,470 @@
+//! Tracing spans following the OpenTelemetry GenAI semantic conventions.
+//!
+//! [`TracedProvider`] wraps each completion in a `chat` span carrying the
+//! `gen_ai.*` attributes that OpenTelemetry backends understand. Spans are
+//! plain [`tracing`] spans; export them with `tracing-opentelemetry` or any
+//! other subscriber. `otel.name`, `otel.kind` and `otel.status_code` are
+//! the fields `tracing-opentelemetry` maps onto the span's name, kind and
+//! status.
+
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::time::Duration;
+use tracing::field::Empty;
+use tracing::Instrument;
+
+/// Value of `gen_ai.operation.name` for chat completions
+pub const OPERATION_CHAT: &str = "chat";
+
+/// Provider wrapper that records a GenAI span around every completion.
+///
+/// The span is named `chat {model}` and carries:
+///
+/// - `gen_ai.operation.name` (`"chat"`) and `gen_ai.system` (the provider
+///   name)
+/// - `gen_ai.request.model`, and `gen_ai.request.max_tokens`,
+///   `gen_ai.request.temperature` and `gen_ai.request.top_p` when set
+/// - `gen_ai.response.id`, `gen_ai.response.model` and
+///   `gen_ai.response.finish_reasons` (a JSON array, as tracing fields
+///   cannot hold arrays)
+/// - `gen_ai.usage.input_tokens` and `gen_ai.usage.output_tokens`
+/// - `error.type` (e.g. `"rate_limit_exceeded"`) when the call fails
+///
+/// Message content is not recorded unless enabled with
+/// [`with_content_recording`](Self::with_content_recording), since prompts
+/// and completions often hold personal data. `execute_with_attempts` is
+/// forwarded in one span covering every attempt; `execute_stream` is
+/// forwarded to the inner provider without a span.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_providers::telemetry::TracedProvider;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example(request: CompletionRequest) -> Result<()> {
+/// let provider = TracedProvider::new(OpenAIProvider::new(ApiKey::new("sk-...")?)?);
+/// let response = provider.complete(&request).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TracedProvider<P> {
+    inner: P,
+    record_content: bool,
+}
+
+impl<P: Provider> TracedProvider<P> {
+    /// Wrap `inner`, without recording message content.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            record_content: false,
+        }
+    }
+
+    /// Record the prompt and completion as span events.
+    ///
+    /// The prompt is recorded as a `gen_ai.content.prompt` event with a
+    /// `gen_ai.prompt` field, and the completion as a
+    /// `gen_ai.content.completion` event with a `gen_ai.completion` field,
+    /// both holding the messages as JSON.
+    pub fn with_content_recording(mut self, record_content: bool) -> Self {
+        self.record_content = record_content;
+        self
+    }
+
+    /// Get the wrapped provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Run `call` in the span for `req`, recording the response that
+    /// `response` picks out of its result, or its error.
+    async fn traced<T>(
+        &self,
+        req: &CompletionRequest,
+        call: impl std::future::Future<Output = Result<T>>,
+        response: impl Fn(&T) -> &CompletionResponse,
+    ) -> Result<T> {
+        let span = self.span(req);
+        if self.record_content {
+            let prompt = serde_json::to_string(&req.messages).unwrap_or_default();
+            span.in_scope(|| tracing::info!(gen_ai.prompt = %prompt, "gen_ai.content.prompt"));
+        }
+
+        let result = call.instrument(span.clone()).await;
+        match &result {
+            Ok(value) => {
+                let response = response(value);
+                span.record("gen_ai.response.id", response.id.as_str());
+                span.record("gen_ai.response.model", response.model.as_str());
+                span.record("gen_ai.response.finish_reasons", finish_reasons(response).as_str());
+                span.record("gen_ai.usage.input_tokens", response.usage.prompt_tokens);
+                span.record("gen_ai.usage.output_tokens", response.usage.completion_tokens);
+                if self.record_content {
+                    let messages: Vec<&Message> = response.choices.iter().map(|c| &c.message).collect();
+                    let completion = serde_json::to_string(&messages).unwrap_or_default();
+                    span.in_scope(|| {
+                        tracing::info!(gen_ai.completion = %completion, "gen_ai.content.completion")
+                    });
+                }
+            }
+            Err(error) => {
+                span.record("error.type", error_type(error));
+                span.record("otel.status_code", "ERROR");
+            }
+        }
+        result
+    }
+
+    fn span(&self, req: &CompletionRequest) -> tracing::Span {
+        let span = tracing::info_span!(
+            "chat",
+            otel.name = %format!("{} {}", OPERATION_CHAT, req.model),
+            otel.kind = "client",
+            otel.status_code = Empty,
+            gen_ai.operation.name = OPERATION_CHAT,
+            gen_ai.system = self.inner.name(),
+            gen_ai.request.model = %req.model,
+            gen_ai.request.max_tokens = Empty,
+            gen_ai.request.temperature = Empty,
+            gen_ai.request.top_p = Empty,
+            gen_ai.response.id = Empty,
+            gen_ai.response.model = Empty,
+            gen_ai.response.finish_reasons = Empty,
+            gen_ai.usage.input_tokens = Empty,
+            gen_ai.usage.output_tokens = Empty,
+            error.type = Empty,
+        );
+        if let Some(max_tokens) = req.max_tokens {
+            span.record("gen_ai.request.max_tokens", max_tokens);
+        }
+        if let Some(temperature) = req.temperature {
+            span.record("gen_ai.request.temperature", f64::from(temperature));
+        }
+        if let Some(top_p) = req.top_p {
+            span.record("gen_ai.request.top_p", f64::from(top_p));
+        }
+        span
+    }
+}
+
+/// JSON array of the response's finish reasons, in choice order
+fn finish_reasons(response: &CompletionResponse) -> String {
+    let reasons: Vec<&str> = response.choices.iter().map(|c| c.finish_reason.as_str()).collect();
+    serde_json::to_string(&reasons).unwrap_or_default()
+}
+
+/// Low-cardinality `error.type` value for `error`
+fn error_type(error: &SimpleAgentsError) -> &'static str {
+    match error.root() {
+        SimpleAgentsError::Provider(e) => e.kind(),
+        SimpleAgentsError::Healing(_) => "healing",
+        SimpleAgentsError::Network(_) => "network",
+        SimpleAgentsError::Config(_) => "config",
+        SimpleAgentsError::Validation(_) => "validation",
+        SimpleAgentsError::Cache(_) => "cache",
+        SimpleAgentsError::Routing(_) => "routing",
+        SimpleAgentsError::Serialization(_) => "serialization",
+        SimpleAgentsError::WithContext(_) => "_OTHER",
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for TracedProvider<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.inner.execute_stream(req).await
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        self.traced(req, self.inner.complete(req), |response| response).await
+    }
+
+    async fn execute_with_attempts(
+        &self,
+        req: &CompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<CompleteOutcome> {
+        let call = self.inner.execute_with_attempts(req, policy);
+        self.traced(req, call, |outcome| &outcome.response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Fields of one span or event, as strings
+    type Fields = HashMap<String, String>;
+
+    #[derive(Default)]
+    struct Collected {
+        spans: HashMap<u64, (String, Fields)>,
+        events: Vec<Fields>,
+    }
+
+    /// Subscriber that keeps every span's fields and every event
+    #[derive(Clone, Default)]
+    struct Collector {
+        next_id: Arc<AtomicU64>,
+        collected: Arc<Mutex<Collected>>,
+    }
+
+    impl Collector {
+        fn span(&self, name: &str) -> Fields {
+            let collected = self.collected.lock().unwrap();
+            let mut spans = collected.spans.values().filter(|(span_name, _)| span_name == name);
+            spans.next().expect("span not recorded").1.clone()
+        }
+
+        fn events(&self) -> Vec<Fields> {
+            self.collected.lock().unwrap().events.clone()
+        }
+    }
+
+    struct FieldVisitor<'a>(&'a mut Fields);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut fields = Fields::new();
+            span.record(&mut FieldVisitor(&mut fields));
+            let name = span.metadata().name().to_string();
+            self.collected.lock().unwrap().spans.insert(id, (name, fields));
+            Id::from_u64(id)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut collected = self.collected.lock().unwrap();
+            if let Some((_, fields)) = collected.spans.get_mut(&span.into_u64()) {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.collected.lock().unwrap().events.push(fields);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    /// Answers every request, or fails with `error` when set
+    struct StaticProvider {
+        error: Option<ProviderError>,
+    }
+
+    #[async_trait]
+    impl Provider for StaticProvider {
+        fn name(&self) -> &str {
+            "openai"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("http://localhost/chat").with_body(serde_json::to_value(req)?))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            match &self.error {
+                Some(error) => Err(error.clone().into()),
+                None => Ok(ProviderResponse::new(200, req.body)),
+            }
+        }
+
+        fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "chatcmpl-42".to_string(),
+                model: "gpt-4o-mini-2024-07-18".to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("Hi there!"),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(12, 3),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+
+        async fn execute_stream(
+            &self,
+            req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: req.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    fn request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("gpt-4o-mini")
+            .message(Message::user("Hello!"))
+            .max_tokens(100)
+            .temperature(0.5)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_span_uses_semantic_convention_keys() {
+        let collector = Collector::default();
+        let _guard = tracing::subscriber::set_default(collector.clone());
+
+        let provider = TracedProvider::new(StaticProvider { error: None });
+        provider.complete(&request()).await.unwrap();
+
+        let span = collector.span("chat");
+        let expected = [
+            ("otel.name", "chat gpt-4o-mini"),
+            ("gen_ai.operation.name", "chat"),
+            ("gen_ai.system", "openai"),
+            ("gen_ai.request.model", "gpt-4o-mini"),
+            ("gen_ai.request.max_tokens", "100"),
+            ("gen_ai.request.temperature", "0.5"),
+            ("gen_ai.response.id", "chatcmpl-42"),
+            ("gen_ai.response.model", "gpt-4o-mini-2024-07-18"),
+            ("gen_ai.response.finish_reasons", r#"["stop"]"#),
+            ("gen_ai.usage.input_tokens", "12"),
+            ("gen_ai.usage.output_tokens", "3"),
+        ];
+        for (key, value) in expected {
+            assert_eq!(span.get(key).map(String::as_str), Some(value), "attribute {}", key);
+        }
+        assert!(!span.contains_key("gen_ai.request.top_p"));
+        assert!(!span.contains_key("error.type"));
+        // Content is opt-in
+        assert!(collector.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failure_records_error_type() {
+        let collector = Collector::default();
+        let _guard = tracing::subscriber::set_default(collector.clone());
+
+        let provider = TracedProvider::new(StaticProvider {
+            error: Some(ProviderError::RateLimit { retry_after: None }),
+        });
+        assert!(provider.complete(&request()).await.is_err());
+
+        let span = collector.span("chat");
+        assert_eq!(span.get("error.type").map(String::as_str), Some("rate_limit_exceeded"));
+        assert_eq!(span.get("otel.status_code").map(String::as_str), Some("ERROR"));
+        assert!(!span.contains_key("gen_ai.response.id"));
+    }
+
+    #[tokio::test]
+    async fn test_content_recording_emits_events() {
+        let collector = Collector::default();
+        let _guard = tracing::subscriber::set_default(collector.clone());
+
+        let provider = TracedProvider::new(StaticProvider { error: None }).with_content_recording(true);
+        provider.complete(&request()).await.unwrap();
+
+        let events = collector.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["message"], "gen_ai.content.prompt");
+        assert!(events[0]["gen_ai.prompt"].contains("Hello!"));
+        assert_eq!(events[1]["message"], "gen_ai.content.completion");
+        assert!(events[1]["gen_ai.completion"].contains("Hi there!"));
+    }
+    #[tokio::test]
+    async fn test_streams_through_inner() {
+        use futures::StreamExt;
+
+        let provider = TracedProvider::new(StaticProvider { error: None });
+        let mut req = request();
+        req.stream = Some(true);
+
+        let stream = provider.execute_stream(provider.transform_request(&req).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().model, "gpt-4o-mini");
+    }
+}
@@ -0,0 +1,394 @@
+//! Cheap-model routing for auxiliary requests.
+//!
+//! Agents often pair one expensive main task with many small side-tasks:
+//! naming a conversation, summarizing tool output before it goes back into
+//! the context. [`TieredProvider`] sends requests marked with the
+//! [`AUXILIARY`] extension to a second, cheaper provider and model, so the
+//! calling code keeps a single provider handle and decides per request.
+
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Request extension marking a request as auxiliary: set it to `true`.
+pub const AUXILIARY: &str = "tier.auxiliary";
+
+/// Which side of a [`TieredProvider`] handles a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tier {
+    /// The main provider and the request's own model
+    Primary,
+    /// The cheap provider and model, for requests marked [`AUXILIARY`]
+    Auxiliary,
+}
+
+impl Tier {
+    /// The tier `extensions` ask for; anything but `AUXILIARY = true` is
+    /// [`Tier::Primary`].
+    pub fn of(extensions: &Extensions) -> Self {
+        if extensions.get_as::<bool>(AUXILIARY) == Some(true) {
+            Self::Auxiliary
+        } else {
+            Self::Primary
+        }
+    }
+
+    /// Lowercase name, e.g. `"auxiliary"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Primary => "primary",
+            Self::Auxiliary => "auxiliary",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Primary => 0,
+            Self::Auxiliary => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Tier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Completions served by one tier of a [`TieredProvider`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TierUsage {
+    /// Successful completions
+    pub requests: u64,
+    /// Prompt tokens across those completions
+    pub prompt_tokens: u64,
+    /// Completion tokens across those completions
+    pub completion_tokens: u64,
+    /// Dollar cost, from the tier provider's [`Provider::pricing`];
+    /// completions of models without a known price add nothing
+    pub cost: f64,
+}
+
+impl TierUsage {
+    fn record(&mut self, usage: &Usage, pricing: Option<Pricing>) {
+        self.requests += 1;
+        self.prompt_tokens += u64::from(usage.prompt_tokens);
+        self.completion_tokens += u64::from(usage.completion_tokens);
+        self.cost += pricing.map_or(0.0, |pricing| pricing.cost(usage));
+    }
+}
+
+/// Provider that sends auxiliary requests to a cheaper provider and model.
+///
+/// Requests whose extensions set [`AUXILIARY`] to `true` go to the
+/// auxiliary provider with their model replaced by the auxiliary model;
+/// every other request goes to the primary provider unchanged. Both tiers
+/// may be the same provider with different models.
+///
+/// [`Provider::complete`] counts tokens and cost per tier; read them with
+/// [`usage`](Self::usage). [`Provider::transform_request`],
+/// [`Provider::execute`] and [`Provider::execute_stream`] route as well,
+/// relying on the provider copying the request's extensions into its
+/// [`ProviderRequest`] as the built-in providers do.
+/// [`Provider::transform_response`] and the remaining hooks delegate to the
+/// primary provider.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::anthropic::AnthropicProvider;
+/// use simple_agents_providers::tiered::{Tier, TieredProvider, AUXILIARY};
+/// use simple_agents_types::prelude::*;
+/// use std::sync::Arc;
+///
+/// # async fn example() -> Result<()> {
+/// let anthropic = Arc::new(AnthropicProvider::new(ApiKey::new("sk-ant-...")?)?);
+/// let provider = TieredProvider::new(anthropic.clone(), anthropic, "claude-3-5-haiku-latest");
+///
+/// let title = CompletionRequest::builder()
+///     .model("claude-sonnet-4-5")
+///     .message(Message::user("Give this conversation a five-word title: ..."))
+///     .extension(AUXILIARY, true)
+///     .build()?;
+/// provider.complete(&title).await?; // answered by claude-3-5-haiku-latest
+///
+/// println!("side-tasks cost ${:.4}", provider.usage(Tier::Auxiliary).cost);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TieredProvider {
+    primary: Arc<dyn Provider>,
+    auxiliary: Arc<dyn Provider>,
+    auxiliary_model: String,
+    usage: Mutex<[TierUsage; 2]>,
+}
+
+impl TieredProvider {
+    /// Route auxiliary requests to `auxiliary_model` on `auxiliary`, and
+    /// everything else to `primary`.
+    pub fn new(
+        primary: Arc<dyn Provider>,
+        auxiliary: Arc<dyn Provider>,
+        auxiliary_model: impl Into<String>,
+    ) -> Self {
+        Self {
+            primary,
+            auxiliary,
+            auxiliary_model: auxiliary_model.into(),
+            usage: Mutex::new(Default::default()),
+        }
+    }
+
+    /// The model auxiliary requests are sent with.
+    pub fn auxiliary_model(&self) -> &str {
+        &self.auxiliary_model
+    }
+
+    /// Tokens and cost of the completions `tier` has served so far.
+    pub fn usage(&self, tier: Tier) -> TierUsage {
+        self.usage.lock().unwrap()[tier.index()].clone()
+    }
+
+    /// Clear the usage of both tiers.
+    pub fn reset_usage(&self) {
+        *self.usage.lock().unwrap() = Default::default();
+    }
+
+    fn provider(&self, tier: Tier) -> &dyn Provider {
+        match tier {
+            Tier::Primary => self.primary.as_ref(),
+            Tier::Auxiliary => self.auxiliary.as_ref(),
+        }
+    }
+
+    /// The tier for `req`, and `req` with the model that tier uses
+    fn route(&self, req: &CompletionRequest) -> (Tier, CompletionRequest) {
+        let tier = Tier::of(&req.extensions);
+        let mut req = req.clone();
+        if tier == Tier::Auxiliary {
+            req.model = self.auxiliary_model.clone();
+        }
+        (tier, req)
+    }
+}
+
+#[async_trait]
+impl Provider for TieredProvider {
+    fn name(&self) -> &str {
+        "tiered"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let (tier, req) = self.route(req);
+        self.provider(tier).transform_request(&req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.provider(Tier::of(&req.extensions)).execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.primary.transform_response(resp)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        self.provider(Tier::of(&req.extensions)).execute_stream(req).await
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.primary.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.primary.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.primary.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.primary.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.primary.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.primary.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.primary.pricing(model)
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.primary.prefill_cache(system_prompt).await
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        let (tier, req) = self.route(req);
+        let provider = self.provider(tier);
+        let response = provider.complete(&req).await?;
+
+        tracing::debug!(tier = tier.as_str(), model = %req.model, "Tiered completion");
+        let pricing = provider.pricing(&req.model);
+        self.usage.lock().unwrap()[tier.index()].record(&response.usage, pricing);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers with the model it was asked for and records the models of
+    /// the requests it receives
+    struct MockProvider {
+        name: &'static str,
+        models: Mutex<Vec<String>>,
+    }
+
+    impl MockProvider {
+        fn new(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                models: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn models(&self) -> Vec<String> {
+            self.models.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            self.models.lock().unwrap().push(req.model.clone());
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "model": req.model }))
+                .with_extensions(req.extensions.clone()))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            Ok(ProviderResponse::new(200, req.body))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            let model = resp.body["model"].as_str().unwrap_or_default().to_string();
+            Ok(CompletionResponse {
+                id: "resp".to_string(),
+                model: model.clone(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant(format!("{} via {}", model, self.name)),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1_000, 100),
+                created: None,
+                created_synthesized: false,
+                provider: Some(self.name.to_string()),
+            })
+        }
+
+        fn pricing(&self, model: &str) -> Option<Pricing> {
+            match model {
+                "big-model" => Some(Pricing {
+                    input_per_million: 3.0,
+                    output_per_million: 15.0,
+                }),
+                "small-model" => Some(Pricing {
+                    input_per_million: 0.25,
+                    output_per_million: 1.25,
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    fn request(auxiliary: Option<serde_json::Value>) -> CompletionRequest {
+        let mut builder = CompletionRequest::builder()
+            .model("big-model")
+            .message(Message::user("Hello"));
+        if let Some(value) = auxiliary {
+            builder = builder.extension(AUXILIARY, value);
+        }
+        builder.build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_auxiliary_flag_routes_to_cheap_tier() {
+        let primary = MockProvider::new("primary");
+        let auxiliary = MockProvider::new("auxiliary");
+        let provider = TieredProvider::new(primary.clone(), auxiliary.clone(), "small-model");
+
+        let response = provider.complete(&request(Some(true.into()))).await.unwrap();
+        assert_eq!(response.content(), Some("small-model via auxiliary"));
+        assert_eq!(auxiliary.models(), vec!["small-model"]);
+        assert!(primary.models().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unmarked_requests_hit_primary() {
+        let primary = MockProvider::new("primary");
+        let auxiliary = MockProvider::new("auxiliary");
+        let provider = TieredProvider::new(primary.clone(), auxiliary.clone(), "small-model");
+
+        for marker in [None, Some(false.into()), Some("yes".into())] {
+            let response = provider.complete(&request(marker)).await.unwrap();
+            assert_eq!(response.content(), Some("big-model via primary"));
+        }
+        assert_eq!(primary.models().len(), 3);
+        assert!(auxiliary.models().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_attributed_per_tier() {
+        let shared = MockProvider::new("shared");
+        let provider = TieredProvider::new(shared.clone(), shared.clone(), "small-model");
+
+        provider.complete(&request(None)).await.unwrap();
+        provider.complete(&request(Some(true.into()))).await.unwrap();
+        provider.complete(&request(Some(true.into()))).await.unwrap();
+        assert_eq!(shared.models(), vec!["big-model", "small-model", "small-model"]);
+
+        let primary = provider.usage(Tier::Primary);
+        assert_eq!(primary.requests, 1);
+        assert_eq!(primary.prompt_tokens, 1_000);
+        assert!((primary.cost - 0.0045).abs() < 1e-9);
+
+        let auxiliary = provider.usage(Tier::Auxiliary);
+        assert_eq!(auxiliary.requests, 2);
+        assert_eq!(auxiliary.completion_tokens, 200);
+        assert!((auxiliary.cost - 0.00075).abs() < 1e-9);
+
+        provider.reset_usage();
+        assert_eq!(provider.usage(Tier::Auxiliary), TierUsage::default());
+    }
+
+    #[tokio::test]
+    async fn test_low_level_hooks_route_by_extension() {
+        let primary = MockProvider::new("primary");
+        let auxiliary = MockProvider::new("auxiliary");
+        let provider = TieredProvider::new(primary, auxiliary.clone(), "small-model");
+
+        let provider_request = provider.transform_request(&request(Some(true.into()))).unwrap();
+        assert_eq!(Tier::of(&provider_request.extensions), Tier::Auxiliary);
+        let response = provider.execute(provider_request).await.unwrap();
+        assert_eq!(response.body["model"], "small-model");
+        assert_eq!(auxiliary.models(), vec!["small-model"]);
+    }
+}
@@ -0,0 +1,352 @@
+//! TLS settings for provider HTTP clients.
+//!
+//! [`TlsConfig`] trusts additional root CAs and presents client
+//! certificates (mTLS), for gateways behind an internal CA. Pass it to a
+//! provider's `with_tls`.
+//!
+//! The TLS backend is picked with crate features: `native-tls` (the
+//! default) or `rustls-tls`, which wins when both are enabled. The backends
+//! accept different client identity formats; see [`ClientIdentity`].
+
+use reqwest::ClientBuilder;
+use simple_agents_types::prelude::*;
+
+/// Client certificate and private key presented for mutual TLS.
+///
+/// | Format | `native-tls` | `rustls-tls` |
+/// |--------|--------------|--------------|
+/// | [`Pem`](Self::Pem) with a PKCS#8 key (`BEGIN PRIVATE KEY`) | yes | yes |
+/// | [`Pem`](Self::Pem) with an RSA or SEC1 key | no | yes |
+/// | [`Pkcs12`](Self::Pkcs12) | yes | no |
+///
+/// Unsupported combinations fail when the client is built.
+#[derive(Clone)]
+pub enum ClientIdentity {
+    /// PEM holding the certificate chain and the private key
+    Pem(Vec<u8>),
+    /// DER-encoded PKCS#12 archive and its password
+    Pkcs12 {
+        /// Archive bytes
+        der: Vec<u8>,
+        /// Archive password
+        password: String,
+    },
+}
+
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        match self {
+            Self::Pem(_) => f.write_str("Pem([REDACTED])"),
+            Self::Pkcs12 { .. } => f.write_str("Pkcs12([REDACTED])"),
+        }
+    }
+}
+
+impl ClientIdentity {
+    /// PEM with the certificate chain followed by the private key.
+    pub fn pem(pem: impl Into<Vec<u8>>) -> Self {
+        Self::Pem(pem.into())
+    }
+
+    /// PKCS#12 (`.p12`/`.pfx`) archive protected by `password`.
+    pub fn pkcs12(der: impl Into<Vec<u8>>, password: impl Into<String>) -> Self {
+        Self::Pkcs12 {
+            der: der.into(),
+            password: password.into(),
+        }
+    }
+}
+
+/// TLS options applied when a provider builds its HTTP client.
+///
+/// The default changes nothing: the system's (or webpki's) root store,
+/// full certificate verification and no client certificate.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_providers::tls::{ClientIdentity, TlsConfig};
+/// use simple_agents_types::prelude::*;
+///
+/// # fn example() -> std::result::Result<(), Box<dyn std::error::Error>> {
+/// let tls = TlsConfig::new()
+///     .add_root_certificate(std::fs::read("internal-ca.pem")?)
+///     .identity(ClientIdentity::pem(std::fs::read("client.pem")?));
+/// let provider = OpenAIProvider::with_base_url(
+///     ApiKey::new("sk-...")?,
+///     "https://llm-gateway.internal/v1".to_string(),
+/// )?
+/// .with_tls(&tls)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    root_certificates: Vec<Vec<u8>>,
+    accept_invalid_certs: bool,
+    identity: Option<ClientIdentity>,
+}
+
+impl TlsConfig {
+    /// Create a configuration that changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust the PEM certificate(s) in `pem` as root CAs, in addition to
+    /// the built-in roots.
+    pub fn add_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Skip server certificate verification entirely.
+    ///
+    /// Any certificate is accepted, including expired, self-signed and
+    /// wrong-host ones, so the connection is open to interception. Only
+    /// for local testing; prefer [`add_root_certificate`](Self::add_root_certificate).
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Present `identity` as the client certificate.
+    pub fn identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Whether this configuration leaves the client unchanged.
+    pub fn is_default(&self) -> bool {
+        self.root_certificates.is_empty() && !self.accept_invalid_certs && self.identity.is_none()
+    }
+
+    /// Apply these options to `builder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if a certificate or identity
+    /// cannot be parsed, if the identity format is not supported by the
+    /// enabled TLS backend, or if no TLS backend feature is enabled.
+    pub fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder> {
+        if self.is_default() {
+            return Ok(builder);
+        }
+        self.apply_to_backend(builder)
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    fn apply_to_backend(&self, mut builder: ClientBuilder) -> Result<ClientBuilder> {
+        #[cfg(feature = "rustls-tls")]
+        {
+            builder = builder.use_rustls_tls();
+        }
+
+        for pem in &self.root_certificates {
+            let certificates = reqwest::Certificate::from_pem_bundle(pem)
+                .map_err(|e| tls_error(format!("invalid root certificate: {}", e)))?;
+            if certificates.is_empty() {
+                return Err(tls_error("root certificate contains no PEM certificate"));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if self.accept_invalid_certs {
+            tracing::warn!("TLS certificate verification is disabled");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(backend_identity(identity)?);
+        }
+        Ok(builder)
+    }
+
+    #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+    fn apply_to_backend(&self, _builder: ClientBuilder) -> Result<ClientBuilder> {
+        Err(tls_error(
+            "TLS options need a TLS backend; enable the `native-tls` or `rustls-tls` feature",
+        ))
+    }
+}
+
+#[cfg(feature = "rustls-tls")]
+fn backend_identity(identity: &ClientIdentity) -> Result<reqwest::Identity> {
+    match identity {
+        ClientIdentity::Pem(pem) => {
+            reqwest::Identity::from_pem(pem).map_err(|e| tls_error(format!("invalid client identity: {}", e)))
+        }
+        ClientIdentity::Pkcs12 { .. } => Err(tls_error(
+            "PKCS#12 client identities are not supported by rustls; pass a PEM identity \
+             or build with only the `native-tls` feature",
+        )),
+    }
+}
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+fn backend_identity(identity: &ClientIdentity) -> Result<reqwest::Identity> {
+    let identity = match identity {
+        ClientIdentity::Pkcs12 { der, password } => reqwest::Identity::from_pkcs12_der(der, password),
+        ClientIdentity::Pem(pem) => {
+            let (certificates, key) = split_pem_identity(pem)?;
+            reqwest::Identity::from_pkcs8_pem(&certificates, &key)
+        }
+    };
+    identity.map_err(|e| tls_error(format!("invalid client identity: {}", e)))
+}
+
+/// Split a PEM identity into its certificate chain and PKCS#8 key, the
+/// form native-tls takes.
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+fn split_pem_identity(pem: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let pem = std::str::from_utf8(pem).map_err(|_| tls_error("client identity is not PEM text"))?;
+
+    let mut certificates = String::new();
+    let mut key = String::new();
+    let mut block: Option<&mut String> = None;
+    for line in pem.lines() {
+        let line = line.trim();
+        if let Some(label) = line.strip_prefix("-----BEGIN ").and_then(|l| l.strip_suffix("-----")) {
+            block = match label {
+                "CERTIFICATE" => Some(&mut certificates),
+                "PRIVATE KEY" => Some(&mut key),
+                label if label.ends_with("PRIVATE KEY") => {
+                    return Err(tls_error(format!(
+                        "native-tls needs an unencrypted PKCS#8 key (BEGIN PRIVATE KEY), found BEGIN {}; \
+                         convert it with `openssl pkcs8 -topk8 -nocrypt` or use the `rustls-tls` feature",
+                        label
+                    )));
+                }
+                _ => None,
+            };
+        }
+        if let Some(block) = block.as_mut() {
+            block.push_str(line);
+            block.push('\n');
+        }
+        if line.starts_with("-----END ") {
+            block = None;
+        }
+    }
+
+    if certificates.is_empty() {
+        return Err(tls_error("client identity contains no certificate"));
+    }
+    if key.is_empty() {
+        return Err(tls_error("client identity contains no private key"));
+    }
+    Ok((certificates.into_bytes(), key.into_bytes()))
+}
+
+fn tls_error(message: impl std::fmt::Display) -> SimpleAgentsError {
+    SimpleAgentsError::Config(format!("TLS configuration: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../tests/fixtures/tls/ca.pem");
+    const CLIENT_PEM: &[u8] = include_bytes!("../tests/fixtures/tls/client.pem");
+    const CLIENT_SEC1_PEM: &[u8] = include_bytes!("../tests/fixtures/tls/client-sec1.pem");
+    const CLIENT_P12: &[u8] = include_bytes!("../tests/fixtures/tls/client.p12");
+
+    fn build(tls: &TlsConfig) -> Result<reqwest::Client> {
+        tls.apply(reqwest::Client::builder())?
+            .build()
+            .map_err(|e| SimpleAgentsError::Config(e.to_string()))
+    }
+
+    fn config_message(result: Result<reqwest::Client>) -> String {
+        match result {
+            Err(SimpleAgentsError::Config(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_is_noop() {
+        assert!(TlsConfig::new().is_default());
+        build(&TlsConfig::new()).unwrap();
+    }
+
+    #[test]
+    fn test_root_certificate() {
+        let tls = TlsConfig::new().add_root_certificate(CA);
+        assert!(!tls.is_default());
+        build(&tls).unwrap();
+
+        let message = config_message(build(&TlsConfig::new().add_root_certificate("not a certificate")));
+        assert!(message.contains("root certificate"), "{}", message);
+    }
+
+    #[test]
+    fn test_danger_accept_invalid_certs() {
+        let tls = TlsConfig::new().danger_accept_invalid_certs(true);
+        assert!(!tls.is_default());
+        build(&tls).unwrap();
+        assert!(TlsConfig::new().danger_accept_invalid_certs(false).is_default());
+    }
+
+    #[test]
+    fn test_pem_identity() {
+        build(&TlsConfig::new().identity(ClientIdentity::pem(CLIENT_PEM))).unwrap();
+
+        // A certificate alone is not an identity
+        assert!(build(&TlsConfig::new().identity(ClientIdentity::pem(CA))).is_err());
+    }
+
+    #[test]
+    fn test_identity_debug_redacts_key() {
+        let debug = format!("{:?}", TlsConfig::new().identity(ClientIdentity::pkcs12(CLIENT_P12, "test")));
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("test"));
+    }
+
+    #[cfg(not(feature = "rustls-tls"))]
+    #[test]
+    fn test_native_tls_identity_formats() {
+        build(&TlsConfig::new().identity(ClientIdentity::pkcs12(CLIENT_P12, "test"))).unwrap();
+        assert!(build(&TlsConfig::new().identity(ClientIdentity::pkcs12(CLIENT_P12, "wrong"))).is_err());
+
+        let message = config_message(build(&TlsConfig::new().identity(ClientIdentity::pem(CLIENT_SEC1_PEM))));
+        assert!(message.contains("found BEGIN EC PRIVATE KEY"), "{}", message);
+        assert!(message.contains("rustls-tls"), "{}", message);
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn test_rustls_identity_formats() {
+        build(&TlsConfig::new().identity(ClientIdentity::pem(CLIENT_SEC1_PEM))).unwrap();
+
+        let tls = TlsConfig::new().identity(ClientIdentity::pkcs12(CLIENT_P12, "test"));
+        let message = config_message(build(&tls));
+        assert!(message.contains("PKCS#12"), "{}", message);
+    }
+
+    #[test]
+    fn test_providers_accept_tls() {
+        use crate::anthropic::AnthropicProvider;
+        use crate::openai::OpenAIProvider;
+        use crate::vllm::VllmProvider;
+
+        let key = || ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+        let tls = TlsConfig::new().add_root_certificate(CA).identity(ClientIdentity::pem(CLIENT_PEM));
+        let invalid = TlsConfig::new().add_root_certificate("not a certificate");
+
+        let openai = OpenAIProvider::new(key()).unwrap();
+        assert!(openai.clone().with_tls(&tls).is_ok());
+        assert!(matches!(openai.with_tls(&invalid), Err(SimpleAgentsError::Config(_))));
+
+        let anthropic = AnthropicProvider::new(key()).unwrap();
+        assert!(anthropic.clone().with_tls(&tls).is_ok());
+        assert!(matches!(anthropic.with_tls(&invalid), Err(SimpleAgentsError::Config(_))));
+
+        let vllm = VllmProvider::with_base_url("https://llm.internal:8000/v1".to_string(), "llama").unwrap();
+        let vllm = vllm.with_tls(&tls).unwrap();
+        assert_eq!(vllm.base_url(), "https://llm.internal:8000/v1");
+    }
+}
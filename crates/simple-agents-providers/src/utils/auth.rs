@@ -0,0 +1,101 @@
+//! API key header construction shared by providers.
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use simple_agents_types::prelude::*;
+use std::borrow::Cow;
+
+/// How an API key is presented to the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AuthScheme {
+    /// `x-api-key: <key>` (Anthropic's native scheme)
+    #[default]
+    XApiKey,
+    /// `Authorization: Bearer <key>` (OpenAI-style APIs and most gateways)
+    Bearer,
+    /// `api-key: <key>` (Azure OpenAI)
+    ApiKey,
+}
+
+impl AuthScheme {
+    /// Name of the header carrying the key.
+    pub fn header_name(&self) -> &'static str {
+        match self {
+            Self::XApiKey => "x-api-key",
+            Self::Bearer => simple_agents_types::provider::headers::AUTHORIZATION,
+            Self::ApiKey => simple_agents_types::provider::headers::API_KEY,
+        }
+    }
+
+    /// Value of the header carrying `key`.
+    pub fn header_value(&self, key: &ApiKey) -> String {
+        match self {
+            Self::XApiKey | Self::ApiKey => key.expose().to_string(),
+            Self::Bearer => format!("Bearer {}", key.expose()),
+        }
+    }
+}
+
+/// Auth header as a [`ProviderRequest`] header pair.
+pub(crate) fn header_pair(scheme: AuthScheme, key: &ApiKey) -> (Cow<'static, str>, Cow<'static, str>) {
+    (Cow::Borrowed(scheme.header_name()), Cow::Owned(scheme.header_value(key)))
+}
+
+/// Auth header for a reqwest request, hidden from `Debug` output.
+pub(crate) fn header(scheme: AuthScheme, key: &ApiKey) -> Result<(HeaderName, HeaderValue)> {
+    let mut value = HeaderValue::from_str(&scheme.header_value(key))
+        .map_err(|e| SimpleAgentsError::Config(format!("Invalid API key header: {}", e)))?;
+    value.set_sensitive(true);
+    // Both names are valid header names
+    let name = HeaderName::from_bytes(scheme.header_name().as_bytes()).expect("valid header name");
+    Ok((name, value))
+}
+
+/// Set the auth header in `headers`, replacing any existing value.
+pub(crate) fn insert(headers: &mut HeaderMap, scheme: AuthScheme, key: &ApiKey) -> Result<()> {
+    let (name, value) = header(scheme, key)?;
+    headers.insert(name, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> ApiKey {
+        ApiKey::new("sk-ant-REDACTED").unwrap()
+    }
+
+    #[test]
+    fn test_x_api_key_scheme() {
+        let (name, value) = header_pair(AuthScheme::XApiKey, &key());
+        assert_eq!(name, "x-api-key");
+        assert_eq!(value, "sk-ant-REDACTED");
+        assert_eq!(AuthScheme::default(), AuthScheme::XApiKey);
+    }
+
+    #[test]
+    fn test_bearer_scheme() {
+        let (name, value) = header_pair(AuthScheme::Bearer, &key());
+        assert_eq!(name, "Authorization");
+        assert_eq!(value, "Bearer sk-ant-REDACTED");
+    }
+
+    #[test]
+    fn test_api_key_scheme() {
+        let (name, value) = header_pair(AuthScheme::ApiKey, &key());
+        assert_eq!(name, "api-key");
+        assert_eq!(value, "sk-ant-REDACTED");
+    }
+
+    #[test]
+    fn test_insert_marks_header_sensitive() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer stale"));
+        insert(&mut headers, AuthScheme::Bearer, &key()).unwrap();
+
+        let value = &headers["authorization"];
+        assert_eq!(value, "Bearer sk-ant-REDACTED");
+        assert!(value.is_sensitive());
+        assert_eq!(headers.len(), 1);
+    }
+}
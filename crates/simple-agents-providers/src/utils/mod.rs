@@ -0,0 +1,508 @@
+//! Shared utilities for provider implementations.
+
+pub(crate) mod auth;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// Default timeout for HTTP requests
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default maximum retries
+#[allow(dead_code)]
+pub(crate) const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// A header that cannot be sent.
+///
+/// Converts into
+/// [`SimpleAgentsError::Config`](simple_agents_types::SimpleAgentsError::Config)
+/// carrying its message.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HeaderError {
+    /// The name (given here) is empty or has a character outside the HTTP
+    /// token set, such as a space or colon
+    #[error("Invalid header name {0:?}: {problem}", problem = name_problem(.0))]
+    InvalidHeaderName(String),
+
+    /// Header name and its value, which has a control character other
+    /// than tab.
+    ///
+    /// Values of headers that may hold credentials (see
+    /// [`is_secret_header`]) are masked: every byte except control
+    /// characters is replaced with `*`, so the length and the position of
+    /// the offending character are kept.
+    #[error(
+        "Invalid value {1:?} for header {0:?}: control character at byte {position}",
+        position = control_character_position(.1)
+    )]
+    InvalidHeaderValue(String, String),
+}
+
+impl HeaderError {
+    /// The invalid value error for `name`, masking secret values.
+    fn invalid_value(name: &str, value: &str) -> Self {
+        let value = if is_secret_header(name) {
+            value
+                .chars()
+                .map(|c| if c.is_ascii_control() { c.to_string() } else { "*".repeat(c.len_utf8()) })
+                .collect()
+        } else {
+            value.to_string()
+        };
+        HeaderError::InvalidHeaderValue(name.to_string(), value)
+    }
+}
+
+impl From<HeaderError> for simple_agents_types::SimpleAgentsError {
+    fn from(error: HeaderError) -> Self {
+        simple_agents_types::SimpleAgentsError::Config(error.to_string())
+    }
+}
+
+/// Whether the value of header `name` may be a credential: one of
+/// [`headers::SENSITIVE`](simple_agents_types::provider::headers::SENSITIVE)
+/// or a name mentioning a key, token, secret, password, cookie or auth.
+pub fn is_secret_header(name: &str) -> bool {
+    const HINTS: &[&str] = &["key", "token", "secret", "password", "cookie", "auth"];
+    let name = name.to_ascii_lowercase();
+    simple_agents_types::provider::headers::SENSITIVE.iter().any(|s| name.eq_ignore_ascii_case(s))
+        || HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// Why `name` is not a valid header name
+fn name_problem(name: &str) -> String {
+    let valid = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if name.is_empty() {
+        return "name is empty".to_string();
+    }
+    match name.char_indices().find(|&(_, c)| !valid(c)) {
+        Some((i, c)) => format!("character {:?} at byte {} is not allowed", c, i),
+        None => "name is too long".to_string(),
+    }
+}
+
+/// Byte offset of the first control character other than tab
+fn control_character_position(value: &str) -> usize {
+    value
+        .char_indices()
+        .find(|&(_, c)| c.is_ascii_control() && c != '\t')
+        .map_or(0, |(i, _)| i)
+}
+
+/// Build HTTP headers from name-value pairs.
+///
+/// Every pair is kept: a name given more than once is sent once per value,
+/// in order (e.g. several `Forwarded` headers), so callers overriding a
+/// header must drop the earlier pair themselves.
+///
+/// Names are case-insensitive and always sent lowercase. `HeaderMap`
+/// normalizes them, and HTTP/2 requires lowercase names on the wire, so a
+/// literal casing such as `X-Api-Key` cannot be preserved.
+///
+/// Values are sent as UTF-8 bytes; control characters other than tab
+/// (such as line breaks) are rejected. Invalid names and values are
+/// reported as a [`HeaderError`], converted to
+/// [`SimpleAgentsError::Config`](simple_agents_types::SimpleAgentsError::Config).
+pub(crate) fn build_headers(
+    pairs: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+) -> simple_agents_types::Result<HeaderMap> {
+    let mut headers = HeaderMap::with_capacity(pairs.len());
+
+    for (name, value) in pairs {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| HeaderError::InvalidHeaderName(name.to_string()))?;
+        let header_value =
+            HeaderValue::from_str(&value).map_err(|_| HeaderError::invalid_value(&name, &value))?;
+        headers.append(header_name, header_value);
+    }
+
+    Ok(headers)
+}
+
+/// Join `base_url` and a caller-supplied path for raw passthrough requests.
+///
+/// The path is always resolved against `base_url`: absolute and
+/// scheme-relative URLs, backslashes and `.`/`..` segments (including
+/// percent-encoded ones) are rejected so credentials are never sent to
+/// another host or outside the API's base path.
+pub(crate) fn join_url(base_url: &str, path: &str) -> simple_agents_types::Result<String> {
+    let invalid = |reason: &str| {
+        simple_agents_types::ValidationError::InvalidFormat {
+            field: "path".to_string(),
+            reason: reason.to_string(),
+        }
+        .into()
+    };
+
+    if path.contains("://") || path.starts_with("//") {
+        return Err(invalid("must be relative to the base URL"));
+    }
+    if path.contains('\\') || path.chars().any(char::is_control) {
+        return Err(invalid("contains a backslash or control character"));
+    }
+    let route = path.split(['?', '#']).next().unwrap_or_default();
+    let traverses = route
+        .split('/')
+        .map(|segment| segment.to_ascii_lowercase().replace("%2e", "."))
+        .any(|segment| segment == "." || segment == "..");
+    if traverses {
+        return Err(invalid("must not contain '.' or '..' segments"));
+    }
+
+    Ok(format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/')))
+}
+
+/// Normalize a provider base URL given at construction.
+///
+/// The URL must be absolute http(s) without a query or fragment. Trailing
+/// and repeated slashes are removed and a version segment repeated back to
+/// back (`/v1/v1`) is collapsed, so `http://localhost:4000/v1/` and
+/// `http://localhost:4000/v1/v1` both become `http://localhost:4000/v1`.
+pub(crate) fn normalize_base_url(base_url: &str) -> simple_agents_types::Result<String> {
+    let mut url = parse_http_url(base_url)?;
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(simple_agents_types::SimpleAgentsError::Config(format!(
+            "Invalid base URL {:?}: query strings and fragments are not supported \
+             (use with_full_chat_url for gateways that need them)",
+            base_url
+        )));
+    }
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in url.path().split('/').filter(|s| !s.is_empty()) {
+        if is_version_segment(segment) && segments.last() == Some(&segment) {
+            continue;
+        }
+        segments.push(segment);
+    }
+    let path = format!("/{}", segments.join("/"));
+    url.set_path(&path);
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// Parse an absolute http(s) URL, with a clear error for anything else.
+pub(crate) fn parse_http_url(value: &str) -> simple_agents_types::Result<url::Url> {
+    let invalid = |reason: String| {
+        simple_agents_types::SimpleAgentsError::Config(format!("Invalid base URL {:?}: {}", value, reason))
+    };
+
+    let url = url::Url::parse(value.trim()).map_err(|e| invalid(e.to_string()))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(invalid(format!("scheme must be http or https, not {:?}", scheme))),
+    }
+}
+
+/// Whether `segment` is an API version such as `v1` or `v2`.
+fn is_version_segment(segment: &str) -> bool {
+    segment.len() > 1
+        && segment.starts_with(['v', 'V'])
+        && segment[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Convert a successful raw passthrough response into a [`ProviderResponse`].
+///
+/// Headers are kept. JSON bodies are parsed; an empty body becomes `null`
+/// and any other body is returned as a JSON string.
+///
+/// [`ProviderResponse`]: simple_agents_types::provider::ProviderResponse
+pub(crate) async fn raw_response(
+    response: reqwest::Response,
+) -> simple_agents_types::Result<simple_agents_types::provider::ProviderResponse> {
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let text = response.text().await.map_err(|e| {
+        simple_agents_types::ProviderError::InvalidResponse(format!("Failed to read response body: {}", e))
+    })?;
+
+    let body = if text.trim().is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text))
+    };
+
+    Ok(simple_agents_types::provider::ProviderResponse {
+        status,
+        body,
+        headers: Some(headers),
+    })
+}
+
+/// Log a request at debug level with secret headers redacted.
+///
+/// Used by providers with request debugging enabled.
+pub(crate) fn log_request(method: &reqwest::Method, req: &simple_agents_types::provider::ProviderRequest, sensitive: &[String]) {
+    tracing::debug!(
+        method = %method,
+        url = %req.url,
+        headers = ?req.redacted_headers(sensitive),
+        body = %serde_json::to_string_pretty(&req.body).unwrap_or_default(),
+        "Sending request"
+    );
+}
+
+/// Timeout for self-hosted servers, where long prompts on modest hardware
+/// take a while.
+pub(crate) const LOCAL_SERVER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Key sent to self-hosted servers started without one; they ignore the
+/// `Authorization` header.
+pub(crate) const LOCAL_SERVER_KEY: &str = "sk-local-server-no-key";
+
+/// HTTP/1.1 client settings for self-hosted OpenAI-compatible servers.
+pub(crate) fn local_server_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(LOCAL_SERVER_TIMEOUT)
+        .pool_max_idle_per_host(10)
+        .pool_idle_timeout(Duration::from_secs(90))
+}
+
+/// HTTP/1.1 client for self-hosted OpenAI-compatible servers.
+pub(crate) fn local_server_client() -> simple_agents_types::Result<reqwest::Client> {
+    build_client(local_server_client_builder())
+}
+
+/// Build an HTTP client, reporting failure as a configuration error.
+pub(crate) fn build_client(builder: reqwest::ClientBuilder) -> simple_agents_types::Result<reqwest::Client> {
+    builder.build().map_err(|e| {
+        simple_agents_types::SimpleAgentsError::Config(format!("Failed to create HTTP client: {}", e))
+    })
+}
+
+/// Warm a server's prefix cache by generating one token after
+/// `system_prompt`.
+pub(crate) async fn prefill_system_prompt(
+    provider: &dyn simple_agents_types::provider::Provider,
+    model: &str,
+    system_prompt: &str,
+) -> simple_agents_types::Result<simple_agents_types::request::PrefillToken> {
+    use simple_agents_types::prelude::*;
+
+    let request = CompletionRequest::builder()
+        .model(model)
+        .message(Message::system(system_prompt))
+        .max_tokens(1)
+        .build()?;
+    provider.complete(&request).await?;
+    tracing::debug!(provider = provider.name(), model, "Prefilled system prompt");
+    Ok(PrefillToken::new(provider.name(), model, system_prompt))
+}
+
+/// Parse a `Retry-After` header value (delay-seconds or HTTP-date).
+///
+/// Re-exported from [`simple_agents_types::rate_limit`], where
+/// [`ProviderResponse::rate_limit_reset`](simple_agents_types::provider::ProviderResponse::rate_limit_reset)
+/// uses it too.
+pub use simple_agents_types::rate_limit::parse_retry_after;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_headers() {
+        let headers = build_headers(vec![
+            (Cow::Borrowed("Authorization"), Cow::Borrowed("Bearer sk-test")),
+            (Cow::Borrowed("Content-Type"), Cow::Borrowed("application/json")),
+        ]).unwrap();
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(
+            headers.get("Authorization").unwrap(),
+            "Bearer sk-test"
+        );
+    }
+
+    #[test]
+    fn test_build_headers_keeps_duplicates() {
+        let headers = build_headers(vec![
+            (Cow::Borrowed("Forwarded"), Cow::Borrowed("for=192.0.2.60")),
+            (Cow::Borrowed("X-Api-Key"), Cow::Borrowed("gw-key")),
+            (Cow::Borrowed("forwarded"), Cow::Borrowed("for=198.51.100.17")),
+        ])
+        .unwrap();
+
+        let forwarded: Vec<_> = headers.get_all("Forwarded").iter().collect();
+        assert_eq!(forwarded, ["for=192.0.2.60", "for=198.51.100.17"]);
+        // Names are normalized to lowercase
+        assert_eq!(headers.keys().map(|name| name.as_str()).collect::<Vec<_>>(), ["forwarded", "x-api-key"]);
+    }
+
+    #[test]
+    fn test_build_headers_reports_offending_header() {
+        let error = build_headers(vec![
+            (Cow::Borrowed("x-tenant"), Cow::Borrowed("acme")),
+            (Cow::Borrowed("x-secret"), Cow::Borrowed("sk-abc\n123")),
+        ])
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("\"x-secret\""), "{}", error);
+        assert!(error.contains("byte 6"), "{}", error);
+        assert!(!error.contains("sk-abc"), "{}", error);
+
+        let error = build_headers(vec![(Cow::Borrowed("x-api key"), Cow::Borrowed("value"))])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("\"x-api key\""), "{}", error);
+
+        let error = build_headers(vec![(Cow::Borrowed("x-line"), Cow::Borrowed("caf\u{e9}\0"))])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("byte 5"), "{}", error);
+
+        // Non-ASCII UTF-8 is sent as raw bytes
+        let headers = build_headers(vec![(Cow::Borrowed("x-user"), Cow::Borrowed("Zo\u{eb}"))]).unwrap();
+        assert_eq!(headers["x-user"].as_bytes(), "Zo\u{eb}".as_bytes());
+    }
+
+    #[test]
+    fn test_header_errors_name_the_value() {
+        let error = build_headers(vec![(Cow::Borrowed("x-trace"), Cow::Borrowed("abc\r\ndef"))]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Configuration error: Invalid value \"abc\\r\\ndef\" for header \"x-trace\": \
+             control character at byte 3"
+        );
+
+        // Secrets keep only their shape
+        let error = HeaderError::invalid_value("X-Auth-Token", "t\u{f8}k\x1b");
+        let masked = HeaderError::InvalidHeaderValue("X-Auth-Token".to_string(), "****\x1b".to_string());
+        assert_eq!(error, masked);
+        assert!(error.to_string().ends_with("control character at byte 4"), "{}", error);
+
+        let error = build_headers(vec![(Cow::Borrowed("x-caf\u{e9}"), Cow::Borrowed("v"))]).unwrap_err();
+        assert!(error.to_string().contains("character '\u{e9}' at byte 5"), "{}", error);
+        let error = build_headers(vec![(Cow::Borrowed(""), Cow::Borrowed("v"))]).unwrap_err();
+        assert!(error.to_string().ends_with("Invalid header name \"\": name is empty"), "{}", error);
+    }
+
+    #[test]
+    fn test_build_headers_accepts_long_values() {
+        let value = "a".repeat(64 * 1024);
+        let headers = build_headers(vec![(Cow::Borrowed("x-long"), Cow::Owned(value.clone()))]).unwrap();
+        assert_eq!(headers["x-long"].as_bytes(), value.as_bytes());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_parsed_retry_after_delays_retry() {
+        let config = simple_agents_types::config::RetryConfig {
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            backoff_multiplier: 1.0,
+            jitter: false,
+        };
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let started = tokio::time::Instant::now();
+
+        let result = crate::retry::execute_with_retry(
+            &config,
+            |_| true,
+            || async {
+                if attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                    let retry_after = parse_retry_after("3");
+                    Err(simple_agents_types::ProviderError::RateLimit { retry_after }.into())
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_join_url() {
+        assert_eq!(
+            join_url("https://api.openai.com/v1", "/files?purpose=batch").unwrap(),
+            "https://api.openai.com/v1/files?purpose=batch"
+        );
+        assert_eq!(
+            join_url("https://api.openai.com/v1/", "files/file-1/content").unwrap(),
+            "https://api.openai.com/v1/files/file-1/content"
+        );
+        // Dots inside a segment or the query are fine
+        assert!(join_url("https://api.openai.com/v1", "/models/gpt-4.1?after=a..b").is_ok());
+    }
+
+    #[test]
+    fn test_join_url_rejects_escapes() {
+        for path in [
+            "https://evil.example.com/steal",
+            "//evil.example.com/steal",
+            "/../admin",
+            "/files/%2E%2E/admin",
+            "/./files",
+            "\\\\evil.example.com",
+            "/files\r\nHost: evil",
+        ] {
+            assert!(
+                matches!(
+                    join_url("https://api.openai.com/v1", path),
+                    Err(simple_agents_types::SimpleAgentsError::Validation(_))
+                ),
+                "accepted {:?}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_base_url_matrix() {
+        let cases = [
+            ("http://localhost:4000", "http://localhost:4000"),
+            ("http://localhost:4000/", "http://localhost:4000"),
+            ("http://localhost:4000//", "http://localhost:4000"),
+            ("http://localhost:4000/v1", "http://localhost:4000/v1"),
+            ("http://localhost:4000/v1/", "http://localhost:4000/v1"),
+            ("http://localhost:4000/v1//", "http://localhost:4000/v1"),
+            ("http://localhost:4000/v1/v1", "http://localhost:4000/v1"),
+            ("http://localhost:4000/v1/v1/", "http://localhost:4000/v1"),
+            ("http://localhost:4000//v1", "http://localhost:4000/v1"),
+            ("https://api.openai.com/v1", "https://api.openai.com/v1"),
+            ("https://proxy.example.com/openai/v1/", "https://proxy.example.com/openai/v1"),
+            ("https://proxy.example.com/openai//v1/v1", "https://proxy.example.com/openai/v1"),
+            ("https://api.groq.com/openai/v1", "https://api.groq.com/openai/v1"),
+            // Different versions and non-version segments are left alone
+            ("https://proxy.example.com/v1/v2", "https://proxy.example.com/v1/v2"),
+            ("https://proxy.example.com/api/api", "https://proxy.example.com/api/api"),
+            ("  https://proxy.example.com/v1  ", "https://proxy.example.com/v1"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize_base_url(input).unwrap(), expected, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_normalize_base_url_rejects_invalid() {
+        for input in [
+            "localhost:4000",
+            "api.openai.com/v1",
+            "ftp://example.com/v1",
+            "ws://localhost:4000",
+            "",
+            "https://proxy.example.com/v1?api-version=2024-02-01",
+            "https://proxy.example.com/v1#chat",
+        ] {
+            assert!(
+                matches!(
+                    normalize_base_url(input),
+                    Err(simple_agents_types::SimpleAgentsError::Config(_))
+                ),
+                "accepted {:?}",
+                input
+            );
+        }
+
+        let err = normalize_base_url("ftp://example.com").unwrap_err().to_string();
+        assert!(err.contains("http or https"), "{}", err);
+    }
+}
@@ -0,0 +1,405 @@
+//! vLLM provider implementation.
+//!
+//! vLLM serves an OpenAI-compatible chat completions API, so this provider
+//! reuses the OpenAI request/response models, error mapping and SSE chunk
+//! parsing. With automatic prefix caching (`--enable-prefix-caching`, the
+//! default in recent releases) requests that share a prompt prefix reuse
+//! its KV cache; [`Provider::prefill_cache`] computes that cache ahead of
+//! the first real request.
+//!
+//! vLLM also accepts sampling and constrained decoding parameters OpenAI
+//! does not; set them with the builder methods, e.g.
+//! [`VllmProvider::guided_json`], and they are sent with every request.
+
+mod models;
+
+pub use models::*;
+
+use crate::openai::{self, OpenAIProvider};
+use crate::utils::LOCAL_SERVER_TIMEOUT;
+use async_trait::async_trait;
+use futures::StreamExt;
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// vLLM server provider
+#[derive(Debug, Clone)]
+pub struct VllmProvider {
+    inner: OpenAIProvider,
+    model: String,
+    params: VllmSamplingParams,
+}
+
+impl VllmProvider {
+    /// Default address of `vllm serve`
+    pub const DEFAULT_BASE_URL: &'static str = "http://localhost:8000/v1";
+
+    /// Create a provider for a local server started without an API key
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - Name of the served model, used by
+    ///   [`prefill_cache`](Provider::prefill_cache)
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the HTTP client cannot be created
+    pub fn new(model: impl Into<String>) -> Result<Self> {
+        Self::with_base_url(Self::DEFAULT_BASE_URL.to_string(), model)
+    }
+
+    /// Create a provider for a server at `base_url`
+    ///
+    /// The URL is normalized as in [`OpenAIProvider::with_base_url`].
+    pub fn with_base_url(base_url: String, model: impl Into<String>) -> Result<Self> {
+        let key = ApiKey::new(crate::utils::LOCAL_SERVER_KEY)?;
+        Self::with_credentials(key, base_url, model)
+    }
+
+    /// Create a provider for a server started with `--api-key`
+    pub fn with_credentials(
+        credentials: impl CredentialSource + 'static,
+        base_url: String,
+        model: impl Into<String>,
+    ) -> Result<Self> {
+        let base_url = crate::utils::normalize_base_url(&base_url)?;
+        let client = crate::utils::local_server_client()?;
+        Ok(Self {
+            inner: OpenAIProvider::with_client(Arc::new(credentials), base_url, client),
+            model: model.into(),
+            params: VllmSamplingParams::default(),
+        })
+    }
+
+    /// Connect with custom TLS settings, e.g. for a server behind an
+    /// internal CA or one requiring client certificates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Config`] if `tls` is invalid or not
+    /// supported by the enabled TLS backend; see [`TlsConfig::apply`].
+    pub fn with_tls(mut self, tls: &crate::tls::TlsConfig) -> Result<Self> {
+        let client = crate::utils::build_client(tls.apply(crate::utils::local_server_client_builder())?)?;
+        self.inner = self.inner.with_http_client(client);
+        Ok(self)
+    }
+
+    /// Log every request at debug level before it is sent
+    ///
+    /// See [`OpenAIProvider::with_debug_requests`].
+    pub fn with_debug_requests(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.with_debug_requests(enabled);
+        self
+    }
+
+    /// Sample from the `top_k` most likely tokens (-1 disables)
+    pub fn with_top_k(mut self, top_k: i32) -> Self {
+        self.params.top_k = Some(top_k);
+        self
+    }
+
+    /// Drop tokens less likely than `min_p` times the most likely token
+    pub fn with_min_p(mut self, min_p: f32) -> Self {
+        self.params.min_p = Some(min_p);
+        self
+    }
+
+    /// Penalize tokens already in the prompt or output (1.0 disables)
+    pub fn with_repetition_penalty(mut self, penalty: f32) -> Self {
+        self.params.repetition_penalty = Some(penalty);
+        self
+    }
+
+    /// Constrain output to JSON matching `schema`
+    ///
+    /// Replaces any other guided decoding mode.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use simple_agents_providers::vllm::VllmProvider;
+    ///
+    /// # fn example() -> simple_agents_types::Result<()> {
+    /// let provider = VllmProvider::new("meta-llama/Llama-3.1-8B-Instruct")?.guided_json(serde_json::json!({
+    ///     "type": "object",
+    ///     "properties": {"sentiment": {"enum": ["positive", "negative"]}},
+    ///     "required": ["sentiment"]
+    /// }));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn guided_json(mut self, schema: serde_json::Value) -> Self {
+        self.params.guided = Some(GuidedDecoding::Json(schema));
+        self
+    }
+
+    /// Constrain output to match the regular expression `pattern`
+    ///
+    /// Replaces any other guided decoding mode.
+    pub fn guided_regex(mut self, pattern: &str) -> Self {
+        self.params.guided = Some(GuidedDecoding::Regex(pattern.to_string()));
+        self
+    }
+
+    /// Constrain output to exactly one of `choices`
+    ///
+    /// Replaces any other guided decoding mode.
+    pub fn guided_choice<I, S>(mut self, choices: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.params.guided = Some(GuidedDecoding::Choice(choices.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// vLLM-specific parameters sent with every request
+    pub fn sampling_params(&self) -> &VllmSamplingParams {
+        &self.params
+    }
+
+    /// Get the base URL for this provider
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// URL chat completions are sent to
+    pub fn chat_url(&self) -> std::borrow::Cow<'_, str> {
+        self.inner.chat_url()
+    }
+
+    /// Name of the served model
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// A provider for the raw `/completions` endpoint of the same server.
+    ///
+    /// Prompts are rendered with the chat template
+    /// [guessed](openai::ChatTemplate::for_model) from the served model's
+    /// name; override it with
+    /// [`with_template`](openai::TextCompletionProvider::with_template).
+    /// vLLM sampling parameters are not sent.
+    pub fn text_completions(&self) -> openai::TextCompletionProvider {
+        openai::TextCompletionProvider::new(self.inner.clone())
+            .with_template(openai::ChatTemplate::for_model(&self.model))
+            .with_name("vllm")
+    }
+
+    /// Stream completion chunks for a request built by `transform_request`.
+    pub async fn execute_streaming(
+        &self,
+        mut req: ProviderRequest,
+    ) -> Result<impl futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin> {
+        req.body["stream"] = serde_json::Value::Bool(true);
+        let response = self.inner.send(req).await?;
+        let events = crate::streaming::sse_events(response.bytes_stream());
+        Ok(openai::chunk_stream(events.boxed()))
+    }
+}
+
+#[async_trait]
+impl Provider for VllmProvider {
+    fn name(&self) -> &str {
+        "vllm"
+    }
+
+    fn model_info(&self, _model: &str) -> Option<ModelInfoStatic> {
+        // Limits depend on how the server loaded the model, and runs are free
+        None
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            function_calling: true,
+            vision: false,
+            json_schema: false,
+            embeddings: false,
+            assistant_prefill: false,
+            documents: false,
+            max_tokens: 32768,
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        LOCAL_SERVER_TIMEOUT
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        // Checked here so the error names vllm rather than the inner provider
+        if req.assistant_prefill.is_some() {
+            return Err(ProviderError::Unsupported {
+                provider: self.name().to_string(),
+                feature: Feature::AssistantPrefill,
+            }
+            .into());
+        }
+
+        let mut request = self.inner.transform_request(req)?;
+        if self.params != VllmSamplingParams::default() {
+            let vllm_request = VllmCompletionRequest {
+                base: &request.body,
+                params: &self.params,
+            };
+            request.body = serde_json::to_value(&vllm_request)?;
+        }
+        Ok(request)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let mut response = self.inner.transform_response(resp)?;
+        response.provider = Some(self.name().to_string());
+        Ok(response)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        Ok(Box::new(self.execute_streaming(req).await?))
+    }
+
+    /// Generate one token after `system_prompt` so vLLM caches its prefix.
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        crate::utils::prefill_system_prompt(self, &self.model, system_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPLETION: &str = r#"{"id":"cmpl-1","object":"chat.completion","created":1700000000,"model":"llama-3.1-8b","choices":[{"index":0,"message":{"role":"assistant","content":"OK"},"finish_reason":"length"}],"usage":{"prompt_tokens":12,"completion_tokens":1,"total_tokens":13}}"#;
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = VllmProvider::new("llama-3.1-8b").unwrap();
+        assert_eq!(provider.name(), "vllm");
+        assert_eq!(provider.model(), "llama-3.1-8b");
+        assert!(provider.supports_model("any-served-model"));
+        assert_eq!(provider.chat_url(), "http://localhost:8000/v1/chat/completions");
+    }
+
+    #[test]
+    fn test_transform_request_sampling_params() {
+        let request = CompletionRequest::builder()
+            .model("llama-3.1-8b")
+            .message(Message::user("Call 555-0100"))
+            .temperature(0.2)
+            .build()
+            .unwrap();
+
+        let plain = VllmProvider::new("llama-3.1-8b").unwrap();
+        let body = plain.transform_request(&request).unwrap().body;
+        for field in ["top_k", "min_p", "repetition_penalty", "guided_json", "guided_regex"] {
+            assert!(body.get(field).is_none(), "{} set by default", field);
+        }
+
+        let provider = plain
+            .with_top_k(20)
+            .with_min_p(0.1)
+            .with_repetition_penalty(1.1)
+            .guided_json(serde_json::json!({"type": "object"}))
+            .guided_regex(r"\d{3}-\d{4}");
+        let body = provider.transform_request(&request).unwrap().body;
+        assert_eq!(body["model"], "llama-3.1-8b");
+        assert_eq!(body["temperature"], serde_json::json!(0.2f32));
+        assert_eq!(body["top_k"], 20);
+        assert_eq!(body["min_p"], serde_json::json!(0.1f32));
+        assert_eq!(body["repetition_penalty"], serde_json::json!(1.1f32));
+        // The last guided mode wins; vLLM rejects more than one
+        assert_eq!(body["guided_regex"], r"\d{3}-\d{4}");
+        assert!(body.get("guided_json").is_none());
+
+        let choice = provider.guided_choice(["yes", "no"]);
+        let body = choice.transform_request(&request).unwrap().body;
+        assert_eq!(body["guided_choice"], serde_json::json!(["yes", "no"]));
+        assert!(body.get("guided_regex").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prefill_cache_token_attaches_prompt() {
+        let prompt = "You are a support agent for Acme. Be brief.";
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "llama-3.1-8b",
+                "max_tokens": 1,
+                "messages": [{"role": "system", "content": prompt}]
+            })))
+            .with_status(200)
+            .with_body(COMPLETION)
+            .create_async()
+            .await;
+
+        let base_url = format!("{}/v1", server.url());
+        let provider = VllmProvider::with_base_url(base_url, "llama-3.1-8b").unwrap();
+        let token = provider.prefill_cache(prompt).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(token.provider(), Some("vllm"));
+        assert_eq!(token.model(), Some("llama-3.1-8b"));
+        assert_eq!(token.system_prompt(), Some(prompt));
+
+        let request = CompletionRequest::builder()
+            .model("llama-3.1-8b")
+            .message(Message::user("Where is my order?"))
+            .build()
+            .unwrap()
+            .with_prefill_token(&token);
+        let body = provider.transform_request(&request).unwrap().body;
+        assert_eq!(
+            body["messages"][0],
+            serde_json::json!({"role": "system", "content": prompt})
+        );
+        assert_eq!(body["messages"][1]["content"], "Where is my order?");
+    }
+
+    #[tokio::test]
+    async fn test_text_completions_use_model_template() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "llama-3.1-8b",
+                "prompt": "<|begin_of_text|><|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|>\
+                           <|start_header_id|>assistant<|end_header_id|>\n\n",
+                "stop": ["<|eot_id|>"]
+            })))
+            .with_status(200)
+            .with_body(r#"{"id":"cmpl-2","model":"llama-3.1-8b","choices":[{"text":"Hello!","index":0}]}"#)
+            .create_async()
+            .await;
+
+        let base_url = format!("{}/v1", server.url());
+        let provider = VllmProvider::with_base_url(base_url, "llama-3.1-8b").unwrap().text_completions();
+        let request = CompletionRequest::builder()
+            .model("llama-3.1-8b")
+            .message(Message::user("Hi"))
+            .build()
+            .unwrap();
+        let response = provider.complete(&request).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(response.content(), Some("Hello!"));
+        assert_eq!(response.provider.as_deref(), Some("vllm"));
+    }
+
+    #[tokio::test]
+    async fn test_prefill_cache_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(404)
+            .with_body(r#"{"error":{"message":"The model `other` does not exist."}}"#)
+            .create_async()
+            .await;
+
+        let provider = VllmProvider::with_base_url(format!("{}/v1", server.url()), "other").unwrap();
+        assert!(provider.prefill_cache("Be brief.").await.is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! vLLM request extensions to the OpenAI chat completions format.
+
+use serde::Serialize;
+
+/// Constrained decoding mode; vLLM accepts one per request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum GuidedDecoding {
+    /// Output must validate against this JSON schema
+    #[serde(rename = "guided_json")]
+    Json(serde_json::Value),
+    /// Output must match this regular expression
+    #[serde(rename = "guided_regex")]
+    Regex(String),
+    /// Output must be exactly one of these strings
+    #[serde(rename = "guided_choice")]
+    Choice(Vec<String>),
+}
+
+/// Sampling parameters vLLM accepts beyond the OpenAI ones
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VllmSamplingParams {
+    /// Sample from the `top_k` most likely tokens (-1 disables)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<i32>,
+
+    /// Minimum probability, relative to the most likely token (0.0-1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+
+    /// Penalty for tokens already in the prompt or output (1.0 disables)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repetition_penalty: Option<f32>,
+
+    /// Constrained decoding
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub guided: Option<GuidedDecoding>,
+}
+
+/// vLLM chat completion request
+///
+/// The OpenAI request body with [`VllmSamplingParams`] added at the top
+/// level, as vLLM expects.
+#[derive(Debug, Serialize)]
+pub struct VllmCompletionRequest<'a> {
+    /// OpenAI chat completion body
+    #[serde(flatten)]
+    pub base: &'a serde_json::Value,
+
+    /// vLLM-specific parameters
+    #[serde(flatten)]
+    pub params: &'a VllmSamplingParams,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_request() {
+        let base = serde_json::json!({"model": "llama-3.1-8b", "messages": [], "top_p": 0.9});
+        let params = VllmSamplingParams {
+            top_k: Some(40),
+            min_p: Some(0.05),
+            repetition_penalty: None,
+            guided: Some(GuidedDecoding::Choice(vec!["yes".to_string(), "no".to_string()])),
+        };
+        let request = VllmCompletionRequest { base: &base, params: &params };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "model": "llama-3.1-8b",
+                "messages": [],
+                "top_p": 0.9,
+                "top_k": 40,
+                "min_p": 0.05f32,
+                "guided_choice": ["yes", "no"]
+            })
+        );
+
+        let defaults = VllmCompletionRequest { base: &base, params: &VllmSamplingParams::default() };
+        assert_eq!(serde_json::to_value(&defaults).unwrap(), base);
+    }
+}
@@ -0,0 +1,357 @@
+//! Connection warmup for providers.
+//!
+//! The first request to a provider pays for DNS, TCP and TLS setup and is
+//! the first point at which bad credentials surface. [`WarmupProvider`]
+//! moves that cost to construction time by sending a minimal request.
+
+use async_trait::async_trait;
+use simple_agents_types::display::Pricing;
+use simple_agents_types::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Provider wrapper that pre-establishes connections.
+///
+/// [`WarmupProvider::new`] sends a one-token request before returning, so
+/// connection pools are primed and configuration errors (bad API key,
+/// unreachable base URL) are reported up front. [`WarmupProvider::new_lazy`]
+/// skips that request and treats the first real call as the warmup.
+pub struct WarmupProvider {
+    inner: Box<dyn Provider>,
+    is_warm: AtomicBool,
+    warmup_latency_ms: AtomicU64,
+}
+
+impl WarmupProvider {
+    /// Wrap `inner` and warm it up with a minimal request.
+    ///
+    /// The warmup uses the first of the provider's [`Provider::supported_models`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the inner provider's error if the warmup request fails, or
+    /// [`SimpleAgentsError::Config`] if the provider lists no models.
+    pub async fn new(inner: Box<dyn Provider>) -> Result<Self> {
+        let model = inner.supported_models().first().ok_or_else(|| {
+            SimpleAgentsError::Config(format!(
+                "Provider '{}' lists no supported models; use WarmupProvider::with_model",
+                inner.name()
+            ))
+        })?;
+
+        Self::with_model(inner, *model).await
+    }
+
+    /// Wrap `inner` and warm it up with a minimal request for `model`.
+    pub async fn with_model(inner: Box<dyn Provider>, model: impl Into<String>) -> Result<Self> {
+        let provider = Self::new_lazy(inner);
+        provider.warm_up(model.into()).await?;
+        Ok(provider)
+    }
+
+    /// Wrap `inner` without sending a warmup request.
+    ///
+    /// The first call to [`Provider::execute`] (or a stream being opened by
+    /// [`Provider::execute_stream`]) records the warmup latency.
+    pub fn new_lazy(inner: Box<dyn Provider>) -> Self {
+        Self {
+            inner,
+            is_warm: AtomicBool::new(false),
+            warmup_latency_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a request has completed through this provider.
+    pub fn is_warm(&self) -> bool {
+        self.is_warm.load(Ordering::Acquire)
+    }
+
+    /// Latency of the warmup request in milliseconds (0 until warm).
+    pub fn warmup_latency_ms(&self) -> u64 {
+        self.warmup_latency_ms.load(Ordering::Acquire)
+    }
+
+    /// Access the wrapped provider.
+    pub fn inner(&self) -> &dyn Provider {
+        self.inner.as_ref()
+    }
+
+    async fn warm_up(&self, model: String) -> Result<()> {
+        let request = CompletionRequest::builder()
+            .model(model)
+            .message(Message::user("ping"))
+            .max_tokens(1)
+            .build()?;
+
+        let provider_request = self.inner.transform_request(&request)?;
+        self.execute(provider_request).await?;
+
+        tracing::debug!(
+            provider = self.inner.name(),
+            latency_ms = self.warmup_latency_ms(),
+            "Provider warmed up"
+        );
+        Ok(())
+    }
+
+    fn record_warm(&self, latency: Duration) {
+        if !self.is_warm.load(Ordering::Acquire) {
+            self.warmup_latency_ms
+                .store(latency.as_millis() as u64, Ordering::Release);
+            self.is_warm.store(true, Ordering::Release);
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for WarmupProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.inner.transform_request(req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        let started = Instant::now();
+        let response = self.inner.execute(req).await?;
+        self.record_warm(started.elapsed());
+        Ok(response)
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        self.inner.supported_models()
+    }
+
+    fn sensitive_headers(&self) -> Vec<String> {
+        self.inner.sensitive_headers()
+    }
+
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        self.inner.model_info(model)
+    }
+
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.inner.pricing(model)
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        let started = Instant::now();
+        let stream = self.inner.execute_stream(req).await?;
+        self.record_warm(started.elapsed());
+        Ok(stream)
+    }
+
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        self.inner.prefill_cache(system_prompt).await
+    }
+
+    async fn execute_with_attempts(
+        &self,
+        req: &CompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<CompleteOutcome> {
+        let outcome = self.inner.execute_with_attempts(req, policy).await?;
+        if let Some(last) = outcome.attempts.attempts().last() {
+            self.record_warm(last.latency);
+        }
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::Arc;
+
+    struct MockProvider {
+        calls: Arc<AtomicU32>,
+        max_tokens_seen: Arc<AtomicU32>,
+        fail: bool,
+        models: &'static [&'static str],
+    }
+
+    impl MockProvider {
+        fn boxed(fail: bool, models: &'static [&'static str]) -> (Box<dyn Provider>, Arc<AtomicU32>, Arc<AtomicU32>) {
+            let calls = Arc::new(AtomicU32::new(0));
+            let max_tokens_seen = Arc::new(AtomicU32::new(0));
+            let provider = MockProvider {
+                calls: calls.clone(),
+                max_tokens_seen: max_tokens_seen.clone(),
+                fail,
+                models,
+            };
+            (Box::new(provider), calls, max_tokens_seen)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn supported_models(&self) -> &'static [&'static str] {
+            self.models
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com")
+                .with_body(serde_json::json!({ "model": req.model, "max_tokens": req.max_tokens })))
+        }
+
+        async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if let Some(max_tokens) = req.body["max_tokens"].as_u64() {
+                self.max_tokens_seen.store(max_tokens as u32, Ordering::SeqCst);
+            }
+            if self.fail {
+                return Err(SimpleAgentsError::Provider(ProviderError::InvalidApiKey));
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok(ProviderResponse::new(200, serde_json::json!({ "model": req.body["model"] })))
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp_1".to_string(),
+                model: resp.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: vec![CompletionChoice {
+                    index: 0,
+                    message: Message::assistant("pong"),
+                    finish_reason: FinishReason::Stop,
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                }],
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: Some("mock".to_string()),
+            })
+        }
+
+        fn sensitive_headers(&self) -> Vec<String> {
+            vec!["x-mock-key".to_string()]
+        }
+
+        fn pricing(&self, _model: &str) -> Option<Pricing> {
+            Some(Pricing { input_per_million: 1.0, output_per_million: 2.0 })
+        }
+
+        async fn execute_stream(
+            &self,
+            req: ProviderRequest,
+        ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+            let chunk = CompletionChunk {
+                id: "chunk".to_string(),
+                model: req.body["model"].as_str().unwrap_or_default().to_string(),
+                choices: Vec::new(),
+                created: None,
+                usage: None,
+            };
+            Ok(Box::new(futures::stream::iter(vec![Ok(chunk)])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eager_warmup() {
+        let (inner, calls, max_tokens_seen) = MockProvider::boxed(false, &["mock-model"]);
+        let provider = WarmupProvider::new(inner).await.unwrap();
+
+        assert!(provider.is_warm());
+        assert!(provider.warmup_latency_ms() >= 5);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(max_tokens_seen.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_eager_warmup_surfaces_errors() {
+        let (inner, _, _) = MockProvider::boxed(true, &["mock-model"]);
+        let result = WarmupProvider::new(inner).await;
+
+        assert!(matches!(
+            result,
+            Err(SimpleAgentsError::Provider(ProviderError::InvalidApiKey))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_eager_warmup_requires_model() {
+        let (inner, calls, _) = MockProvider::boxed(false, &[]);
+        assert!(matches!(
+            WarmupProvider::new(inner).await,
+            Err(SimpleAgentsError::Config(_))
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let (inner, calls, _) = MockProvider::boxed(false, &[]);
+        let provider = WarmupProvider::with_model(inner, "any-model").await.unwrap();
+        assert!(provider.is_warm());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_forwards_hooks_and_streams() {
+        use futures::StreamExt;
+
+        let (inner, _, _) = MockProvider::boxed(false, &["mock-model"]);
+        let provider = WarmupProvider::new_lazy(inner);
+        assert_eq!(provider.sensitive_headers(), ["x-mock-key"]);
+        assert_eq!(provider.pricing("mock-model").unwrap().output_per_million, 2.0);
+
+        let request = CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::user("Hello"))
+            .stream(true)
+            .build()
+            .unwrap();
+        let stream = provider.execute_stream(provider.transform_request(&request).unwrap()).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks[0].as_ref().unwrap().model, "mock-model");
+        assert!(provider.is_warm());
+    }
+
+    #[tokio::test]
+    async fn test_lazy_warmup_on_first_use() {
+        let (inner, calls, _) = MockProvider::boxed(false, &["mock-model"]);
+        let provider = WarmupProvider::new_lazy(inner);
+
+        assert!(!provider.is_warm());
+        assert_eq!(provider.warmup_latency_ms(), 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let request = CompletionRequest::builder()
+            .model("mock-model")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap();
+        let response = provider.complete(&request).await.unwrap();
+
+        assert_eq!(response.content(), Some("pong"));
+        assert!(provider.is_warm());
+        assert!(provider.warmup_latency_ms() >= 5);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,311 @@
+//! HTTP webhook delivery of [`Event`]s.
+//!
+//! [`WebhookNotifier`] POSTs each event as JSON, signed with HMAC-SHA256 so
+//! the receiver can check it came from a holder of the shared secret.
+//! Receivers verify requests with [`verify_signature`].
+//!
+//! The signature covers `"{timestamp}.{body}"`, where the timestamp is the
+//! [`TIMESTAMP_HEADER`] value, so a captured request cannot be replayed
+//! with a fresh timestamp. Receivers should also reject stale timestamps.
+
+use async_trait::async_trait;
+use simple_agents_types::prelude::*;
+use std::time::Duration;
+
+/// Header carrying the event type, e.g. `circuit_opened`
+pub const EVENT_HEADER: &str = "x-simple-agents-event";
+
+/// Header carrying the Unix time (seconds) the request was signed
+pub const TIMESTAMP_HEADER: &str = "x-simple-agents-timestamp";
+
+/// Header carrying `sha256=<hex HMAC>` of the timestamp and body
+pub const SIGNATURE_HEADER: &str = "x-simple-agents-signature";
+
+/// Delivers events to an HTTP endpoint.
+///
+/// Failed deliveries are retried per the retry configuration (5xx, 429 and
+/// network errors only), then logged and dropped.
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_providers::batch::BatchExecutor;
+/// use simple_agents_providers::openai::OpenAIProvider;
+/// use simple_agents_providers::webhook::WebhookNotifier;
+/// use simple_agents_types::prelude::*;
+/// use std::sync::Arc;
+///
+/// # fn example() -> Result<()> {
+/// let notifier = WebhookNotifier::new("https://ops.example.com/hooks/llm", "whsec_shared_secret")?;
+/// let provider = OpenAIProvider::new(ApiKey::new("sk-...")?)?;
+/// let executor = BatchExecutor::new(Arc::new(provider), 4).with_notifier(Arc::new(notifier));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    key: ring::hmac::Key,
+    client: reqwest::Client,
+    retry_config: RetryConfig,
+}
+
+impl std::fmt::Debug for WebhookNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookNotifier")
+            .field("url", &self.url)
+            .field("retry_config", &self.retry_config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebhookNotifier {
+    /// Timeout for one delivery attempt
+    pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Deliver events to `url`, signed with `secret`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a config error if `url` is not an absolute `http(s)` URL or
+    /// the HTTP client cannot be created.
+    pub fn new(url: impl Into<String>, secret: impl AsRef<[u8]>) -> Result<Self> {
+        let url = url.into();
+        match url::Url::parse(&url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            _ => return Err(SimpleAgentsError::Config(format!("Invalid webhook URL: {}", url))),
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Self::TIMEOUT)
+            .build()
+            .map_err(|e| SimpleAgentsError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            url,
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_ref()),
+            client,
+            retry_config: RetryConfig::default(),
+        })
+    }
+
+    /// Set the retry configuration for deliveries (builder pattern).
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Endpoint events are delivered to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Deliver `event`, returning the error of the last attempt on failure.
+    ///
+    /// [`EventNotifier::notify`] calls this and logs the error.
+    pub async fn deliver(&self, event: &Event) -> Result<()> {
+        let body = serde_json::to_vec(event)?;
+        crate::retry::execute_with_classifier(&self.retry_config, crate::retry::classify, || {
+            self.send_once(event.kind(), &body)
+        })
+        .await
+    }
+
+    /// Sign and POST `body` once, mapping non-2xx statuses to errors.
+    async fn send_once(&self, kind: &str, body: &[u8]) -> Result<()> {
+        let timestamp = simple_agents_types::response::unix_timestamp_now().to_string();
+        let signature = sign(&self.key, &timestamp, body);
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, kind)
+            .header(TIMESTAMP_HEADER, &timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|e| SimpleAgentsError::Network(format!("Webhook delivery failed: {}", e)))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let message = format!("webhook returned {}", status);
+        Err(match status.as_u16() {
+            429 => ProviderError::RateLimit { retry_after: None },
+            500..=599 => ProviderError::ServerError(message),
+            _ => ProviderError::BadRequest(message),
+        }
+        .into())
+    }
+}
+
+#[async_trait]
+impl EventNotifier for WebhookNotifier {
+    async fn notify(&self, event: Event) {
+        if let Err(e) = self.deliver(&event).await {
+            tracing::warn!(url = %self.url, event = event.kind(), error = %e, "Dropping undeliverable event");
+        }
+    }
+}
+
+/// `sha256=<hex>` signature of a webhook request.
+fn sign(key: &ring::hmac::Key, timestamp: &str, body: &[u8]) -> String {
+    let mut context = ring::hmac::Context::with_key(key);
+    context.update(timestamp.as_bytes());
+    context.update(b".");
+    context.update(body);
+    let hex: String = context.sign().as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Check the signature of a received webhook request.
+///
+/// `timestamp` and `signature` are the [`TIMESTAMP_HEADER`] and
+/// [`SIGNATURE_HEADER`] values and `body` the raw request body. The
+/// comparison is constant-time. Freshness of `timestamp` is left to the
+/// caller.
+///
+/// # Example
+/// ```
+/// use simple_agents_providers::webhook::verify_signature;
+///
+/// let body = br#"{"type":"circuit_opened"}"#;
+/// assert!(!verify_signature(b"whsec_shared_secret", "1700000000", body, "sha256=00"));
+/// ```
+pub fn verify_signature(secret: &[u8], timestamp: &str, body: &[u8], signature: &str) -> bool {
+    let Some(tag) = signature.strip_prefix("sha256=").and_then(decode_hex) else {
+        return false;
+    };
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret);
+    let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'.');
+    message.extend_from_slice(body);
+    ring::hmac::verify(&key, &message, &tag).is_ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => {
+                let digit = |b: &u8| char::from(*b).to_digit(16);
+                Some((digit(high)? * 16 + digit(low)?) as u8)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_agents_types::event::BatchState;
+
+    fn event() -> Event {
+        Event::BatchStateChanged {
+            batch_id: "batch_abc123".to_string(),
+            state: BatchState::Completed,
+            total: 10,
+            completed: 9,
+            failed: 1,
+        }
+    }
+
+    fn fast_retry(max_attempts: u32) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+            backoff_multiplier: 1.0,
+            jitter: false,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"secret");
+        let signature = sign(&key, "1700000000", b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+
+        assert!(verify_signature(b"secret", "1700000000", b"{}", &signature));
+        assert!(!verify_signature(b"other", "1700000000", b"{}", &signature));
+        assert!(!verify_signature(b"secret", "1700000001", b"{}", &signature));
+        assert!(!verify_signature(b"secret", "1700000000", b"{ }", &signature));
+        assert!(!verify_signature(b"secret", "1700000000", b"{}", "sha256=zz"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_url() {
+        assert!(WebhookNotifier::new("ftp://example.com/hook", "secret").is_err());
+        assert!(WebhookNotifier::new("/hooks/llm", "secret").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delivers_signed_event() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/hooks/llm")
+            .match_header(EVENT_HEADER, "batch_state_changed")
+            .match_header(SIGNATURE_HEADER, mockito::Matcher::Regex("^sha256=[0-9a-f]{64}$".to_string()))
+            .match_body(mockito::Matcher::Json(serde_json::to_value(event()).unwrap()))
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let notifier = WebhookNotifier::new(format!("{}/hooks/llm", server.url()), "secret").unwrap();
+        notifier.deliver(&event()).await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_signature_verifies_on_receiving_side() {
+        // Capture the request as a receiver would see it
+        let captured = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let sink = captured.clone();
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/hook")
+            .with_status(200)
+            .with_body_from_request(move |request| {
+                let header = |name| request.header(name)[0].to_str().unwrap().to_string();
+                let body = request.body().unwrap().clone();
+                *sink.lock().unwrap() = Some((header(TIMESTAMP_HEADER), header(SIGNATURE_HEADER), body));
+                Vec::new()
+            })
+            .create_async()
+            .await;
+
+        let notifier = WebhookNotifier::new(format!("{}/hook", server.url()), "whsec_test").unwrap();
+        notifier.notify(event()).await;
+
+        let (timestamp, signature, body) = captured.lock().unwrap().take().expect("webhook not called");
+        assert!(verify_signature(b"whsec_test", &timestamp, &body, &signature));
+        assert!(!verify_signature(b"whsec_wrong", &timestamp, &body, &signature));
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_only() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server.mock("POST", "/flaky").with_status(503).expect(3).create_async().await;
+        let rejected = server.mock("POST", "/gone").with_status(410).expect(1).create_async().await;
+
+        let flaky = WebhookNotifier::new(format!("{}/flaky", server.url()), "secret")
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+        assert!(matches!(
+            flaky.deliver(&event()).await,
+            Err(SimpleAgentsError::Provider(ProviderError::ServerError(_)))
+        ));
+        failing.assert_async().await;
+
+        let gone = WebhookNotifier::new(format!("{}/gone", server.url()), "secret")
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+        // Failures are logged, not returned
+        gone.notify(event()).await;
+        rejected.assert_async().await;
+    }
+}
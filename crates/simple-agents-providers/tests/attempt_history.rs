@@ -0,0 +1,126 @@
+//! Pass-through wrappers forward `execute_with_attempts`, so a wrapped
+//! provider retries and reports its attempts exactly as it does alone.
+
+use async_trait::async_trait;
+use simple_agents_providers::fallback::FallbackProvider;
+use simple_agents_providers::scheduler::{ScheduledProvider, SchedulerConfig};
+use simple_agents_providers::telemetry::TracedProvider;
+use simple_agents_providers::warmup::WarmupProvider;
+use simple_agents_types::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Retries on its own and reports a fixed history: one overloaded attempt,
+/// then a success
+struct SelfRetryingProvider {
+    calls: Arc<AtomicU32>,
+}
+
+impl SelfRetryingProvider {
+    fn history() -> AttemptHistory {
+        let mut history = AttemptHistory::new();
+        history.push(
+            Attempt::failed("self-retrying", 1, ErrorCode::ServerError, Duration::from_millis(3))
+                .with_backoff(Duration::from_millis(10)),
+        );
+        history.push(Attempt::succeeded("self-retrying", 2, Duration::from_millis(4)));
+        history
+    }
+}
+
+#[async_trait]
+impl Provider for SelfRetryingProvider {
+    fn name(&self) -> &str {
+        "self-retrying"
+    }
+
+    fn transform_request(&self, _req: &CompletionRequest) -> Result<ProviderRequest> {
+        Ok(ProviderRequest::new("https://api.example.com"))
+    }
+
+    async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+        Ok(ProviderResponse::new(200, serde_json::json!({})))
+    }
+
+    fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+        Ok(CompletionResponse {
+            id: "resp".to_string(),
+            model: "mock-model".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant("ok"),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(1, 1),
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        })
+    }
+
+    async fn execute_with_attempts(
+        &self,
+        req: &CompletionRequest,
+        _policy: &RetryPolicy,
+    ) -> Result<CompleteOutcome> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let response = self.complete(req).await?;
+        Ok(CompleteOutcome {
+            response,
+            attempts: Self::history(),
+        })
+    }
+}
+
+fn provider() -> (SelfRetryingProvider, Arc<AtomicU32>) {
+    let calls = Arc::new(AtomicU32::new(0));
+    (SelfRetryingProvider { calls: calls.clone() }, calls)
+}
+
+fn request() -> CompletionRequest {
+    CompletionRequest::builder()
+        .model("mock-model")
+        .message(Message::user("Hello"))
+        .build()
+        .unwrap()
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy::new(RetryConfig::default(), |_: Duration| async {})
+}
+
+#[tokio::test]
+async fn test_wrapped_provider_reports_the_bare_history() {
+    let (bare, _) = provider();
+    let expected = bare.execute_with_attempts(&request(), &policy()).await.unwrap().attempts;
+    assert_eq!(expected, SelfRetryingProvider::history());
+
+    let (inner, calls) = provider();
+    let traced = TracedProvider::new(inner);
+    let outcome = traced.execute_with_attempts(&request(), &policy()).await.unwrap();
+    assert_eq!(outcome.attempts, expected);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (inner, calls) = provider();
+    let scheduled = ScheduledProvider::new(inner, SchedulerConfig::default());
+    let outcome = scheduled.execute_with_attempts(&request(), &policy()).await.unwrap();
+    assert_eq!(outcome.attempts, expected);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (inner, calls) = provider();
+    let warmup = WarmupProvider::new_lazy(Box::new(inner));
+    let outcome = warmup.execute_with_attempts(&request(), &policy()).await.unwrap();
+    assert_eq!(outcome.attempts, expected);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(warmup.is_warm());
+
+    let (inner, calls) = provider();
+    let fallback = FallbackProvider::new(vec![Box::new(inner)]).unwrap();
+    let outcome = fallback.execute_with_attempts(&request(), &policy()).await.unwrap();
+    assert_eq!(outcome.attempts, expected);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
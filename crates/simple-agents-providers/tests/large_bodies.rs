@@ -0,0 +1,159 @@
+//! Memory used to parse multi-megabyte response bodies.
+//!
+//! A counting global allocator records the peak number of bytes live while
+//! each parser runs. Everything is measured in one test so no other test
+//! allocates concurrently.
+
+use serde_json::json;
+use simple_agents_providers::openai::OpenAICompletionResponse;
+use simple_agents_providers::provider_kit::{parse_json_body, JsonLines, DEFAULT_RAW_BODY_LIMIT};
+use simple_agents_types::batch::{BatchResponseFile, BatchResult};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grew(bytes: usize) {
+    let live = LIVE.fetch_add(bytes, Ordering::SeqCst) + bytes;
+    PEAK.fetch_max(live, Ordering::SeqCst);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                grew(new_size - layout.size());
+            } else {
+                LIVE.fetch_sub(layout.size() - new_size, Ordering::SeqCst);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Peak bytes allocated by `f` on top of what was live before it ran
+fn peak_during<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = LIVE.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let result = f();
+    (result, PEAK.load(Ordering::SeqCst) - before)
+}
+
+/// An n=4 chat completion with token logprobs, about 4.7 MB
+fn large_completion() -> Vec<u8> {
+    let logprobs: Vec<_> = (0..15_000)
+        .map(|_| json!({"token": "ab", "logprob": -0.25, "bytes": [97, 98], "top_logprobs": []}))
+        .collect();
+    let choices: Vec<_> = (0..4)
+        .map(|index| {
+            json!({
+                "index": index,
+                "message": {"role": "assistant", "content": "ab".repeat(100_000)},
+                "logprobs": {"content": logprobs},
+                "finish_reason": "length"
+            })
+        })
+        .collect();
+    serde_json::to_vec(&json!({
+        "id": "chatcmpl-large",
+        "object": "chat.completion",
+        "created": 1700000000,
+        "model": "gpt-4o-mini",
+        "choices": choices,
+        "usage": {"prompt_tokens": 10, "completion_tokens": 60_000, "total_tokens": 60_010}
+    }))
+    .unwrap()
+}
+
+/// A batch output file of 2,000 results, about 4 MB
+fn large_batch_output() -> Vec<u8> {
+    let mut jsonl = Vec::new();
+    for i in 0..2_000 {
+        let line = json!({
+            "id": format!("batch_req_{}", i),
+            "custom_id": format!("request-{}", i),
+            "response": {
+                "status_code": 200,
+                "request_id": format!("req_{}", i),
+                "body": {
+                    "id": format!("chatcmpl-{}", i),
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "x".repeat(2_000)}}]
+                }
+            },
+            "error": null
+        });
+        serde_json::to_writer(&mut jsonl, &line).unwrap();
+        jsonl.push(b'\n');
+    }
+    jsonl
+}
+
+#[test]
+fn test_large_bodies_are_parsed_without_extra_copies() {
+    let body = large_completion();
+    assert!(body.len() > 4_000_000, "{} bytes", body.len());
+
+    let (via_value, value_peak) = peak_during(|| {
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        serde_json::from_value::<OpenAICompletionResponse>(value).unwrap()
+    });
+    let (typed, typed_peak) =
+        peak_during(|| parse_json_body::<OpenAICompletionResponse>(&body, DEFAULT_RAW_BODY_LIMIT).unwrap());
+    assert_eq!(typed.choices.len(), 4);
+    assert_eq!(typed.choices[3].message.message.content, via_value.choices[3].message.message.content);
+    // The typed parse holds the four contents (800 KB) and little else
+    assert!(typed_peak < 1_500_000, "typed parse peaked at {} bytes", typed_peak);
+    assert!(typed_peak * 4 < value_peak, "typed {} bytes, via Value {} bytes", typed_peak, value_peak);
+    drop((via_value, typed));
+
+    let jsonl = large_batch_output();
+    assert!(jsonl.len() > 4_000_000, "{} bytes", jsonl.len());
+    let text = std::str::from_utf8(&jsonl).unwrap();
+
+    let (all, whole_file_peak) = peak_during(|| BatchResponseFile::parse(text).unwrap());
+    assert_eq!(all.len(), 2_000);
+    drop(all);
+
+    let (count, streamed_peak) = peak_during(|| {
+        let mut lines = JsonLines::<BatchResult>::new();
+        let mut count = 0;
+        for chunk in jsonl.chunks(64 * 1024) {
+            lines.push(chunk);
+            while let Some(result) = lines.next_value() {
+                assert!(result.unwrap().response.is_some());
+                count += 1;
+            }
+        }
+        assert!(lines.finish().is_none());
+        count
+    });
+    assert_eq!(count, 2_000);
+    // One chunk plus one result at a time
+    assert!(streamed_peak < 512 * 1024, "streamed parse peaked at {} bytes", streamed_peak);
+    assert!(
+        streamed_peak * 8 < whole_file_peak,
+        "streamed {} bytes, whole file {} bytes",
+        streamed_peak,
+        whole_file_peak
+    );
+}
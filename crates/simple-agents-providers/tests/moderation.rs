@@ -0,0 +1,89 @@
+//! Moderation stays in force when `ModerationMiddleware` sits under a
+//! wrapper that drives the lower-level provider hooks, such as
+//! `CachingProvider`.
+
+use async_trait::async_trait;
+use simple_agents_cache::{CachingProvider, InMemoryCache};
+use simple_agents_providers::openai::{ModerationMiddleware, ModerationsClient};
+use simple_agents_types::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Answers every request and counts the upstream calls
+struct CountingProvider {
+    calls: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Provider for CountingProvider {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        Ok(ProviderRequest::new("https://api.example.com").with_body(serde_json::to_value(req)?))
+    }
+
+    async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(ProviderResponse::new(200, serde_json::json!({})))
+    }
+
+    fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+        Ok(CompletionResponse {
+            id: "resp".to_string(),
+            model: "mock-model".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant("ok"),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(1, 1),
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_caching_provider_cannot_bypass_moderation() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/moderations")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({"input": ["I will hurt you"]})))
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "id": "modr-1",
+                "model": "omni-moderation-latest",
+                "results": [{"flagged": true, "categories": {"violence": true}, "category_scores": {}}]
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let api_key = ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+    let moderations = ModerationsClient::with_base_url(api_key, server.url()).unwrap();
+    let calls = Arc::new(AtomicU32::new(0));
+    let moderated = ModerationMiddleware::new(CountingProvider { calls: calls.clone() }, moderations);
+    let cache = InMemoryCache::new(1024 * 1024, 100);
+    let provider = CachingProvider::new(Box::new(moderated), Box::new(cache), Duration::from_secs(60));
+
+    let request = CompletionRequest::builder()
+        .model("gpt-4")
+        .message(Message::user("I will hurt you"))
+        .build()
+        .unwrap();
+    let err = provider.complete(&request).await.unwrap_err();
+
+    assert!(matches!(err.root(), SimpleAgentsError::Validation(_)));
+    assert!(err.to_string().contains("messages[0] flagged by moderation (violence)"));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    mock.assert_async().await;
+}
@@ -119,7 +119,7 @@ async fn test_local_proxy_multiple_requests() {
     )
     .expect("Failed to create provider");
 
-    let test_prompts = vec![
+    let test_prompts = [
         "Count from 1 to 3.",
         "What is 2+2?",
         "Say 'test complete'.",
@@ -190,12 +190,11 @@ async fn test_local_proxy_invalid_model() {
     match result {
         Err(e) => {
             println!("✅ Expected error received: {}", e);
-            // Check if it's a provider error
+            // Some deployments report unknown models as bad requests
             assert!(
-                format!("{}", e).contains("Provider") ||
-                format!("{}", e).contains("Model") ||
-                format!("{}", e).contains("error"),
-                "Expected provider/model error, got: {}",
+                matches!(e.code(), ErrorCode::ModelNotFound | ErrorCode::InvalidRequest),
+                "Expected a model not found error, got {}: {}",
+                e.code(),
                 e
             );
         }
@@ -0,0 +1,315 @@
+//! Routing and response normalization of the OpenAI-compatible proxy.
+//!
+//! Requests are sent straight to the router, so no port is bound.
+
+#![cfg(feature = "proxy")]
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::response::Response;
+use futures::stream;
+use serde_json::{json, Value};
+use simple_agents_providers::proxy::{OpenAIProxyServer, ProxyConfig};
+use simple_agents_types::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+
+/// Echoes the last user message and records the requests it receives.
+#[derive(Default)]
+struct EchoProvider {
+    calls: AtomicUsize,
+    requests: Mutex<Vec<Value>>,
+}
+
+#[async_trait]
+impl Provider for EchoProvider {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let body = serde_json::to_value(req)?;
+        self.requests.lock().unwrap().push(body.clone());
+        Ok(ProviderRequest::new("http://echo.invalid").with_body(body))
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if req.body["model"] == "missing" {
+            return Err(SimpleAgentsError::Provider(ProviderError::ModelNotFound("missing".to_string())));
+        }
+        Ok(ProviderResponse::new(200, req.body))
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let request: CompletionRequest = serde_json::from_value(resp.body)?;
+        let last = request.messages.last().map(|m| m.content.clone()).unwrap_or_default();
+        Ok(CompletionResponse {
+            id: "resp-1".to_string(),
+            model: request.model,
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(format!("echo: {}", last)),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(3, 2),
+            created: Some(1_700_000_000),
+            created_synthesized: false,
+            provider: Some("echo".to_string()),
+        })
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        &["echo-1", "echo-2"]
+    }
+
+    async fn execute_stream(
+        &self,
+        req: ProviderRequest,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+        let model = req.body["model"].as_str().unwrap_or_default().to_string();
+        let chunk = |content: Option<&str>, finish_reason| CompletionChunk {
+            id: "chunk-1".to_string(),
+            model: model.clone(),
+            choices: vec![ChoiceDelta {
+                index: 0,
+                delta: MessageDelta {
+                    role: None,
+                    content: content.map(str::to_string),
+                },
+                finish_reason,
+            }],
+            created: None,
+            usage: None,
+        };
+        let chunks = vec![
+            Ok(chunk(Some("Hel"), None)),
+            Ok(chunk(Some("lo"), None)),
+            Ok(chunk(None, Some(FinishReason::Stop))),
+        ];
+        Ok(Box::new(stream::iter(chunks)))
+    }
+}
+
+#[derive(Default)]
+struct MapCache(Mutex<HashMap<String, Vec<u8>>>);
+
+#[async_trait]
+impl Cache for MapCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, _ttl: Duration) -> Result<()> {
+        self.0.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.0.lock().unwrap().clear();
+        Ok(())
+    }
+}
+
+async fn post(router: &axum::Router, body: Value) -> Response {
+    let request = Request::post("/v1/chat/completions")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    router.clone().oneshot(request).await.unwrap()
+}
+
+async fn body_text(response: Response) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+async fn body_json(response: Response) -> Value {
+    serde_json::from_str(&body_text(response).await).unwrap()
+}
+
+fn chat(model: &str, content: Value) -> Value {
+    json!({"model": model, "messages": [{"role": "user", "content": content}]})
+}
+
+#[tokio::test]
+async fn test_completion_is_normalized() {
+    let provider = Arc::new(EchoProvider::default());
+    let router = OpenAIProxyServer::new(ProxyConfig::new(provider.clone())).router();
+
+    let response = post(&router, chat("echo-1", json!("Hi"))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        body_json(response).await,
+        json!({
+            "id": "resp-1",
+            "object": "chat.completion",
+            "created": 1_700_000_000,
+            "model": "echo-1",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "echo: Hi"},
+                "finish_reason": "stop",
+                "logprobs": null,
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5},
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_content_parts_and_aliases() {
+    let provider = Arc::new(EchoProvider::default());
+    let config = ProxyConfig::new(provider.clone()).with_model_alias("gpt-4o", "echo-2");
+    let router = OpenAIProxyServer::new(config).router();
+
+    let content = json!([
+        {"type": "text", "text": "Describe"},
+        {"type": "image_url", "image_url": {"url": "https://example.com/cat.png", "detail": "low"}},
+        {"type": "text", "text": "briefly"},
+    ]);
+    let mut body = chat("gpt-4o", content);
+    body["max_completion_tokens"] = json!(64);
+    body["stop"] = json!("END");
+    let response = post(&router, body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_json(response).await["model"], "echo-2");
+
+    let sent = provider.requests.lock().unwrap()[0].clone();
+    assert_eq!(sent["model"], "echo-2");
+    assert_eq!(sent["max_tokens"], 64);
+    assert_eq!(sent["stop"], json!(["END"]));
+    assert_eq!(sent["messages"][0]["content"], "Describe\nbriefly");
+    let images = json!([{"url": "https://example.com/cat.png", "detail": "low"}]);
+    assert_eq!(sent["messages"][0]["images"], images);
+}
+
+#[tokio::test]
+async fn test_errors_use_openai_format() {
+    let router = OpenAIProxyServer::new(ProxyConfig::new(Arc::new(EchoProvider::default()))).router();
+
+    let response = post(&router, chat("missing", json!("Hi"))).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let error = body_json(response).await;
+    assert_eq!(error["error"]["type"], "invalid_request_error");
+    assert_eq!(error["error"]["code"], "model_not_found");
+
+    let response = post(&router, json!({"model": "echo-1", "messages": []})).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(body_json(response).await["error"]["code"], "invalid_request");
+
+    let response = post(&router, chat("echo-1", json!([{"type": "input_audio"}]))).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let message = body_json(response).await["error"]["message"].as_str().unwrap().to_string();
+    assert!(message.contains("input_audio"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_streaming_sends_chunks_then_done() {
+    let router = OpenAIProxyServer::new(ProxyConfig::new(Arc::new(EchoProvider::default()))).router();
+
+    let mut body = chat("echo-1", json!("Hi"));
+    body["stream"] = json!(true);
+    let response = post(&router, body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    let text = body_text(response).await;
+    let events: Vec<&str> = text.lines().filter_map(|line| line.strip_prefix("data: ")).collect();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[3], "[DONE]");
+
+    let chunks: Vec<Value> = events[..3].iter().map(|e| serde_json::from_str(e).unwrap()).collect();
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "Hello");
+    assert!(chunks.iter().all(|chunk| chunk["object"] == "chat.completion.chunk"));
+    assert!(chunks.iter().all(|chunk| chunk["created"].as_i64().unwrap() > 0));
+    assert_eq!(chunks[2]["choices"][0]["finish_reason"], "stop");
+}
+
+#[tokio::test]
+async fn test_cache_serves_repeated_requests() {
+    let provider = Arc::new(EchoProvider::default());
+    let cache = Arc::new(MapCache::default());
+    let config = ProxyConfig::new(provider.clone()).with_cache(cache, Duration::from_secs(60));
+    let router = OpenAIProxyServer::new(config).router();
+
+    let first = body_json(post(&router, chat("echo-1", json!("Hi"))).await).await;
+    let second = body_json(post(&router, chat("echo-1", json!("Hi"))).await).await;
+    assert_eq!(first, second);
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+
+    post(&router, chat("echo-1", json!("Bye"))).await;
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_rate_limit_rejects_excess_requests() {
+    let provider = Arc::new(EchoProvider::default());
+    let config = ProxyConfig::new(provider.clone()).with_rate_limit(2, Duration::from_secs(60));
+    let router = OpenAIProxyServer::new(config).router();
+
+    for _ in 0..2 {
+        assert_eq!(post(&router, chat("echo-1", json!("Hi"))).await.status(), StatusCode::OK);
+    }
+    let response = post(&router, chat("echo-1", json!("Hi"))).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "30");
+    assert_eq!(body_json(response).await["error"]["type"], "rate_limit_error");
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_models_lists_provider_models_and_aliases() {
+    let config = ProxyConfig::new(Arc::new(EchoProvider::default())).with_model_alias("gpt-4o", "echo-2");
+    let router = OpenAIProxyServer::new(config).router();
+
+    let request = Request::get("/v1/models").body(Body::empty()).unwrap();
+    let models = body_json(router.oneshot(request).await.unwrap()).await;
+    assert_eq!(models["object"], "list");
+    let data = models["data"].as_array().unwrap();
+    let ids: Vec<&str> = data.iter().map(|model| model["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["echo-1", "echo-2", "gpt-4o"]);
+    assert_eq!(models["data"][0]["owned_by"], "echo");
+}
+
+#[tokio::test]
+async fn test_api_key_is_required_when_set() {
+    let provider = Arc::new(EchoProvider::default());
+    let api_key = ApiKey::new("proxy-key-0123456789abcdef").unwrap();
+    let config = ProxyConfig::new(provider.clone()).with_api_key(api_key);
+    let router = OpenAIProxyServer::new(config).router();
+
+    let response = post(&router, chat("echo-1", json!("Hi"))).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(body_json(response).await["error"]["type"], "authentication_error");
+
+    let attempts = [
+        ("wrong-key-0123456789abcdef", StatusCode::UNAUTHORIZED),
+        ("proxy-key-0123456789abcdef", StatusCode::OK),
+    ];
+    for (key, status) in attempts {
+        let request = Request::post("/v1/chat/completions")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", key))
+            .body(Body::from(chat("echo-1", json!("Hi")).to_string()))
+            .unwrap();
+        assert_eq!(router.clone().oneshot(request).await.unwrap().status(), status);
+    }
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+}
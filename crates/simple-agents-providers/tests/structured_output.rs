@@ -0,0 +1,80 @@
+//! Schema-constrained output through the OpenAI provider: the schema
+//! derived by `with_json_schema_output` reaches the request body, and the
+//! reply parses back into the same type.
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use simple_agents_providers::openai::OpenAIProvider;
+use simple_agents_types::prelude::*;
+
+#[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+struct Forecast {
+    city: String,
+    celsius: i32,
+}
+
+fn forecast_request() -> CompletionRequest {
+    CompletionRequest::builder()
+        .model("gpt-4o")
+        .message(Message::user("Weather in Oslo as JSON"))
+        .build()
+        .unwrap()
+        .with_json_schema_output::<Forecast>(true)
+        .unwrap()
+}
+
+fn provider(base_url: String) -> OpenAIProvider {
+    let api_key = ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+    OpenAIProvider::with_base_url(api_key, base_url).unwrap()
+}
+
+#[test]
+fn test_json_schema_output_is_sent() {
+    let body = provider("https://api.openai.com/v1".to_string())
+        .transform_request(&forecast_request())
+        .unwrap()
+        .body;
+
+    let format = &body["response_format"];
+    assert_eq!(format["type"], "json_schema");
+    assert_eq!(format["json_schema"]["name"], "Forecast");
+    assert_eq!(format["json_schema"]["strict"], true);
+    let schema = &format["json_schema"]["schema"];
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["celsius"]["type"], "integer");
+    assert_eq!(schema["required"], serde_json::json!(["celsius", "city"]));
+    assert_eq!(schema["additionalProperties"], false);
+}
+
+#[tokio::test]
+async fn test_json_schema_output_round_trip() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "response_format": {"type": "json_schema", "json_schema": {"name": "Forecast", "strict": true}}
+        })))
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1700000000,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "{\"city\":\"Oslo\",\"celsius\":4}"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 8, "total_tokens": 28}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let response = provider(server.url()).complete(&forecast_request()).await.unwrap();
+    mock.assert_async().await;
+    let forecast: Forecast = response.parse_content_as().unwrap();
+    assert_eq!(forecast, Forecast { city: "Oslo".to_string(), celsius: 4 });
+}
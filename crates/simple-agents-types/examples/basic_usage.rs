@@ -97,10 +97,13 @@ fn demo_responses() {
                 message: Message::assistant("Hello! How can I help you today?"),
                 finish_reason: FinishReason::Stop,
                 logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
             }
         ],
         usage: Usage::new(10, 15),
         created: Some(1234567890),
+        created_synthesized: false,
         provider: Some("openai".to_string()),
     };
 
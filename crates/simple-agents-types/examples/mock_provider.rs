@@ -98,14 +98,19 @@ impl Provider for MockProvider {
                 ),
                 finish_reason: FinishReason::Stop,
                 logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
             }],
             usage: Usage {
                 prompt_tokens: body["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
                 completion_tokens: body["usage"]["completion_tokens"].as_u64().unwrap_or(0)
                     as u32,
                 total_tokens: body["usage"]["total_tokens"].as_u64().unwrap_or(0) as u32,
+                cache_creation_input_tokens: None,
+                cache_read_input_tokens: None,
             },
             created: None,
+            created_synthesized: false,
             provider: Some(self.name.clone()),
         };
 
@@ -127,6 +132,10 @@ impl Provider for MockProvider {
             streaming: true,
             function_calling: true,
             vision: false,
+            json_schema: false,
+            embeddings: false,
+            assistant_prefill: false,
+            documents: false,
             max_tokens: 4096,
         }
     }
@@ -0,0 +1,127 @@
+//! Request extensions example.
+//!
+//! A routing wrapper picks a deployment and records it in the request's
+//! extensions; the provider underneath reads it when sending, without any
+//! shared state between the two.
+//!
+//! Run with: cargo run --example request_extensions
+
+use async_trait::async_trait;
+use simple_agents_types::prelude::*;
+
+/// Key owned by the router
+const DEPLOYMENT: &str = "router.deployment";
+
+/// Producer: picks a deployment per request and passes the choice down.
+struct DeploymentRouter<P> {
+    inner: P,
+}
+
+impl<P: Provider> DeploymentRouter<P> {
+    fn pick_deployment(&self, req: &CompletionRequest) -> &'static str {
+        // Long conversations go to the deployment with the larger quota
+        if req.messages.len() > 2 {
+            "eastus-large"
+        } else {
+            "westeurope"
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for DeploymentRouter<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let mut req = req.clone();
+        req.extensions.insert(DEPLOYMENT, self.pick_deployment(&req));
+        self.inner.transform_request(&req)
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        self.inner.execute(req).await
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        self.inner.transform_response(resp)
+    }
+}
+
+/// Consumer: sends each request to the deployment the router chose.
+struct DeploymentAwareProvider;
+
+#[async_trait]
+impl Provider for DeploymentAwareProvider {
+    fn name(&self) -> &str {
+        "deployments"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        Ok(ProviderRequest::new("https://{deployment}.api.example.com/v1/chat/completions")
+            .with_body(serde_json::json!({"model": req.model, "messages": req.messages}))
+            // Carry the router's decision through to execute
+            .with_extensions(req.extensions.clone()))
+    }
+
+    async fn execute(&self, mut req: ProviderRequest) -> Result<ProviderResponse> {
+        let deployment = req
+            .extensions
+            .get_as::<String>(DEPLOYMENT)
+            .unwrap_or_else(|| "default".to_string());
+        req.url = req.url.replace("{deployment}", &deployment);
+        println!("  📡 Sending to {}", req.url);
+
+        Ok(ProviderResponse::new(
+            200,
+            serde_json::json!({"deployment": deployment, "model": req.body["model"]}),
+        ))
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let deployment = resp.body["deployment"].as_str().unwrap_or_default();
+        Ok(CompletionResponse {
+            id: "resp_1".to_string(),
+            model: resp.body["model"].as_str().unwrap_or_default().to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(format!("Answered by {}", deployment)),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(10, 4),
+            created: None,
+            created_synthesized: false,
+            provider: Some(self.name().to_string()),
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("=== Request Extensions Example ===\n");
+
+    let provider = DeploymentRouter {
+        inner: DeploymentAwareProvider,
+    };
+
+    let short = CompletionRequest::builder()
+        .model("gpt-4o")
+        .message(Message::user("Hello!"))
+        .build()?;
+    let long = short
+        .to_builder()
+        .message(Message::assistant("Hi! How can I help?"))
+        .message(Message::user("Summarize our conversation."))
+        .build()?;
+
+    for request in [short, long] {
+        let response = provider.complete(&request).await?;
+        println!("  ✓ {}\n", response.content().unwrap_or_default());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,282 @@
+//! Source citations attached to completion content.
+//!
+//! Web-search models cite the pages their answer draws on. OpenAI's
+//! search models mark the cited span of the content; Perplexity lists the
+//! sources and refers to them with `[1]`-style markers in the text.
+//! Claude cites passages of the documents sent with the request. All are
+//! parsed into [`Annotation`]s on
+//! [`CompletionChoice::annotations`](crate::response::CompletionChoice::annotations).
+//!
+//! # Offsets
+//!
+//! [`Annotation::start`] and [`Annotation::end`] are **byte** offsets into
+//! the choice's content, so `&content[start..end]` is always valid.
+//! Providers that count characters instead are converted with
+//! [`Annotation::with_char_range`], which drops ranges that do not fit the
+//! content rather than keeping offsets that would panic when sliced.
+
+use serde::{Deserialize, Serialize};
+
+/// What an [`Annotation`] marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    /// A span of the content cites a web page (OpenAI `url_citation`)
+    UrlCitation,
+    /// The response as a whole cites a source (Perplexity `citations`)
+    Citation,
+    /// A span of the content cites a passage of a document sent with the
+    /// request (Anthropic document citations)
+    DocumentCitation,
+}
+
+/// A source cited by completion content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// What this annotation marks
+    pub kind: AnnotationKind,
+    /// URL of the cited source; `None` for document citations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Title of the cited source, when reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Position of the cited document among those sent with the request,
+    /// counting from 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_index: Option<usize>,
+    /// Passage of the source quoted in support of the span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+    /// Byte offset where the citing span starts, if the source cites a span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    /// Byte offset where the citing span ends (exclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+}
+
+impl Annotation {
+    /// Cite `url` for the response as a whole.
+    pub fn new(kind: AnnotationKind, url: impl Into<String>) -> Self {
+        Self {
+            kind,
+            url: Some(url.into()),
+            title: None,
+            document_index: None,
+            quote: None,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Cite the document at `document_index` in the request.
+    pub fn document(document_index: usize) -> Self {
+        Self {
+            kind: AnnotationKind::DocumentCitation,
+            url: None,
+            title: None,
+            document_index: Some(document_index),
+            quote: None,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Set the source title (builder pattern).
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the quoted passage (builder pattern).
+    pub fn with_quote(mut self, quote: impl Into<String>) -> Self {
+        self.quote = Some(quote.into());
+        self
+    }
+
+    /// Cite the bytes `start..end` of `content` (builder pattern).
+    ///
+    /// The range is left unset if it is out of bounds, reversed, or does
+    /// not fall on character boundaries.
+    pub fn with_byte_range(mut self, content: &str, start: usize, end: usize) -> Self {
+        if start <= end && content.get(start..end).is_some() {
+            self.start = Some(start);
+            self.end = Some(end);
+        }
+        self
+    }
+
+    /// Cite the characters `start..end` of `content`, converted to byte
+    /// offsets (builder pattern).
+    ///
+    /// The range is left unset if it is out of bounds or reversed.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::annotation::{Annotation, AnnotationKind};
+    ///
+    /// let content = "Café prices rose";
+    /// let annotation = Annotation::new(AnnotationKind::UrlCitation, "https://example.com")
+    ///     .with_char_range(content, 5, 11);
+    /// assert_eq!(annotation.cited_text(content), Some("prices"));
+    /// ```
+    pub fn with_char_range(self, content: &str, start: usize, end: usize) -> Self {
+        let byte_offset = |chars: usize| {
+            content
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(content.len()))
+                .nth(chars)
+        };
+        match (byte_offset(start), byte_offset(end)) {
+            (Some(start), Some(end)) => self.with_byte_range(content, start, end),
+            _ => self,
+        }
+    }
+
+    /// The span of `content` this annotation cites, if it cites one.
+    pub fn cited_text<'a>(&self, content: &'a str) -> Option<&'a str> {
+        content.get(self.start?..self.end?)
+    }
+
+    /// Whether `self` and `other` cite the same source.
+    fn same_source(&self, other: &Annotation) -> bool {
+        self.url == other.url && self.document_index == other.document_index
+    }
+}
+
+/// Render `content` with footnote markers for its `annotations`.
+///
+/// Sources are numbered by first appearance; repeated sources share a
+/// number. A `[n]` marker is inserted after each cited span, and the
+/// sources are listed after the content as `[n] Title: url` (or `[n] url`
+/// without a title; documents show as `document <index>`). Annotations
+/// without a range are only listed, which matches Perplexity, whose
+/// content already carries `[n]` markers in citation order.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::annotation::{render_footnotes, Annotation, AnnotationKind};
+///
+/// let content = "Rust 1.75 shipped async fn in traits.";
+/// let annotations = vec![Annotation::new(AnnotationKind::UrlCitation, "https://blog.rust-lang.org")
+///     .with_title("Rust Blog")
+///     .with_byte_range(content, 0, 36)];
+/// assert_eq!(
+///     render_footnotes(content, &annotations),
+///     "Rust 1.75 shipped async fn in traits[1].\n\n[1] Rust Blog: https://blog.rust-lang.org"
+/// );
+/// ```
+pub fn render_footnotes(content: &str, annotations: &[Annotation]) -> String {
+    if annotations.is_empty() {
+        return content.to_string();
+    }
+
+    let mut sources: Vec<&Annotation> = Vec::new();
+    let mut markers = Vec::new();
+    for annotation in annotations {
+        let number = match sources.iter().position(|source| source.same_source(annotation)) {
+            Some(index) => index + 1,
+            None => {
+                sources.push(annotation);
+                sources.len()
+            }
+        };
+        if let Some(end) = annotation.cited_text(content).and(annotation.end) {
+            markers.push((end, number));
+        }
+    }
+    // Stable, so markers at the same offset keep annotation order
+    markers.sort_by_key(|(end, _)| *end);
+
+    let mut rendered = String::with_capacity(content.len() + markers.len() * 4 + sources.len() * 64);
+    let mut copied = 0;
+    for (end, number) in markers {
+        rendered.push_str(&content[copied..end]);
+        rendered.push_str(&format!("[{}]", number));
+        copied = end;
+    }
+    rendered.push_str(&content[copied..]);
+
+    rendered.push('\n');
+    for (index, source) in sources.iter().enumerate() {
+        rendered.push_str(&format!("\n[{}] ", index + 1));
+        let location = match (&source.url, source.document_index) {
+            (Some(url), _) => url.clone(),
+            (None, Some(index)) => format!("document {}", index),
+            (None, None) => String::new(),
+        };
+        match &source.title {
+            Some(title) if location.is_empty() => rendered.push_str(title),
+            Some(title) => rendered.push_str(&format!("{}: {}", title, location)),
+            None => rendered.push_str(&location),
+        }
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(url: &str) -> Annotation {
+        Annotation::new(AnnotationKind::UrlCitation, url)
+    }
+
+    #[test]
+    fn test_ranges_are_validated() {
+        let content = "naïve résumé";
+        assert_eq!(content.len(), 15);
+
+        let valid = citation("https://a.example").with_byte_range(content, 7, 15);
+        assert_eq!(valid.cited_text(content), Some("résumé"));
+
+        // Past the end, reversed, and inside the two-byte "ï"
+        for (start, end) in [(7, 16), (9, 7), (0, 3)] {
+            let invalid = citation("https://a.example").with_byte_range(content, start, end);
+            assert_eq!((invalid.start, invalid.end), (None, None), "{}..{}", start, end);
+        }
+
+        // Character offsets are converted to bytes
+        let chars = citation("https://a.example").with_char_range(content, 6, 12);
+        assert_eq!((chars.start, chars.end), (Some(7), Some(15)));
+        let past_end = citation("https://a.example").with_char_range(content, 6, 13);
+        assert_eq!(past_end.start, None);
+    }
+
+    #[test]
+    fn test_render_footnotes() {
+        let content = "Oslo is the capital. It has 700k people.";
+        let annotations = vec![
+            citation("https://en.wikipedia.org/wiki/Oslo")
+                .with_title("Oslo - Wikipedia")
+                .with_byte_range(content, 0, 19),
+            citation("https://www.ssb.no").with_byte_range(content, 21, 39),
+            citation("https://en.wikipedia.org/wiki/Oslo").with_byte_range(content, 21, 39),
+        ];
+
+        assert_eq!(
+            render_footnotes(content, &annotations),
+            "Oslo is the capital[1]. It has 700k people[2][1].\n\n\
+             [1] Oslo - Wikipedia: https://en.wikipedia.org/wiki/Oslo\n\
+             [2] https://www.ssb.no"
+        );
+        assert_eq!(render_footnotes(content, &[]), content);
+    }
+
+    #[test]
+    fn test_render_footnotes_without_ranges() {
+        let content = "Oslo is the capital of Norway[1][2].";
+        let annotations = vec![
+            Annotation::new(AnnotationKind::Citation, "https://en.wikipedia.org/wiki/Oslo"),
+            Annotation::new(AnnotationKind::Citation, "https://www.visitoslo.com"),
+        ];
+        assert_eq!(
+            render_footnotes(content, &annotations),
+            "Oslo is the capital of Norway[1][2].\n\n\
+             [1] https://en.wikipedia.org/wiki/Oslo\n\
+             [2] https://www.visitoslo.com"
+        );
+    }
+}
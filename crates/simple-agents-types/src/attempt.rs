@@ -0,0 +1,193 @@
+//! Per-attempt history of retried and failed-over requests.
+//!
+//! [`Provider::execute_with_attempts`](crate::provider::Provider::execute_with_attempts)
+//! returns a [`CompleteOutcome`] whose [`AttemptHistory`] lists every call
+//! made, so a request that succeeded only after retries is still visible.
+//! On failure the same history is in the error's
+//! [`ErrorContext::attempts`](crate::error::ErrorContext::attempts).
+//!
+//! Histories serialize as a JSON array, with durations in milliseconds:
+//!
+//! ```
+//! use simple_agents_types::attempt::{Attempt, AttemptHistory};
+//! use simple_agents_types::error::ErrorCode;
+//! use std::time::Duration;
+//!
+//! let mut history = AttemptHistory::new();
+//! history.push(Attempt::failed("openai", 1, ErrorCode::RateLimited, Duration::from_millis(80))
+//!     .with_backoff(Duration::from_secs(1)));
+//! history.push(Attempt::succeeded("openai", 2, Duration::from_millis(950)));
+//!
+//! let json = serde_json::to_string(&history)?;
+//! assert!(json.starts_with(
+//!     r#"[{"provider":"openai","attempt":1,"error_code":"RATE_LIMITED","latency_ms":80,"backoff_ms":1000},"#
+//! ));
+//! assert!(json.ends_with(r#""error_code":null,"latency_ms":950,"backoff_ms":0}]"#));
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use crate::error::ErrorCode;
+use crate::response::CompletionResponse;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// One call to a provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attempt {
+    /// Provider called
+    pub provider: String,
+    /// Attempt number on this provider, starting at 1
+    pub attempt: u32,
+    /// Why the call failed; `None` if it succeeded
+    pub error_code: Option<ErrorCode>,
+    /// Time the call took
+    #[serde(rename = "latency_ms", with = "crate::event::duration_ms")]
+    pub latency: Duration,
+    /// Time waited after the call before the next attempt
+    #[serde(rename = "backoff_ms", with = "crate::event::duration_ms")]
+    pub backoff: Duration,
+}
+
+impl Attempt {
+    /// A successful call.
+    pub fn succeeded(provider: impl Into<String>, attempt: u32, latency: Duration) -> Self {
+        Self {
+            provider: provider.into(),
+            attempt,
+            error_code: None,
+            latency,
+            backoff: Duration::ZERO,
+        }
+    }
+
+    /// A call that failed with `error_code`.
+    pub fn failed(
+        provider: impl Into<String>,
+        attempt: u32,
+        error_code: ErrorCode,
+        latency: Duration,
+    ) -> Self {
+        Self {
+            error_code: Some(error_code),
+            ..Self::succeeded(provider, attempt, latency)
+        }
+    }
+
+    /// Set the wait before the next attempt.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Whether the call succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error_code.is_none()
+    }
+}
+
+/// Every call made for one request, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AttemptHistory {
+    attempts: Vec<Attempt>,
+}
+
+impl AttemptHistory {
+    /// An empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `attempt`.
+    pub fn push(&mut self, attempt: Attempt) {
+        self.attempts.push(attempt);
+    }
+
+    /// Append the attempts of `other`.
+    pub fn extend(&mut self, other: AttemptHistory) {
+        self.attempts.extend(other.attempts);
+    }
+
+    /// The attempts, oldest first.
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    /// The most recent attempt.
+    pub fn last_mut(&mut self) -> Option<&mut Attempt> {
+        self.attempts.last_mut()
+    }
+
+    /// Number of attempts.
+    pub fn len(&self) -> usize {
+        self.attempts.len()
+    }
+
+    /// Whether no attempt was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.attempts.is_empty()
+    }
+
+    /// Number of failed attempts.
+    pub fn failures(&self) -> usize {
+        self.attempts.iter().filter(|attempt| !attempt.is_success()).count()
+    }
+
+    /// Time spent in calls.
+    pub fn total_latency(&self) -> Duration {
+        self.attempts.iter().map(|attempt| attempt.latency).sum()
+    }
+
+    /// Time spent waiting between calls.
+    pub fn total_backoff(&self) -> Duration {
+        self.attempts.iter().map(|attempt| attempt.backoff).sum()
+    }
+}
+
+impl<'a> IntoIterator for &'a AttemptHistory {
+    type Item = &'a Attempt;
+    type IntoIter = std::slice::Iter<'a, Attempt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attempts.iter()
+    }
+}
+
+/// A response together with the attempts it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompleteOutcome {
+    /// The successful response
+    pub response: CompletionResponse,
+    /// Every call made, the successful one last
+    pub attempts: AttemptHistory,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_totals_and_round_trip() {
+        let mut history = AttemptHistory::new();
+        history.push(
+            Attempt::failed("openai", 1, ErrorCode::ServerError, Duration::from_millis(30))
+                .with_backoff(Duration::from_millis(100)),
+        );
+        history.push(
+            Attempt::failed("openai", 2, ErrorCode::Timeout, Duration::from_secs(10))
+                .with_backoff(Duration::from_millis(200)),
+        );
+        history.push(Attempt::succeeded("openai", 3, Duration::from_millis(400)));
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.failures(), 2);
+        assert_eq!(history.total_latency(), Duration::from_millis(10_430));
+        assert_eq!(history.total_backoff(), Duration::from_millis(300));
+
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(json[1]["error_code"], "TIMEOUT");
+        assert_eq!(json[1]["latency_ms"], 10_000);
+        let parsed: AttemptHistory = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, history);
+    }
+}
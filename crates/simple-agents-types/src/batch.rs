@@ -0,0 +1,321 @@
+//! JSONL files for the OpenAI Batch API.
+//!
+//! The Batch API processes an uploaded JSONL file of requests
+//! asynchronously and produces a JSONL file of results. This module
+//! builds the input file from [`CompletionRequest`]s and parses the
+//! output file; uploading files and creating batches is left to the
+//! caller.
+
+use crate::error::{ProviderError, Result, SimpleAgentsError};
+use crate::request::CompletionRequest;
+use crate::response::CompletionResponse;
+use serde::{Deserialize, Serialize};
+
+/// Endpoint every batch line targets
+pub const BATCH_COMPLETIONS_URL: &str = "/v1/chat/completions";
+
+/// Request fields the Chat Completions API does not accept
+const NON_WIRE_FIELDS: &[&str] = &["assistant_prefill", "trim_stop_sequences", "stream", "stream_options"];
+
+/// One line of a batch input file
+#[derive(Debug, Serialize, Deserialize)]
+struct BatchRequestLine {
+    custom_id: String,
+    method: String,
+    url: String,
+    body: serde_json::Value,
+}
+
+impl CompletionRequest {
+    /// Serialize this request as one line of a Batch API input file.
+    ///
+    /// `custom_id` identifies the request's result in the output file and
+    /// must be unique within a batch. Fields the Chat Completions API does
+    /// not accept (assistant prefill, stop-sequence trimming, streaming
+    /// and message cache control) are omitted, and images are sent as
+    /// content parts. The line has no trailing newline.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4o-mini")
+    ///     .message(Message::user("Hello!"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let line: serde_json::Value = serde_json::from_str(&request.to_batch_line("req-1")).unwrap();
+    /// assert_eq!(line["custom_id"], "req-1");
+    /// assert_eq!(line["url"], "/v1/chat/completions");
+    /// assert_eq!(line["body"]["model"], "gpt-4o-mini");
+    /// ```
+    pub fn to_batch_line(&self, custom_id: &str) -> String {
+        let line = BatchRequestLine {
+            custom_id: custom_id.to_string(),
+            method: "POST".to_string(),
+            url: BATCH_COMPLETIONS_URL.to_string(),
+            body: self.batch_body(),
+        };
+        // Serializing strings and JSON values cannot fail.
+        serde_json::to_string(&line).unwrap_or_default()
+    }
+
+    fn batch_body(&self) -> serde_json::Value {
+        let mut body = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = body.as_object_mut() {
+            for field in NON_WIRE_FIELDS {
+                fields.remove(*field);
+            }
+        }
+        if let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) {
+            for (message, source) in messages.iter_mut().zip(&self.messages) {
+                if let Some(message) = message.as_object_mut() {
+                    message.remove("cache_control");
+                    if message.remove("images").is_some() {
+                        message.insert("content".to_string(), source.to_openai_content());
+                    }
+                }
+            }
+        }
+        body
+    }
+}
+
+/// Batch API input file.
+pub struct BatchRequestFile;
+
+impl BatchRequestFile {
+    /// Build a JSONL input file from `(custom_id, request)` pairs.
+    ///
+    /// Each line is terminated by a newline.
+    pub fn from_requests(requests: Vec<(String, CompletionRequest)>) -> String {
+        let mut output = String::new();
+        for (custom_id, request) in &requests {
+            output.push_str(&request.to_batch_line(custom_id));
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Parse an input file back into `(custom_id, request)` pairs.
+    ///
+    /// Blank lines are ignored.
+    pub fn parse(jsonl: &str) -> Result<Vec<(String, CompletionRequest)>> {
+        jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let line: BatchRequestLine = serde_json::from_str(line)?;
+                Ok((line.custom_id, serde_json::from_value(line.body)?))
+            })
+            .collect()
+    }
+}
+
+/// Batch API output file.
+pub struct BatchResponseFile;
+
+impl BatchResponseFile {
+    /// Parse an output (or error) file into one result per line.
+    ///
+    /// Results are in file order, which need not match input order; match
+    /// them up by [`BatchResult::custom_id`]. Blank lines are ignored.
+    pub fn parse(jsonl: &str) -> Result<Vec<BatchResult>> {
+        jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(SimpleAgentsError::from))
+            .collect()
+    }
+}
+
+/// Outcome of one request in a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchResult {
+    /// Result identifier (e.g. "batch_req_abc123")
+    pub id: String,
+    /// `custom_id` of the request
+    pub custom_id: String,
+    /// HTTP response, if the request was sent
+    #[serde(default)]
+    pub response: Option<BatchResponse>,
+    /// Why the request was not sent (e.g. the batch expired)
+    #[serde(default)]
+    pub error: Option<BatchError>,
+}
+
+/// HTTP response to a batched request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchResponse {
+    /// HTTP status code
+    pub status_code: u16,
+    /// Server-assigned request ID
+    #[serde(default)]
+    pub request_id: String,
+    /// Response body: a chat completion, or an error object
+    pub body: serde_json::Value,
+}
+
+/// Error for a request the batch could not send.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchError {
+    /// Error code (e.g. "batch_expired")
+    pub code: String,
+    /// Human-readable message
+    pub message: String,
+}
+
+impl BatchResult {
+    /// Whether the request completed with a 2xx response.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+            && self.response.as_ref().is_some_and(|r| (200..300).contains(&r.status_code))
+    }
+
+    /// The completion returned for this request.
+    ///
+    /// # Errors
+    ///
+    /// - [`ProviderError::ServerError`] for requests the batch could not
+    ///   send and for 5xx responses
+    /// - [`ProviderError::BadRequest`] for other non-2xx responses
+    /// - [`ProviderError::InvalidResponse`] if the body is not a chat completion
+    pub fn completion(&self) -> Result<CompletionResponse> {
+        if let Some(error) = &self.error {
+            return Err(ProviderError::ServerError(format!("{}: {}", error.code, error.message)).into());
+        }
+        let response = self.response.as_ref().ok_or_else(|| {
+            ProviderError::InvalidResponse(format!("batch result {} has no response", self.custom_id))
+        })?;
+
+        if !(200..300).contains(&response.status_code) {
+            let message = response.body["error"]["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| response.body.to_string());
+            let message = format!("{} ({})", message, response.status_code);
+            return Err(if response.status_code >= 500 {
+                ProviderError::ServerError(message)
+            } else {
+                ProviderError::BadRequest(message)
+            }
+            .into());
+        }
+
+        let mut completion: CompletionResponse = serde_json::from_value(response.body.clone())
+            .map_err(|e| ProviderError::InvalidResponse(format!("Failed to deserialize batch result: {}", e)))?;
+        completion.provider.get_or_insert_with(|| "openai".to_string());
+        Ok(completion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{CacheControlType, Message};
+    use crate::response::FinishReason;
+
+    fn requests() -> Vec<(String, CompletionRequest)> {
+        vec![
+            (
+                "req-1".to_string(),
+                CompletionRequest::builder()
+                    .model("gpt-4o-mini")
+                    .message(Message::system("Be brief."))
+                    .message(Message::user("What is 2 + 2?"))
+                    .temperature(0.2)
+                    .max_tokens(16)
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "req-2".to_string(),
+                CompletionRequest::builder()
+                    .model("gpt-4o-mini")
+                    .message(Message::user("Name a color."))
+                    .stop(vec!["\n".to_string()])
+                    .build()
+                    .unwrap(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_request_file_round_trip() {
+        let requests = requests();
+        let jsonl = BatchRequestFile::from_requests(requests.clone());
+
+        assert_eq!(jsonl.lines().count(), 2);
+        assert!(jsonl.ends_with('\n'));
+        let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(first["custom_id"], "req-1");
+        assert_eq!(first["method"], "POST");
+        assert_eq!(first["url"], "/v1/chat/completions");
+        assert_eq!(first["body"]["max_tokens"], 16);
+        assert_eq!(first["body"]["messages"][1]["content"], "What is 2 + 2?");
+
+        assert_eq!(BatchRequestFile::parse(&jsonl).unwrap(), requests);
+    }
+
+    #[test]
+    fn test_batch_line_omits_non_wire_fields() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4o-mini")
+            .message(Message::user("Continue the list.").with_cache_control(CacheControlType::Ephemeral))
+            .stop(vec!["</list>".to_string()])
+            .trim_stop_sequences(true)
+            .assistant_prefill("1.")
+            .build()
+            .unwrap();
+
+        let line: serde_json::Value = serde_json::from_str(&request.to_batch_line("req-1")).unwrap();
+        let body = line["body"].as_object().unwrap();
+        assert!(!body.contains_key("assistant_prefill"));
+        assert!(!body.contains_key("trim_stop_sequences"));
+        assert!(!body["messages"][0].as_object().unwrap().contains_key("cache_control"));
+        assert_eq!(body["stop"][0], "</list>");
+    }
+
+    #[test]
+    fn test_parse_response_file() {
+        let jsonl = concat!(
+            r#"{"id": "batch_req_1", "custom_id": "req-1", "response": {"status_code": 200, "request_id": "req_abc", "body": {"id": "chatcmpl-1", "object": "chat.completion", "created": 1700000000, "model": "gpt-4o-mini-2024-07-18", "choices": [{"index": 0, "message": {"role": "assistant", "content": "4", "refusal": null}, "logprobs": null, "finish_reason": "stop"}], "usage": {"prompt_tokens": 20, "completion_tokens": 1, "total_tokens": 21}, "system_fingerprint": "fp_1"}}, "error": null}"#,
+            "\n\n",
+            r#"{"id": "batch_req_2", "custom_id": "req-2", "response": {"status_code": 400, "request_id": "req_def", "body": {"error": {"message": "Invalid model", "type": "invalid_request_error"}}}, "error": null}"#,
+            "\n",
+            r#"{"id": "batch_req_3", "custom_id": "req-3", "response": null, "error": {"code": "batch_expired", "message": "This request could not be executed before the completion window expired."}}"#,
+            "\n",
+        );
+
+        let results = BatchResponseFile::parse(jsonl).unwrap();
+        assert_eq!(results.len(), 3);
+
+        assert!(results[0].is_success());
+        let completion = results[0].completion().unwrap();
+        assert_eq!(completion.content(), Some("4"));
+        assert_eq!(completion.choices[0].finish_reason, FinishReason::Stop);
+        assert_eq!(completion.usage.total_tokens, 21);
+        assert_eq!(completion.provider.as_deref(), Some("openai"));
+
+        assert!(!results[1].is_success());
+        assert!(matches!(
+            results[1].completion(),
+            Err(SimpleAgentsError::Provider(ProviderError::BadRequest(m))) if m == "Invalid model (400)"
+        ));
+
+        assert_eq!(results[2].custom_id, "req-3");
+        assert!(matches!(
+            results[2].completion(),
+            Err(SimpleAgentsError::Provider(ProviderError::ServerError(m))) if m.starts_with("batch_expired: ")
+        ));
+    }
+
+    #[test]
+    fn test_parse_response_file_rejects_malformed_lines() {
+        assert!(matches!(
+            BatchResponseFile::parse("{not json}\n"),
+            Err(SimpleAgentsError::Serialization(_))
+        ));
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides an abstract interface for caching LLM responses.
 
 use crate::error::Result;
+use crate::request::CompletionRequest;
 use async_trait::async_trait;
 use std::time::Duration;
 
@@ -123,7 +124,11 @@ pub trait Cache: Send + Sync {
 pub struct CacheKey;
 
 impl CacheKey {
-    /// Generate a cache key from a request.
+    /// Generate a cache key from its parts.
+    ///
+    /// Only `content` identifies the request, so callers must put everything
+    /// that affects the response into it; prefer
+    /// [`from_request`](Self::from_request) for completion requests.
     ///
     /// Uses blake3 for cryptographically secure and deterministic hashing.
     ///
@@ -143,6 +148,38 @@ impl CacheKey {
         format!("{}:{}:{}", provider, model, hash.to_hex())
     }
 
+    /// Version of the [`from_request`](Self::from_request) key format.
+    ///
+    /// Bumped whenever the scheme changes, so entries written under an older
+    /// scheme simply stop matching instead of being served for the wrong
+    /// request.
+    pub const VERSION: &'static str = "v2";
+
+    /// Generate a cache key for a completion request.
+    ///
+    /// The key is `v2:<provider>:<model>:<fingerprint>`, where the
+    /// fingerprint is [`CompletionRequest::fingerprint`]. It covers every
+    /// serialized field (messages, sampling parameters, stop sequences,
+    /// tools, tool choice, response format, ...), so requests that share
+    /// messages but differ in tools or schema never collide.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::cache::CacheKey;
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4")
+    ///     .message(Message::user("Hello"))
+    ///     .build()
+    ///     .unwrap();
+    /// let key = CacheKey::from_request("openai", &request);
+    /// assert!(key.starts_with("v2:openai:gpt-4:"));
+    /// ```
+    pub fn from_request(provider: &str, request: &CompletionRequest) -> String {
+        format!("{}:{}:{}:{}", Self::VERSION, provider, request.model, request.fingerprint())
+    }
+
     /// Generate a cache key with custom namespace.
     pub fn with_namespace(namespace: &str, key: &str) -> String {
         format!("{}:{}", namespace, key)
@@ -223,4 +260,88 @@ mod tests {
         assert_eq!(parts[2].len(), 64, "Blake3 hash should be 64 hex characters");
         assert!(parts[2].chars().all(|c| c.is_ascii_hexdigit()), "Hash should be valid hex");
     }
+
+    #[test]
+    fn test_cache_key_from_request() {
+        use crate::message::Message;
+
+        let request = |content: &str, temperature: f32| {
+            CompletionRequest::builder()
+                .model("gpt-4")
+                .message(Message::user(content))
+                .temperature(temperature)
+                .build()
+                .unwrap()
+        };
+
+        let key = CacheKey::from_request("openai", &request("Hello", 0.0));
+        assert_eq!(key, CacheKey::from_request("openai", &request("Hello", 0.0)));
+        assert_ne!(key, CacheKey::from_request("openai", &request("Hello", 0.5)));
+        assert_ne!(key, CacheKey::from_request("openai", &request("Goodbye", 0.0)));
+        assert!(key.starts_with("v2:openai:gpt-4:"));
+    }
+
+    fn keyed_request(builder: crate::request::CompletionRequestBuilder) -> String {
+        let request = builder.build().unwrap();
+        CacheKey::from_request("openai", &request)
+    }
+
+    fn base() -> crate::request::CompletionRequestBuilder {
+        crate::request::CompletionRequest::builder()
+            .model("gpt-4")
+            .message(crate::message::Message::user("What's the weather? Reply in JSON."))
+    }
+
+    fn tool(name: &str) -> crate::tool::ToolDefinition {
+        let parameters = serde_json::json!({"type": "object"});
+        crate::tool::ToolDefinition::function(name, "Look something up", parameters)
+    }
+
+    #[test]
+    fn test_request_key_format() {
+        let key = keyed_request(base());
+        let parts: Vec<&str> = key.split(':').collect();
+        assert_eq!(parts[..3], ["v2", "openai", "gpt-4"]);
+        assert_eq!(parts[3], base().build().unwrap().fingerprint());
+        assert_eq!(key, keyed_request(base()));
+    }
+
+    #[test]
+    fn test_request_key_changes_with_tool_list_only() {
+        let weather = keyed_request(base().tool(tool("get_weather")));
+        let both = keyed_request(base().tool(tool("get_weather")).tool(tool("get_time")));
+        let time = keyed_request(base().tool(tool("get_time")));
+
+        assert_ne!(weather, both);
+        assert_ne!(weather, time);
+        assert_ne!(weather, keyed_request(base()));
+    }
+
+    #[test]
+    fn test_request_key_covers_parameters() {
+        use crate::request::{JsonSchemaFormat, ResponseFormat};
+        use crate::tool::ToolChoice;
+
+        let schema = |name: &str| ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: name.to_string(),
+                schema: serde_json::json!({"type": "object"}),
+                strict: Some(true),
+            },
+        };
+        let keys = [
+            keyed_request(base()),
+            keyed_request(base().tool(tool("get_weather")).tool_choice(ToolChoice::Auto)),
+            keyed_request(base().tool(tool("get_weather")).tool_choice(ToolChoice::Required)),
+            keyed_request(base().response_format(schema("weather"))),
+            keyed_request(base().response_format(schema("forecast"))),
+            keyed_request(base().temperature(0.2)),
+            keyed_request(base().top_p(0.9)),
+            keyed_request(base().stop(vec!["END".to_string()])),
+            keyed_request(base().max_tokens(100)),
+        ];
+
+        let unique: std::collections::HashSet<&String> = keys.iter().collect();
+        assert_eq!(unique.len(), keys.len());
+    }
 }
@@ -0,0 +1,331 @@
+//! Per-model facts: context window, output limit, features and prices.
+//!
+//! [`ModelCatalog::builtin`] covers mainstream OpenAI, Anthropic, Gemini
+//! and Mistral models. Lookups match dated and tagged variants
+//! (`gpt-4o-2024-08-06`, `claude-3-5-sonnet-latest`) to their base entry
+//! by prefix. New models, or corrected prices, can be added at runtime
+//! from JSON without waiting for a release:
+//!
+//! ```
+//! use simple_agents_types::catalog::ModelCatalog;
+//!
+//! let catalog = ModelCatalog::builtin()
+//!     .clone()
+//!     .with_overrides_json(
+//!         r#"[{"name": "acme-1", "context_window": 32768, "max_output_tokens": 4096,
+//!              "function_calling": true,
+//!              "pricing": {"input_per_million": 1.0, "output_per_million": 2.0}}]"#,
+//!     )
+//!     .unwrap();
+//!
+//! assert_eq!(catalog.get("acme-1-preview").unwrap().context_window, 32768);
+//! assert_eq!(catalog.get("gpt-4o-2024-08-06").unwrap().name, "gpt-4o");
+//! ```
+//!
+//! [`Provider::model_info`](crate::provider::Provider::model_info) reads
+//! the catalog, so [`Provider::pricing`](crate::provider::Provider::pricing)
+//! and [`CompletionRequest::check_features`] know every model listed here.
+
+use crate::config::Capabilities;
+use crate::display::Pricing;
+use crate::error::{Result, ValidationError};
+use crate::request::CompletionRequest;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Facts about one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfoStatic {
+    /// Model name; also matches names that extend it with `-`, `:` or `@`
+    /// (dated or tagged variants)
+    pub name: Cow<'static, str>,
+    /// Prompt plus completion tokens the model accepts
+    pub context_window: u32,
+    /// Completion tokens the model can produce
+    pub max_output_tokens: u32,
+    /// Accepts image inputs
+    #[serde(default)]
+    pub vision: bool,
+    /// Supports function/tool calling
+    #[serde(default)]
+    pub function_calling: bool,
+    /// Supports JSON schema constrained output
+    #[serde(default)]
+    pub json_schema: bool,
+    /// List prices, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+}
+
+impl ModelInfoStatic {
+    /// Narrow a provider's capabilities to what this model supports.
+    ///
+    /// Vision, function calling and JSON schema need both the provider and
+    /// the model; `max_tokens` becomes the model's output limit. Other
+    /// features are the provider's.
+    pub fn restrict(&self, capabilities: Capabilities) -> Capabilities {
+        Capabilities {
+            vision: capabilities.vision && self.vision,
+            function_calling: capabilities.function_calling && self.function_calling,
+            json_schema: capabilities.json_schema && self.json_schema,
+            max_tokens: self.max_output_tokens,
+            ..capabilities
+        }
+    }
+
+    /// Whether the estimated prompt of `req` plus its `max_tokens` fits the
+    /// context window.
+    ///
+    /// Uses [`CompletionRequest::token_count_total`], an estimate, so leave
+    /// some margin when trimming a conversation to fit.
+    pub fn fits(&self, req: &CompletionRequest) -> bool {
+        let needed = u64::from(req.token_count_total()) + u64::from(req.max_tokens.unwrap_or(0));
+        needed <= u64::from(self.context_window)
+    }
+}
+
+/// Lookup table of [`ModelInfoStatic`] by model name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelCatalog {
+    models: Vec<ModelInfoStatic>,
+}
+
+impl ModelCatalog {
+    /// An empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The catalog bundled with this crate.
+    ///
+    /// Clone it to add overrides.
+    pub fn builtin() -> &'static ModelCatalog {
+        static BUILTIN: OnceLock<ModelCatalog> = OnceLock::new();
+        BUILTIN.get_or_init(|| ModelCatalog {
+            models: BUILTIN_MODELS.to_vec(),
+        })
+    }
+
+    /// All entries, in insertion order.
+    pub fn models(&self) -> &[ModelInfoStatic] {
+        &self.models
+    }
+
+    /// Look up `model`.
+    ///
+    /// The name is matched case-insensitively, ignoring a `provider/`
+    /// prefix as used by gateways. An exact match wins; otherwise the
+    /// longest entry that `model` extends with `-`, `:` or `@` does, so
+    /// `gpt-4o-mini-2024-07-18` resolves to `gpt-4o-mini`, not `gpt-4o`.
+    pub fn get(&self, model: &str) -> Option<&ModelInfoStatic> {
+        let model = normalize(model);
+        self.models
+            .iter()
+            .filter(|info| {
+                let name = info.name.as_ref();
+                model == name
+                    || model
+                        .strip_prefix(name)
+                        .is_some_and(|rest| rest.starts_with(['-', ':', '@']))
+            })
+            .max_by_key(|info| info.name.len())
+    }
+
+    /// Add `info`, replacing any entry with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::Empty`] if the name is empty.
+    pub fn insert(&mut self, mut info: ModelInfoStatic) -> Result<()> {
+        let name = normalize(&info.name);
+        if name.is_empty() {
+            return Err(ValidationError::Empty {
+                field: "name".to_string(),
+            }
+            .into());
+        }
+        info.name = Cow::Owned(name);
+        match self.models.iter_mut().find(|existing| existing.name == info.name) {
+            Some(existing) => *existing = info,
+            None => self.models.push(info),
+        }
+        Ok(())
+    }
+
+    /// Add the entries of a JSON array of [`ModelInfoStatic`] objects,
+    /// replacing entries with the same names (builder pattern).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimpleAgentsError::Serialization`](crate::SimpleAgentsError::Serialization)
+    /// if `json` is not such an array, or [`ValidationError::Empty`] for an
+    /// entry without a name. The catalog is unchanged on error.
+    pub fn with_overrides_json(mut self, json: &str) -> Result<Self> {
+        let overrides: Vec<ModelInfoStatic> = serde_json::from_str(json)?;
+        if overrides.iter().any(|info| normalize(&info.name).is_empty()) {
+            return Err(ValidationError::Empty {
+                field: "name".to_string(),
+            }
+            .into());
+        }
+        for info in overrides {
+            self.insert(info)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Lowercase `model` and drop a `provider/` prefix.
+fn normalize(model: &str) -> String {
+    let model = model.trim();
+    let model = model.rsplit_once('/').map_or(model, |(_, name)| name);
+    model.to_ascii_lowercase()
+}
+
+const fn model(
+    name: &'static str,
+    context_window: u32,
+    max_output_tokens: u32,
+    (vision, function_calling, json_schema): (bool, bool, bool),
+    (input_per_million, output_per_million): (f64, f64),
+) -> ModelInfoStatic {
+    ModelInfoStatic {
+        name: Cow::Borrowed(name),
+        context_window,
+        max_output_tokens,
+        vision,
+        function_calling,
+        json_schema,
+        pricing: Some(Pricing {
+            input_per_million,
+            output_per_million,
+        }),
+    }
+}
+
+/// Features as `(vision, function_calling, json_schema)`
+const ALL: (bool, bool, bool) = (true, true, true);
+const NO_SCHEMA: (bool, bool, bool) = (true, true, false);
+const TEXT_TOOLS: (bool, bool, bool) = (false, true, false);
+const TEXT_TOOLS_SCHEMA: (bool, bool, bool) = (false, true, true);
+const TEXT_ONLY: (bool, bool, bool) = (false, false, false);
+
+/// List prices in US dollars per million tokens
+const BUILTIN_MODELS: &[ModelInfoStatic] = &[
+    // OpenAI
+    model("gpt-4o", 128_000, 16_384, ALL, (2.5, 10.0)),
+    model("gpt-4o-mini", 128_000, 16_384, ALL, (0.15, 0.6)),
+    model("gpt-4.1", 1_047_576, 32_768, ALL, (2.0, 8.0)),
+    model("gpt-4.1-mini", 1_047_576, 32_768, ALL, (0.4, 1.6)),
+    model("gpt-4.1-nano", 1_047_576, 32_768, ALL, (0.1, 0.4)),
+    model("gpt-4-turbo", 128_000, 4_096, NO_SCHEMA, (10.0, 30.0)),
+    model("gpt-4", 8_192, 8_192, TEXT_TOOLS, (30.0, 60.0)),
+    model("gpt-4-32k", 32_768, 8_192, TEXT_TOOLS, (60.0, 120.0)),
+    model("gpt-3.5-turbo", 16_385, 4_096, TEXT_TOOLS, (0.5, 1.5)),
+    model("o1", 200_000, 100_000, ALL, (15.0, 60.0)),
+    model("o1-mini", 128_000, 65_536, TEXT_ONLY, (1.1, 4.4)),
+    model("o1-preview", 128_000, 32_768, TEXT_ONLY, (15.0, 60.0)),
+    model("o3-mini", 200_000, 100_000, TEXT_TOOLS_SCHEMA, (1.1, 4.4)),
+    // Anthropic
+    model("claude-3-7-sonnet", 200_000, 64_000, NO_SCHEMA, (3.0, 15.0)),
+    model("claude-3-5-sonnet", 200_000, 8_192, NO_SCHEMA, (3.0, 15.0)),
+    model("claude-3-5-haiku", 200_000, 8_192, TEXT_TOOLS, (0.8, 4.0)),
+    model("claude-3-opus", 200_000, 4_096, NO_SCHEMA, (15.0, 75.0)),
+    model("claude-3-sonnet", 200_000, 4_096, NO_SCHEMA, (3.0, 15.0)),
+    model("claude-3-haiku", 200_000, 4_096, NO_SCHEMA, (0.25, 1.25)),
+    // Google
+    model("gemini-2.0-flash", 1_048_576, 8_192, ALL, (0.1, 0.4)),
+    model("gemini-1.5-pro", 2_097_152, 8_192, ALL, (1.25, 5.0)),
+    model("gemini-1.5-flash", 1_048_576, 8_192, ALL, (0.075, 0.3)),
+    // Mistral
+    model("mistral-large", 131_072, 131_072, TEXT_TOOLS_SCHEMA, (2.0, 6.0)),
+    model("mistral-small", 32_768, 32_768, TEXT_TOOLS_SCHEMA, (0.2, 0.6)),
+    model("pixtral-large", 131_072, 131_072, ALL, (2.0, 6.0)),
+    model("codestral", 262_144, 262_144, TEXT_TOOLS_SCHEMA, (0.3, 0.9)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_matching() {
+        let catalog = ModelCatalog::builtin();
+        let name = |model| catalog.get(model).map(|info| info.name.as_ref());
+
+        assert_eq!(name("gpt-4o"), Some("gpt-4o"));
+        assert_eq!(name("gpt-4o-2024-08-06"), Some("gpt-4o"));
+        assert_eq!(name("gpt-4o-mini-2024-07-18"), Some("gpt-4o-mini"));
+        assert_eq!(name("GPT-4-0613"), Some("gpt-4"));
+        assert_eq!(name("gpt-4-turbo-2024-04-09"), Some("gpt-4-turbo"));
+        assert_eq!(name("claude-3-5-sonnet-latest"), Some("claude-3-5-sonnet"));
+        assert_eq!(name("claude-3-5-sonnet@20240620"), Some("claude-3-5-sonnet"));
+        assert_eq!(name("anthropic/claude-3-haiku-20240307"), Some("claude-3-haiku"));
+        assert_eq!(name("mistral-large-2411"), Some("mistral-large"));
+        assert_eq!(name("o1-mini-2024-09-12"), Some("o1-mini"));
+
+        // Prefixes only count at a separator
+        assert_eq!(name("gpt-4oo"), None);
+        assert_eq!(name("o10"), None);
+        assert_eq!(name("llama-3.1-8b"), None);
+    }
+
+    #[test]
+    fn test_override_precedence() {
+        let catalog = ModelCatalog::builtin()
+            .clone()
+            .with_overrides_json(
+                r#"[
+                    {"name": "GPT-4o", "context_window": 128000, "max_output_tokens": 16384,
+                     "vision": true, "pricing": {"input_per_million": 2.0, "output_per_million": 8.0}},
+                    {"name": "gpt-4o-2024-05-13", "context_window": 128000, "max_output_tokens": 4096}
+                ]"#,
+            )
+            .unwrap();
+
+        // Replaced, not duplicated
+        assert_eq!(catalog.models().len(), ModelCatalog::builtin().models().len() + 1);
+        let gpt_4o = catalog.get("gpt-4o-2024-08-06").unwrap();
+        assert_eq!(gpt_4o.pricing.unwrap().input_per_million, 2.0);
+        assert!(!gpt_4o.function_calling);
+        // A more specific entry beats the base model
+        assert_eq!(catalog.get("gpt-4o-2024-05-13").unwrap().max_output_tokens, 4096);
+        assert_eq!(catalog.get("gpt-4o-2024-05-13").unwrap().pricing, None);
+        // The built-in table is untouched
+        assert_eq!(ModelCatalog::builtin().get("gpt-4o").unwrap().pricing.unwrap().input_per_million, 2.5);
+
+        let invalid = ModelCatalog::new().with_overrides_json(r#"[{"name": " ", "context_window": 1}]"#);
+        assert!(invalid.is_err());
+        assert!(ModelCatalog::new().with_overrides_json(r#"{"name": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn test_restrict_and_fits() {
+        let provider = Capabilities {
+            streaming: true,
+            function_calling: true,
+            vision: true,
+            json_schema: true,
+            max_tokens: 16_384,
+            ..Default::default()
+        };
+        let o1_mini = ModelCatalog::builtin().get("o1-mini").unwrap();
+        let restricted = o1_mini.restrict(provider.clone());
+        assert!(restricted.streaming);
+        assert!(!restricted.vision && !restricted.function_calling && !restricted.json_schema);
+        assert_eq!(restricted.max_tokens, 65_536);
+
+        let gpt_4 = ModelCatalog::builtin().get("gpt-4").unwrap();
+        let request = |max_tokens| {
+            CompletionRequest::builder()
+                .model("gpt-4")
+                .message(crate::message::Message::user("Hello"))
+                .max_tokens(max_tokens)
+                .build()
+                .unwrap()
+        };
+        assert!(gpt_4.fits(&request(4_096)));
+        assert!(!gpt_4.fits(&request(8_192)));
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Provides configuration for retry, healing, and provider capabilities.
 
+use crate::error::{ProviderError, SimpleAgentsError};
+use futures_core::future::BoxFuture;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Retry configuration for failed requests.
@@ -64,6 +68,72 @@ impl RetryConfig {
     }
 }
 
+/// Sleeps for the given duration on the caller's async runtime.
+pub type SleepFn = Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Retry schedule plus the timer to wait with, for
+/// [`Provider::execute_with_retries`](crate::provider::Provider::execute_with_retries).
+///
+/// This crate has no async runtime, so the policy carries the sleep
+/// function; `simple_agents_providers::retry::policy` builds one on Tokio.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::config::{RetryConfig, RetryPolicy};
+/// use std::time::Duration;
+///
+/// // Retry without waiting, e.g. in tests
+/// let policy = RetryPolicy::new(RetryConfig::default(), |_: Duration| async {});
+/// assert_eq!(policy.config.max_attempts, 3);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Attempts and backoff schedule
+    pub config: RetryConfig,
+    sleep: SleepFn,
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy that waits between attempts with `sleep`.
+    pub fn new<F, Fut>(config: RetryConfig, sleep: F) -> Self
+    where
+        F: Fn(Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            config,
+            sleep: Arc::new(move |duration| Box::pin(sleep(duration))),
+        }
+    }
+
+    /// Delay before retrying after the failed attempt `attempt` (0-based).
+    ///
+    /// The backoff from [`RetryConfig::calculate_backoff`], raised to the
+    /// provider's `retry_after` hint on rate-limit errors.
+    pub fn delay(&self, attempt: u32, error: &SimpleAgentsError) -> Duration {
+        let backoff = self.config.calculate_backoff(attempt);
+        match error.root() {
+            SimpleAgentsError::Provider(ProviderError::RateLimit {
+                retry_after: Some(retry_after),
+            }) => backoff.max(*retry_after),
+            _ => backoff,
+        }
+    }
+
+    /// Wait for `duration`.
+    pub async fn sleep(&self, duration: Duration) {
+        (self.sleep)(duration).await
+    }
+}
+
 // Cryptographically secure random number generator for jitter (0.0-1.0)
 fn rand() -> f32 {
     use rand::Rng;
@@ -126,6 +196,61 @@ impl HealingConfig {
     }
 }
 
+/// An optional provider feature.
+///
+/// Used both to describe [`Capabilities`] and to report
+/// [`ProviderError::Unsupported`](crate::error::ProviderError::Unsupported).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Streaming responses
+    Streaming,
+    /// Function/tool calling
+    FunctionCalling,
+    /// Vision/image inputs
+    Vision,
+    /// JSON schema constrained output
+    JsonSchema,
+    /// Text embeddings
+    Embeddings,
+    /// Continuing a partial assistant message
+    AssistantPrefill,
+    /// Document (e.g. PDF) inputs
+    Documents,
+}
+
+impl Feature {
+    /// All features, in declaration order.
+    pub const ALL: [Feature; 7] = [
+        Feature::Streaming,
+        Feature::FunctionCalling,
+        Feature::Vision,
+        Feature::JsonSchema,
+        Feature::Embeddings,
+        Feature::AssistantPrefill,
+        Feature::Documents,
+    ];
+
+    /// Snake-case name of the feature (e.g. "function_calling").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::Streaming => "streaming",
+            Feature::FunctionCalling => "function_calling",
+            Feature::Vision => "vision",
+            Feature::JsonSchema => "json_schema",
+            Feature::Embeddings => "embeddings",
+            Feature::AssistantPrefill => "assistant_prefill",
+            Feature::Documents => "documents",
+        }
+    }
+}
+
+impl std::fmt::Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Provider capabilities.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Capabilities {
@@ -135,10 +260,51 @@ pub struct Capabilities {
     pub function_calling: bool,
     /// Supports vision/image inputs
     pub vision: bool,
+    /// Supports JSON schema constrained output
+    #[serde(default)]
+    pub json_schema: bool,
+    /// Supports text embeddings
+    #[serde(default)]
+    pub embeddings: bool,
+    /// Supports continuing a partial assistant message
+    #[serde(default)]
+    pub assistant_prefill: bool,
+    /// Supports document (e.g. PDF) inputs
+    #[serde(default)]
+    pub documents: bool,
     /// Maximum output tokens
     pub max_tokens: u32,
 }
 
+impl Capabilities {
+    /// Check whether a feature is supported.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::config::{Capabilities, Feature};
+    ///
+    /// let caps = Capabilities { streaming: true, ..Default::default() };
+    /// assert!(caps.supports(Feature::Streaming));
+    /// assert!(!caps.supports(Feature::Vision));
+    /// ```
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Streaming => self.streaming,
+            Feature::FunctionCalling => self.function_calling,
+            Feature::Vision => self.vision,
+            Feature::JsonSchema => self.json_schema,
+            Feature::Embeddings => self.embeddings,
+            Feature::AssistantPrefill => self.assistant_prefill,
+            Feature::Documents => self.documents,
+        }
+    }
+
+    /// First feature in `required` that is not supported, if any.
+    pub fn first_missing(&self, required: &[Feature]) -> Option<Feature> {
+        required.iter().copied().find(|feature| !self.supports(*feature))
+    }
+}
+
 /// Provider configuration.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProviderConfig {
@@ -221,6 +387,43 @@ mod duration_millis {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_capabilities_supports() {
+        let caps = Capabilities {
+            streaming: true,
+            json_schema: true,
+            ..Default::default()
+        };
+
+        assert!(caps.supports(Feature::Streaming));
+        assert!(caps.supports(Feature::JsonSchema));
+        assert!(!caps.supports(Feature::FunctionCalling));
+        assert_eq!(
+            caps.first_missing(&[Feature::Streaming, Feature::Vision]),
+            Some(Feature::Vision)
+        );
+        assert_eq!(caps.first_missing(&[Feature::Streaming]), None);
+    }
+
+    #[test]
+    fn test_feature_names() {
+        for feature in Feature::ALL {
+            let json = serde_json::to_string(&feature).unwrap();
+            assert_eq!(json, format!("\"{}\"", feature));
+        }
+        assert_eq!(Feature::FunctionCalling.to_string(), "function_calling");
+    }
+
+    #[test]
+    fn test_capabilities_deserialize_without_new_fields() {
+        let json = r#"{"streaming": true, "function_calling": false, "vision": false, "max_tokens": 1024}"#;
+        let caps: Capabilities = serde_json::from_str(json).unwrap();
+        assert!(caps.streaming);
+        assert!(!caps.json_schema);
+        assert!(!caps.embeddings);
+        assert!(!caps.assistant_prefill);
+    }
+
     #[test]
     fn test_retry_config_default() {
         let config = RetryConfig::default();
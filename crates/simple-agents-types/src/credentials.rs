@@ -0,0 +1,100 @@
+//! Credential sources for providers whose API keys change over time.
+//!
+//! A provider consults its [`CredentialSource`] on every request, so keys
+//! rotated by a secret store (e.g. Vault) are picked up without rebuilding
+//! the provider. A fixed [`ApiKey`] is itself a credential source.
+
+use crate::error::Result;
+use crate::validation::ApiKey;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Supplies the API key to use for a request.
+///
+/// # Example Implementation
+///
+/// ```
+/// use simple_agents_types::credentials::CredentialSource;
+/// use simple_agents_types::error::Result;
+/// use simple_agents_types::validation::ApiKey;
+/// use async_trait::async_trait;
+///
+/// struct EnvCredentials;
+///
+/// #[async_trait]
+/// impl CredentialSource for EnvCredentials {
+///     async fn current_key(&self) -> Result<ApiKey> {
+///         ApiKey::new(std::env::var("OPENAI_API_KEY").unwrap_or_default())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait CredentialSource: Send + Sync {
+    /// Key to use for the next request.
+    ///
+    /// Called once per request; implementations that fetch keys remotely
+    /// should cache them.
+    async fn current_key(&self) -> Result<ApiKey>;
+
+    /// Most recently fetched key, if available without waiting.
+    ///
+    /// Used where a key is needed synchronously, such as
+    /// [`Provider::transform_request`](crate::provider::Provider::transform_request);
+    /// the key is fetched again with [`current_key`](Self::current_key)
+    /// before the request is sent.
+    fn cached_key(&self) -> Option<ApiKey> {
+        None
+    }
+
+    /// Discard any cached key after the provider rejected it.
+    ///
+    /// Returns `true` if [`current_key`](Self::current_key) may now return
+    /// a different key, in which case the rejected request is retried
+    /// once. The default returns `false`.
+    fn invalidate(&self) -> bool {
+        false
+    }
+}
+
+#[async_trait]
+impl CredentialSource for ApiKey {
+    async fn current_key(&self) -> Result<ApiKey> {
+        Ok(self.clone())
+    }
+
+    fn cached_key(&self) -> Option<ApiKey> {
+        Some(self.clone())
+    }
+}
+
+#[async_trait]
+impl<T: CredentialSource + ?Sized> CredentialSource for Arc<T> {
+    async fn current_key(&self) -> Result<ApiKey> {
+        (**self).current_key().await
+    }
+
+    fn cached_key(&self) -> Option<ApiKey> {
+        (**self).cached_key()
+    }
+
+    fn invalidate(&self) -> bool {
+        (**self).invalidate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_api_key_is_a_fixed_source() {
+        let key = ApiKey::new("sk-test1234567890123456789012345678901234567890").unwrap();
+
+        assert_eq!(key.current_key().await.unwrap(), key);
+        assert_eq!(key.cached_key(), Some(key.clone()));
+        assert!(!key.invalidate());
+
+        let shared: Arc<dyn CredentialSource> = Arc::new(key.clone());
+        assert_eq!(shared.current_key().await.unwrap(), key);
+    }
+}
@@ -0,0 +1,787 @@
+//! Structured diffs between responses and transcripts.
+//!
+//! When a prompt template changes, [`ResponseDiff::between`] shows how the
+//! output drifted from a baseline: a word-level diff of the content,
+//! changed finish reasons, usage deltas and tool calls that were added,
+//! removed or called with different arguments. [`TranscriptDiff`] does the
+//! same message by message for whole [`Conversation`]s. Both render as a
+//! human-readable unified diff through [`std::fmt::Display`].
+//!
+//! # Example
+//! ```
+//! use simple_agents_types::diff::ResponseDiff;
+//! use simple_agents_types::prelude::*;
+//! # fn response(content: &str) -> CompletionResponse {
+//! #     CompletionResponse {
+//! #         id: "resp".to_string(),
+//! #         model: "gpt-4o".to_string(),
+//! #         choices: vec![CompletionChoice {
+//! #             index: 0,
+//! #             message: Message::assistant(content),
+//! #             finish_reason: FinishReason::Stop,
+//! #             logprobs: None,
+//! #             matched_stop: None,
+//! #             annotations: Vec::new(),
+//! #         }],
+//! #         usage: Usage::new(10, 5),
+//! #         created: None,
+//! #         created_synthesized: false,
+//! #         provider: None,
+//! #     }
+//! # }
+//!
+//! let baseline = response("The capital of France is Paris.");
+//! let candidate = response("The capital of France is Paris, of course.");
+//!
+//! let diff = ResponseDiff::between(&baseline, &candidate);
+//! assert!(!diff.is_unchanged());
+//! assert!(diff.to_string().contains("+The capital of France is Paris, of course."));
+//! ```
+
+use crate::export::Conversation;
+use crate::message::{Message, Role};
+use crate::response::{CompletionResponse, FinishReason};
+use crate::tool::ToolCall;
+use serde_json::Value;
+use std::fmt;
+
+/// A run of text in a [`TextDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextChange {
+    /// Text present in both
+    Equal(String),
+    /// Text only in the baseline
+    Delete(String),
+    /// Text only in the candidate
+    Insert(String),
+}
+
+/// Word-level diff of two texts.
+///
+/// Words and the whitespace between them are compared as separate tokens,
+/// so joining the runs reproduces either text exactly.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TextDiff {
+    /// Runs of equal, deleted and inserted text, in order
+    pub changes: Vec<TextChange>,
+}
+
+impl TextDiff {
+    /// Diff `baseline` against `candidate` word by word.
+    pub fn words(baseline: &str, candidate: &str) -> Self {
+        let (old, new) = (tokenize(baseline), tokenize(candidate));
+        let mut changes: Vec<TextChange> = Vec::new();
+        for edit in align(&old, &new) {
+            let (token, push): (&str, fn(String) -> TextChange) = match edit {
+                Edit::Equal(token) => (token, TextChange::Equal),
+                Edit::Delete(token) => (token, TextChange::Delete),
+                Edit::Insert(token) => (token, TextChange::Insert),
+            };
+            match (changes.last_mut(), push(String::new())) {
+                (Some(TextChange::Equal(run)), TextChange::Equal(_))
+                | (Some(TextChange::Delete(run)), TextChange::Delete(_))
+                | (Some(TextChange::Insert(run)), TextChange::Insert(_)) => run.push_str(token),
+                _ => changes.push(push(token.to_string())),
+            }
+        }
+        Self { changes }
+    }
+
+    /// Whether both texts are identical.
+    pub fn is_unchanged(&self) -> bool {
+        self.changes.iter().all(|change| matches!(change, TextChange::Equal(_)))
+    }
+
+    /// The baseline text.
+    pub fn baseline(&self) -> String {
+        self.changes
+            .iter()
+            .filter_map(|change| match change {
+                TextChange::Equal(text) | TextChange::Delete(text) => Some(text.as_str()),
+                TextChange::Insert(_) => None,
+            })
+            .collect()
+    }
+
+    /// The candidate text.
+    pub fn candidate(&self) -> String {
+        self.changes
+            .iter()
+            .filter_map(|change| match change {
+                TextChange::Equal(text) | TextChange::Insert(text) => Some(text.as_str()),
+                TextChange::Delete(_) => None,
+            })
+            .collect()
+    }
+
+    /// Write a line-level unified diff of the two texts.
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (baseline, candidate) = (self.baseline(), self.candidate());
+        let old: Vec<&str> = baseline.lines().collect();
+        let new: Vec<&str> = candidate.lines().collect();
+        for edit in align(&old, &new) {
+            match edit {
+                Edit::Equal(line) => writeln!(f, " {}", line)?,
+                Edit::Delete(line) => writeln!(f, "-{}", line)?,
+                Edit::Insert(line) => writeln!(f, "+{}", line)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A difference between two JSON values, located by JSON Pointer
+/// (e.g. `/location/city`; the empty string is the whole value).
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonChange {
+    /// Present only in the candidate
+    Added {
+        /// Location of the value
+        path: String,
+        /// The new value
+        value: Value,
+    },
+    /// Present only in the baseline
+    Removed {
+        /// Location of the value
+        path: String,
+        /// The old value
+        value: Value,
+    },
+    /// Present in both with different values
+    Changed {
+        /// Location of the value
+        path: String,
+        /// The baseline value
+        from: Value,
+        /// The candidate value
+        to: Value,
+    },
+}
+
+/// Diff two JSON values.
+///
+/// Objects are compared key by key and arrays index by index; any other
+/// difference is reported as a [`JsonChange::Changed`] at its path.
+pub fn diff_json(baseline: &Value, candidate: &Value) -> Vec<JsonChange> {
+    let mut changes = Vec::new();
+    diff_json_at(String::new(), baseline, candidate, &mut changes);
+    changes
+}
+
+fn diff_json_at(path: String, baseline: &Value, candidate: &Value, changes: &mut Vec<JsonChange>) {
+    match (baseline, candidate) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let child = format!("{}/{}", path, escape_pointer(key));
+                match new.get(key) {
+                    Some(new_value) => diff_json_at(child, old_value, new_value, changes),
+                    None => changes.push(JsonChange::Removed {
+                        path: child,
+                        value: old_value.clone(),
+                    }),
+                }
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                changes.push(JsonChange::Added {
+                    path: format!("{}/{}", path, escape_pointer(key)),
+                    value: new_value.clone(),
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let child = format!("{}/{}", path, index);
+                match (old.get(index), new.get(index)) {
+                    (Some(old_value), Some(new_value)) => diff_json_at(child, old_value, new_value, changes),
+                    (Some(value), None) => changes.push(JsonChange::Removed {
+                        path: child,
+                        value: value.clone(),
+                    }),
+                    (None, Some(value)) => changes.push(JsonChange::Added {
+                        path: child,
+                        value: value.clone(),
+                    }),
+                    (None, None) => unreachable!("index is below one of the lengths"),
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(JsonChange::Changed {
+            path,
+            from: old.clone(),
+            to: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// A difference in the tool calls of two messages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolCallChange {
+    /// A call made only by the candidate
+    Added(ToolCall),
+    /// A call made only by the baseline
+    Removed(ToolCall),
+    /// The same function called with different arguments
+    ArgumentsChanged {
+        /// Function name
+        name: String,
+        /// Argument differences
+        changes: Vec<JsonChange>,
+    },
+}
+
+/// Diff two lists of tool calls.
+///
+/// Call IDs differ from run to run, so calls are paired by function name,
+/// in order. Arguments that are not valid JSON are compared as strings.
+pub fn diff_tool_calls(baseline: &[ToolCall], candidate: &[ToolCall]) -> Vec<ToolCallChange> {
+    let mut unmatched: Vec<&ToolCall> = candidate.iter().collect();
+    let mut changes = Vec::new();
+
+    for old in baseline {
+        let Some(position) = unmatched.iter().position(|new| new.function.name == old.function.name) else {
+            changes.push(ToolCallChange::Removed(old.clone()));
+            continue;
+        };
+        let new = unmatched.remove(position);
+        let arguments = diff_json(
+            &parse_arguments(&old.function.arguments),
+            &parse_arguments(&new.function.arguments),
+        );
+        if !arguments.is_empty() {
+            changes.push(ToolCallChange::ArgumentsChanged {
+                name: old.function.name.clone(),
+                changes: arguments,
+            });
+        }
+    }
+    changes.extend(unmatched.into_iter().map(|new| ToolCallChange::Added(new.clone())));
+    changes
+}
+
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
+
+/// Differences between two messages' content and tool calls.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MessageDiff {
+    /// Word-level content diff
+    pub content: TextDiff,
+    /// Tool call differences
+    pub tool_calls: Vec<ToolCallChange>,
+}
+
+impl MessageDiff {
+    /// Diff `baseline` against `candidate`.
+    pub fn between(baseline: &Message, candidate: &Message) -> Self {
+        Self {
+            content: TextDiff::words(&baseline.content, &candidate.content),
+            tool_calls: diff_tool_calls(&baseline.tool_calls, &candidate.tool_calls),
+        }
+    }
+
+    /// Whether content and tool calls are identical.
+    pub fn is_unchanged(&self) -> bool {
+        self.content.is_unchanged() && self.tool_calls.is_empty()
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.content.is_unchanged() {
+            self.content.render(f)?;
+        }
+        for change in &self.tool_calls {
+            match change {
+                ToolCallChange::Added(ToolCall { function, .. }) => {
+                    writeln!(f, "+{}({})", function.name, function.arguments)?
+                }
+                ToolCallChange::Removed(ToolCall { function, .. }) => {
+                    writeln!(f, "-{}({})", function.name, function.arguments)?
+                }
+                ToolCallChange::ArgumentsChanged { name, changes } => {
+                    writeln!(f, " {}(..)", name)?;
+                    for change in changes {
+                        match change {
+                            JsonChange::Added { path, value } => writeln!(f, "+  {}: {}", path, value)?,
+                            JsonChange::Removed { path, value } => writeln!(f, "-  {}: {}", path, value)?,
+                            JsonChange::Changed { path, from, to } => {
+                                writeln!(f, "-  {}: {}", path, from)?;
+                                writeln!(f, "+  {}: {}", path, to)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A changed finish reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinishReasonChange {
+    /// The baseline's finish reason, if it had a choice
+    pub from: Option<FinishReason>,
+    /// The candidate's finish reason, if it had a choice
+    pub to: Option<FinishReason>,
+}
+
+/// Change in token usage, candidate minus baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UsageDelta {
+    /// Change in prompt tokens
+    pub prompt_tokens: i64,
+    /// Change in completion tokens
+    pub completion_tokens: i64,
+    /// Change in total tokens
+    pub total_tokens: i64,
+}
+
+/// How a candidate response differs from a baseline.
+///
+/// Only the first choice of each response is compared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseDiff {
+    /// Content and tool call differences
+    pub message: MessageDiff,
+    /// The finish reason, if it changed
+    pub finish_reason: Option<FinishReasonChange>,
+    /// Change in token usage
+    pub usage: UsageDelta,
+}
+
+impl ResponseDiff {
+    /// Diff `candidate` against `baseline`.
+    pub fn between(baseline: &CompletionResponse, candidate: &CompletionResponse) -> Self {
+        let empty = Message::assistant("");
+        let message_of = |response: &CompletionResponse| {
+            response.first_choice().map(|choice| choice.message.clone()).unwrap_or_else(|| empty.clone())
+        };
+        let from = baseline.first_choice().map(|choice| choice.finish_reason);
+        let to = candidate.first_choice().map(|choice| choice.finish_reason);
+        let delta = |old: u32, new: u32| i64::from(new) - i64::from(old);
+
+        Self {
+            message: MessageDiff::between(&message_of(baseline), &message_of(candidate)),
+            finish_reason: (from != to).then_some(FinishReasonChange { from, to }),
+            usage: UsageDelta {
+                prompt_tokens: delta(baseline.usage.prompt_tokens, candidate.usage.prompt_tokens),
+                completion_tokens: delta(baseline.usage.completion_tokens, candidate.usage.completion_tokens),
+                total_tokens: delta(baseline.usage.total_tokens, candidate.usage.total_tokens),
+            },
+        }
+    }
+
+    /// Whether content, tool calls and finish reason are identical.
+    ///
+    /// Usage is not considered, since it drifts with any change in wording.
+    pub fn is_unchanged(&self) -> bool {
+        self.message.is_unchanged() && self.finish_reason.is_none()
+    }
+}
+
+impl fmt::Display for ResponseDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "--- baseline")?;
+        writeln!(f, "+++ candidate")?;
+        if !self.message.is_unchanged() {
+            writeln!(f, "@@ message @@")?;
+            self.message.render(f)?;
+        }
+        if let Some(change) = self.finish_reason {
+            let name = |reason: Option<FinishReason>| reason.map_or("none", |reason| reason.as_str());
+            writeln!(f, "@@ finish_reason @@")?;
+            writeln!(f, "-{}", name(change.from))?;
+            writeln!(f, "+{}", name(change.to))?;
+        }
+        if self.usage != UsageDelta::default() {
+            writeln!(f, "@@ usage @@")?;
+            writeln!(f, " prompt_tokens {:+}", self.usage.prompt_tokens)?;
+            writeln!(f, " completion_tokens {:+}", self.usage.completion_tokens)?;
+            writeln!(f, " total_tokens {:+}", self.usage.total_tokens)?;
+        }
+        Ok(())
+    }
+}
+
+/// A difference at one position of a [`TranscriptDiff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageChange {
+    /// Both transcripts have a message with the same role here, with
+    /// different content or tool calls
+    Changed {
+        /// Position in both transcripts
+        index: usize,
+        /// Role of both messages
+        role: Role,
+        /// The differences
+        diff: MessageDiff,
+    },
+    /// Only the candidate has this message (or the roles differ)
+    Added {
+        /// Position in the candidate
+        index: usize,
+        /// The message
+        message: Message,
+    },
+    /// Only the baseline has this message (or the roles differ)
+    Removed {
+        /// Position in the baseline
+        index: usize,
+        /// The message
+        message: Message,
+    },
+}
+
+/// How a candidate transcript differs from a baseline, message by message.
+///
+/// Messages are paired by position; unchanged messages are omitted.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TranscriptDiff {
+    /// Differences in transcript order
+    pub messages: Vec<MessageChange>,
+}
+
+impl TranscriptDiff {
+    /// Diff the messages of `candidate` against `baseline`.
+    pub fn between(baseline: &Conversation, candidate: &Conversation) -> Self {
+        let (old, new) = (&baseline.messages, &candidate.messages);
+        let mut messages = Vec::new();
+        for index in 0..old.len().max(new.len()) {
+            match (old.get(index), new.get(index)) {
+                (Some(old), Some(new)) if old.role == new.role => {
+                    let diff = MessageDiff::between(old, new);
+                    if !diff.is_unchanged() {
+                        messages.push(MessageChange::Changed {
+                            index,
+                            role: old.role,
+                            diff,
+                        });
+                    }
+                }
+                (old, new) => {
+                    if let Some(message) = old {
+                        messages.push(MessageChange::Removed {
+                            index,
+                            message: message.clone(),
+                        });
+                    }
+                    if let Some(message) = new {
+                        messages.push(MessageChange::Added {
+                            index,
+                            message: message.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Self { messages }
+    }
+
+    /// Whether both transcripts have the same messages.
+    pub fn is_unchanged(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl fmt::Display for TranscriptDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "--- baseline")?;
+        writeln!(f, "+++ candidate")?;
+        for change in &self.messages {
+            match change {
+                MessageChange::Changed { index, role, diff } => {
+                    writeln!(f, "@@ message {} ({}) @@", index, role_name(*role))?;
+                    diff.render(f)?;
+                }
+                MessageChange::Added { index, message } => {
+                    writeln!(f, "@@ message {} ({}) added @@", index, role_name(message.role))?;
+                    MessageDiff::between(&emptied(message), message).render(f)?;
+                }
+                MessageChange::Removed { index, message } => {
+                    writeln!(f, "@@ message {} ({}) removed @@", index, role_name(message.role))?;
+                    MessageDiff::between(message, &emptied(message)).render(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `message` without content or tool calls, to render it as all added or
+/// all removed.
+fn emptied(message: &Message) -> Message {
+    Message {
+        content: String::new(),
+        tool_calls: Vec::new(),
+        ..message.clone()
+    }
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// Split text into alternating runs of whitespace and non-whitespace.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (offset, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|previous| previous != space) {
+            tokens.push(&text[start..offset]);
+            start = offset;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// One step of an alignment produced by [`align`].
+enum Edit<'a, T> {
+    Equal(&'a T),
+    Delete(&'a T),
+    Insert(&'a T),
+}
+
+/// Align two sequences along their longest common subsequence.
+///
+/// A common prefix and suffix are matched directly, so the quadratic table
+/// only covers the part that changed.
+fn align<'a, T: PartialEq>(old: &'a [T], new: &'a [T]) -> Vec<Edit<'a, T>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    // lengths[i][j]: longest common subsequence of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lengths = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits: Vec<Edit<'a, T>> = old[..prefix].iter().map(Edit::Equal).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            edits.push(Edit::Equal(&a[i]));
+            i += 1;
+            j += 1;
+        } else if j == b.len()
+            || (i < a.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            edits.push(Edit::Delete(&a[i]));
+            i += 1;
+        } else {
+            edits.push(Edit::Insert(&b[j]));
+            j += 1;
+        }
+    }
+    edits.extend(old[old.len() - suffix..].iter().map(Edit::Equal));
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::{CompletionChoice, Usage};
+    use serde_json::json;
+
+    fn response(message: Message, finish_reason: FinishReason, usage: Usage) -> CompletionResponse {
+        CompletionResponse {
+            id: "resp".to_string(),
+            model: "gpt-4o".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message,
+                finish_reason,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage,
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        }
+    }
+
+    fn call(id: &str, name: &str, arguments: Value) -> ToolCall {
+        ToolCall::function(id, name, arguments.to_string())
+    }
+
+    #[test]
+    fn test_word_diff_round_trips() {
+        let diff = TextDiff::words("The quick brown fox", "The slow brown fox jumps");
+        assert_eq!(
+            diff.changes,
+            vec![
+                TextChange::Equal("The ".to_string()),
+                TextChange::Delete("quick".to_string()),
+                TextChange::Insert("slow".to_string()),
+                TextChange::Equal(" brown fox".to_string()),
+                TextChange::Insert(" jumps".to_string()),
+            ]
+        );
+        assert_eq!(diff.baseline(), "The quick brown fox");
+        assert_eq!(diff.candidate(), "The slow brown fox jumps");
+        assert!(TextDiff::words("same  text\n", "same  text\n").is_unchanged());
+    }
+
+    #[test]
+    fn test_json_diff_paths() {
+        let changes = diff_json(
+            &json!({"city": "Paris", "units": "metric", "days": [1, 2], "a/b": 1}),
+            &json!({"city": "Paris", "units": "imperial", "days": [1], "lang": "fr", "a/b": 1}),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                JsonChange::Removed { path: "/days/1".to_string(), value: json!(2) },
+                JsonChange::Changed {
+                    path: "/units".to_string(),
+                    from: json!("metric"),
+                    to: json!("imperial"),
+                },
+                JsonChange::Added { path: "/lang".to_string(), value: json!("fr") },
+            ]
+        );
+        assert_eq!(diff_json(&json!(1), &json!("1"))[0], JsonChange::Changed {
+            path: String::new(),
+            from: json!(1),
+            to: json!("1"),
+        });
+    }
+
+    #[test]
+    fn test_tool_call_changes() {
+        let baseline = vec![
+            call("call_1", "get_weather", json!({"city": "Paris", "units": "metric"})),
+            call("call_2", "search", json!({"query": "museums"})),
+        ];
+        let candidate = vec![
+            call("call_9", "get_weather", json!({"city": "Paris", "units": "imperial"})),
+            call("call_8", "book_table", json!({"guests": 2})),
+        ];
+
+        let changes = diff_tool_calls(&baseline, &candidate);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes[0],
+            ToolCallChange::ArgumentsChanged {
+                name: "get_weather".to_string(),
+                changes: vec![JsonChange::Changed {
+                    path: "/units".to_string(),
+                    from: json!("metric"),
+                    to: json!("imperial"),
+                }],
+            }
+        );
+        assert!(matches!(&changes[1], ToolCallChange::Removed(c) if c.function.name == "search"));
+        assert!(matches!(&changes[2], ToolCallChange::Added(c) if c.function.name == "book_table"));
+
+        // IDs alone do not count as a change
+        let renamed = vec![call("other", "search", json!({"query": "museums"}))];
+        assert!(diff_tool_calls(&baseline[1..], &renamed).is_empty());
+    }
+
+    #[test]
+    fn test_response_diff_and_rendering() {
+        let baseline = response(
+            Message::assistant("Let me check.\nOne moment."),
+            FinishReason::Stop,
+            Usage::new(20, 6),
+        );
+        let mut message = Message::assistant("Let me check.");
+        message.tool_calls = vec![call("call_1", "get_weather", json!({"city": "Paris"}))];
+        let candidate = response(message, FinishReason::ToolCalls, Usage::new(20, 15));
+
+        let diff = ResponseDiff::between(&baseline, &candidate);
+        assert!(!diff.is_unchanged());
+        assert_eq!(
+            diff.finish_reason,
+            Some(FinishReasonChange {
+                from: Some(FinishReason::Stop),
+                to: Some(FinishReason::ToolCalls),
+            })
+        );
+        assert_eq!(diff.usage, UsageDelta { prompt_tokens: 0, completion_tokens: 9, total_tokens: 9 });
+
+        assert_eq!(
+            diff.to_string(),
+            "--- baseline\n\
+             +++ candidate\n\
+             @@ message @@\n \
+             Let me check.\n\
+             -One moment.\n\
+             +get_weather({\"city\":\"Paris\"})\n\
+             @@ finish_reason @@\n\
+             -stop\n\
+             +tool_calls\n\
+             @@ usage @@\n \
+             prompt_tokens +0\n \
+             completion_tokens +9\n \
+             total_tokens +9\n"
+        );
+
+        let same = ResponseDiff::between(&baseline, &baseline);
+        assert!(same.is_unchanged());
+        assert_eq!(same.to_string(), "--- baseline\n+++ candidate\n");
+    }
+
+    #[test]
+    fn test_transcript_diff() {
+        let mut assistant = Message::assistant("");
+        assistant.tool_calls = vec![call("call_1", "get_weather", json!({"city": "Paris"}))];
+        let baseline = Conversation::new(vec![
+            Message::system("Be brief."),
+            Message::user("Weather in Paris?"),
+            assistant.clone(),
+        ]);
+        let mut changed = assistant.clone();
+        changed.tool_calls = vec![call("call_2", "get_weather", json!({"city": "Paris", "days": 3}))];
+        let candidate = Conversation::new(vec![
+            Message::system("Be brief and friendly."),
+            Message::user("Weather in Paris?"),
+            changed,
+            Message::tool("Sunny", "call_2"),
+        ]);
+
+        let diff = TranscriptDiff::between(&baseline, &candidate);
+        assert_eq!(diff.messages.len(), 3);
+        assert!(matches!(&diff.messages[0], MessageChange::Changed { index: 0, role: Role::System, .. }));
+        assert!(matches!(&diff.messages[1], MessageChange::Changed { index: 2, role: Role::Assistant, .. }));
+        assert!(matches!(&diff.messages[2], MessageChange::Added { index: 3, .. }));
+
+        let rendered = diff.to_string();
+        assert!(rendered.contains("@@ message 0 (system) @@\n-Be brief.\n+Be brief and friendly.\n"));
+        assert!(rendered.contains("@@ message 2 (assistant) @@\n get_weather(..)\n+  /days: 3\n"));
+        assert!(rendered.contains("@@ message 3 (tool) added @@\n+Sunny\n"));
+        assert!(TranscriptDiff::between(&baseline, &baseline).is_unchanged());
+    }
+}
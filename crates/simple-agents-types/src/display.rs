@@ -0,0 +1,389 @@
+//! Terminal rendering of streamed completions.
+//!
+//! [`CompletionResponse::streaming_display`] writes tokens as they arrive
+//! and returns the accumulated response, so a CLI can show progress and
+//! still keep the full result.
+
+use crate::error::{Result, SimpleAgentsError};
+use crate::response::{ChunkAccumulator, CompletionChunk, CompletionResponse, Usage};
+use futures_core::Stream;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_RED: &str = "\x1b[31m";
+/// Carriage return plus "erase line"
+const ANSI_CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// Price of a model in dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    /// Dollars per million prompt tokens
+    pub input_per_million: f64,
+    /// Dollars per million completion tokens
+    pub output_per_million: f64,
+}
+
+impl Pricing {
+    /// Dollar cost of `usage`.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::display::Pricing;
+    /// use simple_agents_types::response::Usage;
+    ///
+    /// let pricing = Pricing { input_per_million: 0.15, output_per_million: 0.60 };
+    /// assert!((pricing.cost(&Usage::new(1_000_000, 500_000)) - 0.45).abs() < 1e-9);
+    /// ```
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (f64::from(usage.prompt_tokens) * self.input_per_million
+            + f64::from(usage.completion_tokens) * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// What [`CompletionResponse::streaming_display_with`] prints besides the
+/// tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayOptions {
+    /// Use ANSI escape codes (dim metadata, red errors, line redraws)
+    pub color: bool,
+    /// Print the model and finish reason after the final token
+    pub show_metadata: bool,
+    /// Print token counts (and cost, with [`pricing`](Self::pricing)) after
+    /// the final token
+    pub show_usage: bool,
+    /// Prices used to show the cost of the completion
+    pub pricing: Option<Pricing>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            color: false,
+            show_metadata: true,
+            show_usage: true,
+            pricing: None,
+        }
+    }
+}
+
+impl CompletionResponse {
+    /// Write a streamed completion to `writer` and return the full response.
+    ///
+    /// Uses the default [`DisplayOptions`]; see
+    /// [`streaming_display_with`](Self::streaming_display_with).
+    pub async fn streaming_display<S, W>(stream: S, writer: &mut W) -> Result<CompletionResponse>
+    where
+        S: Stream<Item = Result<CompletionChunk>>,
+        W: Write,
+    {
+        Self::streaming_display_with(stream, writer, &DisplayOptions::default()).await
+    }
+
+    /// Write a streamed completion to `writer` as it arrives.
+    ///
+    /// The first choice's content is written (and flushed) chunk by chunk;
+    /// when the stream ends, a metadata line is printed as configured by
+    /// `options`. The chunks of every choice are accumulated into the
+    /// returned response. Usage comes from the stream's final usage chunk
+    /// (see [`StreamOptions`](crate::request::StreamOptions)) and is zero
+    /// when the provider sent none.
+    ///
+    /// # Errors
+    ///
+    /// On a stream error the current line is cleared and redrawn with the
+    /// error after it (only moved to a new line without `color`), then the
+    /// error is returned. Failing to write to `writer` returns
+    /// [`SimpleAgentsError::Config`].
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::display::DisplayOptions;
+    /// use simple_agents_types::prelude::*;
+    /// # async fn example(
+    /// #     stream: impl futures_core::Stream<Item = Result<CompletionChunk>>,
+    /// # ) -> Result<()> {
+    /// let options = DisplayOptions { color: true, ..Default::default() };
+    /// let response =
+    ///     CompletionResponse::streaming_display_with(stream, &mut std::io::stdout(), &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn streaming_display_with<S, W>(
+        stream: S,
+        writer: &mut W,
+        options: &DisplayOptions,
+    ) -> Result<CompletionResponse>
+    where
+        S: Stream<Item = Result<CompletionChunk>>,
+        W: Write,
+    {
+        let mut stream = std::pin::pin!(stream);
+        let mut accumulator = ChunkAccumulator::default();
+        let mut display = Display {
+            writer,
+            options,
+            line: String::new(),
+            ends_with_newline: true,
+        };
+
+        while let Some(item) = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            match item {
+                Ok(chunk) => {
+                    let text = chunk
+                        .choices
+                        .iter()
+                        .find(|choice| choice.index == 0)
+                        .and_then(|choice| choice.delta.content.as_deref());
+                    if let Some(text) = text {
+                        display.token(text)?;
+                    }
+                    accumulator.push(chunk);
+                }
+                Err(err) => {
+                    display.error(&err)?;
+                    return Err(err);
+                }
+            }
+        }
+
+        let response = accumulator.finish();
+        display.metadata(&response)?;
+        Ok(response)
+    }
+}
+
+/// Terminal state while tokens are written
+struct Display<'a, W: Write> {
+    writer: &'a mut W,
+    options: &'a DisplayOptions,
+    /// Text written since the last newline, for redraws
+    line: String,
+    ends_with_newline: bool,
+}
+
+impl<W: Write> Display<'_, W> {
+    fn token(&mut self, text: &str) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        match text.rfind('\n') {
+            Some(newline) => self.line = text[newline + 1..].to_string(),
+            None => self.line.push_str(text),
+        }
+        self.ends_with_newline = text.ends_with('\n');
+        self.write(text)
+    }
+
+    fn error(&mut self, err: &SimpleAgentsError) -> Result<()> {
+        let rendered = if self.options.color {
+            format!(
+                "{}{}{}{}{}[error: {}]{}\n",
+                ANSI_CLEAR_LINE,
+                ANSI_DIM,
+                self.line,
+                ANSI_RESET,
+                ANSI_RED,
+                err,
+                ANSI_RESET
+            )
+        } else {
+            format!("{}[error: {}]\n", self.line_break(), err)
+        };
+        self.write(&rendered)
+    }
+
+    fn metadata(&mut self, response: &CompletionResponse) -> Result<()> {
+        let mut fields = Vec::new();
+        if self.options.show_metadata {
+            fields.push(response.model.clone());
+            if let Some(choice) = response.first_choice() {
+                fields.push(choice.finish_reason.to_string());
+            }
+        }
+        if self.options.show_usage {
+            let usage = &response.usage;
+            fields.push(format!("tokens={}+{}", usage.prompt_tokens, usage.completion_tokens));
+            if let Some(pricing) = self.options.pricing {
+                fields.push(format!("${:.6}", pricing.cost(usage)));
+            }
+        }
+
+        let mut rendered = self.line_break().to_string();
+        if !fields.is_empty() {
+            let line = format!("[{}]", fields.join(" · "));
+            if self.options.color {
+                rendered.push_str(&format!("{}{}{}\n", ANSI_DIM, line, ANSI_RESET));
+            } else {
+                rendered.push_str(&line);
+                rendered.push('\n');
+            }
+        }
+        self.write(&rendered)
+    }
+
+    /// Newline needed to start on a fresh line
+    fn line_break(&self) -> &'static str {
+        if self.ends_with_newline {
+            ""
+        } else {
+            "\n"
+        }
+    }
+
+    fn write(&mut self, text: &str) -> Result<()> {
+        self.writer
+            .write_all(text.as_bytes())
+            .and_then(|()| self.writer.flush())
+            .map_err(|e| SimpleAgentsError::Config(format!("Failed to write completion: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ProviderError;
+    use crate::response::{ChoiceDelta, FinishReason, MessageDelta};
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    struct VecStream(VecDeque<Result<CompletionChunk>>);
+
+    impl Stream for VecStream {
+        type Item = Result<CompletionChunk>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.0.pop_front())
+        }
+    }
+
+    fn chunk(index: u32, content: &str, finish_reason: Option<FinishReason>) -> Result<CompletionChunk> {
+        Ok(CompletionChunk {
+            id: "chatcmpl-1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            choices: vec![ChoiceDelta {
+                index,
+                delta: MessageDelta {
+                    role: None,
+                    content: Some(content.to_string()),
+                },
+                finish_reason,
+            }],
+            created: Some(1_700_000_000),
+            usage: None,
+        })
+    }
+
+    fn usage_chunk() -> Result<CompletionChunk> {
+        Ok(CompletionChunk {
+            id: "chatcmpl-1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            choices: Vec::new(),
+            created: None,
+            usage: Some(Usage::new(1000, 2000)),
+        })
+    }
+
+    fn stream(items: Vec<Result<CompletionChunk>>) -> VecStream {
+        VecStream(items.into())
+    }
+
+    #[tokio::test]
+    async fn test_streaming_display() {
+        let mut out = Vec::new();
+        let response = CompletionResponse::streaming_display(
+            stream(vec![
+                chunk(0, "Hello", None),
+                chunk(1, "Other", None),
+                chunk(0, ", world", Some(FinishReason::Stop)),
+                chunk(1, " choice", Some(FinishReason::Length)),
+                usage_chunk(),
+            ]),
+            &mut out,
+        )
+        .await
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "Hello, world\n[gpt-4o-mini · stop · tokens=1000+2000]\n");
+        assert_eq!(response.id, "chatcmpl-1");
+        assert_eq!(response.content(), Some("Hello, world"));
+        assert_eq!(response.choices[1].message.content, "Other choice");
+        assert_eq!(response.choices[1].finish_reason, FinishReason::Length);
+        assert_eq!(response.usage.total_tokens, 3000);
+        assert_eq!(response.created, Some(1_700_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_display_options() {
+        let options = DisplayOptions {
+            color: true,
+            show_metadata: false,
+            show_usage: true,
+            pricing: Some(Pricing {
+                input_per_million: 0.15,
+                output_per_million: 0.60,
+            }),
+        };
+        let mut out = Vec::new();
+        CompletionResponse::streaming_display_with(
+            stream(vec![chunk(0, "Done.\n", Some(FinishReason::Stop)), usage_chunk()]),
+            &mut out,
+            &options,
+        )
+        .await
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Done.\n\x1b[2m[tokens=1000+2000 · $0.001350]"), "{:?}", out);
+        assert!(out.ends_with("\x1b[0m\n"));
+        assert!(!out.contains("gpt-4o-mini"));
+
+        let options = DisplayOptions {
+            show_metadata: false,
+            show_usage: false,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        CompletionResponse::streaming_display_with(stream(vec![chunk(0, "Hi", None)]), &mut out, &options)
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Hi\n");
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_redraws_line() {
+        let items = || {
+            vec![
+                chunk(0, "First line\nPartial", None),
+                Err(ProviderError::ServerError("connection reset".to_string()).into()),
+                chunk(0, " never shown", None),
+            ]
+        };
+
+        let mut out = Vec::new();
+        let options = DisplayOptions {
+            color: true,
+            ..Default::default()
+        };
+        let err = CompletionResponse::streaming_display_with(stream(items()), &mut out, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SimpleAgentsError::Provider(ProviderError::ServerError(_))));
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("First line\nPartial\r\x1b[2K\x1b[2mPartial\x1b[0m\x1b[31m[error: "));
+        assert!(out.contains("connection reset"));
+        assert!(!out.contains("never shown"));
+
+        let mut out = Vec::new();
+        CompletionResponse::streaming_display(stream(items()), &mut out)
+            .await
+            .unwrap_err();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("First line\nPartial\n[error: "));
+        assert!(!out.contains('\x1b'));
+    }
+}
@@ -0,0 +1,88 @@
+//! Embedding provider trait.
+//!
+//! Provides an abstract interface for turning text into embedding vectors,
+//! used by semantic caching and similarity search.
+
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Trait for embedding providers.
+///
+/// # Example Implementation
+///
+/// ```ignore
+/// use simple_agents_types::embedding::EmbeddingProvider;
+/// use simple_agents_types::error::Result;
+/// use async_trait::async_trait;
+///
+/// struct LengthEmbedder;
+///
+/// #[async_trait]
+/// impl EmbeddingProvider for LengthEmbedder {
+///     async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+///         Ok(vec![input.len() as f32, 1.0])
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embed a single input text.
+    async fn embed(&self, input: &str) -> Result<Vec<f32>>;
+
+    /// Get the embedding provider name.
+    ///
+    /// Used for logging and debugging.
+    fn name(&self) -> &str {
+        "embedding"
+    }
+}
+
+/// Cosine similarity between two vectors (-1.0 to 1.0).
+///
+/// Returns `0.0` when the vectors differ in length or either is all zeros.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::embedding::cosine_similarity;
+///
+/// assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+/// assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+/// ```
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-6);
+        assert!((cosine_similarity(&[1.0, 0.0], &[-1.0, 0.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(cosine_similarity(&[], &[]), 0.0);
+    }
+
+    // Test that EmbeddingProvider trait is object-safe
+    #[test]
+    fn test_embedding_provider_object_safety() {
+        fn _assert_object_safe(_: &dyn EmbeddingProvider) {}
+    }
+}
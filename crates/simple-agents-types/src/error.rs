@@ -2,6 +2,9 @@
 //!
 //! Comprehensive error hierarchy for all failure modes.
 
+use crate::attempt::AttemptHistory;
+use crate::config::Feature;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use thiserror::Error;
 
@@ -39,48 +42,319 @@ pub enum SimpleAgentsError {
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    /// Error annotated with the request it occurred in
+    ///
+    /// Provider errors with a known provider print as
+    /// `openai 429 rate_limit_exceeded (retry after 12s) [model=gpt-4, attempt=1]`.
+    #[error("{}", display_with_context(.0))]
+    WithContext(Box<ErrorContext<SimpleAgentsError>>),
+}
+
+/// Display a context-wrapped error, leading with the provider name for
+/// provider errors.
+fn display_with_context(ctx: &ErrorContext<SimpleAgentsError>) -> String {
+    match (&ctx.provider, ctx.error.root()) {
+        (Some(provider), SimpleAgentsError::Provider(error)) => {
+            format!("{} {}{}", provider, error, ctx.fields_suffix(false))
+        }
+        _ => ctx.to_string(),
+    }
+}
+
+/// Format a duration compactly: `12s`, `1.5s`, `250ms`.
+fn format_duration(duration: &Duration) -> String {
+    if duration.subsec_nanos() == 0 {
+        format!("{}s", duration.as_secs())
+    } else if duration.as_secs() == 0 {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+fn retry_after_suffix(retry_after: &Option<Duration>) -> String {
+    retry_after
+        .as_ref()
+        .map(|d| format!(" (retry after {})", format_duration(d)))
+        .unwrap_or_default()
+}
+
+impl SimpleAgentsError {
+    /// Attach request context to this error.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::error::{ErrorContext, ProviderError, SimpleAgentsError};
+    ///
+    /// let err = SimpleAgentsError::Provider(ProviderError::InvalidApiKey)
+    ///     .with_context(ErrorContext::new().provider("openai").model("gpt-4"));
+    /// assert_eq!(err.model.as_deref(), Some("gpt-4"));
+    /// assert!(err.to_string().contains("provider=openai"));
+    /// ```
+    pub fn with_context(self, ctx: ErrorContext<()>) -> ErrorContext<SimpleAgentsError> {
+        ErrorContext {
+            error: self,
+            request_id: ctx.request_id,
+            model: ctx.model,
+            provider: ctx.provider,
+            attempt: ctx.attempt,
+            total_delay: ctx.total_delay,
+            attempts: ctx.attempts,
+        }
+    }
+
+    /// The context attached to this error, if any.
+    pub fn context(&self) -> Option<&ErrorContext<SimpleAgentsError>> {
+        match self {
+            Self::WithContext(ctx) => Some(ctx),
+            _ => None,
+        }
+    }
+
+    /// The underlying error with any context layers removed.
+    ///
+    /// Match on this rather than on the error itself when checking for a
+    /// specific variant, since errors returned by [`crate::provider::Provider::complete`]
+    /// carry context.
+    pub fn root(&self) -> &SimpleAgentsError {
+        match self {
+            Self::WithContext(ctx) => ctx.error.root(),
+            other => other,
+        }
+    }
+
+    /// Stable code for this error, looking through any added context.
+    ///
+    /// See [`ErrorCode`].
+    pub fn code(&self) -> ErrorCode {
+        match self.root() {
+            Self::Provider(error) => error.code(),
+            Self::Healing(_) => ErrorCode::InvalidResponse,
+            Self::Network(_) => ErrorCode::Network,
+            Self::Config(_) => ErrorCode::Configuration,
+            Self::Validation(ValidationError::PromptTooLong { .. }) => ErrorCode::ContextTooLong,
+            Self::Validation(_) => ErrorCode::InvalidRequest,
+            Self::Cache(_) => ErrorCode::Cache,
+            Self::Routing(_) => ErrorCode::RoutingFailed,
+            Self::Serialization(_) => ErrorCode::Serialization,
+            // root() never returns a context layer
+            Self::WithContext(ctx) => ctx.error.code(),
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later; see
+    /// [`ErrorCode::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.code().is_retryable()
+    }
+
+    /// Whether the request itself has to change; see
+    /// [`ErrorCode::is_user_error`].
+    pub fn is_user_error(&self) -> bool {
+        self.code().is_user_error()
+    }
+
+    /// Owned version of [`SimpleAgentsError::root`].
+    pub fn into_root(self) -> SimpleAgentsError {
+        match self {
+            Self::WithContext(ctx) => ctx.error.into_root(),
+            other => other,
+        }
+    }
+}
+
+/// An error together with the request it occurred in.
+///
+/// `ErrorContext<()>` serves as a template: build it with the setters, then
+/// attach it with [`SimpleAgentsError::with_context`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorContext<E> {
+    /// The underlying error
+    pub error: E,
+    /// Request identifier, when known
+    pub request_id: Option<String>,
+    /// Model the request targeted
+    pub model: Option<String>,
+    /// Provider that handled the request
+    pub provider: Option<String>,
+    /// Attempt number (1 for the first attempt, 0 if unknown)
+    pub attempt: u32,
+    /// Time spent waiting between attempts before the error was returned
+    pub total_delay: Duration,
+    /// Every attempt made, when the wrapper that returned the error
+    /// records them (see [`crate::attempt`])
+    pub attempts: AttemptHistory,
+}
+
+impl ErrorContext<()> {
+    /// Create an empty context template.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<E> ErrorContext<E> {
+    /// Set the request identifier.
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Set the model.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the provider name.
+    pub fn provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    /// Set the attempt number.
+    pub fn attempt(mut self, attempt: u32) -> Self {
+        self.attempt = attempt;
+        self
+    }
+
+    /// Set the time spent waiting between attempts.
+    pub fn total_delay(mut self, total_delay: Duration) -> Self {
+        self.total_delay = total_delay;
+        self
+    }
+
+    /// Set the attempt history.
+    pub fn attempts(mut self, attempts: AttemptHistory) -> Self {
+        self.attempts = attempts;
+        self
+    }
+}
+
+impl<E: std::error::Error + 'static> ErrorContext<E> {
+    /// Render the error, its context, and every underlying cause.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::error::{ErrorContext, ProviderError, SimpleAgentsError};
+    ///
+    /// let err = SimpleAgentsError::Provider(ProviderError::InvalidApiKey)
+    ///     .with_context(ErrorContext::new().provider("openai"));
+    /// assert!(err.display_chain().starts_with("Provider error: 401 invalid_api_key"));
+    /// ```
+    pub fn display_chain(&self) -> String {
+        let mut chain = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            chain.push_str("\n  caused by: ");
+            chain.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        chain
+    }
+}
+
+impl<E> ErrorContext<E> {
+    /// ` [provider=.., model=.., request_id=.., attempt=N, delay=..]`, or ""
+    /// if empty.
+    fn fields_suffix(&self, include_provider: bool) -> String {
+        let provider = if include_provider { &self.provider } else { &None };
+        let fields = [
+            ("provider", provider),
+            ("model", &self.model),
+            ("request_id", &self.request_id),
+        ];
+        let mut parts: Vec<String> = fields
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| format!("{}={}", name, v)))
+            .collect();
+        if self.attempt > 0 {
+            parts.push(format!("attempt={}", self.attempt));
+        }
+        if !self.total_delay.is_zero() {
+            parts.push(format!("delay={}", format_duration(&self.total_delay)));
+        }
+
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", parts.join(", "))
+        }
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for ErrorContext<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.error, self.fields_suffix(true))
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for ErrorContext<E> {
+    // The wrapped error's message is already part of this error's Display,
+    // so the chain continues with its source rather than the error itself.
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
 }
 
 /// Result type alias using SimpleAgentsError.
 pub type Result<T> = std::result::Result<T, SimpleAgentsError>;
 
 /// Provider-specific errors.
+///
+/// Display is compact and log-friendly: the HTTP status (when implied by
+/// the variant), then [`ProviderError::code`], then details, e.g.
+/// `429 rate_limit_exceeded (retry after 12s)`.
 #[derive(Error, Debug, Clone)]
 pub enum ProviderError {
     /// Rate limit exceeded
-    #[error("Rate limit exceeded (retry after {retry_after:?})")]
+    #[error("429 rate_limit_exceeded{}", retry_after_suffix(.retry_after))]
     RateLimit {
         /// Optional duration to wait before retrying
         retry_after: Option<Duration>,
     },
 
     /// Invalid API key
-    #[error("Invalid API key")]
+    #[error("401 invalid_api_key")]
     InvalidApiKey,
 
     /// Model not found
-    #[error("Model not found: {0}")]
+    #[error("404 model_not_found: {0}")]
     ModelNotFound(String),
 
     /// Request timeout
-    #[error("Timeout after {0:?}")]
+    #[error("timeout after {}", format_duration(.0))]
     Timeout(Duration),
 
     /// Server error (5xx)
-    #[error("Server error: {0}")]
+    #[error("server_error: {0}")]
     ServerError(String),
 
     /// Bad request (4xx)
-    #[error("Bad request: {0}")]
+    #[error("400 bad_request: {0}")]
     BadRequest(String),
 
-    /// Unsupported feature
-    #[error("Unsupported feature: {0}")]
-    UnsupportedFeature(String),
+    /// Request or uploaded file exceeds the provider's size limit
+    #[error("413 payload_too_large: {0}")]
+    PayloadTooLarge(String),
+
+    /// The provider does not support a feature the request needs
+    #[error("unsupported: provider '{provider}' does not support {feature}")]
+    Unsupported {
+        /// Provider name
+        provider: String,
+        /// The missing feature
+        feature: Feature,
+    },
 
     /// Invalid response format
-    #[error("Invalid response format: {0}")]
+    #[error("invalid_response: {0}")]
     InvalidResponse(String),
+
+    /// The model declined to answer; holds its refusal message
+    #[error("refusal: {0}")]
+    Refusal(String),
 }
 
 impl ProviderError {
@@ -103,6 +377,194 @@ impl ProviderError {
             Self::RateLimit { .. } | Self::Timeout(_) | Self::ServerError(_)
         )
     }
+
+    /// Snake_case name of the variant, as shown in [`Display`](std::fmt::Display)
+    /// output and telemetry.
+    ///
+    /// Use [`ProviderError::code`] to match on errors.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::RateLimit { .. } => "rate_limit_exceeded",
+            Self::InvalidApiKey => "invalid_api_key",
+            Self::ModelNotFound(_) => "model_not_found",
+            Self::Timeout(_) => "timeout",
+            Self::ServerError(_) => "server_error",
+            Self::BadRequest(_) => "bad_request",
+            Self::PayloadTooLarge(_) => "payload_too_large",
+            Self::Unsupported { .. } => "unsupported",
+            Self::InvalidResponse(_) => "invalid_response",
+            Self::Refusal(_) => "refusal",
+        }
+    }
+
+    /// Stable code for this error; see [`ErrorCode`].
+    ///
+    /// Providers report context-length, quota and content-policy
+    /// rejections as ordinary bad requests, so those codes are derived
+    /// from the provider's message.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::error::{ErrorCode, ProviderError};
+    ///
+    /// let err = ProviderError::BadRequest("This model's maximum context length is 8192 tokens".into());
+    /// assert_eq!(err.code(), ErrorCode::ContextTooLong);
+    /// assert!(err.code().is_user_error());
+    /// ```
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::RateLimit { .. } => ErrorCode::RateLimited,
+            Self::InvalidApiKey => ErrorCode::AuthFailed,
+            Self::ModelNotFound(_) => ErrorCode::ModelNotFound,
+            Self::Timeout(_) => ErrorCode::Timeout,
+            Self::ServerError(_) => ErrorCode::ServerError,
+            Self::BadRequest(message) => bad_request_code(message),
+            Self::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            Self::Unsupported { .. } => ErrorCode::Unsupported,
+            Self::InvalidResponse(_) => ErrorCode::InvalidResponse,
+            Self::Refusal(_) => ErrorCode::Refused,
+        }
+    }
+}
+
+/// Wording providers use for bad requests that have a more specific code
+const BAD_REQUEST_CODES: [(ErrorCode, &[&str]); 3] = [
+    (
+        ErrorCode::ContextTooLong,
+        &["context length", "context_length", "context window", "prompt is too long"],
+    ),
+    (ErrorCode::BudgetExceeded, &["quota", "credit balance", "billing"]),
+    (
+        ErrorCode::ContentBlocked,
+        &["content_policy", "content policy", "content_filter", "content filter", "safety system"],
+    ),
+];
+
+/// Narrow a bad request down by the provider's message
+fn bad_request_code(message: &str) -> ErrorCode {
+    let message = message.to_lowercase();
+    BAD_REQUEST_CODES
+        .iter()
+        .find(|(_, needles)| needles.iter().any(|needle| message.contains(needle)))
+        .map_or(ErrorCode::InvalidRequest, |(code, _)| *code)
+}
+
+/// Stable code identifying what went wrong, for matching in code.
+///
+/// The codes and their string forms are a compatibility surface: a code
+/// is never renamed or given a different meaning, and an error keeps its
+/// code across releases. New codes may be added, so matches need a
+/// wildcard arm. Match on codes rather than on error messages, which are
+/// written for people and change freely.
+///
+/// Serializes as its [`as_str`](ErrorCode::as_str) form.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::error::{ErrorCode, ProviderError, SimpleAgentsError};
+///
+/// let err = SimpleAgentsError::Provider(ProviderError::RateLimit { retry_after: None });
+/// match err.code() {
+///     ErrorCode::RateLimited => assert!(err.is_retryable()),
+///     other => panic!("unexpected {}", other),
+/// }
+/// assert_eq!(err.code().as_str(), "RATE_LIMITED");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// Credentials were missing or rejected
+    AuthFailed,
+    /// Too many requests; retry after a delay
+    RateLimited,
+    /// The account is out of quota or credit
+    BudgetExceeded,
+    /// The model does not exist or is not available to this account
+    ModelNotFound,
+    /// The prompt does not fit the model's context window
+    ContextTooLong,
+    /// The request or an attachment was too large
+    PayloadTooLarge,
+    /// The provider's content policy rejected the request
+    ContentBlocked,
+    /// The model declined to answer
+    Refused,
+    /// The request was rejected as invalid
+    InvalidRequest,
+    /// The provider or model does not support something the request needs
+    Unsupported,
+    /// The request timed out
+    Timeout,
+    /// The provider could not be reached
+    Network,
+    /// The provider failed
+    ServerError,
+    /// The provider's response could not be used
+    InvalidResponse,
+    /// No provider could take the request
+    RoutingFailed,
+    /// The application is misconfigured
+    Configuration,
+    /// A cache backend failed
+    Cache,
+    /// A value could not be serialized or deserialized
+    Serialization,
+}
+
+impl ErrorCode {
+    /// The code as a SCREAMING_SNAKE_CASE string, e.g. `"RATE_LIMITED"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AuthFailed => "AUTH_FAILED",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::BudgetExceeded => "BUDGET_EXCEEDED",
+            Self::ModelNotFound => "MODEL_NOT_FOUND",
+            Self::ContextTooLong => "CONTEXT_TOO_LONG",
+            Self::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            Self::ContentBlocked => "CONTENT_BLOCKED",
+            Self::Refused => "REFUSED",
+            Self::InvalidRequest => "INVALID_REQUEST",
+            Self::Unsupported => "UNSUPPORTED",
+            Self::Timeout => "TIMEOUT",
+            Self::Network => "NETWORK",
+            Self::ServerError => "SERVER_ERROR",
+            Self::InvalidResponse => "INVALID_RESPONSE",
+            Self::RoutingFailed => "ROUTING_FAILED",
+            Self::Configuration => "CONFIGURATION",
+            Self::Cache => "CACHE",
+            Self::Serialization => "SERIALIZATION",
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later.
+    ///
+    /// Matches the errors the retry logic in `simple-agents-providers`
+    /// retries on the same provider.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited | Self::Timeout | Self::Network | Self::ServerError)
+    }
+
+    /// Whether the request itself is at fault and has to change before it
+    /// can succeed, as opposed to the provider, the network or the
+    /// application's configuration.
+    pub fn is_user_error(&self) -> bool {
+        matches!(
+            self,
+            Self::ContextTooLong
+                | Self::PayloadTooLarge
+                | Self::ContentBlocked
+                | Self::Refused
+                | Self::InvalidRequest
+                | Self::Unsupported
+        )
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Healing and coercion errors.
@@ -199,6 +661,27 @@ pub enum ValidationError {
         reason: String,
     },
 
+    /// Two fields that cannot be used together as set
+    #[error("Invalid combination: {field} with {related} ({reason})")]
+    Conflict {
+        /// Field name
+        field: String,
+        /// Field it conflicts with or depends on
+        related: String,
+        /// Reason for rejection
+        reason: String,
+    },
+
+    /// Prompt estimated to exceed the model's context window, caught
+    /// before sending
+    #[error("Prompt too long: about {estimated_tokens} tokens, over the {limit}-token context limit")]
+    PromptTooLong {
+        /// Estimated (or counted) prompt tokens
+        estimated_tokens: u32,
+        /// Context limit that applied
+        limit: u32,
+    },
+
     /// Generic validation error
     #[error("{0}")]
     Custom(String),
@@ -211,6 +694,81 @@ impl ValidationError {
     }
 }
 
+/// Outcome of a batch in which some items may fail while others succeed.
+///
+/// Items are identified by caller-chosen IDs and keep their input order
+/// within `successes` and `failures`. Not to be confused with
+/// [`crate::batch::BatchResult`], one line of an OpenAI Batch API output
+/// file.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::error::{BatchResult, ProviderError};
+///
+/// let batch = BatchResult::from_provider_results(vec![
+///     ("a".to_string(), Ok(1)),
+///     ("b".to_string(), Err(ProviderError::InvalidApiKey.into())),
+///     ("c".to_string(), Ok(3)),
+/// ]);
+///
+/// assert!(!batch.is_complete_success());
+/// assert_eq!(batch.failures[0].0, "b");
+/// assert_eq!(batch.into_successes(), vec![1, 3]);
+/// ```
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    /// Successful items, as `(id, value)`
+    pub successes: Vec<(String, T)>,
+    /// Failed items, as `(id, error)`
+    pub failures: Vec<(String, SimpleAgentsError)>,
+}
+
+impl<T> BatchResult<T> {
+    /// Split per-item results into successes and failures.
+    pub fn from_provider_results(results: Vec<(String, Result<T>)>) -> Self {
+        let mut batch = Self {
+            successes: Vec::new(),
+            failures: Vec::new(),
+        };
+        for (id, result) in results {
+            match result {
+                Ok(value) => batch.successes.push((id, value)),
+                Err(error) => batch.failures.push((id, error)),
+            }
+        }
+        batch
+    }
+
+    /// Whether no item failed (true for an empty batch).
+    pub fn is_complete_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Number of items in the batch.
+    pub fn len(&self) -> usize {
+        self.successes.len() + self.failures.len()
+    }
+
+    /// Whether the batch has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fraction of items that succeeded, from 0.0 to 1.0 (1.0 for an empty
+    /// batch).
+    pub fn success_rate(&self) -> f64 {
+        if self.is_empty() {
+            return 1.0;
+        }
+        self.successes.len() as f64 / self.len() as f64
+    }
+
+    /// Successful values in input order, discarding IDs and failures.
+    pub fn into_successes(self) -> Vec<T> {
+        self.successes.into_iter().map(|(_, value)| value).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +782,20 @@ mod tests {
         assert!(!ProviderError::InvalidApiKey.is_retryable());
         assert!(!ProviderError::ModelNotFound("gpt-5".to_string()).is_retryable());
         assert!(!ProviderError::BadRequest("invalid".to_string()).is_retryable());
+        assert!(!ProviderError::Unsupported {
+            provider: "mock".to_string(),
+            feature: Feature::Vision,
+        }
+        .is_retryable());
+    }
+
+    #[test]
+    fn test_unsupported_display() {
+        let err = ProviderError::Unsupported {
+            provider: "anthropic".to_string(),
+            feature: Feature::Embeddings,
+        };
+        assert_eq!(err.to_string(), "unsupported: provider 'anthropic' does not support embeddings");
     }
 
     #[test]
@@ -242,9 +814,7 @@ mod tests {
         let err = ProviderError::RateLimit {
             retry_after: Some(Duration::from_secs(60)),
         };
-        let display = format!("{}", err);
-        assert!(display.contains("Rate limit"));
-        assert!(display.contains("60s"));
+        assert_eq!(err.to_string(), "429 rate_limit_exceeded (retry after 60s)");
 
         let err = ValidationError::Empty {
             field: "model".to_string(),
@@ -254,6 +824,139 @@ mod tests {
         assert!(display.contains("empty"));
     }
 
+    #[test]
+    fn test_with_context() {
+        let err = SimpleAgentsError::Provider(ProviderError::ServerError("boom".to_string()))
+            .with_context(
+                ErrorContext::new()
+                    .request_id("req_1")
+                    .model("gpt-4")
+                    .provider("openai")
+                    .attempt(2),
+            );
+
+        assert_eq!(err.request_id.as_deref(), Some("req_1"));
+        assert_eq!(err.attempt, 2);
+        assert_eq!(
+            err.to_string(),
+            "Provider error: server_error: boom [provider=openai, model=gpt-4, request_id=req_1, attempt=2]"
+        );
+    }
+
+    #[test]
+    fn test_context_root() {
+        let err = SimpleAgentsError::WithContext(Box::new(
+            SimpleAgentsError::Provider(ProviderError::InvalidApiKey)
+                .with_context(ErrorContext::new().provider("anthropic")),
+        ));
+
+        assert_eq!(err.context().unwrap().provider.as_deref(), Some("anthropic"));
+        assert!(matches!(
+            err.root(),
+            SimpleAgentsError::Provider(ProviderError::InvalidApiKey)
+        ));
+        assert_eq!(err.to_string(), "anthropic 401 invalid_api_key");
+        assert!(matches!(
+            err.into_root(),
+            SimpleAgentsError::Provider(ProviderError::InvalidApiKey)
+        ));
+    }
+
+    #[test]
+    fn test_provider_error_display_snapshots() {
+        let retry = |d| ProviderError::RateLimit { retry_after: Some(d) };
+        let cases = [
+            (retry(Duration::from_secs(12)), "429 rate_limit_exceeded (retry after 12s)"),
+            (retry(Duration::from_millis(1500)), "429 rate_limit_exceeded (retry after 1.5s)"),
+            (ProviderError::RateLimit { retry_after: None }, "429 rate_limit_exceeded"),
+            (ProviderError::InvalidApiKey, "401 invalid_api_key"),
+            (ProviderError::ModelNotFound("gpt-5".into()), "404 model_not_found: gpt-5"),
+            (ProviderError::Timeout(Duration::from_millis(250)), "timeout after 250ms"),
+            (ProviderError::ServerError("overloaded".into()), "server_error: overloaded"),
+            (ProviderError::BadRequest("bad".into()), "400 bad_request: bad"),
+            (ProviderError::PayloadTooLarge("40 MB".into()), "413 payload_too_large: 40 MB"),
+            (ProviderError::InvalidResponse("not json".into()), "invalid_response: not json"),
+            (ProviderError::Refusal("I can't help".into()), "refusal: I can't help"),
+        ];
+
+        for (err, expected) in cases {
+            assert_eq!(err.to_string(), expected);
+            assert!(expected.contains(err.kind()));
+        }
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let bad_request = |message: &str| ProviderError::BadRequest(message.to_string()).code();
+        assert_eq!(
+            bad_request("This model's maximum context length is 8192 tokens"),
+            ErrorCode::ContextTooLong
+        );
+        assert_eq!(bad_request("prompt is too long: 201234 tokens > 200000 maximum"), ErrorCode::ContextTooLong);
+        assert_eq!(bad_request("You exceeded your current quota"), ErrorCode::BudgetExceeded);
+        assert_eq!(bad_request("Your credit balance is too low"), ErrorCode::BudgetExceeded);
+        assert_eq!(bad_request("Rejected by our safety system"), ErrorCode::ContentBlocked);
+        assert_eq!(bad_request("temperature must be at most 2"), ErrorCode::InvalidRequest);
+
+        let err = SimpleAgentsError::Provider(ProviderError::Timeout(Duration::from_secs(1)))
+            .with_context(ErrorContext::new().provider("openai"));
+        let err = SimpleAgentsError::WithContext(Box::new(err));
+        assert_eq!(err.code(), ErrorCode::Timeout);
+        assert!(err.is_retryable());
+        assert!(!err.is_user_error());
+
+        let err = SimpleAgentsError::Validation(ValidationError::new("messages cannot be empty"));
+        assert_eq!(err.code(), ErrorCode::InvalidRequest);
+        assert!(err.is_user_error());
+        assert!(!err.is_retryable());
+        let err = SimpleAgentsError::Validation(ValidationError::PromptTooLong {
+            estimated_tokens: 250_000,
+            limit: 200_000,
+        });
+        assert_eq!(err.code(), ErrorCode::ContextTooLong);
+        assert!(err.is_user_error());
+        assert_eq!(SimpleAgentsError::Routing("no provider".into()).code(), ErrorCode::RoutingFailed);
+
+        assert_eq!(ErrorCode::ContextTooLong.to_string(), "CONTEXT_TOO_LONG");
+        assert_eq!(serde_json::to_string(&ErrorCode::AuthFailed).unwrap(), "\"AUTH_FAILED\"");
+        let parsed: ErrorCode = serde_json::from_str("\"BUDGET_EXCEEDED\"").unwrap();
+        assert_eq!(parsed, ErrorCode::BudgetExceeded);
+    }
+
+    #[test]
+    fn test_nested_provider_error_display() {
+        let err = SimpleAgentsError::WithContext(Box::new(
+            SimpleAgentsError::Provider(ProviderError::RateLimit {
+                retry_after: Some(Duration::from_secs(12)),
+            })
+            .with_context(ErrorContext::new().provider("openai").model("gpt-4").attempt(3)),
+        ));
+        assert_eq!(
+            err.to_string(),
+            "openai 429 rate_limit_exceeded (retry after 12s) [model=gpt-4, attempt=3]"
+        );
+
+        // Non-provider errors keep the full context suffix
+        let err = SimpleAgentsError::WithContext(Box::new(
+            SimpleAgentsError::Config("bad".to_string())
+                .with_context(ErrorContext::new().provider("openai")),
+        ));
+        assert_eq!(err.to_string(), "Configuration error: bad [provider=openai]");
+    }
+
+    #[test]
+    fn test_display_chain() {
+        let inner = SimpleAgentsError::Serialization(serde_json::from_str::<u32>("x").unwrap_err());
+        let err = inner.with_context(ErrorContext::new().model("gpt-4"));
+
+        let chain = err.display_chain();
+        assert!(chain.starts_with("Serialization error:"));
+        assert!(chain.contains("[model=gpt-4]"));
+
+        let bare = SimpleAgentsError::Config("bad".to_string()).with_context(ErrorContext::new());
+        assert_eq!(bare.display_chain(), "Configuration error: bad");
+    }
+
     #[test]
     fn test_healing_error_types() {
         let err = HealingError::ParseFailed {
@@ -268,4 +971,48 @@ mod tests {
         };
         assert!(format!("{}", err).contains("coercion"));
     }
+
+    #[test]
+    fn test_batch_result_mixed() {
+        let batch = BatchResult::from_provider_results(vec![
+            ("a".to_string(), Ok("one")),
+            (
+                "b".to_string(),
+                Err(ProviderError::Timeout(Duration::from_secs(30)).into()),
+            ),
+            ("c".to_string(), Ok("three")),
+            ("d".to_string(), Err(SimpleAgentsError::Network("reset".to_string()))),
+        ]);
+
+        assert_eq!(batch.len(), 4);
+        assert!(!batch.is_complete_success());
+        assert_eq!(batch.success_rate(), 0.5);
+        let failed: Vec<&str> = batch.failures.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(failed, vec!["b", "d"]);
+        assert!(matches!(batch.failures[1].1, SimpleAgentsError::Network(_)));
+        assert_eq!(batch.successes[1], ("c".to_string(), "three"));
+        assert_eq!(batch.into_successes(), vec!["one", "three"]);
+    }
+
+    #[test]
+    fn test_batch_result_all_or_nothing() {
+        let ok = BatchResult::from_provider_results(vec![
+            ("a".to_string(), Ok(1)),
+            ("b".to_string(), Ok(2)),
+        ]);
+        assert!(ok.is_complete_success());
+        assert_eq!(ok.success_rate(), 1.0);
+
+        let failed = BatchResult::<u32>::from_provider_results(vec![(
+            "a".to_string(),
+            Err(ProviderError::InvalidApiKey.into()),
+        )]);
+        assert_eq!(failed.success_rate(), 0.0);
+        assert!(failed.into_successes().is_empty());
+
+        let empty = BatchResult::<u32>::from_provider_results(Vec::new());
+        assert!(empty.is_empty());
+        assert!(empty.is_complete_success());
+        assert_eq!(empty.success_rate(), 1.0);
+    }
 }
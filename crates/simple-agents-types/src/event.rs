@@ -0,0 +1,168 @@
+//! Notifications for long-running and stateful operations.
+//!
+//! Wrappers that track state over time (batch jobs, budgets, circuit
+//! breakers, health checks) report transitions through an
+//! [`EventNotifier`] instead of making every replica poll for them.
+
+use crate::router::ProviderHealth;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Lifecycle state of a batch job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchState {
+    /// Input is being validated
+    Validating,
+    /// Requests are being processed
+    InProgress,
+    /// Results are being collected
+    Finalizing,
+    /// Every request has a result (which may be an error)
+    Completed,
+    /// The job could not run
+    Failed,
+    /// The job did not finish within its window
+    Expired,
+    /// The job was cancelled
+    Cancelled,
+}
+
+impl BatchState {
+    /// Whether the job has stopped changing state.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Expired | Self::Cancelled)
+    }
+}
+
+/// Something a long-running operation wants to report.
+///
+/// Serializes with a `type` tag, e.g.
+/// `{"type": "circuit_opened", "provider": "openai", ...}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// A batch job moved to a new state
+    BatchStateChanged {
+        /// Batch identifier
+        batch_id: String,
+        /// New state
+        state: BatchState,
+        /// Requests in the batch
+        total: usize,
+        /// Requests that succeeded so far
+        completed: usize,
+        /// Requests that failed so far
+        failed: usize,
+    },
+    /// Spending crossed a fraction of a budget
+    BudgetThresholdCrossed {
+        /// Budget name (e.g. a tenant or project)
+        budget: String,
+        /// Fraction of the limit that was crossed (e.g. 0.8)
+        threshold: f64,
+        /// Amount spent, in the budget's unit
+        spent: f64,
+        /// Budget limit, in the same unit
+        limit: f64,
+    },
+    /// A circuit breaker stopped sending requests to a provider
+    CircuitOpened {
+        /// Provider name
+        provider: String,
+        /// Consecutive failures that opened the circuit
+        failures: u32,
+        /// How long requests are refused before a trial request
+        #[serde(with = "duration_ms")]
+        cooldown: Duration,
+    },
+    /// A provider's health status changed
+    ProviderHealthChanged {
+        /// Provider name
+        provider: String,
+        /// Previous status
+        from: ProviderHealth,
+        /// New status
+        to: ProviderHealth,
+    },
+}
+
+impl Event {
+    /// Snake-case event type, as in the serialized `type` tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::BatchStateChanged { .. } => "batch_state_changed",
+            Self::BudgetThresholdCrossed { .. } => "budget_threshold_crossed",
+            Self::CircuitOpened { .. } => "circuit_opened",
+            Self::ProviderHealthChanged { .. } => "provider_health_changed",
+        }
+    }
+}
+
+/// Receiver of [`Event`]s.
+///
+/// Notification is fire-and-forget: implementations handle their own
+/// failures (typically by retrying, then logging), so a broken notifier
+/// never fails the operation that raised the event.
+#[async_trait]
+pub trait EventNotifier: Send + Sync {
+    /// Deliver one event.
+    async fn notify(&self, event: Event);
+}
+
+/// Serialize a `Duration` as whole milliseconds.
+pub(crate) mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = Event::CircuitOpened {
+            provider: "openai".to_string(),
+            failures: 5,
+            cooldown: Duration::from_secs(30),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "circuit_opened",
+                "provider": "openai",
+                "failures": 5,
+                "cooldown": 30000
+            })
+        );
+        assert_eq!(json["type"], event.kind());
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+
+        let health = Event::ProviderHealthChanged {
+            provider: "groq".to_string(),
+            from: ProviderHealth::Healthy,
+            to: ProviderHealth::Degraded,
+        };
+        let json = serde_json::to_value(&health).unwrap();
+        assert_eq!(json["from"], "healthy");
+        assert_eq!(json["type"], health.kind());
+    }
+
+    #[test]
+    fn test_batch_state_is_terminal() {
+        assert!(!BatchState::InProgress.is_terminal());
+        assert!(BatchState::Completed.is_terminal());
+        assert!(BatchState::Cancelled.is_terminal());
+    }
+}
@@ -0,0 +1,676 @@
+//! Conversation datasets for fine-tuning.
+//!
+//! Converts [`Conversation`]s to and from OpenAI's chat fine-tuning JSONL
+//! ([`to_openai_finetune_jsonl`], [`from_openai_finetune_jsonl`]) and the
+//! ShareGPT JSON format used by most open-source trainers
+//! ([`to_sharegpt_json`], [`from_sharegpt_json`]). Both directions check
+//! the fine-tuning constraints described on [`Conversation::validate`].
+
+use crate::error::{Result, ValidationError};
+use crate::message::{ImageUrl, Message, Role};
+use crate::request::CompletionRequest;
+use crate::tool::{ToolCall, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A recorded exchange: the messages and the tools that were offered.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Conversation {
+    /// Messages in order
+    pub messages: Vec<Message>,
+    /// Tools available to the assistant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+}
+
+impl Conversation {
+    /// Create a conversation without tools.
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self {
+            messages,
+            tools: Vec::new(),
+        }
+    }
+
+    /// Set the tools offered to the assistant (builder pattern).
+    pub fn with_tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Check the constraints fine-tuning datasets impose.
+    ///
+    /// A conversation must be non-empty and end with an assistant message.
+    /// Every message needs content, except assistant messages that make
+    /// tool calls or messages with images. Only assistant messages may make
+    /// tool calls, and every tool result must answer an earlier call.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::export::Conversation;
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let conversation = Conversation::new(vec![Message::user("Hi"), Message::assistant("Hello!")]);
+    /// assert!(conversation.validate().is_ok());
+    ///
+    /// let unanswered = Conversation::new(vec![Message::user("Hi")]);
+    /// assert!(unanswered.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        self.validate_at("messages")
+    }
+
+    /// A request for the prompt of this conversation.
+    ///
+    /// Drops the trailing assistant messages so the conversation can be
+    /// replayed through a provider and the new answer compared with the
+    /// recorded one. Tools are carried over.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::Empty`] if no messages precede the final
+    /// assistant turn, or any error from
+    /// [`CompletionRequestBuilder::build`](crate::request::CompletionRequestBuilder::build).
+    pub fn replay_request(&self, model: &str) -> Result<CompletionRequest> {
+        let prompt_len = self
+            .messages
+            .iter()
+            .rposition(|m| m.role != Role::Assistant)
+            .map_or(0, |last| last + 1);
+        let mut builder = CompletionRequest::builder()
+            .model(model)
+            .messages(self.messages[..prompt_len].to_vec());
+        if !self.tools.is_empty() {
+            builder = builder.tools(self.tools.clone());
+        }
+        builder.build()
+    }
+
+    fn validate_at(&self, field: &str) -> Result<()> {
+        let invalid = |index: usize, part: &str, reason: &str| ValidationError::InvalidFormat {
+            field: format!("{}[{}]{}", field, index, part),
+            reason: reason.to_string(),
+        };
+
+        let last = match self.messages.last() {
+            Some(last) => last,
+            None => {
+                return Err(ValidationError::Empty {
+                    field: field.to_string(),
+                }
+                .into())
+            }
+        };
+        if last.role != Role::Assistant {
+            let reason = "conversation must end with an assistant message";
+            return Err(invalid(self.messages.len() - 1, ".role", reason).into());
+        }
+
+        let mut call_ids: Vec<&str> = Vec::new();
+        for (i, message) in self.messages.iter().enumerate() {
+            let has_content = !message.content.trim().is_empty()
+                || !message.tool_calls.is_empty()
+                || !message.images.is_empty();
+            if !has_content {
+                return Err(ValidationError::Empty {
+                    field: format!("{}[{}].content", field, i),
+                }
+                .into());
+            }
+            if !message.tool_calls.is_empty() && message.role != Role::Assistant {
+                return Err(invalid(i, ".tool_calls", "only assistant messages can make tool calls").into());
+            }
+            call_ids.extend(message.tool_calls.iter().map(|call| call.id.as_str()));
+
+            if message.role == Role::Tool {
+                match message.tool_call_id.as_deref() {
+                    Some(id) if call_ids.contains(&id) => {}
+                    Some(_) => {
+                        let reason = "tool result does not answer an earlier tool call";
+                        return Err(invalid(i, ".tool_call_id", reason).into());
+                    }
+                    None => return Err(invalid(i, ".tool_call_id", "tool result has no tool_call_id").into()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Export conversations as OpenAI chat fine-tuning JSONL.
+///
+/// Each conversation becomes one `{"messages": [...]}` line, with `tools`
+/// when the conversation has any. Images are written as content parts;
+/// cache-control markers are dropped.
+///
+/// # Errors
+///
+/// Returns a [`ValidationError`] naming the first conversation that breaks
+/// the rules of [`Conversation::validate`].
+///
+/// # Example
+/// ```
+/// use simple_agents_types::export::{to_openai_finetune_jsonl, Conversation};
+/// use simple_agents_types::message::Message;
+///
+/// let conversations = vec![Conversation::new(vec![
+///     Message::user("Capital of France?"),
+///     Message::assistant("Paris."),
+/// ])];
+/// let jsonl = to_openai_finetune_jsonl(&conversations).unwrap();
+/// assert_eq!(
+///     jsonl,
+///     "{\"messages\":[{\"content\":\"Capital of France?\",\"role\":\"user\"},\
+///      {\"content\":\"Paris.\",\"role\":\"assistant\"}]}\n"
+/// );
+/// ```
+pub fn to_openai_finetune_jsonl(conversations: &[Conversation]) -> Result<String> {
+    let mut jsonl = String::new();
+    for (i, conversation) in conversations.iter().enumerate() {
+        conversation.validate_at(&format!("conversations[{}].messages", i))?;
+
+        let messages: Vec<Value> = conversation.messages.iter().map(openai_message).collect();
+        let mut line = serde_json::json!({ "messages": messages });
+        if !conversation.tools.is_empty() {
+            line["tools"] = serde_json::to_value(&conversation.tools)?;
+        }
+        jsonl.push_str(&line.to_string());
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+/// Load conversations from OpenAI chat fine-tuning JSONL.
+///
+/// Blank lines are skipped; fields the format allows but [`Message`] does
+/// not model (such as `weight`) are ignored.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::InvalidFormat`] for lines that are not
+/// fine-tuning records, and the errors of [`Conversation::validate`] for
+/// conversations that break its rules.
+pub fn from_openai_finetune_jsonl(jsonl: &str) -> Result<Vec<Conversation>> {
+    #[derive(Deserialize)]
+    struct Record {
+        messages: Vec<WireMessage>,
+        #[serde(default)]
+        tools: Vec<ToolDefinition>,
+    }
+
+    #[derive(Deserialize)]
+    struct WireMessage {
+        role: Role,
+        #[serde(default)]
+        content: Option<Value>,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        tool_call_id: Option<String>,
+        #[serde(default)]
+        tool_calls: Vec<ToolCall>,
+    }
+
+    let mut conversations = Vec::new();
+    for (index, line) in jsonl.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let field = format!("line {}", index + 1);
+        let record: Record = serde_json::from_str(line).map_err(|e| ValidationError::InvalidFormat {
+            field: field.clone(),
+            reason: e.to_string(),
+        })?;
+
+        let mut messages = Vec::with_capacity(record.messages.len());
+        for (i, wire) in record.messages.into_iter().enumerate() {
+            let (content, images) = parse_openai_content(wire.content)
+                .map_err(|reason| ValidationError::InvalidFormat {
+                    field: format!("{} messages[{}].content", field, i),
+                    reason,
+                })?;
+            let mut message = Message::user(content);
+            message.role = wire.role;
+            message.name = wire.name;
+            message.tool_call_id = wire.tool_call_id;
+            message.tool_calls = wire.tool_calls;
+            message.images = images;
+            messages.push(message);
+        }
+
+        let conversation = Conversation::new(messages).with_tools(record.tools);
+        conversation.validate_at(&format!("{} messages", field))?;
+        conversations.push(conversation);
+    }
+    Ok(conversations)
+}
+
+/// Export conversations in ShareGPT format.
+///
+/// Produces a JSON array of `{"conversations": [{"from", "value"}, ...]}`
+/// entries. Turns are `system`, `human`, `gpt`, `function_call` (the calls
+/// as `{"name", "arguments"}`, or an array of them) and `observation`;
+/// tools are stored as a JSON string under `tools`. Message names,
+/// tool-call ids and cache-control markers are not represented.
+///
+/// # Errors
+///
+/// Returns the errors of [`Conversation::validate`], or
+/// [`ValidationError::InvalidFormat`] for messages ShareGPT cannot hold:
+/// images, and assistant text alongside tool calls.
+pub fn to_sharegpt_json(conversations: &[Conversation]) -> Result<String> {
+    let mut entries = Vec::with_capacity(conversations.len());
+    for (i, conversation) in conversations.iter().enumerate() {
+        let field = format!("conversations[{}].messages", i);
+        conversation.validate_at(&field)?;
+
+        let mut turns = Vec::with_capacity(conversation.messages.len());
+        for (j, message) in conversation.messages.iter().enumerate() {
+            let unsupported = |reason: &str| ValidationError::InvalidFormat {
+                field: format!("{}[{}]", field, j),
+                reason: reason.to_string(),
+            };
+            if !message.images.is_empty() {
+                return Err(unsupported("ShareGPT export does not support images").into());
+            }
+
+            let (from, value) = match message.role {
+                Role::System => ("system", message.content.clone()),
+                Role::User => ("human", message.content.clone()),
+                Role::Tool => ("observation", message.content.clone()),
+                Role::Assistant if message.tool_calls.is_empty() => ("gpt", message.content.clone()),
+                Role::Assistant => {
+                    if !message.content.trim().is_empty() {
+                        let reason = "ShareGPT cannot hold assistant text alongside tool calls";
+                        return Err(unsupported(reason).into());
+                    }
+                    let calls: Vec<Value> = message.tool_calls.iter().map(sharegpt_call).collect();
+                    let value = match calls.as_slice() {
+                        [call] => call.to_string(),
+                        _ => Value::Array(calls).to_string(),
+                    };
+                    ("function_call", value)
+                }
+            };
+            turns.push(serde_json::json!({ "from": from, "value": value }));
+        }
+
+        let mut entry = serde_json::json!({ "conversations": turns });
+        if !conversation.tools.is_empty() {
+            entry["tools"] = Value::String(serde_json::to_string(&conversation.tools)?);
+        }
+        entries.push(entry);
+    }
+    Ok(serde_json::to_string(&entries)?)
+}
+
+/// Load conversations from ShareGPT JSON.
+///
+/// Accepts the turn names written by [`to_sharegpt_json`] and the common
+/// aliases (`user`, `assistant`, `chatgpt`, `tool`, `function`), a
+/// top-level `system` prompt, and `tools` as a JSON string or array. Tool
+/// calls are given ids `call_0`, `call_1`, … and each observation answers
+/// the oldest unanswered call.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::InvalidFormat`] for input that is not
+/// ShareGPT, and the errors of [`Conversation::validate`] for conversations
+/// that break its rules.
+pub fn from_sharegpt_json(json: &str) -> Result<Vec<Conversation>> {
+    #[derive(Deserialize)]
+    struct Entry {
+        conversations: Vec<Turn>,
+        #[serde(default)]
+        system: Option<String>,
+        #[serde(default)]
+        tools: Option<Value>,
+    }
+
+    #[derive(Deserialize)]
+    struct Turn {
+        from: String,
+        value: String,
+    }
+
+    let entries: Vec<Entry> = serde_json::from_str(json).map_err(|e| ValidationError::InvalidFormat {
+        field: "sharegpt".to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let mut conversations = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.into_iter().enumerate() {
+        let field = format!("conversations[{}]", i);
+        let invalid = |part: String, reason: String| ValidationError::InvalidFormat {
+            field: format!("{}{}", field, part),
+            reason,
+        };
+
+        let tools = match entry.tools {
+            None => Ok(Vec::new()),
+            Some(Value::String(tools)) if tools.trim().is_empty() => Ok(Vec::new()),
+            Some(Value::String(tools)) => serde_json::from_str(&tools),
+            Some(tools) => serde_json::from_value(tools),
+        }
+        .map_err(|e| invalid(".tools".to_string(), e.to_string()))?;
+
+        let mut messages: Vec<Message> = entry.system.into_iter().map(Message::system).collect();
+        let mut next_call = 0;
+        let mut pending: std::collections::VecDeque<String> = std::collections::VecDeque::new();
+        for (j, turn) in entry.conversations.into_iter().enumerate() {
+            let message = match turn.from.as_str() {
+                "system" => Message::system(turn.value),
+                "human" | "user" => Message::user(turn.value),
+                "gpt" | "assistant" | "chatgpt" => Message::assistant(turn.value),
+                "function_call" => {
+                    let calls = parse_sharegpt_calls(&turn.value)
+                        .map_err(|reason| invalid(format!(".conversations[{}].value", j), reason))?;
+                    let mut message = Message::assistant("");
+                    for (name, arguments) in calls {
+                        let id = format!("call_{}", next_call);
+                        next_call += 1;
+                        pending.push_back(id.clone());
+                        message = message.with_tool_call(ToolCall::function(id, name, arguments));
+                    }
+                    message
+                }
+                "observation" | "tool" | "function" => {
+                    let id = pending.pop_front().ok_or_else(|| {
+                        invalid(
+                            format!(".conversations[{}]", j),
+                            "observation without a preceding function_call".to_string(),
+                        )
+                    })?;
+                    Message::tool(turn.value, id)
+                }
+                other => {
+                    return Err(invalid(
+                        format!(".conversations[{}].from", j),
+                        format!("unknown speaker {:?}", other),
+                    )
+                    .into())
+                }
+            };
+            messages.push(message);
+        }
+
+        let conversation = Conversation::new(messages).with_tools(tools);
+        conversation.validate_at(&format!("{}.messages", field))?;
+        conversations.push(conversation);
+    }
+    Ok(conversations)
+}
+
+/// A message as written to fine-tuning JSONL
+fn openai_message(message: &Message) -> Value {
+    let mut wire = serde_json::json!({ "role": message.role });
+    if !(message.content.is_empty() && message.images.is_empty() && !message.tool_calls.is_empty()) {
+        wire["content"] = message.to_openai_content();
+    }
+    if let Some(name) = &message.name {
+        wire["name"] = Value::String(name.clone());
+    }
+    if let Some(id) = &message.tool_call_id {
+        wire["tool_call_id"] = Value::String(id.clone());
+    }
+    if !message.tool_calls.is_empty() {
+        wire["tool_calls"] = serde_json::to_value(&message.tool_calls).unwrap_or_default();
+    }
+    wire
+}
+
+/// Split OpenAI message content into text and images
+fn parse_openai_content(content: Option<Value>) -> std::result::Result<(String, Vec<ImageUrl>), String> {
+    match content {
+        None | Some(Value::Null) => Ok((String::new(), Vec::new())),
+        Some(Value::String(text)) => Ok((text, Vec::new())),
+        Some(Value::Array(parts)) => {
+            let mut text = String::new();
+            let mut images = Vec::new();
+            for part in parts {
+                match part["type"].as_str() {
+                    Some("text") => text.push_str(part["text"].as_str().unwrap_or_default()),
+                    Some("image_url") => images.push(
+                        serde_json::from_value(part["image_url"].clone()).map_err(|e| e.to_string())?,
+                    ),
+                    other => return Err(format!("unsupported content part type {:?}", other)),
+                }
+            }
+            Ok((text, images))
+        }
+        Some(other) => Err(format!("expected a string or content parts, got {}", other)),
+    }
+}
+
+/// A tool call as written to ShareGPT: arguments inline when they are JSON
+fn sharegpt_call(call: &ToolCall) -> Value {
+    let arguments = serde_json::from_str(&call.function.arguments)
+        .unwrap_or_else(|_| Value::String(call.function.arguments.clone()));
+    serde_json::json!({ "name": call.function.name, "arguments": arguments })
+}
+
+/// Parse a ShareGPT `function_call` value into `(name, arguments)` pairs
+fn parse_sharegpt_calls(value: &str) -> std::result::Result<Vec<(String, String)>, String> {
+    let calls = match serde_json::from_str::<Value>(value).map_err(|e| e.to_string())? {
+        Value::Array(calls) => calls,
+        call => vec![call],
+    };
+    calls
+        .into_iter()
+        .map(|call| {
+            let name = call["name"].as_str().ok_or("function call has no name")?.to_string();
+            let arguments = match &call["arguments"] {
+                Value::String(arguments) => arguments.clone(),
+                Value::Null => "{}".to_string(),
+                arguments => arguments.to_string(),
+            };
+            Ok((name, arguments))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SimpleAgentsError;
+    use crate::message::ImageDetail;
+
+    fn weather_tool() -> ToolDefinition {
+        ToolDefinition::function(
+            "get_weather",
+            "Get the current weather for a city",
+            serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        )
+    }
+
+    fn tool_conversation() -> Conversation {
+        Conversation::new(vec![
+            Message::system("You are a weather bot."),
+            Message::user("Weather in Paris and Rome?"),
+            Message::assistant("")
+                .with_tool_call(ToolCall::function("call_0", "get_weather", r#"{"city":"Paris"}"#))
+                .with_tool_call(ToolCall::function("call_1", "get_weather", r#"{"city":"Rome"}"#)),
+            Message::tool(r#"{"temp":18}"#, "call_0"),
+            Message::tool(r#"{"temp":24}"#, "call_1"),
+            Message::assistant("Paris is 18°C and Rome is 24°C."),
+        ])
+        .with_tools(vec![weather_tool()])
+    }
+
+    fn chat_conversation() -> Conversation {
+        Conversation::new(vec![
+            Message::user("Hi"),
+            Message::assistant("Hello! How can I help?"),
+            Message::user("Tell me a joke."),
+            Message::assistant("Why did the crab never share? Because it was shellfish."),
+        ])
+    }
+
+    fn validation_field(err: SimpleAgentsError) -> String {
+        match err {
+            SimpleAgentsError::Validation(ValidationError::InvalidFormat { field, .. })
+            | SimpleAgentsError::Validation(ValidationError::Empty { field }) => field,
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_openai_finetune_round_trip() {
+        let named = Conversation::new(vec![
+            Message::user("What is this?")
+                .with_name("alice")
+                .with_image(ImageUrl::new("https://example.com/cat.png").with_detail(ImageDetail::Low)),
+            Message::assistant("A cat."),
+        ]);
+        let conversations = vec![chat_conversation(), tool_conversation(), named];
+
+        let jsonl = to_openai_finetune_jsonl(&conversations).unwrap();
+        assert_eq!(jsonl.lines().count(), 3);
+
+        let tool_line: Value = serde_json::from_str(jsonl.lines().nth(1).unwrap()).unwrap();
+        assert_eq!(tool_line["tools"][0]["function"]["name"], "get_weather");
+        let call_message = &tool_line["messages"][2];
+        assert!(call_message.get("content").is_none());
+        assert_eq!(call_message["tool_calls"][1]["function"]["arguments"], r#"{"city":"Rome"}"#);
+        assert_eq!(tool_line["messages"][3]["tool_call_id"], "call_0");
+
+        let image_line: Value = serde_json::from_str(jsonl.lines().nth(2).unwrap()).unwrap();
+        assert_eq!(image_line["messages"][0]["content"][1]["type"], "image_url");
+
+        assert_eq!(from_openai_finetune_jsonl(&format!("\n{}\n", jsonl)).unwrap(), conversations);
+    }
+
+    #[test]
+    fn test_sharegpt_round_trip() {
+        let conversations = vec![chat_conversation(), tool_conversation()];
+
+        let json = to_sharegpt_json(&conversations).unwrap();
+        let entries: Value = serde_json::from_str(&json).unwrap();
+        let turns = &entries[1]["conversations"];
+        assert_eq!(turns[1], serde_json::json!({"from": "human", "value": "Weather in Paris and Rome?"}));
+        assert_eq!(turns[2]["from"], "function_call");
+        assert_eq!(
+            serde_json::from_str::<Value>(turns[2]["value"].as_str().unwrap()).unwrap(),
+            serde_json::json!([
+                {"name": "get_weather", "arguments": {"city": "Paris"}},
+                {"name": "get_weather", "arguments": {"city": "Rome"}}
+            ])
+        );
+        assert_eq!(turns[3]["from"], "observation");
+        assert_eq!(turns[5]["from"], "gpt");
+        assert!(entries[1]["tools"].is_string());
+
+        assert_eq!(from_sharegpt_json(&json).unwrap(), conversations);
+    }
+
+    #[test]
+    fn test_sharegpt_import_aliases() {
+        let json = r#"[{
+            "system": "Be terse.",
+            "tools": [],
+            "conversations": [
+                {"from": "user", "value": "Time?"},
+                {"from": "function_call", "value": "{\"name\": \"now\"}"},
+                {"from": "tool", "value": "12:00"},
+                {"from": "chatgpt", "value": "Noon."}
+            ]
+        }]"#;
+        let conversations = from_sharegpt_json(json).unwrap();
+        let messages = &conversations[0].messages;
+        assert_eq!(messages[0], Message::system("Be terse."));
+        assert_eq!(messages[2].tool_calls, vec![ToolCall::function("call_0", "now", "{}")]);
+        assert_eq!(messages[3], Message::tool("12:00", "call_0"));
+        assert_eq!(messages[4].role, Role::Assistant);
+    }
+
+    #[test]
+    fn test_replay_request() {
+        let request = tool_conversation().replay_request("gpt-4o-mini").unwrap();
+        assert_eq!(request.messages.len(), 5);
+        assert_eq!(request.messages[4].role, Role::Tool);
+        assert_eq!(request.tools, Some(vec![weather_tool()]));
+
+        let request = chat_conversation().replay_request("gpt-4o-mini").unwrap();
+        assert_eq!(request.messages.last().unwrap().content, "Tell me a joke.");
+        assert!(request.tools.is_none());
+
+        let only_assistant = Conversation::new(vec![Message::assistant("Hi")]);
+        assert!(only_assistant.replay_request("gpt-4o-mini").is_err());
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let cases = [
+            (Conversation::default(), "conversations[0].messages"),
+            (
+                Conversation::new(vec![Message::user("Hi")]),
+                "conversations[0].messages[0].role",
+            ),
+            (
+                Conversation::new(vec![Message::user("  "), Message::assistant("?")]),
+                "conversations[0].messages[0].content",
+            ),
+            (
+                Conversation::new(vec![
+                    Message::user("Hi"),
+                    Message::tool("{}", "call_9"),
+                    Message::assistant("Done"),
+                ]),
+                "conversations[0].messages[1].tool_call_id",
+            ),
+            (
+                Conversation::new(vec![
+                    Message::user("Hi").with_tool_call(ToolCall::function("call_0", "f", "{}")),
+                    Message::assistant("Done"),
+                ]),
+                "conversations[0].messages[0].tool_calls",
+            ),
+        ];
+        for (conversation, field) in cases {
+            let err = to_openai_finetune_jsonl(std::slice::from_ref(&conversation)).unwrap_err();
+            assert_eq!(validation_field(err), field);
+            assert!(to_sharegpt_json(&[conversation]).is_err());
+        }
+
+        let with_text = Conversation::new(vec![
+            Message::user("Hi"),
+            Message::assistant("Let me check.").with_tool_call(ToolCall::function("call_0", "f", "{}")),
+            Message::tool("{}", "call_0"),
+            Message::assistant("Done"),
+        ]);
+        assert!(to_openai_finetune_jsonl(std::slice::from_ref(&with_text)).is_ok());
+        assert_eq!(
+            validation_field(to_sharegpt_json(&[with_text]).unwrap_err()),
+            "conversations[0].messages[1]"
+        );
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let err = from_openai_finetune_jsonl("{\"messages\": []}\nnot json\n").unwrap_err();
+        assert_eq!(validation_field(err), "line 1 messages");
+
+        let err = from_openai_finetune_jsonl("not json").unwrap_err();
+        assert_eq!(validation_field(err), "line 1");
+
+        let unanswered = r#"{"messages": [{"role": "user", "content": "Hi"}]}"#;
+        let err = from_openai_finetune_jsonl(unanswered).unwrap_err();
+        assert_eq!(validation_field(err), "line 1 messages[0].role");
+
+        let audio = r#"{"messages": [{"role": "user", "content": [{"type": "input_audio"}]}]}"#;
+        let err = from_openai_finetune_jsonl(audio).unwrap_err();
+        assert_eq!(validation_field(err), "line 1 messages[0].content");
+
+        let orphan = r#"[{"conversations": [
+            {"from": "human", "value": "Hi"},
+            {"from": "observation", "value": "{}"},
+            {"from": "gpt", "value": "Done"}
+        ]}]"#;
+        let err = from_sharegpt_json(orphan).unwrap_err();
+        assert_eq!(validation_field(err), "conversations[0].conversations[1]");
+
+        let unknown = r#"[{"conversations": [{"from": "narrator", "value": "Once"}]}]"#;
+        let err = from_sharegpt_json(unknown).unwrap_err();
+        assert_eq!(validation_field(err), "conversations[0].conversations[0].from");
+    }
+}
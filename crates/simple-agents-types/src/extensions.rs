@@ -0,0 +1,172 @@
+//! Values that travel with a request but are never sent to the provider.
+//!
+//! Wrappers sometimes make a decision that a provider further down needs:
+//! a router picks a deployment, a retry layer counts attempts, a cache
+//! layer asks to be bypassed. [`CompletionRequest::extensions`] and
+//! [`ProviderRequest::extensions`] carry such values. Both are skipped
+//! when serializing, so they never reach the wire and do not affect
+//! [`CompletionRequest::fingerprint`] or cache keys. Providers copy the
+//! completion request's extensions into the provider request they build,
+//! so a value set before [`Provider::transform_request`] is still there in
+//! [`Provider::execute`].
+//!
+//! Keys are plain strings; prefix them with the component that owns them
+//! (`router.deployment`, `retry.attempt`) to avoid collisions.
+//!
+//! # Example
+//! ```
+//! use simple_agents_types::prelude::*;
+//!
+//! let request = CompletionRequest::builder()
+//!     .model("gpt-4")
+//!     .message(Message::user("Hello"))
+//!     .extension("router.deployment", "eastus-2")
+//!     .build()?;
+//!
+//! // Not part of the wire format
+//! assert!(!serde_json::to_string(&request)?.contains("eastus-2"));
+//! assert_eq!(request.extensions.get_as::<String>("router.deployment").as_deref(), Some("eastus-2"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! See `examples/request_extensions.rs` for a wrapper that sets a value and
+//! a provider that reads it.
+//!
+//! [`CompletionRequest::extensions`]: crate::request::CompletionRequest::extensions
+//! [`CompletionRequest::fingerprint`]: crate::request::CompletionRequest::fingerprint
+//! [`ProviderRequest::extensions`]: crate::provider::ProviderRequest::extensions
+//! [`Provider::transform_request`]: crate::provider::Provider::transform_request
+//! [`Provider::execute`]: crate::provider::Provider::execute
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// String-keyed JSON values attached to a request.
+///
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Extensions {
+    values: BTreeMap<String, Value>,
+}
+
+impl Extensions {
+    /// Create an empty set of extensions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `key` to `value`, returning the previous value.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.values.insert(key.into(), value.into())
+    }
+
+    /// The value for `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// The value for `key` deserialized as `T`; `None` if it is missing or
+    /// has a different shape.
+    pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        T::deserialize(self.values.get(key)?).ok()
+    }
+
+    /// Remove `key`, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.values.remove(key)
+    }
+
+    /// Whether `key` is set.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// Number of keys set.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether no keys are set.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Keys and values, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values.iter().map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Extensions {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().map(|(key, value)| (key.into(), value.into())).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderRequest;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_typed_access() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.is_empty());
+        assert_eq!(extensions.insert("retry.attempt", 2), None);
+        assert_eq!(extensions.insert("retry.attempt", 3), Some(Value::from(2)));
+        extensions.insert("cache.bypass", true);
+
+        assert_eq!(extensions.get_as::<u32>("retry.attempt"), Some(3));
+        assert_eq!(extensions.get_as::<String>("retry.attempt"), None);
+        assert_eq!(extensions.get_as::<bool>("missing"), None);
+        assert_eq!(
+            extensions.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec!["cache.bypass", "retry.attempt"]
+        );
+
+        assert_eq!(extensions.remove("cache.bypass"), Some(Value::Bool(true)));
+        assert!(!extensions.contains_key("cache.bypass"));
+        assert_eq!(extensions.len(), 1);
+    }
+
+    #[test]
+    fn test_extensions_survive_to_builder() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .extension("router.deployment", "eastus-2")
+            .extension("retry.attempt", 1)
+            .build()
+            .unwrap();
+
+        let rebuilt = request.to_builder().temperature(0.5).build().unwrap();
+        assert_eq!(rebuilt.extensions, request.extensions);
+        assert_eq!(rebuilt.temperature, Some(0.5));
+        assert_eq!(rebuilt.to_builder().build().unwrap(), rebuilt);
+    }
+
+    #[test]
+    fn test_extensions_are_not_serialized() {
+        let mut request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap();
+        let fingerprint = request.fingerprint();
+        request.extensions.insert("cache.bypass", true);
+
+        assert_eq!(request.fingerprint(), fingerprint);
+        assert!(!serde_json::to_string(&request).unwrap().contains("cache.bypass"));
+        let json = serde_json::to_value(&request).unwrap();
+        let parsed: CompletionRequest = serde_json::from_value(json).unwrap();
+        assert!(parsed.extensions.is_empty());
+
+        let mut provider_request = ProviderRequest::new("https://api.example.com");
+        provider_request.extensions = request.extensions.clone();
+        assert!(!serde_json::to_string(&provider_request).unwrap().contains("cache.bypass"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Image token cost estimation.
+//!
+//! Implements OpenAI's published formula for vision inputs, and reads image
+//! dimensions from PNG, JPEG and GIF headers so callers need not decode the
+//! image to estimate it.
+
+use crate::message::ImageDetail;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Tokens charged for a low-detail image, and the base cost of a high-detail one.
+pub const IMAGE_BASE_TOKENS: u32 = 85;
+
+/// Tokens charged per 512px tile of a high-detail image.
+pub const IMAGE_TILE_TOKENS: u32 = 170;
+
+/// Most tokens a high-detail image can cost: one scaled to 768×2048, which
+/// covers 8 tiles.
+pub const IMAGE_MAX_TOKENS: u32 = IMAGE_BASE_TOKENS + 8 * IMAGE_TILE_TOKENS;
+
+/// High-detail images are first scaled to fit in a square of this size.
+const MAX_SIDE: f64 = 2048.0;
+
+/// ...then so their shortest side is at most this long.
+const MAX_SHORT_SIDE: f64 = 768.0;
+
+const TILE_SIZE: f64 = 512.0;
+
+/// Estimate the prompt tokens OpenAI charges for a `width`×`height` image.
+///
+/// Low detail costs a flat [`IMAGE_BASE_TOKENS`]. High detail scales the
+/// image to fit within 2048×2048, then so the shortest side is at most
+/// 768px, and charges [`IMAGE_TILE_TOKENS`] per 512px tile on top of the
+/// base cost. `Auto` lets the model choose, so it is estimated as high
+/// detail, the upper bound.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::image::estimate_image_tokens;
+/// use simple_agents_types::message::ImageDetail;
+///
+/// assert_eq!(estimate_image_tokens(1024, 1024, ImageDetail::High), 765);
+/// assert_eq!(estimate_image_tokens(4096, 8192, ImageDetail::Low), 85);
+/// ```
+pub fn estimate_image_tokens(width: u32, height: u32, detail: ImageDetail) -> u32 {
+    if detail == ImageDetail::Low {
+        return IMAGE_BASE_TOKENS;
+    }
+
+    let (mut width, mut height) = (f64::from(width), f64::from(height));
+    let fit = (MAX_SIDE / width.max(height)).min(1.0);
+    width *= fit;
+    height *= fit;
+    let shrink = (MAX_SHORT_SIDE / width.min(height)).min(1.0);
+    width *= shrink;
+    height *= shrink;
+
+    let tiles = (width / TILE_SIZE).ceil() * (height / TILE_SIZE).ceil();
+    IMAGE_BASE_TOKENS.saturating_add(IMAGE_TILE_TOKENS.saturating_mul(tiles as u32))
+}
+
+/// Width and height of a PNG, JPEG or GIF image, read from its header.
+///
+/// Returns `None` for other formats and truncated or malformed headers.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::image::image_dimensions;
+///
+/// let gif = b"GIF89a\x40\x01\xf0\x00";
+/// assert_eq!(image_dimensions(gif), Some((320, 240)));
+/// ```
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // IHDR is always the first chunk
+        let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+        Some((width, height))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        let width = u16::from_le_bytes(bytes.get(6..8)?.try_into().ok()?);
+        let height = u16::from_le_bytes(bytes.get(8..10)?.try_into().ok()?);
+        Some((width.into(), height.into()))
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        jpeg_dimensions(bytes)
+    } else {
+        None
+    }
+}
+
+/// Bytes [`image_file_dimensions`] reads before giving up on finding a
+/// JPEG frame header (EXIF and ICC segments can push it far into the file).
+const MAX_HEADER_BYTES: usize = 1 << 20;
+
+/// Width and height of a PNG, JPEG or GIF image file, read from its header.
+///
+/// Reads only as much of the file as the header needs, so large images are
+/// not loaded into memory. Returns `Ok(None)` for other formats and for
+/// malformed headers.
+///
+/// # Errors
+///
+/// Returns the I/O error if the file cannot be opened or read.
+pub fn image_file_dimensions(path: impl AsRef<Path>) -> io::Result<Option<(u32, u32)>> {
+    let mut file = File::open(path)?;
+    let mut header = Vec::new();
+    let mut chunk = 64;
+    loop {
+        let read = (&mut file).take(chunk as u64).read_to_end(&mut header)?;
+        if let Some(dimensions) = image_dimensions(&header) {
+            return Ok(Some(dimensions));
+        }
+        if read < chunk || header.len() >= MAX_HEADER_BYTES {
+            return Ok(None);
+        }
+        chunk = header.len();
+    }
+}
+
+/// Walk JPEG segments to the first start-of-frame marker.
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    loop {
+        if *bytes.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(pos + 1)?;
+        pos += 2;
+        match marker {
+            // Fill byte before a marker
+            0xFF => pos -= 1,
+            // Markers without a length
+            0x01 | 0xD0..=0xD7 => {}
+            // SOF0-SOF15, except DHT, JPG and DAC which share the range
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let height = u16::from_be_bytes(bytes.get(pos + 3..pos + 5)?.try_into().ok()?);
+                let width = u16::from_be_bytes(bytes.get(pos + 5..pos + 7)?.try_into().ok()?);
+                return Some((width.into(), height.into()));
+            }
+            // End of image, or scan data before any frame header
+            0xD9 | 0xDA => return None,
+            _ => {
+                let length = u16::from_be_bytes(bytes.get(pos..pos + 2)?.try_into().ok()?);
+                pos += usize::from(length);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_image_tokens_documented_examples() {
+        // 1024x1024 is scaled to 768x768: 4 tiles
+        assert_eq!(estimate_image_tokens(1024, 1024, ImageDetail::High), 765);
+        // 2048x4096 is scaled to 1024x2048, then 768x1536: 6 tiles
+        assert_eq!(estimate_image_tokens(2048, 4096, ImageDetail::High), 1105);
+        assert_eq!(estimate_image_tokens(4096, 8192, ImageDetail::Low), 85);
+        // Small images are not scaled up
+        assert_eq!(estimate_image_tokens(512, 512, ImageDetail::High), 255);
+        assert_eq!(estimate_image_tokens(2048, 4096, ImageDetail::Auto), 1105);
+    }
+
+    #[test]
+    fn test_image_max_tokens_is_the_worst_case() {
+        assert_eq!(IMAGE_MAX_TOKENS, 1445);
+        assert_eq!(estimate_image_tokens(768, 2048, ImageDetail::High), IMAGE_MAX_TOKENS);
+        for (width, height) in [(4096, 4096), (1000, 10_000), (2048, 2048), (100, 30_000)] {
+            assert!(estimate_image_tokens(width, height, ImageDetail::High) <= IMAGE_MAX_TOKENS);
+        }
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&1920u32.to_be_bytes());
+        png.extend_from_slice(&1080u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((1920, 1080)));
+
+        // SOI, a 4-byte APP0 segment, a fill byte, then SOF2 (progressive)
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46, 0xFF, 0xFF, 0xC2, 0x00, 0x11, 0x08, 0x02, 0x58,
+            0x03, 0x20,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some((800, 600)));
+
+        assert_eq!(image_dimensions(&png[..20]), None);
+        assert_eq!(image_dimensions(&[0xFF, 0xD8, 0xFF, 0xDA]), None);
+        assert_eq!(image_dimensions(b"RIFF\x00\x00\x00\x00WEBP"), None);
+    }
+
+    #[test]
+    fn test_image_file_dimensions() {
+        let dir = std::env::temp_dir().join(format!("simple-agents-image-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // A JPEG frame header behind a 20KB APP1 segment, as with EXIF thumbnails
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1, 0x50, 0x00];
+        jpeg.resize(4 + 0x5000, 0);
+        jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x11, 0x08, 0x0C, 0x00, 0x10, 0x00]);
+        jpeg.resize(jpeg.len() + 100_000, 0);
+        let path = dir.join("photo.jpg");
+        std::fs::write(&path, &jpeg).unwrap();
+        assert_eq!(image_file_dimensions(&path).unwrap(), Some((4096, 3072)));
+
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "not an image").unwrap();
+        assert_eq!(image_file_dimensions(&path).unwrap(), None);
+
+        assert!(image_file_dimensions(dir.join("missing.png")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -10,6 +10,9 @@
 //!
 //! - **Provider**: Trait for LLM provider implementations
 //! - **Cache**: Trait for caching responses
+//! - **CredentialSource**: Trait for supplying (rotating) API keys
+//! - **EmbeddingProvider**: Trait for text embedding backends
+//! - **EventNotifier**: Trait for receiving batch, budget and health events
 //! - **RoutingStrategy**: Trait for provider selection
 //!
 //! # Main Types
@@ -49,19 +52,37 @@
 #![deny(unsafe_code)]
 
 // Core modules
+pub mod annotation;
+pub mod attempt;
+pub mod batch;
 pub mod cache;
+pub mod catalog;
 pub mod coercion;
 pub mod config;
+pub mod credentials;
+pub mod diff;
+pub mod display;
+pub mod embedding;
 pub mod error;
+pub mod event;
+pub mod extensions;
+pub mod export;
+pub mod image;
+pub mod memory;
 pub mod message;
 pub mod provider;
+pub mod rate_limit;
 pub mod request;
 pub mod response;
 pub mod router;
+pub mod tool;
+pub mod user_facing;
 pub mod validation;
 
 // Re-export commonly used types at crate root
-pub use error::{HealingError, ProviderError, Result, SimpleAgentsError, ValidationError};
+pub use error::{
+    ErrorCode, ErrorContext, HealingError, ProviderError, Result, SimpleAgentsError, ValidationError,
+};
 
 /// Prelude module for convenient imports.
 ///
@@ -78,36 +99,50 @@ pub use error::{HealingError, ProviderError, Result, SimpleAgentsError, Validati
 /// ```
 pub mod prelude {
     // Messages
-    pub use crate::message::{Message, Role};
+    pub use crate::message::{CacheControlType, Document, ImageDetail, ImageUrl, Message, MessageList, Role};
 
     // Requests and responses
-    pub use crate::request::{CompletionRequest, CompletionRequestBuilder};
+    pub use crate::request::{
+        AssistantPrefill, CompletionRequest, CompletionRequestBuilder, CompletionRequestInspector,
+        JsonSchemaFormat, Prediction, PrefillToken, ReasoningEffort, ResponseFormat, StreamOptions,
+    };
+    pub use crate::tool::{
+        FunctionCall, FunctionDefinition, ToolCall, ToolChoice, ToolDefinition, ToolHandler, ToolRegistry,
+        ToolType,
+    };
+    pub use crate::annotation::{Annotation, AnnotationKind};
+    pub use crate::attempt::{Attempt, AttemptHistory, CompleteOutcome};
+    pub use crate::extensions::Extensions;
     pub use crate::response::{
-        ChoiceDelta, CompletionChoice, CompletionChunk, CompletionResponse, FinishReason,
-        MessageDelta, Usage,
+        ChoiceDelta, ChunkAccumulator, CompletionChoice, CompletionChunk, CompletionResponse,
+        FinishReason, MessageDelta, ResponseSummary, Usage,
     };
 
     // Errors
     pub use crate::error::{
-        HealingError, ProviderError, Result, SimpleAgentsError, ValidationError,
+        ErrorCode, ErrorContext, HealingError, ProviderError, Result, SimpleAgentsError, ValidationError,
     };
 
-    // Validation
+    // Validation and credentials
+    pub use crate::credentials::CredentialSource;
     pub use crate::validation::ApiKey;
 
     // Configuration
-    pub use crate::config::{Capabilities, HealingConfig, ProviderConfig, RetryConfig};
+    pub use crate::config::{Capabilities, Feature, HealingConfig, ProviderConfig, RetryConfig, RetryPolicy};
+    pub use crate::catalog::{ModelCatalog, ModelInfoStatic};
 
     // Coercion
     pub use crate::coercion::{CoercionFlag, CoercionResult};
 
     // Traits
     pub use crate::cache::Cache;
-    pub use crate::provider::Provider;
+    pub use crate::embedding::EmbeddingProvider;
+    pub use crate::event::{Event, EventNotifier};
+    pub use crate::provider::{Provider, ProviderExt};
     pub use crate::router::RoutingStrategy;
 
     // Provider types
-    pub use crate::provider::{ProviderRequest, ProviderResponse};
+    pub use crate::provider::{DryRun, ProviderRequest, ProviderRequestPreview, ProviderResponse};
 
     // Router types
     pub use crate::router::{ProviderHealth, ProviderMetrics, RoutingMode};
@@ -171,9 +206,12 @@ mod tests {
                 message: Message::assistant("Hello!"),
                 finish_reason: FinishReason::Stop,
                 logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
             }],
             usage: Usage::new(10, 5),
             created: None,
+            created_synthesized: false,
             provider: None,
         };
 
@@ -0,0 +1,330 @@
+//! Keeping a conversation history within a token budget.
+//!
+//! [`drop_oldest`] removes the oldest turns until the history fits.
+//! [`SummarizingMemory`] instead asks a (cheap) model to summarize the
+//! oldest turns and replaces them with a single summary message, falling
+//! back to [`drop_oldest`] if the summarizer fails.
+//!
+//! Both work on whole turns and share the same rules:
+//! - system messages are never removed, except earlier summaries, which are
+//!   folded into the next one
+//! - an assistant message that calls tools and the tool results answering
+//!   it are removed together, and a call still waiting for results is kept
+//!   along with everything after it
+//! - the most recent turn is always kept
+//!
+//! Sizes are [`Message::estimate_tokens`] estimates. To compress a
+//! recorded [`Conversation`](crate::export::Conversation), pass its
+//! `messages`.
+
+use crate::error::{Result, SimpleAgentsError};
+use crate::message::{Message, Role};
+use crate::provider::Provider;
+use crate::request::CompletionRequest;
+use std::ops::Range;
+
+/// Start of the content of every summary message.
+pub const SUMMARY_PREFIX: &str = "Summary of earlier conversation:\n";
+
+/// Instructions sent to the summarizer by default.
+pub const DEFAULT_SUMMARY_PROMPT: &str = "Summarize the following conversation excerpt for the assistant \
+taking part in it. Keep names, numbers, decisions, open questions and tool results that may matter \
+later. Reply with the summary only.";
+
+/// Whether `message` is a summary written by [`SummarizingMemory`].
+pub fn is_summary(message: &Message) -> bool {
+    message.role == Role::System && message.content.starts_with(SUMMARY_PREFIX)
+}
+
+/// Estimated prompt tokens of `messages`.
+pub fn history_tokens(messages: &[Message]) -> u32 {
+    messages.iter().map(Message::estimate_tokens).sum()
+}
+
+/// Remove the oldest turns until `messages` fit in `budget` tokens.
+///
+/// Follows the rules in the [module documentation](self), so the history
+/// may stay over budget when nothing more can be removed. Returns the
+/// number of messages removed.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::memory::drop_oldest;
+/// use simple_agents_types::message::Message;
+///
+/// let mut messages = vec![
+///     Message::system("You are terse."),
+///     Message::user("First question, with a long explanation of the problem"),
+///     Message::assistant("First answer"),
+///     Message::user("Second question"),
+/// ];
+/// assert_eq!(drop_oldest(&mut messages, 20), 2);
+/// assert_eq!(messages, vec![Message::system("You are terse."), Message::user("Second question")]);
+/// ```
+pub fn drop_oldest(messages: &mut Vec<Message>, budget: u32) -> usize {
+    let before = messages.len();
+    while history_tokens(messages) > budget {
+        match removable_turns(messages).into_iter().next() {
+            Some(turn) => replace_turns(messages, &[turn.range], None),
+            None => break,
+        }
+    }
+    before - messages.len()
+}
+
+/// What [`SummarizingMemory::compress`] did.
+#[derive(Debug, Default)]
+pub struct Compression {
+    /// Messages folded into summaries, earlier summaries included
+    pub summarized: usize,
+    /// Messages dropped without a summary after the summarizer failed
+    pub dropped: usize,
+    /// The summarizer's error, if it failed
+    pub error: Option<SimpleAgentsError>,
+}
+
+/// Compresses a history by summarizing its oldest turns.
+///
+/// While the history is over budget, the oldest
+/// [`batch_size`](Self::with_batch_size) removable turns, together with the
+/// previous summary, are sent to the summarizer and replaced by one system
+/// message starting with [`SUMMARY_PREFIX`], at the position of the oldest
+/// of them. If the summarizer fails, the history is trimmed with
+/// [`drop_oldest`] instead and the error is reported in [`Compression`].
+///
+/// # Example
+/// ```no_run
+/// use simple_agents_types::memory::SummarizingMemory;
+/// use simple_agents_types::prelude::*;
+///
+/// # async fn example(cheap: Box<dyn Provider>, mut history: Vec<Message>) -> Result<()> {
+/// let memory = SummarizingMemory::new(cheap, "gpt-4o-mini", 8_000).with_batch_size(6);
+/// let compression = memory.compress(&mut history).await;
+/// if let Some(error) = compression.error {
+///     eprintln!("summarizer failed, dropped {} messages: {}", compression.dropped, error);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SummarizingMemory<P> {
+    summarizer: P,
+    model: String,
+    budget: u32,
+    batch_size: usize,
+    prompt: String,
+    max_summary_tokens: u32,
+}
+
+impl<P: Provider> SummarizingMemory<P> {
+    /// Keep histories within `budget` tokens, summarizing with `model` on
+    /// `summarizer`.
+    pub fn new(summarizer: P, model: impl Into<String>, budget: u32) -> Self {
+        Self {
+            summarizer,
+            model: model.into(),
+            budget,
+            batch_size: 4,
+            prompt: DEFAULT_SUMMARY_PROMPT.to_string(),
+            max_summary_tokens: 512,
+        }
+    }
+
+    /// Turns summarized per summarizer call (default 4, at least 1).
+    pub fn with_batch_size(mut self, turns: usize) -> Self {
+        self.batch_size = turns.max(1);
+        self
+    }
+
+    /// Replace [`DEFAULT_SUMMARY_PROMPT`].
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// `max_tokens` of each summarizer call (default 512).
+    pub fn with_max_summary_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_summary_tokens = max_tokens;
+        self
+    }
+
+    /// The token budget.
+    pub fn budget(&self) -> u32 {
+        self.budget
+    }
+
+    /// Summarize the oldest turns of `messages` until they fit the budget.
+    ///
+    /// Does nothing when the history already fits. Never fails: summarizer
+    /// errors fall back to [`drop_oldest`].
+    pub async fn compress(&self, messages: &mut Vec<Message>) -> Compression {
+        let mut compression = Compression::default();
+        while history_tokens(messages) > self.budget {
+            let batch: Vec<Turn> = removable_turns(messages)
+                .into_iter()
+                .scan(0, |new_turns, turn| {
+                    *new_turns += usize::from(!turn.summary);
+                    (*new_turns <= self.batch_size).then_some(turn)
+                })
+                .collect();
+            // Summarizing a lone summary again would not shrink anything
+            if batch.iter().all(|turn| turn.summary) {
+                break;
+            }
+
+            let ranges: Vec<Range<usize>> = batch.iter().map(|turn| turn.range.clone()).collect();
+            let excerpt: Vec<&Message> = ranges.iter().flat_map(|range| &messages[range.clone()]).collect();
+            match self.summarize(&excerpt).await {
+                Ok(summary) => {
+                    compression.summarized += excerpt.len();
+                    let summary = Message::system(format!("{}{}", SUMMARY_PREFIX, summary.trim()));
+                    replace_turns(messages, &ranges, Some(summary));
+                }
+                Err(error) => {
+                    compression.dropped = drop_oldest(messages, self.budget);
+                    compression.error = Some(error);
+                    break;
+                }
+            }
+        }
+        compression
+    }
+
+    async fn summarize(&self, excerpt: &[&Message]) -> Result<String> {
+        let request = CompletionRequest::builder()
+            .model(self.model.clone())
+            .message(Message::system(self.prompt.clone()))
+            .message(Message::user(transcript(excerpt)))
+            .max_tokens(self.max_summary_tokens)
+            .build()?;
+        let response = self.summarizer.complete(&request).await?;
+        match response.content().map(str::trim) {
+            Some(summary) if !summary.is_empty() => Ok(summary.to_string()),
+            _ => Err(crate::error::ProviderError::InvalidResponse(format!(
+                "summarizer returned no summary in response {}",
+                response.id
+            ))
+            .into()),
+        }
+    }
+}
+
+/// A run of messages removed or summarized as a whole
+#[derive(Debug, Clone)]
+struct Turn {
+    range: Range<usize>,
+    summary: bool,
+}
+
+/// The turns that may be removed, oldest first: everything before the
+/// first tool call still waiting for results and before the last turn,
+/// except system messages that are not summaries
+fn removable_turns(messages: &[Message]) -> Vec<Turn> {
+    let mut turns = Vec::new();
+    let mut i = 0;
+    while i < messages.len() {
+        let message = &messages[i];
+        if message.role == Role::System && !is_summary(message) {
+            i += 1;
+            continue;
+        }
+
+        let mut end = i + 1;
+        if message.role == Role::Assistant && !message.tool_calls.is_empty() {
+            let mut pending: Vec<&str> = message.tool_calls.iter().map(|call| call.id.as_str()).collect();
+            while let Some(result) = messages.get(end).filter(|m| m.role == Role::Tool) {
+                pending.retain(|id| Some(*id) != result.tool_call_id.as_deref());
+                end += 1;
+            }
+            if !pending.is_empty() {
+                // The last turn comes after this one, so it is kept as well
+                return turns;
+            }
+        }
+        turns.push(Turn {
+            range: i..end,
+            summary: is_summary(message),
+        });
+        i = end;
+    }
+
+    // The last turn is the one being answered
+    turns.pop();
+    turns
+}
+
+/// Remove the messages in `ranges`, putting `replacement` where the first
+/// of them was
+fn replace_turns(messages: &mut Vec<Message>, ranges: &[Range<usize>], replacement: Option<Message>) {
+    let first = ranges.iter().map(|range| range.start).min().unwrap_or(0);
+    let mut replacement = replacement;
+    let old = std::mem::take(messages);
+    for (i, message) in old.into_iter().enumerate() {
+        if i == first {
+            messages.extend(replacement.take());
+        }
+        if !ranges.iter().any(|range| range.contains(&i)) {
+            messages.push(message);
+        }
+    }
+}
+
+/// Plain-text rendering of `messages` for the summarizer
+fn transcript(messages: &[&Message]) -> String {
+    let mut lines = Vec::new();
+    for message in messages {
+        if is_summary(message) {
+            lines.push(message.content.clone());
+            continue;
+        }
+        let speaker = match message.role {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::System => "System",
+            Role::Tool => "Tool result",
+        };
+        if !message.content.is_empty() {
+            lines.push(format!("{}: {}", speaker, message.content));
+        }
+        for call in &message.tool_calls {
+            lines.push(format!("{} called {}({})", speaker, call.function.name, call.function.arguments));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::ToolCall;
+
+    #[test]
+    fn test_removable_turns_keep_tool_pairs_together() {
+        let messages = vec![
+            Message::system("You are terse."),
+            Message::user("Weather?"),
+            Message::assistant("").with_tool_call(ToolCall::function("call_1", "weather", "{}")),
+            Message::tool("12°C", "call_1"),
+            Message::assistant("12°C"),
+            Message::user("And tomorrow?"),
+            Message::assistant("").with_tool_call(ToolCall::function("call_2", "forecast", "{}")),
+            Message::user("Hurry up"),
+        ];
+
+        let ranges: Vec<Range<usize>> = removable_turns(&messages).into_iter().map(|t| t.range).collect();
+        // The unanswered call_2 and everything after it stay
+        assert_eq!(ranges, vec![1..2, 2..4, 4..5, 5..6]);
+    }
+
+    #[test]
+    fn test_drop_oldest_never_drops_the_last_turn() {
+        let mut messages = vec![
+            Message::system("You are terse."),
+            Message::user("x".repeat(400)),
+            Message::user("y".repeat(400)),
+        ];
+        assert_eq!(drop_oldest(&mut messages, 10), 1);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].content, "y".repeat(400));
+        assert!(history_tokens(&messages) > 10);
+    }
+}
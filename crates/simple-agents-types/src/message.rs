@@ -1,7 +1,48 @@
 //! Message types for LLM interactions.
 //!
 //! Provides role-based messages compatible with OpenAI's message format.
+//!
+//! # Example
+//!
+//! A conversation in which the assistant calls a tool, sees its result and
+//! is then asked about an image:
+//!
+//! ```
+//! use simple_agents_types::message::{CacheControlType, ImageUrl, Message, Role};
+//! use simple_agents_types::tool::ToolCall;
+//!
+//! let messages = vec![
+//!     // A long, stable system prompt is worth caching
+//!     Message::system("You are a travel assistant.").with_cache_control(CacheControlType::Ephemeral),
+//!     Message::user("What's the weather in Paris?"),
+//!     // Assistant turns that only call tools have empty content
+//!     Message::assistant("").with_tool_call(ToolCall::function(
+//!         "call_1",
+//!         "get_weather",
+//!         r#"{"city":"Paris"}"#,
+//!     )),
+//!     Message::tool(r#"{"temp_c":18,"sky":"cloudy"}"#, "call_1"),
+//!     Message::assistant("It's 18°C and cloudy in Paris."),
+//!     Message::user("Is this the Eiffel Tower?").with_image(ImageUrl::new("https://example.com/tower.jpg")),
+//! ];
+//!
+//! let roles: Vec<Role> = messages.iter().map(|m| m.role).collect();
+//! assert_eq!(
+//!     roles,
+//!     [Role::System, Role::User, Role::Assistant, Role::Tool, Role::Assistant, Role::User]
+//! );
+//! assert_eq!(messages[3].tool_call_id.as_deref(), Some(messages[2].tool_calls[0].id.as_str()));
+//! ```
+//!
+//! # Empty content
+//!
+//! Constructors accept empty content. It is expected on assistant turns
+//! that only call tools or only refuse, and on user turns that only carry
+//! images or documents. Providers receive it as-is, and some reject empty
+//! user or system messages, so skip those when nothing is attached.
 
+use crate::error::{Result, SimpleAgentsError, ValidationError};
+use crate::tool::ToolCall;
 use serde::{Deserialize, Serialize};
 
 /// Role of a message in a conversation.
@@ -19,12 +60,218 @@ pub enum Role {
     Tool,
 }
 
+/// Prompt caching mode for a message.
+///
+/// Serializes in Anthropic's `cache_control` format, e.g.
+/// `{"type": "ephemeral"}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CacheControlType {
+    /// Short-lived cache entry (currently five minutes)
+    Ephemeral,
+}
+
+/// Fidelity at which a vision model processes an image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    /// Let the provider choose
+    #[default]
+    Auto,
+    /// Low resolution: faster and cheaper
+    Low,
+    /// High resolution: more detail, more tokens
+    High,
+}
+
+/// An image attached to a message.
+///
+/// Serializes in OpenAI's `image_url` format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageUrl {
+    /// `https://` URL, or a `data:` URL with base64-encoded image bytes
+    pub url: String,
+    /// Requested processing fidelity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
+    /// Width and height in pixels, for token estimates; not sent
+    #[serde(skip)]
+    pub dimensions: Option<(u32, u32)>,
+}
+
+impl ImageUrl {
+    /// Reference an image by URL.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            detail: None,
+            dimensions: None,
+        }
+    }
+
+    /// Set the processing fidelity (builder pattern).
+    pub fn with_detail(mut self, detail: ImageDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// Record the image size for token estimates (builder pattern).
+    ///
+    /// Only needed for `https://` URLs; the size of a `data:` URL image is
+    /// read from its header.
+    pub fn with_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.dimensions = Some((width, height));
+        self
+    }
+
+    /// Record the size of the local file this image was uploaded from
+    /// (builder pattern).
+    ///
+    /// Only the file header is read; see
+    /// [`image_file_dimensions`](crate::image::image_file_dimensions). The
+    /// size is left unset if the file is not a PNG, JPEG or GIF.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file cannot be read.
+    pub fn with_dimensions_from_file(mut self, path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        if let Some(dimensions) = crate::image::image_file_dimensions(path)? {
+            self.dimensions = Some(dimensions);
+        }
+        Ok(self)
+    }
+
+    /// Width and height in pixels, if set or readable from a `data:` URL.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        use base64::Engine;
+
+        if self.dimensions.is_some() {
+            return self.dimensions;
+        }
+        let (header, data) = self.url.strip_prefix("data:")?.split_once(',')?;
+        if !header.ends_with(";base64") {
+            return None;
+        }
+        let bytes = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+        crate::image::image_dimensions(&bytes)
+    }
+
+    /// Estimate the prompt tokens this image consumes.
+    ///
+    /// Uses [`estimate_image_tokens`](crate::image::estimate_image_tokens)
+    /// when the size is known. Otherwise a low-detail image costs
+    /// [`IMAGE_TOKEN_ESTIMATE`], and a high or auto-detail one the worst
+    /// case, [`IMAGE_MAX_TOKENS`](crate::image::IMAGE_MAX_TOKENS), so
+    /// budgets are not exceeded.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{ImageDetail, ImageUrl};
+    ///
+    /// let image = ImageUrl::new("https://example.com/cat.png");
+    /// assert_eq!(image.estimate_tokens(), 1445);
+    /// let image = image.with_dimensions(1024, 1024);
+    /// assert_eq!(image.estimate_tokens(), 765);
+    /// assert_eq!(image.with_detail(ImageDetail::Low).estimate_tokens(), 85);
+    /// ```
+    pub fn estimate_tokens(&self) -> u32 {
+        self.estimate_tokens_at(self.detail.unwrap_or_default())
+    }
+
+    /// Estimate the prompt tokens this image consumes at `detail`.
+    fn estimate_tokens_at(&self, detail: ImageDetail) -> u32 {
+        match self.dimensions() {
+            Some((width, height)) => crate::image::estimate_image_tokens(width, height, detail),
+            None if detail == ImageDetail::Low => IMAGE_TOKEN_ESTIMATE,
+            None => crate::image::IMAGE_MAX_TOKENS,
+        }
+    }
+}
+
+/// A document (e.g. a PDF) attached to a message.
+///
+/// Serializes in Anthropic's document `source` format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Document {
+    /// Document bytes sent inline
+    Base64 {
+        /// MIME type, e.g. `application/pdf`
+        media_type: String,
+        /// Base64-encoded document bytes
+        data: String,
+    },
+    /// File previously uploaded to the provider
+    File {
+        /// Identifier returned by the provider's upload endpoint
+        file_id: String,
+    },
+}
+
+impl Document {
+    /// MIME type of PDF documents
+    pub const PDF_MEDIA_TYPE: &'static str = "application/pdf";
+
+    /// Inline document from base64-encoded `data`.
+    pub fn base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self::Base64 {
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Inline PDF from base64-encoded `data`.
+    pub fn pdf(data: impl Into<String>) -> Self {
+        Self::base64(Self::PDF_MEDIA_TYPE, data)
+    }
+
+    /// Reference to an uploaded file.
+    pub fn file(file_id: impl Into<String>) -> Self {
+        Self::File {
+            file_id: file_id.into(),
+        }
+    }
+
+    /// Size in bytes of an inline document once decoded; `None` for files.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::Document;
+    ///
+    /// assert_eq!(Document::pdf("JVBERi0=").decoded_len(), Some(5));
+    /// assert_eq!(Document::file("file_011").decoded_len(), None);
+    /// ```
+    pub fn decoded_len(&self) -> Option<usize> {
+        match self {
+            Self::Base64 { data, .. } => {
+                let padding = data.bytes().rev().take_while(|b| *b == b'=').count();
+                Some((data.len() / 4 * 3).saturating_sub(padding))
+            }
+            Self::File { .. } => None,
+        }
+    }
+}
+
+/// Tokens charged per message for role and framing (OpenAI chat format).
+pub const MESSAGE_TOKEN_OVERHEAD: u32 = 4;
+
+/// Estimated prompt tokens for a low-detail image (OpenAI's flat
+/// low-detail cost).
+pub const IMAGE_TOKEN_ESTIMATE: u32 = 85;
+
+/// Approximate characters per token used by the local estimator.
+const CHARS_PER_TOKEN: usize = 4;
+
 /// A message in a conversation.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     /// Role of the message sender
     pub role: Role,
     /// Content of the message
+    ///
+    /// Empty when an assistant message carries only tool calls or a
+    /// refusal; `null` deserializes as empty.
+    #[serde(deserialize_with = "null_as_empty")]
     pub content: String,
     /// Optional name (for multi-user conversations or tool calls)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -32,6 +279,29 @@ pub struct Message {
     /// Tool call ID (for tool role messages)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Cache the prompt up to and including this message (Anthropic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControlType>,
+    /// Images sent along with the text, for vision models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageUrl>,
+    /// Documents sent along with the text (Anthropic)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub documents: Vec<Document>,
+    /// Tool calls made by an assistant message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Why the model declined to answer, in place of content (OpenAI)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refusal: Option<String>,
+}
+
+/// Deserialize a string, treating `null` as empty.
+fn null_as_empty<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
 }
 
 impl Message {
@@ -44,6 +314,11 @@ impl Message {
     /// let msg = Message::user("Hello!");
     /// assert_eq!(msg.role, Role::User);
     /// assert_eq!(msg.content, "Hello!");
+    /// assert!(msg.images.is_empty() && msg.name.is_none());
+    ///
+    /// // Empty content is allowed, e.g. when the message only carries an image
+    /// let empty = Message::user("");
+    /// assert_eq!(empty.content, "");
     /// ```
     pub fn user(content: impl Into<String>) -> Self {
         Self {
@@ -51,6 +326,11 @@ impl Message {
             content: content.into(),
             name: None,
             tool_call_id: None,
+            cache_control: None,
+            images: Vec::new(),
+            documents: Vec::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
         }
     }
 
@@ -62,6 +342,8 @@ impl Message {
     ///
     /// let msg = Message::assistant("Hi there!");
     /// assert_eq!(msg.role, Role::Assistant);
+    /// assert_eq!(msg.content, "Hi there!");
+    /// assert!(msg.tool_calls.is_empty() && msg.refusal.is_none());
     /// ```
     pub fn assistant(content: impl Into<String>) -> Self {
         Self {
@@ -69,6 +351,11 @@ impl Message {
             content: content.into(),
             name: None,
             tool_call_id: None,
+            cache_control: None,
+            images: Vec::new(),
+            documents: Vec::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
         }
     }
 
@@ -80,6 +367,7 @@ impl Message {
     ///
     /// let msg = Message::system("You are a helpful assistant.");
     /// assert_eq!(msg.role, Role::System);
+    /// assert_eq!(msg.content, "You are a helpful assistant.");
     /// ```
     pub fn system(content: impl Into<String>) -> Self {
         Self {
@@ -87,17 +375,26 @@ impl Message {
             content: content.into(),
             name: None,
             tool_call_id: None,
+            cache_control: None,
+            images: Vec::new(),
+            documents: Vec::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
         }
     }
 
-    /// Create a tool message.
+    /// Create a tool message carrying the result of a tool call.
+    ///
+    /// `tool_call_id` must match the [`ToolCall::id`] of the assistant's
+    /// call; results are usually JSON, but any text is accepted.
     ///
     /// # Example
     /// ```
     /// use simple_agents_types::message::{Message, Role};
     ///
-    /// let msg = Message::tool("result", "call_123");
+    /// let msg = Message::tool(r#"{"temp_c":18}"#, "call_123");
     /// assert_eq!(msg.role, Role::Tool);
+    /// assert_eq!(msg.content, r#"{"temp_c":18}"#);
     /// assert_eq!(msg.tool_call_id, Some("call_123".to_string()));
     /// ```
     pub fn tool(content: impl Into<String>, tool_call_id: impl Into<String>) -> Self {
@@ -106,6 +403,11 @@ impl Message {
             content: content.into(),
             name: None,
             tool_call_id: Some(tool_call_id.into()),
+            cache_control: None,
+            images: Vec::new(),
+            documents: Vec::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
         }
     }
 
@@ -117,11 +419,447 @@ impl Message {
     ///
     /// let msg = Message::user("Hello").with_name("Alice");
     /// assert_eq!(msg.name, Some("Alice".to_string()));
+    /// assert_eq!(msg.content, "Hello");
     /// ```
     pub fn with_name(mut self, name: impl Into<String>) -> Self {
         self.name = Some(name.into());
         self
     }
+
+    /// Mark this message as a prompt caching breakpoint (builder pattern).
+    ///
+    /// Providers that support prompt caching (Anthropic) cache the prompt
+    /// prefix ending at this message; others ignore it.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{CacheControlType, Message, Role};
+    ///
+    /// let msg = Message::system("<long reference document>")
+    ///     .with_cache_control(CacheControlType::Ephemeral);
+    /// assert_eq!(msg.role, Role::System);
+    /// assert_eq!(msg.content, "<long reference document>");
+    /// assert_eq!(msg.cache_control, Some(CacheControlType::Ephemeral));
+    /// ```
+    pub fn with_cache_control(mut self, cache_type: CacheControlType) -> Self {
+        self.cache_control = Some(cache_type);
+        self
+    }
+
+    /// Attach an image (builder pattern).
+    ///
+    /// Requires a provider with [`Feature::Vision`](crate::config::Feature::Vision).
+    /// Local images are sent inline as `data:` URLs; reading their size with
+    /// [`ImageUrl::with_dimensions_from_file`] makes token estimates exact.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{ImageDetail, ImageUrl, Message, Role};
+    ///
+    /// let msg = Message::user("What is in this picture?")
+    ///     .with_image(ImageUrl::new("https://example.com/cat.png").with_detail(ImageDetail::Low));
+    /// assert_eq!(msg.role, Role::User);
+    /// assert_eq!(msg.content, "What is in this picture?");
+    /// assert_eq!(msg.images.len(), 1);
+    ///
+    /// // An inline image with no text
+    /// let inline = Message::user("").with_image(ImageUrl::new("data:image/png;base64,iVBORw0KGgo="));
+    /// assert_eq!(inline.content, "");
+    /// assert!(inline.images[0].url.starts_with("data:image/png"));
+    /// ```
+    pub fn with_image(mut self, image: ImageUrl) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    /// Attach a document (builder pattern).
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{Document, Message};
+    ///
+    /// let msg = Message::user("Summarize this report").with_document(Document::file("file_011"));
+    /// assert_eq!(msg.documents.len(), 1);
+    /// ```
+    pub fn with_document(mut self, document: Document) -> Self {
+        self.documents.push(document);
+        self
+    }
+
+    /// Record a tool call made by this (assistant) message (builder pattern).
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{Message, Role};
+    /// use simple_agents_types::tool::ToolCall;
+    ///
+    /// let msg = Message::assistant("")
+    ///     .with_tool_call(ToolCall::function("call_1", "get_weather", r#"{"city":"Paris"}"#));
+    /// assert_eq!(msg.role, Role::Assistant);
+    /// assert_eq!(msg.content, "");
+    /// assert_eq!(msg.tool_calls[0].id, "call_1");
+    /// ```
+    pub fn with_tool_call(mut self, call: ToolCall) -> Self {
+        self.tool_calls.push(call);
+        self
+    }
+
+    /// Content in OpenAI's chat format.
+    ///
+    /// A plain string without images; otherwise an array of content parts:
+    /// the text (if any) followed by one `image_url` part per image.
+    pub fn to_openai_content(&self) -> serde_json::Value {
+        if self.images.is_empty() {
+            return serde_json::Value::String(self.content.clone());
+        }
+
+        let mut parts = Vec::with_capacity(self.images.len() + 1);
+        if !self.content.is_empty() {
+            parts.push(serde_json::json!({ "type": "text", "text": self.content }));
+        }
+        for image in &self.images {
+            parts.push(serde_json::json!({ "type": "image_url", "image_url": image }));
+        }
+        serde_json::Value::Array(parts)
+    }
+
+    /// Parse a message from a JSON object in this crate's format.
+    ///
+    /// The inverse of [`to_json_value`](Self::to_json_value). Unlike
+    /// `serde_json::from_value`, a missing or unknown `role` and a missing
+    /// `content` are reported by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidFormat`] naming the offending field
+    /// if `value` is not an object, `role` is missing or not one of `user`,
+    /// `assistant`, `system` and `tool`, `content` is missing or not a
+    /// string, or any other field has the wrong shape.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{Message, Role};
+    ///
+    /// let message = Message::from_json_value(serde_json::json!({
+    ///     "role": "user",
+    ///     "content": "Hello!"
+    /// }))?;
+    /// assert_eq!(message.role, Role::User);
+    ///
+    /// let error = Message::from_json_value(serde_json::json!({ "role": "bot", "content": "" }));
+    /// assert!(error.unwrap_err().to_string().contains("unknown role \"bot\""));
+    /// # Ok::<(), simple_agents_types::error::SimpleAgentsError>(())
+    /// ```
+    pub fn from_json_value(value: serde_json::Value) -> Result<Self> {
+        let invalid = |field: &str, reason: String| {
+            SimpleAgentsError::Validation(ValidationError::InvalidFormat {
+                field: field.to_string(),
+                reason,
+            })
+        };
+
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid("message", format!("expected an object, got {}", json_type(&value))))?;
+        match object.get("role") {
+            None => return Err(invalid("role", "missing".to_string())),
+            Some(serde_json::Value::String(role)) => {
+                if serde_json::from_value::<Role>(serde_json::Value::String(role.clone())).is_err() {
+                    return Err(invalid(
+                        "role",
+                        format!("unknown role \"{}\"; expected user, assistant, system or tool", role),
+                    ));
+                }
+            }
+            Some(other) => {
+                return Err(invalid("role", format!("expected a string, got {}", json_type(other))));
+            }
+        }
+        match object.get("content") {
+            None => return Err(invalid("content", "missing".to_string())),
+            Some(serde_json::Value::String(_)) => {}
+            Some(other) => {
+                return Err(invalid("content", format!("expected a string, got {}", json_type(other))));
+            }
+        }
+
+        serde_json::from_value(value).map_err(|e| invalid("message", e.to_string()))
+    }
+
+    /// This message as a JSON object.
+    ///
+    /// Round-trips through [`from_json_value`](Self::from_json_value),
+    /// except for [`ImageUrl::dimensions`], which is never serialized.
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("messages always serialize to JSON")
+    }
+
+    /// Estimate the number of prompt tokens this message consumes.
+    ///
+    /// Uses a ~4 characters per token heuristic over the content (and name,
+    /// if set) plus [`MESSAGE_TOKEN_OVERHEAD`] for the role framing and
+    /// [`ImageUrl::estimate_tokens`] per image. This is an approximation, not
+    /// a tokenizer.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::Message;
+    ///
+    /// // 4 overhead + "Hello!" (6 chars -> 2 tokens)
+    /// assert_eq!(Message::user("Hello!").estimate_tokens(), 6);
+    /// ```
+    pub fn estimate_tokens(&self) -> u32 {
+        let chars = self.content.chars().count()
+            + self.name.as_ref().map_or(0, |name| name.chars().count());
+        let content_tokens = chars.div_ceil(CHARS_PER_TOKEN);
+        let images = self
+            .images
+            .iter()
+            .fold(0u32, |total, image| total.saturating_add(image.estimate_tokens()));
+        MESSAGE_TOKEN_OVERHEAD
+            .saturating_add(u32::try_from(content_tokens).unwrap_or(u32::MAX))
+            .saturating_add(images)
+    }
+
+    /// Estimate the prompt tokens this message's images consume if all are
+    /// sent at `detail`, ignoring their own [`ImageUrl::detail`].
+    ///
+    /// `Low` costs a flat 85 tokens per image. `High` and `Auto` use the
+    /// tile-based formula of
+    /// [`estimate_image_tokens`](crate::image::estimate_image_tokens) when
+    /// an image's size is known, and the worst case,
+    /// [`IMAGE_MAX_TOKENS`](crate::image::IMAGE_MAX_TOKENS), otherwise.
+    /// Useful to compare detail levels before choosing one.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::message::{ImageDetail, ImageUrl, Message};
+    ///
+    /// let msg = Message::user("Compare these")
+    ///     .with_image(ImageUrl::new("https://example.com/a.png").with_dimensions(1024, 1024))
+    ///     .with_image(ImageUrl::new("https://example.com/b.png").with_dimensions(512, 512));
+    /// assert_eq!(msg.estimate_image_tokens(ImageDetail::Low), 170);
+    /// assert_eq!(msg.estimate_image_tokens(ImageDetail::High), 765 + 255);
+    /// ```
+    pub fn estimate_image_tokens(&self, detail: ImageDetail) -> u32 {
+        self.images
+            .iter()
+            .fold(0u32, |total, image| total.saturating_add(image.estimate_tokens_at(detail)))
+    }
+}
+
+/// The messages of a conversation, oldest first.
+///
+/// Dereferences to `[Message]`, so slice methods (`iter`, `len`,
+/// indexing, ...) work as on a `Vec<Message>`, and converts to and from
+/// one. Serializes as a plain array.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::message::{Message, MessageList};
+///
+/// let mut messages = MessageList::from(vec![Message::system("You are terse.")]);
+/// for turn in 0..5 {
+///     messages.append_and_cap(Message::user(format!("Question {}", turn)), 3);
+/// }
+/// assert_eq!(messages.len(), 3);
+/// assert_eq!(messages.system_messages().count(), 1);
+/// assert_eq!(messages[2].content, "Question 4");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MessageList(Vec<Message>);
+
+impl MessageList {
+    /// An empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `message`.
+    pub fn push(&mut self, message: Message) {
+        self.0.push(message);
+    }
+
+    /// Insert `message` at `index`, shifting later messages back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn insert(&mut self, index: usize, message: Message) {
+        self.0.insert(index, message);
+    }
+
+    /// Remove and return the message at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Message {
+        self.0.remove(index)
+    }
+
+    /// Remove and return the last message.
+    pub fn pop(&mut self) -> Option<Message> {
+        self.0.pop()
+    }
+
+    /// Keep only the first `len` messages.
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    /// Keep only the messages for which `keep` returns `true`.
+    pub fn retain(&mut self, keep: impl FnMut(&Message) -> bool) {
+        self.0.retain(keep);
+    }
+
+    /// Remove every message.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// The system messages, in order.
+    pub fn system_messages(&self) -> impl Iterator<Item = &Message> {
+        self.by_role(Role::System)
+    }
+
+    /// The user messages, in order.
+    pub fn user_messages(&self) -> impl Iterator<Item = &Message> {
+        self.by_role(Role::User)
+    }
+
+    fn by_role(&self, role: Role) -> impl Iterator<Item = &Message> {
+        self.0.iter().filter(move |message| message.role == role)
+    }
+
+    /// Estimated prompt tokens of all messages (see
+    /// [`Message::estimate_tokens`]).
+    pub fn token_estimate(&self) -> u32 {
+        crate::memory::history_tokens(&self.0)
+    }
+
+    /// A copy with the oldest turns removed until it fits in `budget`
+    /// tokens.
+    ///
+    /// System messages are kept, as are the most recent turn and tool
+    /// calls together with their results; see
+    /// [`memory::drop_oldest`](crate::memory::drop_oldest). The result may
+    /// stay over budget when nothing more can be removed.
+    pub fn trim_to_budget(&self, budget: u32) -> MessageList {
+        let mut messages = self.0.clone();
+        crate::memory::drop_oldest(&mut messages, budget);
+        Self(messages)
+    }
+
+    /// Append `message`, then remove the oldest non-system messages until
+    /// at most `max_len` are left.
+    ///
+    /// Tool results left without the call that requested them are removed
+    /// as well. System messages and `message` itself are never removed, so
+    /// the list stays longer than `max_len` if they alone exceed it.
+    pub fn append_and_cap(&mut self, message: Message, max_len: usize) -> &mut Self {
+        self.0.push(message);
+        while self.0.len() > max_len {
+            let newest = self.0.len() - 1;
+            let Some(oldest) = self.0[..newest].iter().position(|m| m.role != Role::System) else {
+                break;
+            };
+            self.0.remove(oldest);
+            while oldest < self.0.len() - 1 && self.0[oldest].role == Role::Tool {
+                self.0.remove(oldest);
+            }
+        }
+        self
+    }
+
+    /// The underlying vector.
+    pub fn into_vec(self) -> Vec<Message> {
+        self.0
+    }
+}
+
+impl std::ops::Deref for MessageList {
+    type Target = [Message];
+
+    fn deref(&self) -> &[Message] {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for MessageList {
+    fn deref_mut(&mut self) -> &mut [Message] {
+        &mut self.0
+    }
+}
+
+impl From<Vec<Message>> for MessageList {
+    fn from(messages: Vec<Message>) -> Self {
+        Self(messages)
+    }
+}
+
+impl From<MessageList> for Vec<Message> {
+    fn from(messages: MessageList) -> Self {
+        messages.0
+    }
+}
+
+impl FromIterator<Message> for MessageList {
+    fn from_iter<I: IntoIterator<Item = Message>>(messages: I) -> Self {
+        Self(messages.into_iter().collect())
+    }
+}
+
+impl Extend<Message> for MessageList {
+    fn extend<I: IntoIterator<Item = Message>>(&mut self, messages: I) {
+        self.0.extend(messages);
+    }
+}
+
+impl IntoIterator for MessageList {
+    type Item = Message;
+    type IntoIter = std::vec::IntoIter<Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MessageList {
+    type Item = &'a Message;
+    type IntoIter = std::slice::Iter<'a, Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut MessageList {
+    type Item = &'a mut Message;
+    type IntoIter = std::slice::IterMut<'a, Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl PartialEq<Vec<Message>> for MessageList {
+    fn eq(&self, other: &Vec<Message>) -> bool {
+        self.0 == *other
+    }
+}
+
+/// JSON type name of `value`, for error messages
+fn json_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "a boolean",
+        serde_json::Value::Number(_) => "a number",
+        serde_json::Value::String(_) => "a string",
+        serde_json::Value::Array(_) => "an array",
+        serde_json::Value::Object(_) => "an object",
+    }
 }
 
 #[cfg(test)]
@@ -165,6 +903,30 @@ mod tests {
         assert_eq!(msg.name, Some("Alice".to_string()));
     }
 
+    #[test]
+    fn test_message_cache_control_serialization() {
+        let json = serde_json::to_value(Message::user("test")).unwrap();
+        assert!(json.get("cache_control").is_none());
+
+        let msg = Message::system("doc").with_cache_control(CacheControlType::Ephemeral);
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["cache_control"], serde_json::json!({"type": "ephemeral"}));
+
+        let parsed: Message = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, msg);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(Message::user("").estimate_tokens(), MESSAGE_TOKEN_OVERHEAD);
+        assert_eq!(Message::user("abcd").estimate_tokens(), MESSAGE_TOKEN_OVERHEAD + 1);
+        assert_eq!(Message::user("abcde").estimate_tokens(), MESSAGE_TOKEN_OVERHEAD + 2);
+        assert_eq!(
+            Message::user("abcd").with_name("Bob").estimate_tokens(),
+            MESSAGE_TOKEN_OVERHEAD + 2
+        );
+    }
+
     #[test]
     fn test_role_serialization() {
         let json = serde_json::to_string(&Role::User).unwrap();
@@ -192,8 +954,8 @@ mod tests {
     fn test_message_optional_fields_not_serialized() {
         let msg = Message::user("test");
         let json = serde_json::to_value(&msg).unwrap();
-        assert!(!json.get("name").is_some());
-        assert!(!json.get("tool_call_id").is_some());
+        assert!(json.get("name").is_none());
+        assert!(json.get("tool_call_id").is_none());
     }
 
     #[test]
@@ -202,4 +964,199 @@ mod tests {
         let json = serde_json::to_value(&msg).unwrap();
         assert_eq!(json.get("name").and_then(|v| v.as_str()), Some("Alice"));
     }
+
+    #[test]
+    fn test_message_images() {
+        let msg = Message::user("What is this?")
+            .with_image(ImageUrl::new("https://example.com/a.png").with_detail(ImageDetail::High))
+            .with_image(ImageUrl::new("data:image/png;base64,iVBORw0KGgo="));
+
+        assert_eq!(
+            msg.to_openai_content(),
+            serde_json::json!([
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/a.png", "detail": "high"}},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+            ])
+        );
+        // 4 overhead + 4 text tokens + 2 images of unknown size
+        assert_eq!(msg.estimate_tokens(), 8 + 2 * crate::image::IMAGE_MAX_TOKENS);
+
+        let parsed: Message = serde_json::from_str(&serde_json::to_string(&msg).unwrap()).unwrap();
+        assert_eq!(parsed, msg);
+    }
+
+    #[test]
+    fn test_image_estimate_tokens() {
+        // PNG header of a 2048x4096 image
+        let png = ImageUrl::new("data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAACAAAABAA");
+        assert_eq!(png.dimensions(), Some((2048, 4096)));
+        assert_eq!(png.estimate_tokens(), 1105);
+        assert_eq!(png.clone().with_detail(ImageDetail::Low).estimate_tokens(), IMAGE_TOKEN_ESTIMATE);
+
+        let remote = ImageUrl::new("https://example.com/a.png");
+        assert_eq!(remote.dimensions(), None);
+        assert_eq!(remote.estimate_tokens(), crate::image::IMAGE_MAX_TOKENS);
+        assert_eq!(remote.clone().with_detail(ImageDetail::Low).estimate_tokens(), IMAGE_TOKEN_ESTIMATE);
+        assert_eq!(remote.with_dimensions(1024, 1024).estimate_tokens(), 765);
+
+        let msg = Message::user("").with_image(png);
+        assert_eq!(msg.estimate_tokens(), MESSAGE_TOKEN_OVERHEAD + 1105);
+        assert!(serde_json::to_value(&msg).unwrap()["images"][0].get("dimensions").is_none());
+    }
+
+    #[test]
+    fn test_message_estimate_image_tokens() {
+        let msg = Message::user("Compare")
+            .with_image(ImageUrl::new("https://example.com/a.png").with_dimensions(2048, 4096))
+            .with_image(
+                ImageUrl::new("https://example.com/b.png")
+                    .with_dimensions(512, 512)
+                    .with_detail(ImageDetail::Low),
+            )
+            .with_image(ImageUrl::new("https://example.com/c.png"));
+
+        assert_eq!(msg.estimate_image_tokens(ImageDetail::Low), 3 * IMAGE_TOKEN_ESTIMATE);
+        // The image of unknown size is counted at the 8-tile worst case
+        assert_eq!(msg.estimate_image_tokens(ImageDetail::High), 1105 + 255 + 1445);
+        assert_eq!(msg.estimate_image_tokens(ImageDetail::Auto), 1105 + 255 + 1445);
+        // Each image's own detail applies to the message estimate
+        assert_eq!(msg.estimate_tokens(), MESSAGE_TOKEN_OVERHEAD + 2 + 1105 + 85 + 1445);
+        assert_eq!(Message::user("No images").estimate_image_tokens(ImageDetail::High), 0);
+    }
+
+    #[test]
+    fn test_text_only_openai_content() {
+        let msg = Message::user("Hello");
+        assert_eq!(msg.to_openai_content(), serde_json::json!("Hello"));
+        assert!(serde_json::to_value(&msg).unwrap().get("images").is_none());
+    }
+
+    #[test]
+    fn test_json_value_round_trip() {
+        let messages = vec![
+            Message::system("Be brief.").with_cache_control(CacheControlType::Ephemeral),
+            Message::user("What is this?")
+                .with_name("alice")
+                .with_image(ImageUrl::new("https://example.com/a.png").with_detail(ImageDetail::High))
+                .with_document(Document::file("file-abc123")),
+            Message::assistant("").with_tool_call(ToolCall::function("call_1", "lookup", "{\"q\":1}")),
+            Message::tool("42", "call_1"),
+        ];
+        for message in messages {
+            let value = message.to_json_value();
+            assert_eq!(value, serde_json::to_value(&message).unwrap());
+            assert_eq!(Message::from_json_value(value).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_from_json_value_errors() {
+        let error = |value: serde_json::Value| Message::from_json_value(value).unwrap_err().to_string();
+
+        assert_eq!(
+            error(serde_json::json!({ "content": "hi" })),
+            "Validation error: Invalid format: role (missing)"
+        );
+        assert_eq!(
+            error(serde_json::json!({ "role": "developer", "content": "hi" })),
+            "Validation error: Invalid format: role (unknown role \"developer\"; \
+             expected user, assistant, system or tool)"
+        );
+        assert_eq!(
+            error(serde_json::json!({ "role": 1, "content": "hi" })),
+            "Validation error: Invalid format: role (expected a string, got a number)"
+        );
+        assert_eq!(
+            error(serde_json::json!({ "role": "user" })),
+            "Validation error: Invalid format: content (missing)"
+        );
+        assert_eq!(
+            error(serde_json::json!({ "role": "user", "content": null })),
+            "Validation error: Invalid format: content (expected a string, got null)"
+        );
+        assert_eq!(
+            error(serde_json::json!(["user", "hi"])),
+            "Validation error: Invalid format: message (expected an object, got an array)"
+        );
+        assert!(error(serde_json::json!({ "role": "user", "content": "hi", "images": "x" }))
+            .starts_with("Validation error: Invalid format: message (invalid type"));
+    }
+
+    fn conversation() -> MessageList {
+        vec![
+            Message::system("You are terse."),
+            Message::user("First question, with a long explanation of the problem"),
+            Message::assistant("").with_tool_call(ToolCall::function("call_1", "lookup", "{}")),
+            Message::tool("found", "call_1"),
+            Message::assistant("First answer"),
+            Message::user("Second question"),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn test_message_list_conversions_and_filters() {
+        let mut messages = conversation();
+        assert_eq!(messages.len(), 6);
+        assert_eq!(messages.system_messages().count(), 1);
+        let questions: Vec<&str> = messages.user_messages().map(|m| m.content.as_str()).collect();
+        assert_eq!(questions, ["First question, with a long explanation of the problem", "Second question"]);
+
+        // Slices, iterators and Vec conversions
+        messages[5].content.push('?');
+        assert_eq!(messages.last().unwrap().content, "Second question?");
+        assert_eq!((&messages).into_iter().filter(|m| m.role == Role::Assistant).count(), 2);
+        let roundtrip: MessageList = Vec::from(messages.clone()).into();
+        assert_eq!(roundtrip, messages);
+        assert_eq!(messages.clone().into_iter().count(), 6);
+
+        // Serialized as a plain array
+        let json = serde_json::to_value(&messages).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 6);
+        assert_eq!(serde_json::from_value::<MessageList>(json).unwrap(), messages);
+    }
+
+    #[test]
+    fn test_message_list_token_estimate_and_trim() {
+        let messages = conversation();
+        let total: u32 = messages.iter().map(Message::estimate_tokens).sum();
+        assert_eq!(messages.token_estimate(), total);
+
+        let trimmed = messages.trim_to_budget(total - 1);
+        // The first question goes first; the original is untouched
+        assert_eq!(trimmed.len(), 5);
+        assert_eq!(trimmed[1].tool_calls[0].id, "call_1");
+        assert_eq!(messages.len(), 6);
+
+        // The tool call and its result are dropped together; the system
+        // message and the last turn stay even when over budget
+        let trimmed = messages.trim_to_budget(0);
+        assert_eq!(trimmed, vec![Message::system("You are terse."), Message::user("Second question")]);
+    }
+
+    #[test]
+    fn test_message_list_append_and_cap() {
+        let mut messages = conversation();
+        messages.append_and_cap(Message::assistant("Second answer"), 7);
+        assert_eq!(messages.len(), 7);
+
+        // Removing the tool call also removes its now orphaned result
+        messages.append_and_cap(Message::user("Third"), 6).append_and_cap(Message::user("Fourth"), 6);
+        let roles: Vec<Role> = messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            [Role::System, Role::Assistant, Role::User, Role::Assistant, Role::User, Role::User]
+        );
+        assert_eq!(messages[1].content, "First answer");
+
+        // Neither system messages nor the appended message are removed
+        let mut system_only = MessageList::from(vec![Message::system("a"), Message::system("b")]);
+        system_only.append_and_cap(Message::user("hi"), 1);
+        assert_eq!(system_only, vec![Message::system("a"), Message::system("b"), Message::user("hi")]);
+
+        let mut messages = MessageList::from(vec![Message::system("a"), Message::user("old")]);
+        messages.append_and_cap(Message::user("new"), 1);
+        assert_eq!(messages, vec![Message::system("a"), Message::user("new")]);
+    }
 }
@@ -2,14 +2,22 @@
 //!
 //! Defines the interface for LLM providers with transformation hooks.
 
-use crate::config::{Capabilities, RetryConfig};
-use crate::error::{Result, SimpleAgentsError, ProviderError};
-use crate::request::CompletionRequest;
-use crate::response::{CompletionResponse, CompletionChunk};
+use crate::attempt::{Attempt, AttemptHistory, CompleteOutcome};
+use crate::catalog::{ModelCatalog, ModelInfoStatic};
+use crate::config::{Capabilities, Feature, RetryConfig, RetryPolicy};
+use crate::display::Pricing;
+use crate::error::{ErrorContext, Result, SimpleAgentsError, ProviderError};
+use crate::extensions::Extensions;
+use crate::rate_limit;
+use crate::message::Message;
+use crate::request::{CompletionRequest, JsonSchemaFormat, PrefillToken, ResponseFormat};
+use crate::response::{CompletionResponse, CompletionChunk, Usage};
+use crate::tool::{ToolCall, ToolRegistry};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Common HTTP header names (static to avoid allocations)
 pub mod headers {
@@ -19,8 +27,24 @@ pub mod headers {
     pub const CONTENT_TYPE: &str = "Content-Type";
     /// API key header (used by some providers like Anthropic)
     pub const X_API_KEY: &str = "x-api-key";
+    /// API key header (used by Azure OpenAI)
+    pub const API_KEY: &str = "api-key";
+
+    /// Headers whose values are always redacted from logs and dry runs
+    pub const SENSITIVE: &[&str] = &[AUTHORIZATION, X_API_KEY, API_KEY];
 }
 
+/// Replacement for redacted header values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Extension set by [`Provider::execute_with_attempts`] to the attempt
+/// number (1-based) of the call in progress.
+///
+/// A provider that retries as it sends, like Anthropic's, sends only once
+/// when this is set, so the caller's [`RetryPolicy`] stays the only retry
+/// layer and every request sent is recorded as an attempt.
+pub const RETRY_ATTEMPT: &str = "retry.attempt";
+
 /// Trait for LLM providers.
 ///
 /// Providers implement this trait to support different LLM APIs while
@@ -38,6 +62,10 @@ pub mod headers {
 /// - Clean separation between protocol logic and business logic
 /// - Easy testing and mocking
 ///
+/// `Arc<P>` and `Box<P>` implement `Provider` whenever `P` does, `dyn
+/// Provider` included, so one provider can be shared across tasks with
+/// `Arc::new(provider)` and passed wherever a `Provider` is expected.
+///
 /// # Example Implementation
 ///
 /// ```ignore
@@ -100,6 +128,154 @@ pub trait Provider: Send + Sync {
     /// the standardized `CompletionResponse`.
     fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse>;
 
+    /// Run the full transform → execute → transform pipeline.
+    ///
+    /// Wrappers that short-circuit whole requests (replay stores, caches)
+    /// override this; everything else can rely on the default.
+    ///
+    /// Streaming requests (`stream: true`) are rejected with a validation
+    /// error; use [`Provider::execute_stream`] for those.
+    ///
+    /// With `trim_stop_sequences` set, echoed stop sequences are trimmed
+    /// from the response content. If the request's assistant prefill sets
+    /// `prepend_to_response`, the prefill is prepended to the response
+    /// content. A response whose `provider` was left unset by
+    /// [`transform_response`](Self::transform_response) gets
+    /// [`name`](Self::name).
+    ///
+    /// Errors are returned as [`SimpleAgentsError::WithContext`] carrying the
+    /// provider name and model; use [`SimpleAgentsError::root`] to match on
+    /// the underlying error. Errors that already carry context are passed
+    /// through unchanged.
+    async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+        complete_with(self, req, |provider_request| async move {
+            let provider_response = self.execute(provider_request).await?;
+            self.transform_response(provider_response)
+        })
+        .await
+    }
+
+    /// Run [`Provider::complete`], retrying failures according to `policy`.
+    ///
+    /// Each attempt runs the whole transform → execute → transform
+    /// pipeline. Only errors whose [`SimpleAgentsError::is_retryable`] is
+    /// true (rate limits, timeouts, network failures, server errors) are
+    /// retried; anything else is returned at once.
+    /// Between attempts it waits [`RetryPolicy::delay`].
+    ///
+    /// The returned error's context records the number of attempts made
+    /// and the total time spent waiting.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use simple_agents_types::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example(provider: &dyn Provider, request: CompletionRequest) -> Result<()> {
+    /// let policy = RetryPolicy::new(provider.retry_config(), |delay: Duration| async move {
+    ///     // Wait with your runtime's timer, e.g. tokio::time::sleep(delay)
+    /// #   let _ = delay;
+    /// });
+    /// let response = provider.execute_with_retries(&request, &policy).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn execute_with_retries(
+        &self,
+        req: &CompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<CompletionResponse> {
+        self.execute_with_attempts(req, policy).await.map(|outcome| outcome.response)
+    }
+
+    /// [`execute_with_retries`](Provider::execute_with_retries), also
+    /// returning every attempt made.
+    ///
+    /// Each attempt is recorded with its latency, error code and the wait
+    /// that followed it; a failed attempt whose error already carries a
+    /// history (from a wrapper such as a fallback chain) contributes that
+    /// history instead. On failure the history is in the error's
+    /// [`ErrorContext::attempts`]. Each call to
+    /// [`complete`](Provider::complete) carries its attempt number in the
+    /// [`RETRY_ATTEMPT`] extension.
+    ///
+    /// Wrappers that only pass requests through should forward this method
+    /// to the provider they wrap, so wrapping does not change the retries
+    /// or the history.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use simple_agents_types::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example(
+    /// #     provider: &dyn Provider,
+    /// #     request: CompletionRequest,
+    /// #     policy: RetryPolicy,
+    /// # ) -> Result<()> {
+    /// let outcome = provider.execute_with_attempts(&request, &policy).await?;
+    /// if outcome.attempts.failures() > 0 {
+    ///     eprintln!("succeeded after retries: {}", serde_json::to_string(&outcome.attempts)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn execute_with_attempts(
+        &self,
+        req: &CompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<CompleteOutcome> {
+        let mut attempts = AttemptHistory::new();
+        let mut total_delay = Duration::ZERO;
+        let mut attempt = 1;
+        let mut tagged = req.clone();
+        loop {
+            tagged.extensions.insert(RETRY_ATTEMPT, attempt);
+            let started = Instant::now();
+            let result = self.complete(&tagged).await;
+            let latency = started.elapsed();
+            let error = match result {
+                Ok(response) => {
+                    attempts.push(Attempt::succeeded(self.name(), attempt, latency));
+                    return Ok(CompleteOutcome { response, attempts });
+                }
+                Err(error) => error,
+            };
+            match error.context().filter(|ctx| !ctx.attempts.is_empty()) {
+                // A wrapper (e.g. a fallback chain) already recorded its calls
+                Some(ctx) => attempts.extend(ctx.attempts.clone()),
+                None => attempts.push(Attempt::failed(self.name(), attempt, error.code(), latency)),
+            }
+
+            if !error.is_retryable() || attempt >= policy.config.max_attempts {
+                return Err(match error {
+                    SimpleAgentsError::WithContext(mut ctx) => {
+                        ctx.attempt = attempt;
+                        ctx.total_delay = total_delay;
+                        ctx.attempts = attempts;
+                        SimpleAgentsError::WithContext(ctx)
+                    }
+                    error => SimpleAgentsError::WithContext(Box::new(error.with_context(
+                        ErrorContext::new()
+                            .provider(self.name())
+                            .model(req.model.clone())
+                            .attempt(attempt)
+                            .total_delay(total_delay)
+                            .attempts(attempts),
+                    ))),
+                });
+            }
+
+            let delay = policy.delay(attempt - 1, &error);
+            if let Some(last) = attempts.last_mut() {
+                last.backoff = delay;
+            }
+            policy.sleep(delay).await;
+            total_delay += delay;
+            attempt += 1;
+        }
+    }
+
     /// Get retry configuration.
     ///
     /// Override to customize retry behavior for this provider.
@@ -119,6 +295,159 @@ pub trait Provider: Send + Sync {
         Duration::from_secs(30)
     }
 
+    /// Model identifiers this provider is known to handle.
+    ///
+    /// Override with a static list of model IDs. The default empty list
+    /// means "unknown", in which case [`Provider::supports_model`] accepts
+    /// every model.
+    fn supported_models(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Names of headers, besides [`headers::SENSITIVE`], whose values are
+    /// redacted by [`Provider::dry_run`] and request logging.
+    ///
+    /// Override when a provider sends custom secret headers.
+    fn sensitive_headers(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Build the request for `req` without sending it.
+    ///
+    /// Returns the URL, headers (with secrets redacted) and pretty-printed
+    /// body that [`Provider::complete`] would send, plus a prompt token
+    /// estimate. Useful for debugging rejected requests.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::prelude::*;
+    /// # use async_trait::async_trait;
+    /// # struct Echo;
+    /// # #[async_trait]
+    /// # impl Provider for Echo {
+    /// #     fn name(&self) -> &str { "echo" }
+    /// #     fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+    /// #         Ok(ProviderRequest::new("https://api.example.com/v1/chat")
+    /// #             .with_header("Authorization", "Bearer sk-secret")
+    /// #             .with_body(serde_json::json!({ "model": req.model })))
+    /// #     }
+    /// #     async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> { unimplemented!() }
+    /// #     fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> { unimplemented!() }
+    /// # }
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4")
+    ///     .message(Message::user("Hello!"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let dry_run = Echo.dry_run(&request).unwrap();
+    /// assert_eq!(dry_run.headers_redacted[0].1, "[REDACTED]");
+    /// assert!(dry_run.body_pretty.contains("\"model\": \"gpt-4\""));
+    /// ```
+    fn dry_run(&self, req: &CompletionRequest) -> Result<DryRun> {
+        let provider_request = self.transform_request(req)?;
+        Ok(DryRun::new(&provider_request, &self.sensitive_headers(), req.token_count_total()))
+    }
+
+    /// Facts about `model`: context window, output limit, features and
+    /// prices.
+    ///
+    /// The default looks the model up in [`ModelCatalog::builtin`].
+    /// Override to consult another catalog, or to return `None` for
+    /// self-hosted models whose limits depend on the server.
+    fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+        ModelCatalog::builtin().get(model).cloned()
+    }
+
+    /// Prices of `model`, for cost estimates in [`Provider::explain`].
+    ///
+    /// The default takes them from [`Provider::model_info`]; override for
+    /// providers with their own price list.
+    fn pricing(&self, model: &str) -> Option<Pricing> {
+        self.model_info(model)?.pricing
+    }
+
+    /// Preview the request for `req` without sending it.
+    ///
+    /// Like [`Provider::dry_run`] (secrets are redacted the same way), plus
+    /// a cost estimate when [`Provider::pricing`] knows the model: the
+    /// estimated prompt tokens at the input price plus `max_tokens` (if
+    /// set) at the output price, i.e. an upper bound.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::display::Pricing;
+    /// use simple_agents_types::prelude::*;
+    /// # use async_trait::async_trait;
+    /// # struct Echo;
+    /// # #[async_trait]
+    /// # impl Provider for Echo {
+    /// #     fn name(&self) -> &str { "echo" }
+    /// #     fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+    /// #         Ok(ProviderRequest::new("https://api.example.com/v1/chat")
+    /// #             .with_header("Authorization", "Bearer sk-secret")
+    /// #             .with_body(serde_json::json!({ "model": req.model })))
+    /// #     }
+    /// #     async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> { unimplemented!() }
+    /// #     fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> { unimplemented!() }
+    /// #     fn pricing(&self, _model: &str) -> Option<Pricing> {
+    /// #         Some(Pricing { input_per_million: 2.5, output_per_million: 10.0 })
+    /// #     }
+    /// # }
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4o")
+    ///     .message(Message::user("Hello!"))
+    ///     .max_tokens(100)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let preview = Echo.explain(&request).unwrap();
+    /// assert_eq!(preview.headers_redacted[0].1, "[REDACTED]");
+    /// assert!(preview.estimated_cost_usd.unwrap() > 0.001);
+    /// ```
+    fn explain(&self, req: &CompletionRequest) -> Result<ProviderRequestPreview> {
+        let dry_run = self.dry_run(req)?;
+        let estimated_cost_usd = self.pricing(&req.model).map(|pricing| {
+            pricing.cost(&Usage::new(dry_run.estimated_prompt_tokens, req.max_tokens.unwrap_or(0)))
+        });
+        Ok(ProviderRequestPreview {
+            url: dry_run.url,
+            headers_redacted: dry_run.headers_redacted,
+            body_pretty: dry_run.body_pretty,
+            estimated_tokens: dry_run.estimated_prompt_tokens,
+            estimated_cost_usd,
+        })
+    }
+
+    /// Check whether this provider can handle the given model.
+    ///
+    /// Returns `true` for any model when [`Provider::supported_models`]
+    /// is empty, so providers without a static list stay usable. Otherwise
+    /// the list is taken as known models rather than every model, and
+    /// dated snapshots and tagged variants of them are accepted too. A
+    /// model [`Provider::model_info`] knows must be in the same family as
+    /// a listed one (`claude-3-5-sonnet-20250101` and
+    /// `claude-3-5-sonnet-latest`); any other must extend a listed ID with
+    /// `-`, `:` or `@`.
+    fn supports_model(&self, model: &str) -> bool {
+        let models = self.supported_models();
+        if models.is_empty() || models.contains(&model) {
+            return true;
+        }
+        match self.model_info(model) {
+            Some(info) => models
+                .iter()
+                .any(|listed| self.model_info(listed).is_some_and(|listed| listed.name == info.name)),
+            None => models.iter().any(|listed| {
+                model
+                    .strip_prefix(listed)
+                    .is_some_and(|rest| rest.starts_with(['-', ':', '@']))
+            }),
+        }
+    }
+
     /// Execute streaming request against provider API.
     ///
     /// This method returns a stream of completion chunks for streaming responses.
@@ -144,12 +473,329 @@ pub trait Provider: Send + Sync {
         &self,
         _req: ProviderRequest,
     ) -> Result<Box<dyn futures_core::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
-        Err(SimpleAgentsError::Provider(
-            ProviderError::UnsupportedFeature("streaming".to_string())
+        Err(SimpleAgentsError::Provider(ProviderError::Unsupported {
+            provider: self.name().to_string(),
+            feature: Feature::Streaming,
+        }))
+    }
+
+    /// Warm the server's KV cache with a shared system prompt.
+    ///
+    /// Attach the returned token to later requests with
+    /// [`CompletionRequest::with_prefill_token`] so they reuse the cached
+    /// prefix. The default does nothing and returns
+    /// [`PrefillToken::noop`]; self-hosted providers with prefix caching
+    /// override it.
+    async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+        let _ = system_prompt;
+        Ok(PrefillToken::noop())
+    }
+}
+
+/// Extension methods for every [`Provider`].
+///
+/// [`Provider`] must stay object-safe so it can be used as
+/// `Box<dyn Provider>` or `Arc<dyn Provider>`. Methods with generic
+/// parameters belong here instead; the blanket implementation makes them
+/// available on concrete providers and trait objects alike.
+///
+/// # Example
+/// ```
+/// use serde::Deserialize;
+/// use simple_agents_types::prelude::*;
+///
+/// #[derive(Deserialize)]
+/// struct Forecast {
+///     city: String,
+///     celsius: i32,
+/// }
+///
+/// # async fn example(provider: Box<dyn Provider>) -> Result<()> {
+/// let request = CompletionRequest::builder()
+///     .model("gpt-4o-mini")
+///     .message(Message::user("Weather in Oslo as JSON with city and celsius"))
+///     .build()?;
+///
+/// let forecast: Forecast = provider.complete_json_request(&request).await?;
+/// println!("{}: {}°C", forecast.city, forecast.celsius);
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait ProviderExt: Provider {
+    /// Complete `req` and deserialize the first choice's content as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::Refusal`] if the model declined to answer,
+    /// [`ProviderError::InvalidResponse`] if the response has no content,
+    /// and [`SimpleAgentsError::Serialization`] if the content is not valid
+    /// JSON for `T`, in addition to any error from [`Provider::complete`].
+    async fn complete_json_request<T>(&self, req: &CompletionRequest) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let content = answer(self.complete(req).await?)?.content;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Send `user_message` to `model` and return the reply text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::Refusal`] if the model declined to answer
+    /// and [`ProviderError::InvalidResponse`] if the response has no
+    /// choices, in addition to any error from building the request or from
+    /// [`Provider::complete`].
+    async fn complete_text(&self, model: &str, user_message: &str) -> Result<String> {
+        let req = CompletionRequest::builder()
+            .model(model)
+            .message(Message::user(user_message))
+            .build()?;
+        Ok(answer(self.complete(&req).await?)?.content)
+    }
+
+    /// Send `user_message` to `model`, requiring a reply that matches the
+    /// JSON Schema `schema`, and deserialize it.
+    ///
+    /// A system message asking for JSON is sent before `user_message`, as
+    /// JSON output requires one that mentions it (see
+    /// [`CompletionRequest::validate_combinations`]).
+    ///
+    /// For a request built by hand, use
+    /// [`complete_json_request`](Self::complete_json_request).
+    ///
+    /// # Errors
+    ///
+    /// As [`complete_json_request`](Self::complete_json_request), plus any
+    /// error from building the request.
+    async fn complete_json<T>(
+        &self,
+        model: &str,
+        user_message: &str,
+        schema: serde_json::Value,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let req = CompletionRequest::builder()
+            .model(model)
+            .message(Message::system("Reply with JSON matching the response schema."))
+            .message(Message::user(user_message))
+            .response_format(ResponseFormat::JsonSchema {
+                json_schema: JsonSchemaFormat {
+                    name: "response".to_string(),
+                    schema,
+                    strict: Some(true),
+                },
+            })
+            .build()?;
+        self.complete_json_request(&req).await
+    }
+
+    /// Complete `req`, running the tools the model calls with `registry`
+    /// until it answers without calling any.
+    ///
+    /// The registry's tools are offered unless `req` already sets
+    /// [`tools`](CompletionRequest::tools). Each round appends the
+    /// assistant's tool calls and one [`Message::tool`] result per call; a
+    /// failing or unknown tool sends `Error: ...` as its result so the
+    /// model can recover.
+    ///
+    /// Returns the final text and every tool call made, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::InvalidResponse`] if the model is still
+    /// calling tools after [`ToolRegistry::max_rounds`] completions, as
+    /// well as the errors of [`complete_text`](Self::complete_text).
+    async fn complete_with_tools(
+        &self,
+        req: &CompletionRequest,
+        registry: &ToolRegistry,
+    ) -> Result<(String, Vec<ToolCall>)> {
+        let mut req = req.clone();
+        if req.tools.is_none() {
+            req.tools = Some(registry.definitions());
+        }
+
+        let mut calls = Vec::new();
+        for _ in 0..registry.max_rounds() {
+            let message = answer(self.complete(&req).await?)?;
+            if message.tool_calls.is_empty() {
+                return Ok((message.content, calls));
+            }
+            req.messages.push(message.clone());
+            for call in message.tool_calls {
+                let result = registry.call(&call).await.unwrap_or_else(|e| format!("Error: {}", e));
+                req.messages.push(Message::tool(result, call.id.clone()));
+                calls.push(call);
+            }
+        }
+        Err(ProviderError::InvalidResponse(format!(
+            "model was still calling tools after {} rounds",
+            registry.max_rounds()
         ))
+        .into())
+    }
+}
+
+/// The first choice's message, or an error if the model refused or
+/// returned no choices.
+fn answer(response: CompletionResponse) -> Result<Message> {
+    let id = response.id;
+    let choice = response.choices.into_iter().next().ok_or_else(|| {
+        ProviderError::InvalidResponse(format!("response {} has no content", id))
+    })?;
+    if choice.is_refusal() {
+        return Err(ProviderError::Refusal(choice.message.refusal.unwrap_or_default()).into());
     }
+    Ok(choice.message)
+}
+
+impl<P: Provider + ?Sized> ProviderExt for P {}
+
+/// Run the default [`Provider::complete`] with `fetch` in place of
+/// `execute` followed by `transform_response`.
+///
+/// For providers that override `complete` to parse response bodies
+/// straight into their own types: the streaming check, response
+/// post-processing and error context stay the same as the default's.
+pub async fn complete_with<P, F, Fut>(
+    provider: &P,
+    req: &CompletionRequest,
+    fetch: F,
+) -> Result<CompletionResponse>
+where
+    P: Provider + ?Sized,
+    F: FnOnce(ProviderRequest) -> Fut,
+    Fut: std::future::Future<Output = Result<CompletionResponse>>,
+{
+    let result = async {
+        req.ensure_not_streaming()?;
+        let mut response = fetch(provider.transform_request(req)?).await?;
+        if response.provider.is_none() {
+            response.provider = Some(provider.name().to_string());
+        }
+        if req.trim_stop_sequences {
+            response.trim_stop_sequences(req.stop.as_deref().unwrap_or_default());
+        }
+        if let Some(prefill) = req.assistant_prefill.as_ref().filter(|p| p.prepend_to_response) {
+            response.prepend_prefill(&prefill.text);
+        }
+        Ok(response)
+    }
+    .await;
+
+    result.map_err(|error| match error {
+        SimpleAgentsError::WithContext(_) => error,
+        error => SimpleAgentsError::WithContext(Box::new(error.with_context(
+            ErrorContext::new()
+                .provider(provider.name())
+                .model(req.model.clone())
+                .attempt(1),
+        ))),
+    })
+}
+
+/// Implement [`Provider`] for a smart pointer by delegating every method,
+/// provided ones included, to the pointee, so its overrides are kept
+macro_rules! delegate_provider {
+    ($($pointer:ident),*) => {$(
+        #[async_trait]
+        impl<T: Provider + ?Sized> Provider for $pointer<T> {
+            fn name(&self) -> &str {
+                (**self).name()
+            }
+
+            fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+                (**self).transform_request(req)
+            }
+
+            async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+                (**self).execute(req).await
+            }
+
+            fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+                (**self).transform_response(resp)
+            }
+
+            async fn complete(&self, req: &CompletionRequest) -> Result<CompletionResponse> {
+                (**self).complete(req).await
+            }
+
+            async fn execute_with_retries(
+                &self,
+                req: &CompletionRequest,
+                policy: &RetryPolicy,
+            ) -> Result<CompletionResponse> {
+                (**self).execute_with_retries(req, policy).await
+            }
+
+            async fn execute_with_attempts(
+                &self,
+                req: &CompletionRequest,
+                policy: &RetryPolicy,
+            ) -> Result<CompleteOutcome> {
+                (**self).execute_with_attempts(req, policy).await
+            }
+
+            fn retry_config(&self) -> RetryConfig {
+                (**self).retry_config()
+            }
+
+            fn capabilities(&self) -> Capabilities {
+                (**self).capabilities()
+            }
+
+            fn timeout(&self) -> Duration {
+                (**self).timeout()
+            }
+
+            fn supported_models(&self) -> &'static [&'static str] {
+                (**self).supported_models()
+            }
+
+            fn sensitive_headers(&self) -> Vec<String> {
+                (**self).sensitive_headers()
+            }
+
+            fn dry_run(&self, req: &CompletionRequest) -> Result<DryRun> {
+                (**self).dry_run(req)
+            }
+
+            fn model_info(&self, model: &str) -> Option<ModelInfoStatic> {
+                (**self).model_info(model)
+            }
+
+            fn pricing(&self, model: &str) -> Option<Pricing> {
+                (**self).pricing(model)
+            }
+
+            fn explain(&self, req: &CompletionRequest) -> Result<ProviderRequestPreview> {
+                (**self).explain(req)
+            }
+
+            fn supports_model(&self, model: &str) -> bool {
+                (**self).supports_model(model)
+            }
+
+            async fn execute_stream(
+                &self,
+                req: ProviderRequest,
+            ) -> Result<Box<dyn futures_core::Stream<Item = Result<CompletionChunk>> + Send + Unpin>> {
+                (**self).execute_stream(req).await
+            }
+
+            async fn prefill_cache(&self, system_prompt: &str) -> Result<PrefillToken> {
+                (**self).prefill_cache(system_prompt).await
+            }
+        }
+    )*};
 }
 
+delegate_provider!(Arc, Box);
+
 /// Opaque provider-specific request.
 ///
 /// This type encapsulates all information needed to make an HTTP request
@@ -168,6 +814,9 @@ pub struct ProviderRequest {
     /// Optional request timeout override
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<Duration>,
+    /// Values copied from [`CompletionRequest::extensions`]; never sent
+    #[serde(skip)]
+    pub extensions: Extensions,
 }
 
 // Custom serde for Cow headers
@@ -175,8 +824,10 @@ mod header_serde {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use std::borrow::Cow;
 
+    type Header = (Cow<'static, str>, Cow<'static, str>);
+
     pub fn serialize<S>(
-        headers: &[(Cow<'static, str>, Cow<'static, str>)],
+        headers: &[Header],
         serializer: S,
     ) -> Result<S::Ok, S::Error>
     where
@@ -191,7 +842,7 @@ mod header_serde {
 
     pub fn deserialize<'de, D>(
         deserializer: D,
-    ) -> Result<Vec<(Cow<'static, str>, Cow<'static, str>)>, D::Error>
+    ) -> Result<Vec<Header>, D::Error>
     where
         D: Deserializer<'de>,
     {
@@ -211,6 +862,7 @@ impl ProviderRequest {
             headers: Vec::new(),
             body: serde_json::Value::Null,
             timeout: None,
+            extensions: Extensions::new(),
         }
     }
 
@@ -237,6 +889,144 @@ impl ProviderRequest {
         self.timeout = Some(timeout);
         self
     }
+
+    /// Set the extensions, normally the completion request's.
+    pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Headers with the values of [`headers::SENSITIVE`] and `sensitive`
+    /// headers replaced by [`REDACTED`].
+    ///
+    /// Header names are compared case-insensitively.
+    pub fn redacted_headers(&self, sensitive: &[String]) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                let redact = headers::SENSITIVE.iter().any(|s| name.eq_ignore_ascii_case(s))
+                    || sensitive.iter().any(|s| name.eq_ignore_ascii_case(s));
+                let value = if redact { REDACTED } else { value.as_ref() };
+                (name.to_string(), value.to_string())
+            })
+            .collect()
+    }
+
+    /// Stable hash of what this request sends, for use as a cache key.
+    ///
+    /// Covers the URL, body and headers, leaving out
+    /// [`headers::SENSITIVE`] headers so rotating credentials does not
+    /// change the fingerprint. The timeout is not included. Use
+    /// [`fingerprint_excluding`](Self::fingerprint_excluding) to leave out
+    /// a provider's [`sensitive_headers`](Provider::sensitive_headers) as
+    /// well.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::provider::ProviderRequest;
+    ///
+    /// let request = ProviderRequest::new("https://api.example.com/v1/chat/completions")
+    ///     .with_body(serde_json::json!({"model": "gpt-4"}));
+    /// let rotated = request.clone().with_header("Authorization", "Bearer sk-new");
+    /// let original = request.with_header("Authorization", "Bearer sk-old");
+    ///
+    /// assert_eq!(original.fingerprint(), rotated.fingerprint());
+    /// assert_eq!(original.fingerprint().len(), 64);
+    /// ```
+    pub fn fingerprint(&self) -> String {
+        self.fingerprint_excluding(&[])
+    }
+
+    /// [`fingerprint`](Self::fingerprint), also leaving out the `sensitive`
+    /// headers (compared case-insensitively).
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::provider::ProviderRequest;
+    ///
+    /// let request = ProviderRequest::new("https://example.com/v1beta/models/gemini:generateContent");
+    /// let sensitive = vec!["x-goog-api-key".to_string()];
+    /// let rotated = request.clone().with_header("x-goog-api-key", "new-key");
+    ///
+    /// assert_eq!(rotated.fingerprint_excluding(&sensitive), request.fingerprint_excluding(&sensitive));
+    /// assert_ne!(rotated.fingerprint(), request.fingerprint());
+    /// ```
+    pub fn fingerprint_excluding(&self, sensitive: &[String]) -> String {
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .filter(|(name, _)| {
+                !headers::SENSITIVE.iter().any(|s| name.eq_ignore_ascii_case(s))
+                    && !sensitive.iter().any(|s| name.eq_ignore_ascii_case(s))
+            })
+            .map(|(name, value)| (name.as_ref(), value.as_ref()))
+            .collect();
+        let canonical = serde_json::json!({
+            "url": self.url,
+            "headers": headers,
+            "body": self.body,
+        });
+        // Serializing a JSON value cannot fail.
+        let canonical = serde_json::to_vec(&canonical).unwrap_or_default();
+        blake3::hash(&canonical).to_hex().to_string()
+    }
+}
+
+/// Result of [`Provider::dry_run`]: what a request would send, with secrets
+/// redacted.
+///
+/// `Display` renders it as a readable request dump.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRun {
+    /// URL the request would be sent to
+    pub url: String,
+    /// Headers, with sensitive values replaced by [`REDACTED`]
+    pub headers_redacted: Vec<(String, String)>,
+    /// Pretty-printed JSON body
+    pub body_pretty: String,
+    /// Estimated prompt tokens (see [`CompletionRequest::token_count_total`])
+    pub estimated_prompt_tokens: u32,
+}
+
+impl DryRun {
+    /// Describe `req`, redacting [`headers::SENSITIVE`] and `sensitive` headers.
+    pub fn new(req: &ProviderRequest, sensitive: &[String], estimated_prompt_tokens: u32) -> Self {
+        Self {
+            url: req.url.clone(),
+            headers_redacted: req.redacted_headers(sensitive),
+            body_pretty: serde_json::to_string_pretty(&req.body).unwrap_or_default(),
+            estimated_prompt_tokens,
+        }
+    }
+}
+
+impl std::fmt::Display for DryRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "POST {}", self.url)?;
+        for (name, value) in &self.headers_redacted {
+            writeln!(f, "{}: {}", name, value)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}", self.body_pretty)?;
+        write!(f, "(~{} prompt tokens)", self.estimated_prompt_tokens)
+    }
+}
+
+/// Result of [`Provider::explain`]: what a request would send and roughly
+/// what it would cost.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderRequestPreview {
+    /// URL the request would be sent to
+    pub url: String,
+    /// Headers, with sensitive values replaced by [`REDACTED`]
+    pub headers_redacted: Vec<(String, String)>,
+    /// Pretty-printed JSON body
+    pub body_pretty: String,
+    /// Estimated prompt tokens (see [`CompletionRequest::token_count_total`])
+    pub estimated_tokens: u32,
+    /// Upper-bound cost in US dollars, if the provider knows the model's
+    /// prices
+    pub estimated_cost_usd: Option<f64>,
 }
 
 /// Opaque provider-specific response.
@@ -278,6 +1068,48 @@ impl ProviderResponse {
         (500..600).contains(&self.status)
     }
 
+    /// Check if the provider rejected the request for exceeding a rate
+    /// limit (429).
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+    }
+
+    /// Value of the header `name` (case-insensitive), if present.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .as_ref()?
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Time until the provider's rate limit resets, if reported.
+    ///
+    /// Uses `Retry-After` (delay-seconds or HTTP-date) when present, and
+    /// otherwise the later of OpenAI/Groq's `x-ratelimit-reset-requests`
+    /// and `x-ratelimit-reset-tokens`, since it is not known which limit
+    /// was hit.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::provider::ProviderResponse;
+    /// use std::time::Duration;
+    ///
+    /// let response = ProviderResponse::new(429, serde_json::Value::Null)
+    ///     .with_headers(vec![("x-ratelimit-reset-requests".to_string(), "1m30s".to_string())]);
+    /// assert!(response.is_rate_limited());
+    /// assert_eq!(response.rate_limit_reset(), Some(Duration::from_secs(90)));
+    /// ```
+    pub fn rate_limit_reset(&self) -> Option<Duration> {
+        if let Some(delay) = self.header("retry-after").and_then(rate_limit::parse_retry_after) {
+            return Some(delay);
+        }
+        ["x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"]
+            .into_iter()
+            .filter_map(|name| self.header(name).and_then(rate_limit::parse_reset_duration))
+            .max()
+    }
+
     /// Add headers.
     pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
         self.headers = Some(headers);
@@ -288,6 +1120,7 @@ impl ProviderResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorCode;
 
     #[test]
     fn test_provider_request_builder() {
@@ -303,6 +1136,36 @@ mod tests {
         assert_eq!(req.timeout, Some(Duration::from_secs(30)));
     }
 
+    #[test]
+    fn test_redacted_headers() {
+        let req = ProviderRequest::new("https://api.example.com")
+            .with_header("authorization", "Bearer sk-test")
+            .with_header("X-Api-Key", "sk-ant-test")
+            .with_header("api-key", "azure-key")
+            .with_header("X-Tenant-Token", "tenant-secret")
+            .with_header("Content-Type", "application/json");
+
+        let headers = req.redacted_headers(&["x-tenant-token".to_string()]);
+        let values: Vec<&str> = headers.iter().map(|(_, v)| v.as_str()).collect();
+        assert_eq!(values, vec![REDACTED, REDACTED, REDACTED, REDACTED, "application/json"]);
+        // Names are kept as sent
+        assert_eq!(headers[1].0, "X-Api-Key");
+    }
+
+    #[test]
+    fn test_dry_run_display() {
+        let req = ProviderRequest::new("https://api.example.com/v1/chat")
+            .with_header("Authorization", "Bearer sk-test")
+            .with_body(serde_json::json!({"model": "test"}));
+
+        let dry_run = DryRun::new(&req, &[], 12);
+        assert_eq!(
+            dry_run.to_string(),
+            "POST https://api.example.com/v1/chat\nAuthorization: [REDACTED]\n\n{\n  \"model\": \"test\"\n}\n(~12 prompt tokens)"
+        );
+        assert!(!format!("{:?}", dry_run).contains("sk-test"));
+    }
+
     #[test]
     fn test_provider_response_status_checks() {
         let resp = ProviderResponse::new(200, serde_json::json!({}));
@@ -332,6 +1195,34 @@ mod tests {
         assert_eq!(req, parsed);
     }
 
+    #[test]
+    fn test_provider_request_fingerprint() {
+        let request = ProviderRequest::new("https://api.example.com")
+            .with_static_header("anthropic-beta", "pdfs-2024-09-25")
+            .with_body(serde_json::json!({"model": "claude", "max_tokens": 10}));
+        let fingerprint = request.fingerprint();
+
+        assert_eq!(request.clone().with_timeout(Duration::from_secs(5)).fingerprint(), fingerprint);
+        assert_eq!(request.clone().with_header("x-api-key", "sk-ant-2").fingerprint(), {
+            request.clone().with_header("x-api-key", "sk-ant-1").fingerprint()
+        });
+        let body = serde_json::json!({"model": "claude"});
+        assert_ne!(request.clone().with_body(body).fingerprint(), fingerprint);
+        assert_ne!(request.clone().with_header("anthropic-beta", "other").fingerprint(), fingerprint);
+        // Credentials are left out entirely, not just redacted
+        assert_eq!(request.clone().with_header("Authorization", "Bearer sk-1").fingerprint(), fingerprint);
+        let sensitive = vec!["X-Goog-Api-Key".to_string()];
+        let keyed = request.clone().with_header("x-goog-api-key", "key-1");
+        assert_ne!(keyed.fingerprint(), fingerprint);
+        assert_eq!(keyed.fingerprint_excluding(&sensitive), request.fingerprint_excluding(&sensitive));
+
+        let moved = ProviderRequest {
+            url: "https://other.example.com".into(),
+            ..request
+        };
+        assert_ne!(moved.fingerprint(), fingerprint);
+    }
+
     #[test]
     fn test_provider_response_serialization() {
         let resp = ProviderResponse::new(200, serde_json::json!({"result": "success"}))
@@ -342,9 +1233,533 @@ mod tests {
         assert_eq!(resp, parsed);
     }
 
+    #[test]
+    fn test_provider_response_rate_limit() {
+        let response = |status, headers: &[(&str, &str)]| {
+            ProviderResponse::new(status, serde_json::Value::Null).with_headers(
+                headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            )
+        };
+
+        let limited = response(429, &[("Retry-After", "20"), ("x-ratelimit-reset-requests", "1m")]);
+        assert!(limited.is_rate_limited());
+        assert!(limited.is_client_error());
+        // Retry-After wins over the provider-specific headers
+        assert_eq!(limited.rate_limit_reset(), Some(Duration::from_secs(20)));
+
+        let openai = response(
+            429,
+            &[("x-ratelimit-reset-requests", "120ms"), ("x-ratelimit-reset-tokens", "6m0s")],
+        );
+        assert_eq!(openai.rate_limit_reset(), Some(Duration::from_secs(360)));
+
+        let groq = response(429, &[("X-RateLimit-Reset-Requests", "2m59.56s")]);
+        assert_eq!(groq.rate_limit_reset(), Some(Duration::from_millis(179_560)));
+
+        let unparseable = response(429, &[("retry-after", "later"), ("x-ratelimit-reset-tokens", "soon")]);
+        assert_eq!(unparseable.rate_limit_reset(), None);
+        assert_eq!(ProviderResponse::new(429, serde_json::Value::Null).rate_limit_reset(), None);
+
+        let ok = response(200, &[("x-ratelimit-reset-requests", "1s")]);
+        assert!(!ok.is_rate_limited());
+        assert_eq!(ok.header("X-RATELIMIT-RESET-REQUESTS"), Some("1s"));
+    }
+
     // Test that Provider trait is object-safe
     #[test]
     fn test_provider_object_safety() {
         fn _assert_object_safe(_: &dyn Provider) {}
     }
+
+    struct StaticModelsProvider {
+        models: &'static [&'static str],
+    }
+
+    #[async_trait]
+    impl Provider for StaticModelsProvider {
+        fn name(&self) -> &str {
+            "static"
+        }
+
+        fn transform_request(&self, _req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com"))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            Ok(ProviderResponse::new(200, serde_json::json!({})))
+        }
+
+        fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+            Err(ProviderError::InvalidResponse("not implemented".to_string()).into())
+        }
+
+        fn supported_models(&self) -> &'static [&'static str] {
+            self.models
+        }
+    }
+
+    #[test]
+    fn test_supports_model_empty_list_accepts_all() {
+        let provider = StaticModelsProvider { models: &[] };
+        assert!(provider.supported_models().is_empty());
+        assert!(provider.supports_model("anything"));
+    }
+
+    #[test]
+    fn test_supports_model_static_list() {
+        let provider = StaticModelsProvider {
+            models: &["model-a", "model-b"],
+        };
+        assert!(provider.supports_model("model-a"));
+        assert!(provider.supports_model("model-b"));
+        assert!(!provider.supports_model("model-c"));
+        // Dated snapshots and tags of a listed model
+        assert!(provider.supports_model("model-a-2024-08-06"));
+        assert!(provider.supports_model("model-b:latest"));
+        assert!(!provider.supports_model("model-ab"));
+    }
+
+    #[test]
+    fn test_supports_model_matches_catalog_family() {
+        let provider = StaticModelsProvider {
+            models: &["gpt-4o", "claude-3-5-sonnet-latest"],
+        };
+        assert!(provider.supports_model("claude-3-5-sonnet-20250101"));
+        assert!(provider.supports_model("gpt-4o-2024-08-06"));
+        // Known to the catalog, but not in a listed family
+        assert!(!provider.supports_model("claude-3-opus-20240229"));
+        assert!(!provider.supports_model("gpt-4o-mini"));
+    }
+
+    #[test]
+    fn test_request_validate_model() {
+        let provider = StaticModelsProvider {
+            models: &["model-a"],
+        };
+
+        let request = CompletionRequest::builder()
+            .model("model-a")
+            .message(crate::message::Message::user("Hello"))
+            .build()
+            .unwrap();
+        assert!(request.validate_model(&provider).is_ok());
+
+        let request = CompletionRequest::builder()
+            .model("model-z")
+            .message(crate::message::Message::user("Hello"))
+            .build()
+            .unwrap();
+        let err = request.validate_model(&provider).unwrap_err();
+        assert!(matches!(
+            err,
+            SimpleAgentsError::Validation(crate::error::ValidationError::InvalidFormat { .. })
+        ));
+        assert!(err.to_string().contains("model-z"));
+    }
+
+    #[tokio::test]
+    async fn test_complete_wraps_errors_with_context() {
+        let provider = StaticModelsProvider { models: &[] };
+        let request = CompletionRequest::builder()
+            .model("model-a")
+            .message(crate::message::Message::user("Hello"))
+            .build()
+            .unwrap();
+
+        let err = provider.complete(&request).await.unwrap_err();
+        let ctx = err.context().expect("complete should attach context");
+        assert_eq!(ctx.provider.as_deref(), Some("static"));
+        assert_eq!(ctx.model.as_deref(), Some("model-a"));
+        assert_eq!(ctx.attempt, 1);
+        assert!(matches!(
+            err.root(),
+            SimpleAgentsError::Provider(ProviderError::InvalidResponse(_))
+        ));
+        assert_eq!(
+            err.to_string(),
+            "static invalid_response: not implemented [model=model-a, attempt=1]"
+        );
+    }
+
+    struct ContextPassthroughProvider;
+
+    #[async_trait]
+    impl Provider for ContextPassthroughProvider {
+        fn name(&self) -> &str {
+            "outer"
+        }
+
+        fn transform_request(&self, _req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com"))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            let inner = StaticModelsProvider { models: &[] };
+            let request = CompletionRequest::builder()
+                .model("inner-model")
+                .message(crate::message::Message::user("Hello"))
+                .build()?;
+            inner.complete(&request).await?;
+            unreachable!("inner provider always fails")
+        }
+
+        fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(serde_json::from_value(resp.body)?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_keeps_innermost_context() {
+        let request = CompletionRequest::builder()
+            .model("outer-model")
+            .message(crate::message::Message::user("Hello"))
+            .build()
+            .unwrap();
+
+        let err = ContextPassthroughProvider.complete(&request).await.unwrap_err();
+        let ctx = err.context().unwrap();
+        assert_eq!(ctx.provider.as_deref(), Some("static"));
+        assert_eq!(ctx.model.as_deref(), Some("inner-model"));
+        assert!(!matches!(ctx.error, SimpleAgentsError::WithContext(_)));
+    }
+
+    #[tokio::test]
+    async fn test_complete_rejects_streaming_requests() {
+        let provider = StaticModelsProvider { models: &[] };
+        let request = CompletionRequest::builder()
+            .model("model-a")
+            .message(crate::message::Message::user("Hello"))
+            .stream(true)
+            .build()
+            .unwrap();
+
+        let err = provider.complete(&request).await.unwrap_err();
+        assert!(matches!(
+            err.root(),
+            SimpleAgentsError::Validation(crate::error::ValidationError::InvalidFormat { field, .. })
+                if field == "stream"
+        ));
+        assert!(err.to_string().contains("execute_stream"));
+    }
+
+    struct PricedProvider;
+
+    #[async_trait]
+    impl Provider for PricedProvider {
+        fn name(&self) -> &str {
+            "priced"
+        }
+
+        fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com/v1/chat")
+                .with_header("Authorization", "Bearer sk-secret")
+                .with_header("X-Tenant-Token", "tenant-secret")
+                .with_header("Content-Type", "application/json")
+                .with_body(serde_json::json!({ "model": req.model, "messages": req.messages })))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            Ok(ProviderResponse::new(200, serde_json::json!({})))
+        }
+
+        fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+            Err(ProviderError::InvalidResponse("not implemented".to_string()).into())
+        }
+
+        fn sensitive_headers(&self) -> Vec<String> {
+            vec!["x-tenant-token".to_string()]
+        }
+
+        fn pricing(&self, model: &str) -> Option<Pricing> {
+            (model == "priced-model").then_some(Pricing {
+                input_per_million: 1.0,
+                output_per_million: 4.0,
+            })
+        }
+    }
+
+    #[test]
+    fn test_explain_redacts_and_pretty_prints() {
+        let request = CompletionRequest::builder()
+            .model("priced-model")
+            .message(crate::message::Message::user("Hello!"))
+            .max_tokens(1000)
+            .build()
+            .unwrap();
+
+        let preview = PricedProvider.explain(&request).unwrap();
+        assert_eq!(preview.url, "https://api.example.com/v1/chat");
+        let values: Vec<&str> = preview.headers_redacted.iter().map(|(_, v)| v.as_str()).collect();
+        assert_eq!(values, vec![REDACTED, REDACTED, "application/json"]);
+        assert!(!format!("{:?}", preview).contains("secret"));
+
+        assert!(preview.body_pretty.contains('\n'));
+        let body: serde_json::Value = serde_json::from_str(&preview.body_pretty).unwrap();
+        assert_eq!(body["model"], "priced-model");
+        assert_eq!(body["messages"][0]["content"], "Hello!");
+
+        assert_eq!(preview.estimated_tokens, request.token_count_total());
+        let expected = (f64::from(preview.estimated_tokens) + 4.0 * 1000.0) / 1_000_000.0;
+        assert!((preview.estimated_cost_usd.unwrap() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_explain_without_pricing() {
+        let request = CompletionRequest::builder()
+            .model("unknown-model")
+            .message(crate::message::Message::user("Hello!"))
+            .build()
+            .unwrap();
+
+        let preview = PricedProvider.explain(&request).unwrap();
+        assert_eq!(preview.estimated_cost_usd, None);
+        let dry_run = PricedProvider.dry_run(&request).unwrap();
+        assert_eq!(preview.body_pretty, dry_run.body_pretty);
+    }
+
+    /// Fails with `error` (a network error when `None`) until `failures`
+    /// calls have been made, then succeeds
+    struct MockProvider {
+        error: Option<ProviderError>,
+        failures: u32,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    impl MockProvider {
+        fn new(error: ProviderError, failures: u32) -> Self {
+            Self {
+                error: Some(error),
+                failures,
+                calls: Default::default(),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn transform_request(&self, _req: &CompletionRequest) -> Result<ProviderRequest> {
+            Ok(ProviderRequest::new("https://api.example.com"))
+        }
+
+        async fn execute(&self, _req: ProviderRequest) -> Result<ProviderResponse> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if call < self.failures {
+                return Err(match &self.error {
+                    Some(error) => error.clone().into(),
+                    None => SimpleAgentsError::Network("connection reset".to_string()),
+                });
+            }
+            Ok(ProviderResponse::new(200, serde_json::json!({})))
+        }
+
+        fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+            Ok(CompletionResponse {
+                id: "resp-1".to_string(),
+                model: "mock-model".to_string(),
+                choices: Vec::new(),
+                usage: Usage::new(1, 1),
+                created: None,
+                created_synthesized: false,
+                provider: None,
+            })
+        }
+    }
+
+    /// Policy without jitter that records its delays instead of sleeping
+    fn recording_policy(max_attempts: u32) -> (RetryPolicy, std::sync::Arc<std::sync::Mutex<Vec<Duration>>>) {
+        let config = RetryConfig {
+            max_attempts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            backoff_multiplier: 2.0,
+            jitter: false,
+        };
+        let delays = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = delays.clone();
+        let policy = RetryPolicy::new(config, move |delay: Duration| {
+            recorded.lock().unwrap().push(delay);
+            async {}
+        });
+        (policy, delays)
+    }
+
+    fn mock_request() -> CompletionRequest {
+        CompletionRequest::builder()
+            .model("mock-model")
+            .message(crate::message::Message::user("Hello"))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_complete_fills_in_provider_name() {
+        // MockProvider leaves `provider` unset in transform_response
+        let response = MockProvider::new(ProviderError::ServerError("503".to_string()), 0)
+            .complete(&mock_request())
+            .await
+            .unwrap();
+        assert_eq!(response.provider.as_deref(), Some("mock"));
+
+        // A name set by transform_response is kept
+        struct NamedProvider(MockProvider);
+
+        #[async_trait]
+        impl Provider for NamedProvider {
+            fn name(&self) -> &str {
+                "wrapper"
+            }
+
+            fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+                self.0.transform_request(req)
+            }
+
+            async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+                self.0.execute(req).await
+            }
+
+            fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+                Ok(self.0.transform_response(resp)?.with_provider("upstream"))
+            }
+        }
+
+        let named = NamedProvider(MockProvider::new(ProviderError::ServerError("503".to_string()), 0));
+        let response = named.complete(&mock_request()).await.unwrap();
+        assert_eq!(response.provider.as_deref(), Some("upstream"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_retries_recovers() {
+        let provider = MockProvider::new(ProviderError::ServerError("503".to_string()), 2);
+        let (policy, delays) = recording_policy(3);
+
+        let response = provider.execute_with_retries(&mock_request(), &policy).await.unwrap();
+        assert_eq!(response.id, "resp-1");
+        assert_eq!(provider.calls(), 3);
+        assert_eq!(*delays.lock().unwrap(), vec![Duration::from_millis(100), Duration::from_millis(200)]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_retries_reports_attempts_and_delay() {
+        let error = ProviderError::RateLimit {
+            retry_after: Some(Duration::from_secs(1)),
+        };
+        let provider = MockProvider::new(error, 5);
+        let (policy, delays) = recording_policy(3);
+
+        let err = provider.execute_with_retries(&mock_request(), &policy).await.unwrap_err();
+        assert_eq!(provider.calls(), 3);
+        // The retry-after hint outweighs the backoff
+        assert_eq!(*delays.lock().unwrap(), vec![Duration::from_secs(1), Duration::from_secs(1)]);
+
+        let ctx = err.context().unwrap();
+        assert_eq!(ctx.attempt, 3);
+        assert_eq!(ctx.total_delay, Duration::from_secs(2));
+        assert!(matches!(ctx.error, SimpleAgentsError::Provider(ProviderError::RateLimit { .. })));
+        assert!(err.to_string().ends_with("[model=mock-model, attempt=3, delay=2s]"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_attempts_records_history() {
+        let provider = MockProvider::new(ProviderError::ServerError("503".to_string()), 2);
+        let (policy, _) = recording_policy(3);
+
+        let outcome = provider.execute_with_attempts(&mock_request(), &policy).await.unwrap();
+        assert_eq!(outcome.response.id, "resp-1");
+        let attempts = outcome.attempts.attempts();
+        assert_eq!(attempts.iter().map(|a| a.attempt).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(
+            attempts.iter().map(|a| a.error_code).collect::<Vec<_>>(),
+            vec![Some(ErrorCode::ServerError), Some(ErrorCode::ServerError), None]
+        );
+        assert_eq!(
+            attempts.iter().map(|a| a.backoff).collect::<Vec<_>>(),
+            vec![Duration::from_millis(100), Duration::from_millis(200), Duration::ZERO]
+        );
+        assert!(attempts.iter().all(|a| a.provider == "mock"));
+
+        // Failures carry the same history
+        let provider = MockProvider::new(ProviderError::Timeout(Duration::from_secs(30)), 5);
+        let err = provider.execute_with_attempts(&mock_request(), &policy).await.unwrap_err();
+        let history = &err.context().unwrap().attempts;
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.failures(), 3);
+        assert_eq!(history.total_backoff(), err.context().unwrap().total_delay);
+        let json = serde_json::to_value(history).unwrap();
+        assert_eq!(json[2]["error_code"], "TIMEOUT");
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_attempts_tags_each_attempt() {
+        struct TagRecorder {
+            inner: MockProvider,
+            seen: std::sync::Mutex<Vec<Option<u32>>>,
+        }
+
+        #[async_trait]
+        impl Provider for TagRecorder {
+            fn name(&self) -> &str {
+                "recorder"
+            }
+
+            fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+                self.seen.lock().unwrap().push(req.extensions.get_as(RETRY_ATTEMPT));
+                self.inner.transform_request(req)
+            }
+
+            async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+                self.inner.execute(req).await
+            }
+
+            fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+                self.inner.transform_response(resp)
+            }
+        }
+
+        let provider = TagRecorder {
+            inner: MockProvider::new(ProviderError::ServerError("503".to_string()), 2),
+            seen: Default::default(),
+        };
+        let (policy, _) = recording_policy(3);
+        let request = mock_request();
+
+        provider.execute_with_attempts(&request, &policy).await.unwrap();
+        assert_eq!(*provider.seen.lock().unwrap(), vec![Some(1), Some(2), Some(3)]);
+        // The caller's request is left as it was
+        assert!(request.extensions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_retries_retries_network_errors() {
+        let provider = MockProvider {
+            error: None,
+            failures: 1,
+            calls: Default::default(),
+        };
+        let (policy, delays) = recording_policy(3);
+
+        let response = provider.execute_with_retries(&mock_request(), &policy).await.unwrap();
+        assert_eq!(response.id, "resp-1");
+        assert_eq!(provider.calls(), 2);
+        assert_eq!(*delays.lock().unwrap(), vec![Duration::from_millis(100)]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_retries_skips_non_retryable() {
+        let provider = MockProvider::new(ProviderError::InvalidApiKey, 2);
+        let (policy, delays) = recording_policy(3);
+
+        let err = provider.execute_with_retries(&mock_request(), &policy).await.unwrap_err();
+        assert_eq!(provider.calls(), 1);
+        assert!(delays.lock().unwrap().is_empty());
+        assert_eq!(err.context().unwrap().attempt, 1);
+        assert_eq!(err.context().unwrap().total_delay, Duration::ZERO);
+    }
 }
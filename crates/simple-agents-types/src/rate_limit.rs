@@ -0,0 +1,145 @@
+//! Parsing of rate-limit response headers.
+//!
+//! Used by [`ProviderResponse::rate_limit_reset`](crate::provider::ProviderResponse::rate_limit_reset)
+//! and by providers that read the headers off the HTTP response directly.
+
+use std::time::{Duration, SystemTime};
+
+/// Parse a `Retry-After` header value into the delay it asks for.
+///
+/// Accepts delay-seconds (`120`) and HTTP-dates
+/// (`Wed, 21 Oct 2015 07:28:00 GMT`, plus the obsolete RFC 850 and asctime
+/// forms). A date in the past means no delay. Returns `None` if the value
+/// is neither.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::rate_limit::parse_retry_after;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+/// assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+/// assert_eq!(parse_retry_after("soon"), None);
+/// ```
+pub fn parse_retry_after(header_value: &str) -> Option<Duration> {
+    parse_retry_after_at(header_value, SystemTime::now())
+}
+
+/// [`parse_retry_after`] relative to `now`.
+fn parse_retry_after_at(header_value: &str, now: SystemTime) -> Option<Duration> {
+    let value = header_value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Parse an `x-ratelimit-reset-*` duration as sent by OpenAI and Groq,
+/// such as `"6m0s"`, `"2m59.56s"`, `"7.66s"` or `"120ms"`.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::rate_limit::parse_reset_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_reset_duration("2m59.56s"), Some(Duration::from_millis(179_560)));
+/// assert_eq!(parse_reset_duration("12"), None);
+/// ```
+pub fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut total = 0.0_f64;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if number_len == 0 {
+            return None;
+        }
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds_per_unit = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+
+        total += number * seconds_per_unit;
+    }
+
+    // Round to whole microseconds so "7.66s" is exactly 7660ms
+    Some(Duration::from_micros((total * 1e6).round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(parse_retry_after("60"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_retry_after("0"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after(" 30 "), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date() {
+        // Wed, 21 Oct 2015 07:28:00 GMT
+        let date = SystemTime::UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+        let before = |secs| date - Duration::from_secs(secs);
+
+        assert_eq!(
+            parse_retry_after_at("Wed, 21 Oct 2015 07:28:00 GMT", before(90)),
+            Some(Duration::from_secs(90))
+        );
+        // Obsolete formats servers may still send
+        assert_eq!(
+            parse_retry_after_at("Wednesday, 21-Oct-15 07:28:00 GMT", before(90)),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(parse_retry_after_at("Wed Oct 21 07:28:00 2015", before(90)), Some(Duration::from_secs(90)));
+        // Dates in the past mean retry now
+        assert_eq!(
+            parse_retry_after_at("Wed, 21 Oct 2015 07:28:00 GMT", date + Duration::from_secs(5)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_invalid() {
+        for value in [
+            "",
+            "invalid",
+            "-5",
+            "1.5",
+            "Wed, 32 Oct 2015 07:28:00 GMT",
+            "Wed, 21 Oct 2015 25:28:00 GMT",
+            "Wed, 21 Foo 2015 07:28:00 GMT",
+            "21 Oct 2015 07:28:00",
+        ] {
+            assert_eq!(parse_retry_after(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_parse_reset_duration() {
+        assert_eq!(parse_reset_duration("7.66s"), Some(Duration::from_millis(7660)));
+        assert_eq!(parse_reset_duration("2m59.56s"), Some(Duration::from_millis(179_560)));
+        assert_eq!(parse_reset_duration("120ms"), Some(Duration::from_millis(120)));
+        assert_eq!(parse_reset_duration("1h0m1s"), Some(Duration::from_secs(3601)));
+        assert_eq!(parse_reset_duration(""), None);
+        assert_eq!(parse_reset_duration("12"), None);
+        assert_eq!(parse_reset_duration("soon"), None);
+    }
+}
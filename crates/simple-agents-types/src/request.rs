@@ -2,15 +2,146 @@
 //!
 //! Provides OpenAI-compatible request structures with validation.
 
-use crate::error::{Result, ValidationError};
-use crate::message::Message;
+use crate::config::Feature;
+use crate::error::{ProviderError, Result, ValidationError};
+use crate::extensions::Extensions;
+use crate::message::{Message, MessageList, Role};
+use crate::provider::Provider;
+use crate::tool::{ToolChoice, ToolDefinition};
 use serde::{Deserialize, Serialize};
 
+/// Output format the model must produce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Free-form text (the default)
+    Text,
+    /// Any valid JSON object
+    JsonObject,
+    /// JSON matching a schema
+    JsonSchema {
+        /// Schema definition
+        json_schema: JsonSchemaFormat,
+    },
+}
+
+/// Schema for [`ResponseFormat::JsonSchema`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    /// Schema name
+    pub name: String,
+    /// JSON Schema the output must match
+    pub schema: serde_json::Value,
+    /// Enforce the schema exactly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+/// Options for streamed responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Send token usage in a final chunk
+    pub include_usage: bool,
+}
+
+/// Predicted output, used to speed up regeneration of mostly-known content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Prediction {
+    /// Expected content of the completion
+    Content {
+        /// Predicted text
+        content: String,
+    },
+}
+
+/// How much reasoning a reasoning model (e.g. OpenAI o1/o3) does before
+/// answering.
+///
+/// Lower effort answers faster and spends fewer reasoning tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    /// Favor speed and fewer reasoning tokens
+    Low,
+    /// Balance speed and depth (the provider default)
+    Medium,
+    /// Favor thorough reasoning
+    High,
+}
+
+/// Start of the assistant's reply, which the model continues.
+///
+/// Steers the output format, e.g. a prefill of `{"` forces JSON. Sent as a
+/// trailing assistant message by providers that support
+/// [`Feature::AssistantPrefill`]; others reject the request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssistantPrefill {
+    /// Text the assistant message starts with
+    pub text: String,
+    /// Prepend `text` to the response content, so it reads as the full reply
+    #[serde(default)]
+    pub prepend_to_response: bool,
+}
+
+/// A system prompt warmed in a provider's KV cache.
+///
+/// Returned by [`Provider::prefill_cache`] and attached to later requests
+/// with [`CompletionRequest::with_prefill_token`], which makes them start
+/// with the exact prompt that was prefilled so the server can reuse its
+/// cached prefix. Providers without prefix caching return a no-op token.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefillToken {
+    provider: Option<String>,
+    model: Option<String>,
+    system_prompt: Option<String>,
+}
+
+impl PrefillToken {
+    /// Token for `system_prompt`, prefilled by `provider` for `model`.
+    pub fn new(
+        provider: impl Into<String>,
+        model: impl Into<String>,
+        system_prompt: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider: Some(provider.into()),
+            model: Some(model.into()),
+            system_prompt: Some(system_prompt.into()),
+        }
+    }
+
+    /// Token that leaves requests unchanged.
+    pub fn noop() -> Self {
+        Self::default()
+    }
+
+    /// Whether attaching this token changes nothing.
+    pub fn is_noop(&self) -> bool {
+        self.system_prompt.is_none()
+    }
+
+    /// Provider that prefilled the prompt.
+    pub fn provider(&self) -> Option<&str> {
+        self.provider.as_deref()
+    }
+
+    /// Model the prompt was prefilled for.
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// The prefilled system prompt.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.system_prompt.as_deref()
+    }
+}
+
 /// A completion request to an LLM provider.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CompletionRequest {
     /// List of messages in the conversation
-    pub messages: Vec<Message>,
+    pub messages: MessageList,
     /// Model identifier (e.g., "gpt-4", "claude-3-opus")
     pub model: String,
     /// Maximum tokens to generate
@@ -25,6 +156,9 @@ pub struct CompletionRequest {
     /// Enable streaming responses
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// Streaming options (requires `stream: true`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
     /// Number of completions to generate
     #[serde(skip_serializing_if = "Option::is_none")]
     pub n: Option<u32>,
@@ -40,8 +174,137 @@ pub struct CompletionRequest {
     /// User identifier (for abuse detection)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    /// Required output format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Tools the model may call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ToolDefinition>>,
+    /// Whether and which tool the model calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// Return log probabilities of output tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+    /// Number of most likely tokens to return per position (0-20)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+    /// Predicted output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prediction: Option<Prediction>,
+    /// Partial assistant message for the model to continue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assistant_prefill: Option<AssistantPrefill>,
+    /// Reasoning effort, for reasoning models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// Trim a stop sequence echoed at the end of the response content
+    ///
+    /// See [`CompletionResponse::trim_stop_sequences`](crate::response::CompletionResponse::trim_stop_sequences).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trim_stop_sequences: bool,
+    /// Values for wrappers and providers further down; never sent
+    ///
+    /// Copied into the [`ProviderRequest`](crate::provider::ProviderRequest)
+    /// built from this request. See [`crate::extensions`].
+    #[serde(skip)]
+    pub extensions: Extensions,
 }
 
+/// Maximum value of `top_logprobs`
+const MAX_TOP_LOGPROBS: u8 = 20;
+
+/// A constraint between two request fields.
+///
+/// Checked by [`CompletionRequest::validate_combinations`]; add a new rule
+/// by appending an entry to [`FIELD_RULES`].
+struct FieldRule {
+    field: &'static str,
+    related: &'static str,
+    reason: &'static str,
+    violated: fn(&CompletionRequest) -> bool,
+}
+
+/// Mark every object schema in `schema` closed, with all its properties
+/// required, for strict structured output
+#[cfg(feature = "schemars")]
+fn close_objects(schema: &mut serde_json::Value) {
+    match schema {
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::Object(properties)) = object.get_mut("properties") {
+                // Property names are not schemas, so only descend into the values
+                properties.values_mut().for_each(close_objects);
+                let required: Vec<serde_json::Value> =
+                    properties.keys().cloned().map(serde_json::Value::String).collect();
+                object.insert("required".to_string(), required.into());
+                object.insert("additionalProperties".to_string(), false.into());
+            }
+            object
+                .iter_mut()
+                .filter(|(key, _)| key.as_str() != "properties")
+                .for_each(|(_, value)| close_objects(value));
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(close_objects),
+        _ => {}
+    }
+}
+
+const FIELD_RULES: &[FieldRule] = &[
+    FieldRule {
+        field: "response_format",
+        related: "messages",
+        reason: "JSON output requires a system or user message mentioning \"json\"",
+        violated: |req| {
+            matches!(
+                req.response_format,
+                Some(ResponseFormat::JsonObject | ResponseFormat::JsonSchema { .. })
+            ) && !req.messages.iter().any(|m| {
+                matches!(m.role, Role::System | Role::User)
+                    && m.content.to_ascii_lowercase().contains("json")
+            })
+        },
+    },
+    FieldRule {
+        field: "tool_choice",
+        related: "tools",
+        reason: "tool_choice requires at least one tool",
+        violated: |req| req.tool_choice.is_some() && !req.tools.as_ref().is_some_and(|t| !t.is_empty()),
+    },
+    FieldRule {
+        field: "top_logprobs",
+        related: "logprobs",
+        reason: "top_logprobs requires logprobs to be true",
+        violated: |req| req.top_logprobs.is_some() && req.logprobs != Some(true),
+    },
+    FieldRule {
+        field: "n",
+        related: "stream",
+        reason: "multiple completions cannot be streamed",
+        violated: |req| req.n.is_some_and(|n| n > 1) && req.is_streaming(),
+    },
+    FieldRule {
+        field: "stream_options",
+        related: "stream",
+        reason: "stream_options requires stream to be true",
+        violated: |req| req.stream_options.is_some() && !req.is_streaming(),
+    },
+    FieldRule {
+        field: "prediction",
+        related: "tools",
+        reason: "predicted outputs cannot be combined with tools",
+        violated: |req| req.prediction.is_some() && req.tools.as_ref().is_some_and(|t| !t.is_empty()),
+    },
+    FieldRule {
+        field: "assistant_prefill",
+        related: "messages",
+        reason: "assistant_prefill cannot follow an assistant message",
+        violated: |req| {
+            req.assistant_prefill.is_some()
+                && req.messages.last().is_some_and(|m| m.role == Role::Assistant)
+        },
+    },
+];
+
 impl CompletionRequest {
     /// Create a new builder.
     ///
@@ -60,6 +323,51 @@ impl CompletionRequest {
         CompletionRequestBuilder::default()
     }
 
+    /// A builder holding every field of this request, extensions included.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4")
+    ///     .message(Message::user("Hello"))
+    ///     .build()?;
+    /// let colder = request.to_builder().temperature(0.2).build()?;
+    ///
+    /// assert_eq!(colder.messages, request.messages);
+    /// assert_eq!(colder.temperature, Some(0.2));
+    /// # Ok::<(), SimpleAgentsError>(())
+    /// ```
+    pub fn to_builder(&self) -> CompletionRequestBuilder {
+        let request = self.clone();
+        CompletionRequestBuilder {
+            messages: request.messages,
+            model: Some(request.model),
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stream: request.stream,
+            stream_options: request.stream_options,
+            n: request.n,
+            stop: request.stop,
+            presence_penalty: request.presence_penalty,
+            frequency_penalty: request.frequency_penalty,
+            user: request.user,
+            response_format: request.response_format,
+            tools: request.tools,
+            tool_choice: request.tool_choice,
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
+            prediction: request.prediction,
+            prepend_prefill: request.assistant_prefill.as_ref().is_some_and(|p| p.prepend_to_response),
+            assistant_prefill: request.assistant_prefill.map(|prefill| prefill.text),
+            reasoning_effort: request.reasoning_effort,
+            trim_stop_sequences: request.trim_stop_sequences,
+            extensions: request.extensions,
+        }
+    }
+
     /// Validate the request.
     ///
     /// # Validation Rules
@@ -186,24 +494,515 @@ impl CompletionRequest {
             }
         }
 
+        // Validate top_logprobs
+        if let Some(top_logprobs) = self.top_logprobs {
+            if top_logprobs > MAX_TOP_LOGPROBS {
+                return Err(ValidationError::OutOfRange {
+                    field: "top_logprobs".to_string(),
+                    min: 0.0,
+                    max: MAX_TOP_LOGPROBS as f32,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that fields which depend on or exclude each other are set consistently.
+    ///
+    /// # Rules
+    /// - JSON `response_format` needs a system/user message mentioning "json"
+    /// - `tool_choice` needs non-empty `tools`
+    /// - `top_logprobs` needs `logprobs: true`
+    /// - `n > 1` cannot be combined with `stream: true`
+    /// - `stream_options` needs `stream: true`
+    /// - `prediction` cannot be combined with `tools`
+    /// - `assistant_prefill` cannot follow an assistant message
+    ///
+    /// Returns [`ValidationError::Conflict`] naming both fields of the first
+    /// violated rule.
+    pub fn validate_combinations(&self) -> Result<()> {
+        match FIELD_RULES.iter().find(|rule| (rule.violated)(self)) {
+            Some(rule) => Err(ValidationError::Conflict {
+                field: rule.field.to_string(),
+                related: rule.related.to_string(),
+                reason: rule.reason.to_string(),
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Require output matching the JSON Schema of `T`, derived with
+    /// [`schemars`].
+    ///
+    /// The schema is named after `T`. With `strict`, every object in it is
+    /// closed (`additionalProperties: false`) and lists all its properties
+    /// as required, as OpenAI's strict mode demands; `Option` fields stay
+    /// optional by accepting `null`. Parse the reply with
+    /// [`CompletionResponse::parse_content_as`](crate::response::CompletionResponse::parse_content_as).
+    ///
+    /// Requires the `schemars` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::Conflict`] if no system or user message
+    /// mentions JSON (see [`validate_combinations`](Self::validate_combinations)).
+    ///
+    /// # Example
+    /// ```
+    /// use schemars::JsonSchema;
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// #[derive(JsonSchema)]
+    /// struct Forecast {
+    ///     city: String,
+    ///     celsius: i32,
+    /// }
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4o")
+    ///     .message(Message::user("Weather in Oslo as JSON"))
+    ///     .build()?
+    ///     .with_json_schema_output::<Forecast>(true)?;
+    ///
+    /// match &request.response_format {
+    ///     Some(ResponseFormat::JsonSchema { json_schema }) => assert_eq!(json_schema.name, "Forecast"),
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// # Ok::<(), SimpleAgentsError>(())
+    /// ```
+    #[cfg(feature = "schemars")]
+    pub fn with_json_schema_output<T: schemars::JsonSchema>(mut self, strict: bool) -> Result<Self> {
+        let root = schemars::gen::SchemaSettings::draft2019_09()
+            .into_generator()
+            .into_root_schema_for::<T>();
+        let mut schema = serde_json::to_value(root)?;
+        if let Some(object) = schema.as_object_mut() {
+            // Some providers reject the meta-schema reference
+            object.remove("$schema");
+        }
+        if strict {
+            close_objects(&mut schema);
+        }
+
+        let name: String = T::schema_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        self.response_format = Some(ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name,
+                schema,
+                strict: Some(strict),
+            },
+        });
+        self.validate_combinations()?;
+        Ok(self)
+    }
+
+    /// Whether the request asks for a streamed response.
+    pub fn is_streaming(&self) -> bool {
+        self.stream == Some(true)
+    }
+
+    /// Reject streaming requests at an entry point that returns a whole response.
+    ///
+    /// Used by [`Provider::complete`] and by wrappers (caches, stores) that
+    /// cannot handle streamed responses. Streaming requests should go
+    /// through [`Provider::execute_stream`] instead.
+    pub fn ensure_not_streaming(&self) -> Result<()> {
+        if self.is_streaming() {
+            return Err(ValidationError::InvalidFormat {
+                field: "stream".to_string(),
+                reason: "complete() returns a whole response; use Provider::execute_stream for streaming requests"
+                    .to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Compute a deterministic fingerprint of this request.
+    ///
+    /// The fingerprint is a blake3 hash (hex) of the request's canonical
+    /// JSON serialization, so two requests share a fingerprint exactly when
+    /// every serialized field is equal.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::request::CompletionRequest;
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4")
+    ///     .message(Message::user("Hello!"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(request.fingerprint(), request.clone().fingerprint());
+    /// assert_eq!(request.fingerprint().len(), 64);
+    /// ```
+    pub fn fingerprint(&self) -> String {
+        // Serializing a plain struct of strings and numbers cannot fail.
+        let canonical = serde_json::to_vec(self).unwrap_or_default();
+        blake3::hash(&canonical).to_hex().to_string()
+    }
+
+    /// Estimate prompt tokens for each message.
+    ///
+    /// Returns `(message_index, estimated_tokens)` pairs in message order.
+    /// Each estimate includes the per-message role overhead; see
+    /// [`Message::estimate_tokens`].
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::request::CompletionRequest;
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4")
+    ///     .message(Message::system("Be brief."))
+    ///     .message(Message::user("Hello!"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(request.token_count_breakdown(), vec![(0, 7), (1, 6)]);
+    /// assert_eq!(request.token_count_total(), 13);
+    /// ```
+    pub fn token_count_breakdown(&self) -> Vec<(usize, u32)> {
+        self.messages
+            .iter()
+            .enumerate()
+            .map(|(i, msg)| (i, msg.estimate_tokens()))
+            .collect()
+    }
+
+    /// Estimate the total prompt tokens across all messages.
+    pub fn token_count_total(&self) -> u32 {
+        self.messages
+            .iter()
+            .fold(0u32, |total, msg| total.saturating_add(msg.estimate_tokens()))
+    }
+
+    /// A `completion_request` span describing this request, for
+    /// distributed tracing.
+    ///
+    /// The span is at info level with the fields `llm.model`,
+    /// `llm.message_count`, `llm.temperature` and `llm.max_tokens`; unset
+    /// parameters are left empty. Instrument the future that sends the
+    /// request with it, or use [`enter_span`](Self::enter_span) in
+    /// synchronous code.
+    ///
+    /// Requires the `tracing` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4o-mini")
+    ///     .message(Message::user("Hello"))
+    ///     .build()?;
+    /// let span = request.span();
+    /// span.in_scope(|| tracing::info!("sending"));
+    /// # Ok::<(), SimpleAgentsError>(())
+    /// ```
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> tracing::Span {
+        let span = tracing::info_span!(
+            "completion_request",
+            llm.model = %self.model,
+            llm.message_count = self.messages.len(),
+            llm.temperature = tracing::field::Empty,
+            llm.max_tokens = tracing::field::Empty,
+        );
+        if let Some(temperature) = self.temperature {
+            span.record("llm.temperature", temperature);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            span.record("llm.max_tokens", max_tokens);
+        }
+        span
+    }
+
+    /// Create this request's [`span`](Self::span) and enter it until the
+    /// returned guard is dropped.
+    ///
+    /// Only for synchronous code: a guard held across an `.await` puts
+    /// unrelated work in the span. Requires the `tracing` feature.
+    #[cfg(feature = "tracing")]
+    pub fn enter_span(&self) -> tracing::span::EnteredSpan {
+        self.span().entered()
+    }
+
+    /// Return the `n` most token-heavy messages.
+    ///
+    /// Results are `(message_index, estimated_tokens)` sorted by descending
+    /// token count; ties keep message order.
+    pub fn top_messages_by_tokens(&self, n: usize) -> Vec<(usize, u32)> {
+        let mut breakdown = self.token_count_breakdown();
+        breakdown.sort_by_key(|&(_, tokens)| std::cmp::Reverse(tokens));
+        breakdown.truncate(n);
+        breakdown
+    }
+
+    /// Check that the request's model is supported by `provider`.
+    ///
+    /// Delegates to [`Provider::supports_model`], so providers that do not
+    /// publish a model list accept every model.
+    pub fn validate_model(&self, provider: &dyn Provider) -> Result<()> {
+        if !provider.supports_model(&self.model) {
+            return Err(ValidationError::InvalidFormat {
+                field: "model".to_string(),
+                reason: format!(
+                    "model '{}' is not supported by provider '{}'",
+                    self.model,
+                    provider.name()
+                ),
+            }
+            .into());
+        }
+
         Ok(())
     }
+
+    /// Optional provider features this request depends on.
+    ///
+    /// Streaming requests need [`Feature::Streaming`]; conversations
+    /// containing tool calls or results need [`Feature::FunctionCalling`]; messages
+    /// with images need [`Feature::Vision`], with documents
+    /// [`Feature::Documents`]; an `assistant_prefill` needs
+    /// [`Feature::AssistantPrefill`].
+    pub fn required_features(&self) -> Vec<Feature> {
+        let mut features = Vec::new();
+        if self.is_streaming() {
+            features.push(Feature::Streaming);
+        }
+        if self
+            .messages
+            .iter()
+            .any(|m| m.role == Role::Tool || m.tool_call_id.is_some() || !m.tool_calls.is_empty())
+        {
+            features.push(Feature::FunctionCalling);
+        }
+        if self.messages.iter().any(|m| !m.images.is_empty()) {
+            features.push(Feature::Vision);
+        }
+        if self.messages.iter().any(|m| !m.documents.is_empty()) {
+            features.push(Feature::Documents);
+        }
+        if self.assistant_prefill.is_some() {
+            features.push(Feature::AssistantPrefill);
+        }
+        features
+    }
+
+    /// Check that `provider` declares every feature this request needs.
+    ///
+    /// When [`Provider::model_info`] knows the model, the provider's
+    /// capabilities are narrowed to the model's (see
+    /// [`ModelInfoStatic::restrict`](crate::catalog::ModelInfoStatic::restrict)),
+    /// so images sent to a text-only model are caught too.
+    ///
+    /// Returns [`ProviderError::Unsupported`] naming the first missing feature.
+    pub fn check_features(&self, provider: &dyn Provider) -> Result<()> {
+        let capabilities = match provider.model_info(&self.model) {
+            Some(info) => info.restrict(provider.capabilities()),
+            None => provider.capabilities(),
+        };
+        match capabilities.first_missing(&self.required_features()) {
+            Some(feature) => Err(ProviderError::Unsupported {
+                provider: provider.name().to_string(),
+                feature,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Start the conversation with the system prompt of a prefill `token`.
+    ///
+    /// The prompt is inserted as the first message unless it already is
+    /// the first message, verbatim; a no-op token changes nothing. Other
+    /// system messages are kept after it.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// let token = PrefillToken::new("vllm", "llama-3.1-8b", "You are a support agent.");
+    /// let request = CompletionRequest::builder()
+    ///     .model("llama-3.1-8b")
+    ///     .message(Message::user("Where is my order?"))
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_prefill_token(&token);
+    /// assert_eq!(request.messages[0], Message::system("You are a support agent."));
+    /// ```
+    pub fn with_prefill_token(mut self, token: &PrefillToken) -> Self {
+        if let Some(prompt) = token.system_prompt() {
+            let attached = self
+                .messages
+                .first()
+                .is_some_and(|first| first.role == Role::System && first.content == prompt);
+            if !attached {
+                self.messages.insert(0, Message::system(prompt));
+            }
+        }
+        self
+    }
+
+    /// Prepend `prefix` to the first system message, separated by a blank
+    /// line.
+    ///
+    /// Inserts a system message holding just `prefix` if the request has
+    /// none. Does nothing if the system message already starts with
+    /// `prefix`, so applying it twice is safe.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("gpt-4")
+    ///     .message(Message::system("You are a travel agent."))
+    ///     .message(Message::user("Book me a flight."))
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_system_prefix("Never share customer data.");
+    /// assert_eq!(request.messages[0].content, "Never share customer data.\n\nYou are a travel agent.");
+    /// ```
+    pub fn with_system_prefix(mut self, prefix: &str) -> Self {
+        if let Some(system) = self.system_message_mut(prefix) {
+            if !system.content.starts_with(prefix) {
+                system.content = format!("{}\n\n{}", prefix, system.content);
+            }
+        }
+        self
+    }
+
+    /// Append `suffix` to the first system message, separated by a blank
+    /// line.
+    ///
+    /// The counterpart of [`with_system_prefix`](Self::with_system_prefix):
+    /// inserts a system message holding just `suffix` if there is none, and
+    /// does nothing if the system message already ends with `suffix`.
+    pub fn with_system_suffix(mut self, suffix: &str) -> Self {
+        if let Some(system) = self.system_message_mut(suffix) {
+            if !system.content.ends_with(suffix) {
+                system.content = format!("{}\n\n{}", system.content, suffix);
+            }
+        }
+        self
+    }
+
+    /// The first system message, or `None` after inserting a new one
+    /// holding `content` at the front.
+    fn system_message_mut(&mut self, content: &str) -> Option<&mut Message> {
+        match self.messages.iter().position(|m| m.role == Role::System) {
+            Some(index) => Some(&mut self.messages[index]),
+            None => {
+                self.messages.insert(0, Message::system(content));
+                None
+            }
+        }
+    }
+}
+
+/// Read-only questions about a request's conversation.
+///
+/// Shorthands for what would otherwise be written as iterations over
+/// [`CompletionRequest::messages`]. In the prelude, so the methods are
+/// available on any request.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::prelude::*;
+///
+/// let request = CompletionRequest::builder()
+///     .model("gpt-4o")
+///     .message(Message::system("Answer in French."))
+///     .message(Message::user("Hello"))
+///     .message(Message::assistant("Bonjour"))
+///     .message(Message::user("How are you?"))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(request.messages_len(), 4);
+/// assert!(request.has_system_message());
+/// assert_eq!(request.user_message_count(), 2);
+/// assert_eq!(request.last_user_message().unwrap().content, "How are you?");
+/// assert_eq!(request.first_system_message().unwrap().content, "Answer in French.");
+/// ```
+pub trait CompletionRequestInspector {
+    /// Number of messages in the conversation.
+    fn messages_len(&self) -> usize;
+
+    /// Whether any message has the system role.
+    fn has_system_message(&self) -> bool;
+
+    /// Number of messages with the user role.
+    fn user_message_count(&self) -> usize;
+
+    /// The most recent user message.
+    fn last_user_message(&self) -> Option<&Message>;
+
+    /// The first system message, which providers with a separate system
+    /// prompt (such as Anthropic) send as that prompt.
+    fn first_system_message(&self) -> Option<&Message>;
+}
+
+impl CompletionRequestInspector for CompletionRequest {
+    fn messages_len(&self) -> usize {
+        self.messages.len()
+    }
+
+    fn has_system_message(&self) -> bool {
+        self.first_system_message().is_some()
+    }
+
+    fn user_message_count(&self) -> usize {
+        self.messages.iter().filter(|m| m.role == Role::User).count()
+    }
+
+    fn last_user_message(&self) -> Option<&Message> {
+        self.messages.iter().rev().find(|m| m.role == Role::User)
+    }
+
+    fn first_system_message(&self) -> Option<&Message> {
+        self.messages.iter().find(|m| m.role == Role::System)
+    }
 }
 
 /// Builder for CompletionRequest.
 #[derive(Debug, Default, Clone)]
 pub struct CompletionRequestBuilder {
-    messages: Vec<Message>,
+    messages: MessageList,
     model: Option<String>,
     max_tokens: Option<u32>,
     temperature: Option<f32>,
     top_p: Option<f32>,
     stream: Option<bool>,
+    stream_options: Option<StreamOptions>,
     n: Option<u32>,
     stop: Option<Vec<String>>,
     presence_penalty: Option<f32>,
     frequency_penalty: Option<f32>,
     user: Option<String>,
+    response_format: Option<ResponseFormat>,
+    tools: Option<Vec<ToolDefinition>>,
+    tool_choice: Option<ToolChoice>,
+    logprobs: Option<bool>,
+    top_logprobs: Option<u8>,
+    prediction: Option<Prediction>,
+    assistant_prefill: Option<String>,
+    prepend_prefill: bool,
+    reasoning_effort: Option<ReasoningEffort>,
+    trim_stop_sequences: bool,
+    extensions: Extensions,
 }
 
 impl CompletionRequestBuilder {
@@ -220,8 +1019,8 @@ impl CompletionRequestBuilder {
     }
 
     /// Set all messages at once.
-    pub fn messages(mut self, messages: Vec<Message>) -> Self {
-        self.messages = messages;
+    pub fn messages(mut self, messages: impl Into<MessageList>) -> Self {
+        self.messages = messages.into();
         self
     }
 
@@ -249,6 +1048,12 @@ impl CompletionRequestBuilder {
         self
     }
 
+    /// Set streaming options.
+    pub fn stream_options(mut self, options: StreamOptions) -> Self {
+        self.stream_options = Some(options);
+        self
+    }
+
     /// Set number of completions.
     pub fn n(mut self, n: u32) -> Self {
         self.n = Some(n);
@@ -261,6 +1066,15 @@ impl CompletionRequestBuilder {
         self
     }
 
+    /// Trim stop sequences echoed at the end of the response content.
+    ///
+    /// For OpenAI-compatible servers that include the matched stop
+    /// sequence in their output.
+    pub fn trim_stop_sequences(mut self, trim: bool) -> Self {
+        self.trim_stop_sequences = trim;
+        self
+    }
+
     /// Set presence penalty.
     pub fn presence_penalty(mut self, penalty: f32) -> Self {
         self.presence_penalty = Some(penalty);
@@ -279,8 +1093,115 @@ impl CompletionRequestBuilder {
         self
     }
 
+    /// Set the required output format.
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    /// Add a tool the model may call.
+    pub fn tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.get_or_insert_with(Vec::new).push(tool);
+        self
+    }
+
+    /// Set all tools at once.
+    pub fn tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Set tool choice.
+    pub fn tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice = Some(choice);
+        self
+    }
+
+    /// Enable log probabilities.
+    pub fn logprobs(mut self, logprobs: bool) -> Self {
+        self.logprobs = Some(logprobs);
+        self
+    }
+
+    /// Set number of top log probabilities per token.
+    pub fn top_logprobs(mut self, top_logprobs: u8) -> Self {
+        self.top_logprobs = Some(top_logprobs);
+        self
+    }
+
+    /// Set predicted output.
+    pub fn prediction(mut self, prediction: Prediction) -> Self {
+        self.prediction = Some(prediction);
+        self
+    }
+
+    /// Set reasoning effort, for reasoning models.
+    pub fn reasoning_effort(mut self, effort: ReasoningEffort) -> Self {
+        self.reasoning_effort = Some(effort);
+        self
+    }
+
+    /// Set text the assistant's reply starts with.
+    ///
+    /// Trailing whitespace is removed: Anthropic rejects it, and models
+    /// continue more naturally without it.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::request::CompletionRequest;
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let request = CompletionRequest::builder()
+    ///     .model("claude-3-5-sonnet-20241022")
+    ///     .message(Message::user("List three colors as a JSON array."))
+    ///     .assistant_prefill("[")
+    ///     .prepend_prefill(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(request.assistant_prefill.unwrap().text, "[");
+    /// ```
+    pub fn assistant_prefill(mut self, text: impl Into<String>) -> Self {
+        self.assistant_prefill = Some(text.into().trim_end().to_string());
+        self
+    }
+
+    /// Prepend the assistant prefill to the response content.
+    ///
+    /// Has no effect without [`assistant_prefill`](Self::assistant_prefill).
+    pub fn prepend_prefill(mut self, prepend: bool) -> Self {
+        self.prepend_prefill = prepend;
+        self
+    }
+
+    /// Set an extension value (see [`crate::extensions`]).
+    pub fn extension(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
+
+    /// Replace all extension values.
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
     /// Build and validate the request.
+    ///
+    /// Runs [`CompletionRequest::validate`] and
+    /// [`CompletionRequest::validate_combinations`].
     pub fn build(self) -> Result<CompletionRequest> {
+        let request = self.build_unchecked()?;
+        request.validate_combinations()?;
+        Ok(request)
+    }
+
+    /// Build the request without checking field combinations.
+    ///
+    /// For proxies that forward to backends with looser rules than
+    /// [`CompletionRequest::validate_combinations`]. Per-field validation
+    /// ([`CompletionRequest::validate`]) still applies.
+    pub fn build_unchecked(self) -> Result<CompletionRequest> {
         let model = self.model.ok_or_else(|| ValidationError::Empty {
             field: "model".to_string(),
         })?;
@@ -292,11 +1213,25 @@ impl CompletionRequestBuilder {
             temperature: self.temperature,
             top_p: self.top_p,
             stream: self.stream,
+            stream_options: self.stream_options,
             n: self.n,
             stop: self.stop,
             presence_penalty: self.presence_penalty,
             frequency_penalty: self.frequency_penalty,
             user: self.user,
+            response_format: self.response_format,
+            tools: self.tools,
+            tool_choice: self.tool_choice,
+            logprobs: self.logprobs,
+            top_logprobs: self.top_logprobs,
+            prediction: self.prediction,
+            assistant_prefill: self.assistant_prefill.map(|text| AssistantPrefill {
+                text,
+                prepend_to_response: self.prepend_prefill,
+            }),
+            reasoning_effort: self.reasoning_effort,
+            trim_stop_sequences: self.trim_stop_sequences,
+            extensions: self.extensions,
         };
 
         request.validate()?;
@@ -308,6 +1243,40 @@ impl CompletionRequestBuilder {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_inspector() {
+        let mut request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("First"))
+            .message(Message::system("Be brief."))
+            .message(Message::assistant("Ok"))
+            .message(Message::system("Be kind."))
+            .message(Message::user("Second"))
+            .message(Message::tool("{}", "call_1"))
+            .build()
+            .unwrap();
+
+        assert_eq!(request.messages_len(), 6);
+        assert!(request.has_system_message());
+        assert_eq!(request.user_message_count(), 2);
+        assert_eq!(request.last_user_message().map(|m| m.content.as_str()), Some("Second"));
+        assert_eq!(request.first_system_message().map(|m| m.content.as_str()), Some("Be brief."));
+
+        request.messages.retain(|m| m.role != Role::System && m.role != Role::User);
+        assert_eq!(request.messages_len(), 2);
+        assert!(!request.has_system_message());
+        assert_eq!(request.user_message_count(), 0);
+        assert!(request.last_user_message().is_none());
+        assert!(request.first_system_message().is_none());
+
+        request.messages.clear();
+        assert_eq!(request.messages_len(), 0);
+        assert!(!request.has_system_message());
+        assert_eq!(request.user_message_count(), 0);
+        assert!(request.last_user_message().is_none());
+        assert!(request.first_system_message().is_none());
+    }
+
     #[test]
     fn test_builder_basic() {
         let request = CompletionRequest::builder()
@@ -349,6 +1318,20 @@ mod tests {
         assert_eq!(request.user, Some("test-user".to_string()));
     }
 
+    #[test]
+    fn test_reasoning_effort_serialization() {
+        let request = CompletionRequest::builder()
+            .model("o3-mini")
+            .message(Message::user("Hello"))
+            .reasoning_effort(ReasoningEffort::High)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["reasoning_effort"], "high");
+        assert_eq!(serde_json::from_value::<CompletionRequest>(json).unwrap(), request);
+    }
+
     #[test]
     fn test_builder_missing_model() {
         let result = CompletionRequest::builder()
@@ -415,8 +1398,56 @@ mod tests {
             .unwrap();
 
         let json = serde_json::to_value(&request).unwrap();
-        assert!(!json.get("max_tokens").is_some());
-        assert!(!json.get("temperature").is_some());
+        assert!(json.get("max_tokens").is_none());
+        assert!(json.get("temperature").is_none());
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_parameters() {
+        let base = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap();
+        let warmer = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .temperature(0.9)
+            .build()
+            .unwrap();
+
+        assert_eq!(base.fingerprint(), base.clone().fingerprint());
+        assert_ne!(base.fingerprint(), warmer.fingerprint());
+    }
+
+    #[test]
+    fn test_token_count_breakdown() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::system("You are a helpful assistant."))
+            .message(Message::user("Hi"))
+            .message(Message::assistant("x".repeat(400)))
+            .build()
+            .unwrap();
+
+        let breakdown = request.token_count_breakdown();
+        assert_eq!(breakdown, vec![(0, 11), (1, 5), (2, 104)]);
+        assert_eq!(request.token_count_total(), 120);
+    }
+
+    #[test]
+    fn test_top_messages_by_tokens() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("short"))
+            .message(Message::user("x".repeat(100)))
+            .message(Message::user("x".repeat(40)))
+            .build()
+            .unwrap();
+
+        assert_eq!(request.top_messages_by_tokens(2), vec![(1, 29), (2, 14)]);
+        assert_eq!(request.top_messages_by_tokens(10).len(), 3);
+        assert!(request.top_messages_by_tokens(0).is_empty());
     }
 
     #[test]
@@ -453,4 +1484,364 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_required_features() {
+        let plain = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .build()
+            .unwrap();
+        assert!(plain.required_features().is_empty());
+
+        let tools = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Weather?"))
+            .message(Message::tool("{\"temp\": 21}", "call_1"))
+            .stream(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            tools.required_features(),
+            vec![Feature::Streaming, Feature::FunctionCalling]
+        );
+
+        let prefill = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Hello"))
+            .assistant_prefill("{")
+            .build()
+            .unwrap();
+        assert_eq!(prefill.required_features(), vec![Feature::AssistantPrefill]);
+
+        let vision = CompletionRequest::builder()
+            .model("gpt-4o")
+            .message(
+                Message::user("What is this?")
+                    .with_image(crate::message::ImageUrl::new("https://example.com/a.png")),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(vision.required_features(), vec![Feature::Vision]);
+
+        let document = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Summarize").with_document(crate::message::Document::file("file_011")))
+            .build()
+            .unwrap();
+        assert_eq!(document.required_features(), vec![Feature::Documents]);
+
+        let call = crate::tool::ToolCall::function("call_1", "get_weather", "{}");
+        let tool_call = CompletionRequest::builder()
+            .model("gpt-4o")
+            .message(Message::user("Weather?"))
+            .message(Message::assistant("").with_tool_call(call))
+            .build()
+            .unwrap();
+        assert_eq!(tool_call.required_features(), vec![Feature::FunctionCalling]);
+    }
+
+    #[test]
+    fn test_trim_stop_sequences_flag() {
+        let base = || {
+            CompletionRequest::builder()
+                .model("llama-3")
+                .message(Message::user("Hello"))
+                .stop(vec!["</answer>".to_string()])
+        };
+
+        let default = base().build().unwrap();
+        assert!(!default.trim_stop_sequences);
+        // Unset flag is not serialized, so existing fingerprints are stable
+        assert!(serde_json::to_value(&default).unwrap().get("trim_stop_sequences").is_none());
+
+        let trimming = base().trim_stop_sequences(true).build().unwrap();
+        assert!(trimming.trim_stop_sequences);
+        assert_ne!(trimming.fingerprint(), default.fingerprint());
+    }
+
+    #[test]
+    fn test_assistant_prefill() {
+        let request = CompletionRequest::builder()
+            .model("claude-3-5-sonnet-20241022")
+            .message(Message::user("Answer in JSON."))
+            .assistant_prefill("{\"answer\": \n")
+            .prepend_prefill(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.assistant_prefill,
+            Some(AssistantPrefill {
+                text: "{\"answer\":".to_string(),
+                prepend_to_response: true,
+            })
+        );
+
+        let without_prefill = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .prepend_prefill(true)
+            .build()
+            .unwrap();
+        assert_eq!(without_prefill.assistant_prefill, None);
+
+        assert_eq!(
+            conflict(
+                CompletionRequest::builder()
+                    .model("claude-3-5-sonnet-20241022")
+                    .message(Message::user("Hello"))
+                    .message(Message::assistant("Hi"))
+                    .assistant_prefill("{")
+                    .build()
+            ),
+            ("assistant_prefill".to_string(), "messages".to_string())
+        );
+    }
+
+    fn conflict(result: Result<CompletionRequest>) -> (String, String) {
+        match result {
+            Err(crate::error::SimpleAgentsError::Validation(ValidationError::Conflict { field, related, .. })) => {
+                (field, related)
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+    }
+
+    fn weather_tool() -> ToolDefinition {
+        ToolDefinition::function("get_weather", "Get the weather", serde_json::json!({"type": "object"}))
+    }
+
+    #[test]
+    fn test_combination_rules() {
+        let base = || CompletionRequest::builder().model("gpt-4").message(Message::user("Hello"));
+
+        assert_eq!(
+            conflict(base().response_format(ResponseFormat::JsonObject).build()),
+            ("response_format".to_string(), "messages".to_string())
+        );
+        assert_eq!(
+            conflict(base().tool_choice(ToolChoice::Auto).build()),
+            ("tool_choice".to_string(), "tools".to_string())
+        );
+        assert_eq!(
+            conflict(base().tools(vec![]).tool_choice(ToolChoice::Required).build()),
+            ("tool_choice".to_string(), "tools".to_string())
+        );
+        assert_eq!(
+            conflict(base().top_logprobs(5).build()),
+            ("top_logprobs".to_string(), "logprobs".to_string())
+        );
+        assert_eq!(
+            conflict(base().n(2).stream(true).build()),
+            ("n".to_string(), "stream".to_string())
+        );
+        assert_eq!(
+            conflict(base().stream_options(StreamOptions { include_usage: true }).build()),
+            ("stream_options".to_string(), "stream".to_string())
+        );
+        assert_eq!(
+            conflict(
+                base()
+                    .tool(weather_tool())
+                    .prediction(Prediction::Content { content: "Sunny".to_string() })
+                    .build()
+            ),
+            ("prediction".to_string(), "tools".to_string())
+        );
+    }
+
+    #[test]
+    fn test_valid_combinations() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::system("Reply in JSON."))
+            .message(Message::user("Weather in Paris?"))
+            .response_format(ResponseFormat::JsonObject)
+            .tool(weather_tool())
+            .tool_choice(ToolChoice::Function { name: "get_weather".to_string() })
+            .logprobs(true)
+            .top_logprobs(5)
+            .n(2)
+            .build()
+            .unwrap();
+
+        assert_eq!(request.tools.as_ref().map(Vec::len), Some(1));
+        assert_eq!(request.top_logprobs, Some(5));
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["response_format"]["type"], "json_object");
+    }
+
+    #[test]
+    fn test_conflict_error_names_both_fields() {
+        let err = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .top_logprobs(3)
+            .build()
+            .unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("top_logprobs"));
+        assert!(message.contains("logprobs"));
+    }
+
+    #[test]
+    fn test_build_unchecked_skips_combination_rules() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .tool_choice(ToolChoice::Auto)
+            .build_unchecked()
+            .unwrap();
+        assert!(request.validate_combinations().is_err());
+
+        // Per-field validation still applies
+        assert!(CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .logprobs(true)
+            .top_logprobs(21)
+            .build_unchecked()
+            .is_err());
+    }
+
+    #[test]
+    fn test_stream_options() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .stream(true)
+            .stream_options(StreamOptions { include_usage: true })
+            .build()
+            .unwrap();
+
+        assert!(request.is_streaming());
+        assert!(request.ensure_not_streaming().is_err());
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["stream_options"]["include_usage"], true);
+
+        let plain = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .stream(false)
+            .build()
+            .unwrap();
+        assert!(!plain.is_streaming());
+        assert!(plain.ensure_not_streaming().is_ok());
+    }
+
+    #[test]
+    fn test_with_prefill_token() {
+        let request = || {
+            CompletionRequest::builder()
+                .model("llama-3.1-8b")
+                .message(Message::system("Answer in French."))
+                .message(Message::user("Hello"))
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(request().with_prefill_token(&PrefillToken::noop()), request());
+
+        let token = PrefillToken::new("vllm", "llama-3.1-8b", "You are a support agent.");
+        assert!(!token.is_noop());
+        let attached = request().with_prefill_token(&token);
+        assert_eq!(attached.messages.len(), 3);
+        assert_eq!(attached.messages[0], Message::system("You are a support agent."));
+        assert_eq!(attached.messages[1].content, "Answer in French.");
+
+        // Attaching twice does not duplicate the prompt
+        assert_eq!(attached.clone().with_prefill_token(&token), attached);
+    }
+
+    #[test]
+    fn test_with_system_prefix_and_suffix() {
+        let request = CompletionRequest::builder()
+            .model("gpt-4")
+            .message(Message::user("Hello"))
+            .message(Message::system("You are a travel agent."))
+            .build()
+            .unwrap();
+
+        let wrapped = request.with_system_prefix("Be safe.").with_system_suffix("Be brief.");
+        assert_eq!(wrapped.messages.len(), 2);
+        assert_eq!(wrapped.messages[0], Message::user("Hello"));
+        assert_eq!(wrapped.messages[1].content, "Be safe.\n\nYou are a travel agent.\n\nBe brief.");
+
+        // Already applied: no-op
+        let again = wrapped.clone().with_system_prefix("Be safe.").with_system_suffix("Be brief.");
+        assert_eq!(again, wrapped);
+    }
+
+    #[test]
+    fn test_with_system_prefix_without_system_message() {
+        let request = || {
+            CompletionRequest::builder()
+                .model("gpt-4")
+                .message(Message::user("Hello"))
+                .build()
+                .unwrap()
+        };
+
+        let prefixed = request().with_system_prefix("Be safe.");
+        assert_eq!(prefixed.messages, vec![Message::system("Be safe."), Message::user("Hello")]);
+        assert_eq!(prefixed.clone().with_system_prefix("Be safe."), prefixed);
+
+        let suffixed = request().with_system_suffix("Be brief.");
+        assert_eq!(suffixed.messages, vec![Message::system("Be brief."), Message::user("Hello")]);
+        assert_eq!(suffixed.clone().with_system_suffix("Be brief."), suffixed);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_span_records_request_fields() {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::fmt::format::FmtSpan;
+        use tracing_subscriber::fmt::writer::MakeWriterExt;
+        use tracing_subscriber::fmt::TestWriter;
+
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let capture = Capture::default();
+        let output = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(TestWriter::new().and(move || capture.clone()))
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(false)
+            .finish();
+
+        let request = CompletionRequest::builder()
+            .model("gpt-4o-mini")
+            .message(Message::system("Be brief."))
+            .message(Message::user("Hello"))
+            .temperature(0.5)
+            .build()
+            .unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            let _entered = request.enter_span();
+            tracing::info!("sending");
+        });
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("completion_request"), "{}", output);
+        assert!(output.contains("llm.model=gpt-4o-mini"), "{}", output);
+        assert!(output.contains("llm.message_count=2"), "{}", output);
+        assert!(output.contains("llm.temperature=0.5"), "{}", output);
+        assert!(!output.contains("llm.max_tokens"), "{}", output);
+        assert!(output.contains("sending"), "{}", output);
+    }
 }
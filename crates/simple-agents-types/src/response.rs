@@ -2,7 +2,10 @@
 //!
 //! Provides OpenAI-compatible response structures.
 
+use crate::annotation::{render_footnotes, Annotation};
+use crate::error::{ProviderError, Result};
 use crate::message::Message;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 /// A completion response from an LLM provider.
@@ -16,14 +19,54 @@ pub struct CompletionResponse {
     pub choices: Vec<CompletionChoice>,
     /// Token usage statistics
     pub usage: Usage,
-    /// Unix timestamp of creation
+    /// Unix timestamp of creation, in seconds
+    ///
+    /// Providers normalize this with [`normalize_unix_timestamp`];
+    /// deserialized values may still be in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created: Option<i64>,
+    /// `created` was set from the time the response was received because
+    /// the provider does not report one
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub created_synthesized: bool,
     /// Provider that generated this response
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
 }
 
+/// Unix timestamps above this are taken to be in milliseconds.
+///
+/// 10^12 seconds is tens of thousands of years away, while 10^12
+/// milliseconds is September 2001.
+pub const MILLISECOND_TIMESTAMP_THRESHOLD: i64 = 1_000_000_000_000;
+
+/// Convert a Unix timestamp in seconds or milliseconds to seconds.
+///
+/// Some OpenAI-compatible servers report `created` in milliseconds; values
+/// above [`MILLISECOND_TIMESTAMP_THRESHOLD`] are treated as such.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::response::normalize_unix_timestamp;
+///
+/// assert_eq!(normalize_unix_timestamp(1_700_000_000), 1_700_000_000);
+/// assert_eq!(normalize_unix_timestamp(1_700_000_000_123), 1_700_000_000);
+/// ```
+pub fn normalize_unix_timestamp(timestamp: i64) -> i64 {
+    if timestamp > MILLISECOND_TIMESTAMP_THRESHOLD {
+        timestamp / 1000
+    } else {
+        timestamp
+    }
+}
+
+/// Current Unix time in seconds, for providers that report no timestamp.
+pub fn unix_timestamp_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX))
+}
+
 impl CompletionResponse {
     /// Get the content of the first choice (convenience method).
     ///
@@ -40,28 +83,493 @@ impl CompletionResponse {
     ///         message: Message::assistant("Hello!"),
     ///         finish_reason: FinishReason::Stop,
     ///         logprobs: None,
+    ///         matched_stop: None,
+    ///         annotations: Vec::new(),
     ///     }],
     ///     usage: Usage {
     ///         prompt_tokens: 10,
     ///         completion_tokens: 5,
     ///         total_tokens: 15,
+    ///         cache_creation_input_tokens: None,
+    ///         cache_read_input_tokens: None,
     ///     },
     ///     created: None,
+    ///     created_synthesized: false,
     ///     provider: None,
     /// };
     ///
     /// assert_eq!(response.content(), Some("Hello!"));
     /// ```
+    ///
+    /// Returns `None` if the model refused (see [`refusal`](Self::refusal)).
     pub fn content(&self) -> Option<&str> {
         self.choices
             .first()
+            .filter(|choice| !choice.is_refusal())
             .map(|choice| choice.message.content.as_str())
     }
 
+    /// Get the refusal message of the first choice, if the model declined
+    /// to answer.
+    pub fn refusal(&self) -> Option<&str> {
+        self.choices.first()?.message.refusal.as_deref()
+    }
+
+    /// Deserialize the first choice's content as JSON.
+    ///
+    /// A Markdown code fence around the JSON (` ```json ... ``` `), which
+    /// models often add even when asked for JSON only, is removed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::Refusal`] if the model declined to answer,
+    /// [`ProviderError::InvalidResponse`] if there are no choices, and
+    /// [`SimpleAgentsError::Serialization`] if the content is not valid
+    /// JSON for `T`.
+    ///
+    /// [`ProviderError::Refusal`]: crate::error::ProviderError::Refusal
+    /// [`ProviderError::InvalidResponse`]: crate::error::ProviderError::InvalidResponse
+    /// [`SimpleAgentsError::Serialization`]: crate::error::SimpleAgentsError::Serialization
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Deserialize;
+    /// use simple_agents_types::prelude::*;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Forecast {
+    ///     celsius: i32,
+    /// }
+    ///
+    /// let response = CompletionResponse {
+    ///     id: "resp_123".to_string(),
+    ///     model: "gpt-4".to_string(),
+    ///     choices: vec![CompletionChoice {
+    ///         index: 0,
+    ///         message: Message::assistant("```json\n{\"celsius\": 12}\n```"),
+    ///         finish_reason: FinishReason::Stop,
+    ///         logprobs: None,
+    ///         matched_stop: None,
+    ///         annotations: Vec::new(),
+    ///     }],
+    ///     usage: Usage::new(10, 5),
+    ///     created: None,
+    ///     created_synthesized: false,
+    ///     provider: None,
+    /// };
+    ///
+    /// let forecast: Forecast = response.parse_content_as()?;
+    /// assert_eq!(forecast.celsius, 12);
+    /// # Ok::<(), SimpleAgentsError>(())
+    /// ```
+    pub fn parse_content_as<T: DeserializeOwned>(&self) -> Result<T> {
+        if let Some(refusal) = self.refusal() {
+            return Err(ProviderError::Refusal(refusal.to_string()).into());
+        }
+        let content = self
+            .content()
+            .ok_or_else(|| ProviderError::InvalidResponse(format!("response {} has no content", self.id)))?;
+        Ok(serde_json::from_str(strip_code_fence(content))?)
+    }
+
     /// Get the first choice.
     pub fn first_choice(&self) -> Option<&CompletionChoice> {
         self.choices.first()
     }
+
+    /// Split into `(id, choices, usage)`.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::response::{CompletionChoice, CompletionResponse, FinishReason, Usage};
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let response = CompletionResponse {
+    ///     id: "resp_123".to_string(),
+    ///     model: "gpt-4".to_string(),
+    ///     choices: vec![CompletionChoice {
+    ///         index: 0,
+    ///         message: Message::assistant("Hello!"),
+    ///         finish_reason: FinishReason::Stop,
+    ///         logprobs: None,
+    ///         matched_stop: None,
+    ///         annotations: Vec::new(),
+    ///     }],
+    ///     usage: Usage::new(10, 5),
+    ///     created: None,
+    ///     created_synthesized: false,
+    ///     provider: None,
+    /// };
+    ///
+    /// let (id, choices, usage) = response.into_parts();
+    /// assert_eq!(id, "resp_123");
+    /// assert_eq!(choices[0].message.content, "Hello!");
+    /// assert_eq!(usage.total_tokens, 15);
+    /// ```
+    pub fn into_parts(self) -> (String, Vec<CompletionChoice>, Usage) {
+        (self.id, self.choices, self.usage)
+    }
+
+    /// Take the content of the first choice.
+    ///
+    /// Meant for tests and examples, like [`Option::unwrap`]; match on
+    /// [`content`](Self::content) to handle a missing answer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no choices or the model refused.
+    #[track_caller]
+    pub fn unwrap_content(self) -> String {
+        if let Some(refusal) = self.refusal() {
+            panic!(
+                "called `CompletionResponse::unwrap_content()` on refused response {}: {}",
+                self.id, refusal
+            );
+        }
+        let id = self.id.clone();
+        match self.into_content() {
+            Some(content) => content,
+            None => panic!(
+                "called `CompletionResponse::unwrap_content()` on response {} without choices",
+                id
+            ),
+        }
+    }
+
+    /// Take the content of the first choice, panicking with `msg` if there
+    /// is none.
+    ///
+    /// Like [`Option::expect`]; see [`unwrap_content`](Self::unwrap_content).
+    ///
+    /// # Panics
+    ///
+    /// Panics with `msg` if there are no choices or the model refused.
+    #[track_caller]
+    pub fn expect_content(self, msg: &str) -> String {
+        match self.into_content() {
+            Some(content) => content,
+            None => panic!("{}", msg),
+        }
+    }
+
+    /// The owned counterpart of [`content`](Self::content).
+    fn into_content(self) -> Option<String> {
+        let choice = self.choices.into_iter().next().filter(|choice| !choice.is_refusal())?;
+        Some(choice.message.content)
+    }
+
+    /// Serialize to the Anthropic Messages API response format.
+    ///
+    /// Useful when serving Anthropic clients. Only the first choice is
+    /// included, since Anthropic responses carry a single message. Its
+    /// tool calls follow the text as `tool_use` blocks, with the arguments
+    /// parsed into `input` (empty arguments become `{}`, and arguments
+    /// that are not valid JSON are passed on as a string).
+    /// [`FinishReason::ContentFilter`] maps to `"refusal"`. A stop with a
+    /// [`matched_stop`](CompletionChoice::matched_stop) maps to
+    /// `"stop_sequence"` and fills `stop_sequence`; otherwise
+    /// `stop_sequence` is `null`. Prompt cache counts are emitted when
+    /// set and subtracted from `input_tokens`, which excludes them.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::response::{CompletionResponse, CompletionChoice, Usage, FinishReason};
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let response = CompletionResponse {
+    ///     id: "msg_123".to_string(),
+    ///     model: "claude-3-5-sonnet-20241022".to_string(),
+    ///     choices: vec![CompletionChoice {
+    ///         index: 0,
+    ///         message: Message::assistant("Hello!"),
+    ///         finish_reason: FinishReason::Length,
+    ///         logprobs: None,
+    ///         matched_stop: None,
+    ///         annotations: Vec::new(),
+    ///     }],
+    ///     usage: Usage::new(10, 5),
+    ///     created: None,
+    ///     created_synthesized: false,
+    ///     provider: None,
+    /// };
+    ///
+    /// let wire = response.to_anthropic_wire();
+    /// assert_eq!(wire["content"][0]["text"], "Hello!");
+    /// assert_eq!(wire["stop_reason"], "max_tokens");
+    /// assert_eq!(wire["usage"]["input_tokens"], 10);
+    /// ```
+    pub fn to_anthropic_wire(&self) -> serde_json::Value {
+        let choice = self.first_choice();
+
+        let mut content: Vec<serde_json::Value> = choice
+            .map(|choice| choice.message.content.as_str())
+            .filter(|text| !text.is_empty())
+            .map(|text| serde_json::json!({ "type": "text", "text": text }))
+            .into_iter()
+            .collect();
+        for call in choice.into_iter().flat_map(|choice| &choice.message.tool_calls) {
+            let input = match call.function.arguments.trim() {
+                "" => serde_json::json!({}),
+                arguments => serde_json::from_str(arguments).unwrap_or_else(|_| arguments.into()),
+            };
+            content.push(serde_json::json!({
+                "type": "tool_use",
+                "id": call.id,
+                "name": call.function.name,
+                "input": input,
+            }));
+        }
+
+        let stop_sequence = choice
+            .filter(|choice| choice.finish_reason == FinishReason::Stop)
+            .and_then(|choice| choice.matched_stop.as_deref());
+        let stop_reason = choice.map(|choice| match choice.finish_reason {
+            FinishReason::Stop if stop_sequence.is_some() => "stop_sequence",
+            FinishReason::Stop => "end_turn",
+            FinishReason::Length => "max_tokens",
+            FinishReason::ToolCalls => "tool_use",
+            FinishReason::ContentFilter => "refusal",
+        });
+
+        let cache_creation = self.usage.cache_creation_input_tokens;
+        let cache_read = self.usage.cache_read_input_tokens;
+        let input_tokens = self
+            .usage
+            .prompt_tokens
+            .saturating_sub(cache_creation.unwrap_or(0))
+            .saturating_sub(cache_read.unwrap_or(0));
+
+        let mut usage = serde_json::json!({
+            "input_tokens": input_tokens,
+            "output_tokens": self.usage.completion_tokens,
+        });
+        if let Some(tokens) = cache_creation {
+            usage["cache_creation_input_tokens"] = tokens.into();
+        }
+        if let Some(tokens) = cache_read {
+            usage["cache_read_input_tokens"] = tokens.into();
+        }
+
+        serde_json::json!({
+            "id": self.id,
+            "type": "message",
+            "role": "assistant",
+            "content": content,
+            "model": self.model,
+            "stop_reason": stop_reason,
+            "stop_sequence": stop_sequence,
+            "usage": usage,
+        })
+    }
+
+    /// Set the provider that generated this response.
+    ///
+    /// [`Provider::complete`](crate::provider::Provider::complete) fills in
+    /// the provider's name when `transform_response` leaves it unset; use
+    /// this in `transform_response` to report a different one.
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+
+    /// Prepend an assistant prefill to every choice's content.
+    ///
+    /// Models continue from the prefill without repeating it, so this
+    /// restores the full reply. [`Provider::complete`](crate::provider::Provider::complete)
+    /// calls it when [`AssistantPrefill::prepend_to_response`](crate::request::AssistantPrefill::prepend_to_response)
+    /// is set.
+    pub fn prepend_prefill(&mut self, prefill: &str) {
+        for choice in &mut self.choices {
+            choice.message.content.insert_str(0, prefill);
+        }
+    }
+
+    /// Remove a stop sequence echoed at the end of each choice's content.
+    ///
+    /// OpenAI omits the stop sequence from the output, but some compatible
+    /// servers (vLLM, llama.cpp) include it. Only choices that finished with
+    /// [`FinishReason::Stop`] and whose content *ends* with one of `stops`
+    /// are trimmed; a sequence appearing mid-content is left alone. The
+    /// longest matching sequence wins and is recorded in
+    /// [`CompletionChoice::matched_stop`] unless the provider already
+    /// reported one.
+    ///
+    /// [`Provider::complete`](crate::provider::Provider::complete) calls this
+    /// when [`CompletionRequest::trim_stop_sequences`](crate::request::CompletionRequest::trim_stop_sequences)
+    /// is set.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::response::{CompletionResponse, CompletionChoice, Usage, FinishReason};
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let mut response = CompletionResponse {
+    ///     id: "resp_123".to_string(),
+    ///     model: "llama-3".to_string(),
+    ///     choices: vec![CompletionChoice {
+    ///         index: 0,
+    ///         message: Message::assistant("Paris.</answer>"),
+    ///         finish_reason: FinishReason::Stop,
+    ///         logprobs: None,
+    ///         matched_stop: None,
+    ///         annotations: Vec::new(),
+    ///     }],
+    ///     usage: Usage::new(10, 5),
+    ///     created: None,
+    ///     created_synthesized: false,
+    ///     provider: None,
+    /// };
+    ///
+    /// response.trim_stop_sequences(&["</answer>".to_string()]);
+    /// assert_eq!(response.content(), Some("Paris."));
+    /// assert_eq!(response.choices[0].matched_stop.as_deref(), Some("</answer>"));
+    /// ```
+    pub fn trim_stop_sequences(&mut self, stops: &[String]) {
+        for choice in &mut self.choices {
+            if choice.finish_reason != FinishReason::Stop {
+                continue;
+            }
+
+            let content = &choice.message.content;
+            let matched = stops
+                .iter()
+                .filter(|stop| !stop.is_empty() && content.ends_with(stop.as_str()))
+                .max_by_key(|stop| stop.len());
+
+            if let Some(stop) = matched {
+                let trimmed_len = content.len() - stop.len();
+                choice.message.content.truncate(trimmed_len);
+                choice.matched_stop.get_or_insert_with(|| stop.clone());
+            }
+        }
+    }
+
+    /// Condensed view of this response for logging.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::response::{CompletionResponse, CompletionChoice, Usage, FinishReason};
+    /// use simple_agents_types::message::Message;
+    ///
+    /// let response = CompletionResponse {
+    ///     id: "resp_123".to_string(),
+    ///     model: "gpt-4".to_string(),
+    ///     choices: vec![CompletionChoice {
+    ///         index: 0,
+    ///         message: Message::assistant("Hello!"),
+    ///         finish_reason: FinishReason::Stop,
+    ///         logprobs: None,
+    ///         matched_stop: None,
+    ///         annotations: Vec::new(),
+    ///     }],
+    ///     usage: Usage::new(10, 5),
+    ///     created: None,
+    ///     created_synthesized: false,
+    ///     provider: Some("openai".to_string()),
+    /// };
+    ///
+    /// assert_eq!(response.summary().total_tokens, 15);
+    /// assert_eq!(response.to_string(), r#"openai/gpt-4 stop tokens=10+5 "Hello!""#);
+    /// ```
+    pub fn summary(&self) -> ResponseSummary {
+        let content = self.content().unwrap_or_default();
+        // Collapse whitespace so the preview stays on one line
+        let flattened = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut content_preview: String = flattened.chars().take(SUMMARY_PREVIEW_CHARS).collect();
+        if content_preview.len() < flattened.len() {
+            content_preview.push('…');
+        }
+
+        ResponseSummary {
+            id: self.id.clone(),
+            model: self.model.clone(),
+            provider: self.provider.clone(),
+            finish_reason: self.first_choice().map(|choice| choice.finish_reason),
+            prompt_tokens: self.usage.prompt_tokens,
+            completion_tokens: self.usage.completion_tokens,
+            total_tokens: self.usage.total_tokens,
+            content_preview,
+        }
+    }
+
+    /// Creation time as a UTC date-time.
+    ///
+    /// `created` values above [`MILLISECOND_TIMESTAMP_THRESHOLD`] are read
+    /// as milliseconds, everything else as seconds. Check
+    /// [`created_synthesized`](Self::created_synthesized) to tell receipt
+    /// times from provider-reported ones.
+    #[cfg(feature = "chrono")]
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let created = self.created?;
+        if created > MILLISECOND_TIMESTAMP_THRESHOLD {
+            chrono::DateTime::from_timestamp_millis(created)
+        } else {
+            chrono::DateTime::from_timestamp(created, 0)
+        }
+    }
+}
+
+/// `text` without a surrounding Markdown code fence, trimmed.
+fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+    let Some(fenced) = text.strip_prefix("```") else {
+        return text;
+    };
+    // Drop the info string (`json`) along with the opening fence
+    let body = fenced.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+impl std::fmt::Display for CompletionResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.summary().fmt(f)
+    }
+}
+
+/// Characters of content kept in [`ResponseSummary::content_preview`].
+pub const SUMMARY_PREVIEW_CHARS: usize = 80;
+
+/// Condensed, serializable view of a [`CompletionResponse`].
+///
+/// Displays as a single line, e.g. `openai/gpt-4 stop tokens=10+5 "Hello!"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseSummary {
+    /// Response identifier
+    pub id: String,
+    /// Model used for completion
+    pub model: String,
+    /// Provider that generated the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Finish reason of the first choice, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    /// Tokens in the prompt
+    pub prompt_tokens: u32,
+    /// Tokens in the completion
+    pub completion_tokens: u32,
+    /// Total tokens used
+    pub total_tokens: u32,
+    /// First [`SUMMARY_PREVIEW_CHARS`] characters of the first choice, with
+    /// whitespace collapsed and `…` appended when cut
+    pub content_preview: String,
+}
+
+impl std::fmt::Display for ResponseSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(provider) = &self.provider {
+            write!(f, "{}/", provider)?;
+        }
+        match self.finish_reason {
+            Some(reason) => write!(f, "{} {}", self.model, reason)?,
+            None => write!(f, "{} no_choices", self.model)?,
+        }
+        write!(
+            f,
+            " tokens={}+{} {:?}",
+            self.prompt_tokens, self.completion_tokens, self.content_preview
+        )
+    }
 }
 
 /// A single completion choice.
@@ -76,6 +584,29 @@ pub struct CompletionChoice {
     /// Log probabilities (if requested)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logprobs: Option<serde_json::Value>,
+    /// Stop sequence that ended generation, when known
+    ///
+    /// Set from the provider's report (Anthropic's `stop_sequence`) or by
+    /// [`CompletionResponse::trim_stop_sequences`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_stop: Option<String>,
+    /// Sources the content cites (web-search models)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+impl CompletionChoice {
+    /// Whether the model declined to answer, leaving a refusal message
+    /// instead of content.
+    pub fn is_refusal(&self) -> bool {
+        self.message.refusal.is_some() && self.message.content.is_empty()
+    }
+
+    /// The content with footnote markers for its
+    /// [`annotations`](Self::annotations); see [`render_footnotes`].
+    pub fn content_with_footnotes(&self) -> String {
+        render_footnotes(&self.message.content, &self.annotations)
+    }
 }
 
 /// Reason why a completion finished.
@@ -92,6 +623,24 @@ pub enum FinishReason {
     ToolCalls,
 }
 
+impl FinishReason {
+    /// Wire name, as serialized (e.g. `"content_filter"`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stop => "stop",
+            Self::Length => "length",
+            Self::ContentFilter => "content_filter",
+            Self::ToolCalls => "tool_calls",
+        }
+    }
+}
+
+impl std::fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Token usage statistics.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Usage {
@@ -101,6 +650,12 @@ pub struct Usage {
     pub completion_tokens: u32,
     /// Total tokens used
     pub total_tokens: u32,
+    /// Prompt tokens written to the provider's prompt cache (Anthropic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u32>,
+    /// Prompt tokens served from the provider's prompt cache (Anthropic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u32>,
 }
 
 impl Usage {
@@ -110,6 +665,8 @@ impl Usage {
             prompt_tokens,
             completion_tokens,
             total_tokens: prompt_tokens + completion_tokens,
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: None,
         }
     }
 }
@@ -126,6 +683,10 @@ pub struct CompletionChunk {
     /// Unix timestamp of creation
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created: Option<i64>,
+    /// Token usage, on the final chunk when requested via
+    /// [`StreamOptions::include_usage`](crate::request::StreamOptions::include_usage)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
 }
 
 /// A delta in a streaming choice.
@@ -151,6 +712,79 @@ pub struct MessageDelta {
     pub content: Option<String>,
 }
 
+/// Builds a [`CompletionResponse`] from stream chunks.
+///
+/// Content is concatenated per choice index. Usage comes from the stream's
+/// final usage chunk (see
+/// [`StreamOptions`](crate::request::StreamOptions)) and is zero when the
+/// provider sent none; a choice without a finish reason finishes with
+/// [`FinishReason::Stop`].
+#[derive(Debug, Default)]
+pub struct ChunkAccumulator {
+    id: String,
+    model: String,
+    created: Option<i64>,
+    usage: Option<Usage>,
+    /// Content and finish reason per choice index
+    choices: Vec<(u32, String, Option<FinishReason>)>,
+}
+
+impl ChunkAccumulator {
+    /// Add the next chunk of the stream.
+    pub fn push(&mut self, chunk: CompletionChunk) {
+        if self.id.is_empty() {
+            self.id = chunk.id;
+        }
+        if self.model.is_empty() {
+            self.model = chunk.model;
+        }
+        self.created = self.created.or(chunk.created);
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        for delta in chunk.choices {
+            let position = match self.choices.iter().position(|(index, ..)| *index == delta.index) {
+                Some(position) => position,
+                None => {
+                    self.choices.push((delta.index, String::new(), None));
+                    self.choices.len() - 1
+                }
+            };
+            let choice = &mut self.choices[position];
+            if let Some(content) = delta.delta.content {
+                choice.1.push_str(&content);
+            }
+            choice.2 = delta.finish_reason.or(choice.2);
+        }
+    }
+
+    /// The response built from every chunk pushed so far.
+    pub fn finish(mut self) -> CompletionResponse {
+        self.choices.sort_by_key(|(index, ..)| *index);
+        CompletionResponse {
+            id: self.id,
+            model: self.model,
+            choices: self
+                .choices
+                .into_iter()
+                .map(|(index, content, finish_reason)| CompletionChoice {
+                    index,
+                    message: Message::assistant(content),
+                    finish_reason: finish_reason.unwrap_or(FinishReason::Stop),
+                    logprobs: None,
+                    matched_stop: None,
+                    annotations: Vec::new(),
+                })
+                .collect(),
+            usage: self.usage.unwrap_or_else(|| Usage::new(0, 0)),
+            created: self.created,
+            created_synthesized: false,
+            provider: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,9 +799,12 @@ mod tests {
                 message: Message::assistant("Hello!"),
                 finish_reason: FinishReason::Stop,
                 logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
             }],
             usage: Usage::new(10, 5),
             created: Some(1234567890),
+            created_synthesized: false,
             provider: Some("openai".to_string()),
         };
 
@@ -183,6 +820,7 @@ mod tests {
             choices: vec![],
             usage: Usage::new(10, 0),
             created: None,
+            created_synthesized: false,
             provider: None,
         };
 
@@ -190,6 +828,112 @@ mod tests {
         assert_eq!(response.first_choice(), None);
     }
 
+    fn response_with(choices: Vec<CompletionChoice>) -> CompletionResponse {
+        CompletionResponse {
+            id: "resp_123".to_string(),
+            model: "gpt-4".to_string(),
+            choices,
+            usage: Usage::new(10, 5),
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        }
+    }
+
+    fn choice(message: Message) -> CompletionChoice {
+        CompletionChoice {
+            index: 0,
+            message,
+            finish_reason: FinishReason::Stop,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_into_parts_and_content_accessors() {
+        let (id, choices, usage) = response_with(vec![choice(Message::assistant("Hello!"))]).into_parts();
+        assert_eq!(id, "resp_123");
+        assert_eq!(choices, vec![choice(Message::assistant("Hello!"))]);
+        assert_eq!(usage, Usage::new(10, 5));
+
+        let (_, choices, _) = response_with(Vec::new()).into_parts();
+        assert!(choices.is_empty());
+
+        assert_eq!(response_with(vec![choice(Message::assistant("Hello!"))]).unwrap_content(), "Hello!");
+        assert_eq!(response_with(vec![choice(Message::assistant(""))]).unwrap_content(), "");
+        assert_eq!(
+            response_with(vec![choice(Message::assistant("Hello!"))]).expect_content("no answer"),
+            "Hello!"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "on response resp_123 without choices")]
+    fn test_unwrap_content_panics_without_choices() {
+        response_with(Vec::new()).unwrap_content();
+    }
+
+    #[test]
+    #[should_panic(expected = "on refused response resp_123: I can't help with that.")]
+    fn test_unwrap_content_panics_on_refusal() {
+        let mut message = Message::assistant("");
+        message.refusal = Some("I can't help with that.".to_string());
+        response_with(vec![choice(message)]).unwrap_content();
+    }
+
+    #[test]
+    fn test_parse_content_as_strips_code_fences() {
+        let parse = |content: &str| {
+            response_with(vec![choice(Message::assistant(content))]).parse_content_as::<Vec<u32>>()
+        };
+        assert_eq!(parse("[1, 2]").unwrap(), vec![1, 2]);
+        assert_eq!(parse("```json\n[1, 2]\n```").unwrap(), vec![1, 2]);
+        assert_eq!(parse("  ```\n[1, 2]\n```\n").unwrap(), vec![1, 2]);
+        assert_eq!(parse("```json\n[1, 2]").unwrap(), vec![1, 2]);
+        assert!(matches!(parse("two numbers"), Err(crate::error::SimpleAgentsError::Serialization(_))));
+
+        let mut message = Message::assistant("");
+        message.refusal = Some("I can't help with that.".to_string());
+        let refused = response_with(vec![choice(message)]).parse_content_as::<Vec<u32>>();
+        assert_eq!(refused.unwrap_err().code(), crate::error::ErrorCode::Refused);
+        let empty = response_with(Vec::new()).parse_content_as::<Vec<u32>>();
+        assert_eq!(empty.unwrap_err().code(), crate::error::ErrorCode::InvalidResponse);
+    }
+
+    #[test]
+    #[should_panic(expected = "model gave no answer")]
+    fn test_expect_content_panics_with_message() {
+        response_with(Vec::new()).expect_content("model gave no answer");
+    }
+
+    #[test]
+    fn test_refusal_has_no_content() {
+        let mut message = Message::assistant("");
+        message.refusal = Some("I'm sorry, I can't help with that.".to_string());
+        let response = CompletionResponse {
+            id: "resp_123".to_string(),
+            model: "gpt-4o".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message,
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(10, 8),
+            created: None,
+            created_synthesized: false,
+            provider: None,
+        };
+
+        assert!(response.choices[0].is_refusal());
+        assert_eq!(response.content(), None);
+        assert_eq!(response.refusal(), Some("I'm sorry, I can't help with that."));
+    }
+
     #[test]
     fn test_usage_calculation() {
         let usage = Usage::new(100, 50);
@@ -223,9 +967,12 @@ mod tests {
                 message: Message::assistant("Hello!"),
                 finish_reason: FinishReason::Stop,
                 logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
             }],
             usage: Usage::new(10, 5),
             created: None,
+            created_synthesized: false,
             provider: None,
         };
 
@@ -248,6 +995,7 @@ mod tests {
                 finish_reason: None,
             }],
             created: Some(1234567890),
+            usage: None,
         };
 
         let json = serde_json::to_string(&chunk).unwrap();
@@ -275,11 +1023,236 @@ mod tests {
             choices: vec![],
             usage: Usage::new(10, 5),
             created: None,
+            created_synthesized: false,
             provider: None,
         };
 
         let json = serde_json::to_value(&response).unwrap();
-        assert!(!json.get("created").is_some());
-        assert!(!json.get("provider").is_some());
+        assert!(json.get("created").is_none());
+        assert!(json.get("provider").is_none());
+    }
+
+    fn response_created(created: Option<i64>) -> CompletionResponse {
+        CompletionResponse {
+            id: "resp_123".to_string(),
+            model: "gpt-4".to_string(),
+            choices: vec![],
+            usage: Usage::new(1, 1),
+            created,
+            created_synthesized: false,
+            provider: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_unix_timestamp() {
+        assert_eq!(normalize_unix_timestamp(0), 0);
+        assert_eq!(normalize_unix_timestamp(1_700_000_000), 1_700_000_000);
+        assert_eq!(normalize_unix_timestamp(MILLISECOND_TIMESTAMP_THRESHOLD), MILLISECOND_TIMESTAMP_THRESHOLD);
+        assert_eq!(normalize_unix_timestamp(1_700_000_000_999), 1_700_000_000);
+        assert!(unix_timestamp_now() > 1_700_000_000);
+    }
+
+    #[test]
+    fn test_created_synthesized_serialization() {
+        let response = response_created(Some(1_700_000_000));
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json.get("created_synthesized").is_none());
+
+        let parsed: CompletionResponse = serde_json::from_value(json).unwrap();
+        assert!(!parsed.created_synthesized);
+
+        let synthesized = CompletionResponse {
+            created_synthesized: true,
+            ..response
+        };
+        let json = serde_json::to_value(&synthesized).unwrap();
+        assert_eq!(json["created_synthesized"], true);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_created_at() {
+        use chrono::{TimeZone, Utc};
+
+        let expected = Utc.with_ymd_and_hms(2023, 11, 14, 22, 13, 20).unwrap();
+        assert_eq!(response_created(Some(1_700_000_000)).created_at(), Some(expected));
+
+        let millis = response_created(Some(1_700_000_000_250)).created_at().unwrap();
+        assert_eq!(millis, expected + chrono::Duration::milliseconds(250));
+
+        assert_eq!(response_created(None).created_at(), None);
+    }
+
+    #[test]
+    fn test_to_anthropic_wire() {
+        let mut response = response_created(None);
+        response.choices.push(CompletionChoice {
+            index: 0,
+            message: Message::assistant("Hi"),
+            finish_reason: FinishReason::ContentFilter,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        });
+
+        let wire = response.to_anthropic_wire();
+        assert_eq!(wire["type"], "message");
+        assert_eq!(wire["role"], "assistant");
+        assert_eq!(wire["content"], serde_json::json!([{"type": "text", "text": "Hi"}]));
+        assert_eq!(wire["stop_reason"], "refusal");
+        assert!(wire["stop_sequence"].is_null());
+        assert_eq!(wire["usage"], serde_json::json!({"input_tokens": 1, "output_tokens": 1}));
+
+        let empty = response_created(None).to_anthropic_wire();
+        assert_eq!(empty["content"], serde_json::json!([]));
+        assert!(empty["stop_reason"].is_null());
+    }
+
+    #[test]
+    fn test_to_anthropic_wire_tool_calls() {
+        let mut response = response_created(None);
+        let message = Message::assistant("Checking.")
+            .with_tool_call(crate::tool::ToolCall::function("toolu_1", "get_weather", r#"{"city":"Paris"}"#))
+            .with_tool_call(crate::tool::ToolCall::function("toolu_2", "get_time", ""));
+        response.choices.push(CompletionChoice {
+            index: 0,
+            message,
+            finish_reason: FinishReason::ToolCalls,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        });
+
+        let wire = response.to_anthropic_wire();
+        assert_eq!(
+            wire["content"],
+            serde_json::json!([
+                {"type": "text", "text": "Checking."},
+                {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Paris"}},
+                {"type": "tool_use", "id": "toolu_2", "name": "get_time", "input": {}},
+            ])
+        );
+        assert_eq!(wire["stop_reason"], "tool_use");
+    }
+
+    #[test]
+    fn test_display_snapshots() {
+        let mut response = response_created(None);
+        assert_eq!(response.to_string(), r#"gpt-4 no_choices tokens=1+1 """#);
+
+        response.provider = Some("openai".to_string());
+        response.choices.push(CompletionChoice {
+            index: 0,
+            message: Message::assistant("Line one.\n\n  Line \"two\"."),
+            finish_reason: FinishReason::ContentFilter,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        });
+        assert_eq!(
+            response.to_string(),
+            r#"openai/gpt-4 content_filter tokens=1+1 "Line one. Line \"two\".""#
+        );
+
+        response.choices[0].message = Message::assistant("é".repeat(100));
+        response.choices[0].finish_reason = FinishReason::Length;
+        let expected = format!(r#"openai/gpt-4 length tokens=1+1 "{}…""#, "é".repeat(80));
+        assert_eq!(response.to_string(), expected);
+    }
+
+    #[test]
+    fn test_summary_serialization() {
+        let mut response = response_created(None);
+        response.choices.push(CompletionChoice {
+            index: 0,
+            message: Message::assistant("Hi"),
+            finish_reason: FinishReason::ToolCalls,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        });
+
+        let json = serde_json::to_value(response.summary()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "resp_123",
+                "model": "gpt-4",
+                "finish_reason": "tool_calls",
+                "prompt_tokens": 1,
+                "completion_tokens": 1,
+                "total_tokens": 2,
+                "content_preview": "Hi",
+            })
+        );
+
+        let parsed: ResponseSummary = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, response.summary());
+    }
+
+    fn stop_response(content: &str, finish_reason: FinishReason) -> CompletionResponse {
+        let mut response = response_created(None);
+        response.choices.push(CompletionChoice {
+            index: 0,
+            message: Message::assistant(content),
+            finish_reason,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        });
+        response
+    }
+
+    #[test]
+    fn test_trim_stop_sequences_suffix_only() {
+        let stops = vec!["END".to_string(), "\n\nEND".to_string()];
+
+        let mut echoed = stop_response("Done.\n\nEND", FinishReason::Stop);
+        echoed.trim_stop_sequences(&stops);
+        assert_eq!(echoed.content(), Some("Done."));
+        assert_eq!(echoed.choices[0].matched_stop.as_deref(), Some("\n\nEND"));
+
+        let mid_content = stop_response("The END is near", FinishReason::Stop);
+        let mut trimmed = mid_content.clone();
+        trimmed.trim_stop_sequences(&stops);
+        assert_eq!(trimmed, mid_content);
+
+        let mut truncated = stop_response("Out of tokens END", FinishReason::Length);
+        truncated.trim_stop_sequences(&stops);
+        assert_eq!(truncated.content(), Some("Out of tokens END"));
+        assert_eq!(truncated.choices[0].matched_stop, None);
+
+        let mut empty_stop = stop_response("text", FinishReason::Stop);
+        empty_stop.trim_stop_sequences(&[String::new()]);
+        assert_eq!(empty_stop.content(), Some("text"));
+    }
+
+    #[test]
+    fn test_trim_stop_sequences_unicode() {
+        let stops = vec!["。終わり".to_string()];
+
+        let mut response = stop_response("こんにちは。終わり", FinishReason::Stop);
+        response.trim_stop_sequences(&stops);
+        assert_eq!(response.content(), Some("こんにちは"));
+        assert_eq!(response.choices[0].matched_stop.as_deref(), Some("。終わり"));
+
+        // Shares a final character with the stop but is not a suffix match
+        let mut partial = stop_response("終わり", FinishReason::Stop);
+        partial.trim_stop_sequences(&stops);
+        assert_eq!(partial.content(), Some("終わり"));
+    }
+
+    #[test]
+    fn test_trim_keeps_reported_matched_stop() {
+        let mut response = stop_response("Answer: 42\nObservation:", FinishReason::Stop);
+        response.choices[0].matched_stop = Some("Observation:".to_string());
+        response.trim_stop_sequences(&["\nObservation:".to_string()]);
+        assert_eq!(response.content(), Some("Answer: 42"));
+        assert_eq!(response.choices[0].matched_stop.as_deref(), Some("Observation:"));
+
+        let wire = response.to_anthropic_wire();
+        assert_eq!(wire["stop_reason"], "stop_sequence");
+        assert_eq!(wire["stop_sequence"], "Observation:");
     }
 }
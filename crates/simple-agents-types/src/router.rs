@@ -119,7 +119,8 @@ impl RoutingMode {
 }
 
 /// Provider health status.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ProviderHealth {
     /// Provider is healthy
     Healthy,
@@ -0,0 +1,327 @@
+//! Tool (function calling) definitions.
+//!
+//! Provides OpenAI-compatible tool definitions and tool choice settings,
+//! and a [`ToolRegistry`] of handlers for
+//! [`ProviderExt::complete_with_tools`](crate::provider::ProviderExt::complete_with_tools).
+
+use crate::error::{Result, ValidationError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Kind of tool. Only functions are supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolType {
+    /// A callable function
+    Function,
+}
+
+/// A function the model may call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionDefinition {
+    /// Function name
+    pub name: String,
+    /// What the function does, to help the model decide when to call it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema of the function's arguments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+/// A tool the model may call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    /// Tool kind
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+    /// Function definition
+    pub function: FunctionDefinition,
+}
+
+impl ToolDefinition {
+    /// Create a function tool.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::tool::ToolDefinition;
+    ///
+    /// let tool = ToolDefinition::function(
+    ///     "get_weather",
+    ///     "Get the current weather for a city",
+    ///     serde_json::json!({
+    ///         "type": "object",
+    ///         "properties": { "city": { "type": "string" } },
+    ///         "required": ["city"]
+    ///     }),
+    /// );
+    /// assert_eq!(tool.function.name, "get_weather");
+    /// ```
+    pub fn function(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            tool_type: ToolType::Function,
+            function: FunctionDefinition {
+                name: name.into(),
+                description: Some(description.into()),
+                parameters: Some(parameters),
+            },
+        }
+    }
+}
+
+/// Controls whether and which tool the model calls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoice {
+    /// Never call a tool
+    None,
+    /// Let the model decide
+    Auto,
+    /// Call at least one tool
+    Required,
+    /// Call the named function
+    Function {
+        /// Function name
+        name: String,
+    },
+}
+
+/// A function call requested by the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    /// Function name
+    pub name: String,
+    /// Arguments as a JSON-encoded string
+    pub arguments: String,
+}
+
+/// A tool call made by an assistant message.
+///
+/// Serializes in OpenAI's `tool_calls` format; the matching
+/// [`Message::tool`](crate::message::Message::tool) result carries the same
+/// `id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Call identifier
+    pub id: String,
+    /// Tool kind
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+    /// The requested call
+    pub function: FunctionCall,
+}
+
+impl ToolCall {
+    /// Create a function call.
+    ///
+    /// # Example
+    /// ```
+    /// use simple_agents_types::tool::ToolCall;
+    ///
+    /// let call = ToolCall::function("call_1", "get_weather", r#"{"city":"Paris"}"#);
+    /// assert_eq!(call.function.name, "get_weather");
+    /// ```
+    pub fn function(id: impl Into<String>, name: impl Into<String>, arguments: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            tool_type: ToolType::Function,
+            function: FunctionCall {
+                name: name.into(),
+                arguments: arguments.into(),
+            },
+        }
+    }
+}
+
+/// Runs a tool when the model calls it.
+///
+/// Implemented for closures taking the call's arguments and returning the
+/// result to send back to the model.
+#[async_trait]
+pub trait ToolHandler: Send + Sync {
+    /// Run the tool with the model's `arguments`.
+    async fn call(&self, arguments: serde_json::Value) -> Result<String>;
+}
+
+#[async_trait]
+impl<F> ToolHandler for F
+where
+    F: Fn(serde_json::Value) -> Result<String> + Send + Sync,
+{
+    async fn call(&self, arguments: serde_json::Value) -> Result<String> {
+        self(arguments)
+    }
+}
+
+/// Tools offered to the model, with the handlers that run them.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::tool::{ToolCall, ToolDefinition, ToolRegistry};
+///
+/// # #[tokio::main]
+/// # async fn main() -> simple_agents_types::Result<()> {
+/// let add = ToolDefinition::function("add", "Add two numbers", serde_json::json!({"type": "object"}));
+/// let registry = ToolRegistry::new().register(add, |args: serde_json::Value| {
+///     Ok((args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0)).to_string())
+/// });
+///
+/// let call = ToolCall::function("call_1", "add", r#"{"a": 2, "b": 3}"#);
+/// assert_eq!(registry.call(&call).await?, "5");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: Vec<(ToolDefinition, Arc<dyn ToolHandler>)>,
+    max_rounds: usize,
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.tools.iter().map(|(tool, _)| &tool.function.name).collect::<Vec<_>>())
+            .field("max_rounds", &self.max_rounds)
+            .finish()
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolRegistry {
+    /// Model calls allowed per completion unless set with
+    /// [`with_max_rounds`](Self::with_max_rounds)
+    pub const DEFAULT_MAX_ROUNDS: usize = 8;
+
+    /// Create a registry with no tools.
+    pub fn new() -> Self {
+        Self {
+            tools: Vec::new(),
+            max_rounds: Self::DEFAULT_MAX_ROUNDS,
+        }
+    }
+
+    /// Offer `definition` to the model and run `handler` when it is called
+    /// (builder pattern).
+    ///
+    /// Replaces an earlier tool with the same name.
+    pub fn register(mut self, definition: ToolDefinition, handler: impl ToolHandler + 'static) -> Self {
+        self.tools.retain(|(tool, _)| tool.function.name != definition.function.name);
+        self.tools.push((definition, Arc::new(handler)));
+        self
+    }
+
+    /// Set how many times the model is called before giving up on a final
+    /// answer (builder pattern).
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    /// Model calls allowed per completion.
+    pub fn max_rounds(&self) -> usize {
+        self.max_rounds
+    }
+
+    /// Definitions of the registered tools, in registration order.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.iter().map(|(tool, _)| tool.clone()).collect()
+    }
+
+    /// Run the handler for `call`.
+    ///
+    /// Empty arguments are passed as `{}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError`] if no tool has the called name,
+    /// [`SimpleAgentsError::Serialization`](crate::error::SimpleAgentsError::Serialization)
+    /// if the arguments are not valid JSON, and any error from the handler.
+    pub async fn call(&self, call: &ToolCall) -> Result<String> {
+        let name = &call.function.name;
+        let (_, handler) = self
+            .tools
+            .iter()
+            .find(|(tool, _)| &tool.function.name == name)
+            .ok_or_else(|| ValidationError::new(format!("Unknown tool {:?}", name)))?;
+        let arguments = match call.function.arguments.trim() {
+            "" => serde_json::json!({}),
+            arguments => serde_json::from_str(arguments)?,
+        };
+        handler.call(arguments).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_definition_serialization() {
+        let tool = ToolDefinition::function("lookup", "Look something up", serde_json::json!({"type": "object"}));
+        let json = serde_json::to_value(&tool).unwrap();
+
+        assert_eq!(json["type"], "function");
+        assert_eq!(json["function"]["name"], "lookup");
+        assert_eq!(json["function"]["parameters"]["type"], "object");
+
+        let parsed: ToolDefinition = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, tool);
+    }
+
+    #[test]
+    fn test_tool_choice_serialization() {
+        assert_eq!(serde_json::to_value(ToolChoice::Auto).unwrap(), "auto");
+        assert_eq!(serde_json::to_value(ToolChoice::Required).unwrap(), "required");
+        assert_eq!(
+            serde_json::to_value(ToolChoice::Function { name: "lookup".to_string() }).unwrap(),
+            serde_json::json!({"function": {"name": "lookup"}})
+        );
+    }
+
+    #[test]
+    fn test_tool_call_serialization() {
+        let call = ToolCall::function("call_1", "lookup", r#"{"q":"rust"}"#);
+        let json = serde_json::to_value(&call).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "lookup", "arguments": "{\"q\":\"rust\"}"}
+            })
+        );
+        assert_eq!(serde_json::from_value::<ToolCall>(json).unwrap(), call);
+    }
+
+    #[tokio::test]
+    async fn test_tool_registry() {
+        let echo = |name: &str| ToolDefinition::function(name, "Echo the arguments", serde_json::json!({}));
+        let registry = ToolRegistry::new()
+            .register(echo("echo"), |args: serde_json::Value| Ok(format!("v1 {}", args)))
+            .register(echo("other"), |_: serde_json::Value| Ok(String::new()))
+            .register(echo("echo"), |args: serde_json::Value| Ok(format!("v2 {}", args)));
+
+        let names: Vec<_> = registry.definitions().into_iter().map(|tool| tool.function.name).collect();
+        assert_eq!(names, vec!["other", "echo"]);
+
+        let call = |name: &str, arguments: &str| ToolCall::function("call_1", name, arguments);
+        assert_eq!(registry.call(&call("echo", r#"{"a":1}"#)).await.unwrap(), r#"v2 {"a":1}"#);
+        // Some models send no arguments for parameterless tools
+        assert_eq!(registry.call(&call("echo", "")).await.unwrap(), "v2 {}");
+
+        let unknown = registry.call(&call("missing", "{}")).await.unwrap_err();
+        assert!(unknown.to_string().contains("missing"), "{}", unknown);
+        let malformed = registry.call(&call("echo", "{not json")).await.unwrap_err();
+        assert!(matches!(malformed, crate::error::SimpleAgentsError::Serialization(_)));
+    }
+}
@@ -0,0 +1,289 @@
+//! End-user wording for errors.
+//!
+//! Provider error messages such as `insufficient_quota` or
+//! `overloaded_error` are written for developers and often carry URLs,
+//! request fragments or key prefixes. [`UserFacingError`] maps each error
+//! to an [`ErrorClass`] and returns a fixed sentence for that class, so
+//! nothing from the error itself ever reaches the user.
+
+use crate::error::{ProviderError, SimpleAgentsError};
+use std::collections::HashMap;
+
+/// Locale used when no registered locale matches.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Coarse category of an error, as far as an end user is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// Rate limit or quota exceeded
+    RateLimited,
+    /// Credentials were rejected
+    Authentication,
+    /// The requested model does not exist or is not available
+    ModelUnavailable,
+    /// The request timed out
+    Timeout,
+    /// The provider failed or no provider was available
+    ServiceUnavailable,
+    /// The request was rejected as invalid
+    InvalidRequest,
+    /// The request or an attachment was too large
+    PayloadTooLarge,
+    /// The model does not support something the request needs
+    Unsupported,
+    /// The provider's response could not be used
+    InvalidResponse,
+    /// The provider could not be reached
+    Network,
+    /// The application is misconfigured
+    Configuration,
+    /// Anything else
+    Internal,
+}
+
+impl ErrorClass {
+    /// Every class, in declaration order.
+    pub const ALL: [ErrorClass; 12] = [
+        Self::RateLimited,
+        Self::Authentication,
+        Self::ModelUnavailable,
+        Self::Timeout,
+        Self::ServiceUnavailable,
+        Self::InvalidRequest,
+        Self::PayloadTooLarge,
+        Self::Unsupported,
+        Self::InvalidResponse,
+        Self::Network,
+        Self::Configuration,
+        Self::Internal,
+    ];
+
+    /// Classify an error, looking through any added context.
+    pub fn of(error: &SimpleAgentsError) -> Self {
+        match error.root() {
+            SimpleAgentsError::Provider(error) => Self::of_provider(error),
+            SimpleAgentsError::Network(_) => Self::Network,
+            SimpleAgentsError::Config(_) => Self::Configuration,
+            SimpleAgentsError::Validation(_) => Self::InvalidRequest,
+            SimpleAgentsError::Healing(_) => Self::InvalidResponse,
+            SimpleAgentsError::Routing(_) => Self::ServiceUnavailable,
+            SimpleAgentsError::Cache(_)
+            | SimpleAgentsError::Serialization(_)
+            | SimpleAgentsError::WithContext(_) => Self::Internal,
+        }
+    }
+
+    /// Classify a provider error.
+    ///
+    /// The match is exhaustive, so a new [`ProviderError`] variant does not
+    /// compile until it is given a class.
+    pub fn of_provider(error: &ProviderError) -> Self {
+        match error {
+            ProviderError::RateLimit { .. } => Self::RateLimited,
+            ProviderError::InvalidApiKey => Self::Authentication,
+            ProviderError::ModelNotFound(_) => Self::ModelUnavailable,
+            ProviderError::Timeout(_) => Self::Timeout,
+            ProviderError::ServerError(_) => Self::ServiceUnavailable,
+            ProviderError::BadRequest(_) | ProviderError::Refusal(_) => Self::InvalidRequest,
+            ProviderError::PayloadTooLarge(_) => Self::PayloadTooLarge,
+            ProviderError::Unsupported { .. } => Self::Unsupported,
+            ProviderError::InvalidResponse(_) => Self::InvalidResponse,
+        }
+    }
+
+    /// Built-in English message for this class.
+    pub fn default_message(&self) -> &'static str {
+        match self {
+            Self::RateLimited => "The service is busy right now. Please try again in a moment.",
+            Self::Authentication | Self::Configuration => {
+                "The service is not set up correctly. Please contact support."
+            }
+            Self::ModelUnavailable => "The selected model is not available.",
+            Self::Timeout => "The request took too long. Please try again.",
+            Self::ServiceUnavailable => "The service is temporarily unavailable. Please try again later.",
+            Self::InvalidRequest => "The request could not be processed. Please rephrase it and try again.",
+            Self::PayloadTooLarge => "The message or attachment is too large.",
+            Self::Unsupported => "This feature is not available with the selected model.",
+            Self::InvalidResponse => "The service returned an unexpected response. Please try again.",
+            Self::Network => "The service could not be reached. Please check your connection and try again.",
+            Self::Internal => "Something went wrong. Please try again.",
+        }
+    }
+}
+
+/// Table of end-user messages by locale and [`ErrorClass`].
+///
+/// English messages are built in. Register overrides and further locales
+/// with [`with_message`](Self::with_message). Lookups try the exact locale
+/// (`pt-BR`), then its language (`pt`), then [`DEFAULT_LOCALE`]; locale
+/// tags are case-insensitive and accept `_` for `-`.
+///
+/// Messages are only ever taken from the table, never from the error, so
+/// URLs, key fragments and request bodies in provider errors are never
+/// shown.
+///
+/// # Example
+/// ```
+/// use simple_agents_types::error::{ProviderError, SimpleAgentsError};
+/// use simple_agents_types::user_facing::{ErrorClass, UserFacingError};
+///
+/// let messages = UserFacingError::new()
+///     .with_message("de", ErrorClass::RateLimited, "Der Dienst ist gerade ausgelastet.");
+///
+/// let error = SimpleAgentsError::Provider(ProviderError::RateLimit { retry_after: None });
+/// assert_eq!(messages.user_message(&error, "de-AT"), "Der Dienst ist gerade ausgelastet.");
+/// assert_eq!(
+///     messages.user_message(&error, "fr"),
+///     "The service is busy right now. Please try again in a moment."
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UserFacingError {
+    overrides: HashMap<String, HashMap<ErrorClass, String>>,
+}
+
+impl UserFacingError {
+    /// Table with only the built-in English messages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the message for `class` in `locale` (builder pattern).
+    ///
+    /// Registering under [`DEFAULT_LOCALE`] overrides the built-in message.
+    pub fn with_message(
+        mut self,
+        locale: &str,
+        class: ErrorClass,
+        message: impl Into<String>,
+    ) -> Self {
+        self.overrides
+            .entry(normalize_locale(locale))
+            .or_default()
+            .insert(class, message.into());
+        self
+    }
+
+    /// Message for `class` in `locale`, falling back as described above.
+    pub fn message(&self, class: ErrorClass, locale: &str) -> &str {
+        let locale = normalize_locale(locale);
+        let language = locale.split('-').next().unwrap_or_default();
+        let found = [locale.as_str(), language, DEFAULT_LOCALE]
+            .into_iter()
+            .find_map(|tag| self.overrides.get(tag)?.get(&class));
+        found.map_or_else(|| class.default_message(), String::as_str)
+    }
+
+    /// Message to show an end user for `error`.
+    pub fn user_message(&self, error: &SimpleAgentsError, locale: &str) -> String {
+        self.message(ErrorClass::of(error), locale).to_string()
+    }
+}
+
+/// Built-in message to show an end user for `error`.
+///
+/// Shorthand for [`UserFacingError::user_message`] on a table without
+/// overrides; only [`DEFAULT_LOCALE`] is built in.
+pub fn user_message(error: &SimpleAgentsError, locale: &str) -> String {
+    UserFacingError::new().user_message(error, locale)
+}
+
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Feature;
+    use crate::error::{ErrorContext, ValidationError};
+    use std::time::Duration;
+
+    /// One instance of every `ProviderError` variant, stuffed with details
+    /// that must not leak.
+    fn provider_errors() -> Vec<ProviderError> {
+        let detail = "https://api.internal.example.com/v1 sk-proj-abc123 {\"messages\": []}".to_string();
+        let errors = vec![
+            ProviderError::RateLimit {
+                retry_after: Some(Duration::from_secs(12)),
+            },
+            ProviderError::InvalidApiKey,
+            ProviderError::ModelNotFound(detail.clone()),
+            ProviderError::Timeout(Duration::from_secs(30)),
+            ProviderError::ServerError(detail.clone()),
+            ProviderError::BadRequest(detail.clone()),
+            ProviderError::PayloadTooLarge(detail.clone()),
+            ProviderError::Unsupported {
+                provider: detail.clone(),
+                feature: Feature::Vision,
+            },
+            ProviderError::InvalidResponse(detail.clone()),
+            ProviderError::Refusal(detail),
+        ];
+        // No wildcard: a new variant fails to compile here until it is
+        // added to the list above
+        for error in &errors {
+            match error {
+                ProviderError::RateLimit { .. }
+                | ProviderError::InvalidApiKey
+                | ProviderError::ModelNotFound(_)
+                | ProviderError::Timeout(_)
+                | ProviderError::ServerError(_)
+                | ProviderError::BadRequest(_)
+                | ProviderError::PayloadTooLarge(_)
+                | ProviderError::Unsupported { .. }
+                | ProviderError::InvalidResponse(_)
+                | ProviderError::Refusal(_) => {}
+            }
+        }
+        errors
+    }
+
+    #[test]
+    fn test_every_provider_error_has_a_message() {
+        for error in provider_errors() {
+            let class = ErrorClass::of_provider(&error);
+            assert_ne!(class, ErrorClass::Internal, "{:?} is unclassified", error);
+
+            let message = user_message(&SimpleAgentsError::Provider(error), "en");
+            assert_eq!(message, class.default_message());
+            for secret in ["https://", "sk-proj", "messages", "12"] {
+                assert!(!message.contains(secret), "{:?} leaks {:?}", message, secret);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_class_has_a_default_message() {
+        for class in ErrorClass::ALL {
+            assert!(class.default_message().ends_with('.'));
+        }
+    }
+
+    #[test]
+    fn test_classifies_through_context() {
+        let error = SimpleAgentsError::WithContext(Box::new(
+            SimpleAgentsError::Provider(ProviderError::InvalidApiKey)
+                .with_context(ErrorContext::new().provider("openai")),
+        ));
+        assert_eq!(ErrorClass::of(&error), ErrorClass::Authentication);
+        assert!(!user_message(&error, "en").contains("openai"));
+
+        let error = SimpleAgentsError::Validation(ValidationError::new("messages cannot be empty"));
+        assert_eq!(ErrorClass::of(&error), ErrorClass::InvalidRequest);
+    }
+
+    #[test]
+    fn test_locale_fallback() {
+        let messages = UserFacingError::new()
+            .with_message("pt", ErrorClass::Timeout, "A solicitação demorou demais.")
+            .with_message("pt_BR", ErrorClass::Timeout, "A requisição demorou demais.")
+            .with_message("en", ErrorClass::Internal, "Oops.");
+
+        assert_eq!(messages.message(ErrorClass::Timeout, "pt-BR"), "A requisição demorou demais.");
+        assert_eq!(messages.message(ErrorClass::Timeout, "PT-pt"), "A solicitação demorou demais.");
+        assert_eq!(messages.message(ErrorClass::Timeout, "ja"), ErrorClass::Timeout.default_message());
+        // Missing classes fall back to English, including English overrides
+        assert_eq!(messages.message(ErrorClass::Internal, "pt-BR"), "Oops.");
+    }
+}
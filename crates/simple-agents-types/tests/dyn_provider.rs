@@ -0,0 +1,166 @@
+//! Object-safety tests for the `Provider` trait.
+//!
+//! These fail to compile if a method that breaks dyn compatibility (e.g. one
+//! with generic parameters) is added to `Provider`; such methods belong on
+//! `ProviderExt`.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use simple_agents_types::prelude::*;
+use std::sync::Arc;
+
+/// Provider replying with a fixed content string
+struct FixedProvider {
+    content: &'static str,
+}
+
+#[async_trait]
+impl Provider for FixedProvider {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        Ok(ProviderRequest::new("https://api.example.com").with_body(serde_json::json!({ "model": req.model })))
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        Ok(ProviderResponse::new(200, req.body))
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        Ok(CompletionResponse {
+            id: "resp-1".to_string(),
+            model: resp.body["model"].as_str().unwrap_or_default().to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(self.content),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(3, 4),
+            created: None,
+            created_synthesized: false,
+            provider: Some(self.name().to_string()),
+        })
+    }
+
+    fn supported_models(&self) -> &'static [&'static str] {
+        &["fixed-1"]
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Answer {
+    value: u32,
+}
+
+fn request() -> CompletionRequest {
+    CompletionRequest::builder()
+        .model("fixed-1")
+        .message(Message::user("What is 6 * 7?"))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_boxed_provider() {
+    let provider: Box<dyn Provider> = Box::new(FixedProvider { content: "42" });
+
+    assert_eq!(provider.name(), "fixed");
+    let response = provider.complete(&request()).await.unwrap();
+    assert_eq!(response.content(), Some("42"));
+    assert_eq!(response.model, "fixed-1");
+}
+
+#[tokio::test]
+async fn test_arc_provider_shared_across_tasks() {
+    let provider: Arc<dyn Provider> = Arc::new(FixedProvider { content: "42" });
+
+    let handles: Vec<_> = (0..3)
+        .map(|_| {
+            let provider = provider.clone();
+            tokio::spawn(async move { provider.complete(&request()).await })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap().unwrap().content(), Some("42"));
+    }
+}
+
+/// Fails to compile unless `P` implements `Provider`
+fn assert_provider<P: Provider>() {}
+
+#[test]
+fn test_smart_pointers_implement_provider() {
+    assert_provider::<Arc<FixedProvider>>();
+    assert_provider::<Box<FixedProvider>>();
+    assert_provider::<Arc<dyn Provider>>();
+    assert_provider::<Box<dyn Provider>>();
+    assert_provider::<Arc<Box<dyn Provider>>>();
+
+    // Pointers coerce to trait objects themselves
+    let shared = Arc::new(FixedProvider { content: "42" });
+    let as_dyn: &dyn Provider = &shared;
+    assert_eq!(as_dyn.name(), "fixed");
+    let boxed: Box<dyn Provider> = Box::new(shared);
+    assert!(boxed.supports_model("fixed-1"));
+    assert!(!boxed.supports_model("other"));
+}
+
+/// Completes with any provider, as a generic library function would
+async fn ask<P: Provider>(provider: P) -> String {
+    provider.complete(&request()).await.unwrap().content().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_arc_provider_passed_by_value() {
+    let shared = Arc::new(FixedProvider { content: "42" });
+
+    let handles: Vec<_> = (0..3).map(|_| tokio::spawn(ask(shared.clone()))).collect();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap(), "42");
+    }
+    assert_eq!(ask(Box::new(FixedProvider { content: "7" })).await, "7");
+    assert_eq!(Arc::strong_count(&shared), 1);
+}
+
+#[tokio::test]
+async fn test_heterogeneous_provider_list() {
+    let providers: Vec<Box<dyn Provider>> = vec![
+        Box::new(FixedProvider { content: "one" }),
+        Box::new(FixedProvider { content: "two" }),
+    ];
+
+    let mut contents = Vec::new();
+    for provider in &providers {
+        contents.push(provider.complete(&request()).await.unwrap().content().unwrap().to_string());
+    }
+    assert_eq!(contents, vec!["one", "two"]);
+}
+
+#[tokio::test]
+async fn test_provider_ext_on_trait_objects() {
+    let boxed: Box<dyn Provider> = Box::new(FixedProvider { content: r#"{"value": 42}"# });
+    let answer: Answer = boxed.complete_json_request(&request()).await.unwrap();
+    assert_eq!(answer, Answer { value: 42 });
+
+    let shared: Arc<dyn Provider> = Arc::new(FixedProvider { content: r#"{"value": 7}"# });
+    let answer: Answer = shared.complete_json_request(&request()).await.unwrap();
+    assert_eq!(answer, Answer { value: 7 });
+
+    // Also available on concrete types
+    let concrete = FixedProvider { content: "not json" };
+    let err = concrete.complete_json_request::<Answer>(&request()).await.unwrap_err();
+    assert!(matches!(err, SimpleAgentsError::Serialization(_)));
+}
+
+#[tokio::test]
+async fn test_default_prefill_cache_is_noop() {
+    let provider: Box<dyn Provider> = Box::new(FixedProvider { content: "42" });
+    let token = provider.prefill_cache("You are terse.").await.unwrap();
+    assert!(token.is_noop());
+    assert_eq!(request().with_prefill_token(&token), request());
+}
@@ -35,9 +35,12 @@ fn test_complete_request_response_cycle() {
             message: Message::assistant("Hi there!"),
             finish_reason: FinishReason::Stop,
             logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
         }],
         usage: Usage::new(20, 10),
         created: Some(1234567890),
+        created_synthesized: false,
         provider: Some("openai".to_string()),
     };
 
@@ -210,6 +213,7 @@ fn test_streaming_types() {
             finish_reason: None,
         }],
         created: Some(1234567890),
+        usage: None,
     };
 
     // Serialize and deserialize
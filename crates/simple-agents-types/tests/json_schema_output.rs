@@ -0,0 +1,104 @@
+//! Structured output with a schema derived by `schemars`.
+
+#![cfg(feature = "schemars")]
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use simple_agents_types::prelude::*;
+
+#[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+struct Forecast {
+    city: String,
+    celsius: i32,
+    /// Short summary, if any
+    summary: Option<String>,
+    wind: Wind,
+}
+
+#[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+struct Wind {
+    speed: f64,
+}
+
+fn request(prompt: &str) -> CompletionRequest {
+    CompletionRequest::builder()
+        .model("gpt-4o")
+        .message(Message::user(prompt))
+        .build()
+        .unwrap()
+}
+
+fn json_schema(request: &CompletionRequest) -> &JsonSchemaFormat {
+    match &request.response_format {
+        Some(ResponseFormat::JsonSchema { json_schema }) => json_schema,
+        other => panic!("expected a JSON schema response format, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_strict_schema_closes_every_object() {
+    let request = request("Weather in Oslo as JSON").with_json_schema_output::<Forecast>(true).unwrap();
+    let format = json_schema(&request);
+    assert_eq!(format.name, "Forecast");
+    assert_eq!(format.strict, Some(true));
+
+    let schema = &format.schema;
+    assert!(schema.get("$schema").is_none());
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["additionalProperties"], false);
+    assert_eq!(schema["required"], json!(["celsius", "city", "summary", "wind"]));
+    assert_eq!(schema["properties"]["summary"]["type"], json!(["string", "null"]));
+
+    let wind = &schema["definitions"]["Wind"];
+    assert_eq!(wind["additionalProperties"], false);
+    assert_eq!(wind["required"], json!(["speed"]));
+}
+
+#[test]
+fn test_non_strict_schema_is_left_as_derived() {
+    let request = request("Weather in Oslo as JSON").with_json_schema_output::<Forecast>(false).unwrap();
+    let format = json_schema(&request);
+    assert_eq!(format.strict, Some(false));
+    assert!(format.schema.get("additionalProperties").is_none());
+    assert_eq!(format.schema["required"], json!(["celsius", "city", "wind"]));
+}
+
+#[test]
+fn test_schema_output_needs_a_json_prompt() {
+    let err = request("Weather in Oslo").with_json_schema_output::<Forecast>(true).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidRequest);
+}
+
+#[test]
+fn test_reply_parses_into_the_schema_type() {
+    let forecast = json!({"city": "Oslo", "celsius": 12, "summary": null, "wind": {"speed": 3.5}});
+    let reply = format!("```json\n{}\n```", forecast);
+    let response = CompletionResponse {
+        id: "resp-1".to_string(),
+        model: "gpt-4o".to_string(),
+        choices: vec![CompletionChoice {
+            index: 0,
+            message: Message::assistant(reply),
+            finish_reason: FinishReason::Stop,
+            logprobs: None,
+            matched_stop: None,
+            annotations: Vec::new(),
+        }],
+        usage: Usage::new(20, 18),
+        created: None,
+        created_synthesized: false,
+        provider: None,
+    };
+
+    let forecast: Forecast = response.parse_content_as().unwrap();
+    assert_eq!(
+        forecast,
+        Forecast {
+            city: "Oslo".to_string(),
+            celsius: 12,
+            summary: None,
+            wind: Wind { speed: 3.5 },
+        }
+    );
+}
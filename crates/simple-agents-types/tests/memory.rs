@@ -0,0 +1,190 @@
+//! History compression with `SummarizingMemory`.
+
+use async_trait::async_trait;
+use simple_agents_types::memory::{history_tokens, is_summary, SummarizingMemory, SUMMARY_PREFIX};
+use simple_agents_types::prelude::*;
+use std::sync::{Arc, Mutex};
+
+/// Summarizer answering `summary <n>` and recording the transcripts it is
+/// sent, or failing every call
+#[derive(Default)]
+struct MockSummarizer {
+    fail: bool,
+    transcripts: Mutex<Vec<String>>,
+}
+
+impl MockSummarizer {
+    fn failing() -> Self {
+        Self {
+            fail: true,
+            ..Self::default()
+        }
+    }
+
+    fn transcripts(&self) -> Vec<String> {
+        self.transcripts.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Provider for MockSummarizer {
+    fn name(&self) -> &str {
+        "summarizer"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        let transcript = req.messages.last().unwrap().content.clone();
+        self.transcripts.lock().unwrap().push(transcript);
+        Ok(ProviderRequest::new("https://api.example.com"))
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        if self.fail {
+            return Err(ProviderError::ServerError("overloaded".to_string()).into());
+        }
+        Ok(ProviderResponse::new(200, req.body))
+    }
+
+    fn transform_response(&self, _resp: ProviderResponse) -> Result<CompletionResponse> {
+        let n = self.transcripts.lock().unwrap().len();
+        Ok(CompletionResponse {
+            id: "resp-1".to_string(),
+            model: "cheap-1".to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: Message::assistant(format!("summary {}", n)),
+                finish_reason: FinishReason::Stop,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(50, 5),
+            created: None,
+            created_synthesized: false,
+            provider: Some(self.name().to_string()),
+        })
+    }
+}
+
+/// A message of about 54 tokens
+fn long(prefix: &str) -> String {
+    format!("{} {}", prefix, "x".repeat(200))
+}
+
+fn history(turns: usize) -> Vec<Message> {
+    let mut messages = vec![Message::system("You are a travel agent.")];
+    for i in 0..turns {
+        messages.push(Message::user(long(&format!("question {}", i))));
+        messages.push(Message::assistant(long(&format!("answer {}", i))));
+    }
+    messages.push(Message::user("Book it."));
+    messages
+}
+
+fn contents(messages: &[Message]) -> Vec<String> {
+    messages.iter().map(|m| m.content.split(' ').take(2).collect::<Vec<_>>().join(" ")).collect()
+}
+
+#[tokio::test]
+async fn test_history_within_budget_is_untouched() {
+    let summarizer = Arc::new(MockSummarizer::default());
+    let memory = SummarizingMemory::new(summarizer.clone(), "cheap-1", 10_000);
+    let mut messages = history(3);
+
+    let compression = memory.compress(&mut messages).await;
+    assert_eq!(messages, history(3));
+    assert_eq!(compression.summarized, 0);
+    assert!(summarizer.transcripts().is_empty());
+}
+
+#[tokio::test]
+async fn test_oldest_turns_become_one_summary() {
+    let summarizer = Arc::new(MockSummarizer::default());
+    let memory = SummarizingMemory::new(summarizer.clone(), "cheap-1", 300).with_batch_size(4);
+    let mut messages = history(4);
+
+    let compression = memory.compress(&mut messages).await;
+    assert!(compression.error.is_none());
+    assert_eq!(compression.summarized, 4);
+    assert!(history_tokens(&messages) <= 300);
+    assert_eq!(
+        contents(&messages),
+        vec![
+            "You are",
+            "Summary of",
+            "question 2",
+            "answer 2",
+            "question 3",
+            "answer 3",
+            "Book it.",
+        ]
+    );
+    assert_eq!(messages[1], Message::system(format!("{}summary 1", SUMMARY_PREFIX)));
+
+    let transcript = &summarizer.transcripts()[0];
+    assert!(transcript.starts_with("User: question 0"), "{}", transcript);
+    assert!(transcript.contains("Assistant: answer 1"), "{}", transcript);
+    assert!(!transcript.contains("travel agent"), "{}", transcript);
+}
+
+#[tokio::test]
+async fn test_earlier_summary_is_folded_into_the_next() {
+    let summarizer = Arc::new(MockSummarizer::default());
+    let memory = SummarizingMemory::new(summarizer.clone(), "cheap-1", 200).with_batch_size(2);
+    let mut messages = history(4);
+
+    memory.compress(&mut messages).await;
+    let transcripts = summarizer.transcripts();
+    assert!(transcripts.len() > 1);
+    assert!(transcripts[1].starts_with(&format!("{}summary 1", SUMMARY_PREFIX)), "{}", transcripts[1]);
+
+    assert_eq!(messages.iter().filter(|m| is_summary(m)).count(), 1);
+    assert_eq!(messages[0], Message::system("You are a travel agent."));
+    assert_eq!(messages.last(), Some(&Message::user("Book it.")));
+    assert!(history_tokens(&messages) <= 200);
+}
+
+#[tokio::test]
+async fn test_tool_calls_stay_with_their_results() {
+    let summarizer = Arc::new(MockSummarizer::default());
+    let memory = SummarizingMemory::new(summarizer.clone(), "cheap-1", 150).with_batch_size(2);
+    let flights = ToolCall::function("call_1", "search_flights", r#"{"to": "Oslo"}"#);
+    let hotels = ToolCall::function("call_2", "search_hotels", r#"{"city": "Oslo"}"#);
+    let mut messages = vec![
+        Message::system("You are a travel agent."),
+        Message::user(long("question 0")),
+        Message::assistant("").with_tool_call(flights),
+        Message::tool(long("flights found"), "call_1"),
+        Message::user(long("question 1")),
+        Message::assistant(long("answer 1")),
+        // Still waiting for its result
+        Message::assistant("").with_tool_call(hotels.clone()),
+    ];
+
+    memory.compress(&mut messages).await;
+    let transcript = &summarizer.transcripts()[0];
+    assert!(transcript.contains("Assistant called search_flights"), "{}", transcript);
+    assert!(transcript.contains("Tool result: flights found"), "{}", transcript);
+
+    // The pending call is never summarized, even over budget
+    assert_eq!(messages.last().unwrap().tool_calls, vec![hotels]);
+    assert!(messages.iter().all(|m| m.tool_call_id.as_deref() != Some("call_1")));
+    assert!(messages.iter().all(|m| m.tool_calls.iter().all(|call| call.id != "call_1")));
+}
+
+#[tokio::test]
+async fn test_failing_summarizer_falls_back_to_dropping() {
+    let summarizer = Arc::new(MockSummarizer::failing());
+    let memory = SummarizingMemory::new(summarizer.clone(), "cheap-1", 300);
+    let mut messages = history(4);
+
+    let compression = memory.compress(&mut messages).await;
+    assert_eq!(compression.error.unwrap().code(), ErrorCode::ServerError);
+    assert_eq!(compression.summarized, 0);
+    assert_eq!(compression.dropped, 4);
+    assert_eq!(summarizer.transcripts().len(), 1);
+    assert_eq!(
+        contents(&messages),
+        vec!["You are", "question 2", "answer 2", "question 3", "answer 3", "Book it."]
+    );
+}
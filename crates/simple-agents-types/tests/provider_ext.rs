@@ -0,0 +1,187 @@
+//! Tests for the `ProviderExt` convenience helpers.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use simple_agents_types::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Provider replying with scripted assistant messages, in order, and
+/// recording every request it receives
+struct MockProvider {
+    replies: Mutex<VecDeque<Message>>,
+    requests: Mutex<Vec<CompletionRequest>>,
+}
+
+impl MockProvider {
+    fn new(replies: impl IntoIterator<Item = Message>) -> Self {
+        Self {
+            replies: Mutex::new(replies.into_iter().collect()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn requests(&self) -> Vec<CompletionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Provider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn transform_request(&self, req: &CompletionRequest) -> Result<ProviderRequest> {
+        self.requests.lock().unwrap().push(req.clone());
+        Ok(ProviderRequest::new("https://api.example.com").with_body(serde_json::json!({ "model": req.model })))
+    }
+
+    async fn execute(&self, req: ProviderRequest) -> Result<ProviderResponse> {
+        Ok(ProviderResponse::new(200, req.body))
+    }
+
+    fn transform_response(&self, resp: ProviderResponse) -> Result<CompletionResponse> {
+        let message = self.replies.lock().unwrap().pop_front().expect("no scripted reply left");
+        let finish_reason = if message.tool_calls.is_empty() {
+            FinishReason::Stop
+        } else {
+            FinishReason::ToolCalls
+        };
+        Ok(CompletionResponse {
+            id: "resp-1".to_string(),
+            model: resp.body["model"].as_str().unwrap_or_default().to_string(),
+            choices: vec![CompletionChoice {
+                index: 0,
+                message,
+                finish_reason,
+                logprobs: None,
+                matched_stop: None,
+                annotations: Vec::new(),
+            }],
+            usage: Usage::new(3, 4),
+            created: None,
+            created_synthesized: false,
+            provider: Some(self.name().to_string()),
+        })
+    }
+}
+
+fn weather_registry() -> ToolRegistry {
+    let parameters = serde_json::json!({"type": "object"});
+    let tool = ToolDefinition::function("get_weather", "Current weather", parameters);
+    ToolRegistry::new().register(tool, |args: serde_json::Value| {
+        Ok(format!("{}: 12°C", args["city"].as_str().unwrap_or("?")))
+    })
+}
+
+fn weather_call(id: &str, city: &str) -> Message {
+    let arguments = serde_json::json!({ "city": city }).to_string();
+    Message::assistant("").with_tool_call(ToolCall::function(id, "get_weather", arguments))
+}
+
+#[tokio::test]
+async fn test_complete_text() {
+    let provider = MockProvider::new([Message::assistant("Hello!")]);
+    assert_eq!(provider.complete_text("mock-1", "Hi").await.unwrap(), "Hello!");
+
+    let request = &provider.requests()[0];
+    assert_eq!(request.model, "mock-1");
+    assert_eq!(request.messages, vec![Message::user("Hi")]);
+
+    let refusal = Message {
+        refusal: Some("I can't help with that.".to_string()),
+        ..Message::assistant("")
+    };
+    let refusing = MockProvider::new([refusal]);
+    let err = refusing.complete_text("mock-1", "Hi").await.unwrap_err();
+    assert!(matches!(err.root(), SimpleAgentsError::Provider(ProviderError::Refusal(_))), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_complete_json() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Forecast {
+        city: String,
+        celsius: i32,
+    }
+
+    let provider = MockProvider::new([Message::assistant(r#"{"city": "Oslo", "celsius": 12}"#)]);
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": { "city": { "type": "string" }, "celsius": { "type": "integer" } },
+        "required": ["city", "celsius"]
+    });
+    let forecast: Forecast = provider
+        .complete_json("mock-1", "Weather in Oslo", schema.clone())
+        .await
+        .unwrap();
+    assert_eq!(forecast, Forecast { city: "Oslo".to_string(), celsius: 12 });
+
+    let request = &provider.requests()[0];
+    assert_eq!(request.messages.last(), Some(&Message::user("Weather in Oslo")));
+    match &request.response_format {
+        Some(ResponseFormat::JsonSchema { json_schema }) => {
+            assert_eq!(json_schema.schema, schema);
+            assert_eq!(json_schema.strict, Some(true));
+        }
+        other => panic!("expected a JSON schema response format, got {:?}", other),
+    }
+
+    let provider = MockProvider::new([Message::assistant("sunny")]);
+    let err = provider
+        .complete_json::<Forecast>("mock-1", "Weather in Oslo", schema)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SimpleAgentsError::Serialization(_)));
+}
+
+#[tokio::test]
+async fn test_complete_with_tools() {
+    let provider = MockProvider::new([
+        weather_call("call_1", "Oslo"),
+        weather_call("call_2", "Bergen").with_tool_call(ToolCall::function("call_3", "get_tide", "{}")),
+        Message::assistant("Oslo and Bergen are both 12°C."),
+    ]);
+    let request = CompletionRequest::builder()
+        .model("mock-1")
+        .message(Message::user("Weather in Oslo and Bergen?"))
+        .build()
+        .unwrap();
+
+    let (text, calls) = provider.complete_with_tools(&request, &weather_registry()).await.unwrap();
+    assert_eq!(text, "Oslo and Bergen are both 12°C.");
+    assert_eq!(
+        calls.iter().map(|call| call.id.as_str()).collect::<Vec<_>>(),
+        vec!["call_1", "call_2", "call_3"]
+    );
+
+    let requests = provider.requests();
+    assert_eq!(requests.len(), 3);
+    let offered = requests[0].tools.as_ref().unwrap();
+    assert_eq!(offered[0].function.name, "get_weather");
+
+    // Each round carries the assistant's calls and one result per call
+    let last = &requests[2].messages;
+    assert_eq!(last.len(), 6);
+    assert_eq!(last[2], Message::tool("Oslo: 12°C", "call_1"));
+    assert_eq!(last[4], Message::tool("Bergen: 12°C", "call_2"));
+    assert_eq!(last[5].tool_call_id.as_deref(), Some("call_3"));
+    assert!(last[5].content.starts_with("Error: "), "{}", last[5].content);
+}
+
+#[tokio::test]
+async fn test_complete_with_tools_gives_up_after_max_rounds() {
+    let provider = MockProvider::new((0..3).map(|i| weather_call(&format!("call_{}", i), "Oslo")));
+    let request = CompletionRequest::builder()
+        .model("mock-1")
+        .message(Message::user("Weather in Oslo?"))
+        .build()
+        .unwrap();
+
+    let registry = weather_registry().with_max_rounds(3);
+    let err = provider.complete_with_tools(&request, &registry).await.unwrap_err();
+    let gave_up = matches!(err.root(), SimpleAgentsError::Provider(ProviderError::InvalidResponse(_)));
+    assert!(gave_up, "{:?}", err);
+    assert_eq!(provider.requests().len(), 3);
+}